@@ -0,0 +1,2 @@
+// A login screen laid out from the frames a design states
+Login:{(device:"iPhone 15"):{Image:"logo"@(y:159,w:60,h:60),title:"Welcome back"@(x:20,y:235),button:"Sign in"@(y:753)}}
@@ -0,0 +1,14 @@
+struct LoginView: View {
+    var body: some View {
+        VStack {
+            Image("logo")
+            Text("Welcome back")
+                .font(.title)
+                .padding()
+            Spacer()
+            Button("Sign in") { }
+                .padding()
+        }
+        .padding()
+    }
+}
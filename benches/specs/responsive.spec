@@ -0,0 +1,3 @@
+// The same content in portrait and landscape
+{(width:390,height:844):{title:"Profile",button:"Edit"}}
+{(width:844,height:390):{title:"Profile",button:"Edit"}}
@@ -0,0 +1,9 @@
+VStack {
+    Text("Profile")
+        .font(.title)
+        .padding()
+    Spacer()
+    Button("Edit") { }
+        .padding()
+}
+.padding()
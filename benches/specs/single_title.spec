@@ -0,0 +1,2 @@
+// One text: the smallest layout there is
+{(width:390,height:844):{title:"Hello"}}
@@ -0,0 +1,7 @@
+VStack {
+    Text("Hello")
+        .font(.title)
+        .padding()
+    Spacer()
+}
+.padding()
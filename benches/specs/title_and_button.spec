@@ -0,0 +1,2 @@
+// A title at the top and a button pushed to the bottom
+{(width:390,height:844):{title:"Welcome",button:"Continue"}}
@@ -0,0 +1,9 @@
+VStack {
+    Text("Welcome")
+        .font(.title)
+        .padding()
+    Spacer()
+    Button("Continue") { }
+        .padding()
+}
+.padding()
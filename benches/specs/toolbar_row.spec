@@ -0,0 +1,2 @@
+// Buttons side by side, pinned to the leading and trailing edges
+{(width:390,height:844):HStack:{"Cancel","Spacer","Done"}}
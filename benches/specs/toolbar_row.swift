@@ -0,0 +1,10 @@
+HStack {
+    Text("Cancel")
+        .font(.title)
+        .padding()
+    Spacer()
+    Text("Done")
+        .font(.title)
+        .padding()
+}
+.padding()
@@ -0,0 +1,2 @@
+// Wildcards match any text, so the layout is all that is asked for
+{(width:390,height:844):{title:*,button:*,button:*}}
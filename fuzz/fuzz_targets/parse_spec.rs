@@ -0,0 +1,12 @@
+// File: fuzz/fuzz_targets/parse_spec.rs
+// The spec parser must turn any text into examples or diagnostics, never a panic.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use swiftui_synth::input::parser::{parse_examples_all, parse_spec_with, ParseOptions};
+
+fuzz_target!(|input: &str| {
+    let _ = parse_examples_all(input, &ParseOptions::default());
+    let _ = parse_spec_with(input, &ParseOptions::default());
+    let _ = parse_spec_with(input, &ParseOptions { strict: true, ..ParseOptions::default() });
+});
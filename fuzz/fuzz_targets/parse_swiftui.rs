@@ -0,0 +1,16 @@
+// File: fuzz/fuzz_targets/parse_swiftui.rs
+// The reverse parser must reject malformed Swift without panicking, and whatever it does
+// read must render, read back and render again unchanged.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use swiftui_synth::input::swift::parse_swiftui;
+use swiftui_synth::output::render::render_swiftui;
+
+fuzz_target!(|code: &str| {
+    if let Ok(ir) = parse_swiftui(code) {
+        let rendered = render_swiftui(&ir);
+        let reparsed = parse_swiftui(&rendered).expect("rendered code reads back");
+        assert_eq!(render_swiftui(&reparsed), rendered);
+    }
+});
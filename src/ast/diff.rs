@@ -0,0 +1,264 @@
+// File: src/ast/diff.rs
+use std::fmt;
+
+use crate::ast::ir::equivalent;
+use crate::ast::IR;
+
+/// A single structural change between two layouts.
+///
+/// `path` is the list of child indices from the root to the affected node;
+/// an empty path means the root itself.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Inserted { path: Vec<usize>, node: IR },
+    Removed { path: Vec<usize>, node: IR },
+    Relabeled { path: Vec<usize>, kind: &'static str, old: String, new: String },
+    Replaced { path: Vec<usize>, old: IR, new: IR },
+}
+
+/// Computes the changes needed to turn `old` into `new`.
+/// Subtrees are compared by their canonical forms so purely cosmetic differences are
+/// ignored, but paths index the trees as given.
+pub fn diff(old: &IR, new: &IR) -> Vec<Change> {
+    let mut changes = Vec::new();
+    diff_node(old, new, &mut Vec::new(), &mut changes);
+    changes
+}
+
+/// Short human-readable name for the kind of node.
+pub fn kind_name(ir: &IR) -> &'static str {
+    match ir {
+        IR::VStack(_) => "VStack",
+        IR::HStack(_) => "HStack",
+        IR::Text(_) => "Text",
+        IR::Button(_) => "Button",
+        IR::Image(_) => "Image",
+        IR::Spacer => "Spacer",
+        IR::Custom { .. } => "Custom",
+    }
+}
+
+fn label(ir: &IR) -> Option<String> {
+    match ir {
+        IR::Text(s) | IR::Button(s) | IR::Image(s) => Some(s.clone()),
+        IR::Custom { name, args } => Some(format!("{}({})", name, args)),
+        _ => None,
+    }
+}
+
+fn same_kind(a: &IR, b: &IR) -> bool {
+    std::mem::discriminant(a) == std::mem::discriminant(b)
+}
+
+fn diff_node(old: &IR, new: &IR, path: &mut Vec<usize>, changes: &mut Vec<Change>) {
+    if equivalent(old, new) {
+        return;
+    }
+    match (old, new) {
+        // Stacks of the same axis, once a lone view's stack has lost its axis
+        (IR::VStack(a) | IR::HStack(a), IR::VStack(b) | IR::HStack(b)) if same_kind(&old.canonicalize(), &new.canonicalize()) => {
+            diff_children(a, b, path, changes)
+        }
+        _ if same_kind(old, new) => changes.push(Change::Relabeled {
+            path: path.clone(),
+            kind: kind_name(old),
+            old: label(old).unwrap_or_default(),
+            new: label(new).unwrap_or_default(),
+        }),
+        _ => changes.push(Change::Replaced { path: path.clone(), old: old.clone(), new: new.clone() }),
+    }
+}
+
+// Aligns two child lists: identical children are matched first via LCS, then the
+// remaining children between those anchors are paired up by kind so that a changed
+// label is reported as a relabel rather than a remove + insert. Children are compared by
+// their canonical forms, leaving out spacers right after another, which share its space.
+fn diff_children(old: &[IR], new: &[IR], path: &mut Vec<usize>, changes: &mut Vec<Change>) {
+    // Each child kept, with its index among the children as given
+    fn kept(children: &[IR]) -> (Vec<usize>, Vec<IR>) {
+        let mut kept = (Vec::new(), Vec::<IR>::new());
+        for (index, child) in children.iter().enumerate() {
+            let child = child.canonicalize();
+            if child == IR::Spacer && kept.1.last() == Some(&IR::Spacer) {
+                continue;
+            }
+            kept.0.push(index);
+            kept.1.push(child);
+        }
+        kept
+    }
+    let (old_indices, canonical_old) = kept(old);
+    let (new_indices, canonical_new) = kept(new);
+    let (raw_old, raw_new) = (old, new);
+    let (old, new) = (canonical_old.as_slice(), canonical_new.as_slice());
+    let (n, m) = (old.len(), new.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if old[i] == new[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut anchors = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            anchors.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    anchors.push((n, m));
+
+    let (mut oi, mut nj) = (0, 0);
+    for (ai, aj) in anchors {
+        let mut olds: Vec<usize> = (oi..ai).collect();
+        let mut news: Vec<usize> = (nj..aj).collect();
+        // Pair same-kind nodes in order; leftovers are removals/insertions.
+        let mut paired = Vec::new();
+        news.retain(|&nidx| {
+            if let Some(pos) = olds.iter().position(|&oidx| same_kind(&old[oidx], &new[nidx])) {
+                paired.push((olds.remove(pos), nidx));
+                false
+            } else {
+                true
+            }
+        });
+        for oidx in olds {
+            path.push(old_indices[oidx]);
+            changes.push(Change::Removed { path: path.clone(), node: raw_old[old_indices[oidx]].clone() });
+            path.pop();
+        }
+        for (oidx, nidx) in paired {
+            path.push(new_indices[nidx]);
+            diff_node(&raw_old[old_indices[oidx]], &raw_new[new_indices[nidx]], path, changes);
+            path.pop();
+        }
+        for nidx in news {
+            path.push(new_indices[nidx]);
+            changes.push(Change::Inserted { path: path.clone(), node: raw_new[new_indices[nidx]].clone() });
+            path.pop();
+        }
+        oi = ai + 1;
+        nj = aj + 1;
+    }
+}
+
+fn describe_path(path: &[usize]) -> String {
+    match path {
+        [] => "the root".to_string(),
+        [index] => format!("index {}", index),
+        _ => format!(
+            "path {}",
+            path.iter().map(|i| i.to_string()).collect::<Vec<_>>().join(".")
+        ),
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Change::Inserted { path, node } => {
+                write!(f, "{} inserted at {}", kind_name(node), describe_path(path))
+            }
+            Change::Removed { path, node } => {
+                write!(f, "{} removed from {}", kind_name(node), describe_path(path))
+            }
+            Change::Relabeled { path, kind, old, new } => {
+                let what = if *kind == "Image" { "name" } else { "label" };
+                write!(f, "{} {} changed at {}: \"{}\" -> \"{}\"", kind, what, describe_path(path), old, new)
+            }
+            Change::Replaced { path, old, new } => write!(
+                f,
+                "{} replaced by {} at {}",
+                kind_name(old),
+                kind_name(new),
+                describe_path(path)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> IR {
+        IR::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_diff_identical() {
+        let ir = IR::VStack(vec![text("A"), IR::Spacer]);
+        assert!(diff(&ir, &ir).is_empty());
+    }
+
+    #[test]
+    fn test_diff_button_label_changed() {
+        let old = IR::VStack(vec![text("A"), IR::Spacer, IR::Button("Go".to_string())]);
+        let new = IR::VStack(vec![text("A"), IR::Spacer, IR::Button("Next".to_string())]);
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].to_string(), "Button label changed at index 2: \"Go\" -> \"Next\"");
+    }
+
+    #[test]
+    fn test_diff_spacer_inserted() {
+        let old = IR::HStack(vec![text("A"), text("B"), text("C")]);
+        let new = IR::HStack(vec![text("A"), text("B"), IR::Spacer, text("C")]);
+        let changes = diff(&old, &new);
+        assert_eq!(changes, vec![Change::Inserted { path: vec![2], node: IR::Spacer }]);
+        assert_eq!(changes[0].to_string(), "Spacer inserted at index 2");
+    }
+
+    #[test]
+    fn test_diff_paths_index_the_given_trees() {
+        // Collapsed spacers and single-view stacks still count towards the indices
+        let old = IR::VStack(vec![IR::Spacer, IR::Spacer, IR::HStack(vec![text("A")]), text("B")]);
+        let new = IR::VStack(vec![IR::Spacer, IR::Spacer, IR::VStack(vec![text("A"), text("C")]), IR::Image("x".to_string()), text("B")]);
+        let changes = diff(&old, &new);
+        assert_eq!(
+            changes,
+            vec![
+                Change::Inserted { path: vec![2, 1], node: text("C") },
+                Change::Inserted { path: vec![3], node: IR::Image("x".to_string()) },
+            ]
+        );
+        assert!(diff(&old, &IR::VStack(vec![IR::Spacer, IR::VStack(vec![text("A")]), text("B")])).is_empty());
+    }
+
+    #[test]
+    fn test_diff_removed_and_nested() {
+        let old = IR::VStack(vec![IR::Image("icon".to_string()), IR::HStack(vec![text("A"), text("B")])]);
+        let new = IR::VStack(vec![IR::HStack(vec![text("A"), text("X")])]);
+        let changes = diff(&old, &new);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.contains(&Change::Removed { path: vec![0], node: IR::Image("icon".to_string()) }));
+        assert_eq!(changes[1].to_string(), "Text label changed at path 0.1: \"B\" -> \"X\"");
+    }
+
+    #[test]
+    fn test_diff_custom_view_changed() {
+        let old = IR::VStack(vec![IR::custom("Chart(data: a)").unwrap()]);
+        let new = IR::VStack(vec![IR::custom("Chart(data: b)").unwrap()]);
+        assert_eq!(
+            diff(&old, &new)[0].to_string(),
+            "Custom label changed at index 0: \"Chart(data: a)\" -> \"Chart(data: b)\""
+        );
+    }
+
+    #[test]
+    fn test_diff_root_replaced() {
+        let old = IR::VStack(vec![text("A"), text("B")]);
+        let new = IR::HStack(vec![text("A"), text("B")]);
+        assert_eq!(diff(&old, &new)[0].to_string(), "VStack replaced by HStack at the root");
+    }
+}
@@ -1,4 +1,10 @@
-#[derive(Clone, Debug, PartialEq)]
+pub mod build;
+pub mod background;
+pub mod modifier;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub enum IR {
     VStack(Vec<IR>),
     HStack(Vec<IR>),
@@ -6,4 +12,131 @@ pub enum IR {
     Button(String),
     Image(String), // Added Image variant
     Spacer,
+    /// A bespoke view rendered verbatim as `name(args)`.
+    Custom { name: String, args: String },
+}
+
+impl IR {
+    /// Builds an `IR::Custom` from source like `MyChartView(data: points)`.
+    /// A bare name such as `Divider` is accepted and gets empty arguments.
+    pub fn custom(source: &str) -> Result<IR, String> {
+        let source = source.trim();
+        let (name, args) = match source.find('(') {
+            Some(open) if source.ends_with(')') => (&source[..open], &source[open + 1..source.len() - 1]),
+            Some(_) => return Err(format!("Custom view '{}' is missing a closing ')'", source)),
+            None => (source, ""),
+        };
+        let name = name.trim();
+        let is_identifier = name.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.');
+        if !is_identifier {
+            return Err(format!("Custom view '{}' must start with a type name, e.g. MyView(arg: value)", source));
+        }
+        Ok(IR::Custom { name: name.to_string(), args: args.trim().to_string() })
+    }
+
+    /// Returns a canonical form of this layout, so that two trees which render to
+    /// the same visual structure compare equal.
+    ///
+    /// - adjacent `Spacer`s are collapsed into one (they share the free space anyway)
+    /// - stacks with no children, or a single view that is neither a `Spacer` nor a stack,
+    ///   lose their axis and become a `VStack`; a lone `Spacer` or stack keeps it, since it
+    ///   expands or lays out along it
+    pub fn canonicalize(&self) -> IR {
+        match self {
+            IR::VStack(children) | IR::HStack(children) => {
+                let mut canonical: Vec<IR> = Vec::with_capacity(children.len());
+                for child in children {
+                    let child = child.canonicalize();
+                    if child == IR::Spacer && canonical.last() == Some(&IR::Spacer) {
+                        continue;
+                    }
+                    canonical.push(child);
+                }
+                let axisless = match canonical.as_slice() {
+                    [] => true,
+                    [IR::Spacer | IR::VStack(_) | IR::HStack(_)] => false,
+                    [_] => true,
+                    _ => false,
+                };
+                if axisless || matches!(self, IR::VStack(_)) {
+                    IR::VStack(canonical)
+                } else {
+                    IR::HStack(canonical)
+                }
+            }
+            leaf => leaf.clone(),
+        }
+    }
+}
+
+/// Structural equivalence: true if both layouts have the same canonical form.
+pub fn equivalent(a: &IR, b: &IR) -> bool {
+    a.canonicalize() == b.canonicalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonicalize_collapses_adjacent_spacers() {
+        let ir = IR::VStack(vec![
+            IR::Text("A".to_string()),
+            IR::Spacer,
+            IR::Spacer,
+            IR::Button("B".to_string()),
+        ]);
+        assert_eq!(
+            ir.canonicalize(),
+            IR::VStack(vec![
+                IR::Text("A".to_string()),
+                IR::Spacer,
+                IR::Button("B".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_canonicalize_single_child_stack_axis() {
+        let h = IR::HStack(vec![IR::Text("A".to_string())]);
+        let v = IR::VStack(vec![IR::Text("A".to_string())]);
+        assert!(equivalent(&h, &v));
+        assert!(equivalent(&IR::HStack(vec![]), &IR::VStack(vec![])));
+    }
+
+    #[test]
+    fn test_canonicalize_nested() {
+        let a = IR::VStack(vec![IR::HStack(vec![IR::Spacer, IR::Spacer]), IR::Text("x".to_string())]);
+        let b = IR::VStack(vec![IR::HStack(vec![IR::Spacer]), IR::Text("x".to_string())]);
+        assert!(equivalent(&a, &b));
+        // A lone spacer or stack expands along its stack's axis, which it keeps
+        let c = IR::VStack(vec![IR::VStack(vec![IR::Spacer]), IR::Text("x".to_string())]);
+        assert!(!equivalent(&a, &c));
+        let row = IR::HStack(vec![IR::Text("A".to_string()), IR::Text("B".to_string())]);
+        assert!(!equivalent(&IR::HStack(vec![row.clone()]), &IR::VStack(vec![row])));
+    }
+
+    #[test]
+    fn test_custom_from_source() {
+        assert_eq!(
+            IR::custom("MyChartView(data: points)").unwrap(),
+            IR::Custom { name: "MyChartView".to_string(), args: "data: points".to_string() }
+        );
+        assert_eq!(
+            IR::custom("Divider").unwrap(),
+            IR::Custom { name: "Divider".to_string(), args: String::new() }
+        );
+        assert!(IR::custom("MyView(data: x").is_err());
+        assert!(IR::custom("(data: x)").is_err());
+    }
+
+    #[test]
+    fn test_not_equivalent() {
+        let a = IR::HStack(vec![IR::Text("A".to_string()), IR::Text("B".to_string())]);
+        let b = IR::VStack(vec![IR::Text("A".to_string()), IR::Text("B".to_string())]);
+        assert!(!equivalent(&a, &b));
+        let c = IR::HStack(vec![IR::Text("B".to_string()), IR::Text("A".to_string())]);
+        assert!(!equivalent(&a, &c));
+    }
 }
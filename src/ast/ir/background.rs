@@ -0,0 +1,143 @@
+// File: src/ast/ir/background.rs
+//! Backgrounds of a screen's root container, stated with a `background` element:
+//! `background:{gradient:["#FF5733","#C70039"], direction:vertical}` for a linear gradient, or
+//! `background:"ultraThinMaterial"` for a material. Like decorations, they do not affect the
+//! layout and are kept beside the IR.
+use crate::ast::{Example, Value};
+use crate::synthesis::a11y::Rgb;
+
+use super::modifier::component;
+
+/// SwiftUI's materials, thinnest first.
+pub const MATERIALS: &[&str] = &["ultraThinMaterial", "thinMaterial", "regularMaterial", "thickMaterial", "ultraThickMaterial", "bar"];
+
+/// Which way a gradient runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientDirection {
+    /// Top to bottom.
+    Vertical,
+    /// Leading to trailing edge.
+    Horizontal,
+    /// Top leading to bottom trailing corner.
+    Diagonal,
+}
+
+impl GradientDirection {
+    fn parse(name: &str) -> Option<GradientDirection> {
+        match name {
+            "vertical" => Some(GradientDirection::Vertical),
+            "horizontal" => Some(GradientDirection::Horizontal),
+            "diagonal" => Some(GradientDirection::Diagonal),
+            _ => None,
+        }
+    }
+
+    // The unit points the gradient starts and ends at
+    fn points(&self) -> (&'static str, &'static str) {
+        match self {
+            GradientDirection::Vertical => ("top", "bottom"),
+            GradientDirection::Horizontal => ("leading", "trailing"),
+            GradientDirection::Diagonal => ("topLeading", "bottomTrailing"),
+        }
+    }
+}
+
+/// What is drawn behind a container.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Background {
+    /// A linear gradient through two or more colors.
+    Gradient { colors: Vec<Rgb>, direction: GradientDirection },
+    /// One of [`MATERIALS`], blurring what is behind the screen.
+    Material(String),
+}
+
+impl Background {
+    /// The background a parsed `background` element holds: a material's name, or a dict with
+    /// the `gradient` colors and an optional `direction` (`vertical` by default).
+    pub fn from_value(value: &Value) -> Result<Background, String> {
+        match value {
+            Value::String(name) if MATERIALS.contains(&name.as_str()) => Ok(Background::Material(name.clone())),
+            Value::String(name) => Err(format!("Unknown material '{}': materials are {}", name, MATERIALS.join(", "))),
+            Value::Dict(entries) => {
+                let mut colors = None;
+                let mut direction = GradientDirection::Vertical;
+                for (key, value) in entries {
+                    match (key.as_str(), value) {
+                        ("gradient", Value::Array(items)) => {
+                            let parsed: Option<Vec<Rgb>> = items.iter().map(|item| match item {
+                                Value::String(hex) => Rgb::parse(hex),
+                                _ => None,
+                            }).collect();
+                            colors = Some(parsed.ok_or("Gradient colors must be hex colors such as \"#FF5733\"")?);
+                        }
+                        ("direction", Value::String(name)) => {
+                            direction = GradientDirection::parse(name)
+                                .ok_or_else(|| format!("Unknown gradient direction '{}': expected vertical, horizontal or diagonal", name))?;
+                        }
+                        ("gradient", _) => return Err("A gradient is an array of colors, e.g. gradient:[\"#FF5733\",\"#C70039\"]".to_string()),
+                        (key, _) => return Err(format!("Unsupported background key: '{}'; keys are `gradient` and `direction`", key)),
+                    }
+                }
+                match colors {
+                    Some(colors) if colors.len() >= 2 => Ok(Background::Gradient { colors, direction }),
+                    Some(_) => Err("A gradient needs at least two colors".to_string()),
+                    None => Err("A background block needs a `gradient` of colors".to_string()),
+                }
+            }
+            _ => Err("A background is a material such as \"ultraThinMaterial\" or a {gradient:[...]} block".to_string()),
+        }
+    }
+
+    /// The SwiftUI modifier, without its leading dot, e.g. `background(.ultraThinMaterial)`.
+    pub fn swiftui(&self) -> String {
+        match self {
+            Background::Gradient { colors, direction } => {
+                let colors: Vec<String> =
+                    colors.iter().map(|rgb| format!("Color(red: {}, green: {}, blue: {})", component(rgb.r), component(rgb.g), component(rgb.b))).collect();
+                let (start, end) = direction.points();
+                format!("background(LinearGradient(colors: [{}], startPoint: .{}, endPoint: .{}))", colors.join(", "), start, end)
+            }
+            Background::Material(name) => format!("background(.{})", name),
+        }
+    }
+}
+
+/// The background of the screen `examples` show: that of the first example stating one.
+pub fn stated(examples: &[Example]) -> Option<Background> {
+    examples.iter().find_map(|(dims, _)| {
+        let Value::Dict(dims) = dims else { return None };
+        dims.iter().find(|(key, _)| key == "background").and_then(|(_, value)| Background::from_value(value).ok())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    #[test]
+    fn test_backgrounds_are_read_and_rendered() {
+        let examples = parse_examples(
+            "{(width:390,height:844):{title:\"Hi\",background:{gradient:[\"#FF5733\",\"#C70039\"], direction:diagonal}}}
+             {(width:320,height:568):{title:\"Hi\",background:\"thinMaterial\"}}",
+        )
+        .unwrap();
+        let background = stated(&examples).unwrap();
+        assert_eq!(
+            background.swiftui(),
+            "background(LinearGradient(colors: [Color(red: 1, green: 0.341, blue: 0.2), Color(red: 0.78, green: 0, blue: 0.224)], startPoint: .topLeading, endPoint: .bottomTrailing))"
+        );
+        assert_eq!(stated(&examples[1..]).unwrap().swiftui(), "background(.thinMaterial)");
+        assert_eq!(stated(&parse_examples("{(width:390,height:844):{title:\"Hi\"}}").unwrap()), None);
+    }
+
+    #[test]
+    fn test_invalid_backgrounds() {
+        let gradient = |colors: &[&str]| Value::Dict(vec![("gradient".to_string(), Value::Array(colors.iter().map(|c| Value::String(c.to_string())).collect()))]);
+        assert!(Background::from_value(&gradient(&["#FFF"])).unwrap_err().contains("at least two"));
+        assert!(Background::from_value(&gradient(&["#FFF", "red"])).unwrap_err().contains("hex colors"));
+        assert!(Background::from_value(&Value::String("frosted".to_string())).unwrap_err().starts_with("Unknown material 'frosted'"));
+        let sideways = Value::Dict(vec![("direction".to_string(), Value::String("sideways".to_string()))]);
+        assert!(Background::from_value(&sideways).unwrap_err().contains("sideways"));
+    }
+}
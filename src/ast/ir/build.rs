@@ -0,0 +1,161 @@
+// File: src/ast/ir/build.rs
+//! Fluent constructors for assembling layouts without going through the example parser.
+//!
+//! ```
+//! use swiftui_synth::ast::ir::build;
+//!
+//! let ir = build::vstack()
+//!     .text("Hi")
+//!     .hstack().text("A").spacer().text("B").end()
+//!     .spacer()
+//!     .button("Go")
+//!     .finish();
+//! ```
+use crate::ast::IR;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Axis {
+    Vertical,
+    Horizontal,
+}
+
+/// Builder for a stack and, while nested stacks are open, its ancestors.
+#[derive(Clone, Debug)]
+pub struct StackBuilder {
+    axis: Axis,
+    children: Vec<IR>,
+    parent: Option<Box<StackBuilder>>,
+}
+
+/// Starts a new `VStack`.
+pub fn vstack() -> StackBuilder {
+    StackBuilder { axis: Axis::Vertical, children: Vec::new(), parent: None }
+}
+
+/// Starts a new `HStack`.
+pub fn hstack() -> StackBuilder {
+    StackBuilder { axis: Axis::Horizontal, children: Vec::new(), parent: None }
+}
+
+fn stack(axis: Axis, children: Vec<IR>) -> IR {
+    match axis {
+        Axis::Vertical => IR::VStack(children),
+        Axis::Horizontal => IR::HStack(children),
+    }
+}
+
+impl StackBuilder {
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.child(IR::Text(text.into()))
+    }
+
+    pub fn button(self, label: impl Into<String>) -> Self {
+        self.child(IR::Button(label.into()))
+    }
+
+    pub fn image(self, name: impl Into<String>) -> Self {
+        self.child(IR::Image(name.into()))
+    }
+
+    pub fn spacer(self) -> Self {
+        self.child(IR::Spacer)
+    }
+
+    /// Appends a bespoke view rendered verbatim as `name(args)`.
+    pub fn custom(self, name: impl Into<String>, args: impl Into<String>) -> Self {
+        self.child(IR::Custom { name: name.into(), args: args.into() })
+    }
+
+    /// Appends an already-built node.
+    pub fn child(mut self, node: impl Into<IR>) -> Self {
+        self.children.push(node.into());
+        self
+    }
+
+    /// Opens a nested `VStack`; close it with [`StackBuilder::end`].
+    pub fn vstack(self) -> Self {
+        self.nested(Axis::Vertical)
+    }
+
+    /// Opens a nested `HStack`; close it with [`StackBuilder::end`].
+    pub fn hstack(self) -> Self {
+        self.nested(Axis::Horizontal)
+    }
+
+    fn nested(self, axis: Axis) -> Self {
+        StackBuilder { axis, children: Vec::new(), parent: Some(Box::new(self)) }
+    }
+
+    /// Closes the innermost nested stack. At the root this is a no-op.
+    pub fn end(self) -> Self {
+        let StackBuilder { axis, children, parent } = self;
+        match parent {
+            Some(parent) => parent.child(stack(axis, children)),
+            None => StackBuilder { axis, children, parent: None },
+        }
+    }
+
+    /// Closes any open nested stacks and returns the root layout.
+    pub fn finish(self) -> IR {
+        let mut builder = self;
+        while builder.parent.is_some() {
+            builder = builder.end();
+        }
+        stack(builder.axis, builder.children)
+    }
+}
+
+impl From<StackBuilder> for IR {
+    fn from(builder: StackBuilder) -> IR {
+        builder.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::render::render_swiftui;
+
+    #[test]
+    fn test_build_flat_vstack() {
+        let ir = vstack().text("Hi").spacer().button("Go").finish();
+        assert_eq!(
+            ir,
+            IR::VStack(vec![IR::Text("Hi".to_string()), IR::Spacer, IR::Button("Go".to_string())])
+        );
+    }
+
+    #[test]
+    fn test_build_nested() {
+        let ir = vstack()
+            .image("logo")
+            .hstack().text("A").spacer().text("B").end()
+            .button("Go")
+            .finish();
+        assert_eq!(
+            ir,
+            IR::VStack(vec![
+                IR::Image("logo".to_string()),
+                IR::HStack(vec![IR::Text("A".to_string()), IR::Spacer, IR::Text("B".to_string())]),
+                IR::Button("Go".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_finish_closes_open_stacks() {
+        let ir = hstack().text("A").vstack().text("B").finish();
+        assert_eq!(
+            ir,
+            IR::HStack(vec![IR::Text("A".to_string()), IR::VStack(vec![IR::Text("B".to_string())])])
+        );
+        // `end` at the root is a no-op
+        assert_eq!(vstack().end().finish(), IR::VStack(vec![]));
+    }
+
+    #[test]
+    fn test_build_feeds_renderer() {
+        let built: IR = vstack().text("Welcome").spacer().into();
+        assert!(render_swiftui(&built).starts_with("VStack {\n    Text(\"Welcome\")"));
+    }
+}
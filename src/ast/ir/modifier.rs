@@ -0,0 +1,402 @@
+// File: src/ast/ir/modifier.rs
+//! Decoration modifiers of a view, stated in its element's frame alongside the coordinates:
+//! `button:"Buy"@(h:44,cornerRadius:12,border:"1 #D1D1D6",shadow:"8 0 4")`, or a child
+//! anchored on it, `Image:"bell"@(overlay:"topTrailing title:3")`, or how it animates,
+//! `title:"Hi"@(animation:"spring",transition:"slide")`, or the gestures it handles,
+//! `Image:"avatar"@(onTap:"openProfile",onSwipe:"left archive")`. They do not affect the
+//! layout, only how the view is drawn and responds, so they are kept beside the IR rather
+//! than in it, by the view they apply to.
+use std::collections::BTreeMap;
+
+use crate::ast::{Example, Value, IR};
+use crate::output::render::string_literal;
+use crate::synthesis::a11y::Rgb;
+use crate::synthesis::verify;
+
+/// Frame keys that state a modifier rather than a coordinate.
+pub const MODIFIER_KEYS: &[&str] = &["cornerRadius", "border", "shadow", "overlay", "animation", "transition", "onTap", "onLongPress", "onSwipe"];
+
+/// SwiftUI's named animations.
+pub const ANIMATIONS: &[&str] = &["default", "spring", "bouncy", "smooth", "snappy", "interactiveSpring", "easeIn", "easeOut", "easeInOut", "linear"];
+
+/// Transitions a view may be inserted with; `move` takes an edge, e.g. `move bottom`.
+pub const TRANSITIONS: &[&str] = &["opacity", "slide", "scale", "move"];
+
+/// The state variable a view with animations declares, which turns true once it appears.
+pub const APPEAR_STATE: &str = "appeared";
+
+/// Directions a view may be swiped in.
+pub const SWIPE_DIRECTIONS: &[&str] = &["left", "right", "up", "down"];
+
+/// Points a drag must travel in its direction to count as a swipe.
+pub const SWIPE_DISTANCE: f64 = 50.0;
+
+// Names a handler method cannot take in the view struct or in Swift
+const RESERVED_NAMES: &[&str] = &["body", APPEAR_STATE, "init", "func", "var", "let", "self", "return", "if", "else", "for", "in", "while", "switch", "case", "default", "private", "public", "struct", "class", "true", "false", "nil"];
+
+/// Where an overlay may be anchored on its view, as SwiftUI's `Alignment`s name them.
+pub const ALIGNMENTS: &[&str] = &["topLeading", "top", "topTrailing", "leading", "center", "trailing", "bottomLeading", "bottom", "bottomTrailing"];
+
+/// A decoration of a view.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Modifier {
+    /// Rounded corners of this radius, in points, clipping the view.
+    CornerRadius(f64),
+    /// A stroke around the view, in the secondary color without a `color`.
+    Border { width: f64, color: Option<Rgb> },
+    /// A drop shadow of this blur radius, offset by `x` and `y`.
+    Shadow { radius: f64, x: f64, y: f64 },
+    /// A text or image drawn over the view at one of [`ALIGNMENTS`], such as a badge.
+    Overlay { alignment: String, view: IR },
+    /// One of [`ANIMATIONS`], for changes driven by [`APPEAR_STATE`].
+    Animation(String),
+    /// How the view is inserted once the screen appears, as SwiftUI spells it after the dot,
+    /// e.g. `move(edge: .bottom)`.
+    Transition(String),
+    /// A tap on the view calls the handler method of this name.
+    Tap(String),
+    /// A long press on the view calls the handler method of this name.
+    LongPress(String),
+    /// A swipe in one of [`SWIPE_DIRECTIONS`] calls the handler method named `handler`.
+    Swipe { direction: String, handler: String },
+}
+
+// A number of points that must not be negative
+fn points(text: &str, what: &str) -> Result<f64, String> {
+    match text.parse::<f64>() {
+        Ok(number) if number.is_finite() && number >= 0.0 => Ok(number),
+        _ => Err(format!("Invalid {} '{}': expected a number of points, at least 0", what, text)),
+    }
+}
+
+// The name of a handler method, which must be a Swift identifier the view does not use
+fn handler(name: &str) -> Result<String, String> {
+    let identifier = name.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_') && name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+    match identifier && !RESERVED_NAMES.contains(&name) {
+        true => Ok(name.to_string()),
+        false => Err(format!("Invalid handler name '{}': expected a method name such as openProfile", name)),
+    }
+}
+
+/// A component of a color from 0 to 1, rounded to thousandths as the code spells it.
+pub fn component(channel: u8) -> f64 {
+    (f64::from(channel) / 255.0 * 1000.0).round() / 1000.0
+}
+
+impl Modifier {
+    /// Parses the value of the frame key `key`, one of [`MODIFIER_KEYS`]: `cornerRadius:12`,
+    /// `border:1` or `border:"1 #D1D1D6"` (its width and color), and `shadow:4` or
+    /// `shadow:"8 0 4"` (its radius and offset), `overlay:"topTrailing title:3"` (where the
+    /// overlay is anchored, then the element it shows, a `title` or an `Image`),
+    /// `animation:"spring"` and `transition:"slide"` or `transition:"move bottom"`, and the
+    /// handler methods of gestures, `onTap:"openProfile"`, `onLongPress:"showMenu"` and
+    /// `onSwipe:"left archive"` (the direction, then the handler).
+    pub fn parse(key: &str, value: &str) -> Result<Modifier, String> {
+        if key == "overlay" {
+            return parse_overlay(value);
+        }
+        if key == "animation" {
+            return match ANIMATIONS.contains(&value.trim()) {
+                true => Ok(Modifier::Animation(value.trim().to_string())),
+                false => Err(format!("Unknown animation '{}': animations are {}", value, ANIMATIONS.join(", "))),
+            };
+        }
+        let parts: Vec<&str> = value.split_whitespace().collect();
+        match (key, parts.as_slice()) {
+            ("cornerRadius", [radius]) => Ok(Modifier::CornerRadius(points(radius, "corner radius")?)),
+            ("border", [width]) => Ok(Modifier::Border { width: points(width, "border width")?, color: None }),
+            ("border", [width, color]) => {
+                let color = Rgb::parse(color).ok_or_else(|| format!("Invalid border color '{}': expected a hex color such as #D1D1D6", color))?;
+                Ok(Modifier::Border { width: points(width, "border width")?, color: Some(color) })
+            }
+            ("transition", [name]) if TRANSITIONS.contains(name) && *name != "move" => Ok(Modifier::Transition(name.to_string())),
+            ("transition", ["move", edge]) if ["top", "bottom", "leading", "trailing"].contains(edge) => {
+                Ok(Modifier::Transition(format!("move(edge: .{})", edge)))
+            }
+            ("onTap", [name]) => Ok(Modifier::Tap(handler(name)?)),
+            ("onLongPress", [name]) => Ok(Modifier::LongPress(handler(name)?)),
+            ("onSwipe", [direction, name]) if SWIPE_DIRECTIONS.contains(direction) => {
+                Ok(Modifier::Swipe { direction: direction.to_string(), handler: handler(name)? })
+            }
+            ("onTap" | "onLongPress", _) => Err(format!("Invalid {} '{}': expected the name of its handler method, e.g. \"openProfile\"", key, value)),
+            ("onSwipe", [direction, _]) => Err(format!("Unknown swipe direction '{}': directions are {}", direction, SWIPE_DIRECTIONS.join(", "))),
+            ("onSwipe", _) => Err(format!("Invalid swipe '{}': expected a direction and the name of its handler method, e.g. \"left archive\"", value)),
+            ("transition", _) => Err(format!("Unknown transition '{}': transitions are opacity, slide, scale and move with an edge, e.g. \"move bottom\"", value)),
+            ("shadow", [radius]) => Ok(Modifier::Shadow { radius: points(radius, "shadow radius")?, x: 0.0, y: 0.0 }),
+            ("shadow", [radius, x, y]) => {
+                let offset = |text: &str| text.parse::<f64>().ok().filter(|n| n.is_finite()).ok_or_else(|| format!("Invalid shadow offset '{}': expected a number of points", text));
+                Ok(Modifier::Shadow { radius: points(radius, "shadow radius")?, x: offset(x)?, y: offset(y)? })
+            }
+            ("cornerRadius", _) => Err(format!("Invalid corner radius '{}': expected a number of points", value)),
+            ("border", _) => Err(format!("Invalid border '{}': expected its width, optionally followed by a hex color, e.g. \"1 #D1D1D6\"", value)),
+            ("shadow", _) => Err(format!("Invalid shadow '{}': expected its radius, optionally followed by an x and y offset, e.g. \"8 0 4\"", value)),
+            _ => Err(format!("Unsupported modifier key: '{}'", key)),
+        }
+    }
+}
+
+// Parses an overlay such as `topTrailing title:3`
+fn parse_overlay(value: &str) -> Result<Modifier, String> {
+    let invalid = || format!("Invalid overlay '{}': expected an alignment and the element it shows, e.g. \"topTrailing title:3\"", value);
+    let (alignment, element) = value.trim().split_once(char::is_whitespace).ok_or_else(invalid)?;
+    if !ALIGNMENTS.contains(&alignment) {
+        return Err(format!("Unknown overlay alignment '{}': alignments are {}", alignment, ALIGNMENTS.join(", ")));
+    }
+    let (key, content) = element.trim().split_once(':').ok_or_else(invalid)?;
+    let view = match key.trim() {
+        "title" => IR::Text(content.to_string()),
+        "Image" if !content.trim().is_empty() => IR::Image(content.trim().to_string()),
+        "Image" => return Err(invalid()),
+        key => return Err(format!("An overlay shows a `title` or an `Image`, not '{}'", key)),
+    };
+    Ok(Modifier::Overlay { alignment: alignment.to_string(), view })
+}
+
+// The code of an overlay's view, on one line: a text is set in the caption style of a badge
+fn overlay_view(view: &IR) -> String {
+    match view {
+        IR::Text(text) => format!("Text({}).font(.caption)", string_literal(text)),
+        IR::Image(name) => format!("Image({})", string_literal(name)),
+        _ => String::new(),
+    }
+}
+
+/// The SwiftUI modifiers for `modifiers`, without their leading dot: the corners are clipped
+/// first, the border follows them, overlays go on top, and the shadow is cast by the result.
+/// Gestures follow, each calling its handler. Last comes a transition, with the view's animation or else `animation`, or the animation
+/// alone, of changes to [`APPEAR_STATE`].
+pub fn swiftui(modifiers: &[Modifier], animation: &str) -> Vec<String> {
+    let radius = modifiers.iter().find_map(|modifier| match modifier {
+        Modifier::CornerRadius(radius) => Some(*radius),
+        _ => None,
+    });
+    let mut code = Vec::new();
+    if let Some(radius) = radius {
+        code.push(format!("clipShape(RoundedRectangle(cornerRadius: {}))", radius));
+    }
+    for modifier in modifiers {
+        if let Modifier::Border { width, color } = modifier {
+            let color = match color {
+                Some(rgb) => format!("Color(red: {}, green: {}, blue: {})", component(rgb.r), component(rgb.g), component(rgb.b)),
+                None => "Color.secondary".to_string(),
+            };
+            code.push(match radius {
+                Some(radius) => format!("overlay(RoundedRectangle(cornerRadius: {}).stroke({}, lineWidth: {}))", radius, color, width),
+                None => format!("border({}, width: {})", color, width),
+            });
+        }
+    }
+    for modifier in modifiers {
+        if let Modifier::Overlay { alignment, view } = modifier {
+            code.push(format!("overlay(alignment: .{}) {{ {} }}", alignment, overlay_view(view)));
+        }
+    }
+    for modifier in modifiers {
+        if let Modifier::Shadow { radius, x, y } = modifier {
+            code.push(match (x, y) {
+                (x, y) if *x == 0.0 && *y == 0.0 => format!("shadow(radius: {})", radius),
+                (x, y) => format!("shadow(radius: {}, x: {}, y: {})", radius, x, y),
+            });
+        }
+    }
+    for modifier in modifiers {
+        match modifier {
+            Modifier::Tap(handler) => code.push(format!("onTapGesture {{ {}() }}", handler)),
+            Modifier::LongPress(handler) => code.push(format!("onLongPressGesture {{ {}() }}", handler)),
+            Modifier::Swipe { direction, handler } => {
+                let (axis, sign) = match direction.as_str() {
+                    "left" => ("width", "<"),
+                    "right" => ("width", ">"),
+                    "up" => ("height", "<"),
+                    _ => ("height", ">"),
+                };
+                let distance = match sign {
+                    "<" => -SWIPE_DISTANCE,
+                    _ => SWIPE_DISTANCE,
+                };
+                code.push(format!(
+                    "gesture(DragGesture(minimumDistance: 20).onEnded {{ value in if value.translation.{} {} {} {{ {}() }} }})",
+                    axis, sign, distance, handler
+                ));
+            }
+            _ => {}
+        }
+    }
+    let stated = modifiers.iter().find_map(|modifier| match modifier {
+        Modifier::Animation(name) => Some(name.as_str()),
+        _ => None,
+    });
+    match (transition(modifiers), stated) {
+        (Some(transition), stated) => code.push(format!("transition(.{}.animation(.{}))", transition, stated.unwrap_or(animation))),
+        (None, Some(stated)) => code.push(format!("animation(.{}, value: {})", stated, APPEAR_STATE)),
+        (None, None) => {}
+    }
+    code
+}
+
+/// The transition of a view with `modifiers`, if it has one.
+pub fn transition(modifiers: &[Modifier]) -> Option<&str> {
+    modifiers.iter().find_map(|modifier| match modifier {
+        Modifier::Transition(transition) => Some(transition.as_str()),
+        _ => None,
+    })
+}
+
+/// Whether a view with `modifiers` animates once the screen appears.
+pub fn animates(modifiers: &[Modifier]) -> bool {
+    modifiers.iter().any(|modifier| matches!(modifier, Modifier::Animation(_) | Modifier::Transition(_)))
+}
+
+/// The handler methods the gestures of rendered `code` call, each with what it handles (`tap`,
+/// `long press` or `swipe`), in the order they are first called.
+pub fn handlers(code: &str) -> Vec<(String, &'static str)> {
+    let mut handlers: Vec<(String, &'static str)> = Vec::new();
+    for line in code.lines().map(str::trim) {
+        let (gesture, call) = match line {
+            line if line.starts_with(".onTapGesture { ") => ("tap", line.strip_suffix("() }")),
+            line if line.starts_with(".onLongPressGesture { ") => ("long press", line.strip_suffix("() }")),
+            line if line.starts_with(".gesture(DragGesture(") => ("swipe", line.strip_suffix("() } })")),
+            _ => continue,
+        };
+        let Some(name) = call.and_then(|call| call.rsplit(' ').next()) else { continue };
+        if !handlers.iter().any(|(given, _)| given == name) {
+            handlers.push((name.to_string(), gesture));
+        }
+    }
+    handlers
+}
+
+/// The modifiers the elements of `examples` state, by the view each asks for. A modifier is
+/// taken from the first example that states one of its kind for the view; wildcards and
+/// separators state none.
+pub fn stated(examples: &[Example]) -> BTreeMap<IR, Vec<Modifier>> {
+    let mut stated: BTreeMap<IR, Vec<Modifier>> = BTreeMap::new();
+    for example in examples {
+        let (Value::Dict(dims), Value::Dict(elements)) = example else { continue };
+        let Some((_, Value::Dict(frames))) = dims.iter().find(|(key, _)| key == "frames") else { continue };
+        let separators = verify::separators(example);
+        for (index, frame) in frames {
+            let Value::Dict(entries) = frame else { continue };
+            let Some(index) = index.parse::<usize>().ok().filter(|index| !separators.contains(index)) else { continue };
+            let Some(leaf) = elements.get(index).filter(|(_, value)| *value != Value::Any).and_then(|(key, value)| verify::element_leaf(key, value)) else {
+                continue;
+            };
+            for (key, value) in entries {
+                let Value::String(value) = value else { continue };
+                let Ok(modifier) = Modifier::parse(key, value) else { continue };
+                let modifiers = stated.entry(leaf.clone()).or_default();
+                if !modifiers.iter().any(|given| std::mem::discriminant(given) == std::mem::discriminant(&modifier)) {
+                    modifiers.push(modifier);
+                }
+            }
+        }
+    }
+    stated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    #[test]
+    fn test_modifiers_are_parsed() {
+        assert_eq!(Modifier::parse("cornerRadius", "12"), Ok(Modifier::CornerRadius(12.0)));
+        assert_eq!(
+            Modifier::parse("border", "1 #D1D1D6"),
+            Ok(Modifier::Border { width: 1.0, color: Some(Rgb { r: 0xD1, g: 0xD1, b: 0xD6 }) })
+        );
+        assert_eq!(Modifier::parse("shadow", "8 0 -4"), Ok(Modifier::Shadow { radius: 8.0, x: 0.0, y: -4.0 }));
+        assert!(Modifier::parse("cornerRadius", "-1").unwrap_err().contains("at least 0"));
+        assert!(Modifier::parse("border", "1 grey").unwrap_err().contains("hex color"));
+        assert!(Modifier::parse("shadow", "8 0").unwrap_err().starts_with("Invalid shadow '8 0'"));
+    }
+
+    #[test]
+    fn test_modifiers_render_in_order() {
+        let modifiers = [
+            Modifier::Shadow { radius: 8.0, x: 0.0, y: 4.0 },
+            Modifier::Border { width: 1.0, color: Some(Rgb { r: 0xD1, g: 0xD1, b: 0xD6 }) },
+            Modifier::CornerRadius(12.0),
+        ];
+        assert_eq!(
+            swiftui(&modifiers, "default"),
+            [
+                "clipShape(RoundedRectangle(cornerRadius: 12))",
+                "overlay(RoundedRectangle(cornerRadius: 12).stroke(Color(red: 0.82, green: 0.82, blue: 0.839), lineWidth: 1))",
+                "shadow(radius: 8, x: 0, y: 4)",
+            ]
+        );
+        assert_eq!(
+            swiftui(&[Modifier::Border { width: 2.0, color: None }, Modifier::Shadow { radius: 4.0, x: 0.0, y: 0.0 }], "default"),
+            ["border(Color.secondary, width: 2)", "shadow(radius: 4)"]
+        );
+    }
+
+    #[test]
+    fn test_overlays_are_anchored_views() {
+        let badge = Modifier::parse("overlay", "topTrailing title:New item").unwrap();
+        assert_eq!(badge, Modifier::Overlay { alignment: "topTrailing".to_string(), view: IR::Text("New item".to_string()) });
+        let check = Modifier::parse("overlay", "bottomLeading Image:check").unwrap();
+        assert_eq!(
+            swiftui(&[Modifier::Shadow { radius: 2.0, x: 0.0, y: 0.0 }, badge, check], "default"),
+            [
+                "overlay(alignment: .topTrailing) { Text(\"New item\").font(.caption) }",
+                "overlay(alignment: .bottomLeading) { Image(\"check\") }",
+                "shadow(radius: 2)",
+            ]
+        );
+        assert!(Modifier::parse("overlay", "corner title:3").unwrap_err().starts_with("Unknown overlay alignment 'corner'"));
+        assert!(Modifier::parse("overlay", "top button:Go").unwrap_err().contains("not 'button'"));
+        assert!(Modifier::parse("overlay", "top").unwrap_err().starts_with("Invalid overlay 'top'"));
+    }
+
+    #[test]
+    fn test_animations_and_transitions() {
+        let spring = Modifier::parse("animation", "spring").unwrap();
+        let slide = Modifier::parse("transition", "move bottom").unwrap();
+        assert_eq!(slide, Modifier::Transition("move(edge: .bottom)".to_string()));
+        assert_eq!(swiftui(std::slice::from_ref(&spring), "default"), ["animation(.spring, value: appeared)"]);
+        assert_eq!(swiftui(&[slide.clone(), spring], "default"), ["transition(.move(edge: .bottom).animation(.spring))"]);
+        assert_eq!(swiftui(&[Modifier::Transition("opacity".to_string())], "easeIn"), ["transition(.opacity.animation(.easeIn))"]);
+        assert!(animates(&[slide]) && !animates(&[Modifier::CornerRadius(4.0)]));
+        assert!(Modifier::parse("animation", "wobble").unwrap_err().starts_with("Unknown animation 'wobble'"));
+        assert!(Modifier::parse("transition", "move").unwrap_err().starts_with("Unknown transition 'move'"));
+    }
+
+    #[test]
+    fn test_gestures_call_their_handlers() {
+        let tap = Modifier::parse("onTap", "openProfile").unwrap();
+        let swipe = Modifier::parse("onSwipe", "left archive").unwrap();
+        assert_eq!(swipe, Modifier::Swipe { direction: "left".to_string(), handler: "archive".to_string() });
+        let code = swiftui(&[swipe, Modifier::CornerRadius(8.0), Modifier::parse("onLongPress", "showMenu").unwrap(), tap], "default");
+        assert_eq!(
+            code,
+            [
+                "clipShape(RoundedRectangle(cornerRadius: 8))",
+                "gesture(DragGesture(minimumDistance: 20).onEnded { value in if value.translation.width < -50 { archive() } })",
+                "onLongPressGesture { showMenu() }",
+                "onTapGesture { openProfile() }",
+            ]
+        );
+        let body: String = code.iter().chain(["onTapGesture { openProfile() }".to_string()].iter()).map(|line| format!("    .{}\n", line)).collect();
+        assert_eq!(handlers(&body), [("archive".to_string(), "swipe"), ("showMenu".to_string(), "long press"), ("openProfile".to_string(), "tap")]);
+        assert!(Modifier::parse("onTap", "open profile").unwrap_err().starts_with("Invalid onTap 'open profile'"));
+        assert!(Modifier::parse("onTap", "2fa").unwrap_err().starts_with("Invalid handler name '2fa'"));
+        assert!(Modifier::parse("onLongPress", "body").unwrap_err().starts_with("Invalid handler name 'body'"));
+        assert!(Modifier::parse("onSwipe", "sideways archive").unwrap_err().starts_with("Unknown swipe direction 'sideways'"));
+        assert!(Modifier::parse("onSwipe", "left").unwrap_err().starts_with("Invalid swipe 'left'"));
+    }
+
+    #[test]
+    fn test_modifiers_stated_by_the_examples() {
+        let examples = parse_examples(
+            "{(width:390,height:844):{button:\"Buy\"@(cornerRadius:12),title:\"Buy\"@(h:34),title:*@(shadow:2)}}
+             {(width:320,height:568):{button:\"Buy\"@(cornerRadius:8,border:1)}}",
+        )
+        .unwrap();
+        let stated = stated(&examples);
+        assert_eq!(stated.len(), 1);
+        assert_eq!(stated[&IR::Button("Buy".to_string())], [Modifier::CornerRadius(12.0), Modifier::Border { width: 1.0, color: None }]);
+    }
+}
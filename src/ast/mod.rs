@@ -1,5 +1,6 @@
 pub mod types;
 pub mod ir;
+pub mod diff;
 
-pub use types::Value;
+pub use types::{Example, Screen, Value};
 pub use ir::IR;
@@ -1,6 +1,36 @@
-#[derive(Clone, Debug, PartialEq)]
+use serde::Serialize;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub enum Value {
     Int(i32),
+    Float(f64),
+    Bool(bool),
     String(String),
+    Array(Vec<Value>),
     Dict(Vec<(String, Value)>),
+    /// A wildcard (`*` for an element, `?` for a dimension): present, but any value will do.
+    Any,
+}
+
+impl Value {
+    /// The number this value holds, if it is one. Lengths in an example's dimensions (screen
+    /// size, safe area and frames) are stored as `Float`s, since points need not be whole.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(n) => Some(f64::from(*n)),
+            Value::Float(x) => Some(*x),
+            _ => None,
+        }
+    }
+}
+
+/// One example: `(dimensions, elements)`, both `Value::Dict`s.
+pub type Example = (Value, Value);
+
+/// One screen of a spec and the examples it is synthesized from. A spec with several
+/// screens names each one, e.g. `Login:{...} Home:{...}`; a plain spec is a single unnamed screen.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Screen {
+    pub name: Option<String>,
+    pub examples: Vec<Example>,
 }
@@ -0,0 +1,254 @@
+// File: src/components.rs
+// Catalog of a team's design-system views, read from `components.toml`.
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use crate::ast::IR;
+use crate::input::lexer;
+use crate::layout::{CustomSize, Metrics};
+use crate::output::render;
+
+pub const COMPONENTS_FILE_NAME: &str = "components.toml";
+
+/// Built-in elements a component can stand in for.
+pub const ROLES: &[&str] = &["title", "button", "Image"];
+
+/// How a component sizes itself along one axis.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Extent {
+    /// A fixed number of points.
+    Points(f64),
+    /// `"fill"`: takes all the space offered.
+    Keyword(String),
+}
+
+/// One registered view.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Component {
+    /// Swift type name, e.g. `PrimaryButton`.
+    pub name: String,
+    /// Parameter labels; the element's value is passed to the first one.
+    #[serde(default)]
+    pub params: Vec<String>,
+    /// Built-in element this component replaces in generated code, e.g. `"button"`.
+    pub role: Option<String>,
+    /// Width in points, or `"fill"` (the default) to take the full width.
+    pub width: Option<Extent>,
+    /// Height in points; defaults to the layout model's custom view height.
+    pub height: Option<f64>,
+}
+
+impl Component {
+    /// Argument list passing `value` to the first parameter, e.g. `title: "Go"`.
+    pub fn args(&self, value: &str) -> String {
+        match value.is_empty() {
+            true => String::new(),
+            false => self.args_with(&render::string_literal(value)),
+        }
+    }
+
+    /// Argument list passing the Swift `literal` to the first parameter, e.g. `rating: 4.5`.
+    pub fn args_with(&self, literal: &str) -> String {
+        match self.params.first() {
+            Some(label) => format!("{}: {}", label, literal),
+            None => literal.to_string(),
+        }
+    }
+
+    // Recovers the value from arguments produced by `args`.
+    fn value_of(&self, args: &str) -> Option<String> {
+        let args = args.trim();
+        if args.is_empty() {
+            return Some(String::new());
+        }
+        let literal = match self.params.first() {
+            Some(label) => args.strip_prefix(label.as_str())?.trim_start().strip_prefix(':')?.trim(),
+            None => args,
+        };
+        let inner = literal.strip_prefix('"')?.strip_suffix('"')?;
+        lexer::unescape(inner).ok()
+    }
+
+    /// Swift call for this component showing `value`, e.g. `PrimaryButton(title: "Go")`.
+    pub fn call(&self, value: &str) -> IR {
+        IR::Custom { name: self.name.clone(), args: self.args(value) }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComponentLibrary {
+    #[serde(rename = "component")]
+    pub components: Vec<Component>,
+}
+
+impl ComponentLibrary {
+    /// Parses `components.toml` contents: a list of `[[component]]` tables.
+    pub fn parse(contents: &str) -> Result<ComponentLibrary, String> {
+        let library: ComponentLibrary = toml::from_str(contents).map_err(|e| e.to_string())?;
+        for component in &library.components {
+            if component.name.is_empty() || !component.name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+                return Err(format!("Invalid component name '{}'", component.name));
+            }
+            if let Some(role) = component.role.as_deref().filter(|role| !ROLES.contains(role)) {
+                return Err(format!(
+                    "Unknown role '{}' for component '{}': expected one of {}",
+                    role,
+                    component.name,
+                    ROLES.join(", ")
+                ));
+            }
+            if let Some(Extent::Keyword(keyword)) = component.width.as_ref().filter(|w| **w != Extent::fill()) {
+                return Err(format!(
+                    "Invalid width '{}' for component '{}': expected a number or \"fill\"",
+                    keyword, component.name
+                ));
+            }
+        }
+        Ok(library)
+    }
+
+    pub fn load(path: &Path) -> Result<ComponentLibrary, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read component library '{}': {}", path.display(), e))?;
+        ComponentLibrary::parse(&contents)
+            .map_err(|e| format!("Invalid component library '{}': {}", path.display(), e))
+    }
+
+    pub fn find(&self, name: &str) -> Option<&Component> {
+        self.components.iter().find(|c| c.name == name)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.components.iter().map(|c| c.name.clone()).collect()
+    }
+
+    /// The component registered for a built-in role, if any (the first one wins).
+    pub fn for_role(&self, role: &str) -> Option<&Component> {
+        self.components.iter().find(|c| c.role.as_deref() == Some(role))
+    }
+
+    /// Registers each component's size behavior with the layout model.
+    pub fn apply_metrics(&self, metrics: &mut Metrics) {
+        for component in &self.components {
+            let width = match component.width {
+                Some(Extent::Points(points)) => Some(points),
+                _ => None,
+            };
+            let height = component.height.unwrap_or(metrics.custom_height);
+            metrics.custom_sizes.insert(component.name.clone(), CustomSize { width, height });
+        }
+    }
+
+    /// Replaces built-in views with the components registered for their role.
+    pub fn substitute(&self, ir: &IR) -> IR {
+        let role = |role: &str, value: &str, original: &IR| {
+            self.for_role(role).map_or_else(|| original.clone(), |c| c.call(value))
+        };
+        match ir {
+            IR::VStack(children) => IR::VStack(children.iter().map(|c| self.substitute(c)).collect()),
+            IR::HStack(children) => IR::HStack(children.iter().map(|c| self.substitute(c)).collect()),
+            IR::Text(text) => role("title", text, ir),
+            IR::Button(label) => role("button", label, ir),
+            IR::Image(name) => role("Image", name, ir),
+            other => other.clone(),
+        }
+    }
+
+    /// Inverse of [`ComponentLibrary::substitute`]: turns calls of role components back
+    /// into the built-in views they stand in for, so existing code can be checked against examples.
+    pub fn restore(&self, ir: &IR) -> IR {
+        match ir {
+            IR::VStack(children) => IR::VStack(children.iter().map(|c| self.restore(c)).collect()),
+            IR::HStack(children) => IR::HStack(children.iter().map(|c| self.restore(c)).collect()),
+            IR::Custom { name, args } => {
+                let Some(component) = self.find(name) else { return ir.clone() };
+                let value = component.value_of(args);
+                match (component.role.as_deref(), value) {
+                    (Some("title"), Some(value)) => IR::Text(value),
+                    (Some("button"), Some(value)) => IR::Button(value),
+                    (Some("Image"), Some(value)) => IR::Image(value),
+                    _ => ir.clone(),
+                }
+            }
+            other => other.clone(),
+        }
+    }
+}
+
+impl Extent {
+    fn fill() -> Extent {
+        Extent::Keyword("fill".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LIBRARY: &str = r#"
+[[component]]
+name = "PrimaryButton"
+params = ["title"]
+role = "button"
+height = 50
+
+[[component]]
+name = "AvatarView"
+params = ["user"]
+width = 64
+height = 64
+
+[[component]]
+name = "Divider"
+"#;
+
+    #[test]
+    fn test_parse_library() {
+        let library = ComponentLibrary::parse(LIBRARY).unwrap();
+        assert_eq!(library.names(), vec!["PrimaryButton", "AvatarView", "Divider"]);
+        assert_eq!(library.for_role("button").unwrap().name, "PrimaryButton");
+        assert_eq!(library.find("AvatarView").unwrap().width, Some(Extent::Points(64.0)));
+        assert_eq!(ComponentLibrary::parse("").unwrap(), ComponentLibrary::default());
+    }
+
+    #[test]
+    fn test_rejects_invalid_entries() {
+        let err = ComponentLibrary::parse("[[component]]\nname = \"X\"\nrole = \"slider\"").unwrap_err();
+        assert!(err.contains("Unknown role 'slider' for component 'X'"));
+        let err = ComponentLibrary::parse("[[component]]\nname = \"X\"\nwidth = \"wide\"").unwrap_err();
+        assert!(err.contains("Invalid width 'wide'"));
+        assert!(ComponentLibrary::parse("[[component]]\nname = \"My View\"").unwrap_err().contains("Invalid component name"));
+        assert!(ComponentLibrary::parse("[[component]]\nname = \"X\"\ncolour = 1").unwrap_err().contains("unknown field"));
+    }
+
+    #[test]
+    fn test_call_and_substitute() {
+        let library = ComponentLibrary::parse(LIBRARY).unwrap();
+        let avatar = library.find("AvatarView").unwrap();
+        assert_eq!(avatar.call("me"), IR::Custom { name: "AvatarView".to_string(), args: "user: \"me\"".to_string() });
+        assert_eq!(library.find("Divider").unwrap().call(""), IR::Custom { name: "Divider".to_string(), args: String::new() });
+
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::Spacer, IR::Button("Say \"Go\"".to_string())]);
+        assert_eq!(
+            library.substitute(&ir),
+            IR::VStack(vec![
+                IR::Text("Hi".to_string()),
+                IR::Spacer,
+                IR::Custom { name: "PrimaryButton".to_string(), args: "title: \"Say \\\"Go\\\"\"".to_string() },
+            ])
+        );
+        assert_eq!(library.restore(&library.substitute(&ir)), ir);
+    }
+
+    #[test]
+    fn test_apply_metrics() {
+        let mut metrics = Metrics::default();
+        ComponentLibrary::parse(LIBRARY).unwrap().apply_metrics(&mut metrics);
+        assert_eq!(metrics.custom_sizes["AvatarView"], CustomSize { width: Some(64.0), height: 64.0 });
+        assert_eq!(metrics.custom_sizes["Divider"], CustomSize { width: None, height: metrics.custom_height });
+    }
+}
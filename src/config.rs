@@ -0,0 +1,532 @@
+// File: src/config.rs
+// Per-run configuration read from `swiftui-synth.toml`.
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::ast::ir::modifier::ANIMATIONS;
+use crate::components::COMPONENTS_FILE_NAME;
+use crate::layout::calibrate::CONSTANTS;
+use crate::layout::spacing::{Scale, DEFAULT_SCALE, DEFAULT_TOLERANCE};
+use crate::layout::text_metrics::FontTableConfig;
+use crate::layout::{Metrics, TextMetrics};
+use crate::output::backend::Registry;
+use crate::output::format::FormatOptions;
+use crate::output::render::{RenderOptions, SENSORY_FEEDBACKS};
+use crate::platform::Platform;
+use crate::synthesis::evaluate::CostWeights;
+use crate::synthesis::lint::LintOptions;
+#[cfg(feature = "llm")]
+use crate::synthesis::llm::Provider;
+use crate::synthesis::search::Budget;
+use crate::synthesis::strategy::STRATEGIES;
+
+pub const CONFIG_FILE_NAME: &str = "swiftui-synth.toml";
+
+/// Settings loaded from a config file. Every field is optional; anything left
+/// out falls back to the built-in defaults, and CLI flags take precedence.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Default output target, e.g. `"swiftui"`: a built-in one or a plugin's.
+    pub target: Option<String>,
+    /// Platform the views are for, e.g. `"watchos"`; see `--platform`.
+    pub platform: Option<String>,
+    pub render: RenderSection,
+    pub synthesis: SynthesisSection,
+    /// Weights of the search's ranking cost.
+    pub cost: CostWeights,
+    pub modifiers: ModifierSection,
+    pub tokens: TokenSection,
+    /// Snapping of the paddings and spacings the code spells out; see `--snap-spacing`.
+    pub spacing: SpacingSection,
+    pub components: ComponentSection,
+    pub format: FormatSection,
+    /// Thresholds of the lint pass over synthesized layouts.
+    pub lint: LintOptions,
+    pub backends: BackendSection,
+    /// Character widths the layout measures text with.
+    pub fonts: FontSection,
+    /// Layout constants that replace the platform's, e.g. as fitted by `calibrate`.
+    pub layout: BTreeMap<String, f64>,
+    /// Endpoint asked for hints with `--llm`.
+    pub llm: LlmSection,
+    /// Directory containing the config file; relative paths resolve against it.
+    #[serde(skip)]
+    pub base_dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct RenderSection {
+    /// Spaces per indentation level.
+    pub indent: Option<usize>,
+    /// Directory of Tera templates the SwiftUI code is rendered from; see `--template-dir`.
+    pub template_dir: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SynthesisSection {
+    pub timeout_ms: Option<u64>,
+    pub max_depth: Option<usize>,
+    pub max_candidates: Option<usize>,
+    /// Memory the search's record of candidates may take, in MiB; see `--max-memory`.
+    pub max_memory_mb: Option<usize>,
+    /// Seed for the search's exploration order; see `--seed`.
+    pub seed: Option<u64>,
+    /// Synthesis strategy, e.g. `"constraint"`; see `--strategy`.
+    pub strategy: Option<String>,
+    /// Number of ranked layouts to offer; see `--top-n`.
+    pub top_n: Option<usize>,
+    /// Partial layouts the beam strategy keeps at each step; see `--beam-width`.
+    pub beam_width: Option<usize>,
+    /// Model ordering the `--top-n` layouts, written by `train-ranker`; see `--ranker`.
+    pub ranker: Option<PathBuf>,
+    /// Log of the layouts picked interactively, to train a ranker on; see `--feedback-log`.
+    pub feedback_log: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmSection {
+    /// Provider to ask without `--llm`, `"openai"` or `"ollama"`; unset asks none.
+    pub provider: Option<String>,
+    /// Model name, e.g. `"gpt-4o-mini"`; defaults per provider.
+    pub model: Option<String>,
+    /// Chat endpoint URL, for proxies and self-hosted servers; defaults per provider.
+    pub endpoint: Option<String>,
+    /// Time allowed for a reply before synthesis carries on without one.
+    pub timeout_ms: Option<u64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ModifierSection {
+    /// Text style for `Text` views, e.g. `"headline"`.
+    pub text_font: Option<String>,
+    /// Whether to emit `.padding()` on stacks, texts and buttons.
+    pub padding: Option<bool>,
+    /// Whether to emit `.accessibilityIdentifier` on texts, buttons and images.
+    pub accessibility_identifiers: Option<bool>,
+    /// Animation of views with a transition but no animation of their own, e.g. `"spring"`.
+    pub animation: Option<String>,
+    /// Sensory feedback buttons give when tapped, e.g. `"impact"`; none without it.
+    pub haptics: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct TokenSection {
+    /// Design token files (colors, spacing, typography).
+    pub files: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpacingSection {
+    /// Whether to snap to the scale without `--snap-spacing`.
+    pub snap: Option<bool>,
+    /// Steps of the scale in points; defaults to the spacing tokens, else 4, 8, 12, 16, 20, 24, 32, 40, 48, 64.
+    pub scale: Vec<f64>,
+    /// Points a value may be from its nearest step and still snap to it; 2 by default.
+    pub tolerance: Option<f64>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ComponentSection {
+    /// Component library file; defaults to `components.toml` next to the config file.
+    pub file: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackendSection {
+    /// WebAssembly modules adding output targets, each named after its file: `dsl.wasm`
+    /// adds the target `dsl`.
+    pub plugins: Vec<PathBuf>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FontSection {
+    /// Character widths of `Text` views, for a custom typeface.
+    pub title: FontTableConfig,
+    /// Character widths of button labels.
+    pub body: FontTableConfig,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FormatSection {
+    /// Formatter `--post-format` pipes the code through, e.g. `"swift-format"`.
+    pub command: Option<String>,
+    /// Line width of the built-in pretty-printer.
+    pub line_width: Option<usize>,
+}
+
+impl Config {
+    /// Parses config file contents.
+    pub fn parse(contents: &str) -> Result<Config, String> {
+        let config: Config = toml::from_str(contents).map_err(|e| e.to_string())?;
+        if let Some(target) = config.target.as_ref().filter(|target| !config.plugin_targets().contains(target)) {
+            validate_target(target)?;
+        }
+        if let Some(platform) = &config.platform {
+            platform.parse::<Platform>()?;
+        }
+        if let Some(strategy) = &config.synthesis.strategy {
+            if !STRATEGIES.contains(&strategy.as_str()) {
+                return Err(format!("Unknown strategy '{}': expected one of {}", strategy, STRATEGIES.join(", ")));
+            }
+        }
+        #[cfg(feature = "llm")]
+        if let Some(provider) = &config.llm.provider {
+            provider.parse::<Provider>()?;
+        }
+        for (name, value) in &config.layout {
+            if !CONSTANTS.iter().any(|(constant, _, _)| constant == name) {
+                let names: Vec<&str> = CONSTANTS.iter().map(|(constant, _, _)| *constant).collect();
+                return Err(format!("Unknown layout constant '{}': expected one of {}", name, names.join(", ")));
+            }
+            if !(value.is_finite() && *value >= 0.0) {
+                return Err(format!("The layout constant '{}' must be a number of points of 0 or more, not {}", name, value));
+            }
+        }
+        for (style, table) in [("title", &config.fonts.title), ("body", &config.fonts.body)] {
+            table.table().map_err(|e| format!("[fonts.{}]: {}", style, e))?;
+        }
+        config.spacing_scale(&[]).map_err(|e| format!("[spacing]: {}", e))?;
+        if let Some(animation) = config.modifiers.animation.as_ref().filter(|animation| !ANIMATIONS.contains(&animation.as_str())) {
+            return Err(format!("[modifiers]: Unknown animation '{}': animations are {}", animation, ANIMATIONS.join(", ")));
+        }
+        if let Some(haptics) = config.modifiers.haptics.as_ref().filter(|haptics| !SENSORY_FEEDBACKS.contains(&haptics.as_str())) {
+            return Err(format!("[modifiers]: Unknown haptics '{}': kinds of feedback are {}", haptics, SENSORY_FEEDBACKS.join(", ")));
+        }
+        Ok(config)
+    }
+
+    /// Reads and parses the config file at `path`.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read config file '{}': {}", path.display(), e))?;
+        let mut config = Config::parse(&contents)
+            .map_err(|e| format!("Invalid config file '{}': {}", path.display(), e))?;
+        config.base_dir = path.parent().map(Path::to_path_buf);
+        Ok(config)
+    }
+
+    /// Finds the nearest `swiftui-synth.toml` in `start` or any of its ancestors.
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        start
+            .ancestors()
+            .map(|dir| dir.join(CONFIG_FILE_NAME))
+            .find(|candidate| candidate.is_file())
+    }
+
+    /// Loads the explicitly requested config, or the discovered one, or defaults.
+    pub fn resolve(explicit: Option<&Path>, cwd: &Path) -> Result<Config, String> {
+        match explicit.map(Path::to_path_buf).or_else(|| Config::discover(cwd)) {
+            Some(path) => Config::load(&path),
+            None => Ok(Config::default()),
+        }
+    }
+
+    /// The configured platform; iOS if none is.
+    pub fn platform(&self) -> Platform {
+        self.platform.as_deref().and_then(|platform| platform.parse().ok()).unwrap_or_default()
+    }
+
+    /// Layout constants of the configured platform with those set under `[layout]`, measuring
+    /// text with the configured font tables.
+    pub fn metrics(&self) -> Metrics {
+        let table = |config: &FontTableConfig| config.table().expect("font tables are checked when the config is parsed");
+        let mut metrics = Metrics { text: TextMetrics { title: table(&self.fonts.title), body: table(&self.fonts.body) }, ..self.platform().metrics() };
+        for (name, _, set) in CONSTANTS {
+            if let Some(value) = self.layout.get(*name) {
+                set(&mut metrics, *value);
+            }
+        }
+        metrics
+    }
+
+    pub fn render_options(&self) -> RenderOptions {
+        let defaults = RenderOptions::default();
+        let platform = self.platform();
+        RenderOptions {
+            indent_width: self.render.indent.unwrap_or(defaults.indent_width),
+            text_font: self.modifiers.text_font.clone().unwrap_or_else(|| platform.text_font().to_string()),
+            platform,
+            padding: self.modifiers.padding.unwrap_or(defaults.padding),
+            accessibility_identifiers: self.modifiers.accessibility_identifiers.unwrap_or(defaults.accessibility_identifiers),
+            animation: self.modifiers.animation.clone().unwrap_or_else(|| defaults.animation.clone()),
+            haptics: self.modifiers.haptics.clone(),
+            ..defaults
+        }
+    }
+
+    /// The configured spacing scale; without one, the steps of the spacing `tokens`, or the
+    /// default scale if there are none.
+    pub fn spacing_scale(&self, tokens: &[f64]) -> Result<Scale, String> {
+        let steps = match (self.spacing.scale.is_empty(), tokens.is_empty()) {
+            (false, _) => self.spacing.scale.clone(),
+            (true, false) => tokens.to_vec(),
+            (true, true) => DEFAULT_SCALE.to_vec(),
+        };
+        Scale::new(steps, self.spacing.tolerance.unwrap_or(DEFAULT_TOLERANCE))
+    }
+
+    pub fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            line_width: self.format.line_width.unwrap_or(FormatOptions::default().line_width),
+            indent_width: self.render_options().indent_width,
+        }
+    }
+
+    /// Lint thresholds, with buttons' padding as the renderer emits it.
+    pub fn lint_options(&self) -> LintOptions {
+        LintOptions { padding: self.render_options().padding, text_font: self.render_options().text_font, ..self.lint.clone() }
+    }
+
+    pub fn budget(&self) -> Budget {
+        let defaults = Budget::default();
+        Budget {
+            timeout: self.synthesis.timeout_ms.map(Duration::from_millis).or(defaults.timeout),
+            max_depth: self.synthesis.max_depth.unwrap_or(defaults.max_depth),
+            max_candidates: self.synthesis.max_candidates.unwrap_or(defaults.max_candidates),
+            seed: self.synthesis.seed.or(defaults.seed),
+            top_n: self.synthesis.top_n.unwrap_or(defaults.top_n),
+            beam_width: self.synthesis.beam_width.unwrap_or(defaults.beam_width),
+            max_memory: self.synthesis.max_memory_mb.map(|mb| mb.saturating_mul(1 << 20)).or(defaults.max_memory),
+            cancel: defaults.cancel,
+        }
+    }
+
+    /// Component library path: the configured file, resolved relative to the config file's
+    /// directory, else a `components.toml` beside the config file, if one exists.
+    pub fn components_file(&self) -> Option<PathBuf> {
+        match (&self.components.file, &self.base_dir) {
+            (Some(file), Some(base)) if file.is_relative() => Some(base.join(file)),
+            (Some(file), _) => Some(file.clone()),
+            (None, Some(base)) => Some(base.join(COMPONENTS_FILE_NAME)).filter(|path| path.is_file()),
+            (None, None) => None,
+        }
+    }
+
+    // `files` resolved relative to the config file's directory
+    fn resolve_files(&self, files: &[PathBuf]) -> Vec<PathBuf> {
+        files
+            .iter()
+            .map(|file| match &self.base_dir {
+                Some(base) if file.is_relative() => base.join(file),
+                _ => file.clone(),
+            })
+            .collect()
+    }
+
+    /// Token file paths, resolved relative to the config file's directory.
+    pub fn token_files(&self) -> Vec<PathBuf> {
+        self.resolve_files(&self.tokens.files)
+    }
+
+    /// Backend plugin paths, resolved relative to the config file's directory.
+    pub fn plugin_files(&self) -> Vec<PathBuf> {
+        self.resolve_files(&self.backends.plugins)
+    }
+
+    /// The ranking model's path, resolved relative to the config file's directory.
+    pub fn ranker_file(&self) -> Option<PathBuf> {
+        self.synthesis.ranker.as_ref().map(|file| self.resolve_files(std::slice::from_ref(file)).remove(0))
+    }
+
+    /// The feedback log's path, resolved relative to the config file's directory.
+    pub fn feedback_log_file(&self) -> Option<PathBuf> {
+        self.synthesis.feedback_log.as_ref().map(|file| self.resolve_files(std::slice::from_ref(file)).remove(0))
+    }
+
+    /// The template directory, resolved relative to the config file's directory.
+    pub fn template_dir(&self) -> Option<PathBuf> {
+        self.render.template_dir.as_ref().map(|dir| self.resolve_files(std::slice::from_ref(dir)).remove(0))
+    }
+
+    /// Names of the targets the backend plugins add.
+    pub fn plugin_targets(&self) -> Vec<String> {
+        self.backends.plugins.iter().filter_map(|path| path.file_stem()).map(|stem| stem.to_string_lossy().into_owned()).collect()
+    }
+}
+
+/// Checks that `target` is a built-in output target.
+pub fn validate_target(target: &str) -> Result<(), String> {
+    Registry::builtin().resolve(target).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("swiftui-synth-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_parse_full_config() {
+        let config = Config::parse(
+            r#"
+target = "swiftui"
+
+[render]
+indent = 2
+
+[synthesis]
+timeout_ms = 5000
+max_depth = 4
+
+[modifiers]
+text_font = "headline"
+padding = false
+
+[cost]
+depth = 10.0
+
+[tokens]
+files = ["tokens/colors.json"]
+
+[format]
+command = "swift-format"
+line_width = 80
+
+[lint]
+max_depth = 5
+allow = ["W0007"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.target.as_deref(), Some("swiftui"));
+        assert_eq!(config.synthesis.timeout_ms, Some(5000));
+        assert_eq!(config.synthesis.max_depth, Some(4));
+        assert_eq!(config.synthesis.max_candidates, None);
+        assert_eq!(
+            config.budget(),
+            Budget { timeout: Some(Duration::from_secs(5)), max_depth: 4, ..Budget::default() }
+        );
+        assert_eq!(
+            config.render_options(),
+            RenderOptions { indent_width: 2, text_font: "headline".to_string(), padding: false, ..RenderOptions::default() }
+        );
+        assert_eq!(config.cost, CostWeights { depth: 10.0, ..CostWeights::default() });
+        assert_eq!(config.format.command.as_deref(), Some("swift-format"));
+        assert_eq!(config.format_options(), FormatOptions { line_width: 80, indent_width: 2 });
+        assert_eq!(
+            config.lint_options(),
+            LintOptions { max_depth: 5, allow: vec!["W0007".to_string()], padding: false, text_font: "headline".to_string(), ..LintOptions::default() }
+        );
+    }
+
+    #[test]
+    fn test_font_tables() {
+        let config = Config::parse("[fonts.title]\nbase = \"uniform\"\ndefault = 0.5\nadvances = { \"W\" = 1.0 }\n").unwrap();
+        let metrics = config.metrics();
+        assert_eq!(metrics.text.title.width("aW", 10.0), 15.0);
+        assert_eq!(metrics.text.body, TextMetrics::default().body);
+        assert!(Config::parse("[fonts.body]\nadvances = { \"ab\" = 0.5 }").unwrap_err().contains("[fonts.body]"));
+        assert!(Config::parse("[fonts.title]\nsize = 3").unwrap_err().contains("unknown field"));
+    }
+
+    #[test]
+    fn test_llm_section() {
+        let config = Config::parse("[llm]\nprovider = \"ollama\"\nmodel = \"qwen2.5\"\ntimeout_ms = 500").unwrap();
+        assert_eq!((config.llm.provider.as_deref(), config.llm.timeout_ms), (Some("ollama"), Some(500)));
+        if cfg!(feature = "llm") {
+            assert!(Config::parse("[llm]\nprovider = \"gemini\"").unwrap_err().contains("Unknown LLM provider 'gemini'"));
+        }
+    }
+
+    #[test]
+    fn test_layout_constants() {
+        let metrics = Config::parse("[layout]\npadding = 12.5\nbody_line_height = 20").unwrap().metrics();
+        assert_eq!((metrics.padding, metrics.body_line_height), (12.5, 20.0));
+        assert_eq!(metrics.stack_spacing, Metrics::default().stack_spacing);
+        assert!(Config::parse("[layout]\nmargin = 4").unwrap_err().contains("Unknown layout constant 'margin'"));
+        assert!(Config::parse("[layout]\npadding = -1").unwrap_err().contains("0 or more"));
+    }
+
+    #[test]
+    fn test_spacing_scale() {
+        let config = Config::parse("[spacing]\nsnap = true\ntolerance = 1").unwrap();
+        assert_eq!(config.spacing.snap, Some(true));
+        let scale = config.spacing_scale(&[]).unwrap();
+        assert_eq!((scale.steps().len(), scale.tolerance()), (10, 1.0));
+        assert_eq!(config.spacing_scale(&[6.0, 10.0]).unwrap().steps(), [6.0, 10.0]);
+        // A configured scale wins over the tokens
+        let config = Config::parse("[spacing]\nscale = [5, 10]").unwrap();
+        assert_eq!(config.spacing_scale(&[6.0]).unwrap().steps(), [5.0, 10.0]);
+        assert!(Config::parse("[spacing]\nscale = [0, 4]").unwrap_err().contains("[spacing]: Invalid spacing step 0"));
+        assert!(Config::parse("[spacing]\ntolerance = -2").unwrap_err().contains("must not be negative"));
+    }
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config, Config::default());
+        assert_eq!(config.render_options(), RenderOptions::default());
+        assert_eq!(config.lint_options(), LintOptions::default());
+    }
+
+    #[test]
+    fn test_rejects_unknown_keys_and_targets() {
+        assert!(Config::parse("colour = \"red\"").unwrap_err().contains("unknown field"));
+        assert!(Config::parse("[cost]\nwidth = 1.0").unwrap_err().contains("unknown field `width`"));
+        assert!(Config::parse("target = \"flutter\"").unwrap_err().contains("Unsupported target 'flutter'"));
+        assert!(Config::parse("[synthesis]\nstrategy = \"annealing\"").unwrap_err().contains("Unknown strategy 'annealing'"));
+        assert!(Config::parse("platform = \"visionos\"").unwrap_err().contains("Unknown platform 'visionos'"));
+        // Plugins add targets
+        let config = Config::parse("target = \"dsl\"\n[backends]\nplugins = [\"plugins/dsl.wasm\"]").unwrap();
+        assert_eq!(config.plugin_targets(), vec!["dsl"]);
+    }
+
+    #[test]
+    fn test_platform_picks_the_default_text_font() {
+        let config = Config::parse("platform = \"watchos\"").unwrap();
+        assert_eq!(config.platform(), Platform::Watchos);
+        assert_eq!(config.render_options().text_font, "headline");
+        assert_eq!(config.render_options().platform, Platform::Watchos);
+        let config = Config::parse("platform = \"watchos\"\n[modifiers]\ntext_font = \"title3\"").unwrap();
+        assert_eq!(config.render_options().text_font, "title3");
+        assert_eq!(Config::default().platform(), Platform::Ios);
+    }
+
+    #[test]
+    fn test_modifiers_name_a_known_animation() {
+        assert_eq!(Config::parse("[modifiers]\nanimation = \"spring\"").unwrap().render_options().animation, "spring");
+        assert_eq!(Config::default().render_options().animation, "default");
+        assert!(Config::parse("[modifiers]\nanimation = \"wobble\"").unwrap_err().starts_with("[modifiers]: Unknown animation 'wobble'"));
+        assert_eq!(Config::parse("[modifiers]\nhaptics = \"success\"").unwrap().render_options().haptics.as_deref(), Some("success"));
+        assert_eq!(Config::default().render_options().haptics, None);
+        assert!(Config::parse("[modifiers]\nhaptics = \"buzz\"").unwrap_err().starts_with("[modifiers]: Unknown haptics 'buzz'"));
+    }
+
+    #[test]
+    fn test_discover_walks_upward_and_resolves_token_paths() {
+        let root = temp_dir("discover");
+        let nested = root.join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(CONFIG_FILE_NAME), "[tokens]\nfiles = [\"tokens.json\"]\n[synthesis]\nranker = \"ranker.json\"\n").unwrap();
+
+        assert_eq!(Config::discover(&nested), Some(root.join(CONFIG_FILE_NAME)));
+        let config = Config::resolve(None, &nested).unwrap();
+        assert_eq!(config.token_files(), vec![root.join("tokens.json")]);
+        assert_eq!(config.ranker_file(), Some(root.join("ranker.json")));
+        assert_eq!(config.feedback_log_file(), None);
+        assert_eq!(config.components_file(), None);
+        fs::write(root.join(COMPONENTS_FILE_NAME), "").unwrap();
+        assert_eq!(config.components_file(), Some(root.join(COMPONENTS_FILE_NAME)));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
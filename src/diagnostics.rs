@@ -0,0 +1,243 @@
+// File: src/diagnostics.rs
+// Structured errors and warnings with codes, source spans and fix suggestions.
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// Byte range into the source text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+
+    /// Span of `part` within `source`. `part` must be a subslice of `source`.
+    pub fn of(source: &str, part: &str) -> Span {
+        let start = (part.as_ptr() as usize).saturating_sub(source.as_ptr() as usize).min(source.len());
+        Span { start, end: (start + part.len()).min(source.len()) }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Diagnostic {
+    /// Stable identifier, e.g. `E0012`.
+    pub code: &'static str,
+    pub severity: Severity,
+    pub span: Option<Span>,
+    pub message: String,
+    /// Human-readable fixes, e.g. "did you mean `title`?".
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(code: &'static str, message: impl Into<String>) -> Diagnostic {
+        Diagnostic { code, severity: Severity::Error, span: None, message: message.into(), suggestions: Vec::new() }
+    }
+
+    pub fn warning(code: &'static str, message: impl Into<String>) -> Diagnostic {
+        Diagnostic { severity: Severity::Warning, ..Diagnostic::error(code, message) }
+    }
+
+    pub fn with_span(mut self, span: Span) -> Diagnostic {
+        self.span = Some(span);
+        self
+    }
+
+    pub fn with_suggestion(mut self, suggestion: impl Into<String>) -> Diagnostic {
+        self.suggestions.push(suggestion.into());
+        self
+    }
+
+    /// Renders the diagnostic for a terminal, underlining the span with carets:
+    ///
+    /// ```text
+    /// error[E0012]: Unsupported element key 'titel'
+    ///  --> spec.txt:1:26
+    ///   |
+    /// 1 | {(width:390,height:844):{titel:"Hello"}}
+    ///   |                          ^^^^^
+    ///   = help: did you mean `title`?
+    /// ```
+    pub fn render(&self, source: &str, origin: &str) -> String {
+        let mut out = format!("{}[{}]: {}\n", self.severity, self.code, self.message);
+        if let Some(span) = self.span {
+            let start = floor_char_boundary(source, span.start);
+            let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = source[start..].find('\n').map_or(source.len(), |i| start + i);
+            let line_no = source[..start].matches('\n').count() + 1;
+            let line = &source[line_start..line_end];
+            let column = source[line_start..start].chars().count();
+            let end = floor_char_boundary(source, span.end.clamp(start, line_end));
+            let width = source[start..end].chars().count().max(1);
+            let gutter = " ".repeat(line_no.to_string().len());
+            out.push_str(&format!("{}--> {}:{}:{}\n", gutter, origin, line_no, column + 1));
+            out.push_str(&format!("{} |\n", gutter));
+            out.push_str(&format!("{} | {}\n", line_no, line));
+            out.push_str(&format!("{} | {}{}\n", gutter, " ".repeat(column), "^".repeat(width)));
+            for suggestion in &self.suggestions {
+                out.push_str(&format!("{} = help: {}\n", gutter, suggestion));
+            }
+        } else {
+            for suggestion in &self.suggestions {
+                out.push_str(&format!("  = help: {}\n", suggestion));
+            }
+        }
+        out
+    }
+}
+
+/// Edit distance between two strings, counted in chars. Insertions, deletions,
+/// substitutions and transpositions of adjacent chars (`titel` → `title`) cost one each.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Returns up to three candidates close to `input`, closest first.
+///
+/// Comparison is case-insensitive; a candidate qualifies when it is within
+/// roughly a third of the input's length in edits (at least one edit).
+pub fn did_you_mean<'a>(input: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+    let needle = input.to_lowercase();
+    let threshold = (needle.chars().count() / 3).max(1);
+    let mut scored: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(&needle, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .collect();
+    scored.sort_by_key(|(distance, _)| *distance); // stable: ties keep candidate order
+    scored.dedup_by_key(|(_, candidate)| *candidate);
+    scored.into_iter().take(3).map(|(_, candidate)| candidate).collect()
+}
+
+/// Formats `did_you_mean` matches as a help line, e.g. "did you mean `title` or `Image`?".
+pub fn suggestion_text(matches: &[&str]) -> Option<String> {
+    let quoted: Vec<String> = matches.iter().map(|m| format!("`{}`", m)).collect();
+    match quoted.as_slice() {
+        [] => None,
+        [only] => Some(format!("did you mean {}?", only)),
+        [init @ .., last] => Some(format!("did you mean {} or {}?", init.join(", "), last)),
+    }
+}
+
+fn floor_char_boundary(source: &str, index: usize) -> usize {
+    let mut index = index.min(source.len());
+    while !source.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for Diagnostic {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_span_of_subslice() {
+        let source = "{(width:390):{titel:\"x\"}}";
+        let part = &source[14..19];
+        assert_eq!(Span::of(source, part), Span::new(14, 19));
+    }
+
+    #[test]
+    fn test_render_with_caret_and_help() {
+        let source = "{(width:390,height:844):{titel:\"Hello\"}}";
+        let diagnostic = Diagnostic::error("E0012", "Unsupported element key 'titel'")
+            .with_span(Span::new(25, 30))
+            .with_suggestion("did you mean `title`?");
+        assert_eq!(
+            diagnostic.render(source, "spec.txt"),
+            "error[E0012]: Unsupported element key 'titel'
+ --> spec.txt:1:26
+  |
+1 | {(width:390,height:844):{titel:\"Hello\"}}
+  |                          ^^^^^
+  = help: did you mean `title`?
+"
+        );
+    }
+
+    #[test]
+    fn test_render_multiline_and_multibyte() {
+        let source = "// ✨ spec\n{(width:390):{bad:\"x\"}}";
+        let start = source.find("bad").unwrap();
+        let rendered = Diagnostic::warning("W0001", "ignored")
+            .with_span(Span::new(start, start + 3))
+            .render(source, "<examples>");
+        assert!(rendered.starts_with("warning[W0001]: ignored\n --> <examples>:2:15\n"));
+        assert!(rendered.contains("2 | {(width:390):{bad:\"x\"}}\n  |               ^^^\n"));
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("titel", "title"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("buton", "button"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("émoji", "emoji"), 1);
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let keys = ["title", "button", "Image", "Custom"];
+        assert_eq!(did_you_mean("titel", keys), vec!["title"]);
+        assert_eq!(did_you_mean("image", keys), vec!["Image"]);
+        assert_eq!(did_you_mean("Butto", keys), vec!["button"]);
+        assert!(did_you_mean("TextField", keys).is_empty());
+        assert_eq!(suggestion_text(&["a", "b", "c"]).unwrap(), "did you mean `a`, `b` or `c`?");
+        assert_eq!(suggestion_text(&[]), None);
+    }
+
+    #[test]
+    fn test_serializes_to_json() {
+        let diagnostic = Diagnostic::error("E0007", "Invalid width value").with_span(Span::new(8, 11));
+        let json = serde_json::to_string(&diagnostic).unwrap();
+        assert_eq!(
+            json,
+            r#"{"code":"E0007","severity":"error","span":{"start":8,"end":11},"message":"Invalid width value","suggestions":[]}"#
+        );
+    }
+}
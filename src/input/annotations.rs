@@ -0,0 +1,296 @@
+// File: src/input/annotations.rs
+// Importer for annotated screenshots: a screenshot and the labeled bounding boxes a labeling
+// tool exported for it, as JSON or CSV, become a screen of a spec whose elements state where
+// the boxes are, so synthesis finds the SwiftUI layout that reproduces the screenshot.
+use serde_json::Value;
+
+use crate::input::import::{device_safe_area, screen_name, write_screen, Element, Example, Import};
+use crate::layout::devices::Insets;
+
+/// A labeled box, in the screenshot's pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annotation {
+    pub label: String,
+    /// What the box says, for texts and buttons, or the name of its image.
+    pub text: Option<String>,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Width and height in pixels of a PNG or JPEG image, read from its header.
+pub fn image_size(bytes: &[u8]) -> Result<(u32, u32), String> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") && bytes.len() >= 24 && &bytes[12..16] == b"IHDR" {
+        let word = |at: usize| u32::from_be_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]]);
+        return Ok((word(16), word(20)));
+    }
+    if bytes.starts_with(&[0xff, 0xd8]) {
+        // Walk the segments to a start of frame, which holds the size
+        let mut at = 2;
+        while at + 9 < bytes.len() && bytes[at] == 0xff {
+            let marker = bytes[at + 1];
+            let length = u16::from_be_bytes([bytes[at + 2], bytes[at + 3]]) as usize;
+            if matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+                let height = u16::from_be_bytes([bytes[at + 5], bytes[at + 6]]);
+                let width = u16::from_be_bytes([bytes[at + 7], bytes[at + 8]]);
+                return Ok((width.into(), height.into()));
+            }
+            at += 2 + length;
+        }
+        return Err("the JPEG has no frame size".to_string());
+    }
+    Err("not a PNG or JPEG image".to_string())
+}
+
+// The first of `names` `object` has, as a string or a number
+fn field<'a>(object: &'a Value, names: &[&str]) -> Option<&'a Value> {
+    names.iter().find_map(|name| object.get(*name)).filter(|value| !value.is_null())
+}
+
+fn text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        _ => None,
+    }
+}
+
+// A box given as `bbox: [x, y, width, height]` or as separate fields
+fn bounds(object: &Value) -> Option<(f64, f64, f64, f64)> {
+    if let Some(Value::Array(bbox)) = object.get("bbox") {
+        let numbers: Vec<f64> = bbox.iter().filter_map(Value::as_f64).collect();
+        return match numbers[..] {
+            [x, y, width, height] => Some((x, y, width, height)),
+            _ => None,
+        };
+    }
+    let number = |names: &[&str]| field(object, names).and_then(Value::as_f64);
+    let x = number(&["x", "left", "xmin"])?;
+    let y = number(&["y", "top", "ymin"])?;
+    let width = number(&["width", "w"]).or_else(|| Some(number(&["xmax", "right"])? - x))?;
+    let height = number(&["height", "h"]).or_else(|| Some(number(&["ymax", "bottom"])? - y))?;
+    Some((x, y, width, height))
+}
+
+const LABEL_FIELDS: &[&str] = &["label", "class", "category", "type"];
+const TEXT_FIELDS: &[&str] = &["text", "value", "title", "content"];
+
+/// Reads the boxes a labeling tool exported as JSON: a list of boxes, or an object with them
+/// under `boxes` or `annotations`, each with a label, an optional text and its bounds as
+/// `bbox: [x, y, width, height]`, `x`/`y`/`width`/`height` or `xmin`/`ymin`/`xmax`/`ymax`.
+/// COCO files, whose boxes name a category by `category_id` and keep their text in
+/// `attributes`, are read too; of several images, the boxes of `image_name` are taken.
+pub fn parse_json(contents: &str, image_name: &str) -> Result<Vec<Annotation>, String> {
+    let document: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    let categories: Vec<(i64, String)> = document
+        .get("categories")
+        .and_then(Value::as_array)
+        .map(|categories| categories.iter().filter_map(|category| Some((category.get("id")?.as_i64()?, category.get("name")?.as_str()?.to_string()))).collect())
+        .unwrap_or_default();
+    let images = document.get("images").and_then(Value::as_array);
+    let image_id = images.and_then(|images| match images.len() {
+        1 => images[0].get("id"),
+        _ => images.iter().find(|image| image.get("file_name").and_then(Value::as_str).is_some_and(|name| name.rsplit('/').next() == Some(image_name)))?.get("id"),
+    });
+    let boxes = match &document {
+        Value::Array(boxes) => boxes,
+        _ => field(&document, &["boxes", "annotations"]).and_then(Value::as_array).ok_or("expected a list of boxes, or one under `boxes` or `annotations`")?,
+    };
+    let mut annotations = Vec::new();
+    for (index, object) in boxes.iter().enumerate() {
+        if image_id.is_some() && object.get("image_id").is_some_and(|id| Some(id) != image_id) {
+            continue;
+        }
+        let category = object.get("category_id").and_then(Value::as_i64).and_then(|id| categories.iter().find(|(category, _)| *category == id));
+        let label = match category {
+            Some((_, name)) => name.clone(),
+            None => field(object, LABEL_FIELDS).and_then(text).ok_or_else(|| format!("box {} has no label", index + 1))?,
+        };
+        let content = field(object, TEXT_FIELDS).or_else(|| object.get("attributes").and_then(|attributes| field(attributes, TEXT_FIELDS))).and_then(text);
+        let (x, y, width, height) = bounds(object).ok_or_else(|| format!("box {} ({}) has no bounds", index + 1, label))?;
+        annotations.push(Annotation { label, text: content, x, y, width, height });
+    }
+    Ok(annotations)
+}
+
+// The fields of a CSV line; quoted fields may hold commas and `""` for a quote
+fn csv_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().expect("never empty").push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().expect("never empty").push(ch),
+        }
+    }
+    fields.iter().map(|field| field.trim().to_string()).collect()
+}
+
+/// Reads the boxes a labeling tool exported as CSV. The first line names the columns, the way
+/// the JSON fields are named, e.g. `label,x,y,width,height,text` or Pascal VOC's
+/// `filename,width,height,class,xmin,ymin,xmax,ymax`, whose `width` and `height` are the
+/// image's. Rows of other files than `image_name` are skipped.
+pub fn parse_csv(contents: &str, image_name: &str) -> Result<Vec<Annotation>, String> {
+    let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+    let header = csv_fields(lines.next().ok_or("the CSV is empty")?);
+    let has = |name: &str| header.iter().any(|column| column.eq_ignore_ascii_case(name));
+    // With corners given, `width` and `height` are the image's
+    let corners = has("xmax") && has("ymax");
+    let mut annotations = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let fields = csv_fields(line);
+        let mut object = serde_json::Map::new();
+        for (column, value) in header.iter().zip(fields) {
+            let column = column.to_lowercase();
+            if corners && (column == "width" || column == "height") {
+                continue;
+            }
+            let value = match value.parse::<f64>() {
+                Ok(number) if !TEXT_FIELDS.contains(&column.as_str()) => serde_json::json!(number),
+                _ => Value::String(value),
+            };
+            object.insert(column, value);
+        }
+        let object = Value::Object(object);
+        if object.get("filename").and_then(Value::as_str).is_some_and(|name| !name.is_empty() && name.rsplit('/').next() != Some(image_name)) {
+            continue;
+        }
+        let label = field(&object, LABEL_FIELDS).and_then(text).ok_or_else(|| format!("row {} has no label", index + 2))?;
+        let content = field(&object, TEXT_FIELDS).and_then(text).filter(|text| !text.is_empty());
+        let (x, y, width, height) = bounds(&object).ok_or_else(|| format!("row {} ({}) has no bounds", index + 2, label))?;
+        annotations.push(Annotation { label, text: content, x, y, width, height });
+    }
+    Ok(annotations)
+}
+
+/// The boxes of the text the screenshot at `path` shows, read with OCR (see
+/// [`crate::input::ocr`]).
+#[cfg(feature = "ocr")]
+pub fn read_text(path: &std::path::Path) -> Result<Vec<Annotation>, String> {
+    crate::input::ocr::annotate(path)
+}
+
+/// The boxes of the text the screenshot at `path` shows, which takes the `ocr` feature.
+#[cfg(not(feature = "ocr"))]
+pub fn read_text(_: &std::path::Path) -> Result<Vec<Annotation>, String> {
+    Err("--screenshot needs --annotations, or swiftui-synth built with the ocr feature to read the screenshot's text itself".to_string())
+}
+
+/// The spec element a box labeled `label` is, e.g. `button` for `Button` or `btn`.
+fn element_key(label: &str) -> Option<&'static str> {
+    match label.to_lowercase().as_str() {
+        "text" | "label" | "title" | "heading" | "textview" => Some("title"),
+        "button" | "btn" => Some("button"),
+        "image" | "img" | "icon" | "logo" | "picture" | "imageview" => Some("Image"),
+        _ => None,
+    }
+}
+
+/// Converts a screenshot of `pixels` and its boxes to a spec of one screen, named after the
+/// screenshot's file `name`. The pixels are points times `scale`; without one the scale is
+/// that of the device, at @3x, @2x or @1x, whose screen the screenshot's size is, and 1
+/// otherwise. The device's safe area is the screen's.
+pub fn import(pixels: (u32, u32), annotations: &[Annotation], name: &str, scale: Option<f64>) -> Result<Import, String> {
+    let (width, height) = (f64::from(pixels.0), f64::from(pixels.1));
+    let device = [3.0, 2.0, 1.0].into_iter().find_map(|scale| Some((scale, device_safe_area(width / scale, height / scale)?)));
+    let (scale, insets) = match (scale, device) {
+        (Some(scale), _) => (scale, device_safe_area(width / scale, height / scale).unwrap_or_default()),
+        (None, Some(device)) => device,
+        (None, None) => (1.0, Insets::default()),
+    };
+    if !(scale.is_finite() && scale > 0.0) {
+        return Err(format!("the scale must be more than 0, not {}", scale));
+    }
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+    let screen = screen_name(stem.strip_suffix("View").unwrap_or(stem));
+    let mut elements = Vec::new();
+    let mut warnings = Vec::new();
+    // Points are rounded to tenths, finer than labeling tools place a box
+    let point = |pixels: f64| (pixels / scale * 10.0).round() / 10.0;
+    for annotation in annotations {
+        let Some(key) = element_key(&annotation.label) else {
+            warnings.push(format!("{}: skipped the '{}' box at ({}, {}), which has no spec element", screen, annotation.label, annotation.x, annotation.y));
+            continue;
+        };
+        let bounds = (point(annotation.x), point(annotation.y), point(annotation.width), point(annotation.height));
+        elements.push(Element::new(key, annotation.text.as_deref(), bounds));
+    }
+    if elements.is_empty() {
+        return Err("found no text, button or image boxes".to_string());
+    }
+    Ok(Import { spec: write_screen(&screen, vec![Example { width: point(width), height: point(height), insets, elements }]), warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn annotation(label: &str, text: Option<&str>, (x, y, width, height): (f64, f64, f64, f64)) -> Annotation {
+        Annotation { label: label.to_string(), text: text.map(str::to_string), x, y, width, height }
+    }
+
+    #[test]
+    fn test_json_formats() {
+        let list = r#"[{"label": "Text", "text": "Welcome", "bbox": [60, 300, 1059, 100]},
+                       {"class": "button", "x": 60, "y": 2280, "width": 1059, "height": 132, "attributes": {"text": "Sign in"}}]"#;
+        let expected = vec![annotation("Text", Some("Welcome"), (60.0, 300.0, 1059.0, 100.0)), annotation("button", Some("Sign in"), (60.0, 2280.0, 1059.0, 132.0))];
+        assert_eq!(parse_json(list, "login.png").unwrap(), expected);
+
+        let coco = r#"{"images": [{"id": 1, "file_name": "home.png"}, {"id": 2, "file_name": "shots/login.png"}],
+                       "categories": [{"id": 1, "name": "Text"}, {"id": 2, "name": "button"}],
+                       "annotations": [{"image_id": 1, "category_id": 1, "bbox": [0, 0, 10, 10]},
+                                       {"image_id": 2, "category_id": 1, "bbox": [60, 300, 1059, 100], "attributes": {"text": "Welcome"}},
+                                       {"image_id": 2, "category_id": 2, "bbox": [60, 2280, 1059, 132], "attributes": {"text": "Sign in"}}]}"#;
+        assert_eq!(parse_json(coco, "login.png").unwrap(), expected);
+        assert_eq!(parse_json(r#"[{"label": "button"}]"#, "x.png").unwrap_err(), "box 1 (button) has no bounds");
+        assert_eq!(parse_json(r#"{"shapes": []}"#, "x.png").unwrap_err(), "expected a list of boxes, or one under `boxes` or `annotations`");
+    }
+
+    #[test]
+    fn test_csv_formats() {
+        let csv = "label,x,y,width,height,text\nText,60,300,1059,100,\"Hello, \"\"you\"\"\"\nimage,440,600,300,300,\n";
+        assert_eq!(
+            parse_csv(csv, "login.png").unwrap(),
+            vec![annotation("Text", Some("Hello, \"you\""), (60.0, 300.0, 1059.0, 100.0)), annotation("image", None, (440.0, 600.0, 300.0, 300.0))]
+        );
+        let voc = "filename,width,height,class,xmin,ymin,xmax,ymax\nlogin.png,1179,2556,button,60,2280,1119,2412\nhome.png,1179,2556,button,0,0,10,10\n";
+        assert_eq!(parse_csv(voc, "login.png").unwrap(), vec![annotation("button", None, (60.0, 2280.0, 1059.0, 132.0))]);
+        assert_eq!(parse_csv("label,x,y\nbutton,1,2\n", "x.png").unwrap_err(), "row 2 (button) has no bounds");
+    }
+
+    #[test]
+    fn test_import_finds_the_device_and_scale() {
+        let annotations = vec![
+            annotation("button", Some("Sign in"), (60.0, 2280.0, 1059.0, 132.0)),
+            annotation("Text", Some("Welcome"), (60.0, 300.0, 1059.0, 100.0)),
+            annotation("checkbox", None, (60.0, 500.0, 60.0, 60.0)),
+        ];
+        // An iPhone 15 at @3x
+        let import = import((1179, 2556), &annotations, "login.png", None).unwrap();
+        assert_eq!(import.spec, "Login:{(width:393,height:852,safe_top:59,safe_bottom:34):{title:\"Welcome\"@(x:20,y:100,w:353),button:\"Sign in\"@(x:20,y:760,w:353,h:44)}}");
+        assert_eq!(import.warnings, vec!["Login: skipped the 'checkbox' box at (60, 500), which has no spec element"]);
+
+        let import = super::import((1000, 500), &annotations[..1], "Card View.jpg", Some(2.0)).unwrap();
+        assert_eq!(import.spec, "Card:{(width:500,height:250):{button:\"Sign in\"@(x:30,y:1140,w:529.5,h:66)}}");
+        assert!(super::import((100, 100), &annotations[2..], "x.png", None).unwrap_err().contains("no text, button or image"));
+    }
+
+    #[test]
+    fn test_image_size() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&1179u32.to_be_bytes());
+        png.extend_from_slice(&2556u32.to_be_bytes());
+        assert_eq!(image_size(&png).unwrap(), (1179, 2556));
+        // A JPEG with an APP0 segment before its baseline frame
+        let jpeg = [0xff, 0xd8, 0xff, 0xe0, 0, 4, 0, 0, 0xff, 0xc0, 0, 11, 8, 0x09, 0xfc, 0x04, 0x9b, 3, 0, 0, 0];
+        assert_eq!(image_size(&jpeg).unwrap(), (1179, 2556));
+        assert_eq!(image_size(b"GIF89a").unwrap_err(), "not a PNG or JPEG image");
+    }
+}
@@ -0,0 +1,166 @@
+// File: src/input/import.rs
+// What the importers share: they convert a file of another tool to spec text, whose screens
+// state where each view is, and go through synthesis as a written spec does.
+use crate::layout::devices::{self, Insets, Orientation};
+
+/// A spec converted from another tool's file, and what it could not convert.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Import {
+    pub spec: String,
+    pub warnings: Vec<String>,
+}
+
+/// A view converted to an element of the spec, at its position on the screen in points.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Element {
+    pub key: &'static str,
+    /// Quoted and escaped, or `*` when the view has no content.
+    pub value: String,
+    pub x: f64,
+    pub y: f64,
+    /// `None` when the file does not say.
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+}
+
+impl Element {
+    /// An element of kind `key` showing `content`, if it shows any.
+    pub fn new(key: &'static str, content: Option<&str>, (x, y, width, height): (f64, f64, f64, f64)) -> Element {
+        // A view without content is one of its kind, whatever it shows
+        let value = content.filter(|content| !content.is_empty()).map_or("*".to_string(), quote);
+        Element { key, value, x, y, width: Some(width), height: Some(height) }
+    }
+
+    fn write(&self) -> String {
+        let mut frame = format!("x:{},y:{}", self.x, self.y);
+        if let Some(width) = self.width {
+            frame.push_str(&format!(",w:{}", width));
+        }
+        // A label's height is the lines its text takes, which synthesis measures itself
+        if let Some(height) = self.height.filter(|_| self.key != "title") {
+            frame.push_str(&format!(",h:{}", height));
+        }
+        format!("{}:{}@({})", self.key, self.value, frame)
+    }
+}
+
+/// `value` as a quoted spec string, with the escapes the spec parser reads.
+pub(crate) fn quote(value: &str) -> String {
+    let mut quoted = String::from("\"");
+    for ch in value.chars() {
+        match ch {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ => quoted.push(ch),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// The safe area of the device whose screen, held either way, is `width` by `height` points.
+pub(crate) fn device_safe_area(width: f64, height: f64) -> Option<Insets> {
+    devices::DEVICES
+        .iter()
+        .flat_map(|device| [Orientation::Portrait, Orientation::Landscape].map(|orientation| (device, orientation)))
+        .find(|(device, orientation)| device.size(*orientation) == (width, height))
+        .map(|(device, orientation)| device.safe_area(orientation))
+}
+
+/// `name` as a screen name: a Swift identifier that starts with a capital letter.
+pub(crate) fn screen_name(name: &str) -> String {
+    let words: String = name
+        .split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word[..1].to_uppercase() + &word[1..])
+        .collect();
+    match words.starts_with(|ch: char| ch.is_ascii_alphabetic()) {
+        true => words,
+        false => format!("Screen{}", words),
+    }
+}
+
+/// A screen of `width` by `height` points and the elements on it.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Example {
+    pub width: f64,
+    pub height: f64,
+    pub insets: Insets,
+    pub elements: Vec<Element>,
+}
+
+/// `example` written as the spec writes one, with its elements in reading order, top to
+/// bottom and then leading to trailing.
+pub(crate) fn write_example(mut example: Example) -> String {
+    let mut size = format!("width:{},height:{}", example.width, example.height);
+    let insets = example.insets;
+    for (key, inset) in [("safe_top", insets.top), ("safe_bottom", insets.bottom), ("safe_leading", insets.leading), ("safe_trailing", insets.trailing)] {
+        if inset > 0.0 {
+            size.push_str(&format!(",{}:{}", key, inset));
+        }
+    }
+    example.elements.sort_by(|a, b| a.y.total_cmp(&b.y).then(a.x.total_cmp(&b.x)));
+    let elements: Vec<String> = example.elements.iter().map(Element::write).collect();
+    format!("{{({}):{{{}}}}}", size, elements.join(","))
+}
+
+/// The screen `name` with `examples`, written as the spec writes a named screen.
+pub(crate) fn write_screen(name: &str, examples: Vec<Example>) -> String {
+    let examples: Vec<String> = examples.into_iter().map(write_example).collect();
+    format!("{}:{}", name, examples.join(""))
+}
+
+/// Whether a design layer named `name` is a button, as designers name them, e.g.
+/// `Button/Primary` or `btn-login`.
+pub(crate) fn is_button_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("button") || name.split(|ch: char| !ch.is_ascii_alphanumeric()).any(|word| word == "btn")
+}
+
+/// Writes artboards as screens, each artboard an example. Artboards of one screen at several
+/// sizes are named alike up to a ` - `, ` – ` or `/` (`Login - iPad`, `Login/iPhone`), and
+/// become the examples of one screen, named for what precedes it.
+pub(crate) fn write_artboards(artboards: Vec<(String, Example)>) -> String {
+    let mut screens: Vec<(String, Vec<Example>)> = Vec::new();
+    for (name, example) in artboards {
+        let stem = [" - ", " – ", " — ", "/"].iter().filter_map(|separator| name.split_once(separator)).map(|(stem, _)| stem).min_by_key(|stem| stem.len());
+        let name = screen_name(stem.unwrap_or(&name));
+        match screens.iter_mut().find(|(screen, _)| *screen == name) {
+            Some((_, examples)) => examples.push(example),
+            None => screens.push((name, vec![example])),
+        }
+    }
+    screens.into_iter().map(|(name, examples)| write_screen(&name, examples)).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_artboards_of_a_screen_are_its_examples() {
+        let example = |width: f64, height: f64| Example {
+            width,
+            height,
+            insets: device_safe_area(width, height).unwrap_or_default(),
+            elements: vec![Element { width: None, ..Element::new("title", Some("Hi \"you\""), (20.0, 100.0, 0.0, 30.0)) }],
+        };
+        let artboards = vec![
+            ("Login - iPhone 15".to_string(), example(393.0, 852.0)),
+            ("home".to_string(), example(320.0, 480.0)),
+            ("Login/iPad".to_string(), example(820.0, 1180.0)),
+        ];
+        assert_eq!(
+            write_artboards(artboards),
+            concat!(
+                "Login:{(width:393,height:852,safe_top:59,safe_bottom:34):{title:\"Hi \\\"you\\\"\"@(x:20,y:100)}}",
+                "{(width:820,height:1180,safe_top:24,safe_bottom:20):{title:\"Hi \\\"you\\\"\"@(x:20,y:100)}}\n",
+                "Home:{(width:320,height:480):{title:\"Hi \\\"you\\\"\"@(x:20,y:100)}}"
+            )
+        );
+        assert!(is_button_name("Button/Primary") && is_button_name("btn-login") && !is_button_name("Subtitle"));
+    }
+}
@@ -0,0 +1,103 @@
+// File: src/input/lexer.rs
+// Lexical clean-up of example specs before parsing.
+
+/// Blanks out `//` and `#` comments, which run to the end of their line, outside quoted
+/// values. Every byte of a comment becomes a space, so offsets into the result are offsets
+/// into `input` and diagnostics point at the text the user wrote.
+pub fn strip_comments(input: &str) -> String {
+    let mut output = String::with_capacity(input.len());
+    let (mut in_quotes, mut escaped, mut in_comment) = (false, false, false);
+    let mut chars = input.char_indices().peekable();
+    while let Some((_, ch)) = chars.next() {
+        if in_comment {
+            if ch == '\n' {
+                in_comment = false;
+                output.push(ch);
+            } else {
+                output.extend(std::iter::repeat_n(' ', ch.len_utf8()));
+            }
+            continue;
+        }
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '#' if !in_quotes => in_comment = true,
+            '/' if !in_quotes && chars.peek().is_some_and(|&(_, next)| next == '/') => in_comment = true,
+            _ => {}
+        }
+        if in_comment {
+            output.push(' ');
+        } else {
+            output.push(ch);
+        }
+    }
+    output
+}
+
+/// Resolves the escapes in the text of a quoted value, which are Swift's: `\"`, `\\`, `\'`,
+/// `\n`, `\t`, `\r`, `\0` and `\u{...}` with one to eight hex digits naming a Unicode scalar.
+/// An invalid escape is returned with the text it spans, for the diagnostic.
+pub fn unescape(raw: &str) -> Result<String, (String, &str)> {
+    let mut value = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(at) = rest.find('\\') {
+        value.push_str(&rest[..at]);
+        let escape = &rest[at..];
+        let Some(ch) = escape[1..].chars().next() else {
+            return Err(("Incomplete escape sequence at the end of the value".to_string(), escape));
+        };
+        let mut len = 1 + ch.len_utf8();
+        match ch {
+            '"' | '\\' | '\'' => value.push(ch),
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            'r' => value.push('\r'),
+            '0' => value.push('\0'),
+            'u' => {
+                let digits = escape[2..].strip_prefix('{').and_then(|hex| hex.find('}').map(|end| &hex[..end]));
+                let Some(digits) = digits else {
+                    return Err(("Unicode escape must be written as \\u{...}".to_string(), &escape[..2]));
+                };
+                len = 4 + digits.len();
+                let scalar = match digits.len() {
+                    1..=8 => u32::from_str_radix(digits, 16).ok().and_then(char::from_u32),
+                    _ => None,
+                };
+                let Some(scalar) = scalar else {
+                    return Err((format!("Invalid Unicode scalar '{}' in escape", digits), &escape[..len]));
+                };
+                value.push(scalar);
+            }
+            _ => return Err((format!("Unknown escape sequence '\\{}'", ch), &escape[..len])),
+        }
+        rest = &escape[len..];
+    }
+    value.push_str(rest);
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_comments_become_spaces() {
+        let input = "# Login screen\n{(width:390,height:844): // iPhone\n  {title:\"Hi // there #1\"}} # é";
+        let stripped = strip_comments(input);
+        assert_eq!(stripped.len(), input.len());
+        let lines: Vec<&str> = stripped.lines().map(str::trim_end).collect();
+        assert_eq!(lines, vec!["", "{(width:390,height:844):", "  {title:\"Hi // there #1\"}}"]);
+        assert_eq!(strip_comments("{title:\"a \\\" # b\"}"), "{title:\"a \\\" # b\"}");
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(unescape(r#"Say \"hi\"\n\tC:\\ \u{1F44B} \u{E9}"#).unwrap(), "Say \"hi\"\n\tC:\\ 👋 é");
+        assert_eq!(unescape("héllo").unwrap(), "héllo");
+        assert_eq!(unescape(r"a\d"), Err(("Unknown escape sequence '\\d'".to_string(), r"\d")));
+        assert_eq!(unescape(r"\u{D800}!").unwrap_err().1, r"\u{D800}");
+        assert_eq!(unescape(r"\u00e9").unwrap_err().1, r"\u");
+        assert!(unescape("a\\").is_err());
+    }
+}
@@ -0,0 +1,236 @@
+// File: src/input/minimize.rs
+// Spec minimization (`minimize`): delta debugging over a spec's screens, examples, elements
+// and element frames, keeping only what is needed to reproduce a behavior such as failing
+// synthesis, so the spec left over can be filed with a bug report or read at a glance.
+use std::fmt;
+
+use crate::input::lexer::strip_comments;
+use crate::input::parser::{split_elements, split_examples, split_frame};
+use crate::input::preprocess;
+
+/// One `{(dimensions):{elements}}` block, as text.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExampleText {
+    /// The dimensions, parentheses included.
+    pub dims: String,
+    /// Whether the elements are the `HStack:{...}` form's children.
+    pub hstack: bool,
+    pub elements: Vec<String>,
+}
+
+/// A screen of a spec: its name, if named, and its examples.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScreenText {
+    pub name: Option<String>,
+    pub examples: Vec<ExampleText>,
+}
+
+/// A spec split into the parts the minimizer may drop. Comments are gone and `let` bindings
+/// and `@include`s expanded, so the spec written back stands on its own.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpecText {
+    pub screens: Vec<ScreenText>,
+    /// The `{...}` block after `flows:`, if any.
+    pub flows: Option<String>,
+}
+
+impl SpecText {
+    /// Splits a spec that parses; the spec is not checked here beyond its blocks.
+    pub fn parse(input: &str) -> Result<SpecText, String> {
+        let expansion = preprocess::expand(&strip_comments(input)).map_err(|diagnostic| diagnostic.message)?;
+        let mut spec = SpecText { screens: Vec::new(), flows: None };
+        let mut in_flows = false;
+        for block in split_examples(expansion.text.trim()) {
+            if !block.starts_with('{') {
+                let name = block.strip_suffix(':').ok_or_else(|| format!("expected a screen name, found '{}'", block))?.trim();
+                in_flows = name == "flows";
+                if !in_flows {
+                    spec.screens.push(ScreenText { name: Some(name.to_string()), examples: Vec::new() });
+                }
+            } else if in_flows {
+                spec.flows = Some(block.to_string());
+                in_flows = false;
+            } else {
+                if spec.screens.is_empty() {
+                    spec.screens.push(ScreenText { name: None, examples: Vec::new() });
+                }
+                spec.screens.last_mut().expect("a screen was just added").examples.push(ExampleText::parse(block)?);
+            }
+        }
+        Ok(spec)
+    }
+
+    /// Number of examples, over every screen.
+    pub fn examples(&self) -> usize {
+        self.screens.iter().map(|screen| screen.examples.len()).sum()
+    }
+
+    /// Number of elements, over every example.
+    pub fn elements(&self) -> usize {
+        self.screens.iter().flat_map(|screen| &screen.examples).map(|example| example.elements.len()).sum()
+    }
+}
+
+impl ExampleText {
+    fn parse(block: &str) -> Result<ExampleText, String> {
+        let inner = block[1..block.len() - 1].trim();
+        let (dims, elements) = inner
+            .find(')')
+            .and_then(|end| Some((&inner[..=end], inner[end + 1..].trim_start().strip_prefix(':')?.trim())))
+            .ok_or_else(|| format!("expected (dimensions):{{elements}} in '{}'", block))?;
+        let (hstack, elements) = match elements.strip_prefix("HStack:") {
+            Some(children) => (true, children.trim()),
+            None => (false, elements),
+        };
+        let elements = elements.strip_prefix('{').and_then(|e| e.strip_suffix('}')).ok_or_else(|| format!("expected {{elements}} in '{}'", block))?;
+        Ok(ExampleText { dims: dims.to_string(), hstack, elements: split_elements(elements).into_iter().map(str::to_string).collect() })
+    }
+}
+
+impl fmt::Display for ExampleText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hstack = if self.hstack { "HStack:" } else { "" };
+        write!(f, "{{{}:{}{{{}}}}}", self.dims, hstack, self.elements.join(", "))
+    }
+}
+
+impl fmt::Display for SpecText {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for screen in &self.screens {
+            if let Some(name) = &screen.name {
+                writeln!(f, "{}:", name)?;
+            }
+            for example in &screen.examples {
+                writeln!(f, "{}", example)?;
+            }
+        }
+        if let Some(flows) = &self.flows {
+            writeln!(f, "flows:{}", flows)?;
+        }
+        Ok(())
+    }
+}
+
+// Delta debugging (ddmin) by complements: drops ever smaller chunks of `items` while the
+// rest still `reproduces`, down to single items; keeps at least `min` items
+fn ddmin<T: Clone>(mut items: Vec<T>, min: usize, mut reproduces: impl FnMut(&[T]) -> bool) -> Vec<T> {
+    let mut chunks = 2;
+    while items.len() > min {
+        let size = items.len().div_ceil(chunks);
+        let reduced = (0..items.len()).step_by(size).find_map(|start| {
+            let rest: Vec<T> = items[..start].iter().chain(&items[(start + size).min(items.len())..]).cloned().collect();
+            (rest.len() >= min && reproduces(&rest)).then_some(rest)
+        });
+        match reduced {
+            Some(rest) => {
+                items = rest;
+                chunks = (chunks - 1).max(2);
+            }
+            None if size == 1 => break,
+            None => chunks = (chunks * 2).min(items.len()),
+        }
+    }
+    items
+}
+
+/// The smallest spec found that still `reproduces`, and the number of specs tried. Screens,
+/// then each screen's examples, then each example's elements are reduced by delta debugging,
+/// then frames are dropped from elements one at a time, over and over until nothing more can
+/// go. Every screen keeps an example and every example an element. `reproduces` is given spec
+/// text and should be false for text that does not parse.
+pub fn minimize(spec: &SpecText, mut reproduces: impl FnMut(&str) -> bool) -> (SpecText, usize) {
+    let mut tries = 0;
+    let mut test = |spec: &SpecText| {
+        tries += 1;
+        reproduces(&spec.to_string())
+    };
+    let mut spec = spec.clone();
+    loop {
+        let before = spec.clone();
+        if spec.flows.is_some() {
+            let trial = SpecText { flows: None, ..spec.clone() };
+            if test(&trial) {
+                spec = trial;
+            }
+        }
+        let mut trial = spec.clone();
+        spec.screens = ddmin(spec.screens.clone(), 1, |screens| {
+            trial.screens = screens.to_vec();
+            test(&trial)
+        });
+        for s in 0..spec.screens.len() {
+            let mut trial = spec.clone();
+            spec.screens[s].examples = ddmin(spec.screens[s].examples.clone(), 1, |examples| {
+                trial.screens[s].examples = examples.to_vec();
+                test(&trial)
+            });
+            for e in 0..spec.screens[s].examples.len() {
+                let mut trial = spec.clone();
+                spec.screens[s].examples[e].elements = ddmin(spec.screens[s].examples[e].elements.clone(), 1, |elements| {
+                    trial.screens[s].examples[e].elements = elements.to_vec();
+                    test(&trial)
+                });
+                for i in 0..spec.screens[s].examples[e].elements.len() {
+                    let (element, frame) = split_frame(&spec.screens[s].examples[e].elements[i]);
+                    if frame.is_some() {
+                        let mut trial = spec.clone();
+                        trial.screens[s].examples[e].elements[i] = element.to_string();
+                        if test(&trial) {
+                            spec = trial;
+                        }
+                    }
+                }
+            }
+        }
+        if spec == before {
+            return (spec, tries);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_specs_split_and_write_back() {
+        let spec = SpecText::parse(
+            "// Sign in\nLogin:{(width:390,height:844):{title:\"Hi\"@(y:20), button:\"Go, now\"}}\n\
+             Home:{(width:390,height:844):HStack:{\"A\",\"Spacer\"}} flows:{Login.button -> Home}",
+        )
+        .unwrap();
+        assert_eq!((spec.screens.len(), spec.examples(), spec.elements()), (2, 2, 4));
+        assert_eq!(spec.screens[0].examples[0].elements, ["title:\"Hi\"@(y:20)", "button:\"Go, now\""]);
+        assert_eq!(
+            spec.to_string(),
+            "Login:\n{(width:390,height:844):{title:\"Hi\"@(y:20), button:\"Go, now\"}}\n\
+             Home:\n{(width:390,height:844):HStack:{\"A\", \"Spacer\"}}\nflows:{Login.button -> Home}\n"
+        );
+        assert_eq!(SpecText::parse(&spec.to_string()).unwrap(), spec);
+    }
+
+    #[test]
+    fn test_ddmin_finds_a_minimal_subset() {
+        let items: Vec<u32> = (0..16).collect();
+        let mut tries = 0;
+        let kept = ddmin(items, 1, |rest| {
+            tries += 1;
+            rest.contains(&3) && rest.contains(&11)
+        });
+        assert_eq!(kept, [3, 11]);
+        assert!(tries < 40, "{}", tries);
+    }
+
+    #[test]
+    fn test_minimize_keeps_what_reproduces() {
+        let spec = SpecText::parse(
+            "{(width:390,height:844):{title:\"Hi\"@(x:16), button:\"Go\", Image:\"logo\"}}\n\
+             {(width:320,height:568):{title:\"Hi\", button:\"Go\"@(y:500), Image:\"logo\"}}",
+        )
+        .unwrap();
+        // Reproduces whenever a button is at y:500
+        let (minimal, tries) = minimize(&spec, |text| text.contains("button:\"Go\"@(y:500)"));
+        assert_eq!(minimal.to_string(), "{(width:320,height:568):{button:\"Go\"@(y:500)}}\n");
+        assert!(tries > 0);
+    }
+}
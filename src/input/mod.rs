@@ -1 +1,13 @@
+pub mod lexer;
+pub mod preprocess;
 pub mod parser;
+pub mod validate;
+pub mod swift;
+pub mod import;
+pub mod storyboard;
+pub mod sketch;
+pub mod xd;
+pub mod annotations;
+pub mod minimize;
+#[cfg(feature = "ocr")]
+pub mod ocr;
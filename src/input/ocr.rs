@@ -0,0 +1,222 @@
+// File: src/input/ocr.rs
+// Reads a raw screenshot's text with Tesseract (`--screenshot` without `--annotations`, with
+// the `ocr` feature), so it can be synthesized without labeling it first. Each line of text
+// becomes a box, labeled a button when it sits on a filled shape of its own color and a text
+// otherwise; the boxes are then imported as those of a labeling tool are.
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tiny_skia::Pixmap;
+
+use crate::input::annotations::Annotation;
+use crate::utils::process::on_path;
+
+/// A word Tesseract read, with its bounds in pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Word {
+    /// The block, paragraph and line the word is in.
+    pub line: (u32, u32, u32),
+    pub text: String,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Words read with less confidence than this, in percent, are noise such as icons.
+pub const MIN_CONFIDENCE: f64 = 40.0;
+
+/// The Tesseract command-line tool.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tesseract {
+    pub command: PathBuf,
+}
+
+impl Tesseract {
+    /// Finds `tesseract` at `$TESSERACT` or on the `PATH`.
+    pub fn find() -> Option<Tesseract> {
+        let command = std::env::var_os("TESSERACT").map(PathBuf::from).or_else(|| on_path("tesseract"))?;
+        Some(Tesseract { command })
+    }
+
+    /// The words of the image at `path`.
+    pub fn words(&self, path: &Path) -> Result<Vec<Word>, String> {
+        let output = Command::new(&self.command)
+            .arg(path)
+            .args(["stdout", "tsv"])
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", self.command.display(), e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("{} failed: {}", self.command.display(), stderr.trim()));
+        }
+        parse_tsv(&String::from_utf8_lossy(&output.stdout))
+    }
+}
+
+/// Reads Tesseract's TSV output: a header line, then a row per page, block, paragraph, line and
+/// word, of which the confident words are kept.
+pub fn parse_tsv(tsv: &str) -> Result<Vec<Word>, String> {
+    let mut lines = tsv.lines();
+    let header: Vec<&str> = lines.next().ok_or("Tesseract wrote no output")?.split('\t').collect();
+    let column = |name: &str| header.iter().position(|column| *column == name).ok_or_else(|| format!("Tesseract's output has no {} column", name));
+    let [block, par, line, left, top, width, height, conf, text] =
+        ["block_num", "par_num", "line_num", "left", "top", "width", "height", "conf", "text"].map(column);
+    let (block, par, line, left, top, width, height, conf, text) = (block?, par?, line?, left?, top?, width?, height?, conf?, text?);
+    let mut words = Vec::new();
+    for row in lines {
+        let fields: Vec<&str> = row.split('\t').collect();
+        let number = |index: usize| fields.get(index).and_then(|field| field.trim().parse::<f64>().ok());
+        let content = fields.get(text).map_or("", |text| text.trim());
+        if content.is_empty() || number(conf).is_none_or(|conf| conf < MIN_CONFIDENCE) {
+            continue;
+        }
+        let index = |index: usize| number(index).unwrap_or(0.0) as u32;
+        words.push(Word {
+            line: (index(block), index(par), index(line)),
+            text: content.to_string(),
+            x: number(left).unwrap_or(0.0),
+            y: number(top).unwrap_or(0.0),
+            width: number(width).unwrap_or(0.0),
+            height: number(height).unwrap_or(0.0),
+        });
+    }
+    Ok(words)
+}
+
+/// The lines of `words`, as texts spanning their words, in the order Tesseract read them.
+pub fn lines(words: &[Word]) -> Vec<Annotation> {
+    let mut lines: Vec<((u32, u32, u32), Annotation)> = Vec::new();
+    for word in words {
+        match lines.iter_mut().find(|(line, _)| *line == word.line) {
+            Some((_, line)) => {
+                let (right, bottom) = ((line.x + line.width).max(word.x + word.width), (line.y + line.height).max(word.y + word.height));
+                line.x = line.x.min(word.x);
+                line.y = line.y.min(word.y);
+                line.width = right - line.x;
+                line.height = bottom - line.y;
+                let text = line.text.get_or_insert_with(String::new);
+                text.push(' ');
+                text.push_str(&word.text);
+            }
+            None => {
+                let text = Annotation { label: "text".to_string(), text: Some(word.text.clone()), x: word.x, y: word.y, width: word.width, height: word.height };
+                lines.push((word.line, text));
+            }
+        }
+    }
+    lines.into_iter().map(|(_, line)| line).collect()
+}
+
+type Rgb = [u8; 3];
+
+fn color(pixmap: &Pixmap, x: f64, y: f64) -> Option<Rgb> {
+    if x < 0.0 || y < 0.0 {
+        return None;
+    }
+    let pixel = pixmap.pixel(x as u32, y as u32)?.demultiply();
+    Some([pixel.red(), pixel.green(), pixel.blue()])
+}
+
+fn close(a: Rgb, b: Rgb) -> bool {
+    a.iter().zip(b).map(|(a, b)| a.abs_diff(b) as u32).sum::<u32>() <= 48
+}
+
+// The color all but a few of the pixels `margin` outside the box share, if there is one; those
+// past the screen's edge are left out
+fn ring_color(pixmap: &Pixmap, line: &Annotation, margin: f64) -> Option<Rgb> {
+    let (left, top, right, bottom) = (line.x - margin, line.y - margin, line.x + line.width + margin, line.y + line.height + margin);
+    let mut samples = Vec::new();
+    for step in 0..=8 {
+        let fraction = f64::from(step) / 8.0;
+        let (x, y) = (left + (right - left) * fraction, top + (bottom - top) * fraction);
+        samples.extend([color(pixmap, x, top), color(pixmap, x, bottom), color(pixmap, left, y), color(pixmap, right, y)]);
+    }
+    let samples: Vec<Rgb> = samples.into_iter().flatten().collect();
+    let shared = samples.iter().copied().max_by_key(|sample| samples.iter().filter(|other| close(*sample, **other)).count())?;
+    let count = samples.iter().filter(|sample| close(shared, **sample)).count();
+    (count * 10 >= samples.len() * 9).then_some(shared)
+}
+
+// How far from (`x`, `y`) pixels of `fill` reach by steps of (`dx`, `dy`)
+fn reach(pixmap: &Pixmap, fill: Rgb, (x, y): (f64, f64), (dx, dy): (f64, f64)) -> f64 {
+    let mut distance = 0.0;
+    while color(pixmap, x + dx * (distance + 1.0), y + dy * (distance + 1.0)).is_some_and(|pixel| close(pixel, fill)) {
+        distance += 1.0;
+    }
+    distance
+}
+
+/// Labels as a button each line that sits on a filled shape: a color of its own all around it
+/// that the screen further out does not have. The button's box is the shape's.
+pub fn find_buttons(lines: Vec<Annotation>, pixmap: &Pixmap) -> Vec<Annotation> {
+    lines
+        .into_iter()
+        .map(|line| {
+            let margin = (line.height / 2.0).max(4.0);
+            let Some(fill) = ring_color(pixmap, &line, margin) else { return line };
+            if ring_color(pixmap, &line, margin * 4.0).is_some_and(|outside| close(outside, fill)) {
+                return line;
+            }
+            let (middle_x, middle_y) = (line.x + line.width / 2.0, line.y + line.height / 2.0);
+            let left = line.x - reach(pixmap, fill, (line.x, middle_y), (-1.0, 0.0));
+            let right = line.x + line.width + reach(pixmap, fill, (line.x + line.width - 1.0, middle_y), (1.0, 0.0));
+            let top = line.y - reach(pixmap, fill, (middle_x, line.y), (0.0, -1.0));
+            let bottom = line.y + line.height + reach(pixmap, fill, (middle_x, line.y + line.height - 1.0), (0.0, 1.0));
+            Annotation { label: "button".to_string(), x: left, y: top, width: right - left, height: bottom - top, ..line }
+        })
+        .collect()
+}
+
+/// The boxes of the screenshot at `path`: its lines of text, with those on buttons labeled so.
+/// Only PNG screenshots are looked at for buttons; in a JPEG every line is a text.
+pub fn annotate(path: &Path) -> Result<Vec<Annotation>, String> {
+    let tesseract = Tesseract::find().ok_or("Reading a screenshot's text needs tesseract on the PATH (or $TESSERACT)")?;
+    let lines = lines(&tesseract.words(path)?);
+    let bytes = std::fs::read(path).map_err(|e| format!("Failed to read screenshot '{}': {}", path.display(), e))?;
+    Ok(match Pixmap::decode_png(&bytes) {
+        Ok(pixmap) => find_buttons(lines, &pixmap),
+        Err(_) => lines,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiny_skia::{Color, Paint, Rect, Transform};
+
+    const TSV: &str = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext
+1\t1\t0\t0\t0\t0\t0\t0\t400\t800\t-1\t
+5\t1\t1\t1\t1\t1\t40\t100\t120\t30\t96.5\tWelcome
+5\t1\t1\t1\t1\t2\t170\t102\t80\t28\t95.1\tback
+5\t1\t1\t1\t2\t1\t40\t150\t20\t20\t12.0\t@
+5\t1\t2\t1\t1\t1\t170\t700\t60\t20\t91.0\tSign
+5\t1\t2\t1\t1\t2\t240\t700\t30\t20\t90.0\tin";
+
+    #[test]
+    fn test_tsv_words_make_lines() {
+        let words = parse_tsv(TSV).unwrap();
+        assert_eq!(words.len(), 4);
+        let lines = lines(&words);
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0], Annotation { label: "text".to_string(), text: Some("Welcome back".to_string()), x: 40.0, y: 100.0, width: 210.0, height: 30.0 });
+        assert_eq!((lines[1].text.as_deref(), lines[1].x, lines[1].width), (Some("Sign in"), 170.0, 100.0));
+        assert!(parse_tsv("level\ttext\n").unwrap_err().contains("no block_num column"));
+    }
+
+    #[test]
+    fn test_lines_on_filled_shapes_are_buttons() {
+        let mut pixmap = Pixmap::new(400, 800).unwrap();
+        pixmap.fill(Color::WHITE);
+        let mut paint = Paint::default();
+        paint.set_color_rgba8(0, 122, 255, 255);
+        pixmap.fill_rect(Rect::from_xywh(20.0, 680.0, 360.0, 60.0).unwrap(), &paint, Transform::identity(), None);
+        let found = find_buttons(lines(&parse_tsv(TSV).unwrap()), &pixmap);
+        assert_eq!(found[0].label, "text");
+        assert_eq!((found[1].label.as_str(), found[1].x, found[1].y, found[1].width, found[1].height), ("button", 20.0, 680.0, 360.0, 60.0));
+
+        // A line on a panel of its own color is still a text
+        pixmap.fill(Color::from_rgba8(0, 122, 255, 255));
+        assert_eq!(find_buttons(lines(&parse_tsv(TSV).unwrap()), &pixmap)[1].label, "text");
+    }
+}
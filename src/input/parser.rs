@@ -1,231 +1,1289 @@
 // File: src/input/parser.rs
-use crate::ast::Value;
+use std::io::BufRead;
 
-pub fn parse_examples(input: &str) -> Result<Vec<(Value, Value)>, String> {
+use crate::ast::ir::background::{Background, MATERIALS};
+use crate::ast::ir::modifier::{Modifier, MODIFIER_KEYS};
+use crate::ast::{Example, Screen, Value, IR};
+use crate::components::ComponentLibrary;
+use crate::diagnostics::{self, Diagnostic, Severity, Span};
+use crate::input::preprocess::{self, Expansion};
+use crate::input::{lexer, validate};
+use crate::layout::devices::{self, Orientation};
+use crate::platform::Platform;
+use crate::layout::{DynamicType, LayoutDirection};
+use crate::output::render;
+use crate::synthesis::flow::{self, Flow};
+use crate::synthesis::refine::{self, Constraint};
+use crate::synthesis::verify;
+
+/// Extra context for parsing.
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    /// Registered components; their names are accepted as element keys.
+    pub components: ComponentLibrary,
+    /// Reject unknown element keys and unquoted values instead of accepting them with a
+    /// warning (see [`validate`]).
+    pub strict: bool,
+    /// Platform the layout is for; device presets of other platforms are rejected.
+    pub platform: Platform,
+}
+
+pub fn parse_examples(input: &str) -> Result<Vec<Example>, Diagnostic> {
+    parse_examples_with(input, &ParseOptions::default())
+}
+
+/// Parses one or more examples. Several examples of the same screen (typically at
+/// different sizes) are written as consecutive `{...}` blocks, optionally separated
+/// by commas or newlines. `//` and `#` comments are ignored, and `let` bindings and
+/// `@include` directives expanded (see [`preprocess::expand`]). Only the first problem
+/// found is returned ([`parse_examples_all`] returns them all), and warnings are dropped;
+/// use [`parse_spec_with`] to see them.
+pub fn parse_examples_with(input: &str, options: &ParseOptions) -> Result<Vec<Example>, Diagnostic> {
+    parse_examples_all(input, options).map_err(|mut errors| errors.remove(0))
+}
+
+/// Parses examples as [`parse_examples_with`] does, but carries on past a problem to report
+/// every one it finds, in the order they appear in the input.
+pub fn parse_examples_all(input: &str, options: &ParseOptions) -> Result<Vec<Example>, Vec<Diagnostic>> {
+    // Comments are blanked in place, so spans into the clean text are spans into the input
+    let expansion = preprocess::expand(&lexer::strip_comments(input)).map_err(|diagnostic| vec![diagnostic])?;
+    let mut diagnostics = Vec::new();
+    let examples = parse_expanded_examples(&expansion.text, options, &mut diagnostics);
+    settle(examples, diagnostics, &expansion).map(|(examples, _)| examples)
+}
+
+// Settles a parse that carried on past errors, pointing every diagnostic back at the input:
+// the result and its warnings if there were no errors, otherwise the errors in input order.
+fn settle<T>(
+    result: Result<T, Diagnostic>,
+    diagnostics: Vec<Diagnostic>,
+    expansion: &Expansion,
+) -> Result<(T, Vec<Diagnostic>), Vec<Diagnostic>> {
+    let (value, fatal) = match result {
+        Ok(value) => (Some(value), None),
+        Err(diagnostic) => (None, Some(diagnostic)),
+    };
+    let (mut errors, warnings): (Vec<_>, Vec<_>) = diagnostics
+        .into_iter()
+        .chain(fatal)
+        .map(|diagnostic| expansion.restore(diagnostic))
+        .partition(|diagnostic| diagnostic.severity == Severity::Error);
+    match value {
+        Some(value) if errors.is_empty() => Ok((value, warnings)),
+        _ => {
+            errors.sort_by_key(|diagnostic| diagnostic.span.map(|span| span.start));
+            Err(errors)
+        }
+    }
+}
+
+// Parses the examples of `input`. A problem with one example is added to `diagnostics` and
+// parsing carries on with the next; only input that is not a list of examples at all is fatal.
+fn parse_expanded_examples(input: &str, options: &ParseOptions, diagnostics: &mut Vec<Diagnostic>) -> Result<Vec<Example>, Diagnostic> {
     let trimmed = input.trim();
+    let err = |code, message: String, part: &str| Diagnostic::error(code, message).with_span(Span::of(input, part));
+
     if !trimmed.starts_with('{') || !trimmed.ends_with('}') {
-        return Err("Input must be enclosed in curly braces, e.g., {example}".to_string());
+        return Err(err("E0001", "Input must be enclosed in curly braces, e.g., {example}".to_string(), trimmed)
+            .with_suggestion("wrap the example in braces: {(width:390,height:844):{title:\"Hello\"}}"));
+    }
+
+    let mut examples = Vec::new();
+    for block in split_examples(trimmed) {
+        if !block.starts_with('{') {
+            diagnostics.push(
+                err("E0017", format!("Unexpected text between examples: '{}'", block), block)
+                    .with_suggestion("each example must be a separate {...} block"),
+            );
+            continue;
+        }
+        match parse_example(input, block, options, diagnostics) {
+            Ok(example) => examples.push(example),
+            Err(diagnostic) => diagnostics.push(diagnostic),
+        }
     }
+    Ok(examples)
+}
+
+/// Parses examples from `reader` one at a time; see [`parse_examples_streaming_with`].
+pub fn parse_examples_streaming<R: BufRead>(reader: R) -> ExampleStream<R> {
+    parse_examples_streaming_with(reader, &ParseOptions::default())
+}
+
+/// Parses examples from `reader` as [`parse_examples_with`] parses them from a string, for
+/// batch-generated specs too large to load whole: only the example being read is held, and
+/// each is yielded as soon as its closing brace is, so work on it can start before the rest
+/// of the input is read. A problem with an example is yielded in its place and reading
+/// carries on with the next; spans are byte offsets into the whole input. Comments are
+/// skipped, but `let` bindings, `@include`s, screen names and `flows:` need the whole spec
+/// and are reported as text between examples (E0017); use [`parse_spec_with`] for those.
+/// A read error, or input that is not UTF-8, is yielded as error E0031 and ends the stream.
+///
+/// This is a library API only: the CLI synthesizes one layout from all of a screen's
+/// examples together, so `--examples-file` and stdin are still read and parsed whole.
+pub fn parse_examples_streaming_with<R: BufRead>(reader: R, options: &ParseOptions) -> ExampleStream<R> {
+    let scanner = Scanner {
+        options: options.clone(),
+        offset: 0,
+        block: Vec::new(),
+        block_start: None,
+        stray: Vec::new(),
+        stray_start: 0,
+        depth: 0,
+        in_quotes: false,
+        escaped: false,
+        in_comment: false,
+        slash: false,
+        done: false,
+    };
+    ExampleStream { reader, scanner }
+}
+
+/// Iterator over the examples of a reader; see [`parse_examples_streaming_with`].
+pub struct ExampleStream<R> {
+    reader: R,
+    scanner: Scanner,
+}
+
+// What the stream has read so far, kept apart from the reader so bytes it lends can be read
+struct Scanner {
+    options: ParseOptions,
+    /// Offset in the input of the next byte read.
+    offset: usize,
+    /// The example being read, comments blanked, and where the last one started.
+    block: Vec<u8>,
+    block_start: Option<usize>,
+    /// Text between examples since the last one, and where it starts.
+    stray: Vec<u8>,
+    stray_start: usize,
+    depth: usize,
+    in_quotes: bool,
+    escaped: bool,
+    in_comment: bool,
+    /// The last byte was a `/` outside quotes, which starts a comment if another follows.
+    slash: bool,
+    done: bool,
+}
+
+impl Scanner {
+    // Takes in the byte at `offset`, returning what it completes: an example, or the text
+    // between examples that a `{` ends
+    fn push(&mut self, byte: u8, offset: usize) -> Option<Result<Example, Diagnostic>> {
+        if self.in_comment {
+            if byte == b'\n' {
+                self.in_comment = false;
+            }
+            // Blanked like `lexer::strip_comments` does, so offsets still match the input
+            return self.take(if byte == b'\n' { byte } else { b' ' }, offset);
+        }
+        if std::mem::take(&mut self.slash) {
+            if byte == b'/' {
+                self.in_comment = true;
+                self.take(b' ', offset - 1);
+                return self.take(b' ', offset);
+            }
+            self.take(b'/', offset - 1);
+        }
+        if !self.in_quotes && !self.escaped {
+            match byte {
+                b'#' => {
+                    self.in_comment = true;
+                    return self.take(b' ', offset);
+                }
+                b'/' => {
+                    self.slash = true;
+                    return None;
+                }
+                _ => {}
+            }
+        }
+        self.take(byte, offset)
+    }
+
+    // Adds a byte with comments blanked to the example or the text between examples
+    fn take(&mut self, byte: u8, offset: usize) -> Option<Result<Example, Diagnostic>> {
+        if self.depth == 0 && byte != b'{' {
+            // Between examples: only separators are allowed
+            if self.stray.is_empty() && (byte == b',' || byte.is_ascii_whitespace()) {
+                return None;
+            }
+            if self.stray.is_empty() {
+                self.stray_start = offset;
+            }
+            self.stray.push(byte);
+            return None;
+        }
+        let mut stray = None;
+        match byte {
+            _ if self.escaped => self.escaped = false,
+            b'\\' if self.in_quotes => self.escaped = true,
+            b'"' => self.in_quotes = !self.in_quotes,
+            b'{' if !self.in_quotes => {
+                if self.depth == 0 {
+                    stray = self.stray();
+                    self.block_start = Some(offset);
+                }
+                self.depth += 1;
+            }
+            b'}' if !self.in_quotes => {
+                self.block.push(byte);
+                self.depth -= 1;
+                return (self.depth == 0).then(|| self.example());
+            }
+            _ => {}
+        }
+        self.block.push(byte);
+        stray
+    }
+
+    // The text between examples read since the last one, as a problem
+    fn stray(&mut self) -> Option<Result<Example, Diagnostic>> {
+        let stray = std::mem::take(&mut self.stray);
+        let text = String::from_utf8_lossy(&stray);
+        let text = text.trim_end();
+        (!text.is_empty()).then(|| {
+            Err(Diagnostic::error("E0017", format!("Unexpected text between examples: '{}'", text))
+                .with_span(Span::new(self.stray_start, self.stray_start + text.len()))
+                .with_suggestion("each example must be a separate {...} block"))
+        })
+    }
+
+    // Parses the example just read, pointing its diagnostics at the input
+    fn example(&mut self) -> Result<Example, Diagnostic> {
+        let start = self.block_start.unwrap_or_default();
+        let block = String::from_utf8(std::mem::take(&mut self.block)).map_err(|e| {
+            self.done = true;
+            let invalid = start + e.utf8_error().valid_up_to();
+            Diagnostic::error("E0031", "Spec is not valid UTF-8").with_span(Span::new(invalid, invalid + 1))
+        })?;
+        let mut diagnostics = Vec::new();
+        let example = parse_example(&block, &block, &self.options, &mut diagnostics);
+        let shift = |mut diagnostic: Diagnostic| {
+            diagnostic.span = diagnostic.span.map(|span| Span::new(span.start + start, span.end + start));
+            diagnostic
+        };
+        // As in `settle`: the first error in the example, warnings dropped
+        let mut errors: Vec<Diagnostic> =
+            diagnostics.into_iter().chain(example.as_ref().err().cloned()).filter(|diagnostic| diagnostic.severity == Severity::Error).collect();
+        errors.sort_by_key(|diagnostic| diagnostic.span.map(|span| span.start));
+        match errors.into_iter().next() {
+            Some(error) => Err(shift(error)),
+            None => example,
+        }
+    }
+
+    // What is left once the input ends: text after the last example, or one left open
+    fn finish(&mut self) -> Option<Result<Example, Diagnostic>> {
+        self.done = true;
+        if std::mem::take(&mut self.slash) {
+            self.take(b'/', self.offset - 1);
+        }
+        if self.depth > 0 {
+            let start = self.block_start.unwrap_or_default();
+            let end = start + self.block.len();
+            return Some(Err(Diagnostic::error("E0001", "Example is not closed: the input ends inside its braces")
+                .with_span(Span::new(start, end))
+                .with_suggestion("add the missing '}' at the end of the example")));
+        }
+        if self.block_start.is_none() && self.stray.is_empty() {
+            return Some(Err(Diagnostic::error("E0002", "Input must contain at least one example")));
+        }
+        self.stray()
+    }
+}
+
+impl<R: BufRead> Iterator for ExampleStream<R> {
+    type Item = Result<Example, Diagnostic>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let scanner = &mut self.scanner;
+        while !scanner.done {
+            let buffer = match self.reader.fill_buf() {
+                Ok(buffer) => buffer,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    scanner.done = true;
+                    return Some(Err(Diagnostic::error("E0031", format!("Failed to read the spec: {}", e))));
+                }
+            };
+            if buffer.is_empty() {
+                return scanner.finish();
+            }
+            let mut item = None;
+            let mut read = 0;
+            while item.is_none() && read < buffer.len() {
+                item = scanner.push(buffer[read], scanner.offset);
+                scanner.offset += 1;
+                read += 1;
+            }
+            self.reader.consume(read);
+            if item.is_some() {
+                return item;
+            }
+        }
+        None
+    }
+}
+
+/// A parsed spec: its screens and the navigation between them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spec {
+    pub screens: Vec<Screen>,
+    pub flows: Vec<Flow>,
+    /// Input accepted leniently that `--strict` would reject.
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// Parses a spec of one or more screens. Named screens are written as `Name:` followed by
+/// that screen's examples, e.g. `Login:{...} Home:{...}{...}`, and may be followed by
+/// `flows:{Login.button -> Home, ...}`; input starting with `{` is a single unnamed screen,
+/// parsed as by [`parse_examples_with`]. Parsing carries on past a problem with an example,
+/// element, screen name or flow, so every problem found is reported, in input order.
+pub fn parse_spec_with(input: &str, options: &ParseOptions) -> Result<Spec, Vec<Diagnostic>> {
+    let expansion = preprocess::expand(&lexer::strip_comments(input)).map_err(|diagnostic| vec![diagnostic])?;
+    let mut diagnostics = Vec::new();
+    let spec = parse_expanded_spec(&expansion.text, options, &mut diagnostics);
+    let (spec, warnings) = settle(spec, diagnostics, &expansion)?;
+    Ok(Spec { warnings, ..spec })
+}
+
+fn parse_expanded_spec(input: &str, options: &ParseOptions, diagnostics: &mut Vec<Diagnostic>) -> Result<Spec, Diagnostic> {
+    let trimmed = input.trim();
+    if trimmed.starts_with('{') || trimmed.is_empty() {
+        let screens = vec![Screen { name: None, examples: parse_expanded_examples(input, options, diagnostics)? }];
+        return Ok(Spec { screens, flows: Vec::new(), warnings: Vec::new() });
+    }
+    let err = |message: String, part: &str| Diagnostic::error("E0022", message).with_span(Span::of(input, part));
+
+    let mut screens: Vec<Screen> = Vec::new();
+    let mut flows = Vec::new();
+    let mut flow_parts = Vec::new();
+    // Whether the last screen named has had no example blocks yet, even ones with problems
+    let (mut last_name, mut in_flows, mut awaiting_examples) = ("", false, false);
+    // Screens with an example that had problems
+    let mut broken = Vec::new();
+    for block in split_examples(trimmed) {
+        if block.starts_with('{') {
+            if in_flows {
+                match parse_flow_block(input, block) {
+                    Ok(parsed) => {
+                        for (flow, part) in parsed {
+                            flows.push(flow);
+                            flow_parts.push(part);
+                        }
+                    }
+                    Err(diagnostic) => diagnostics.push(diagnostic),
+                }
+                in_flows = false;
+            } else if let Some(screen) = screens.last_mut() {
+                // The first block is never an example here, since the input does not start with one
+                awaiting_examples = false;
+                let before = diagnostics.len();
+                match parse_example(input, block, options, diagnostics) {
+                    Ok(example) => screen.examples.push(example),
+                    Err(diagnostic) => diagnostics.push(diagnostic),
+                }
+                if diagnostics[before..].iter().any(|diagnostic| diagnostic.severity == Severity::Error) {
+                    broken.push(last_name);
+                }
+            }
+            continue;
+        }
+        if awaiting_examples {
+            diagnostics.push(
+                err(format!("Screen '{}' has no examples", last_name), last_name)
+                    .with_suggestion(format!("follow the name with its examples, e.g. {}:{{(width:390,height:844):{{...}}}}", last_name)),
+            );
+        }
+        let name = block.strip_suffix(':').map(str::trim_end).unwrap_or(block);
+        if let Some((first, _)) = name.split_once(':') {
+            let first = first.trim_end();
+            diagnostics.push(err(format!("Screen '{}' has no examples", first), first));
+            awaiting_examples = false;
+            continue;
+        }
+        if name == "flows" && block.ends_with(':') {
+            (in_flows, awaiting_examples) = (true, false);
+            continue;
+        }
+        let valid = name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !block.ends_with(':') || !valid {
+            diagnostics.push(
+                err(format!("Expected a screen name such as 'Login:', found '{}'", block), block)
+                    .with_suggestion("screen names start with a letter and contain only letters, digits and '_'"),
+            );
+        } else if screens.iter().any(|screen| screen.name.as_deref() == Some(name)) {
+            diagnostics.push(err(format!("Screen '{}' is defined twice", name), name));
+        }
+        // Kept even when misnamed, so the examples that follow are still checked
+        (last_name, awaiting_examples) = (name, true);
+        screens.push(Screen { name: Some(name.to_string()), examples: Vec::new() });
+    }
+    if awaiting_examples {
+        diagnostics.push(err(format!("Screen '{}' has no examples", last_name), last_name));
+    }
+    if in_flows {
+        diagnostics.push(
+            err("'flows:' must be followed by a {...} block of flows".to_string(), trimmed)
+                .with_suggestion("write flows:{Login.button -> Home}"),
+        );
+    }
+    // Flows are checked once every screen is known
+    for (flow, part) in flows.iter().zip(flow_parts) {
+        // Such a screen may well have the button the flow starts from
+        if broken.contains(&flow.from.as_str()) {
+            continue;
+        }
+        if let Err(message) = flow::validate(std::slice::from_ref(flow), &screens) {
+            let names = screens.iter().filter_map(|screen| screen.name.as_deref());
+            let unknown = [&flow.from, &flow.to].into_iter().find(|name| !screens.iter().any(|s| s.name.as_ref() == Some(*name)));
+            let mut diagnostic = Diagnostic::error("E0023", message).with_span(Span::of(input, part));
+            if let Some(help) = unknown.and_then(|name| diagnostics::suggestion_text(&diagnostics::did_you_mean(name, names))) {
+                diagnostic = diagnostic.with_suggestion(help);
+            }
+            diagnostics.push(diagnostic);
+        }
+    }
+    Ok(Spec { screens, flows, warnings: Vec::new() })
+}
+
+// Parses `{Login.button -> Home, ...}` into flows, each with the text it came from.
+fn parse_flow_block<'a>(source: &str, block: &'a str) -> Result<Vec<(Flow, &'a str)>, Diagnostic> {
+    let inner = &block[1..block.len() - 1];
+    let mut parts = Vec::new();
+    let (mut start, mut in_quotes) = (0, false);
+    for (i, ch) in inner.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                parts.push(&inner[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&inner[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.parse() {
+            Ok(flow) => Ok((flow, part)),
+            Err(message) => Err(Diagnostic::error("E0023", message).with_span(Span::of(source, part))),
+        })
+        .collect()
+}
+
+// Splits the input into top-level `{...}` blocks, skipping commas and whitespace
+// between them. Stray text is returned as its own block so the caller can report it.
+// If braces never balance, the whole input is returned as a single block and
+// `parse_example` reports the precise problem.
+pub(crate) fn split_examples(trimmed: &str) -> Vec<&str> {
+    let mut blocks = Vec::new();
+    let mut depth = 0usize;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut block_start = 0;
+    let mut stray_start = None;
+    for (i, ch) in trimmed.char_indices() {
+        if depth == 0 && ch != '{' {
+            // Between blocks: only separators are allowed
+            if ch != ',' && !ch.is_whitespace() && stray_start.is_none() {
+                stray_start = Some(i);
+            }
+            continue;
+        }
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '{' if !in_quotes => {
+                if depth == 0 {
+                    if let Some(stray) = stray_start.take() {
+                        blocks.push(trimmed[stray..i].trim());
+                    }
+                    block_start = i;
+                }
+                depth += 1;
+            }
+            '}' if !in_quotes => {
+                depth -= 1;
+                if depth == 0 {
+                    blocks.push(&trimmed[block_start..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(stray) = stray_start {
+        blocks.push(trimmed[stray..].trim());
+    }
+    if depth != 0 || blocks.is_empty() {
+        return vec![trimmed];
+    }
+    blocks
+}
+
+/// Splits the inside of an example's `{...}` elements at top-level commas, respecting quotes,
+/// frame parentheses and constraint braces. Elements are slices of the input, trimmed, so
+/// diagnostics can point at them; unescaping happens in `parse_element`.
+pub(crate) fn split_elements(elements_inner: &str) -> Vec<&str> {
+    let mut elements = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut depth = 0usize;
+    for (i, ch) in elements_inner.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '(' | '{' | '[' if !in_quotes => depth += 1,
+            ')' | '}' | ']' if !in_quotes => depth = depth.saturating_sub(1),
+            ',' if !in_quotes && depth == 0 => {
+                elements.push(elements_inner[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    elements.push(elements_inner[start..].trim());
+    elements.retain(|element| !element.is_empty());
+    elements
+}
+
+// Parses a single `{(dimensions):elements}` block.
+fn parse_example(input: &str, trimmed: &str, options: &ParseOptions, diagnostics: &mut Vec<Diagnostic>) -> Result<Example, Diagnostic> {
+    let err = |code, message: String, part: &str| Diagnostic::error(code, message).with_span(Span::of(input, part));
 
     // Get content inside outer braces
     let inner = &trimmed[1..trimmed.len() - 1];
     if inner.is_empty() {
-        return Err("Input must contain at least one example".to_string());
+        return Err(err("E0002", "Input must contain at least one example".to_string(), trimmed));
     }
 
     // --- Find the split point between dimensions and elements ---
     let mut depth = 0;
     let mut colon_pos = None;
-    let chars: Vec<_> = inner.chars().collect(); // Collect characters for indexed access
+    let mut chars = inner.char_indices().peekable(); // Byte offsets, so slicing stays on char boundaries
 
-    for (i, &ch) in chars.iter().enumerate() { // Iterate through character indices
+    while let Some((i, ch)) = chars.next() {
         match ch {
             '(' => depth += 1,
             ')' => {
                 if depth == 0 { // Cannot close parenthesis if not inside one
-                     return Err("Mismatched parenthesis in dimensions".to_string());
+                    return Err(err("E0003", "Mismatched parenthesis in dimensions".to_string(), &inner[i..i + 1]));
                 }
                 depth -= 1;
                 if depth == 0 {
                     // Found the closing ')' for dimensions. Now find the ':' after it, skipping whitespace.
-                    let mut next_char_idx = i + 1;
-                    while next_char_idx < chars.len() && chars[next_char_idx].is_whitespace() {
-                        next_char_idx += 1;
-                    }
+                    while chars.next_if(|(_, c)| c.is_whitespace()).is_some() {}
                     // Check if the next non-whitespace char is indeed ':'
-                    if next_char_idx < chars.len() && chars[next_char_idx] == ':' {
-                        colon_pos = Some(next_char_idx); // Store the index of the colon
-                        break; // Found our split point
-                    } else {
+                    match chars.peek() {
+                        Some(&(next, ':')) => {
+                            colon_pos = Some(next); // Store the index of the colon
+                            break; // Found our split point
+                        }
                         // Found ')' but no ':' following it correctly
-                        return Err("Expected ':' after dimensions '(...)', possibly missing or misplaced.".to_string());
+                        _ => {
+                            return Err(err(
+                                "E0004",
+                                "Expected ':' after dimensions '(...)', possibly missing or misplaced.".to_string(),
+                                &inner[i..i + 1],
+                            )
+                            .with_suggestion("separate dimensions and elements with ':', e.g. (width:390,height:844):{...}"))
+                        }
                     }
                 }
             }
-             // Ignore ':' if inside parentheses
+            // Ignore ':' if inside parentheses
             ':' if depth > 0 => {}
             // If we hit a top-level ':' before closing parenthesis, format is wrong
-            ':' if depth == 0 => return Err("Found ':' before dimensions '(..)' were closed or defined.".to_string()),
+            ':' => {
+                return Err(err(
+                    "E0004",
+                    "Found ':' before dimensions '(..)' were closed or defined.".to_string(),
+                    &inner[i..i + 1],
+                ))
+            }
             _ => {} // Other characters
         }
-         // Ensure we don't go below depth 0 outside the check for ')'
-        if depth < 0 {
-             return Err("Mismatched parenthesis in dimensions (extra closing parenthesis?)".to_string());
-        }
     }
-     // Check if parenthesis were left open
+    // Check if parenthesis were left open
     if depth != 0 {
-        return Err("Mismatched parenthesis in dimensions (not closed)".to_string());
+        return Err(err("E0003", "Mismatched parenthesis in dimensions (not closed)".to_string(), inner));
     }
 
-
     // --- Parse Dimensions ---
-    let colon_idx = colon_pos.ok_or("Could not find dimensions-elements separator '):{'")?;
+    let colon_idx = colon_pos.ok_or_else(|| {
+        err("E0004", "Could not find dimensions-elements separator '):{'".to_string(), inner)
+    })?;
     let dims_str = inner[..colon_idx].trim(); // Text before the colon
     let elements_str = inner[colon_idx + 1..].trim(); // Text after the colon
 
     if !dims_str.starts_with('(') || !dims_str.ends_with(')') {
-         return Err("Dimensions part must be enclosed in parentheses, e.g., (width: W, height: H)".to_string());
+        return Err(err(
+            "E0005",
+            "Dimensions part must be enclosed in parentheses, e.g., (width: W, height: H)".to_string(),
+            dims_str,
+        ));
     }
-let dims_inner = dims_str.trim_start_matches('(').trim_end_matches(')').trim();
-// *** FIX: Check for extra parentheses inside the dimensions block ***
-let dims_content = &dims_str[1..dims_str.len()-1];
-if dims_content.contains('(') || dims_content.contains(')') {
-    return Err("Extra or mismatched parentheses within dimensions block.".to_string());
-}
-// *** End FIX ***
-let mut width = None;
+    // Check for extra parentheses inside the dimensions block
+    let dims_content = &dims_str[1..dims_str.len() - 1];
+    if dims_content.contains('(') || dims_content.contains(')') {
+        return Err(err("E0005", "Extra or mismatched parentheses within dimensions block.".to_string(), dims_str));
+    }
+    let mut width = None;
     let mut height = None;
+    let mut device = None;
+    let mut orientation = None;
+    let mut safe_area: [Option<f64>; 4] = [None; 4];
+    let mut dynamic_type = None;
+    let mut direction = None;
+    // Width and height given relative to a device's screen, e.g. `width:"50%"`
+    let mut relative: [Option<&str>; 2] = [None; 2];
+    // Each key with where it was last given; a later value replaces an earlier one
+    let mut given: Vec<(&str, &str)> = Vec::new();
 
-    for part in dims_inner.split(',') {
+    for part in dims_content.split(',') {
         let part = part.trim();
         if part.is_empty() { continue; } // Allow trailing comma
-        let mut kv = part.splitn(2,':'); // Use splitn to handle potential ':' in values if ever needed
-        let key = kv.next().ok_or_else(|| format!("Missing dimension key in part: '{}'", part))?.trim();
-        let value = kv.next().ok_or_else(|| format!("Missing dimension value for key '{}'", key))?.trim();
+        let mut kv = part.splitn(2, ':'); // Use splitn to handle potential ':' in values if ever needed
+        let key = kv.next().unwrap_or_default().trim();
+        let value = kv
+            .next()
+            .ok_or_else(|| {
+                err("E0006", format!("Missing dimension value for key '{}'", key), part)
+                    .with_suggestion("dimensions are written as key:value pairs, e.g. (width:390,height:844)")
+            })?
+            .trim();
+        if DIMENSION_KEYS.contains(&key) {
+            if let Some(at) = given.iter().position(|(given, _)| *given == key) {
+                let (_, earlier) = given.remove(at);
+                diagnostics.push(
+                    Diagnostic::warning("W0008", format!("Dimension '{}' is given again later; this value is ignored", key))
+                        .with_span(Span::of(input, earlier))
+                        .with_suggestion(format!("give '{}' once", key)),
+                );
+            }
+            given.push((key, part));
+        }
 
+        // `?` leaves the dimension unknown, see `layout::screen_for`
+        let parse_dimension = |name: &str| match value {
+            "?" => Ok(Value::Any),
+            _ => parse_length(value).map(Value::Float).map_err(|e| err("E0007", format!("Invalid {} value '{}': {}", name, value, e), value)),
+        };
         match key {
-            "width" => width = Some(value.parse::<i32>().map_err(|e| format!("Invalid width value '{}': {}", value, e))?),
-            "height" => height = Some(value.parse::<i32>().map_err(|e| format!("Invalid height value '{}': {}", value, e))?),
-            _ => return Err(format!("Unsupported dimension key: '{}'", key)),
+            "width" | "height" if value.starts_with('"') => relative[usize::from(key == "height")] = Some(value),
+            "width" => width = Some(parse_dimension("width")?),
+            "height" => height = Some(parse_dimension("height")?),
+            "device" => {
+                let Some(name) = value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) else {
+                    return Err(err("E0024", format!("Device name must be enclosed in double quotes: got '{}'", value), value)
+                        .with_suggestion(format!("write it as device:\"{}\"", value.trim_matches('"'))));
+                };
+                let Some(found) = devices::find(name) else {
+                    let mut diagnostic = err("E0024", format!("Unknown device '{}'", name), value);
+                    let names = devices::DEVICES.iter().map(|device| device.name);
+                    match diagnostics::suggestion_text(&diagnostics::did_you_mean(name, names)) {
+                        Some(help) => diagnostic = diagnostic.with_suggestion(help),
+                        None => diagnostic = diagnostic.with_suggestion("or give the screen size as (width:W,height:H)"),
+                    }
+                    return Err(diagnostic);
+                };
+                if found.platform != options.platform {
+                    let others = devices::DEVICES.iter().filter(|device| device.platform == options.platform).map(|device| device.name);
+                    return Err(err(
+                        "E0024",
+                        format!("'{}' is a {} device, but the layout is for {}", found.name, found.platform, options.platform),
+                        value,
+                    )
+                    .with_suggestion(format!("pass --platform {}, or name a {} device: {}", found.platform, options.platform, others.collect::<Vec<_>>().join(", "))));
+                }
+                device = Some(found);
+            }
+            "orientation" => orientation = Some((value.parse::<Orientation>().map_err(|e| err("E0024", e, value))?, part)),
+            "dynamicType" => {
+                let size = value.parse::<DynamicType>().map_err(|e| {
+                    let diagnostic = err("E0025", e, value);
+                    match diagnostics::suggestion_text(&diagnostics::did_you_mean(value, DynamicType::names())) {
+                        Some(help) => diagnostic.with_suggestion(help),
+                        None => diagnostic,
+                    }
+                })?;
+                dynamic_type = Some(size);
+            }
+            "layoutDirection" => direction = Some(value.parse::<LayoutDirection>().map_err(|e| err("E0026", e, value))?),
+            "safe_top" | "safe_bottom" | "safe_leading" | "safe_trailing" => {
+                let edge = SAFE_AREA_EDGES.iter().position(|edge| key.ends_with(edge)).unwrap_or_default();
+                let inset = parse_length(value).map_err(|e| err("E0007", format!("Invalid {} value '{}': {}", key, value, e), value))?;
+                safe_area[edge] = Some(inset);
+            }
+            _ => {
+                let mut diagnostic = err("E0008", format!("Unsupported dimension key: '{}'", key), key);
+                match diagnostics::suggestion_text(&diagnostics::did_you_mean(key, DIMENSION_KEYS.iter().copied())) {
+                    Some(help) => diagnostic = diagnostic.with_suggestion(help),
+                    None => {
+                        let keys: Vec<String> = DIMENSION_KEYS.iter().map(|key| format!("`{}`", key)).collect();
+                        diagnostic = diagnostic.with_suggestion(format!("supported dimension keys are {}", keys.join(", ")));
+                    }
+                }
+                return Err(diagnostic);
+            }
+        }
+    }
+
+    // A device preset stands for its screen size in the given orientation, with its safe area
+    let mut preset = Vec::new();
+    let mut insets = None;
+    if let Some(device) = device {
+        if width.is_some() || height.is_some() {
+            return Err(err("E0024", "Give either a device or width and height, not both".to_string(), dims_str));
         }
+        let orientation = orientation.map(|(orientation, _)| orientation).unwrap_or_default();
+        let (w, h) = device.size(orientation);
+        let resolve = |relative: Option<&str>, axis, length: f64| match relative {
+            Some(value) => resolve_relative(unquote(value), axis, [Some(w), Some(h)]).map_err(|e| err("E0007", e, value)),
+            None => Ok(length),
+        };
+        (width, height) = (Some(Value::Float(resolve(relative[0], "width", w)?)), Some(Value::Float(resolve(relative[1], "height", h)?)));
+        let device_insets = device.safe_area(orientation);
+        insets = Some([device_insets.top, device_insets.bottom, device_insets.leading, device_insets.trailing]);
+        preset.push(("device".to_string(), Value::String(device.name.to_string())));
+        preset.push(("orientation".to_string(), Value::String(orientation.to_string())));
+    } else if let Some((_, part)) = orientation {
+        return Err(err("E0024", "An orientation needs a device preset, e.g. (device:\"iPhone 15\", orientation:landscape)".to_string(), part));
+    } else if let Some(value) = relative.iter().flatten().next() {
+        return Err(err("E0007", format!("Relative dimension {} needs a device preset to be relative to", value), value)
+            .with_suggestion("name the device, e.g. (device:\"iPad Pro 13-inch\", width:\"50%\")"));
+    }
+    // Insets stated outright override the device's
+    if safe_area.iter().any(Option::is_some) {
+        let base = insets.unwrap_or_default();
+        insets = Some(std::array::from_fn(|edge| safe_area[edge].unwrap_or(base[edge])));
+    }
+    if let Some(insets) = insets {
+        let edges = SAFE_AREA_EDGES.iter().zip(insets).map(|(edge, inset)| (edge.to_string(), Value::Float(inset))).collect();
+        preset.push(("safe_area".to_string(), Value::Dict(edges)));
     }
 
-    let width = width.ok_or("Missing width dimension")?;
-    let height = height.ok_or("Missing height dimension")?;
+    let width = width.ok_or_else(|| err("E0009", "Missing width dimension".to_string(), dims_str))?;
+    let height = height.ok_or_else(|| err("E0009", "Missing height dimension".to_string(), dims_str))?;
+    // Frames may be stated relative to the screen
+    let screen = [&width, &height].map(Value::as_f64);
+    let mut dims = vec![("width".to_string(), width), ("height".to_string(), height)];
+    dims.extend(preset);
+    dims.extend(dynamic_type.map(|size| ("dynamic_type".to_string(), Value::String(size.to_string()))));
+    if direction == Some(LayoutDirection::RightToLeft) {
+        dims.push(("layout_direction".to_string(), Value::String("rtl".to_string())));
+    }
 
     // --- Parse Elements ---
-    let elements_str = elements_str.trim();
-
     // Handle HStack case specifically
-    if elements_str.starts_with("HStack:") {
-        let hstack_inner = elements_str["HStack:".len()..].trim();
+    if let Some(hstack_inner) = elements_str.strip_prefix("HStack:") {
+        let hstack_inner = hstack_inner.trim();
         if !hstack_inner.starts_with('{') || !hstack_inner.ends_with('}') {
-            return Err(format!("HStack elements must be enclosed in braces: '{}'", hstack_inner));
+            return Err(err("E0010", format!("HStack elements must be enclosed in braces: '{}'", hstack_inner), hstack_inner));
         }
         let hstack_children_str = &hstack_inner[1..hstack_inner.len() - 1];
         let mut hstack_children = Vec::new();
         // Simple comma split for HStack children for now
         for elem in hstack_children_str.split(',') {
             let elem = elem.trim();
-             if elem.is_empty() { continue; }
-             // Ensure HStack children are quoted strings
-            if !elem.starts_with('"') || !elem.ends_with('"') {
-                 return Err(format!("HStack child value must be quoted: {}", elem));
+            if elem.is_empty() { continue; }
+            // Ensure HStack children are quoted strings
+            if elem.len() < 2 || !elem.starts_with('"') || !elem.ends_with('"') {
+                return Err(err("E0011", format!("HStack child value must be quoted: {}", elem), elem)
+                    .with_suggestion(format!("write it as \"{}\"", elem.trim_matches('"'))));
             }
-            let value = elem[1..elem.len()-1].to_string(); // Remove quotes
+            let value = unescape(input, &elem[1..elem.len() - 1])?; // Remove quotes
             hstack_children.push((format!("child{}", hstack_children.len()), Value::String(value)));
         }
         let example = (
-            Value::Dict(vec![
-                ("width".to_string(), Value::Int(width)),
-                ("height".to_string(), Value::Int(height)),
-            ]),
+            Value::Dict(dims),
             Value::Dict(vec![("HStack".to_string(), Value::Dict(hstack_children))]),
         );
-        return Ok(vec![example]);
+        return Ok(example);
     }
 
     // Handle regular {key: "value", ...} case
     if !elements_str.starts_with('{') || !elements_str.ends_with('}') {
-        return Err(format!("Elements must be enclosed in braces: '{}'", elements_str));
+        return Err(err("E0016", format!("Elements must be enclosed in braces: '{}'", elements_str), elements_str));
     }
 
-    let elements_inner = &elements_str[1..elements_str.len() - 1].trim(); // Trim inner whitespace too
-    let mut elements = Vec::new();
+    let elements_inner = elements_str[1..elements_str.len() - 1].trim(); // Trim inner whitespace too
+    let mut parsed = ParsedElements::default();
+    for elem in split_elements(elements_inner) {
+        if let Err(diagnostic) = parse_element(input, elem, options, screen, &mut parsed, diagnostics) {
+            diagnostics.push(diagnostic);
+        }
+    }
 
-    // Robust comma splitting respecting quotes
-    let mut current = String::new();
-    let mut in_quotes = false;
-    let mut escaped = false;
+    // A constraint on a view the example lacks holds trivially, see `Constraint::check`
+    for (constraint, span) in &parsed.mentions {
+        for target in constraint.targets() {
+            if !parsed.elements.iter().any(|(key, value)| target.names_element(key, value)) {
+                diagnostics.push(
+                    Diagnostic::warning("W0008", format!("The example has no {}, so '{}' is ignored", target, constraint))
+                        .with_span(*span)
+                        .with_suggestion("add the element, or name one the example has"),
+                );
+            }
+        }
+    }
+
+    // Stated frames are keyed by the index of their element
+    if !parsed.frames.is_empty() {
+        dims.push(("frames".to_string(), Value::Dict(parsed.frames)));
+    }
+    // Stored where `--reject` feedback goes, so both are checked the same way
+    if !parsed.constraints.is_empty() {
+        dims.push(("constraints".to_string(), Value::Dict(parsed.constraints)));
+    }
+    if let Some(background) = parsed.background {
+        dims.push(("background".to_string(), background));
+    }
+    let example = (Value::Dict(dims), Value::Dict(parsed.elements));
+
+    Ok(example)
+}
 
-    for ch in elements_inner.chars() {
+/// Keys accepted in an example's dimensions.
+pub const DIMENSION_KEYS: &[&str] = &[
+    "width",
+    "height",
+    "device",
+    "orientation",
+    "safe_top",
+    "safe_bottom",
+    "safe_leading",
+    "safe_trailing",
+    "dynamicType",
+    "layoutDirection",
+];
+
+// Edges of the safe area, in the order `layout::safe_area` reads them from the `safe_area` dims.
+const SAFE_AREA_EDGES: [&str; 4] = ["top", "bottom", "leading", "trailing"];
+
+/// Element keys accepted in the `{key:"value", ...}` form.
+pub const SUPPORTED_ELEMENT_KEYS: &[&str] = &["title", "button", "Image", "Custom", "hint", "constraints", "background"];
+
+/// Keys accepted in an element's frame, `@(x:..,y:..,w:..,h:..)`.
+pub const FRAME_KEYS: &[&str] = &["x", "y", "w", "h", "font", "aspect", "cornerRadius", "border", "shadow", "overlay", "animation", "transition", "onTap", "onLongPress", "onSwipe"];
+
+// Splits `"value"@(frame)` (or `*@(frame)`, `[1, 2]@(frame)`) into the value and the frame
+// text, if there is one.
+pub(crate) fn split_frame(value: &str) -> (&str, Option<&str>) {
+    let (mut in_quotes, mut escaped, mut depth) = (false, false, 0usize);
+    for (i, ch) in value.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '[' if !in_quotes => depth += 1,
+            ']' if !in_quotes => depth = depth.saturating_sub(1),
+            '@' if !in_quotes && depth == 0 => return (value[..i].trim_end(), Some(value[i + 1..].trim_start())),
+            _ => {}
+        }
+    }
+    (value, None)
+}
+
+// Splits the inside of an array at its top-level commas. A trailing comma is allowed.
+fn split_items(inner: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let (mut start, mut in_quotes, mut escaped, mut depth) = (0, false, false, 0usize);
+    for (i, ch) in inner.char_indices() {
         match ch {
-            '\\' if !escaped => escaped = true,
-            '"' if !escaped => {
-                in_quotes = !in_quotes;
-                current.push(ch);
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '[' if !in_quotes => depth += 1,
+            ']' if !in_quotes => depth = depth.saturating_sub(1),
+            ',' if !in_quotes && depth == 0 => {
+                items.push(inner[start..i].trim());
+                start = i + 1;
             }
-            ',' if !in_quotes => {
-                let elem = current.trim();
-                if !elem.is_empty() {
-                    parse_element(elem, &mut elements)?;
-                }
-                current.clear(); // Clear after processing
+            _ => {}
+        }
+    }
+    let last = inner[start..].trim();
+    if !last.is_empty() {
+        items.push(last);
+    }
+    items
+}
+
+// Parses a value given to a component or custom view that is not a plain string: a number
+// (`4`, `4.5`), `true` or `false`, or an array of literals such as `["a", "b"]`. `None` if
+// `text` is none of these.
+fn parse_literal(source: &str, text: &str) -> Result<Option<Value>, Diagnostic> {
+    if let Some(inner) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+        let mut items = Vec::new();
+        for item in split_items(inner) {
+            let value = match item.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+                Some(raw) => Some(Value::String(unescape(source, raw)?)),
+                None => parse_literal(source, item)?,
+            };
+            let Some(value) = value else {
+                return Err(Diagnostic::error("E0029", format!("Invalid array item '{}'", item))
+                    .with_span(Span::of(source, item))
+                    .with_suggestion("array items are quoted strings, numbers, true, false or arrays"));
+            };
+            items.push(value);
+        }
+        return Ok(Some(Value::Array(items)));
+    }
+    Ok(match text {
+        "true" => Some(Value::Bool(true)),
+        "false" => Some(Value::Bool(false)),
+        _ => match text.parse::<i32>() {
+            Ok(n) => Some(Value::Int(n)),
+            Err(_) => text.parse::<f64>().ok().filter(|x| x.is_finite() && text.starts_with(|c: char| c.is_ascii_digit() || c == '-')).map(Value::Float),
+        },
+    })
+}
+
+// Parses a length in points, such as `390` or `393.5`.
+fn parse_length(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(length) if length.is_finite() && length >= 0.0 => Ok(length),
+        Ok(_) => Err("expected a finite number of points, at least 0".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+// Strips the double quotes around a relative length such as `"80%"`.
+fn unquote(value: &str) -> &str {
+    value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value).trim()
+}
+
+// Resolves a length relative to the `[width, height]` of the screen, such as `80%` or
+// `0.5*height`, to points. A bare percentage is of `axis`, the dimension the length runs along.
+fn resolve_relative(value: &str, axis: &str, screen: [Option<f64>; 2]) -> Result<f64, String> {
+    let invalid = || {
+        format!("Invalid relative value '{}': expected a percentage such as \"80%\" or a fraction of the screen such as \"0.5*height\"", value)
+    };
+    let number = |text: &str| text.trim().parse::<f64>().ok().filter(|n| n.is_finite()).ok_or_else(invalid);
+    let (fraction, dimension) = match value.strip_suffix('%') {
+        Some(percent) => (number(percent)? / 100.0, axis),
+        None => {
+            let (a, b) = value.split_once('*').unwrap_or(("1", value));
+            let (factor, dimension) = if matches!(b.trim(), "width" | "height") { (a, b) } else { (b, a) };
+            (number(factor)?, dimension.trim())
+        }
+    };
+    let length = match dimension {
+        "width" => screen[0],
+        "height" => screen[1],
+        _ => return Err(invalid()),
+    };
+    let length = length.ok_or_else(|| format!("'{}' is relative to the screen {}, which is unknown", value, dimension))?;
+    Ok(fraction * length)
+}
+
+// Parses an aspect ratio written as `width:height`, e.g. `16:9`, to the width over the height
+fn aspect_ratio(ratio: &str) -> Option<f64> {
+    let (width, height) = ratio.split_once(':')?;
+    let (width, height): (f64, f64) = (width.trim().parse().ok()?, height.trim().parse().ok()?);
+    Some(width / height).filter(|aspect| aspect.is_finite())
+}
+
+// Parses an element frame such as `(x:20,y:60,w:350,h:34)`; any subset of the keys may be given.
+// Coordinates may be relative to the `[width, height]` of the screen, e.g. `w:"80%"`.
+fn parse_frame(source: &str, frame: &str, screen: [Option<f64>; 2], diagnostics: &mut Vec<Diagnostic>) -> Result<Value, Diagnostic> {
+    let err = |message: String, part: &str| Diagnostic::error("E0018", message).with_span(Span::of(source, part));
+    let Some(inner) = frame.strip_prefix('(').and_then(|f| f.strip_suffix(')')) else {
+        return Err(err(format!("Element frame must be enclosed in parentheses: '{}'", frame), frame)
+            .with_suggestion("write the frame as @(x:20,y:60,w:350,h:34)"));
+    };
+    let mut coords = Vec::new();
+    for part in inner.split(',').map(str::trim).filter(|part| !part.is_empty()) {
+        let Some((key, value)) = part.split_once(':').map(|(k, v)| (k.trim(), v.trim())) else {
+            return Err(err(format!("Missing value in element frame: '{}'", part), part));
+        };
+        if !FRAME_KEYS.contains(&key) {
+            let mut diagnostic = err(format!("Unsupported frame key: '{}'", key), key);
+            match diagnostics::suggestion_text(&diagnostics::did_you_mean(key, FRAME_KEYS.iter().copied())) {
+                Some(help) => diagnostic = diagnostic.with_suggestion(help),
+                None => diagnostic = diagnostic.with_suggestion("frame keys are `x`, `y`, `w`, `h`, `font`, `aspect`, `cornerRadius`, `border`, `shadow`, `overlay`, `animation`, `transition`, `onTap`, `onLongPress` and `onSwipe`"),
             }
-            _ => {
-                // Handle escaped backslash or quote properly
-                if escaped && (ch == '\\' || ch == '"') {
-                    current.push(ch); // Keep the escaped char
-                } else if escaped {
-                    current.push('\\'); // Keep the backslash if it didn't escape anything special
-                    current.push(ch);
-                } else {
-                    current.push(ch);
-                }
-                escaped = false;
+            return Err(diagnostic);
+        }
+        // Layouts read the first value given for a key
+        if coords.iter().any(|(given, _)| given == key) {
+            diagnostics.push(
+                Diagnostic::warning("W0008", format!("Frame key '{}' is given twice; this later value is ignored", key))
+                    .with_span(Span::of(source, part))
+                    .with_suggestion(format!("give '{}' once", key)),
+            );
+            continue;
+        }
+        // Decorations are kept as written, for the renderer to read
+        if MODIFIER_KEYS.contains(&key) {
+            Modifier::parse(key, unquote(value)).map_err(|e| err(e, value))?;
+            coords.push((key.to_string(), Value::String(unquote(value).to_string())));
+            continue;
+        }
+        let number = match value.starts_with('"') {
+            true if key == "font" => return Err(err(format!("Invalid font size {}: expected a number of points", value), value)),
+            true if key == "aspect" => aspect_ratio(unquote(value)).ok_or_else(|| err(format!("Invalid aspect ratio {}: expected a ratio such as \"16:9\"", value), value))?,
+            true => {
+                let axis = if matches!(key, "x" | "w") { "width" } else { "height" };
+                resolve_relative(unquote(value), axis, screen).map_err(|e| err(e, value))?
             }
+            false => match value.parse::<f64>() {
+                Ok(number) if number.is_finite() => number,
+                Ok(_) => return Err(err(format!("Invalid frame value '{}' for '{}': expected a finite number", value, key), value)),
+                Err(e) => return Err(err(format!("Invalid frame value '{}' for '{}': {}", value, key, e), value)),
+            },
+        };
+        if key == "font" && number <= 0.0 {
+            return Err(err(format!("Invalid font size {}: it must be more than 0", value), value));
         }
-         // Ensure escaped status is reset if not followed by specific chars
-        if escaped && ch != '\\' && ch != '"' {
-             escaped = false;
+        if key == "aspect" && number <= 0.0 {
+            return Err(err(format!("Invalid aspect ratio {}: it must be more than 0", value), value));
         }
+        coords.push((key.to_string(), Value::Float(number)));
     }
+    if coords.is_empty() {
+        return Err(err("Element frame is empty".to_string(), frame));
+    }
+    Ok(Value::Dict(coords))
+}
+
+// Parses a background, a quoted material such as `"ultraThinMaterial"` or a gradient block
+// such as `{gradient:["#FF5733","#C70039"], direction:vertical}`.
+fn parse_background(source: &str, value: &str) -> Result<Value, Diagnostic> {
+    let err = |message: String, part: &str| Diagnostic::error("E0032", message).with_span(Span::of(source, part));
+    let parsed = match value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+        Some(inner) => {
+            let mut entries = Vec::new();
+            for entry in split_elements(inner) {
+                let Some((key, item)) = entry.split_once(':').map(|(k, v)| (k.trim(), v.trim())) else {
+                    return Err(err(format!("Missing value in background: '{}'", entry), entry));
+                };
+                let item = match key {
+                    "gradient" => parse_literal(source, item)?.unwrap_or(Value::String(item.to_string())),
+                    _ => Value::String(unquote(item).to_string()),
+                };
+                entries.push((key.to_string(), item));
+            }
+            Value::Dict(entries)
+        }
+        None => Value::String(unquote(value).to_string()),
+    };
+    match Background::from_value(&parsed) {
+        Ok(_) => Ok(parsed),
+        Err(message) => {
+            let mut diagnostic = err(message, value);
+            if let Value::String(name) = &parsed {
+                if let Some(help) = diagnostics::suggestion_text(&diagnostics::did_you_mean(name, MATERIALS.iter().copied())) {
+                    diagnostic = diagnostic.with_suggestion(help);
+                }
+            }
+            Err(diagnostic)
+        }
+    }
+}
+
+// Parses a block of relational constraints such as `{ "title above button", "image leftOf title" }`.
+// Each constraint comes with its text in `source`.
+fn parse_constraint_block<'a>(source: &str, block: &'a str) -> Result<Vec<(Constraint, &'a str)>, Diagnostic> {
+    let err = |message: String, part: &str| Diagnostic::error("E0020", message).with_span(Span::of(source, part));
+    let Some(inner) = block.strip_prefix('{').and_then(|b| b.strip_suffix('}')) else {
+        return Err(err(format!("Constraints must be enclosed in braces: '{}'", block), block)
+            .with_suggestion("write them as constraints:{ \"title above button\", \"image leftOf title\" }"));
+    };
+
+    let mut stated = Vec::new();
+    let mut rest = inner.trim_start();
+    while !rest.is_empty() {
+        let Some(quoted) = rest.strip_prefix('"') else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            return Err(err(format!("Constraint must be enclosed in double quotes: got '{}'", rest[..end].trim()), rest[..end].trim()));
+        };
+        // Find the closing quote, skipping escaped ones
+        let mut escaped = false;
+        let end = quoted.char_indices().find_map(|(i, ch)| match ch {
+            _ if escaped => {
+                escaped = false;
+                None
+            }
+            '\\' => {
+                escaped = true;
+                None
+            }
+            '"' => Some(i),
+            _ => None,
+        });
+        let Some(end) = end else {
+            return Err(err("Unterminated constraint string".to_string(), rest));
+        };
+        let raw = &quoted[..end];
+        let text = unescape(source, raw)?;
+        stated.push((text.parse().map_err(|e| err(e, raw))?, raw));
 
-    // Process the last element after the loop
-    let elem = current.trim();
-    if !elem.is_empty() {
-        parse_element(elem, &mut elements)?;
+        rest = quoted[end + 1..].trim_start();
+        match rest.strip_prefix(',') {
+            Some(next) => rest = next.trim_start(),
+            None if rest.is_empty() => {}
+            None => return Err(err(format!("Expected ',' between constraints, found '{}'", rest), rest)),
+        }
     }
+    Ok(stated)
+}
 
-    let example = (
-        Value::Dict(vec![
-            ("width".to_string(), Value::Int(width)),
-            ("height".to_string(), Value::Int(height)),
-        ]),
-        Value::Dict(elements),
-    );
+// What the elements of one example parse into.
+#[derive(Default)]
+struct ParsedElements {
+    elements: Vec<(String, Value)>,
+    frames: Vec<(String, Value)>,
+    constraints: Vec<(String, Value)>,
+    /// Constraints and hints, with where each was given, to check they name elements.
+    mentions: Vec<(Constraint, Span)>,
+    /// The root container's background, a material's name or a gradient block.
+    background: Option<Value>,
+}
 
-    Ok(vec![example])
+// Resolves the escapes in `raw`, the text between the quotes of a value in `source`.
+fn unescape(source: &str, raw: &str) -> Result<String, Diagnostic> {
+    lexer::unescape(raw).map_err(|(message, part)| {
+        Diagnostic::error("E0028", message)
+            .with_span(Span::of(source, part))
+            .with_suggestion("escapes are \\\", \\\\, \\n, \\t, \\r, \\0 and \\u{...}, e.g. \\u{1F44B}")
+    })
 }
 
-// Helper to parse a single key:"value" element
-fn parse_element(elem: &str, elements: &mut Vec<(String, Value)>) -> Result<(), String> {
+// Helper to parse a single key:"value" element, with an optional `@(frame)`
+fn parse_element(
+    source: &str,
+    elem: &str,
+    options: &ParseOptions,
+    screen: [Option<f64>; 2],
+    parsed: &mut ParsedElements,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(), Diagnostic> {
+    let err = |code, message: String, part: &str| Diagnostic::error(code, message).with_span(Span::of(source, part));
+    let ParsedElements { elements, frames, constraints, mentions, background } = parsed;
     let mut kv = elem.splitn(2, ':');
-    let key = kv.next()
-        .ok_or_else(|| format!("Invalid element format (missing key?): '{}'", elem))?
-        .trim();
-    if key != "title" && key != "button" && key != "Image" {
-        return Err(format!("Unsupported element key '{}': must be 'title', 'button', or 'Image'", key));
+    let key = kv.next().unwrap_or_default().trim();
+    let value_str = kv.next();
+    let component = options.components.find(key);
+    let unknown = !SUPPORTED_ELEMENT_KEYS.contains(&key) && component.is_none();
+    // Data the view shows under a key of its own, e.g. `rating:4.5`
+    let data = match value_str {
+        Some(value) if unknown && validate::data_key(key, options) => parse_literal(source, split_frame(value.trim()).0)?.is_some(),
+        _ => false,
+    };
+    // Accepted leniently as a custom view of that name
+    let mut custom_view = None;
+    if unknown && !data {
+        diagnostics.push(validate::unknown_key(source, key, options)?);
+        custom_view = Some(key);
     }
-    let value_str = kv.next()
-        .ok_or_else(|| format!("Missing value for element key '{}'", key))?
+    let value_str = value_str
+        .ok_or_else(|| {
+            err("E0013", format!("Missing value for element key '{}'", key), elem)
+                .with_suggestion(format!("add a value, e.g. {}:\"...\"", key))
+        })?
         .trim();
+    if key == "constraints" {
+        for (constraint, text) in parse_constraint_block(source, value_str)? {
+            constraints.push((constraints.len().to_string(), Value::String(constraint.to_string())));
+            mentions.push((constraint, Span::of(source, text)));
+        }
+        return Ok(());
+    }
+    if key == "background" {
+        let value = parse_background(source, value_str)?;
+        match background {
+            Some(_) => diagnostics.push(
+                Diagnostic::warning("W0008", "The background is given twice; this later one is ignored".to_string())
+                    .with_span(Span::of(source, elem))
+                    .with_suggestion("give the background once"),
+            ),
+            None => *background = Some(value),
+        }
+        return Ok(());
+    }
+    let (value_str, frame) = split_frame(value_str);
+    if let (Some(frame), "hint") = (frame, key) {
+        return Err(err("E0019", "A hint describes the whole layout and cannot have a frame".to_string(), frame)
+            .with_suggestion("state frames on the elements the hint mentions"));
+    }
+    if let Some(frame) = frame {
+        frames.push((elements.len().to_string(), parse_frame(source, frame, screen, diagnostics)?));
+    }
 
-    // Value must be enclosed in double quotes
-    if !value_str.starts_with('"') || !value_str.ends_with('"') {
-        return Err(format!("Value for key '{}' must be enclosed in double quotes: got '{}'", key, value_str));
-    }
-
-    // Remove quotes and handle escaped quotes within the value
-    let inner_value = &value_str[1..value_str.len()-1];
-    let mut final_value = String::with_capacity(inner_value.len());
-    let mut chars = inner_value.chars().peekable();
-    while let Some(ch) = chars.next() {
-        if ch == '\\' {
-            match chars.peek() {
-                Some(&'"') => {
-                    final_value.push('"');
-                    chars.next(); // Consume the quote
-                }
-                Some(&'\\') => {
-                    final_value.push('\\');
-                    chars.next(); // Consume the backslash
-                }
-                _ => final_value.push('\\'), // Keep backslash if it doesn't escape " or \
+    // A wildcard asks for the view without caring what it says
+    if value_str == "*" {
+        if verify::placeholder(key).is_none() {
+            return Err(err("E0021", format!("Element '{}' cannot be a wildcard: its value is needed", key), value_str)
+                .with_suggestion("only `title`, `button` and `Image` may be written as `*`"));
+        }
+        elements.push((key.to_string(), Value::Any));
+        return Ok(());
+    }
+
+    // Data, components and custom views also take numbers, booleans and arrays
+    let quoted = value_str.len() >= 2 && value_str.starts_with('"') && value_str.ends_with('"');
+    let typed = match (component, custom_view) {
+        (None, None) if !data => None,
+        _ if quoted => None,
+        _ => parse_literal(source, value_str)?,
+    };
+    if let (true, Some(value)) = (data, &typed) {
+        elements.push((key.to_string(), value.clone()));
+        return Ok(());
+    }
+    if let Some(literal) = typed.as_ref().and_then(render::value_literal) {
+        let call = match component {
+            Some(component) => format!("{}({})", component.name, component.args_with(&literal)),
+            None => format!("{}({})", custom_view.unwrap_or_default(), literal),
+        };
+        elements.push(("Custom".to_string(), Value::String(call)));
+        return Ok(());
+    }
+
+    // Value should be enclosed in double quotes
+    if !quoted {
+        diagnostics.push(validate::unquoted_value(source, key, value_str, options)?);
+    }
+
+    // Remove quotes and resolve escapes within the value
+    let inner_value = if quoted { &value_str[1..value_str.len() - 1] } else { value_str };
+    let final_value = unescape(source, inner_value)?;
+
+    if let Some(name) = custom_view {
+        let call = format!("{}({})", name, render::string_literal(&final_value));
+        elements.push(("Custom".to_string(), Value::String(call)));
+        return Ok(());
+    }
+
+    // A registered component is a custom view built from its parameter list
+    if let Some(component) = component {
+        let call = format!("{}({})", component.name, component.args(&final_value));
+        elements.push(("Custom".to_string(), Value::String(call)));
+        return Ok(());
+    }
+
+    if key == "Custom" {
+        // Validate early so a malformed custom view is reported as a parse error
+        IR::custom(&final_value).map_err(|e| err("E0015", e, value_str))?;
+    }
+
+    if key == "hint" {
+        // Checked now so a phrase the solver cannot use is not silently dropped
+        match refine::parse_hint(&final_value) {
+            Ok(stated) => mentions.extend(stated.into_iter().map(|constraint| (constraint, Span::of(source, value_str)))),
+            Err((message, phrase)) => {
+                let part = inner_value.find(phrase).map_or(value_str, |at| &inner_value[at..at + phrase.len()]);
+                return Err(err("E0019", message, part)
+                    .with_suggestion("write phrases such as hint:\"title centered, button pinned to bottom, image above title\""));
             }
-        } else {
-            final_value.push(ch);
         }
     }
 
@@ -234,10 +1292,15 @@ fn parse_element(elem: &str, elements: &mut Vec<(String, Value)>) -> Result<(),
 }
 
 
-// --- Unit Tests --- (Keep existing tests, they should now pass with the fixed parser logic)
+// --- Unit Tests ---
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::layout;
+
+    fn strict() -> ParseOptions {
+        ParseOptions { strict: true, ..ParseOptions::default() }
+    }
 
     #[test]
     fn test_parse_valid_full_example() {
@@ -249,8 +1312,8 @@ mod tests {
         match dims {
             Value::Dict(d) => {
                 assert_eq!(d.len(), 2);
-                assert!(d.iter().any(|(k, v)| k == "width" && matches!(v, Value::Int(390))));
-                assert!(d.iter().any(|(k, v)| k == "height" && matches!(v, Value::Int(844))));
+                assert!(d.iter().any(|(k, v)| k == "width" && *v == Value::Float(390.0)));
+                assert!(d.iter().any(|(k, v)| k == "height" && *v == Value::Float(844.0)));
             }
             _ => panic!("Expected Dict for dimensions"),
         }
@@ -295,6 +1358,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_streaming_matches_parsing_the_whole_input() {
+        let input = "# Generated\n{(width:390,height:844):{title:\"Hi // not a comment\",button:\"Go\"}}, // first\n\
+                     {(width:320,height:568):HStack:{\"A\",\"Spacer\"}}\n{(width:390,height:844):{Image:\"logo\"}}";
+        // A three-byte buffer splits comments, quotes and examples across reads
+        let reader = std::io::BufReader::with_capacity(3, input.as_bytes());
+        let streamed: Vec<Example> = parse_examples_streaming(reader).collect::<Result<_, _>>().unwrap();
+        assert_eq!(streamed, parse_examples(input).unwrap());
+        assert_eq!(streamed.len(), 3);
+    }
+
+    #[test]
+    fn test_streaming_yields_each_example_as_it_is_read() {
+        struct Failing;
+        impl std::io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+                Err(std::io::Error::other("disk on fire"))
+            }
+        }
+        let first = "{(width:390,height:844):{title:\"Hi\"}} ";
+        let mut stream = parse_examples_streaming(std::io::BufReader::new(std::io::Read::chain(first.as_bytes(), Failing)));
+        assert_eq!(stream.next(), Some(parse_examples(first).map(|mut examples| examples.remove(0))));
+        let err = stream.next().unwrap().unwrap_err();
+        assert_eq!((err.code, err.message.as_str()), ("E0031", "Failed to read the spec: disk on fire"));
+        assert_eq!(stream.next(), None);
+    }
+
+    #[test]
+    fn test_streaming_reports_problems_in_place() {
+        let input = "{(width:390,height:844):{title:\"Hi\"}}\n{(width:390,height:844):{titel:\"Hi\"}}\nLogin: {(width:390,height:844):{title:\"Hi\"}} {(width:390";
+        let items: Vec<Result<Example, Diagnostic>> = parse_examples_streaming(input.as_bytes()).collect();
+        let problem = |item: &Result<Example, Diagnostic>| {
+            let err = item.as_ref().unwrap_err();
+            (err.code, err.span.map(|span| &input[span.start..span.end]))
+        };
+        assert_eq!(items.len(), 5);
+        assert!(items[0].is_ok() && items[3].is_ok());
+        // Spans point into the whole input, not the example
+        assert_eq!(problem(&items[1]), ("E0012", Some("titel")));
+        assert_eq!(problem(&items[2]), ("E0017", Some("Login:")));
+        assert_eq!(problem(&items[4]), ("E0001", Some("{(width:390")));
+        assert_eq!(problem(&parse_examples_streaming(" \n// nothing".as_bytes()).next().unwrap()), ("E0002", None));
+        assert_eq!(parse_examples_streaming(" \n// nothing".as_bytes()).count(), 1);
+    }
+
     #[test]
     fn test_missing_braces() {
         let input = "(width:390,height:844):{title:\"Hello\"}";
@@ -305,7 +1413,7 @@ mod tests {
     fn test_invalid_dimension_value() {
         let input = "{(width:abc,height:844):{title:\"Hello\"}}";
         let err = parse_examples(input).expect_err("Should fail");
-        assert!(err.contains("Invalid width value"));
+        assert!(err.message.contains("Invalid width value"));
     }
 
      #[test]
@@ -317,22 +1425,451 @@ mod tests {
     #[test]
     fn test_unsupported_key() {
         let input = "{(width:390,height:844):{TextField:\"placeholder\"}}";
+        let err = parse_examples_with(input, &strict()).expect_err("Should fail");
+        assert!(err.message.contains("Unsupported element key 'TextField'"));
+    }
+
+    #[test]
+    fn test_lenient_keys_and_values() {
+        let input = "{(width:390,height:844):{title:Sign in, RatingView:\"4 \\\"stars\\\"\"}}";
+        let spec = parse_spec_with(input, &ParseOptions::default()).unwrap();
+        assert_eq!(
+            spec.screens[0].examples[0].1,
+            Value::Dict(vec![
+                ("title".to_string(), Value::String("Sign in".to_string())),
+                ("Custom".to_string(), Value::String("RatingView(\"4 \\\"stars\\\"\")".to_string())),
+            ])
+        );
+        let warnings: Vec<_> = spec.warnings.iter().map(|w| (w.code, &input[w.span.unwrap().start..w.span.unwrap().end])).collect();
+        assert_eq!(warnings, vec![("W0002", "Sign in"), ("W0001", "RatingView")]);
+        // Values that are not simple still need their quotes
+        assert_eq!(parse_examples("{(width:390,height:844):{title:Hello\"}}").unwrap_err().code, "E0014");
+    }
+
+    #[test]
+    fn test_ignored_input_warns() {
+        let input = "{(width:390,height:844,width:320):{title:\"Hi\"@(y:20,y:40), button:\"Go\", \
+                     constraints:{\"button below title\", \"Image above title\"}, hint:\"button \\\"Buy\\\" top\"}}";
+        let spec = parse_spec_with(input, &ParseOptions::default()).unwrap();
+        let warnings: Vec<_> = spec.warnings.iter().map(|w| (w.code, &input[w.span.unwrap().start..w.span.unwrap().end])).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                ("W0008", "width:390"),
+                ("W0008", "y:40"),
+                ("W0008", "Image above title"),
+                ("W0008", "\"button \\\"Buy\\\" top\""),
+            ]
+        );
+        assert_eq!(spec.warnings[2].message, "The example has no Image, so 'Image above title' is ignored");
+        // The later width is the one used, the earlier y
+        let Value::Dict(dims) = &spec.screens[0].examples[0].0 else { panic!("Expected Dict for dimensions") };
+        assert_eq!(dims[0], ("width".to_string(), Value::Float(320.0)));
+        assert_eq!(layout::stated_frames(&spec.screens[0].examples[0])[0].1.y, Some(20.0));
+        // Wildcards and custom views may be named
+        let spec = parse_spec_with("{(width:390,height:844):{title:*, RatingView:\"4\", hint:\"title above RatingView\"}}", &ParseOptions::default()).unwrap();
+        assert_eq!(spec.warnings.iter().map(|w| w.code).collect::<Vec<_>>(), ["W0001"]);
+    }
+
+    #[test]
+    fn test_diagnostic_code_span_and_suggestion() {
+        let input = "{(width:390,height:844):{Title:\"Hello\"}}";
+        let err = parse_examples(input).expect_err("Should fail");
+        assert_eq!(err.code, "E0012");
+        assert_eq!(&input[err.span.unwrap().start..err.span.unwrap().end], "Title");
+        assert_eq!(err.suggestions, vec!["did you mean `title`?".to_string()]);
+
+        let input = "{(width:39x,height:844):{title:\"Hello\"}}";
         let err = parse_examples(input).expect_err("Should fail");
-        assert!(err.contains("Unsupported element key 'TextField'"));
+        assert_eq!(err.code, "E0007");
+        assert_eq!(&input[err.span.unwrap().start..err.span.unwrap().end], "39x");
+    }
+
+    #[test]
+    fn test_did_you_mean_for_keys() {
+        let err = parse_examples("{(width:390,height:844):{titel:\"Hello\"}}").expect_err("Should fail");
+        assert_eq!(err.suggestions, vec!["did you mean `title`?".to_string()]);
+
+        let err = parse_examples("{(widht:390,height:844):{title:\"Hello\"}}").expect_err("Should fail");
+        assert_eq!(err.code, "E0008");
+        assert_eq!(err.suggestions, vec!["did you mean `width`?".to_string()]);
+
+        let components = ComponentLibrary::parse("[[component]]\nname = \"AvatarView\"\n[[component]]\nname = \"PrimaryButton\"").unwrap();
+        let options = ParseOptions { components, ..ParseOptions::default() };
+        let err = parse_examples_with("{(width:390,height:844):{PrimaryButon:\"Go\"}}", &options).expect_err("Should fail");
+        assert_eq!(err.suggestions, vec!["did you mean `PrimaryButton`?".to_string()]);
+
+        let err = parse_examples_with("{(width:390,height:844):{TextField:\"x\"}}", &strict()).expect_err("Should fail");
+        assert!(err.suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_registered_component_keys() {
+        let components = ComponentLibrary::parse("[[component]]\nname = \"AvatarView\"\nparams = [\"user\"]").unwrap();
+        let options = ParseOptions { components, ..ParseOptions::default() };
+        let examples = parse_examples_with("{(width:390,height:844):{title:\"Hi\",AvatarView:\"me\"}}", &options).unwrap();
+        assert_eq!(
+            examples[0].1,
+            Value::Dict(vec![
+                ("title".to_string(), Value::String("Hi".to_string())),
+                ("Custom".to_string(), Value::String("AvatarView(user: \"me\")".to_string())),
+            ])
+        );
+        // Without the library the key is unknown
+        assert_eq!(parse_examples_with("{(width:390,height:844):{AvatarView:\"me\"}}", &strict()).unwrap_err().code, "E0012");
+    }
+
+    #[test]
+    fn test_parse_element_frames() {
+        let input = "{(width:390,height:844):{title:\"Hi, you\"@(x:20, y:60, w:350, h:34), button:\"Go\"@(y:790)}}";
+        let (dims, elements) = &parse_examples(input).unwrap()[0];
+        let Value::Dict(dims) = dims else { panic!("Expected Dict for dimensions") };
+        assert_eq!(
+            dims[2],
+            (
+                "frames".to_string(),
+                Value::Dict(vec![
+                    (
+                        "0".to_string(),
+                        Value::Dict(vec![
+                            ("x".to_string(), Value::Float(20.0)),
+                            ("y".to_string(), Value::Float(60.0)),
+                            ("w".to_string(), Value::Float(350.0)),
+                            ("h".to_string(), Value::Float(34.0)),
+                        ])
+                    ),
+                    ("1".to_string(), Value::Dict(vec![("y".to_string(), Value::Float(790.0))])),
+                ])
+            )
+        );
+        assert_eq!(
+            elements,
+            &Value::Dict(vec![
+                ("title".to_string(), Value::String("Hi, you".to_string())),
+                ("button".to_string(), Value::String("Go".to_string())),
+            ])
+        );
+
+        let err = parse_examples("{(width:390,height:844):{title:\"Hi\"@(x:20,width:350)}}").unwrap_err();
+        assert_eq!(err.code, "E0018");
+        assert_eq!(err.message, "Unsupported frame key: 'width'");
+        let err = parse_examples("{(width:390,height:844):{title:\"Hi\"@(font:\"50%\")}}").unwrap_err();
+        assert_eq!(err.message, "Invalid font size \"50%\": expected a number of points");
+        assert!(parse_examples("{(width:390,height:844):{title:\"Hi\"@(font:0)}}").unwrap_err().message.contains("more than 0"));
+        let (dims, _) = &parse_examples("{(width:390,height:844):{Image:\"hero\"@(w:390,aspect:\"16:9\")}}").unwrap()[0];
+        assert!(format!("{:?}", dims).contains(&format!("Float({:?})", 16.0 / 9.0)), "{:?}", dims);
+        let err = parse_examples("{(width:390,height:844):{Image:\"hero\"@(aspect:\"wide\")}}").unwrap_err();
+        assert_eq!(err.message, "Invalid aspect ratio \"wide\": expected a ratio such as \"16:9\"");
+        assert!(parse_examples("{(width:390,height:844):{Image:\"hero\"@(aspect:\"4:0\")}}").is_err());
+        assert!(parse_examples("{(width:390,height:844):{Image:\"hero\"@(aspect:-1)}}").unwrap_err().message.contains("more than 0"));
+        let (dims, _) = &parse_examples("{(width:390,height:844):{button:\"Buy\"@(h:44,cornerRadius:12,border:\"1 #D1D1D6\")}}").unwrap()[0];
+        assert!(format!("{:?}", dims).contains("(\"cornerRadius\", String(\"12\")), (\"border\", String(\"1 #D1D1D6\"))"), "{:?}", dims);
+        let err = parse_examples("{(width:390,height:844):{button:\"Buy\"@(shadow:\"a lot\")}}").unwrap_err();
+        assert_eq!((err.code, err.message.as_str()), ("E0018", "Invalid shadow 'a lot': expected its radius, optionally followed by an x and y offset, e.g. \"8 0 4\""));
+        let err = parse_examples("{(width:390,height:844):{title:\"Hi\"@x:20}}").unwrap_err();
+        assert!(err.message.starts_with("Element frame must be enclosed in parentheses"));
+        let err = parse_examples("{(width:390,height:844):{title:\"Hi\"@(x:2o)}}").unwrap_err();
+        assert!(err.message.starts_with("Invalid frame value '2o' for 'x'"));
+    }
+
+    #[test]
+    fn test_relative_lengths() {
+        let input = "{(width:400,height:800):{title:\"Hi\"@(x:\"10%\",w:\"80%\",h:\"0.05*height\",y:\"width*0.5\")}}";
+        let (dims, _) = &parse_examples(input).unwrap()[0];
+        let Value::Dict(dims) = dims else { panic!("Expected Dict for dimensions") };
+        let frame = Value::Dict(vec![
+            ("x".to_string(), Value::Float(40.0)),
+            ("w".to_string(), Value::Float(320.0)),
+            ("h".to_string(), Value::Float(40.0)),
+            ("y".to_string(), Value::Float(200.0)),
+        ]);
+        assert_eq!(dims[2], ("frames".to_string(), Value::Dict(vec![("0".to_string(), frame)])));
+
+        let split = &parse_examples("{(device:\"iPad Pro 13-inch\",width:\"50%\"):{title:\"Hi\"}}").unwrap()[0];
+        assert_eq!(layout::screen_size(split), Some(layout::Size::new(516.0, 1376.0)));
+        let err = parse_examples("{(width:400,height:?):{title:\"Hi\"@(h:\"50%\")}}").unwrap_err();
+        assert_eq!(err.message, "'50%' is relative to the screen height, which is unknown");
+        let err = parse_examples("{(width:400,height:800):{title:\"Hi\"@(w:\"half\")}}").unwrap_err();
+        assert_eq!(err.code, "E0018");
+        assert!(err.message.starts_with("Invalid relative value 'half'"));
+        assert_eq!(parse_examples("{(width:\"50%\",height:800):{}}").unwrap_err().code, "E0007");
+    }
+
+    #[test]
+    fn test_constraints_block() {
+        let input = "{(width:390,height:844):{title:\"Hi\",constraints:{ \"title above button\", \"image leftOf title\" },button:\"Go\"}}";
+        let (dims, elements) = &parse_examples(input).unwrap()[0];
+        let Value::Dict(dims) = dims else { panic!("Expected Dict for dimensions") };
+        assert_eq!(
+            dims[2],
+            (
+                "constraints".to_string(),
+                Value::Dict(vec![
+                    ("0".to_string(), Value::String("title above button".to_string())),
+                    ("1".to_string(), Value::String("Image left of title".to_string())),
+                ])
+            )
+        );
+        assert_eq!(
+            elements,
+            &Value::Dict(vec![
+                ("title".to_string(), Value::String("Hi".to_string())),
+                ("button".to_string(), Value::String("Go".to_string())),
+            ])
+        );
+
+        let input = "{(width:390,height:844):{title:\"Hi\",constraints:{\"title above\"}}}";
+        let err = parse_examples(input).unwrap_err();
+        assert_eq!(err.code, "E0020");
+        assert_eq!(err.span.map(|span| &input[span.start..span.end]), Some("title above"));
+        let err = parse_examples("{(width:390,height:844):{constraints:{title above button}}}").unwrap_err();
+        assert!(err.message.starts_with("Constraint must be enclosed in double quotes"));
+        let err = parse_examples("{(width:390,height:844):{constraints:\"title top\"}}").unwrap_err();
+        assert!(err.message.starts_with("Constraints must be enclosed in braces"));
+    }
+
+    #[test]
+    fn test_wildcards() {
+        let input = "{(width:390,height:?):{title:*,button:\"Go\",Image:*@(y:0)}}";
+        let (dims, elements) = &parse_examples(input).unwrap()[0];
+        let Value::Dict(dims) = dims else { panic!("Expected Dict for dimensions") };
+        assert_eq!(dims[..2], [("width".to_string(), Value::Float(390.0)), ("height".to_string(), Value::Any)]);
+        assert_eq!(dims[2].0, "frames");
+        assert_eq!(
+            elements,
+            &Value::Dict(vec![
+                ("title".to_string(), Value::Any),
+                ("button".to_string(), Value::String("Go".to_string())),
+                ("Image".to_string(), Value::Any),
+            ])
+        );
+
+        let err = parse_examples("{(width:390,height:844):{Custom:*}}").unwrap_err();
+        assert_eq!(err.code, "E0021");
+        let quoted = &parse_examples("{(width:390,height:844):{title:\"*\"}}").unwrap()[0].1;
+        assert_eq!(quoted, &Value::Dict(vec![("title".to_string(), Value::String("*".to_string()))]));
+        assert_eq!(parse_examples("{(width:??,height:844):{title:*}}").unwrap_err().code, "E0007");
+    }
+
+    #[test]
+    fn test_device_presets() {
+        let (dims, _) = &parse_examples("{(device:\"iPhone 15 Pro\", orientation:landscape):{title:\"Hi\"}}").unwrap()[0];
+        let Value::Dict(dims) = dims else { panic!("Expected Dict for dims") };
+        assert_eq!(dims[0], ("width".to_string(), Value::Float(852.0)));
+        assert_eq!(dims[1], ("height".to_string(), Value::Float(393.0)));
+        assert_eq!(dims[2], ("device".to_string(), Value::String("iPhone 15 Pro".to_string())));
+        assert_eq!(dims[3], ("orientation".to_string(), Value::String("landscape".to_string())));
+        let portrait = &parse_examples("{(device:\"iphone se\"):{title:\"Hi\"}}").unwrap()[0];
+        assert_eq!(layout::screen_size(portrait), Some(layout::Size::new(375.0, 667.0)));
+        assert_eq!(layout::safe_area(portrait).bottom, 0.0);
+
+        let input = "{(device:\"iPhone 15 Prp\"):{title:\"Hi\"}}";
+        let err = parse_examples(input).unwrap_err();
+        assert_eq!(err.code, "E0024");
+        assert!(err.suggestions[0].starts_with("did you mean `iPhone 15 Pro`"));
+        let both = parse_examples("{(device:\"iPhone 15\",width:390):{title:\"Hi\"}}").unwrap_err();
+        assert_eq!(both.message, "Give either a device or width and height, not both");
+        assert_eq!(parse_examples("{(width:390,height:844,orientation:landscape):{}}").unwrap_err().code, "E0024");
+        assert_eq!(parse_examples("{(device:iPhone):{}}").unwrap_err().code, "E0024");
+
+        let stated = &parse_examples("{(device:\"iPhone 15\",safe_top:20):{title:\"Hi\"}}").unwrap()[0];
+        assert_eq!(layout::safe_area(stated), layout::Insets::new(20.0, 34.0, 0.0, 0.0));
+        let plain = &parse_examples("{(width:390,height:844,safe_bottom:34):{title:\"Hi\"}}").unwrap()[0];
+        assert_eq!(layout::safe_area(plain), layout::Insets::new(0.0, 34.0, 0.0, 0.0));
+        assert_eq!(parse_examples("{(width:390,height:844,safe_top:-4):{}}").unwrap_err().code, "E0007");
+    }
+
+    #[test]
+    fn test_device_presets_belong_to_the_platform() {
+        let input = "{(device:\"Apple Watch Series 9 45mm\"):{title:\"Hi\"}}";
+        let err = parse_examples(input).unwrap_err();
+        assert_eq!(err.code, "E0024");
+        assert_eq!(err.message, "'Apple Watch Series 9 45mm' is a watchos device, but the layout is for ios");
+        assert!(err.suggestions[0].starts_with("pass --platform watchos, or name a ios device: iPhone SE, "), "{}", err.suggestions[0]);
+
+        let options = ParseOptions { platform: Platform::Watchos, ..ParseOptions::default() };
+        let watch = &parse_examples_with(input, &options).unwrap()[0];
+        assert_eq!(layout::screen_size(watch), Some(layout::Size::new(198.0, 242.0)));
+        assert_eq!(layout::safe_area(watch).top, 31.0);
+        assert!(parse_examples_with("{(device:\"iPhone 15\"):{title:\"Hi\"}}", &options).is_err());
+    }
+
+    #[test]
+    fn test_dynamic_type_dimension() {
+        let example = &parse_examples("{(width:390,height:844,dynamicType:accessibility3):{title:\"Hi\"}}").unwrap()[0];
+        assert_eq!(layout::dynamic_type(example), layout::DynamicType::Accessibility3);
+        let err = parse_examples("{(width:390,height:844,dynamicType:acessibility3):{}}").unwrap_err();
+        assert_eq!(err.code, "E0025");
+        assert!(err.suggestions[0].starts_with("did you mean `accessibility3`"));
+    }
+
+    #[test]
+    fn test_layout_direction_dimension() {
+        let rtl = &parse_examples("{(width:390,height:844,layoutDirection:rtl):{title:\"مرحبا\"}}").unwrap()[0];
+        assert_eq!(layout::layout_direction(rtl), layout::LayoutDirection::RightToLeft);
+        let ltr = &parse_examples("{(width:390,height:844,layoutDirection:ltr):{title:\"Hi\"}}").unwrap()[0];
+        assert_eq!(ltr.0, parse_examples("{(width:390,height:844):{title:\"Hi\"}}").unwrap()[0].0);
+        assert_eq!(parse_examples("{(width:390,height:844,layoutDirection:up):{}}").unwrap_err().code, "E0026");
+    }
+
+    #[test]
+    fn test_background_element() {
+        let (dims, elements) = &parse_examples("{(width:390,height:844):{title:\"Hi\",background:{gradient:[\"#FF5733\",\"#C70039\"], direction:vertical}}}").unwrap()[0];
+        let Value::Dict(dims) = dims else { panic!("dimensions are a dict") };
+        assert_eq!(
+            dims.last().unwrap(),
+            &(
+                "background".to_string(),
+                Value::Dict(vec![
+                    ("gradient".to_string(), Value::Array(vec![Value::String("#FF5733".to_string()), Value::String("#C70039".to_string())])),
+                    ("direction".to_string(), Value::String("vertical".to_string())),
+                ])
+            )
+        );
+        assert_eq!(elements, &Value::Dict(vec![("title".to_string(), Value::String("Hi".to_string()))]));
+
+        let err = parse_examples("{(width:390,height:844):{background:\"ultraThinMaterail\"}}").unwrap_err();
+        assert_eq!((err.code, err.suggestions), ("E0032", vec!["did you mean `ultraThinMaterial` or `ultraThickMaterial`?".to_string()]));
+        let err = parse_examples("{(width:390,height:844):{background:{gradient:[\"#FFF\"]}}}").unwrap_err();
+        assert_eq!(err.message, "A gradient needs at least two colors");
+        let spec = parse_spec_with("{(width:390,height:844):{background:\"bar\",background:\"thinMaterial\"}}", &ParseOptions::default()).unwrap();
+        assert_eq!(spec.warnings[0].code, "W0008");
+    }
+
+    #[test]
+    fn test_hint_element() {
+        let input = "{(width:390,height:844):{title:\"Hi\",button:\"Go\",hint:\"title centered, button pinned to bottom\"}}";
+        let (_, elements) = &parse_examples(input).unwrap()[0];
+        let Value::Dict(elements) = elements else { panic!("Expected Dict for elements") };
+        assert_eq!(elements[2], ("hint".to_string(), Value::String("title centered, button pinned to bottom".to_string())));
+
+        let input = "{(width:390,height:844):{title:\"Hi\",hint:\"title centered and button wobbly\"}}";
+        let err = parse_examples(input).unwrap_err();
+        assert_eq!(err.code, "E0019");
+        assert!(err.message.starts_with("Could not understand where 'button' should be"));
+        assert_eq!(err.span.map(|span| &input[span.start..span.end]), Some("button wobbly"));
+        let err = parse_examples("{(width:390,height:844):{hint:\"title top\"@(y:0)}}").unwrap_err();
+        assert_eq!(err.code, "E0019");
     }
 
     #[test]
     fn test_malformed_elements_missing_colon() {
         let input = "{(width:390,height:844):{title}}";
         let err = parse_examples(input).expect_err("Should fail");
-        assert!(err.contains("Missing value for element key 'title'"));
+        assert!(err.message.contains("Missing value for element key 'title'"));
+    }
+
+    #[test]
+    fn test_typed_values_for_components_and_custom_views() {
+        let components = ComponentLibrary::parse("[[component]]\nname = \"StarRating\"\nparams = [\"rating\"]").unwrap();
+        let options = ParseOptions { components, ..ParseOptions::default() };
+        let input = r#"{(width:390,height:844):{StarRating:4.5@(y:20), PromoToggle:true, TagList:["new", "sale",], Grid:[[1, 2], [3]], Count:-3}}"#;
+        let examples = parse_examples_with(input, &options).unwrap();
+        let calls: Vec<&Value> = match &examples[0].1 {
+            Value::Dict(elements) => elements.iter().map(|(_, value)| value).collect(),
+            _ => panic!("Expected Dict for elements"),
+        };
+        let expected = ["StarRating(rating: 4.5)", "PromoToggle(true)", r#"TagList(["new", "sale"])"#, "Grid([[1, 2], [3]])", "Count(-3)"];
+        assert_eq!(calls, expected.map(|call| Value::String(call.to_string())).iter().collect::<Vec<_>>());
+
+        let input = "{(width:390,height:844):{TagList:[\"a\", b]}}";
+        let err = parse_examples(input).unwrap_err();
+        assert_eq!((err.code, err.span.map(|span| &input[span.start..span.end])), ("E0029", Some("b")));
+        // Built-in elements still take text
+        assert_eq!(parse_examples("{(width:390,height:844):{title:[\"a\"]}}").unwrap_err().code, "E0014");
+    }
+
+    #[test]
+    fn test_typed_values_under_data_keys() {
+        let input = r#"{(width:390,height:844):{title:"Prefs", rating:4.5@(y:20), enabled:true, tags:["a","b"], count:3}}"#;
+        for options in [ParseOptions::default(), ParseOptions { strict: true, ..ParseOptions::default() }] {
+            let examples = parse_examples_with(input, &options).unwrap();
+            let Value::Dict(elements) = &examples[0].1 else { panic!("Expected Dict for elements") };
+            let expected = [
+                ("title", Value::String("Prefs".to_string())),
+                ("rating", Value::Float(4.5)),
+                ("enabled", Value::Bool(true)),
+                ("tags", Value::Array(vec![Value::String("a".to_string()), Value::String("b".to_string())])),
+                ("count", Value::Int(3)),
+            ];
+            assert_eq!(elements, &expected.map(|(k, v)| (k.to_string(), v)).to_vec());
+        }
+        // Text under an unknown key, and misspelled keys, are still rejected
+        assert_eq!(parse_examples("{(width:390,height:844):{subtitle:\"Hi\"}}").unwrap_err().code, "E0012");
+        assert_eq!(parse_examples("{(width:390,height:844):{titel:4}}").unwrap_err().code, "E0012");
+    }
+
+    #[test]
+    fn test_fractional_dimensions() {
+        let example = &parse_examples("{(width:393.5,height:852,safe_top:59.5):{title:\"Hi\"@(x:20.25,y:\"10%\")}}").unwrap()[0];
+        assert_eq!(layout::screen_size(example), Some(layout::Size::new(393.5, 852.0)));
+        assert_eq!(layout::safe_area(example).top, 59.5);
+        let frame = layout::stated_frames(example)[0].1;
+        assert_eq!((frame.x, frame.y), (Some(20.25), Some(85.2)));
+        let relative = &parse_examples("{(device:\"iPhone 15\",width:\"33.3%\"):{title:\"Hi\"}}").unwrap()[0];
+        assert!((layout::screen_size(relative).unwrap().width - 393.0 * 0.333).abs() < 1e-9);
+        for bad in ["nan", "inf", "-1", "1e400"] {
+            let err = parse_examples(&format!("{{(width:{},height:844):{{}}}}", bad)).unwrap_err();
+            assert_eq!(err.code, "E0007", "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_escapes_in_values() {
+        let result = parse_examples(r#"{(width:390,height:844):{title:"Welcome\nback \u{1F44B}", button:"Tab\there"}}"#).unwrap();
+        assert_eq!(
+            result[0].1,
+            Value::Dict(vec![
+                ("title".to_string(), Value::String("Welcome\nback 👋".to_string())),
+                ("button".to_string(), Value::String("Tab\there".to_string())),
+            ])
+        );
+        let input = r#"{(width:390,height:844):{title:"ça \u{zz}"}}"#;
+        let err = parse_examples(input).unwrap_err();
+        assert_eq!(err.code, "E0028");
+        assert_eq!(err.span.map(|span| &input[span.start..span.end]), Some(r"\u{zz}"));
+        assert_eq!(parse_examples(r#"{(width:390,height:844):HStack:{"a\qb"}}"#).unwrap_err().code, "E0028");
+    }
+
+    #[test]
+    fn test_multibyte_content() {
+        // Emoji, CJK and combining marks in every place a value can go
+        let input = "let greeting = \"こんにちは 👋\" // 挨拶\n{(width:390,height:844):{title:greeting@(y:\"10%\"), button:\"Go ✅\", \
+                     Image:\"e\u{301}toile\", hint:\"title 👋 centered\", constraints:{\"title above button\"}}}\n\
+                     {(width:390,height:844):HStack:{\"日本\", \"🇫🇷\"}}";
+        let examples = parse_examples(input).unwrap();
+        let Value::Dict(elements) = &examples[0].1 else { panic!("Expected Dict for elements") };
+        let values: Vec<&Value> = elements.iter().map(|(_, value)| value).collect();
+        let expected = ["こんにちは 👋", "Go ✅", "e\u{301}toile", "title 👋 centered"].map(|s| Value::String(s.to_string()));
+        assert_eq!(values, expected.iter().collect::<Vec<_>>());
+        assert_eq!(examples[1].1, Value::Dict(vec![("HStack".to_string(), Value::Dict(vec![
+            ("child0".to_string(), Value::String("日本".to_string())),
+            ("child1".to_string(), Value::String("🇫🇷".to_string())),
+        ]))]));
+
+        // Diagnostics point at whole characters
+        let cases = [
+            ("{(width:３９０,height:844):{title:\"é\"}}", "３９０"),
+            ("{(width:390,height:844):{título:\"é\"}}", "título"),
+            ("{(width:390,height:844):{title:\"a\"@(é:1)}}", "é"),
+            ("{(width:390,height:844):{title:\"日本\", button:\"👋\"@(x:👋)}}", "👋"),
+            ("{(width:390,height:844):HStack:{\"👋\", 日本}}", "日本"),
+            ("{(width:390,height:844):{constraints:{\"タイトル 上 button\"}}}", "タイトル 上 button"),
+            ("{(width:390,height:844):{hint:\"title 👋 sideways\"}}", "title 👋 sideways"),
+            ("{(width:390,height:844):{title:\"👋\\é\"}}", "\\é"),
+            ("{(width:390,height:844):{title:\"a\"}} é {(width:390,height:844):{}}", "é"),
+        ];
+        for (input, part) in cases {
+            let span = parse_examples(input).unwrap_err().span.unwrap();
+            assert_eq!(&input[span.start..span.end], part, "in {}", input);
+        }
     }
 
     #[test]
     fn test_missing_quotes_in_value() {
         let input = "{(width:390,height:844):{title:Hello}}";
-         let err = parse_examples(input).expect_err("Should fail");
-        assert!(err.contains("Value for key 'title' must be enclosed in double quotes"));
+        let err = parse_examples_with(input, &strict()).expect_err("Should fail");
+        assert!(err.message.contains("Value for key 'title' must be enclosed in double quotes"));
     }
 
     #[test]
@@ -345,8 +1882,8 @@ mod tests {
          let (dims, elements) = &result.unwrap()[0];
          match dims {
             Value::Dict(d) => {
-                assert!(d.iter().any(|(k, v)| k == "width" && matches!(v, Value::Int(390))));
-                assert!(d.iter().any(|(k, v)| k == "height" && matches!(v, Value::Int(844))));
+                assert!(d.iter().any(|(k, v)| k == "width" && *v == Value::Float(390.0)));
+                assert!(d.iter().any(|(k, v)| k == "height" && *v == Value::Float(844.0)));
             }
             _ => panic!("Expected Dict for dimensions"),
         }
@@ -369,8 +1906,8 @@ mod tests {
         match dims {
             Value::Dict(d) => {
                 assert_eq!(d.len(), 2);
-                assert!(d.iter().any(|(k, v)| k == "width" && matches!(v, Value::Int(390))));
-                assert!(d.iter().any(|(k, v)| k == "height" && matches!(v, Value::Int(844))));
+                assert!(d.iter().any(|(k, v)| k == "width" && *v == Value::Float(390.0)));
+                assert!(d.iter().any(|(k, v)| k == "height" && *v == Value::Float(844.0)));
             }
             _ => panic!("Expected Dict for dimensions"),
         }
@@ -398,7 +1935,7 @@ mod tests {
         let input = "{(width:390,height:844):HStack:\"A\",\"B\",\"Spacer\",\"C\"}";
         let result = parse_examples(input);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("HStack elements must be enclosed in braces"));
+        assert!(result.unwrap_err().message.contains("HStack elements must be enclosed in braces"));
     }
 
      #[test]
@@ -406,7 +1943,7 @@ mod tests {
         let input = "{(width:390,height:844):HStack:{\"A\",B,\"Spacer\",\"C\"}}";
         let result = parse_examples(input);
         assert!(result.is_err());
-        assert!(result.unwrap_err().contains("HStack child value must be quoted"));
+        assert!(result.unwrap_err().message.contains("HStack child value must be quoted"));
     }
 
 
@@ -425,6 +1962,100 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_custom_view() {
+        let input = "{(width:390,height:844):{title:\"Stats\", Custom:\"MyChartView(data: points)\"}}";
+        let result = parse_examples(input).unwrap();
+        match &result[0].1 {
+            Value::Dict(e) => {
+                assert!(e.iter().any(|(k, v)| k == "Custom" && matches!(v, Value::String(s) if s == "MyChartView(data: points)")));
+            }
+            _ => panic!("Expected Dict for elements"),
+        }
+
+        let err = parse_examples("{(width:390,height:844):{Custom:\"MyChartView(data: points\"}}").expect_err("Should fail");
+        assert!(err.message.contains("missing a closing ')'"));
+    }
+
+    #[test]
+    fn test_parse_multiple_examples() {
+        let input = "{(width:390,height:844):{title:\"Hi, {you}\"}}\n{(width:320,height:568):{title:\"Hi, {you}\"}},\n{(width:1024,height:768):{title:\"Hi, {you}\"}}";
+        let result = parse_examples(input).unwrap();
+        assert_eq!(result.len(), 3);
+        assert!(matches!(&result[1].0, Value::Dict(d) if d[0] == ("width".to_string(), Value::Float(320.0))));
+        assert!(matches!(&result[2].1, Value::Dict(e) if e[0].1 == Value::String("Hi, {you}".to_string())));
+    }
+
+    #[test]
+    fn test_named_screens() {
+        let options = ParseOptions::default();
+        let input = "Login:{(width:390,height:844):{title:\"Sign in\"}}{(width:320,height:568):{title:\"Sign in\"}},\nHome :{(width:390,height:844):{title:\"Home\"}}";
+        let screens = parse_spec_with(input, &options).unwrap().screens;
+        let summary: Vec<(Option<&str>, usize)> = screens.iter().map(|s| (s.name.as_deref(), s.examples.len())).collect();
+        assert_eq!(summary, vec![(Some("Login"), 2), (Some("Home"), 1)]);
+
+        let plain = parse_spec_with("{(width:390,height:844):{title:\"Hi\"}}", &options).unwrap().screens;
+        assert_eq!(plain.len(), 1);
+        assert_eq!(plain[0].name, None);
+
+        let errors = [
+            ("Login: Home:{(width:390,height:844):{title:\"Hi\"}}", "Screen 'Login' has no examples"),
+            ("Login:{(width:390,height:844):{title:\"Hi\"}} Home:", "Screen 'Home' has no examples"),
+            ("Log in:{(width:390,height:844):{title:\"Hi\"}}", "Expected a screen name such as 'Login:', found 'Log in:'"),
+            ("A:{(width:390,height:844):{title:\"Hi\"}} A:{(width:390,height:844):{title:\"Hi\"}}", "Screen 'A' is defined twice"),
+        ];
+        for (input, message) in errors {
+            let errors = parse_spec_with(input, &options).unwrap_err();
+            let found: Vec<_> = errors.iter().map(|err| (err.code, err.message.as_str())).collect();
+            assert_eq!(found, vec![("E0022", message)]);
+        }
+    }
+
+    #[test]
+    fn test_flows() {
+        let options = ParseOptions::default();
+        let screens = "Login:{(width:390,height:844):{title:\"Hi\",button:\"Go\"}} Home:{(width:390,height:844):{title:\"Home\"}}";
+        let spec = parse_spec_with(&format!("{} flows:{{Login.button -> Home}}", screens), &options).unwrap();
+        assert_eq!(spec.screens.len(), 2);
+        assert_eq!(spec.flows.iter().map(|f| f.to_string()).collect::<Vec<_>>(), vec!["Login.button -> Home"]);
+
+        let input = format!("{} flows:{{Login.button -> Hom}}", screens);
+        let err = &parse_spec_with(&input, &options).unwrap_err()[0];
+        assert_eq!(err.code, "E0023");
+        assert_eq!(err.span.map(|span| &input[span.start..span.end]), Some("Login.button -> Hom"));
+        assert!(err.suggestions[0].contains("Home"));
+        let err = &parse_spec_with(&format!("{} flows:{{Login.title -> Home}}", screens), &options).unwrap_err()[0];
+        assert!(err.message.starts_with("Only buttons can navigate"));
+        assert_eq!(parse_spec_with(&format!("{} flows:", screens), &options).unwrap_err()[0].code, "E0022");
+    }
+
+    #[test]
+    fn test_every_problem_is_reported() {
+        let input = "Login:{(width:390,height:844):{titel:\"Hi\", button:\"Go\"@(y:x)}}\n\
+                     {(widht:320,height:568):{title:\"Hi\"}}\n\
+                     Home:{(width:390,height:844):{Image:\"logo\", hint:\"title sideways\"}}\n\
+                     Settings:{(width:390,height:844):{title:\"Settings\", button:\"Done\"}}\n\
+                     flows:{Login.button -> Home, Settings.button -> Hom}";
+        let errors = parse_spec_with(input, &ParseOptions::default()).unwrap_err();
+        let found: Vec<_> = errors.iter().map(|err| (err.code, &input[err.span.unwrap().start..err.span.unwrap().end])).collect();
+        assert_eq!(
+            found,
+            vec![("E0012", "titel"), ("E0018", "x"), ("E0008", "widht"), ("E0019", "title sideways"), ("E0023", "Settings.button -> Hom")]
+        );
+        // The single-problem API reports the first of them
+        let examples = "{(width:390,height:844):{titel:\"Hi\"}}{(widht:320,height:568):{}}";
+        assert_eq!(parse_examples_all(examples, &ParseOptions::default()).unwrap_err().len(), 2);
+        assert_eq!(parse_examples(examples).unwrap_err().code, "E0012");
+    }
+
+    #[test]
+    fn test_text_between_examples_is_rejected() {
+        let input = "{(width:390,height:844):{title:\"A\"}} oops {(width:320,height:568):{title:\"A\"}}";
+        let err = parse_examples(input).expect_err("Should fail");
+        assert_eq!(err.code, "E0017");
+        assert_eq!(&input[err.span.unwrap().start..err.span.unwrap().end], "oops");
+    }
+
     #[test]
     fn test_mismatched_parentheses() {
         let input1 = "{(width:390,height:844:{title:\"Hello\"}}"; // Missing closing )
@@ -0,0 +1,326 @@
+// File: src/input/preprocess.rs
+// Expands `let` bindings and `@include` directives in a spec before it is parsed, keeping
+// track of where each substitution came from so diagnostics still point at the input.
+use std::ops::Range;
+
+use crate::diagnostics::{self, Diagnostic, Span};
+
+/// What a `let` binds a name to.
+#[derive(Clone, Debug, PartialEq)]
+enum Binding {
+    /// Text substituted where the name is used as a value: `"Continue"`, `20` or `["a", "b"]`.
+    Value(String),
+    /// The inside of a `{...}` block, inserted by `@include name`.
+    Block(String),
+}
+
+/// A `let` binding of a spec, for finding what depends on it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bound {
+    pub name: String,
+    /// The value or the block's contents, expanded.
+    pub text: String,
+    /// Bindings the block's contents use.
+    pub uses: Vec<String>,
+}
+
+/// A spec with its bindings expanded.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Expansion {
+    pub text: String,
+    /// The spec's bindings, in the order they are made.
+    pub bindings: Vec<Bound>,
+    // Each substitution: where it sits in `text`, the use it replaced in the input and the
+    // binding it inserted
+    substitutions: Vec<(Range<usize>, Range<usize>, String)>,
+}
+
+impl Expansion {
+    // Maps an offset into `text` to the input. Inside a substitution it lands on the start
+    // of the use, or its end for the `end` of a span.
+    fn map(&self, offset: usize, end: bool) -> usize {
+        let mut shift = 0isize;
+        for (expanded, original, _) in &self.substitutions {
+            if offset < expanded.start || (end && offset == expanded.start) {
+                break;
+            }
+            if offset < expanded.end || (end && offset == expanded.end) {
+                return if end { original.end } else { original.start };
+            }
+            shift += original.len() as isize - expanded.len() as isize;
+        }
+        offset.saturating_add_signed(shift)
+    }
+
+    /// Points `diagnostic` at the input instead of the expanded text. Problems inside a
+    /// substituted value or block are reported at the name or `@include` that brought it in.
+    pub fn restore(&self, mut diagnostic: Diagnostic) -> Diagnostic {
+        diagnostic.span = diagnostic.span.map(|span| Span::new(self.map(span.start, false), self.map(span.end, true)));
+        diagnostic
+    }
+
+    /// The bindings used within `range` of `text`, with those their blocks use, in the
+    /// order they are made.
+    pub fn uses(&self, range: Range<usize>) -> Vec<&Bound> {
+        let mut names: Vec<&str> = self
+            .substitutions
+            .iter()
+            .filter(|(expanded, ..)| range.start <= expanded.start && expanded.end <= range.end)
+            .map(|(.., name)| name.as_str())
+            .collect();
+        // Blocks only use bindings made before them, so one pass from the last covers them all
+        for bound in self.bindings.iter().rev() {
+            if names.contains(&bound.name.as_str()) {
+                names.extend(bound.uses.iter().map(String::as_str));
+            }
+        }
+        self.bindings.iter().filter(|bound| names.contains(&bound.name.as_str())).collect()
+    }
+}
+
+/// Expands `input`. `let name = "value"` (or a number or bare word) binds a value, which
+/// replaces the name wherever it is used as one, as in `button:name`; `let name = {...}`
+/// binds a block, whose contents `@include name` inserts. Bindings are made at the top level,
+/// outside every block, and blank out to spaces; each must come before its uses.
+pub fn expand(input: &str) -> Result<Expansion, Diagnostic> {
+    let mut expander = Expander { source: input, bindings: Vec::new() };
+    let mut expansion = expander.expand(input, true)?;
+    expansion.bindings = expander
+        .bindings
+        .into_iter()
+        .map(|(name, binding, uses)| {
+            let text = match binding {
+                Binding::Value(text) | Binding::Block(text) => text,
+            };
+            Bound { name: name.to_string(), text, uses }
+        })
+        .collect();
+    Ok(expansion)
+}
+
+fn is_identifier_start(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || ch == '_'
+}
+
+fn is_identifier_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+// The identifier at the start of `text`, possibly empty.
+fn identifier(text: &str) -> &str {
+    if !text.starts_with(is_identifier_start) {
+        return "";
+    }
+    let end = text.find(|ch| !is_identifier_char(ch)).unwrap_or(text.len());
+    &text[..end]
+}
+
+// Length of the quoted string, `{...}` block or `[...]` array at the start of `text`, if it is closed.
+fn delimited_len(text: &str) -> Option<usize> {
+    let (mut depth, mut in_quotes, mut escaped) = (0usize, false, false);
+    for (i, ch) in text.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '{' | '[' if !in_quotes => depth += 1,
+            '}' | ']' if !in_quotes => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        if !in_quotes && depth == 0 {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+struct Expander<'a> {
+    source: &'a str,
+    // Each binding with the bindings its block uses
+    bindings: Vec<(&'a str, Binding, Vec<String>)>,
+}
+
+impl<'a> Expander<'a> {
+    fn err(&self, message: String, part: &str) -> Diagnostic {
+        Diagnostic::error("E0027", message).with_span(Span::of(self.source, part))
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.bindings.iter().find(|(bound, ..)| *bound == name).map(|(_, binding, _)| binding)
+    }
+
+    // Parses the `let name = value` at the start of `text`, binding it; returns its length.
+    fn definition(&mut self, text: &'a str) -> Result<usize, Diagnostic> {
+        let keyword = &text[..3];
+        let rest = text[3..].trim_start();
+        let name = identifier(rest);
+        if name.is_empty() {
+            return Err(self.err("Expected a name after 'let'".to_string(), keyword)
+                .with_suggestion("bind a value as let primaryAction = \"Continue\""));
+        }
+        let Some(rest) = rest[name.len()..].trim_start().strip_prefix('=') else {
+            return Err(self.err(format!("Expected '=' after 'let {}'", name), name));
+        };
+        let value = rest.trim_start();
+        let len = match value.chars().next() {
+            Some('"' | '{' | '[') => delimited_len(value).ok_or_else(|| self.err(format!("Unterminated value for '{}'", name), value))?,
+            _ => value.find(|ch: char| ch.is_whitespace() || ch == ',').unwrap_or(value.len()),
+        };
+        if len == 0 {
+            return Err(self.err(format!("Missing value for '{}'", name), name));
+        }
+        if self.lookup(name).is_some() {
+            return Err(self.err(format!("'{}' is defined twice", name), name));
+        }
+        let (binding, uses) = match value.strip_prefix('{') {
+            // Blocks are expanded with the bindings made so far
+            Some(_) => {
+                let expansion = self.expand(&value[1..len - 1], false)?;
+                let mut uses: Vec<String> = expansion.substitutions.into_iter().map(|(.., name)| name).collect();
+                uses.dedup();
+                (Binding::Block(expansion.text.trim().to_string()), uses)
+            }
+            None => (Binding::Value(value[..len].to_string()), Vec::new()),
+        };
+        self.bindings.push((name, binding, uses));
+        Ok(text.len() - value.len() + len)
+    }
+
+    fn expand(&mut self, fragment: &'a str, top_level: bool) -> Result<Expansion, Diagnostic> {
+        let mut text = String::with_capacity(fragment.len());
+        let mut substitutions = Vec::new();
+        let (mut in_quotes, mut escaped, mut depth) = (false, false, 0usize);
+        let mut i = 0;
+        while let Some(ch) = fragment[i..].chars().next() {
+            let rest = &fragment[i..];
+            if in_quotes {
+                match ch {
+                    _ if escaped => escaped = false,
+                    '\\' => escaped = true,
+                    '"' => in_quotes = false,
+                    _ => {}
+                }
+                text.push(ch);
+                i += ch.len_utf8();
+                continue;
+            }
+            let boundary = !fragment[..i].ends_with(is_identifier_char);
+            let used = match ch {
+                'l' if top_level && depth == 0 && boundary && rest.starts_with("let") && rest[3..].starts_with(char::is_whitespace) => {
+                    // Definitions become blanks, so the text around them keeps its offsets
+                    let len = self.definition(rest)?;
+                    for ch in rest[..len].chars() {
+                        match ch {
+                            '\n' => text.push('\n'),
+                            _ => text.extend(std::iter::repeat_n(' ', ch.len_utf8())),
+                        }
+                    }
+                    len
+                }
+                '@' if rest.starts_with("@include") && rest[8..].starts_with(char::is_whitespace) => {
+                    let name = identifier(rest[8..].trim_start());
+                    let directive = &rest[..rest.len() - rest[8..].trim_start().len() + name.len()];
+                    let block = match self.lookup(name) {
+                        Some(Binding::Block(block)) => block.clone(),
+                        Some(Binding::Value(_)) => {
+                            return Err(self.err(format!("'{}' is a value, not a block", name), directive)
+                                .with_suggestion(format!("use it as a value, e.g. title:{}", name)));
+                        }
+                        None => {
+                            let mut diagnostic = self.err(format!("Unknown block '{}'", name), directive);
+                            let blocks = self.bindings.iter().filter(|(_, b, _)| matches!(b, Binding::Block(_))).map(|(n, ..)| *n);
+                            match diagnostics::suggestion_text(&diagnostics::did_you_mean(name, blocks)) {
+                                Some(help) => diagnostic = diagnostic.with_suggestion(help),
+                                None => diagnostic = diagnostic.with_suggestion(format!("define it first, e.g. let {} = {{ title:\"...\" }}", name)),
+                            }
+                            return Err(diagnostic);
+                        }
+                    };
+                    let start = text.len();
+                    text.push_str(&block);
+                    let span = Span::of(self.source, directive);
+                    substitutions.push((start..text.len(), span.start..span.end, name.to_string()));
+                    directive.len()
+                }
+                _ if boundary && is_identifier_start(ch) && text.trim_end().ends_with(':') => {
+                    let name = identifier(rest);
+                    match self.lookup(name) {
+                        Some(Binding::Value(value)) => {
+                            let start = text.len();
+                            text.push_str(value);
+                            let span = Span::of(self.source, name);
+                            substitutions.push((start..text.len(), span.start..span.end, name.to_string()));
+                        }
+                        Some(Binding::Block(_)) => {
+                            return Err(self.err(format!("'{}' is a block, not a value", name), name)
+                                .with_suggestion(format!("insert it with @include {}", name)));
+                        }
+                        None => text.push_str(name),
+                    }
+                    name.len()
+                }
+                _ => 0,
+            };
+            if used > 0 {
+                i += used;
+                continue;
+            }
+            match ch {
+                '"' => in_quotes = true,
+                '{' => depth += 1,
+                '}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+            text.push(ch);
+            i += ch.len_utf8();
+        }
+        Ok(Expansion { text, bindings: Vec::new(), substitutions })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_and_blocks_expand_in_place() {
+        let input = "let primaryAction = \"Continue\"\nlet header = { Image:\"logo\", title:\"Acme\" }\n{(width:390,height:844):{@include header, button:primaryAction}}";
+        let expansion = expand(input).unwrap();
+        assert_eq!(expansion.text.trim(), "{(width:390,height:844):{Image:\"logo\", title:\"Acme\", button:\"Continue\"}}");
+
+        // A problem inside a substitution is reported at the use
+        let name = input.rfind("primaryAction").unwrap();
+        let substituted = expansion.text.find("\"Continue\"").unwrap();
+        let span = expansion.restore(Diagnostic::error("E0000", "").with_span(Span::new(substituted, substituted + 3))).span;
+        assert_eq!(span, Some(Span::new(name, name + "primaryAction".len())));
+        let brace = expansion.text.rfind('}').unwrap();
+        assert_eq!(expansion.map(brace, false), input.rfind('}').unwrap());
+    }
+
+    #[test]
+    fn test_uses_follow_blocks() {
+        let input = "let brand = \"Acme\"\nlet unused = \"x\"\nlet header = { title:brand }\n\
+                     {(width:390,height:844):{@include header}}\n{(width:390,height:844):{button:\"Go\"}}";
+        let expansion = expand(input).unwrap();
+        let first = expansion.text.find("{(").unwrap();
+        let second = expansion.text.rfind("{(").unwrap();
+        let names = |range: Range<usize>| expansion.uses(range).into_iter().map(|bound| bound.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names(first..second), ["brand", "header"]);
+        assert!(names(second..expansion.text.len()).is_empty());
+        assert_eq!(expansion.bindings[2], Bound { name: "header".to_string(), text: "title:\"Acme\"".to_string(), uses: vec!["brand".to_string()] });
+    }
+
+    #[test]
+    fn test_binding_errors() {
+        let err = expand("{(width:390,height:844):{@include heder}} ").unwrap_err();
+        assert_eq!((err.code, err.message.as_str()), ("E0027", "Unknown block 'heder'"));
+        let err = expand("let header = {title:\"A\"}\n{(width:390,height:844):{@include heder}}").unwrap_err();
+        assert_eq!(err.suggestions, vec!["did you mean `header`?".to_string()]);
+        assert_eq!(expand("let x = \"a\"\nlet x = \"b\"").unwrap_err().message, "'x' is defined twice");
+        assert_eq!(expand("let x \"a\"").unwrap_err().message, "Expected '=' after 'let x'");
+        assert_eq!(expand("let tags = [\"a\", \"b\"]\n{Tags:tags}").unwrap().text.trim(), "{Tags:[\"a\", \"b\"]}");
+        assert_eq!(expand("let header = {title:\"A\"}\n{(width:390,height:844):{title:header}}").unwrap_err().message, "'header' is a block, not a value");
+        // Names that are not bound are left for the parser
+        assert_eq!(expand("{(width:390,height:844,orientation:landscape):{}}").unwrap().text, "{(width:390,height:844,orientation:landscape):{}}");
+    }
+}
@@ -0,0 +1,211 @@
+// File: src/input/sketch.rs
+// Importer for Sketch documents: a `.sketch` file, the ZIP of JSON pages Sketch saves, or the
+// JSON of a page or document. Each artboard is an example of the screen it is named after.
+use std::io::{Cursor, Read};
+
+use serde_json::Value;
+
+use crate::input::import::{device_safe_area, is_button_name, write_artboards, Element, Example, Import};
+
+// A layer's `frame`, as (x, y, width, height) in its parent
+fn frame(layer: &Value) -> (f64, f64, f64, f64) {
+    let number = |name: &str| layer["frame"][name].as_f64().unwrap_or(0.0);
+    (number("x"), number("y"), number("width"), number("height"))
+}
+
+fn class(layer: &Value) -> &str {
+    layer["_class"].as_str().unwrap_or("")
+}
+
+fn name(layer: &Value) -> &str {
+    layer["name"].as_str().unwrap_or("")
+}
+
+fn children(layer: &Value) -> &[Value] {
+    layer["layers"].as_array().map_or(&[], Vec::as_slice)
+}
+
+// The text of the first text layer in `layer`, or the first text a symbol overrides
+fn first_text(layer: &Value) -> Option<String> {
+    if class(layer) == "text" {
+        return layer["attributedString"]["string"].as_str().map(str::to_string);
+    }
+    let overridden = layer["overrideValues"].as_array().and_then(|overrides| {
+        overrides
+            .iter()
+            .find(|value| value["overrideName"].as_str().is_some_and(|name| name.ends_with("_stringValue")))
+            .and_then(|value| value["value"].as_str())
+    });
+    overridden.map(str::to_string).or_else(|| children(layer).iter().find_map(first_text))
+}
+
+// Whether a layer is filled with an image rather than a color
+fn has_image_fill(layer: &Value) -> bool {
+    layer["style"]["fills"]
+        .as_array()
+        .is_some_and(|fills| fills.iter().any(|fill| fill["fillType"].as_i64() == Some(4) && fill["isEnabled"].as_bool() != Some(false)))
+}
+
+struct Walker<'a> {
+    artboard: &'a str,
+    elements: Vec<Element>,
+    warnings: Vec<String>,
+}
+
+impl Walker<'_> {
+    // Converts the layers of a group whose top left corner is at (`x`, `y`) on the artboard
+    fn layers(&mut self, layers: &[Value], x: f64, y: f64) {
+        for layer in layers.iter().filter(|layer| layer["isVisible"].as_bool() != Some(false)) {
+            let (left, top, width, height) = frame(layer);
+            let bounds = (x + left, y + top, width, height);
+            let element = match class(layer) {
+                "text" => Element::new("title", first_text(layer).as_deref(), bounds),
+                "group" | "symbolInstance" | "shapeGroup" if is_button_name(name(layer)) => Element::new("button", first_text(layer).as_deref(), bounds),
+                "bitmap" => Element::new("Image", Some(name(layer)), bounds),
+                _ if has_image_fill(layer) => Element::new("Image", Some(name(layer)), bounds),
+                "group" => {
+                    self.layers(children(layer), bounds.0, bounds.1);
+                    continue;
+                }
+                "symbolInstance" => {
+                    self.warnings.push(format!("{}: skipped the symbol '{}', which is not a button", self.artboard, name(layer)));
+                    continue;
+                }
+                // Shapes are backgrounds and decoration
+                _ => continue,
+            };
+            self.elements.push(element);
+        }
+    }
+}
+
+fn artboards<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(object) if object.get("_class").and_then(Value::as_str) == Some("artboard") => out.push(value),
+        // Symbol masters are the components symbols are instances of, not screens
+        Value::Object(object) if object.get("_class").and_then(Value::as_str) == Some("symbolMaster") => {}
+        Value::Object(object) => object.values().for_each(|value| artboards(value, out)),
+        Value::Array(values) => values.iter().for_each(|value| artboards(value, out)),
+        _ => {}
+    }
+}
+
+// The JSON of the pages of a `.sketch` file
+fn pages(bytes: &[u8]) -> Result<Vec<Value>, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let mut pages = Vec::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index).map_err(|e| e.to_string())?;
+        if !(file.name().starts_with("pages/") && file.name().ends_with(".json")) {
+            continue;
+        }
+        let mut contents = String::new();
+        file.read_to_string(&mut contents).map_err(|e| format!("{}: {}", file.name(), e))?;
+        pages.push(serde_json::from_str(&contents).map_err(|e| format!("{}: {}", file.name(), e))?);
+    }
+    Ok(pages)
+}
+
+/// Converts a Sketch document, as a `.sketch` file's bytes or JSON, to a spec. Text layers
+/// become titles, bitmaps and image-filled layers images named after the layer, and groups and
+/// symbols named as buttons (see [`is_button_name`]) buttons titled by their first text.
+/// Other groups are looked into; shapes are taken for decoration and left out.
+pub fn import(bytes: &[u8]) -> Result<Import, String> {
+    let documents = match bytes.starts_with(b"PK") {
+        true => pages(bytes)?,
+        false => vec![serde_json::from_slice(bytes).map_err(|e| e.to_string())?],
+    };
+    let mut found = Vec::new();
+    documents.iter().for_each(|document| artboards(document, &mut found));
+    if found.is_empty() {
+        return Err("found no artboards".to_string());
+    }
+    let mut warnings = Vec::new();
+    let mut examples = Vec::new();
+    for artboard in found {
+        let (_, _, width, height) = frame(artboard);
+        let mut walker = Walker { artboard: name(artboard), elements: Vec::new(), warnings: Vec::new() };
+        walker.layers(children(artboard), 0.0, 0.0);
+        if walker.elements.is_empty() {
+            walker.warnings.push(format!("{}: found no texts, buttons or images", name(artboard)));
+        }
+        warnings.append(&mut walker.warnings);
+        let insets = device_safe_area(width, height).unwrap_or_default();
+        examples.push((name(artboard).to_string(), Example { width, height, insets, elements: walker.elements }));
+    }
+    Ok(Import { spec: write_artboards(examples), warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn page() -> Value {
+        serde_json::json!({
+            "_class": "page",
+            "name": "Page 1",
+            "layers": [
+                {
+                    "_class": "artboard",
+                    "name": "Login - iPhone",
+                    "frame": {"_class": "rect", "x": 0, "y": 0, "width": 393, "height": 852},
+                    "layers": [
+                        {"_class": "rectangle", "name": "Background", "frame": {"x": 0, "y": 0, "width": 393, "height": 852}},
+                        {
+                            "_class": "group",
+                            "name": "Header",
+                            "frame": {"x": 20, "y": 100, "width": 353, "height": 200},
+                            "layers": [
+                                {"_class": "bitmap", "name": "logo", "frame": {"x": 126, "y": 0, "width": 100, "height": 100}},
+                                {"_class": "text", "name": "Title", "frame": {"x": 0, "y": 120, "width": 353, "height": 34},
+                                 "attributedString": {"_class": "attributedString", "string": "Welcome"}},
+                                {"_class": "text", "name": "Hidden", "isVisible": false, "frame": {"x": 0, "y": 160, "width": 353, "height": 20},
+                                 "attributedString": {"string": "Error"}}
+                            ]
+                        },
+                        {"_class": "symbolInstance", "name": "Button/Primary", "frame": {"x": 20, "y": 760, "width": 353, "height": 44},
+                         "overrideValues": [{"overrideName": "A1B2_stringValue", "value": "Sign in"}]},
+                        {"_class": "symbolInstance", "name": "Status Bar", "frame": {"x": 0, "y": 0, "width": 393, "height": 54}}
+                    ]
+                },
+                {
+                    "_class": "artboard",
+                    "name": "Login - iPad",
+                    "frame": {"x": 500, "y": 0, "width": 820, "height": 1180},
+                    "layers": [
+                        {"_class": "group", "name": "btn-sign-in", "frame": {"x": 235, "y": 1080, "width": 350, "height": 50},
+                         "layers": [{"_class": "text", "frame": {"x": 140, "y": 15, "width": 70, "height": 20}, "attributedString": {"string": "Sign in"}}]}
+                    ]
+                },
+                {"_class": "symbolMaster", "name": "Button/Primary", "frame": {"x": 0, "y": 0, "width": 353, "height": 44}, "layers": []}
+            ]
+        })
+    }
+
+    #[test]
+    fn test_artboards_become_examples() {
+        let import = import(page().to_string().as_bytes()).unwrap();
+        assert_eq!(
+            import.spec,
+            concat!(
+                "Login:{(width:393,height:852,safe_top:59,safe_bottom:34):{",
+                "Image:\"logo\"@(x:146,y:100,w:100,h:100),title:\"Welcome\"@(x:20,y:220,w:353),button:\"Sign in\"@(x:20,y:760,w:353,h:44)}}",
+                "{(width:820,height:1180,safe_top:24,safe_bottom:20):{button:\"Sign in\"@(x:235,y:1080,w:350,h:50)}}"
+            )
+        );
+        assert_eq!(import.warnings, vec!["Login - iPhone: skipped the symbol 'Status Bar', which is not a button"]);
+    }
+
+    #[test]
+    fn test_sketch_files_are_read_page_by_page() {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("document.json", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"{\"_class\": \"document\"}").unwrap();
+        zip.start_file("pages/6A1B.json", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(page().to_string().as_bytes()).unwrap();
+        let bytes = zip.finish().unwrap().into_inner();
+        assert_eq!(import(&bytes).unwrap(), import(page().to_string().as_bytes()).unwrap());
+        assert_eq!(import(b"{\"_class\": \"page\", \"layers\": []}").unwrap_err(), "found no artboards");
+    }
+}
@@ -0,0 +1,266 @@
+// File: src/input/storyboard.rs
+// Importer for Interface Builder files: reads the scenes of a `.storyboard`, or the top-level
+// views of a `.xib`, and writes each as a named screen of a spec whose elements state the
+// frames they have in the file, so synthesis finds the SwiftUI layout that reproduces them.
+use roxmltree::{Document, Node};
+
+use crate::input::import::{device_safe_area, screen_name, write_screen, Element, Example, Import};
+use crate::layout::devices::Insets;
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str, key: Option<&str>) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(tag) && (key.is_none() || child.attribute("key") == key))
+}
+
+fn number(node: Node, name: &str) -> f64 {
+    node.attribute(name).and_then(|value| value.parse().ok()).unwrap_or(0.0)
+}
+
+// The `frame` rect of a view, as (x, y, width, height) in its superview
+fn frame(view: Node) -> Option<(f64, f64, f64, f64)> {
+    let rect = child(view, "rect", Some("frame"))?;
+    Some((number(rect, "x"), number(rect, "y"), number(rect, "width"), number(rect, "height")))
+}
+
+// A label's text, given as an attribute or, for several lines, a `string` element
+fn label_text(label: Node) -> Option<String> {
+    label.attribute("text").map(str::to_string).or_else(|| child(label, "string", Some("text")).and_then(|string| string.text()).map(str::to_string))
+}
+
+// A button's title in its normal state, or in its configuration on iOS 15 and later
+fn button_title(button: Node) -> Option<String> {
+    button
+        .children()
+        .find(|child| child.has_tag_name("state") && child.attribute("key") == Some("normal"))
+        .and_then(|state| state.attribute("title"))
+        .or_else(|| child(button, "buttonConfiguration", Some("configuration")).and_then(|configuration| configuration.attribute("title")))
+        .map(str::to_string)
+}
+
+// What a view is called in warnings: its label in Interface Builder's outline, or its id
+fn describe(view: Node) -> String {
+    let name = view.attribute("userLabel").or(view.attribute("id")).unwrap_or("?");
+    format!("{} '{}'", view.tag_name().name(), name)
+}
+
+// Views laid out by their subviews alone, whose subviews are taken instead
+const CONTAINERS: &[&str] = &["view", "stackView", "scrollView", "visualEffectView"];
+
+struct Walker<'a> {
+    screen: &'a str,
+    elements: Vec<Element>,
+    warnings: Vec<String>,
+}
+
+impl Walker<'_> {
+    // Converts the subviews of `view`, whose top left corner is at (`x`, `y`) on the screen
+    fn subviews(&mut self, view: Node, x: f64, y: f64) {
+        let Some(subviews) = child(view, "subviews", None) else { return };
+        for subview in subviews.children().filter(Node::is_element) {
+            if subview.attribute("hidden") == Some("YES") {
+                continue;
+            }
+            let (left, top, width, height) = frame(subview).unwrap_or_default();
+            let (left, top) = (x + left, y + top);
+            let converted = match subview.tag_name().name() {
+                "label" => Some(("title", label_text(subview))),
+                "button" => Some(("button", button_title(subview))),
+                "imageView" => Some(("Image", subview.attribute("image").map(str::to_string))),
+                tag if CONTAINERS.contains(&tag) => {
+                    self.subviews(subview, left, top);
+                    None
+                }
+                _ => {
+                    self.warnings.push(format!("{}: skipped {}, which has no spec element", self.screen, describe(subview)));
+                    None
+                }
+            };
+            if let Some((key, content)) = converted {
+                self.elements.push(Element::new(key, content.as_deref(), (left, top, width, height)));
+            }
+        }
+    }
+}
+
+// The safe area of a root view: its layout guide's frame when the file has one, or else that
+// of the device of its size
+fn safe_area(view: Node, width: f64, height: f64) -> Insets {
+    let guide = view.children().find(|child| child.has_tag_name("viewLayoutGuide") && child.attribute("key") == Some("safeArea"));
+    if let Some((x, y, guide_width, guide_height)) = guide.and_then(frame) {
+        return Insets::new(y, height - y - guide_height, x, width - x - guide_width);
+    }
+    device_safe_area(width, height).unwrap_or_default()
+}
+
+// The screen of a root view named `name`
+fn screen(name: &str, view: Node, warnings: &mut Vec<String>) -> String {
+    let (_, _, width, height) = frame(view).unwrap_or_default();
+    let mut walker = Walker { screen: name, elements: Vec::new(), warnings: Vec::new() };
+    walker.subviews(view, 0.0, 0.0);
+    if walker.elements.is_empty() {
+        walker.warnings.push(format!("{}: found no labels, buttons or images", name));
+    }
+    warnings.append(&mut walker.warnings);
+    write_screen(name, vec![Example { width, height, insets: safe_area(view, width, height), elements: walker.elements }])
+}
+
+/// Converts the Interface Builder file `xml` to a spec of one screen per view controller with
+/// a view, for a storyboard, or per top-level view, for a XIB. Screens are named after the
+/// controller's storyboard ID or class, e.g. `LoginViewController` becomes `Login`; a XIB's
+/// views after `file_name`, the name of the file without its extension.
+pub fn import(xml: &str, file_name: &str) -> Result<Import, String> {
+    let document = Document::parse(xml).map_err(|e| e.to_string())?;
+    let root = document.root_element();
+    if !root.has_tag_name("document") || !root.attribute("type").is_some_and(|kind| kind.starts_with("com.apple.InterfaceBuilder")) {
+        return Err("not an Interface Builder document".to_string());
+    }
+    let mut names: Vec<String> = Vec::new();
+    let mut screens = Vec::new();
+    let mut warnings = Vec::new();
+    let mut add = |name: String, view: Node, warnings: &mut Vec<String>| {
+        let name = (1..)
+            .map(|n| if n == 1 { name.clone() } else { format!("{}{}", name, n) })
+            .find(|name| !names.contains(name))
+            .expect("some number is free");
+        screens.push(screen(&name, view, warnings));
+        names.push(name);
+    };
+    match child(root, "scenes", None) {
+        Some(scenes) => {
+            for (index, scene) in scenes.children().filter(|child| child.has_tag_name("scene")).enumerate() {
+                let controller = child(scene, "objects", None).and_then(|objects| objects.children().find(|object| child(*object, "view", Some("view")).is_some()));
+                let Some(controller) = controller else { continue };
+                let class = controller.attribute("customClass").map(|class| class.strip_suffix("ViewController").or(class.strip_suffix("Controller")).unwrap_or(class));
+                let name = controller.attribute("storyboardIdentifier").or(class).or(controller.attribute("title"));
+                let name = name.map_or(format!("Scene{}", index + 1), screen_name);
+                add(name, child(controller, "view", Some("view")).expect("found above"), &mut warnings);
+            }
+        }
+        None => {
+            let stem = screen_name(file_name.strip_suffix("View").unwrap_or(file_name));
+            let objects = child(root, "objects", None).ok_or("the XIB has no objects")?;
+            for view in objects.children().filter(|object| CONTAINERS.contains(&object.tag_name().name())) {
+                add(stem.clone(), view, &mut warnings);
+            }
+        }
+    }
+    if screens.is_empty() {
+        return Err("found no view controllers or views to import".to_string());
+    }
+    Ok(Import { spec: screens.join("\n"), warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STORYBOARD: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<document type="com.apple.InterfaceBuilder3.CocoaTouch.Storyboard.XIB" version="3.0" toolsVersion="22505" targetRuntime="iOS.CocoaTouch" initialViewController="BYZ-38-t0r">
+    <device id="retina6_12" orientation="portrait" appearance="light"/>
+    <scenes>
+        <scene sceneID="tne-QT-ifu">
+            <objects>
+                <viewController storyboardIdentifier="login" id="BYZ-38-t0r" customClass="LoginViewController" sceneMemberID="viewController">
+                    <view key="view" contentMode="scaleToFill" id="8bC-Xf-vdC">
+                        <rect key="frame" x="0.0" y="0.0" width="393" height="852"/>
+                        <subviews>
+                            <button opaque="NO" id="b1" userLabel="Sign In">
+                                <rect key="frame" x="20" y="760" width="353" height="44"/>
+                                <state key="normal" title="Sign in"/>
+                            </button>
+                            <stackView opaque="NO" axis="vertical" id="s1">
+                                <rect key="frame" x="20" y="120" width="353" height="180"/>
+                                <subviews>
+                                    <imageView id="i1" image="logo">
+                                        <rect key="frame" x="126" y="0.0" width="100" height="100"/>
+                                    </imageView>
+                                    <label id="l1" text="Say &quot;hi&quot;">
+                                        <rect key="frame" x="0.0" y="120" width="353" height="34.5"/>
+                                    </label>
+                                </subviews>
+                            </stackView>
+                            <textField id="t1" userLabel="Email" placeholder="Email">
+                                <rect key="frame" x="20" y="400" width="353" height="34"/>
+                            </textField>
+                            <label hidden="YES" id="l2" text="Error">
+                                <rect key="frame" x="20" y="450" width="353" height="20"/>
+                            </label>
+                        </subviews>
+                        <viewLayoutGuide key="safeArea" id="6Tk-OE-BBY"/>
+                    </view>
+                </viewController>
+            </objects>
+        </scene>
+        <scene sceneID="nav">
+            <objects>
+                <navigationController id="n1" sceneMemberID="viewController"/>
+            </objects>
+        </scene>
+        <scene sceneID="home">
+            <objects>
+                <viewController id="h1" customClass="HomeViewController" sceneMemberID="viewController">
+                    <view key="view" id="hv">
+                        <rect key="frame" x="0.0" y="0.0" width="393" height="852"/>
+                        <subviews>
+                            <label id="hl">
+                                <rect key="frame" x="20" y="100" width="353" height="30"/>
+                                <string key="text">Welcome
+back</string>
+                            </label>
+                            <button id="hb">
+                                <rect key="frame" x="20" y="140" width="353" height="44"/>
+                                <buttonConfiguration key="configuration" style="filled" title="Continue"/>
+                            </button>
+                        </subviews>
+                    </view>
+                </viewController>
+            </objects>
+        </scene>
+    </scenes>
+</document>"#;
+
+    #[test]
+    fn test_storyboard_scenes_become_named_screens() {
+        let import = import(STORYBOARD, "Main").unwrap();
+        assert_eq!(
+            import.spec,
+            concat!(
+                "Login:{(width:393,height:852,safe_top:59,safe_bottom:34):{",
+                "Image:\"logo\"@(x:146,y:120,w:100,h:100),",
+                "title:\"Say \\\"hi\\\"\"@(x:20,y:240,w:353),",
+                "button:\"Sign in\"@(x:20,y:760,w:353,h:44)}}\n",
+                "Home:{(width:393,height:852,safe_top:59,safe_bottom:34):{",
+                "title:\"Welcome\\nback\"@(x:20,y:100,w:353),",
+                "button:\"Continue\"@(x:20,y:140,w:353,h:44)}}"
+            )
+        );
+        assert_eq!(import.warnings, vec!["Login: skipped textField 'Email', which has no spec element"]);
+    }
+
+    #[test]
+    fn test_xib_views_are_named_after_the_file() {
+        let xib = r#"<document type="com.apple.InterfaceBuilder3.CocoaTouch.XIB" version="3.0">
+    <objects>
+        <placeholder placeholderIdentifier="IBFilesOwner" id="-1" userLabel="File's Owner"/>
+        <view contentMode="scaleToFill" id="iN0-l3-epB">
+            <rect key="frame" x="0.0" y="0.0" width="320" height="200"/>
+            <subviews>
+                <button id="b1"><rect key="frame" x="10" y="10" width="100" height="44"/></button>
+            </subviews>
+            <viewLayoutGuide key="safeArea" id="vUN-kp-3ea">
+                <rect key="frame" x="0.0" y="20" width="320" height="180"/>
+            </viewLayoutGuide>
+        </view>
+    </objects>
+</document>"#;
+        let import = import(xib, "ProfileCardView").unwrap();
+        assert_eq!(import.spec, "ProfileCard:{(width:320,height:200,safe_top:20):{button:*@(x:10,y:10,w:100,h:44)}}");
+        assert!(import.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_other_documents_are_rejected() {
+        assert!(import("<document type=\"com.apple.InterfaceBuilder3.CocoaTouch.XIB\"><objects/></document>", "Empty").unwrap_err().contains("no view controllers"));
+        assert_eq!(import("<plist/>", "Info").unwrap_err(), "not an Interface Builder document");
+        assert!(import("<document", "Broken").is_err());
+    }
+}
@@ -0,0 +1,304 @@
+// File: src/input/swift.rs
+// Reverse parser: reads SwiftUI code (as produced by `output::render`) back into IR.
+use crate::ast::IR;
+use crate::input::lexer;
+
+/// Parses a SwiftUI view expression back into IR.
+///
+/// Only the vocabulary the renderer emits is understood. Modifier chains
+/// (`.padding()`, `.font(.title)`, ...) are skipped, and a surrounding
+/// `struct X: View { var body: some View { ... } }` wrapper, public or not, and a
+/// `ScrollView` around the content are unwrapped.
+pub fn parse_swiftui(code: &str) -> Result<IR, String> {
+    let mut parser = SwiftParser { code, pos: 0 };
+    parser.skip_trivia();
+    if matches!(parser.peek_ident().as_deref(), Some("struct" | "public" | "import")) {
+        parser.seek_body()?;
+    }
+    let ir = parser.parse_view()?;
+    parser.skip_trivia();
+    if parser.pos < code.len() && parser.peek() != Some('}') {
+        return Err(format!("Unexpected trailing content at offset {}", parser.pos));
+    }
+    Ok(ir)
+}
+
+// Reads `code` from `pos`, a byte offset that only ever moves by whole characters, so the
+// offsets in error messages are the same kind the spec parser's diagnostics use.
+struct SwiftParser<'a> {
+    code: &'a str,
+    pos: usize,
+}
+
+impl SwiftParser<'_> {
+    fn rest(&self) -> &str {
+        &self.code[self.pos..]
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    // Moves past the next character
+    fn bump(&mut self) {
+        self.pos += self.peek().map_or(0, char::len_utf8);
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            self.pos = self.code.len() - self.rest().trim_start().len();
+            if self.rest().starts_with("//") {
+                self.pos += self.rest().find('\n').unwrap_or(self.rest().len());
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn next_non_trivia(&mut self) -> Option<char> {
+        let saved = self.pos;
+        self.skip_trivia();
+        let next = self.peek();
+        self.pos = saved;
+        next
+    }
+
+    fn peek_ident(&self) -> Option<String> {
+        let rest = self.rest();
+        let ident = &rest[..rest.find(|c: char| !(c.is_alphanumeric() || c == '_')).unwrap_or(rest.len())];
+        (!ident.is_empty()).then(|| ident.to_string())
+    }
+
+    fn ident(&mut self) -> Result<String, String> {
+        self.skip_trivia();
+        let ident = self.peek_ident().ok_or_else(|| format!("Expected a view name at offset {}", self.pos))?;
+        self.pos += ident.len();
+        Ok(ident)
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        self.skip_trivia();
+        if self.peek() == Some(expected) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(format!(
+                "Expected '{}' at offset {}, found {:?}",
+                expected,
+                self.pos,
+                self.peek()
+            ))
+        }
+    }
+
+    // Skips forward to the first view inside `var body: some View {`.
+    fn seek_body(&mut self) -> Result<(), String> {
+        let body = self.code.find("some View").ok_or("Could not find `var body: some View` in Swift source")?;
+        let brace = self.code[body..].find('{').ok_or("Missing '{' after `some View`")?;
+        self.pos = body + brace + 1;
+        Ok(())
+    }
+
+    fn string_literal(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        // Escapes are resolved once the closing quote is found
+        let start = self.pos;
+        loop {
+            match self.peek() {
+                None => return Err("Unterminated string literal".to_string()),
+                Some('"') => {
+                    let raw = &self.code[start..self.pos];
+                    self.bump();
+                    return lexer::unescape(raw).map_err(|(message, _)| message);
+                }
+                Some('\\') => {
+                    self.bump();
+                    if self.peek().is_none() {
+                        return Err("Unterminated escape in string literal".to_string());
+                    }
+                    self.bump();
+                }
+                Some(_) => self.bump(),
+            }
+        }
+    }
+
+    // Skips a balanced `(...)` or `{...}` group, honouring string literals.
+    fn skip_group(&mut self, open: char, close: char) -> Result<(), String> {
+        self.expect(open)?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                None => return Err(format!("Unbalanced '{}'", open)),
+                Some('"') => {
+                    self.string_literal()?;
+                    continue;
+                }
+                Some(c) if c == open => depth += 1,
+                Some(c) if c == close => depth -= 1,
+                _ => {}
+            }
+            self.bump();
+        }
+        Ok(())
+    }
+
+    fn skip_modifiers(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_trivia();
+            if self.peek() != Some('.') {
+                return Ok(());
+            }
+            self.bump();
+            self.ident()?;
+            self.skip_trivia();
+            if self.peek() == Some('(') {
+                self.skip_group('(', ')')?;
+            }
+        }
+    }
+
+    fn parse_view(&mut self) -> Result<IR, String> {
+        let name = self.ident()?;
+        let ir = match name.as_str() {
+            "VStack" | "HStack" => {
+                self.skip_trivia();
+                if self.peek() == Some('(') {
+                    self.skip_group('(', ')')?;
+                }
+                self.expect('{')?;
+                let mut children = Vec::new();
+                loop {
+                    self.skip_trivia();
+                    match self.peek() {
+                        Some('}') => {
+                            self.bump();
+                            break;
+                        }
+                        None => return Err(format!("Unclosed {} block", name)),
+                        _ => children.push(self.parse_view()?),
+                    }
+                }
+                if name == "VStack" { IR::VStack(children) } else { IR::HStack(children) }
+            }
+            "Text" | "Image" => {
+                self.expect('(')?;
+                let value = self.string_literal()?;
+                self.expect(')')?;
+                if name == "Text" { IR::Text(value) } else { IR::Image(value) }
+            }
+            "Button" => {
+                self.expect('(')?;
+                let label = self.string_literal()?;
+                self.expect(')')?;
+                self.skip_trivia();
+                if self.peek() == Some('{') {
+                    self.skip_group('{', '}')?;
+                }
+                IR::Button(label)
+            }
+            "Spacer" => {
+                self.expect('(')?;
+                self.expect(')')?;
+                IR::Spacer
+            }
+            // The renderer puts a screen that scrolls in a ScrollView, which the IR leaves out
+            "ScrollView" => {
+                self.skip_trivia();
+                if self.peek() == Some('(') {
+                    self.skip_group('(', ')')?;
+                }
+                self.expect('{')?;
+                let content = self.parse_view()?;
+                self.expect('}')?;
+                content
+            }
+            // Any other `Name(args)` call is kept verbatim as a custom view
+            other if self.next_non_trivia() == Some('(') => {
+                self.skip_trivia();
+                let start = self.pos + 1;
+                self.skip_group('(', ')')?;
+                let args = &self.code[start..self.pos - 1];
+                IR::Custom { name: other.to_string(), args: args.trim().to_string() }
+            }
+            other => return Err(format!("Unsupported SwiftUI view '{}'", other)),
+        };
+        self.skip_modifiers()?;
+        Ok(ir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::render::render_swiftui;
+
+    #[test]
+    fn test_parse_rendered_vstack() {
+        let ir = IR::VStack(vec![
+            IR::Image("icon".to_string()),
+            IR::Text("Hello".to_string()),
+            IR::Spacer,
+            IR::Button("Click".to_string()),
+        ]);
+        assert_eq!(parse_swiftui(&render_swiftui(&ir)).unwrap(), ir);
+    }
+
+    #[test]
+    fn test_parse_nested_and_escaped() {
+        let ir = IR::VStack(vec![
+            IR::Text("Hello, \"World\"!".to_string()),
+            IR::Text("Line one\nLine two\t👋 \u{7}".to_string()),
+            IR::HStack(vec![IR::Text("A".to_string()), IR::Spacer]),
+        ]);
+        assert_eq!(parse_swiftui(&render_swiftui(&ir)).unwrap(), ir);
+    }
+
+    #[test]
+    fn test_parse_view_struct_wrapper() {
+        let code = "import SwiftUI\n\nstruct ContentView: View {\n    var body: some View {\n        HStack(spacing: 8) {\n            Text(\"A\") // label\n            Spacer()\n        }\n    }\n}\n";
+        assert_eq!(
+            parse_swiftui(code).unwrap(),
+            IR::HStack(vec![IR::Text("A".to_string()), IR::Spacer])
+        );
+    }
+
+    #[test]
+    fn test_parse_custom_view() {
+        let ir = IR::VStack(vec![
+            IR::Custom { name: "MyChartView".to_string(), args: "data: points.map { $0 * 2 }".to_string() },
+            IR::Spacer,
+        ]);
+        assert_eq!(parse_swiftui(&render_swiftui(&ir)).unwrap(), ir);
+    }
+
+    #[test]
+    fn test_parse_scroll_view() {
+        let code = "ScrollView(.vertical) {\n    VStack {\n        Text(\"Hi\")\n    }\n}\n.ignoresSafeArea()";
+        assert_eq!(parse_swiftui(code).unwrap(), IR::VStack(vec![IR::Text("Hi".to_string())]));
+        assert!(parse_swiftui("ScrollView { Text(\"A\") Text(\"B\") }").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_swiftui("ZStack { }").unwrap_err().contains("Unsupported SwiftUI view 'ZStack'"));
+        assert!(parse_swiftui("VStack { Text(\"A\")").is_err());
+        assert!(parse_swiftui("Text(\"unterminated)").is_err());
+    }
+
+    #[test]
+    fn test_multibyte_source() {
+        let code = "// 画面 👋\nVStack {\n    Text(\"こんにちは\") // 挨拶\n    RatingView(stars: \"★★★\")\n    Image(\"e\u{301}toile\")\n}\n";
+        assert_eq!(
+            parse_swiftui(code).unwrap(),
+            IR::VStack(vec![
+                IR::Text("こんにちは".to_string()),
+                IR::Custom { name: "RatingView".to_string(), args: "stars: \"★★★\"".to_string() },
+                IR::Image("e\u{301}toile".to_string()),
+            ])
+        );
+        // Offsets are in bytes, like the spans of spec diagnostics
+        let err = parse_swiftui("VStack { Text(\"日本\") 👋 }").unwrap_err();
+        assert_eq!(err, format!("Expected a view name at offset {}", "VStack { Text(\"日本\") ".len()));
+    }
+}
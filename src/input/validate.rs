@@ -0,0 +1,97 @@
+// File: src/input/validate.rs
+// Decides what to make of element input the parser understands but that is not written as
+// specified: keys it does not know and values without quotes. `--strict` rejects both; the
+// lenient default accepts what it can read unambiguously and warns about it.
+use crate::diagnostics::{self, Diagnostic, Span};
+use crate::input::parser::{ParseOptions, SUPPORTED_ELEMENT_KEYS};
+
+/// Checks an element key that is neither built in nor a registered component. Leniently, a
+/// key named like a view type that is not a misspelling of a known key, such as `RatingView`,
+/// is accepted as a custom view of that name: the warning is returned. Otherwise the error is.
+pub fn unknown_key(source: &str, key: &str, options: &ParseOptions) -> Result<Diagnostic, Diagnostic> {
+    let typo = typo(key, options);
+    let view_type = key.starts_with(|c: char| c.is_ascii_uppercase()) && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if options.strict || typo.is_some() || !view_type {
+        let diagnostic = Diagnostic::error(
+            "E0012",
+            format!("Unsupported element key '{}': must be 'title', 'button', 'Image', 'Custom', 'hint', or 'constraints'", key),
+        )
+        .with_span(Span::of(source, key));
+        return Err(match typo {
+            Some(help) => diagnostic.with_suggestion(help),
+            None if !options.strict => diagnostic.with_suggestion("custom views are named like types, e.g. RatingView:\"4\""),
+            None => diagnostic,
+        });
+    }
+    Ok(Diagnostic::warning("W0001", format!("Unknown element key '{}' is taken as a custom view", key))
+        .with_span(Span::of(source, key))
+        .with_suggestion(format!("register '{}' in components.toml, or pass --strict to reject unknown keys", key)))
+}
+
+/// Whether an element key that is neither built in nor a registered component may hold data,
+/// a number, boolean or array such as `rating:4.5`: it is named like a property, not a view
+/// type, and is not a misspelling of a known key. Data is part of the format, so `--strict`
+/// accepts it too.
+pub fn data_key(key: &str, options: &ParseOptions) -> bool {
+    key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && typo(key, options).is_none()
+}
+
+// The known key `key` looks like a misspelling of, as a suggestion.
+fn typo(key: &str, options: &ParseOptions) -> Option<String> {
+    let candidates = SUPPORTED_ELEMENT_KEYS
+        .iter()
+        .copied()
+        .chain(options.components.components.iter().map(|c| c.name.as_str()));
+    diagnostics::suggestion_text(&diagnostics::did_you_mean(key, candidates))
+}
+
+/// Checks an element value written without double quotes. Leniently, a simple value (letters,
+/// digits, spaces, `_`, `.` and `-`) is taken as written: the warning is returned. Otherwise
+/// the error is.
+pub fn unquoted_value(source: &str, key: &str, value: &str, options: &ParseOptions) -> Result<Diagnostic, Diagnostic> {
+    let suggestion = format!("write it as {}:\"{}\"", key, value.trim_matches('"'));
+    let simple = !value.is_empty() && value.chars().all(|c| c.is_alphanumeric() || matches!(c, ' ' | '_' | '.' | '-'));
+    if options.strict || !simple {
+        return Err(Diagnostic::error(
+            "E0014",
+            format!("Value for key '{}' must be enclosed in double quotes: got '{}'", key, value),
+        )
+        .with_span(Span::of(source, value))
+        .with_suggestion(suggestion));
+    }
+    Ok(Diagnostic::warning("W0002", format!("Value for key '{}' is not quoted", key))
+        .with_span(Span::of(source, value))
+        .with_suggestion(suggestion))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strict() -> ParseOptions {
+        ParseOptions { strict: true, ..ParseOptions::default() }
+    }
+
+    #[test]
+    fn test_unknown_keys() {
+        let lenient = ParseOptions::default();
+        let warning = unknown_key("RatingView", "RatingView", &lenient).unwrap();
+        assert_eq!((warning.code, warning.span), ("W0001", Some(Span::new(0, 10))));
+        assert_eq!(unknown_key("RatingView", "RatingView", &strict()).unwrap_err().code, "E0012");
+        // Misspellings and lowercase keys are never taken as views
+        let err = unknown_key("Imag", "Imag", &lenient).unwrap_err();
+        assert!(err.suggestions[0].starts_with("did you mean `Image`"));
+        assert_eq!(unknown_key("subtitle", "subtitle", &lenient).unwrap_err().code, "E0012");
+    }
+
+    #[test]
+    fn test_unquoted_values() {
+        let lenient = ParseOptions::default();
+        assert_eq!(unquoted_value("Sign in", "title", "Sign in", &lenient).unwrap().code, "W0002");
+        assert_eq!(unquoted_value("Sign in", "title", "Sign in", &strict()).unwrap_err().code, "E0014");
+        let err = unquoted_value("\"Sign in", "title", "\"Sign in", &lenient).unwrap_err();
+        assert_eq!(err.suggestions, vec!["write it as title:\"Sign in\"".to_string()]);
+    }
+}
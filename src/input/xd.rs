@@ -0,0 +1,207 @@
+// File: src/input/xd.rs
+// Importer for Adobe XD documents: a `.xd` file is a ZIP whose `manifest` lists the artboards
+// and whose `artwork/<artboard>/graphics/graphicContent.agc` holds each one's scene graph.
+// Each artboard is an example of the screen it is named after.
+use std::io::{Cursor, Read};
+
+use serde_json::Value;
+
+use crate::input::import::{device_safe_area, is_button_name, write_artboards, Element, Example, Import};
+
+type Archive<'a> = zip::ZipArchive<Cursor<&'a [u8]>>;
+
+fn read_json(archive: &mut Archive, path: &str) -> Result<Value, String> {
+    let mut file = archive.by_name(path).map_err(|e| format!("{}: {}", path, e))?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).map_err(|e| format!("{}: {}", path, e))?;
+    serde_json::from_str(&contents).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn children(value: &Value) -> &[Value] {
+    value["children"].as_array().map_or(&[], Vec::as_slice)
+}
+
+fn name(node: &Value) -> &str {
+    node["name"].as_str().unwrap_or("")
+}
+
+// The artboards the manifest lists, as (path, name, width, height)
+fn manifest_artboards(manifest: &Value, out: &mut Vec<(String, String, f64, f64)>) {
+    for entry in children(manifest) {
+        match entry["path"].as_str() {
+            Some(path) if path.starts_with("artboard-") => {
+                let bounds = &entry["uxdesign#bounds"];
+                let number = |name: &str| bounds[name].as_f64().unwrap_or(0.0);
+                out.push((path.to_string(), name(entry).to_string(), number("width"), number("height")));
+            }
+            _ => manifest_artboards(entry, out),
+        }
+    }
+}
+
+// A node's bounds in its group, when its kind states them: a shape's, an image's or an area
+// text's. A text set at a point has no width, and its top is its baseline less its font size.
+fn bounds(node: &Value) -> Option<(f64, f64, Option<f64>, Option<f64>)> {
+    let (x, y) = (node["transform"]["tx"].as_f64().unwrap_or(0.0), node["transform"]["ty"].as_f64().unwrap_or(0.0));
+    match node["type"].as_str()? {
+        "text" => {
+            let frame = &node["text"]["frame"];
+            match frame["type"].as_str() {
+                Some("area") => Some((x, y, frame["width"].as_f64(), frame["height"].as_f64())),
+                _ => Some((x, y - node["style"]["font"]["size"].as_f64().unwrap_or(0.0), None, None)),
+            }
+        }
+        "shape" => {
+            let shape = &node["shape"];
+            let number = |name: &str| shape[name].as_f64().unwrap_or(0.0);
+            Some((x + number("x"), y + number("y"), Some(number("width")), Some(number("height"))))
+        }
+        _ => None,
+    }
+}
+
+fn group_children(node: &Value) -> &[Value] {
+    children(&node["group"])
+}
+
+// The text of the first text node in `node`
+fn first_text(node: &Value) -> Option<String> {
+    match node["type"].as_str() {
+        Some("text") => node["text"]["rawText"].as_str().map(str::to_string),
+        _ => group_children(node).iter().find_map(first_text),
+    }
+}
+
+// The union of the bounds of the shapes and area texts in the group `node`, which is at (`x`, `y`)
+fn extent(node: &Value, x: f64, y: f64) -> Option<(f64, f64, f64, f64)> {
+    let mut union: Option<(f64, f64, f64, f64)> = None;
+    for child in group_children(node).iter().filter(|child| child["visible"].as_bool() != Some(false)) {
+        let found = match bounds(child) {
+            Some((left, top, Some(width), Some(height))) => Some((x + left, y + top, x + left + width, y + top + height)),
+            Some(_) => None,
+            None => {
+                let (tx, ty) = (child["transform"]["tx"].as_f64().unwrap_or(0.0), child["transform"]["ty"].as_f64().unwrap_or(0.0));
+                extent(child, x + tx, y + ty).map(|(left, top, width, height)| (left, top, left + width, top + height))
+            }
+        };
+        if let Some((left, top, right, bottom)) = found {
+            union = Some(match union {
+                Some((l, t, r, b)) => (l.min(left), t.min(top), r.max(right), b.max(bottom)),
+                None => (left, top, right, bottom),
+            });
+        }
+    }
+    union.map(|(left, top, right, bottom)| (left, top, right - left, bottom - top))
+}
+
+struct Walker<'a> {
+    artboard: &'a str,
+    elements: Vec<Element>,
+    warnings: Vec<String>,
+}
+
+impl Walker<'_> {
+    // Converts the nodes of a group at (`x`, `y`) on the artboard
+    fn nodes(&mut self, nodes: &[Value], x: f64, y: f64) {
+        for node in nodes.iter().filter(|node| node["visible"].as_bool() != Some(false)) {
+            let (tx, ty) = (node["transform"]["tx"].as_f64().unwrap_or(0.0), node["transform"]["ty"].as_f64().unwrap_or(0.0));
+            match node["type"].as_str().unwrap_or("") {
+                "text" => {
+                    let Some((left, top, width, height)) = bounds(node) else { continue };
+                    let mut element = Element::new("title", first_text(node).as_deref(), (x + left, y + top, 0.0, 0.0));
+                    (element.width, element.height) = (width, height);
+                    self.elements.push(element);
+                }
+                // Shapes filled with a pattern show an image; the others are decoration
+                "shape" if node["style"]["fill"]["type"].as_str() == Some("pattern") => {
+                    let Some((left, top, Some(width), Some(height))) = bounds(node) else { continue };
+                    self.elements.push(Element::new("Image", Some(name(node)), (x + left, y + top, width, height)));
+                }
+                "shape" => {}
+                "group" if is_button_name(name(node)) => match extent(node, x + tx, y + ty) {
+                    Some(bounds) => self.elements.push(Element::new("button", first_text(node).as_deref(), bounds)),
+                    None => self.warnings.push(format!("{}: skipped the button '{}', which has no shapes to size it", self.artboard, name(node))),
+                },
+                "group" => self.nodes(group_children(node), x + tx, y + ty),
+                kind => self.warnings.push(format!("{}: skipped the {} '{}', which has no spec element", self.artboard, kind, name(node))),
+            }
+        }
+    }
+}
+
+/// Converts an XD document's bytes to a spec. Texts become titles, shapes filled with an image
+/// images named after the shape, and groups named as buttons (see [`is_button_name`]) buttons
+/// titled by their first text, over the shapes they hold. Other groups are looked into; other
+/// shapes are taken for decoration and left out.
+pub fn import(bytes: &[u8]) -> Result<Import, String> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(|e| format!("not an XD file: {}", e))?;
+    let manifest = read_json(&mut archive, "manifest")?;
+    let mut found = Vec::new();
+    manifest_artboards(&manifest, &mut found);
+    if found.is_empty() {
+        return Err("found no artboards".to_string());
+    }
+    let mut warnings = Vec::new();
+    let mut examples = Vec::new();
+    for (path, artboard, width, height) in found {
+        let content = read_json(&mut archive, &format!("artwork/{}/graphics/graphicContent.agc", path))?;
+        let mut walker = Walker { artboard: &artboard, elements: Vec::new(), warnings: Vec::new() };
+        for node in children(&content).iter().filter(|node| node["type"].as_str() == Some("artboard")) {
+            walker.nodes(children(&node["artboard"]), 0.0, 0.0);
+        }
+        if walker.elements.is_empty() {
+            walker.warnings.push(format!("{}: found no texts, buttons or images", artboard));
+        }
+        warnings.append(&mut walker.warnings);
+        let insets = device_safe_area(width, height).unwrap_or_default();
+        examples.push((artboard.clone(), Example { width, height, insets, elements: walker.elements }));
+    }
+    Ok(Import { spec: write_artboards(examples), warnings })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::Write;
+
+    fn xd(files: &[(&str, Value)]) -> Vec<u8> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("mimetype", zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"application/vnd.adobe.sparkler.project+dcxucf").unwrap();
+        for (path, value) in files {
+            zip.start_file(*path, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(value.to_string().as_bytes()).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_artboards_become_examples() {
+        let manifest = json!({"name": "Login", "children": [{"path": "artwork", "children": [
+            {"path": "pasteboard"},
+            {"path": "artboard-1A", "name": "Login", "uxdesign#bounds": {"x": 0, "y": 0, "width": 393, "height": 852}}
+        ]}]});
+        let content = json!({"version": "1.5.0", "children": [{"type": "artboard", "artboard": {"children": [
+            {"type": "shape", "name": "Background", "shape": {"type": "rect", "x": 0, "y": 0, "width": 393, "height": 852}},
+            {"type": "shape", "name": "logo", "transform": {"tx": 146, "ty": 100}, "style": {"fill": {"type": "pattern"}},
+             "shape": {"type": "rect", "x": 0, "y": 0, "width": 100, "height": 100}},
+            {"type": "text", "name": "Welcome", "transform": {"tx": 20, "ty": 244}, "style": {"font": {"size": 24}},
+             "text": {"rawText": "Welcome", "frame": {"type": "positioned"}}},
+            {"type": "group", "name": "Primary Button", "transform": {"tx": 20, "ty": 760}, "group": {"children": [
+                {"type": "shape", "shape": {"type": "rect", "x": 0, "y": 0, "width": 353, "height": 44}},
+                {"type": "text", "transform": {"tx": 150, "ty": 28}, "text": {"rawText": "Sign in", "frame": {"type": "positioned"}}}
+            ]}},
+            {"type": "text", "visible": false, "text": {"rawText": "Error"}},
+            {"type": "syncRef", "name": "Tab Bar"}
+        ]}}]});
+        let bytes = xd(&[("manifest", manifest), ("artwork/artboard-1A/graphics/graphicContent.agc", content)]);
+        let import = import(&bytes).unwrap();
+        assert_eq!(
+            import.spec,
+            "Login:{(width:393,height:852,safe_top:59,safe_bottom:34):{Image:\"logo\"@(x:146,y:100,w:100,h:100),title:\"Welcome\"@(x:20,y:220),button:\"Sign in\"@(x:20,y:760,w:353,h:44)}}"
+        );
+        assert_eq!(import.warnings, vec!["Login: skipped the syncRef 'Tab Bar', which has no spec element"]);
+        assert!(super::import(b"{}").unwrap_err().starts_with("not an XD file"));
+    }
+}
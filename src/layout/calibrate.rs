@@ -0,0 +1,367 @@
+// File: src/layout/calibrate.rs
+// Calibration of the layout model (`calibrate`): a Swift harness lays out sample views on a
+// simulator and prints the frames SwiftUI gave them, and the model's constants are fitted so
+// its simulated frames land as close to those as they can.
+use serde::Deserialize;
+
+use super::{simulate_within, Insets, Metrics, Rect, Size};
+use crate::ast::IR;
+use crate::input::swift;
+use crate::output::render::{render_swiftui, string_literal};
+
+/// The layouts the harness measures, each exercising a few of the constants.
+pub const SAMPLES: &[(&str, &str)] = &[
+    ("title", "VStack {\n    Text(\"Welcome\")\n}"),
+    ("button", "VStack {\n    Button(\"Continue\") {}\n}"),
+    ("title_and_button", "VStack {\n    Text(\"Sign in\")\n    Spacer()\n    Button(\"Continue\") {}\n}"),
+    ("spacing", "VStack {\n    Text(\"One\")\n    Text(\"Two\")\n    Button(\"Three\") {}\n}"),
+    ("row", "HStack {\n    Text(\"Name\")\n    Spacer()\n    Button(\"Edit\") {}\n}"),
+    ("wrapping", "VStack {\n    Text(\"A longer title that wraps onto a second line\")\n    Spacer()\n}"),
+    ("toolbar", "VStack {\n    HStack {\n        Button(\"Back\") {}\n        Spacer()\n        Text(\"Settings\")\n    }\n    Spacer()\n}"),
+];
+
+/// One sample as the harness observed it: its layout, the screen and safe area it was shown
+/// in, and the frame of each text, button and image, in the order the code states them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    pub name: String,
+    pub ir: IR,
+    pub screen: Size,
+    pub safe_area: Insets,
+    pub frames: Vec<Rect>,
+}
+
+#[derive(Deserialize)]
+struct ObservedSample {
+    name: String,
+    code: String,
+    width: f64,
+    height: f64,
+    #[serde(default)]
+    safe_top: f64,
+    #[serde(default)]
+    safe_bottom: f64,
+    #[serde(default)]
+    safe_leading: f64,
+    #[serde(default)]
+    safe_trailing: f64,
+    frames: Vec<ObservedFrame>,
+}
+
+#[derive(Deserialize)]
+struct ObservedFrame {
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+#[derive(Deserialize)]
+struct Observations {
+    samples: Vec<ObservedSample>,
+}
+
+/// Reads the JSON the harness prints: `{"samples": [{"name", "code", "width", "height",
+/// "safe_top", ..., "frames": [{"x", "y", "width", "height"}]}]}`.
+pub fn parse_observations(json: &str) -> Result<Vec<Observation>, String> {
+    let observations: Observations = serde_json::from_str(json).map_err(|e| format!("Invalid observations: {}", e))?;
+    if observations.samples.is_empty() {
+        return Err("The observations have no samples".to_string());
+    }
+    observations
+        .samples
+        .into_iter()
+        .map(|sample| {
+            let ir = swift::parse_swiftui(&sample.code).map_err(|e| format!("Sample '{}': {}", sample.name, e))?;
+            let views = leaf_count(&ir);
+            if views != sample.frames.len() {
+                return Err(format!("Sample '{}': observed {} frames, but its layout has {} views", sample.name, sample.frames.len(), views));
+            }
+            Ok(Observation {
+                name: sample.name,
+                ir,
+                screen: Size::new(sample.width, sample.height),
+                safe_area: Insets::new(sample.safe_top, sample.safe_bottom, sample.safe_leading, sample.safe_trailing),
+                frames: sample.frames.into_iter().map(|f| Rect::new(f.x, f.y, f.width, f.height)).collect(),
+            })
+        })
+        .collect()
+}
+
+fn leaf_count(ir: &IR) -> usize {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => children.iter().map(leaf_count).sum(),
+        IR::Spacer => 0,
+        _ => 1,
+    }
+}
+
+// The code of a sample with a probe after each leaf's modifiers, numbering the leaves in order
+fn probed(code: &str) -> String {
+    let lines: Vec<&str> = code.lines().collect();
+    let indent = |line: &str| line.len() - line.trim_start().len();
+    let mut out = Vec::new();
+    let mut probes = 0;
+    for (index, line) in lines.iter().enumerate() {
+        out.push(line.to_string());
+        // The view a modifier line belongs to is the last line before it that is not one
+        let view = lines[..=index].iter().rfind(|line| !line.trim_start().starts_with('.')).unwrap_or(line);
+        let is_leaf = ["Text(", "Button(", "Image("].iter().any(|start| view.trim_start().starts_with(start));
+        let chain_ends = lines.get(index + 1).is_none_or(|next| !next.trim_start().starts_with('.') || indent(next) <= indent(view));
+        if is_leaf && chain_ends {
+            out.push(format!("{}    .calibrationProbe({})", " ".repeat(indent(view)), probes));
+            probes += 1;
+        }
+    }
+    out.join("\n")
+}
+
+/// A SwiftUI app that shows each of [`SAMPLES`] in turn and prints the frames of their views
+/// as the JSON [`parse_observations`] reads.
+pub fn harness() -> Result<String, String> {
+    let mut samples = Vec::new();
+    for (name, code) in SAMPLES {
+        let code = render_swiftui(&swift::parse_swiftui(code)?);
+        let ir = swift::parse_swiftui(&code)?;
+        let view: String = probed(&code).lines().map(|line| format!("\n        {}", line)).collect();
+        samples.push(format!(
+            "    CalibrationSample(name: {}, code: {}, probes: {}, view: AnyView({}\n    )),",
+            string_literal(name),
+            string_literal(&code),
+            leaf_count(&ir),
+            view
+        ));
+    }
+    Ok(HARNESS.replace("{samples}", &samples.join("\n")))
+}
+
+const HARNESS: &str = r#"// Calibration harness for swiftui-synth. Add this file as the only source of a new iOS app,
+// run it on the simulator of the device to calibrate for and save the JSON it prints as
+// observations.json, then run `swiftui-synth calibrate observations.json`.
+import SwiftUI
+
+struct CalibrationFrames: PreferenceKey {
+    static var defaultValue: [Int: CGRect] = [:]
+
+    static func reduce(value: inout [Int: CGRect], nextValue: () -> [Int: CGRect]) {
+        value.merge(nextValue()) { $1 }
+    }
+}
+
+extension View {
+    func calibrationProbe(_ index: Int) -> some View {
+        background(GeometryReader { proxy in
+            Color.clear.preference(key: CalibrationFrames.self, value: [index: proxy.frame(in: .global)])
+        })
+    }
+}
+
+struct CalibrationSample {
+    let name: String
+    let code: String
+    let probes: Int
+    let view: AnyView
+}
+
+let calibrationSamples: [CalibrationSample] = [
+{samples}
+]
+
+struct CalibrationView: View {
+    @State private var index = 0
+    @State private var observed: [[String: Any]] = []
+
+    var body: some View {
+        GeometryReader { screen in
+            calibrationSamples[index].view
+                .frame(maxWidth: .infinity, maxHeight: .infinity)
+                .onPreferenceChange(CalibrationFrames.self) { frames in
+                    record(frames, screen: screen)
+                }
+        }
+        .id(index)
+    }
+
+    private func record(_ frames: [Int: CGRect], screen: GeometryProxy) {
+        let sample = calibrationSamples[index]
+        guard frames.count == sample.probes, observed.count == index else { return }
+        let insets = screen.safeAreaInsets
+        observed.append([
+            "name": sample.name,
+            "code": sample.code,
+            "width": screen.size.width + insets.leading + insets.trailing,
+            "height": screen.size.height + insets.top + insets.bottom,
+            "safe_top": insets.top,
+            "safe_bottom": insets.bottom,
+            "safe_leading": insets.leading,
+            "safe_trailing": insets.trailing,
+            "frames": frames.keys.sorted().map { key -> [String: Double] in
+                let frame = frames[key]!
+                return ["x": frame.minX, "y": frame.minY, "width": frame.width, "height": frame.height]
+            },
+        ])
+        DispatchQueue.main.async {
+            if index + 1 < calibrationSamples.count {
+                index += 1
+            } else if let json = try? JSONSerialization.data(withJSONObject: ["samples": observed], options: [.prettyPrinted, .sortedKeys]) {
+                print(String(decoding: json, as: UTF8.self))
+            }
+        }
+    }
+}
+
+@main
+struct CalibrationApp: App {
+    var body: some Scene {
+        WindowGroup {
+            CalibrationView()
+        }
+    }
+}
+"#;
+
+// The constants the fit adjusts, as getters and setters on the metrics
+type Constant = (&'static str, fn(&Metrics) -> f64, fn(&mut Metrics, f64));
+
+/// The constants [`fit`] adjusts, by the name the config's `[layout]` section gives them.
+pub const CONSTANTS: &[Constant] = &[
+    ("padding", |m| m.padding, |m, v| m.padding = v),
+    ("stack_spacing", |m| m.stack_spacing, |m, v| m.stack_spacing = v),
+    ("title_font_size", |m| m.title_font_size, |m, v| m.title_font_size = v),
+    ("title_line_height", |m| m.title_line_height, |m, v| m.title_line_height = v),
+    ("body_font_size", |m| m.body_font_size, |m, v| m.body_font_size = v),
+    ("body_line_height", |m| m.body_line_height, |m, v| m.body_line_height = v),
+];
+
+/// Root-mean-square distance in points between the edges of the frames the model gives the
+/// observed samples with `metrics` and the observed ones.
+pub fn error(observations: &[Observation], metrics: &Metrics) -> f64 {
+    let (mut sum, mut count) = (0.0, 0);
+    for observation in observations {
+        let layout = simulate_within(&observation.ir, observation.screen, observation.safe_area, metrics);
+        for (simulated, observed) in layout.leaves().zip(&observation.frames) {
+            let rect = &simulated.rect;
+            for (a, b) in [(rect.x, observed.x), (rect.y, observed.y), (rect.max_x(), observed.max_x()), (rect.max_y(), observed.max_y())] {
+                sum += (a - b).powi(2);
+                count += 1;
+            }
+        }
+    }
+    if count == 0 {
+        0.0
+    } else {
+        (sum / count as f64).sqrt()
+    }
+}
+
+/// The result of [`fit`]: the calibrated metrics and the error before and after.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fit {
+    pub metrics: Metrics,
+    pub before: f64,
+    pub after: f64,
+}
+
+impl Fit {
+    /// The fitted constants as a `[layout]` config section.
+    pub fn config(&self) -> String {
+        let mut lines = vec!["[layout]".to_string()];
+        for (name, get, _) in CONSTANTS {
+            lines.push(format!("{} = {}", name, get(&self.metrics)));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Fits the [`CONSTANTS`] of `metrics` to the observations by coordinate descent: each
+/// constant in turn moves by a step while that lowers the error, and the step halves when
+/// none does. Constants stay positive and end rounded to hundredths of a point.
+pub fn fit(observations: &[Observation], metrics: &Metrics) -> Fit {
+    let before = error(observations, metrics);
+    let (mut best, mut best_error) = (metrics.clone(), before);
+    let mut step = 4.0;
+    while step >= 0.01 {
+        let mut improved = false;
+        for (_, get, set) in CONSTANTS {
+            for direction in [1.0, -1.0] {
+                loop {
+                    let value = get(&best) + direction * step;
+                    if value <= 0.0 {
+                        break;
+                    }
+                    let mut candidate = best.clone();
+                    set(&mut candidate, value);
+                    let candidate_error = error(observations, &candidate);
+                    if candidate_error >= best_error - 1e-9 {
+                        break;
+                    }
+                    (best, best_error, improved) = (candidate, candidate_error, true);
+                }
+            }
+        }
+        if !improved {
+            step /= 2.0;
+        }
+    }
+    for (_, get, set) in CONSTANTS {
+        let value = (get(&best) * 100.0).round() / 100.0;
+        set(&mut best, value);
+    }
+    let after = error(observations, &best);
+    Fit { metrics: best, before, after }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::simulate_within;
+
+    // What a simulator would report if SwiftUI laid the samples out with `metrics`
+    fn observe(metrics: &Metrics) -> Vec<Observation> {
+        SAMPLES
+            .iter()
+            .map(|(name, code)| {
+                let ir = swift::parse_swiftui(code).unwrap();
+                let (screen, safe_area) = (Size::new(390.0, 844.0), Insets::new(47.0, 34.0, 0.0, 0.0));
+                let frames = simulate_within(&ir, screen, safe_area, metrics).leaves().map(|f| f.rect).collect();
+                Observation { name: name.to_string(), ir, screen, safe_area, frames }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_fit_recovers_the_constants_of_the_observations() {
+        let actual = Metrics { padding: 12.0, stack_spacing: 10.0, title_font_size: 30.0, title_line_height: 36.0, body_line_height: 20.0, ..Metrics::default() };
+        let observations = observe(&actual);
+        let fit = fit(&observations, &Metrics::default());
+        assert!(fit.before > 1.0);
+        assert!(fit.after < 0.1, "{:?}", fit);
+        for (name, get, _) in CONSTANTS {
+            assert!((get(&fit.metrics) - get(&actual)).abs() < 0.2, "{}: {} vs {}", name, get(&fit.metrics), get(&actual));
+        }
+        assert!(fit.config().starts_with("[layout]\npadding = 12\nstack_spacing = 10\n"), "{}", fit.config());
+    }
+
+    #[test]
+    fn test_parse_observations() {
+        let json = r#"{"samples": [{"name": "title", "code": "VStack {\n    Text(\"Hi\")\n}", "width": 390, "height": 844, "safe_top": 47,
+            "frames": [{"x": 150, "y": 400, "width": 90, "height": 66}]}]}"#;
+        let observations = parse_observations(json).unwrap();
+        assert_eq!(observations[0].safe_area, Insets::new(47.0, 0.0, 0.0, 0.0));
+        assert_eq!(observations[0].frames, [Rect::new(150.0, 400.0, 90.0, 66.0)]);
+        let missing = json.replace(r#"{"x": 150, "y": 400, "width": 90, "height": 66}"#, "");
+        assert!(parse_observations(&missing).unwrap_err().contains("observed 0 frames, but its layout has 1 views"));
+        assert!(parse_observations(r#"{"samples": []}"#).is_err());
+    }
+
+    #[test]
+    fn test_harness_probes_every_view() {
+        let harness = harness().unwrap();
+        for (name, code) in SAMPLES {
+            assert!(harness.contains(&format!("name: \"{}\"", name)));
+            let probes = leaf_count(&swift::parse_swiftui(code).unwrap());
+            assert!(harness.contains(&format!(".calibrationProbe({})", probes - 1)));
+        }
+        assert!(harness.contains("                .padding()\n                .calibrationProbe(0)"), "{}", harness);
+    }
+}
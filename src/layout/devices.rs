@@ -0,0 +1,151 @@
+// File: src/layout/devices.rs
+// Screen sizes and safe-area insets of common devices, so examples can name a device
+// instead of stating raw dimensions, e.g. `(device:"iPhone 15 Pro", orientation:landscape)`.
+use crate::platform::Platform;
+
+/// Distances in points from each screen edge that content should stay clear of.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Insets {
+    pub top: f64,
+    pub bottom: f64,
+    pub leading: f64,
+    pub trailing: f64,
+}
+
+impl Insets {
+    pub const fn new(top: f64, bottom: f64, leading: f64, trailing: f64) -> Insets {
+        Insets { top, bottom, leading, trailing }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+impl std::fmt::Display for Orientation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Orientation::Portrait => "portrait",
+            Orientation::Landscape => "landscape",
+        })
+    }
+}
+
+impl std::str::FromStr for Orientation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "portrait" => Ok(Orientation::Portrait),
+            "landscape" => Ok(Orientation::Landscape),
+            other => Err(format!("Unknown orientation '{}': expected portrait or landscape", other)),
+        }
+    }
+}
+
+/// A device in the built-in database. Sizes are in points, held in portrait; Macs and TVs,
+/// which do not rotate, are held the way they stand.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Device {
+    pub name: &'static str,
+    pub platform: Platform,
+    pub width: f64,
+    pub height: f64,
+    pub portrait: Insets,
+    pub landscape: Insets,
+}
+
+impl Device {
+    /// Screen width and height in `orientation`.
+    pub fn size(&self, orientation: Orientation) -> (f64, f64) {
+        match orientation {
+            Orientation::Portrait => (self.width, self.height),
+            Orientation::Landscape => (self.height, self.width),
+        }
+    }
+
+    pub fn safe_area(&self, orientation: Orientation) -> Insets {
+        match orientation {
+            Orientation::Portrait => self.portrait,
+            Orientation::Landscape => self.landscape,
+        }
+    }
+}
+
+const HOME_BUTTON: (Insets, Insets) = (Insets::new(20.0, 0.0, 0.0, 0.0), Insets::new(0.0, 0.0, 0.0, 0.0));
+const NOTCH: (Insets, Insets) = (Insets::new(47.0, 34.0, 0.0, 0.0), Insets::new(0.0, 21.0, 47.0, 47.0));
+const MINI_NOTCH: (Insets, Insets) = (Insets::new(50.0, 34.0, 0.0, 0.0), Insets::new(0.0, 21.0, 50.0, 50.0));
+const ISLAND: (Insets, Insets) = (Insets::new(59.0, 34.0, 0.0, 0.0), Insets::new(0.0, 21.0, 59.0, 59.0));
+const ISLAND_16_PRO: (Insets, Insets) = (Insets::new(62.0, 34.0, 0.0, 0.0), Insets::new(0.0, 21.0, 62.0, 62.0));
+const IPAD: (Insets, Insets) = (Insets::new(24.0, 20.0, 0.0, 0.0), Insets::new(24.0, 20.0, 0.0, 0.0));
+// The time shown at the top of every watch screen
+const WATCH: (Insets, Insets) = (Insets::new(31.0, 0.0, 0.0, 0.0), Insets::new(31.0, 0.0, 0.0, 0.0));
+// The title-safe area of a TV, which the overscan of some sets cuts into
+const TV: (Insets, Insets) = (Insets::new(60.0, 60.0, 80.0, 80.0), Insets::new(60.0, 60.0, 80.0, 80.0));
+const WINDOW: (Insets, Insets) = (Insets::new(0.0, 0.0, 0.0, 0.0), Insets::new(0.0, 0.0, 0.0, 0.0));
+
+const fn device(name: &'static str, width: f64, height: f64, insets: (Insets, Insets)) -> Device {
+    device_on(Platform::Ios, name, width, height, insets)
+}
+
+const fn device_on(platform: Platform, name: &'static str, width: f64, height: f64, insets: (Insets, Insets)) -> Device {
+    Device { name, platform, width, height, portrait: insets.0, landscape: insets.1 }
+}
+
+/// Every device [`find`] knows.
+pub const DEVICES: &[Device] = &[
+    device("iPhone SE", 375.0, 667.0, HOME_BUTTON),
+    device("iPhone 13 mini", 375.0, 812.0, MINI_NOTCH),
+    device("iPhone 14", 390.0, 844.0, NOTCH),
+    device("iPhone 14 Plus", 428.0, 926.0, NOTCH),
+    device("iPhone 14 Pro", 393.0, 852.0, ISLAND),
+    device("iPhone 14 Pro Max", 430.0, 932.0, ISLAND),
+    device("iPhone 15", 393.0, 852.0, ISLAND),
+    device("iPhone 15 Plus", 430.0, 932.0, ISLAND),
+    device("iPhone 15 Pro", 393.0, 852.0, ISLAND),
+    device("iPhone 15 Pro Max", 430.0, 932.0, ISLAND),
+    device("iPhone 16", 393.0, 852.0, ISLAND),
+    device("iPhone 16 Plus", 430.0, 932.0, ISLAND),
+    device("iPhone 16 Pro", 402.0, 874.0, ISLAND_16_PRO),
+    device("iPhone 16 Pro Max", 440.0, 956.0, ISLAND_16_PRO),
+    device("iPad mini", 744.0, 1133.0, IPAD),
+    device("iPad", 820.0, 1180.0, IPAD),
+    device("iPad Air 11-inch", 820.0, 1180.0, IPAD),
+    device("iPad Pro 11-inch", 834.0, 1194.0, IPAD),
+    device("iPad Pro 13-inch", 1032.0, 1376.0, IPAD),
+    device_on(Platform::Watchos, "Apple Watch SE 40mm", 162.0, 197.0, WATCH),
+    device_on(Platform::Watchos, "Apple Watch SE 44mm", 184.0, 224.0, WATCH),
+    device_on(Platform::Watchos, "Apple Watch Series 9 41mm", 176.0, 215.0, WATCH),
+    device_on(Platform::Watchos, "Apple Watch Series 9 45mm", 198.0, 242.0, WATCH),
+    device_on(Platform::Watchos, "Apple Watch Ultra 2", 205.0, 251.0, WATCH),
+    device_on(Platform::Tvos, "Apple TV", 1920.0, 1080.0, TV),
+    device_on(Platform::Macos, "Mac window", 800.0, 600.0, WINDOW),
+    device_on(Platform::Macos, "MacBook Air 13-inch", 1470.0, 956.0, WINDOW),
+    device_on(Platform::Macos, "MacBook Pro 14-inch", 1512.0, 982.0, WINDOW),
+];
+
+/// Looks up a device by name, ignoring case.
+pub fn find(name: &str) -> Option<&'static Device> {
+    DEVICES.iter().find(|device| device.name.eq_ignore_ascii_case(name.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_and_rotate() {
+        let device = find(" iphone 15 pro").unwrap();
+        assert_eq!(device.name, "iPhone 15 Pro");
+        assert_eq!(device.size(Orientation::Portrait), (393.0, 852.0));
+        assert_eq!(device.size(Orientation::Landscape), (852.0, 393.0));
+        assert_eq!(device.safe_area(Orientation::Landscape), Insets::new(0.0, 21.0, 59.0, 59.0));
+        assert_eq!(find("iPhone 15 Prp"), None);
+        assert_eq!("sideways".parse::<Orientation>().unwrap_err(), "Unknown orientation 'sideways': expected portrait or landscape");
+        assert_eq!(find("apple tv").unwrap().platform, Platform::Tvos);
+        assert_eq!(find("Apple TV").unwrap().size(Orientation::Portrait), (1920.0, 1080.0));
+    }
+}
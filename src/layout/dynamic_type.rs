@@ -0,0 +1,85 @@
+// File: src/layout/dynamic_type.rs
+// Dynamic Type sizes, so examples can check a layout at the text size a user picked,
+// e.g. `(width:390,height:844,dynamicType:accessibility3)`.
+use std::fmt;
+use std::str::FromStr;
+
+/// A text size from the iOS Dynamic Type setting, named like SwiftUI's `DynamicTypeSize`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DynamicType {
+    XSmall,
+    Small,
+    Medium,
+    /// The system default, at which [`Metrics`](super::Metrics) are stated.
+    #[default]
+    Large,
+    XLarge,
+    XXLarge,
+    XXXLarge,
+    Accessibility1,
+    Accessibility2,
+    Accessibility3,
+    Accessibility4,
+    Accessibility5,
+}
+
+// Every size with its name and the point size of `.body` text at it (17pt at `large`).
+const SIZES: [(DynamicType, &str, f64); 12] = [
+    (DynamicType::XSmall, "xSmall", 14.0),
+    (DynamicType::Small, "small", 15.0),
+    (DynamicType::Medium, "medium", 16.0),
+    (DynamicType::Large, "large", 17.0),
+    (DynamicType::XLarge, "xLarge", 19.0),
+    (DynamicType::XXLarge, "xxLarge", 21.0),
+    (DynamicType::XXXLarge, "xxxLarge", 23.0),
+    (DynamicType::Accessibility1, "accessibility1", 28.0),
+    (DynamicType::Accessibility2, "accessibility2", 33.0),
+    (DynamicType::Accessibility3, "accessibility3", 40.0),
+    (DynamicType::Accessibility4, "accessibility4", 47.0),
+    (DynamicType::Accessibility5, "accessibility5", 53.0),
+];
+
+impl DynamicType {
+    /// Names of every size, smallest first.
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        SIZES.iter().map(|(_, name, _)| *name)
+    }
+
+    /// How much larger text is at this size than at `large`.
+    pub fn scale(self) -> f64 {
+        SIZES.iter().find(|(size, ..)| *size == self).map_or(1.0, |(_, _, body)| body / 17.0)
+    }
+}
+
+impl fmt::Display for DynamicType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = SIZES.iter().find(|(size, ..)| size == self).map_or("large", |(_, name, _)| name);
+        f.write_str(name)
+    }
+}
+
+impl FromStr for DynamicType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SIZES
+            .iter()
+            .find(|(_, name, _)| name.eq_ignore_ascii_case(s))
+            .map(|(size, ..)| *size)
+            .ok_or_else(|| format!("Unknown Dynamic Type size '{}': expected xSmall through xxxLarge or accessibility1 through accessibility5", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sizes_scale_text_from_large() {
+        assert_eq!("accessibility3".parse::<DynamicType>(), Ok(DynamicType::Accessibility3));
+        assert_eq!("XXXLARGE".parse::<DynamicType>().unwrap().to_string(), "xxxLarge");
+        assert_eq!(DynamicType::default().scale(), 1.0);
+        assert_eq!(DynamicType::Accessibility3.scale(), 40.0 / 17.0);
+        assert!("huge".parse::<DynamicType>().unwrap_err().starts_with("Unknown Dynamic Type size 'huge'"));
+    }
+}
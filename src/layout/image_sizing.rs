@@ -0,0 +1,143 @@
+// File: src/layout/image_sizing.rs
+// Sizing of the images of a screen from the frames its examples state for them: an image
+// with a stated size is made resizable and framed, and fits or fills its frame depending on
+// how the frame's shape compares with its asset's (`Image:"hero"@(w:390,h:200,aspect:"3:2")`).
+use std::collections::BTreeMap;
+
+use crate::ast::{Example, Value};
+use crate::layout;
+
+/// How far, as a ratio, a frame's aspect may be from its asset's for the image to fit it.
+pub const FIT_TOLERANCE: f64 = 1.1;
+
+/// How an image is scaled into its frame.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContentMode {
+    /// The whole image shows, letterboxed if the frame's shape differs.
+    Fit,
+    /// The image covers the frame, clipped to it.
+    Fill,
+}
+
+/// How an image is emitted, as the frames stated for it tell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ImageSizing {
+    pub mode: ContentMode,
+    /// Stated width in points; `None` if only the height is stated or the image spans the screen.
+    pub width: Option<f64>,
+    /// Whether the image spans the screen's width in every example, whatever its size.
+    pub fills_width: bool,
+    pub height: Option<f64>,
+}
+
+impl ImageSizing {
+    /// The SwiftUI modifiers, without their leading dot, e.g. `scaledToFit()`.
+    pub fn modifiers(&self) -> Vec<String> {
+        let mut modifiers = vec!["resizable()".to_string()];
+        modifiers.push(match self.mode {
+            ContentMode::Fit => "scaledToFit()".to_string(),
+            ContentMode::Fill => "scaledToFill()".to_string(),
+        });
+        let frame = match (self.fills_width, self.width, self.height) {
+            (true, _, Some(height)) => Some(format!("maxWidth: .infinity, minHeight: {height}, maxHeight: {height}", height = height)),
+            (true, _, None) => Some("maxWidth: .infinity".to_string()),
+            (false, Some(width), Some(height)) => Some(format!("width: {}, height: {}", width, height)),
+            (false, Some(width), None) => Some(format!("width: {}", width)),
+            (false, None, Some(height)) => Some(format!("height: {}", height)),
+            (false, None, None) => None,
+        };
+        modifiers.extend(frame.map(|frame| format!("frame({})", frame)));
+        if self.mode == ContentMode::Fill {
+            modifiers.push("clipped()".to_string());
+        }
+        modifiers
+    }
+}
+
+// How an image whose asset is `aspect` wide for its height goes in `frame`: it fills a frame
+// of another shape, and fits one of its own or with a side left to follow from the asset
+fn content_mode(frame: &layout::StatedFrame, aspect: f64) -> ContentMode {
+    let (Some(width), Some(height)) = (frame.width, frame.height) else { return ContentMode::Fit };
+    let ratio = (width / height) / aspect;
+    match ratio.max(1.0 / ratio) <= FIT_TOLERANCE {
+        true => ContentMode::Fit,
+        false => ContentMode::Fill,
+    }
+}
+
+/// The sizing of each image of `examples` that states a width or height, by its name. Its
+/// asset is as wide for its height as its stated `aspect`, or square without one, as the
+/// layout assumes. The size is the first one stated, except that an image as wide as the
+/// screen in every example fills the width instead. An image that fills its frame in any
+/// example fills it in all.
+pub fn sizing(examples: &[Example]) -> BTreeMap<String, ImageSizing> {
+    let mut sizing: BTreeMap<String, ImageSizing> = BTreeMap::new();
+    for example in examples {
+        let Value::Dict(elements) = &example.1 else { continue };
+        let screen_width = layout::screen_size(example).map(|size| size.width);
+        for (index, frame) in layout::stated_frames(example) {
+            let Some((_, Value::String(name))) = elements.get(index).filter(|(key, _)| key == "Image") else { continue };
+            if frame.width.is_none() && frame.height.is_none() {
+                continue;
+            }
+            let mode = content_mode(&frame, frame.aspect.unwrap_or(1.0));
+            let spans = frame.width.zip(screen_width).map(|(width, screen)| width >= screen - 0.5);
+            let found = sizing.entry(name.clone()).or_insert(ImageSizing {
+                mode,
+                width: frame.width,
+                fills_width: spans.unwrap_or(false),
+                height: frame.height,
+            });
+            if mode == ContentMode::Fill {
+                found.mode = ContentMode::Fill;
+            }
+            found.width = found.width.or(frame.width);
+            found.height = found.height.or(frame.height);
+            if let Some(spans) = spans {
+                found.fills_width &= spans;
+            }
+        }
+    }
+    for image in sizing.values_mut() {
+        if image.fills_width {
+            image.width = None;
+        }
+    }
+    sizing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    #[test]
+    fn test_images_fit_or_fill_their_frames() {
+        let examples = parse_examples(
+            "{(width:390,height:844):{Image:\"avatar\"@(w:80,h:80),Image:\"hero\"@(w:390,h:200,aspect:\"4:3\"),Image:\"logo\"@(h:40,aspect:3),Image:\"icon\"}}
+             {(width:320,height:568):{Image:\"avatar\"@(w:64,h:64),Image:\"hero\"@(w:320,h:180,aspect:\"4:3\")}}",
+        )
+        .unwrap();
+        let sizing = sizing(&examples);
+        assert_eq!(sizing["avatar"], ImageSizing { mode: ContentMode::Fit, width: Some(80.0), fills_width: false, height: Some(80.0) });
+        assert_eq!(sizing["avatar"].modifiers(), ["resizable()", "scaledToFit()", "frame(width: 80, height: 80)"]);
+        // A banner much wider than its 4:3 photo, across every screen
+        assert_eq!(
+            sizing["hero"].modifiers(),
+            ["resizable()", "scaledToFill()", "frame(maxWidth: .infinity, minHeight: 200, maxHeight: 200)", "clipped()"]
+        );
+        assert_eq!(sizing["logo"].modifiers(), ["resizable()", "scaledToFit()", "frame(height: 40)"]);
+        assert!(!sizing.contains_key("icon"));
+    }
+
+    #[test]
+    fn test_frames_of_another_shape_are_filled() {
+        let frame = |width, height| layout::StatedFrame { width: Some(width), height: Some(height), ..layout::StatedFrame::default() };
+        assert_eq!(content_mode(&frame(160.0, 90.0), 16.0 / 9.0), ContentMode::Fit);
+        assert_eq!(content_mode(&frame(100.0, 95.0), 1.0), ContentMode::Fit);
+        assert_eq!(content_mode(&frame(100.0, 50.0), 1.0), ContentMode::Fill);
+        assert_eq!(content_mode(&frame(50.0, 100.0), 1.0), ContentMode::Fill);
+        let examples = parse_examples("{(width:390,height:844):{Image:\"photo\"@(x:20,w:200,h:200)}} {(width:320,height:568):{Image:\"photo\"@(w:200,h:100)}}").unwrap();
+        assert_eq!(sizing(&examples)["photo"].modifiers(), ["resizable()", "scaledToFill()", "frame(width: 200, height: 200)", "clipped()"]);
+    }
+}
@@ -0,0 +1,672 @@
+// File: src/layout/mod.rs
+// A small model of SwiftUI's stack layout, used to predict where each view ends up
+// at a given screen size without running SwiftUI.
+use std::collections::BTreeMap;
+
+use crate::ast::{Example, Value, IR};
+
+pub mod calibrate;
+pub mod devices;
+pub mod dynamic_type;
+pub mod image_sizing;
+pub mod spacing;
+pub mod text_style;
+pub mod text_metrics;
+
+pub use devices::Insets;
+pub use dynamic_type::DynamicType;
+pub use text_metrics::TextMetrics;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Size {
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Size {
+    pub fn new(width: f64, height: f64) -> Size {
+        Size { width, height }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Rect {
+    pub fn new(x: f64, y: f64, width: f64, height: f64) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    pub fn max_x(&self) -> f64 {
+        self.x + self.width
+    }
+
+    pub fn max_y(&self) -> f64 {
+        self.y + self.height
+    }
+}
+
+/// Constants of the layout model. The defaults approximate iOS with the
+/// modifiers the renderer emits (`.font(.title)` text, `.padding()` everywhere).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Metrics {
+    /// Inset added by `.padding()` on each edge.
+    pub padding: f64,
+    /// Default spacing between stack children.
+    pub stack_spacing: f64,
+    /// Font size and line height for `Text` (`.title`).
+    pub title_font_size: f64,
+    pub title_line_height: f64,
+    /// Font size and line height for button labels (`.body`).
+    pub body_font_size: f64,
+    pub body_line_height: f64,
+    /// Character widths of the text and button fonts.
+    pub text: TextMetrics,
+    /// Assumed intrinsic size of an image asset.
+    pub image_size: f64,
+    /// Assumed height of a custom view, which otherwise fills the available width.
+    pub custom_height: f64,
+    /// Minimum length of a `Spacer` along its stack's axis.
+    pub spacer_min_length: f64,
+    /// Sizes of registered custom views by name, overriding `custom_height`.
+    pub custom_sizes: BTreeMap<String, CustomSize>,
+}
+
+impl Metrics {
+    /// These metrics with text `scale` times larger, as at a larger Dynamic Type size.
+    pub fn scaled(&self, scale: f64) -> Metrics {
+        Metrics {
+            title_font_size: self.title_font_size * scale,
+            title_line_height: self.title_line_height * scale,
+            body_font_size: self.body_font_size * scale,
+            body_line_height: self.body_line_height * scale,
+            ..self.clone()
+        }
+    }
+}
+
+/// Name of SwiftUI's `Divider`, which the layout lays out as a hairline across its stack
+/// unless a component registers another size for it.
+pub const DIVIDER: &str = "Divider";
+
+/// Thickness of a `Divider`.
+pub const DIVIDER_THICKNESS: f64 = 1.0;
+
+/// Size behavior of a custom view.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CustomSize {
+    /// Fixed width in points; `None` fills the available width.
+    pub width: Option<f64>,
+    pub height: f64,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Metrics {
+            padding: 16.0,
+            stack_spacing: 8.0,
+            title_font_size: 28.0,
+            title_line_height: 34.0,
+            body_font_size: 17.0,
+            body_line_height: 22.0,
+            text: TextMetrics::default(),
+            image_size: 60.0,
+            custom_height: 44.0,
+            spacer_min_length: 8.0,
+            custom_sizes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Where one node of the IR ended up.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    /// Child indices from the root to this node.
+    pub path: Vec<usize>,
+    pub node: IR,
+    pub rect: Rect,
+}
+
+/// Direction in which horizontal content flows, from the locale's script.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LayoutDirection {
+    #[default]
+    LeftToRight,
+    /// For right-to-left scripts such as Arabic and Hebrew: leading is on the right.
+    RightToLeft,
+}
+
+impl std::str::FromStr for LayoutDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ltr" | "leftToRight" => Ok(LayoutDirection::LeftToRight),
+            "rtl" | "rightToLeft" => Ok(LayoutDirection::RightToLeft),
+            _ => Err(format!("Unknown layout direction '{}': expected ltr or rtl", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layout {
+    pub screen: Size,
+    /// Insets the content was kept clear of; see [`simulate_within`].
+    pub safe_area: Insets,
+    /// Which side of the screen is leading; see [`Layout::with_direction`].
+    pub direction: LayoutDirection,
+    /// Frames for every node in depth-first order, the root first.
+    pub frames: Vec<Frame>,
+    /// Size the content needs at minimum (spacers at their minimum length).
+    pub required: Size,
+}
+
+impl Layout {
+    /// The part of the screen the content is laid out in: the screen minus the safe-area insets.
+    pub fn bounds(&self) -> Rect {
+        inset(self.screen, self.safe_area)
+    }
+
+    /// This layout as SwiftUI arranges it in `direction`: right to left, every frame is
+    /// mirrored across the screen, so stacks run from the right edge.
+    pub fn with_direction(mut self, direction: LayoutDirection) -> Layout {
+        if direction != self.direction {
+            let width = self.screen.width;
+            for frame in &mut self.frames {
+                frame.rect.x = width - frame.rect.max_x();
+            }
+            let insets = &mut self.safe_area;
+            (insets.leading, insets.trailing) = (insets.trailing, insets.leading);
+            self.direction = direction;
+        }
+        self
+    }
+
+    /// True if the content does not fit within [`Layout::bounds`].
+    pub fn overflows(&self) -> bool {
+        let bounds = self.bounds();
+        self.required.width > bounds.width + 0.5 || self.required.height > bounds.height + 0.5
+    }
+
+    /// Frames of leaf views (everything except stacks and spacers).
+    pub fn leaves(&self) -> impl Iterator<Item = &Frame> {
+        self.frames
+            .iter()
+            .filter(|f| !matches!(f.node, IR::VStack(_) | IR::HStack(_) | IR::Spacer))
+    }
+}
+
+/// Frame an example states for one of its elements, e.g. `title:"Hi"@(x:20,y:60,w:350,h:34)`.
+/// Coordinates left out are unconstrained.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StatedFrame {
+    pub x: Option<f64>,
+    pub y: Option<f64>,
+    pub width: Option<f64>,
+    pub height: Option<f64>,
+    /// Point size of a text's font (`font:13`), which picks its text style; not a coordinate.
+    pub font_size: Option<f64>,
+    /// Width over height of an image's asset (`aspect:"16:9"`), which picks how it fills its
+    /// frame; not a coordinate.
+    pub aspect: Option<f64>,
+}
+
+/// Tallest frame, in points, read as a separator line rather than a view.
+pub const SEPARATOR_MAX_HEIGHT: f64 = 2.0;
+
+/// Share of the screen's width a separator line spans at least.
+pub const SEPARATOR_MIN_WIDTH: f64 = 0.8;
+
+impl StatedFrame {
+    /// Whether the frame has the shape of a separator on a screen `screen_width` wide: a
+    /// stated height of at most [`SEPARATOR_MAX_HEIGHT`] and a stated width of at least
+    /// [`SEPARATOR_MIN_WIDTH`] of the screen's.
+    pub fn is_separator(&self, screen_width: f64) -> bool {
+        self.height.is_some_and(|height| height <= SEPARATOR_MAX_HEIGHT)
+            && self.width.is_some_and(|width| width >= SEPARATOR_MIN_WIDTH * screen_width)
+    }
+
+    /// Total distance in points between the stated coordinates and `rect`.
+    pub fn deviation(&self, rect: &Rect) -> f64 {
+        [(self.x, rect.x), (self.y, rect.y), (self.width, rect.width), (self.height, rect.height)]
+            .iter()
+            .filter_map(|&(stated, actual)| stated.map(|stated| (stated - actual).abs()))
+            .sum()
+    }
+}
+
+/// Frames stated in an example, by the index of their element in the example's elements.
+/// A frame that only decorates its view, such as `@(cornerRadius:4)`, states none.
+pub fn stated_frames(example: &Example) -> Vec<(usize, StatedFrame)> {
+    let Value::Dict(dims) = &example.0 else { return Vec::new() };
+    let Some((_, Value::Dict(frames))) = dims.iter().find(|(k, _)| k == "frames") else { return Vec::new() };
+    frames
+        .iter()
+        .filter_map(|(index, frame)| {
+            let Value::Dict(coords) = frame else { return None };
+            let get = |key: &str| coords.iter().find(|(k, _)| k == key).and_then(|(_, v)| v.as_f64());
+            let frame = StatedFrame { x: get("x"), y: get("y"), width: get("w"), height: get("h"), font_size: get("font"), aspect: get("aspect") };
+            if frame == StatedFrame::default() {
+                return None;
+            }
+            Some((index.parse().ok()?, frame))
+        })
+        .collect()
+}
+
+/// Screen size stated in an example's dimensions, if both `width` and `height` are present.
+pub fn screen_size(example: &Example) -> Option<Size> {
+    let Value::Dict(dims) = &example.0 else { return None };
+    let get = |key: &str| dims.iter().find(|(k, _)| k == key).and_then(|(_, v)| v.as_f64());
+    Some(Size::new(get("width")?, get("height")?))
+}
+
+/// Safe-area insets stated in an example's dimensions, e.g. by a device preset; zero if none are.
+pub fn safe_area(example: &Example) -> Insets {
+    let Value::Dict(dims) = &example.0 else { return Insets::default() };
+    let Some((_, Value::Dict(insets))) = dims.iter().find(|(k, _)| k == "safe_area") else { return Insets::default() };
+    let get = |key: &str| insets.iter().find(|(k, _)| k == key).and_then(|(_, v)| v.as_f64()).unwrap_or(0.0);
+    Insets::new(get("top"), get("bottom"), get("leading"), get("trailing"))
+}
+
+/// Dynamic Type size stated in an example's dimensions; `large` if none is.
+pub fn dynamic_type(example: &Example) -> DynamicType {
+    let Value::Dict(dims) = &example.0 else { return DynamicType::default() };
+    match dims.iter().find(|(k, _)| k == "dynamic_type") {
+        Some((_, Value::String(size))) => size.parse().unwrap_or_default(),
+        _ => DynamicType::default(),
+    }
+}
+
+/// Layout direction stated in an example's dimensions; left to right if none is.
+pub fn layout_direction(example: &Example) -> LayoutDirection {
+    let Value::Dict(dims) = &example.0 else { return LayoutDirection::default() };
+    match dims.iter().find(|(k, _)| k == "layout_direction") {
+        Some((_, Value::String(direction))) => direction.parse().unwrap_or_default(),
+        _ => LayoutDirection::default(),
+    }
+}
+
+/// How a layout copes with text that outgrows the screen at an example's Dynamic Type size.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TextFit {
+    /// The content is taller than the screen, so it goes in a `ScrollView`.
+    pub scrolls: bool,
+    /// The content is wider than the screen, so text may shrink down to this fraction of
+    /// its size (`.minimumScaleFactor`).
+    pub minimum_scale_factor: Option<f64>,
+}
+
+/// Text shrunk further than this is unreadable, so wider content is left to overflow.
+pub const MINIMUM_SCALE_FACTOR: f64 = 0.5;
+
+/// What `ir` needs to stay usable at the Dynamic Type sizes the examples state. Examples at
+/// the default size get no such help: content must fit them as laid out.
+pub fn text_fit(ir: &IR, examples: &[Example], metrics: &Metrics) -> TextFit {
+    let ignores_safe_area = ignores_safe_area(examples);
+    let mut fit = TextFit::default();
+    for example in examples.iter().filter(|example| dynamic_type(example) != DynamicType::default()) {
+        let metrics = metrics.scaled(dynamic_type(example).scale());
+        let Some(screen) = screen_for(ir, example, &metrics) else { continue };
+        let insets = if ignores_safe_area { Insets::default() } else { safe_area(example) };
+        let layout = simulate_within(ir, screen, insets, &metrics);
+        let (required, bounds) = (layout.required, layout.bounds());
+        fit.scrolls |= required.height > bounds.height + 0.5;
+        if required.width > bounds.width + 0.5 {
+            // Rounded down to a twentieth, so the code reads `.minimumScaleFactor(0.75)`
+            let factor = (bounds.width / required.width * 20.0).floor() / 20.0;
+            if factor >= MINIMUM_SCALE_FACTOR {
+                fit.minimum_scale_factor = Some(fit.minimum_scale_factor.map_or(factor, |other: f64| other.min(factor)));
+            }
+        }
+    }
+    fit
+}
+
+/// Whether any example states a frame reaching into its safe-area insets. Such content sits
+/// under the status bar or home indicator, so the layout must ignore the safe area (and is
+/// emitted with `.ignoresSafeArea()`) instead of staying inside it.
+pub fn ignores_safe_area(examples: &[Example]) -> bool {
+    examples.iter().any(|example| {
+        let (Some(screen), insets) = (screen_size(example), safe_area(example)) else { return false };
+        stated_frames(example).iter().any(|(_, frame)| {
+            let max_x = frame.x.zip(frame.width).map(|(x, width)| x + width);
+            let max_y = frame.y.zip(frame.height).map(|(y, height)| y + height);
+            frame.y.is_some_and(|y| y < insets.top - 0.5)
+                || max_y.is_some_and(|max_y| max_y > screen.height - insets.bottom + 0.5)
+                || frame.x.is_some_and(|x| x < insets.leading - 0.5)
+                || max_x.is_some_and(|max_x| max_x > screen.width - insets.trailing + 0.5)
+        })
+    })
+}
+
+/// Short description of an example's screen: `390x844`, or with a device preset
+/// `iPhone 15 Pro landscape (852x393)`.
+pub fn describe_screen(example: &Example) -> Option<String> {
+    let size = screen_size(example)?;
+    let Value::Dict(dims) = &example.0 else { return None };
+    let text = |key: &str| match dims.iter().find(|(k, _)| k == key) {
+        Some((_, Value::String(s))) => Some(s.as_str()),
+        _ => None,
+    };
+    let dimensions = format!("{}x{}", size.width, size.height);
+    Some(match (text("device"), text("orientation")) {
+        (Some(device), Some(orientation)) => format!("{} {} ({})", device, orientation, dimensions),
+        (Some(device), None) => format!("{} ({})", device, dimensions),
+        _ => dimensions,
+    })
+}
+
+/// Screen on which to lay out `ir` for an example. A dimension given as `?` is unknown and
+/// taken to be exactly what the content needs plus the safe area, so the content never
+/// overflows along it.
+pub fn screen_for(ir: &IR, example: &Example, metrics: &Metrics) -> Option<Size> {
+    let Value::Dict(dims) = &example.0 else { return None };
+    let get = |key: &str| match dims.iter().find(|(k, _)| k == key).map(|(_, v)| v) {
+        Some(Value::Any) => Some(None),
+        Some(value) => value.as_f64().map(Some),
+        None => None,
+    };
+    let (width, height) = (get("width")?, get("height")?);
+    let insets = safe_area(example);
+    let (horizontal, vertical) = (insets.leading + insets.trailing, insets.top + insets.bottom);
+    let width = width.unwrap_or_else(|| measure(ir, f64::INFINITY, metrics).width + horizontal);
+    let height = height.unwrap_or_else(|| measure(ir, width - horizontal, metrics).height + vertical);
+    Some(Size::new(width, height))
+}
+
+/// Lays out `ir` on a screen of the given size.
+pub fn simulate(ir: &IR, screen: Size, metrics: &Metrics) -> Layout {
+    simulate_within(ir, screen, Insets::default(), metrics)
+}
+
+/// Lays out `ir` on a screen of the given size, keeping the content inside the safe area
+/// left by `safe_area`. Frames are still in screen coordinates.
+pub fn simulate_within(ir: &IR, screen: Size, safe_area: Insets, metrics: &Metrics) -> Layout {
+    let bounds = inset(screen, safe_area);
+    let required = measure(ir, bounds.width, metrics);
+    let size = Size::new(
+        if is_flexible(ir, Axis::Horizontal, metrics) { bounds.width } else { required.width.min(bounds.width) },
+        if is_flexible(ir, Axis::Vertical, metrics) { bounds.height } else { required.height },
+    );
+    // Like a root view, the content is centered in the safe area
+    let origin_x = bounds.x + (bounds.width - size.width) / 2.0;
+    let origin_y = bounds.y + ((bounds.height - size.height) / 2.0).max(0.0);
+    let mut frames = Vec::new();
+    place(ir, Rect::new(origin_x, origin_y, size.width, size.height), &mut Vec::new(), metrics, &mut frames);
+    Layout { screen, safe_area, direction: LayoutDirection::default(), frames, required }
+}
+
+fn inset(screen: Size, insets: Insets) -> Rect {
+    Rect::new(
+        insets.leading,
+        insets.top,
+        screen.width - insets.leading - insets.trailing,
+        screen.height - insets.top - insets.bottom,
+    )
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+// Whether a view grows to fill the space offered along `axis`.
+fn is_flexible(ir: &IR, axis: Axis, metrics: &Metrics) -> bool {
+    match ir {
+        IR::Spacer => true,
+        IR::Custom { name, .. } => {
+            axis == Axis::Horizontal && metrics.custom_sizes.get(name).is_none_or(|size| size.width.is_none())
+        }
+        IR::VStack(children) | IR::HStack(children) => children.iter().any(|c| is_flexible(c, axis, metrics)),
+        _ => false,
+    }
+}
+
+/// Minimum size of `ir` when offered `width` points horizontally.
+fn measure(ir: &IR, width: f64, metrics: &Metrics) -> Size {
+    let pad = metrics.padding;
+    match ir {
+        IR::Text(text) => {
+            let label = text_metrics::measure(text, &metrics.text.title, metrics.title_font_size, metrics.title_line_height, width - 2.0 * pad);
+            Size::new(label.width + 2.0 * pad, label.height + 2.0 * pad)
+        }
+        IR::Button(label) => {
+            let label = text_metrics::measure(label, &metrics.text.body, metrics.body_font_size, metrics.body_line_height, width - 2.0 * pad);
+            Size::new(label.width + 2.0 * pad, label.height + 2.0 * pad)
+        }
+        IR::Image(_) => Size::new(metrics.image_size, metrics.image_size),
+        IR::Custom { name, .. } => match metrics.custom_sizes.get(name) {
+            Some(size) => Size::new(size.width.unwrap_or(0.0), size.height),
+            None if name == DIVIDER => Size::new(0.0, DIVIDER_THICKNESS),
+            None => Size::new(0.0, metrics.custom_height),
+        },
+        IR::Spacer => Size::new(metrics.spacer_min_length, metrics.spacer_min_length),
+        IR::VStack(children) => {
+            let inner = width - 2.0 * pad;
+            let sizes: Vec<Size> = children.iter().map(|c| measure(c, inner, metrics)).collect();
+            let spacing = metrics.stack_spacing * children.len().saturating_sub(1) as f64;
+            Size::new(
+                sizes.iter().map(|s| s.width).fold(0.0, f64::max) + 2.0 * pad,
+                sizes.iter().map(|s| s.height).sum::<f64>() + spacing + 2.0 * pad,
+            )
+        }
+        IR::HStack(children) => {
+            let inner = width - 2.0 * pad;
+            let spacing = metrics.stack_spacing * children.len().saturating_sub(1) as f64;
+            // Offer each child an equal share of the row, like SwiftUI's first pass
+            let share = (inner - spacing) / children.len().max(1) as f64;
+            let sizes: Vec<Size> = children.iter().map(|c| measure(c, share, metrics)).collect();
+            Size::new(
+                sizes.iter().map(|s| s.width).sum::<f64>() + spacing + 2.0 * pad,
+                sizes.iter().map(|s| s.height).fold(0.0, f64::max) + 2.0 * pad,
+            )
+        }
+    }
+}
+
+fn place(ir: &IR, rect: Rect, path: &mut Vec<usize>, metrics: &Metrics, frames: &mut Vec<Frame>) {
+    frames.push(Frame { path: path.clone(), node: ir.clone(), rect });
+    let (children, axis) = match ir {
+        IR::VStack(children) => (children, Axis::Vertical),
+        IR::HStack(children) => (children, Axis::Horizontal),
+        _ => return,
+    };
+    let pad = metrics.padding;
+    let inner = Rect::new(rect.x + pad, rect.y + pad, rect.width - 2.0 * pad, rect.height - 2.0 * pad);
+    let spacing = metrics.stack_spacing * children.len().saturating_sub(1) as f64;
+    let (main_len, cross_len) = match axis {
+        Axis::Vertical => (inner.height, inner.width),
+        Axis::Horizontal => (inner.width, inner.height),
+    };
+    let share = (inner.width - spacing) / children.len().max(1) as f64;
+    let sizes: Vec<Size> = children
+        .iter()
+        .map(|c| measure(c, if axis == Axis::Vertical { inner.width } else { share }, metrics))
+        .collect();
+    let main = |s: &Size| if axis == Axis::Vertical { s.height } else { s.width };
+    let cross = |s: &Size| if axis == Axis::Vertical { s.width } else { s.height };
+
+    // Flexible children split whatever the fixed ones leave over
+    let flexible: Vec<bool> = children.iter().map(|c| is_flexible(c, axis, metrics)).collect();
+    let fixed: f64 = sizes.iter().zip(&flexible).filter(|(_, f)| !**f).map(|(s, _)| main(s)).sum();
+    let flexible_count = flexible.iter().filter(|f| **f).count();
+    let free = main_len - fixed - spacing;
+    let flexible_share = if flexible_count > 0 { free / flexible_count as f64 } else { 0.0 };
+    // Without flexible children the content is centered along the axis
+    let mut cursor = if flexible_count == 0 { (free / 2.0).max(0.0) } else { 0.0 };
+
+    for (index, child) in children.iter().enumerate() {
+        let size = sizes[index];
+        let length = if flexible[index] { flexible_share.max(main(&size)) } else { main(&size) };
+        let child_cross = if is_flexible(child, other(axis), metrics) { cross_len } else { cross(&size).min(cross_len) };
+        let cross_offset = (cross_len - child_cross) / 2.0;
+        let child_rect = match axis {
+            Axis::Vertical => Rect::new(inner.x + cross_offset, inner.y + cursor, child_cross, length),
+            Axis::Horizontal => Rect::new(inner.x + cursor, inner.y + cross_offset, length, child_cross),
+        };
+        path.push(index);
+        place(child, child_rect, path, metrics, frames);
+        path.pop();
+        cursor += length + metrics.stack_spacing;
+    }
+}
+
+fn other(axis: Axis) -> Axis {
+    match axis {
+        Axis::Horizontal => Axis::Vertical,
+        Axis::Vertical => Axis::Horizontal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn screen() -> Size {
+        Size::new(390.0, 844.0)
+    }
+
+    #[test]
+    fn test_safe_area_keeps_content_clear_of_the_insets() {
+        let ir = IR::VStack(vec![IR::Text("Hello".to_string()), IR::Spacer, IR::Button("Go".to_string())]);
+        let insets = Insets::new(59.0, 34.0, 0.0, 0.0);
+        let layout = simulate_within(&ir, screen(), insets, &Metrics::default());
+        assert_eq!(layout.bounds(), Rect::new(0.0, 59.0, 390.0, 844.0 - 59.0 - 34.0));
+        assert_eq!(layout.frames[0].rect, layout.bounds());
+        let button = layout.leaves().last().unwrap().rect;
+        assert_eq!(button.max_y(), 844.0 - 34.0 - 16.0);
+
+        let example = |frame: &str| {
+            crate::input::parser::parse_examples(&format!("{{(device:\"iPhone 15\"):{{title:\"Hi\"{}}}}}", frame)).unwrap()
+        };
+        assert!(!ignores_safe_area(&example("@(y:70)")));
+        assert!(ignores_safe_area(&example("@(y:0)")));
+        assert!(ignores_safe_area(&example("@(y:820,h:32)")));
+    }
+
+    #[test]
+    fn test_right_to_left_mirrors_the_frames() {
+        let ir = IR::HStack(vec![IR::Text("A".to_string()), IR::Spacer, IR::Text("B".to_string())]);
+        let ltr = simulate_within(&ir, screen(), Insets::new(0.0, 0.0, 44.0, 0.0), &Metrics::default());
+        let rtl = ltr.clone().with_direction(LayoutDirection::RightToLeft);
+        for (a, b) in ltr.frames.iter().zip(&rtl.frames) {
+            assert_eq!(b.rect.x, 390.0 - a.rect.max_x());
+            assert_eq!((b.rect.y, b.rect.width), (a.rect.y, a.rect.width));
+        }
+        assert_eq!(rtl.bounds(), Rect::new(0.0, 0.0, 390.0 - 44.0, 844.0));
+        assert_eq!(rtl.clone().with_direction(LayoutDirection::RightToLeft), rtl);
+    }
+
+    #[test]
+    fn test_unknown_dimensions_fit_the_content() {
+        let ir = IR::VStack(vec![IR::Text("Hello".to_string()), IR::Spacer, IR::Button("Go".to_string())]);
+        let dims = |width, height| {
+            (Value::Dict(vec![("width".to_string(), width), ("height".to_string(), height)]), Value::Dict(vec![]))
+        };
+        let metrics = Metrics::default();
+        let fitted = screen_for(&ir, &dims(Value::Int(390), Value::Any), &metrics).unwrap();
+        assert_eq!(fitted, Size::new(390.0, measure(&ir, 390.0, &metrics).height));
+        assert!(!simulate(&ir, fitted, &metrics).overflows());
+        let fitted = screen_for(&ir, &dims(Value::Any, Value::Any), &metrics).unwrap();
+        assert_eq!(fitted.width, metrics.text.title.width("Hello", 28.0) + 4.0 * 16.0);
+        assert_eq!(screen_for(&ir, &dims(Value::Int(390), Value::String("tall".to_string())), &metrics), None);
+    }
+
+    #[test]
+    fn test_vstack_with_spacer_fills_screen() {
+        let ir = IR::VStack(vec![IR::Text("Hello".to_string()), IR::Spacer, IR::Button("Go".to_string())]);
+        let layout = simulate(&ir, screen(), &Metrics::default());
+        assert_eq!(layout.frames[0].rect, Rect::new(0.0, 0.0, 390.0, 844.0));
+        assert!(!layout.overflows());
+
+        let leaves: Vec<&Frame> = layout.leaves().collect();
+        assert_eq!(leaves.len(), 2);
+        // Title at the top (inside the stack padding), button pinned to the bottom
+        assert_eq!(leaves[0].rect.y, 16.0);
+        assert_eq!(leaves[0].rect.height, 34.0 + 32.0);
+        assert_eq!(leaves[1].rect.max_y(), 844.0 - 16.0);
+        // Both centered horizontally
+        let center = |r: &Rect| r.x + r.width / 2.0;
+        assert_eq!(center(&leaves[0].rect), 195.0);
+        assert_eq!(center(&leaves[1].rect), 195.0);
+    }
+
+    #[test]
+    fn test_vstack_without_spacer_is_centered() {
+        let ir = IR::VStack(vec![IR::Image("logo".to_string())]);
+        let layout = simulate(&ir, screen(), &Metrics::default());
+        let stack = layout.frames[0].rect;
+        assert_eq!(stack.height, 60.0 + 32.0);
+        assert_eq!(stack.y, (844.0 - 92.0) / 2.0);
+    }
+
+    #[test]
+    fn test_hstack_spacer_pushes_children_apart() {
+        let ir = IR::HStack(vec![IR::Text("A".to_string()), IR::Spacer, IR::Text("B".to_string())]);
+        let layout = simulate(&ir, screen(), &Metrics::default());
+        let leaves: Vec<&Frame> = layout.leaves().collect();
+        assert_eq!(leaves[0].rect.x, 16.0);
+        assert_eq!(leaves[1].rect.max_x(), 390.0 - 16.0);
+        assert_eq!(leaves[0].rect.y, leaves[1].rect.y);
+    }
+
+    #[test]
+    fn test_long_text_wraps_and_overflow_is_detected() {
+        let long = "word ".repeat(40);
+        let ir = IR::VStack(vec![IR::Text(long.clone()), IR::Text(long.clone()), IR::Text(long), IR::Spacer]);
+        let big = simulate(&ir, Size::new(1024.0, 1366.0), &Metrics::default());
+        assert!(!big.overflows());
+        let small = simulate(&ir, Size::new(320.0, 480.0), &Metrics::default());
+        assert!(small.overflows());
+        assert!(small.leaves().next().unwrap().rect.height > 34.0 * 5.0);
+    }
+
+    #[test]
+    fn test_line_breaks_start_new_lines() {
+        let metrics = Metrics::default();
+        let height = |text: &str| simulate(&IR::Text(text.to_string()), Size::new(390.0, 844.0), &metrics).leaves().next().unwrap().rect.height;
+        assert_eq!(height("Welcome\nback"), height("Welcome") + metrics.title_line_height);
+    }
+
+    #[test]
+    fn test_frame_paths() {
+        let ir = IR::VStack(vec![IR::HStack(vec![IR::Text("A".to_string())])]);
+        let layout = simulate(&ir, screen(), &Metrics::default());
+        let paths: Vec<Vec<usize>> = layout.frames.iter().map(|f| f.path.clone()).collect();
+        assert_eq!(paths, vec![vec![], vec![0], vec![0, 0]]);
+    }
+
+    #[test]
+    fn test_registered_custom_sizes() {
+        let avatar = IR::Custom { name: "AvatarView".to_string(), args: String::new() };
+        let chart = IR::Custom { name: "Chart".to_string(), args: String::new() };
+        let ir = IR::VStack(vec![avatar, chart]);
+        let mut metrics = Metrics::default();
+        metrics.custom_sizes.insert("AvatarView".to_string(), CustomSize { width: Some(64.0), height: 64.0 });
+        let layout = simulate(&ir, screen(), &metrics);
+        let leaves: Vec<&Frame> = layout.leaves().collect();
+        assert_eq!((leaves[0].rect.width, leaves[0].rect.height), (64.0, 64.0));
+        // Unregistered custom views still fill the width
+        assert_eq!((leaves[1].rect.width, leaves[1].rect.height), (390.0 - 32.0, 44.0));
+    }
+
+    #[test]
+    fn test_dividers_are_hairlines_across_the_stack() {
+        let divider = IR::Custom { name: DIVIDER.to_string(), args: String::new() };
+        let layout = simulate(&IR::VStack(vec![IR::Text("A".to_string()), divider]), screen(), &Metrics::default());
+        let rect = layout.leaves().last().unwrap().rect;
+        assert_eq!((rect.width, rect.height), (390.0 - 32.0, DIVIDER_THICKNESS));
+        let frame = |width, height| StatedFrame { width, height, ..StatedFrame::default() };
+        assert!(frame(Some(358.0), Some(1.0)).is_separator(390.0));
+        assert!(!frame(Some(200.0), Some(1.0)).is_separator(390.0));
+        assert!(!frame(Some(390.0), Some(20.0)).is_separator(390.0));
+        assert!(!frame(None, Some(1.0)).is_separator(390.0));
+    }
+}
@@ -0,0 +1,194 @@
+// File: src/layout/spacing.rs
+// Snapping of the paddings and spacings the generated code spells out to a spacing scale, so
+// a stack spacing fitted as 13pt is written as the design system's 12 instead, unless no step
+// of the scale is close enough to stand for it.
+use serde_json::Value;
+use std::fmt;
+
+use crate::layout::Metrics;
+
+/// Steps of the scale without a configured one or spacing tokens.
+pub const DEFAULT_SCALE: [f64; 10] = [4.0, 8.0, 12.0, 16.0, 20.0, 24.0, 32.0, 40.0, 48.0, 64.0];
+
+/// Points a value may be from its step and still snap to it.
+pub const DEFAULT_TOLERANCE: f64 = 2.0;
+
+/// A spacing scale: its steps in increasing order, and how far a value may be from one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scale {
+    steps: Vec<f64>,
+    tolerance: f64,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Scale { steps: DEFAULT_SCALE.to_vec(), tolerance: DEFAULT_TOLERANCE }
+    }
+}
+
+impl Scale {
+    /// A scale of `steps`, in any order, which must be positive; `tolerance` must not be negative.
+    pub fn new(mut steps: Vec<f64>, tolerance: f64) -> Result<Scale, String> {
+        if steps.is_empty() {
+            return Err("The spacing scale has no steps".to_string());
+        }
+        if let Some(step) = steps.iter().find(|step| !step.is_finite() || **step <= 0.0) {
+            return Err(format!("Invalid spacing step {}: steps must be positive", step));
+        }
+        if !tolerance.is_finite() || tolerance < 0.0 {
+            return Err(format!("Invalid spacing tolerance {}: it must not be negative", tolerance));
+        }
+        steps.sort_by(f64::total_cmp);
+        steps.dedup();
+        Ok(Scale { steps, tolerance })
+    }
+
+    pub fn steps(&self) -> &[f64] {
+        &self.steps
+    }
+
+    pub fn tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    /// The step nearest `value`; the smaller of two as near.
+    pub fn nearest(&self, value: f64) -> f64 {
+        self.steps.iter().copied().min_by(|a, b| (a - value).abs().total_cmp(&(b - value).abs())).expect("a scale has steps")
+    }
+
+    /// `value` snapped to its nearest step, or that step as the error if it is beyond the tolerance.
+    pub fn snap(&self, value: f64) -> Result<f64, f64> {
+        let step = self.nearest(value);
+        match (step - value).abs() <= self.tolerance {
+            true => Ok(step),
+            false => Err(step),
+        }
+    }
+}
+
+/// A value left as it was because the nearest step is beyond the tolerance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Miss {
+    /// The layout constant, e.g. `stack_spacing`.
+    pub name: &'static str,
+    pub value: f64,
+    pub step: f64,
+    pub tolerance: f64,
+}
+
+impl fmt::Display for Miss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {}pt is {}pt from the nearest step of the spacing scale, {}pt, beyond the tolerance of {}pt; it is kept as is",
+            self.name,
+            self.value,
+            (self.value - self.step).abs(),
+            self.step,
+            self.tolerance
+        )
+    }
+}
+
+/// Snaps the padding and stack spacing of `metrics` to `scale`, returning those too far from
+/// any step, which keep their value.
+pub fn snap_metrics(metrics: &mut Metrics, scale: &Scale) -> Vec<Miss> {
+    let mut misses = Vec::new();
+    for (name, value) in [("padding", &mut metrics.padding), ("stack_spacing", &mut metrics.stack_spacing)] {
+        match scale.snap(*value) {
+            Ok(step) => *value = step,
+            Err(step) => misses.push(Miss { name, value: *value, step, tolerance: scale.tolerance }),
+        }
+    }
+    misses
+}
+
+// A dimension written as a number or a string like `16`, `16px` or `16pt`
+fn dimension(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => {
+            let text = text.trim();
+            text.strip_suffix("px").or_else(|| text.strip_suffix("pt")).unwrap_or(text).trim().parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// The spacing tokens of a JSON token file, named by their dotted path, e.g. `spacing.md`.
+/// Tokens nest as for [`crate::synthesis::a11y::load_colors`]; a spacing token is a positive
+/// dimension under a key naming spacing, space or gap. Other tokens are skipped.
+pub fn load_spacing(json: &str) -> Result<Vec<(String, f64)>, String> {
+    fn walk(value: &Value, path: &mut Vec<String>, out: &mut Vec<(String, f64)>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let named = key != "value" && key != "$value";
+                    if named {
+                        path.push(key.clone());
+                    }
+                    walk(value, path, out);
+                    if named {
+                        path.pop();
+                    }
+                }
+            }
+            leaf => {
+                let spacing = path.iter().any(|key| ["spacing", "space", "gap"].iter().any(|word| key.to_lowercase().contains(word)));
+                if let Some(points) = dimension(leaf).filter(|points| spacing && points.is_finite() && *points > 0.0) {
+                    out.push((path.join("."), points));
+                }
+            }
+        }
+    }
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let mut spacing = Vec::new();
+    walk(&value, &mut Vec::new(), &mut spacing);
+    Ok(spacing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_values_snap_within_the_tolerance() {
+        let scale = Scale::default();
+        assert_eq!(scale.snap(13.0), Ok(12.0));
+        assert_eq!(scale.snap(14.0), Ok(12.0));
+        assert_eq!(scale.snap(28.0), Err(24.0));
+        assert_eq!(scale.snap(100.0), Err(64.0));
+        let mut metrics = Metrics { padding: 15.2, stack_spacing: 28.0, ..Metrics::default() };
+        let misses = snap_metrics(&mut metrics, &scale);
+        assert_eq!((metrics.padding, metrics.stack_spacing), (16.0, 28.0));
+        assert_eq!(misses.len(), 1);
+        assert_eq!(
+            misses[0].to_string(),
+            "stack_spacing of 28pt is 4pt from the nearest step of the spacing scale, 24pt, beyond the tolerance of 2pt; it is kept as is"
+        );
+    }
+
+    #[test]
+    fn test_scales_are_checked() {
+        assert_eq!(Scale::new(vec![16.0, 4.0, 8.0, 4.0], 1.0).unwrap().steps(), [4.0, 8.0, 16.0]);
+        assert!(Scale::new(Vec::new(), 1.0).unwrap_err().contains("no steps"));
+        assert!(Scale::new(vec![0.0], 1.0).unwrap_err().contains("must be positive"));
+        assert!(Scale::new(vec![4.0], -1.0).unwrap_err().contains("must not be negative"));
+    }
+
+    #[test]
+    fn test_spacing_tokens() {
+        let json = r##"{
+            "spacing": { "sm": { "$value": "8px" }, "md": 16, "lg": { "value": "24" } },
+            "layout": { "gap": "12pt" },
+            "color": { "primary": "#007AFF" },
+            "font": { "size": 17 }
+        }"##;
+        let spacing = load_spacing(json).unwrap();
+        assert_eq!(
+            spacing,
+            [("layout.gap".to_string(), 12.0), ("spacing.lg".to_string(), 24.0), ("spacing.md".to_string(), 16.0), ("spacing.sm".to_string(), 8.0)]
+        );
+        assert!(load_spacing("{").is_err());
+    }
+}
@@ -0,0 +1,227 @@
+// File: src/layout/text_metrics.rs
+// How wide text is and where it wraps, so the simulator can size a `Text` or a button's label
+// without running SwiftUI. Widths come from a table of character advances per font style,
+// which the config can replace (`[fonts]`) for apps with a custom typeface.
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use super::Size;
+
+/// Character advances of a font, in ems: fractions of the font size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontTable {
+    /// Advance of a character the table does not list.
+    pub default: f64,
+    pub advances: BTreeMap<char, f64>,
+}
+
+// Advances in thousandths of an em of Helvetica's standard metrics, whose proportions the
+// system font shares closely enough for layout
+const PROPORTIONAL: &[(&str, f64)] = &[
+    (" !,./:;I[]\\ft", 278.0),
+    ("ijl", 222.0),
+    ("\"", 355.0),
+    ("'", 191.0),
+    ("()-`r{}", 333.0),
+    ("*", 389.0),
+    ("+<=>~", 584.0),
+    ("#$0123456789?_abdeghnopqu", 556.0),
+    ("Jckszvxy", 500.0),
+    ("%", 889.0),
+    ("&ABEKPSVXY", 667.0),
+    ("CDHNRUw", 722.0),
+    ("FTZ", 611.0),
+    ("GOQ", 778.0),
+    ("L", 556.0),
+    ("Mm", 833.0),
+    ("W", 944.0),
+    ("@", 1015.0),
+    ("|", 260.0),
+    ("^", 469.0),
+];
+
+impl FontTable {
+    /// Every character `advance` ems wide.
+    pub fn uniform(advance: f64) -> FontTable {
+        FontTable { default: advance, advances: BTreeMap::new() }
+    }
+
+    /// Proportional widths, close to those of the system font: `i` is narrow and `W` wide.
+    pub fn proportional() -> FontTable {
+        let advances = PROPORTIONAL.iter().flat_map(|(chars, width)| chars.chars().map(move |ch| (ch, width / 1000.0))).collect();
+        FontTable { default: 0.556, advances }
+    }
+
+    /// Advance of `ch` in ems. Combining marks take no room, and ideographs, kana, hangul and
+    /// emoji a full em, unless the table lists them.
+    pub fn advance(&self, ch: char) -> f64 {
+        if let Some(advance) = self.advances.get(&ch) {
+            return *advance;
+        }
+        match u32::from(ch) {
+            0x0300..=0x036f | 0x200b..=0x200d | 0xfe00..=0xfe0f => 0.0,
+            0x1100..=0x115f | 0x2e80..=0xa4cf | 0xac00..=0xd7a3 | 0xf900..=0xfaff | 0xff00..=0xff60 | 0x1f300..=0x1faff => 1.0,
+            _ => self.default,
+        }
+    }
+
+    /// Width in points of `text`, on one line, at `font_size`.
+    pub fn width(&self, text: &str, font_size: f64) -> f64 {
+        text.chars().map(|ch| self.advance(ch)).sum::<f64>() * font_size
+    }
+}
+
+/// The font tables of the text styles the layout uses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextMetrics {
+    /// `Text` views (`.title`, or the configured text font).
+    pub title: FontTable,
+    /// Button labels (`.body`).
+    pub body: FontTable,
+}
+
+impl Default for TextMetrics {
+    fn default() -> Self {
+        TextMetrics { title: FontTable::proportional(), body: FontTable::proportional() }
+    }
+}
+
+/// A font table as the config states it: a built-in table and advances that override it.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct FontTableConfig {
+    /// `proportional` (the default) or `uniform`.
+    pub base: Option<String>,
+    /// Advance in ems of characters the table does not list; for `uniform`, of every one.
+    pub default: Option<f64>,
+    /// Advances in ems by character, e.g. `{ "W" = 0.95 }`.
+    pub advances: BTreeMap<String, f64>,
+}
+
+impl FontTableConfig {
+    /// The table this describes, or why it is not one.
+    pub fn table(&self) -> Result<FontTable, String> {
+        let mut table = match self.base.as_deref() {
+            None | Some("proportional") => FontTable::proportional(),
+            Some("uniform") => FontTable::uniform(0.55),
+            Some(other) => return Err(format!("Unknown font table '{}': expected proportional or uniform", other)),
+        };
+        if let Some(default) = self.default {
+            table.default = default;
+        }
+        for (text, advance) in &self.advances {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(ch), None) if advance.is_finite() && *advance >= 0.0 => {
+                    table.advances.insert(ch, *advance);
+                }
+                (Some(_), None) => return Err(format!("The advance of '{}' must be a number of ems of 0 or more, not {}", text, advance)),
+                _ => return Err(format!("Font table advances are by single character, not '{}'", text)),
+            }
+        }
+        Ok(table)
+    }
+}
+
+/// Breaks `line` into the lines it takes in `available` points, as text wraps: at spaces,
+/// and inside a word only when the word alone is too wide. Spaces at a break are dropped.
+pub fn wrap<'a>(line: &'a str, table: &FontTable, font_size: f64, available: f64) -> Vec<&'a str> {
+    let mut lines = Vec::new();
+    let (mut start, mut end, mut width) = (0, 0, 0.0);
+    let mut rest = line;
+    let mut offset = 0;
+    while !rest.is_empty() {
+        // The next word with the spaces before it
+        let spaces = rest.len() - rest.trim_start_matches(' ').len();
+        let word_len = rest[spaces..].find(' ').unwrap_or(rest.len() - spaces);
+        let (gap, word) = (&rest[..spaces], &rest[spaces..spaces + word_len]);
+        let (gap_width, word_width) = (table.width(gap, font_size), table.width(word, font_size));
+        if end > start && width + gap_width + word_width > available {
+            lines.push(&line[start..end]);
+            (start, width) = (offset + spaces, 0.0);
+        } else {
+            width += gap_width;
+        }
+        // A word wider than a whole line is broken between characters
+        let mut at = offset + spaces;
+        for (index, ch) in word.char_indices() {
+            let advance = table.advance(ch) * font_size;
+            if width + advance > available && at > start {
+                lines.push(&line[start..at]);
+                (start, width) = (at, 0.0);
+            }
+            width += advance;
+            at = offset + spaces + index + ch.len_utf8();
+        }
+        end = at;
+        offset += spaces + word_len;
+        rest = &rest[spaces + word_len..];
+    }
+    lines.push(&line[start..end.max(start)]);
+    lines
+}
+
+/// Size of a label of `text` at `font_size` given `available` points of width. Each line of
+/// a multi-line label wraps on its own, and the label is as wide as its widest line.
+pub fn measure(text: &str, table: &FontTable, font_size: f64, line_height: f64, available: f64) -> Size {
+    let available = available.max(font_size);
+    let (mut width, mut lines) = (0.0f64, 0usize);
+    for line in text.split('\n') {
+        for wrapped in wrap(line, table, font_size, available) {
+            width = width.max(table.width(wrapped, font_size));
+            lines += 1;
+        }
+    }
+    Size::new(width.min(available), lines as f64 * line_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_widths_match_the_standard_metrics() {
+        let table = FontTable::proportional();
+        // Helvetica's advances at 10pt: H 7.22, e 5.56, l 2.22 twice, o 5.56
+        assert!((table.width("Hello", 10.0) - 22.78).abs() < 1e-9);
+        assert!((table.width("Sign in", 17.0) - 17.0 * (0.667 + 0.222 + 0.556 + 0.556 + 0.278 + 0.222 + 0.556)).abs() < 1e-9);
+        assert!(table.width("WWW", 17.0) > 2.0 * table.width("iii", 17.0) * 2.0);
+        // A combining accent adds nothing; an emoji or ideograph is an em
+        assert_eq!(table.width("e\u{301}", 10.0), table.width("e", 10.0));
+        assert_eq!(table.width("日本", 10.0), 20.0);
+        assert_eq!(FontTable::uniform(0.5).width("Wi", 10.0), 10.0);
+    }
+
+    #[test]
+    fn test_wrapping_breaks_at_spaces_then_inside_words() {
+        let table = FontTable::uniform(0.5);
+        // Each character is 5pt, so 50pt holds ten
+        assert_eq!(wrap("Welcome back to the app", &table, 10.0, 50.0), ["Welcome", "back to", "the app"]);
+        assert_eq!(wrap("Internationalization", &table, 10.0, 50.0), ["Internatio", "nalization"]);
+        assert_eq!(wrap("Hi  there", &table, 10.0, 100.0), ["Hi  there"]);
+        assert_eq!(wrap("", &table, 10.0, 50.0), [""]);
+        assert_eq!(wrap("  lead", &table, 10.0, 50.0), ["  lead"]);
+    }
+
+    #[test]
+    fn test_measure_lines_and_width() {
+        let table = FontTable::uniform(0.5);
+        assert_eq!(measure("Welcome back to the app", &table, 10.0, 12.0, 50.0), Size::new(35.0, 36.0));
+        assert_eq!(measure("Hi\nthere", &table, 10.0, 12.0, 100.0), Size::new(25.0, 24.0));
+        // Less room than the font size is taken as the font size, which holds two here
+        assert_eq!(measure("abc", &table, 10.0, 12.0, 1.0), Size::new(10.0, 24.0));
+    }
+
+    #[test]
+    fn test_configured_tables() {
+        let config = FontTableConfig { base: Some("uniform".to_string()), default: Some(0.6), advances: [("W".to_string(), 1.0)].into() };
+        let table = config.table().unwrap();
+        assert_eq!((table.advance('a'), table.advance('W')), (0.6, 1.0));
+        assert_eq!(FontTableConfig::default().table().unwrap(), FontTable::proportional());
+        let bad = |advances: &[(&str, f64)]| FontTableConfig { advances: advances.iter().map(|(k, v)| (k.to_string(), *v)).collect(), ..Default::default() }.table().unwrap_err();
+        assert!(bad(&[("ab", 0.5)]).contains("single character"));
+        assert!(bad(&[("a", -1.0)]).contains("0 or more"));
+        assert!(FontTableConfig { base: Some("mono".to_string()), ..Default::default() }.table().unwrap_err().contains("Unknown font table 'mono'"));
+    }
+}
@@ -0,0 +1,216 @@
+// File: src/layout/text_style.rs
+// Dynamic Type text styles for the texts of a screen, told apart by their size across its
+// examples: the font size an example states for a text (`title:"Hi"@(font:13)`) or, failing
+// that, the height stated for it, read as a number of lines of one style. The heights also
+// tell whether a text wraps onto several lines or is meant to stay on one.
+use std::collections::BTreeMap;
+
+use crate::ast::{Example, Value};
+use crate::layout;
+
+/// SwiftUI's text styles with their font size and line height in points at the default
+/// (`large`) Dynamic Type size, largest first.
+pub const TEXT_STYLES: &[(&str, f64, f64)] = &[
+    ("largeTitle", 34.0, 41.0),
+    ("title", 28.0, 34.0),
+    ("title2", 22.0, 28.0),
+    ("title3", 20.0, 25.0),
+    ("headline", 17.0, 22.0),
+    ("body", 17.0, 22.0),
+    ("callout", 16.0, 21.0),
+    ("subheadline", 15.0, 20.0),
+    ("footnote", 13.0, 18.0),
+    ("caption", 12.0, 16.0),
+    ("caption2", 11.0, 13.0),
+];
+
+// The style whose `measure` (font size or line height) is nearest `value`; the first of two as near
+fn nearest(value: f64, measure: fn(&(&str, f64, f64)) -> f64) -> &'static (&'static str, f64, f64) {
+    TEXT_STYLES.iter().min_by(|a, b| (measure(a) - value).abs().total_cmp(&(measure(b) - value).abs())).expect("there are text styles")
+}
+
+/// The style of a font `size` points large: `headline` for the 17pt of both it and `body`.
+pub fn style_of_size(size: f64) -> &'static str {
+    nearest(size, |style| style.1).0
+}
+
+/// The font size, at the default Dynamic Type size, of the text `text` an example states
+/// `frame` for: its `font`, else the style whose lines its height fits best.
+fn stated_size(text: &str, frame: &layout::StatedFrame, scale: f64) -> Option<f64> {
+    if let Some(size) = frame.font_size {
+        return Some(size / scale);
+    }
+    let lines = text.lines().count().max(1) as f64;
+    // A line taller than the largest style's by half is text wrapping, which tells no size
+    let largest = TEXT_STYLES[0].2;
+    let height = frame.height.map(|height| height / lines / scale).filter(|line| *line <= largest * 1.5)?;
+    Some(nearest(height, |style| style.2).1)
+}
+
+/// Line height in points of the text style named `style`; that of `body` for an unknown one.
+pub fn line_height(style: &str) -> f64 {
+    TEXT_STYLES.iter().find(|(name, ..)| *name == style).map_or(22.0, |(_, _, line_height)| *line_height)
+}
+
+/// How a multiline text lines up its lines, from where its stated frame sits on the screen.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAlignment {
+    Leading,
+    Center,
+    Trailing,
+}
+
+/// How a text's lines are emitted, as the heights stated for it tell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wrapping {
+    /// Taller than a line in some example: it wraps, with no limit on its lines.
+    Multiline(TextAlignment),
+    /// A line tall in every example: it stays on one line, truncated at the end.
+    SingleLine,
+}
+
+impl Wrapping {
+    /// The SwiftUI modifiers, without their leading dot, e.g. `lineLimit(1)`.
+    pub fn modifiers(&self) -> Vec<String> {
+        match self {
+            Wrapping::Multiline(alignment) => {
+                let alignment = match alignment {
+                    TextAlignment::Leading => "leading",
+                    TextAlignment::Center => "center",
+                    TextAlignment::Trailing => "trailing",
+                };
+                vec!["lineLimit(nil)".to_string(), format!("multilineTextAlignment(.{})", alignment)]
+            }
+            Wrapping::SingleLine => vec!["lineLimit(1)".to_string(), "truncationMode(.tail)".to_string()],
+        }
+    }
+}
+
+// Where a frame sits across the screen: centered within a tenth of the screen's width, else
+// on the side it is nearer; centered if its `x` or width is not stated
+fn alignment(frame: &layout::StatedFrame, screen_width: Option<f64>) -> TextAlignment {
+    let (Some(x), Some(width), Some(screen)) = (frame.x, frame.width, screen_width) else { return TextAlignment::Center };
+    let offset = x + width / 2.0 - screen / 2.0;
+    match offset {
+        offset if offset.abs() <= screen / 10.0 => TextAlignment::Center,
+        offset if offset < 0.0 => TextAlignment::Leading,
+        _ => TextAlignment::Trailing,
+    }
+}
+
+/// The wrapping of each text of `examples` that states a height, by its content: lines are as
+/// tall as its style in `styles`, or `default_style`, at each example's Dynamic Type size. A
+/// text more lines tall than it has line breaks in any example wraps; one a line tall in every
+/// example stays on one line.
+pub fn wrapping(examples: &[Example], styles: &BTreeMap<String, String>, default_style: &str) -> BTreeMap<String, Wrapping> {
+    let mut wrapping: BTreeMap<String, Wrapping> = BTreeMap::new();
+    for example in examples {
+        let Value::Dict(elements) = &example.1 else { continue };
+        let scale = layout::dynamic_type(example).scale();
+        let screen_width = layout::screen_size(example).map(|size| size.width);
+        for (index, frame) in layout::stated_frames(example) {
+            let Some((_, Value::String(text))) = elements.get(index).filter(|(key, _)| key == "title") else { continue };
+            let Some(height) = frame.height else { continue };
+            let style = styles.get(text).map_or(default_style, String::as_str);
+            let lines = (height / (line_height(style) * scale)).round().max(1.0) as usize;
+            let found = match lines > text.lines().count().max(1) {
+                true => Wrapping::Multiline(alignment(&frame, screen_width)),
+                false if lines == 1 => Wrapping::SingleLine,
+                false => continue,
+            };
+            // Wrapping in one example outweighs a single line in another
+            match wrapping.get(text) {
+                Some(Wrapping::Multiline(_)) => {}
+                _ => {
+                    wrapping.insert(text.clone(), found);
+                }
+            }
+        }
+    }
+    wrapping
+}
+
+/// The text style of each text of `examples` that states a size, by its content. Sizes are
+/// averaged over the examples, at their Dynamic Type size. A 17pt text is a `headline` when it
+/// is the largest text on the screen, and `body` text otherwise. Texts that state no size are
+/// left out, to be rendered in the default style.
+pub fn classify(examples: &[Example]) -> BTreeMap<String, String> {
+    let mut sizes: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+    for example in examples {
+        let Value::Dict(elements) = &example.1 else { continue };
+        let scale = layout::dynamic_type(example).scale();
+        for (index, frame) in layout::stated_frames(example) {
+            if let Some((_, Value::String(text))) = elements.get(index).filter(|(key, _)| key == "title") {
+                if let Some(size) = stated_size(text, &frame, scale) {
+                    sizes.entry(text.clone()).or_default().push(size);
+                }
+            }
+        }
+    }
+    let sizes: BTreeMap<String, f64> = sizes.into_iter().map(|(text, sizes)| (text, sizes.iter().sum::<f64>() / sizes.len() as f64)).collect();
+    sizes
+        .iter()
+        .map(|(text, &size)| {
+            let heading = sizes.iter().all(|(other, &other_size)| other == text || other_size < size - 0.5);
+            let style = match style_of_size(size) {
+                "headline" if !heading || sizes.len() == 1 => "body",
+                style => style,
+            };
+            (text.clone(), style.to_string())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    #[test]
+    fn test_sizes_pick_the_nearest_style() {
+        assert_eq!(style_of_size(28.0), "title");
+        assert_eq!(style_of_size(12.4), "caption");
+        assert_eq!(style_of_size(17.0), "headline");
+        assert_eq!(style_of_size(60.0), "largeTitle");
+    }
+
+    #[test]
+    fn test_texts_are_classified_across_examples() {
+        let examples = parse_examples(
+            "{(width:390,height:844):{title:\"Welcome\"@(h:34),title:\"Sign in to continue\"@(font:17),title:\"Terms apply\"@(h:16),title:\"Hi\"}}
+             {(width:320,height:568,dynamicType:accessibility1):{title:\"Sign in to continue\"@(font:28)}}",
+        )
+        .unwrap();
+        let styles = classify(&examples);
+        assert_eq!(styles["Welcome"], "title");
+        // 28pt at accessibility1 is 17pt at the default size, and a larger text heads the screen
+        assert_eq!(styles["Sign in to continue"], "body");
+        assert_eq!(styles["Terms apply"], "caption");
+        assert!(!styles.contains_key("Hi"));
+
+        // The largest text of a screen at 17pt is its headline; two lines of 20pt are subheadlines
+        let examples = parse_examples("{(width:390,height:844):{title:\"Settings\"@(font:17),title:\"Two\\nlines\"@(h:40)}}").unwrap();
+        let styles = classify(&examples);
+        assert_eq!((styles["Settings"].as_str(), styles["Two\nlines"].as_str()), ("headline", "subheadline"));
+    }
+
+    #[test]
+    fn test_wrapping_from_stated_heights() {
+        let examples = parse_examples(
+            "{(width:390,height:844):{title:\"Welcome back\"@(h:34),title:\"A long story\"@(x:20,w:350,h:34),title:\"Fine print\"@(x:20,w:200,h:48,font:12),title:\"Hi\"}}
+             {(width:320,height:568):{title:\"Welcome back\"@(h:34),title:\"A long story\"@(x:20,w:280,h:68)}}",
+        )
+        .unwrap();
+        let styles = classify(&examples);
+        let wrapping = wrapping(&examples, &styles, "title");
+        assert_eq!(wrapping["Welcome back"], Wrapping::SingleLine);
+        // Two title lines on the small screen; three caption lines left of center
+        assert_eq!(wrapping["A long story"], Wrapping::Multiline(TextAlignment::Center));
+        assert_eq!(wrapping["Fine print"], Wrapping::Multiline(TextAlignment::Leading));
+        assert!(!wrapping.contains_key("Hi"));
+        assert_eq!(wrapping["Fine print"].modifiers(), ["lineLimit(nil)", "multilineTextAlignment(.leading)"]);
+        assert_eq!(Wrapping::SingleLine.modifiers(), ["lineLimit(1)", "truncationMode(.tail)"]);
+        // Wrapped heights tell no size
+        assert_eq!(styles["A long story"], "title");
+    }
+}
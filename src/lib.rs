@@ -1,5 +1,11 @@
 pub mod ast;
+pub mod components;
+pub mod config;
+pub mod diagnostics;
 pub mod input;
+pub mod layout;
+pub mod lsp;
+pub mod platform;
 pub mod synthesis;
 pub mod output;
 pub mod utils;
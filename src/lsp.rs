@@ -0,0 +1,438 @@
+// File: src/lsp.rs
+// Editor server (`--lsp`): JSON-RPC 2.0 over stdio, framed as in the Language Server
+// Protocol. Open spec documents get their parse diagnostics published as they change, and
+// `swiftui-synth/*` requests parse, synthesize and render without a process per keystroke.
+// Syntheses run on the shared worker pool, so a long search does not hold up other requests.
+use std::io::{self, BufRead, Write};
+use std::sync::mpsc::{self, Sender};
+use std::sync::Arc;
+use std::thread;
+
+use serde_json::{json, Value};
+
+use crate::ast::ir::{background, modifier};
+use crate::ast::IR;
+use crate::components::ComponentLibrary;
+use crate::config::Config;
+use crate::diagnostics::{Diagnostic, Severity, Span};
+use crate::input::parser::{parse_spec_with, ParseOptions, Spec};
+use crate::layout::{self, Insets, Metrics, Size};
+use crate::output::{preview, render};
+use crate::synthesis::verify::verify;
+use crate::synthesis::{flow, lint, pool, strategy};
+
+// JSON-RPC error codes
+const PARSE_ERROR: i64 = -32700;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const REQUEST_FAILED: i64 = -32803;
+
+/// Reads one message: `Content-Length` and other headers, a blank line, then the JSON body.
+/// `Ok(None)` at the end of the input. A body that is not JSON is returned as a string.
+pub fn read_message(input: &mut impl BufRead) -> io::Result<Option<Value>> {
+    let mut length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "message without a Content-Length header"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&body).into_owned()))))
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+// Line and UTF-16 column of the byte `offset` in `text`, as LSP positions count them
+fn position(text: &str, offset: usize) -> Value {
+    let before = &text[..offset.min(text.len())];
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    json!({ "line": before.matches('\n').count(), "character": before[line_start..].encode_utf16().count() })
+}
+
+/// `diagnostic` as an LSP diagnostic in `text`. Its suggestions follow the message.
+pub fn lsp_diagnostic(diagnostic: &Diagnostic, text: &str) -> Value {
+    let span = diagnostic.span.unwrap_or(Span::new(0, 0));
+    let mut message = diagnostic.message.clone();
+    for suggestion in &diagnostic.suggestions {
+        message.push_str(&format!("\nhelp: {}", suggestion));
+    }
+    json!({
+        "range": { "start": position(text, span.start), "end": position(text, span.end) },
+        "severity": if diagnostic.severity == Severity::Error { 1 } else { 2 },
+        "code": diagnostic.code,
+        "source": "swiftui-synth",
+        "message": message,
+    })
+}
+
+// What requests are answered with, shared with the syntheses running on the pool
+struct Settings {
+    config: Config,
+    options: ParseOptions,
+    metrics: Metrics,
+}
+
+/// State of the server between messages.
+pub struct Server {
+    settings: Arc<Settings>,
+    /// Open documents by URI, with their latest text.
+    documents: Vec<(String, String)>,
+    shut_down: bool,
+    /// Set by the `exit` notification: whether `shutdown` came first.
+    pub exit: Option<bool>,
+}
+
+impl Server {
+    pub fn new(config: Config, components: ComponentLibrary) -> Server {
+        let mut metrics = config.metrics();
+        components.apply_metrics(&mut metrics);
+        let options = ParseOptions { components, strict: false, platform: config.platform() };
+        Server { settings: Arc::new(Settings { config, options, metrics }), documents: Vec::new(), shut_down: false, exit: None }
+    }
+
+    /// Handles one message as [`Server::handle`] does, sending the messages back through
+    /// `replies`, except that a `swiftui-synth/synthesize` request is answered from a worker
+    /// of the shared [`pool`] once its synthesis is done, while later messages are handled.
+    pub fn handle_concurrently(&mut self, message: &Value, replies: &Sender<Value>) {
+        let synthesize = message.get("method").and_then(Value::as_str) == Some("swiftui-synth/synthesize");
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        if let (true, Some(id), Ok(text)) = (synthesize, message.get("id"), self.text(&params)) {
+            let (settings, id, replies) = (Arc::clone(&self.settings), id.clone(), replies.clone());
+            // The handle is dropped: the job still runs, and answers through its sender
+            drop(pool::shared().spawn(move || {
+                let _ = replies.send(response(&id, settings.synthesize(&text)));
+            }));
+            return;
+        }
+        for reply in self.handle(message) {
+            let _ = replies.send(reply);
+        }
+    }
+
+    /// Handles one message, returning the messages to send back: the response to a request,
+    /// and any notifications it causes.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let Some(method) = message.get("method").and_then(Value::as_str) else {
+            return vec![json!({ "jsonrpc": "2.0", "id": null, "error": { "code": PARSE_ERROR, "message": "Expected a JSON-RPC request or notification" } })];
+        };
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        let Some(id) = message.get("id") else {
+            return self.notification(method, &params);
+        };
+        vec![response(id, self.request(method, &params))]
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params.pointer("/textDocument/uri").and_then(Value::as_str).unwrap_or_default().to_string();
+        let text = match method {
+            "textDocument/didOpen" => params.pointer("/textDocument/text").and_then(Value::as_str),
+            // Documents are synced in full, so the last change is the whole text
+            "textDocument/didChange" => params.get("contentChanges").and_then(Value::as_array).and_then(|changes| changes.last()?.get("text")?.as_str()),
+            "textDocument/didClose" => {
+                self.documents.retain(|(open, _)| *open != uri);
+                return vec![publish(&uri, Vec::new())];
+            }
+            "exit" => {
+                self.exit = Some(self.shut_down);
+                return Vec::new();
+            }
+            _ => return Vec::new(),
+        };
+        let Some(text) = text else { return Vec::new() };
+        let diagnostics = self.diagnostics(text).iter().map(|diagnostic| lsp_diagnostic(diagnostic, text)).collect();
+        match self.documents.iter_mut().find(|(open, _)| *open == uri) {
+            Some((_, document)) => *document = text.to_string(),
+            None => self.documents.push((uri.clone(), text.to_string())),
+        }
+        vec![publish(&uri, diagnostics)]
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        match method {
+            "initialize" => Ok(json!({
+                "capabilities": { "textDocumentSync": 1 },
+                "serverInfo": { "name": "swiftui-synth", "version": env!("CARGO_PKG_VERSION") },
+            })),
+            "shutdown" => {
+                self.shut_down = true;
+                Ok(Value::Null)
+            }
+            "swiftui-synth/diagnostics" => {
+                let text = self.text(params)?;
+                let diagnostics: Vec<Value> = self.diagnostics(&text).iter().map(|diagnostic| lsp_diagnostic(diagnostic, &text)).collect();
+                Ok(json!({ "diagnostics": diagnostics }))
+            }
+            "swiftui-synth/synthesize" => {
+                let text = self.text(params)?;
+                self.settings.synthesize(&text)
+            }
+            "swiftui-synth/render" => {
+                let layout = params.get("layout").cloned().ok_or((INVALID_PARAMS, "Expected a layout to render".to_string()))?;
+                let ir: IR = serde_json::from_value(layout).map_err(|e| (INVALID_PARAMS, format!("Invalid layout: {}", e)))?;
+                let options = self.settings.config.render_options();
+                let code = render::render_swiftui_with(&ir, &options);
+                let code = match params.get("screen").and_then(Value::as_str) {
+                    Some(screen) => render::render_view(&render::view_name(Some(screen)), &code, &options),
+                    None => code,
+                };
+                Ok(json!({ "code": code }))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method '{}'", method))),
+        }
+    }
+
+    // The spec a request is about: `text`, or the open document at `uri`
+    fn text(&self, params: &Value) -> Result<String, (i64, String)> {
+        if let Some(text) = params.get("text").and_then(Value::as_str) {
+            return Ok(text.to_string());
+        }
+        let uri = params.get("uri").and_then(Value::as_str).ok_or((INVALID_PARAMS, "Expected the spec as text or the uri of an open document".to_string()))?;
+        let document = self.documents.iter().find(|(open, _)| open == uri);
+        document.map(|(_, text)| text.clone()).ok_or((INVALID_PARAMS, format!("'{}' is not open", uri)))
+    }
+
+    // Errors if the spec does not parse, else its warnings
+    fn diagnostics(&self, text: &str) -> Vec<Diagnostic> {
+        match parse_spec_with(text, &self.settings.options) {
+            Ok(spec) => spec.warnings,
+            Err(errors) => errors,
+        }
+    }
+}
+
+impl Settings {
+    // The code and a text preview of each screen, or the diagnostics that stop the spec parsing
+    fn synthesize(&self, text: &str) -> Result<Value, (i64, String)> {
+        let Spec { screens, flows, warnings } = match parse_spec_with(text, &self.options) {
+            Ok(spec) => spec,
+            Err(errors) => {
+                let diagnostics: Vec<Value> = errors.iter().map(|diagnostic| lsp_diagnostic(diagnostic, text)).collect();
+                return Ok(json!({ "screens": [], "diagnostics": diagnostics }));
+            }
+        };
+        let strategy = self.config.synthesis.strategy.as_deref().unwrap_or(strategy::STRATEGIES[0]);
+        let strategy = strategy::by_name(strategy, self.metrics.clone(), self.config.cost.clone()).map_err(|e| (REQUEST_FAILED, e))?;
+        let budget = self.config.budget();
+        let mut results = Vec::new();
+        let mut warnings = warnings;
+        for screen in &screens {
+            let name = screen.name.as_deref();
+            let examples = &screen.examples;
+            let Some(outcome) = strategy.synthesize(examples, &budget) else {
+                results.push(json!({ "screen": name, "error": "No matching layout found for the given examples" }));
+                continue;
+            };
+            warnings.extend(lint::lint(&outcome.ir, examples, &self.metrics, &self.config.lint_options()).into_iter().map(|mut lint| {
+                if let Some(name) = name {
+                    lint.message = format!("{}: {}", render::view_name(Some(name)), lint.message);
+                }
+                lint
+            }));
+            // As the CLI renders it: links for flows, the team's components, safe area and text fit
+            let ir = self.options.components.substitute(&flow::wire(&outcome.ir, name, &flows));
+            let defaults = self.config.render_options();
+            let text_styles = layout::text_style::classify(examples);
+            let options = render::RenderOptions {
+                ignores_safe_area: layout::ignores_safe_area(examples),
+                text_fit: layout::text_fit(&ir, examples, &self.metrics),
+                text_wrapping: layout::text_style::wrapping(examples, &text_styles, &defaults.text_font),
+                text_styles,
+                image_sizing: layout::image_sizing::sizing(examples),
+                view_modifiers: modifier::stated(examples),
+                background: background::stated(examples),
+                ..defaults
+            };
+            let mut code = render::render_swiftui_with(&ir, &options);
+            if name.is_some() {
+                code = render::render_view(&render::view_name(name), &code, &options);
+            }
+            let size = examples.first().and_then(layout::screen_size).unwrap_or(Size::new(390.0, 844.0));
+            let safe_area = match examples.first() {
+                Some(example) if !options.ignores_safe_area => layout::safe_area(example),
+                _ => Insets::default(),
+            };
+            results.push(json!({
+                "screen": name,
+                "code": code,
+                "layout": ir,
+                "preview": preview::preview(&ir, size, safe_area, &self.metrics, 32),
+                "violations": outcome.violations,
+                "unresolved": verify(&outcome.ir, examples, &self.metrics).iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "exhausted": outcome.exhausted.map(|reason| reason.to_string()),
+            }));
+        }
+        let routes = (!flows.is_empty()).then(|| render::render_routes(&flow::destinations(&flows), &self.config.render_options()));
+        let diagnostics: Vec<Value> = warnings.iter().map(|diagnostic| lsp_diagnostic(diagnostic, text)).collect();
+        Ok(json!({ "screens": results, "routes": routes, "diagnostics": diagnostics }))
+    }
+}
+
+fn response(id: &Value, result: Result<Value, (i64, String)>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+    }
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({ "jsonrpc": "2.0", "method": "textDocument/publishDiagnostics", "params": { "uri": uri, "diagnostics": diagnostics } })
+}
+
+// Reads and handles messages until `exit` or the end of the input; whether `shutdown` came first
+fn dispatch(server: &mut Server, input: &mut impl BufRead, replies: &Sender<Value>) -> io::Result<bool> {
+    while let Some(message) = read_message(input)? {
+        server.handle_concurrently(&message, replies);
+        if let Some(shut_down) = server.exit {
+            return Ok(shut_down);
+        }
+    }
+    Ok(false)
+}
+
+/// Serves messages from `input` until the `exit` notification or the end of the input.
+/// Returns whether the client shut the server down first, as the protocol asks. Messages are
+/// handled with [`Server::handle_concurrently`], so the responses to syntheses may come out
+/// of order; the syntheses still running when the input ends are answered before returning.
+pub fn serve(server: &mut Server, input: &mut impl BufRead, output: &mut (impl Write + Send)) -> io::Result<bool> {
+    let (replies, outbox) = mpsc::channel::<Value>();
+    thread::scope(|scope| {
+        let writer = scope.spawn(move || outbox.iter().try_for_each(|reply| write_message(output, &reply)));
+        let read = dispatch(server, input, &replies);
+        // The writer stops once this sender and those of the syntheses still running are gone
+        drop(replies);
+        let written = writer.join().expect("the writer does not panic");
+        let shut_down = read?;
+        written.map(|_| shut_down)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn server() -> Server {
+        Server::new(Config::default(), ComponentLibrary::default())
+    }
+
+    fn request(server: &mut Server, method: &str, params: Value) -> Value {
+        let mut replies = server.handle(&json!({ "jsonrpc": "2.0", "id": 7, "method": method, "params": params }));
+        assert_eq!(replies.len(), 1);
+        replies.remove(0)
+    }
+
+    #[test]
+    fn test_framing() {
+        let mut framed = Vec::new();
+        write_message(&mut framed, &json!({ "id": 1, "text": "é" })).unwrap();
+        assert!(framed.starts_with(b"Content-Length: 20\r\n\r\n{"));
+        let mut input = io::Cursor::new([framed.clone(), framed].concat());
+        assert_eq!(read_message(&mut input).unwrap(), Some(json!({ "id": 1, "text": "é" })));
+        assert!(read_message(&mut input).unwrap().is_some());
+        assert_eq!(read_message(&mut input).unwrap(), None);
+        assert!(read_message(&mut io::Cursor::new("Content-Type: json\r\n\r\n{}")).is_err());
+    }
+
+    #[test]
+    fn test_diagnostics_are_published_as_documents_change() {
+        let mut server = server();
+        let open = json!({ "jsonrpc": "2.0", "method": "textDocument/didOpen", "params": { "textDocument": { "uri": "file:///a.spec", "text": "{(width:390,height:844):{title:\"Hi\"}}" } } });
+        let published = server.handle(&open);
+        assert_eq!(published[0]["method"], "textDocument/publishDiagnostics");
+        assert_eq!(published[0]["params"]["diagnostics"], json!([]));
+
+        // Columns count UTF-16 code units: the emoji before the typo counts twice
+        let text = "{(width:390,height:844):{title:\"👋\",titel:\"Hi\"}}";
+        let change = json!({ "jsonrpc": "2.0", "method": "textDocument/didChange", "params": { "textDocument": { "uri": "file:///a.spec" }, "contentChanges": [{ "text": text }] } });
+        let diagnostic = &server.handle(&change)[0]["params"]["diagnostics"][0];
+        assert_eq!(diagnostic["code"], "E0012");
+        assert_eq!(diagnostic["severity"], 1);
+        assert_eq!(diagnostic["range"]["start"], json!({ "line": 0, "character": 36 }));
+        assert!(diagnostic["message"].as_str().unwrap().ends_with("help: did you mean `title`?"));
+
+        let response = request(&mut server, "swiftui-synth/diagnostics", json!({ "uri": "file:///a.spec" }));
+        assert_eq!(response["result"]["diagnostics"][0]["code"], "E0012");
+        let close = json!({ "jsonrpc": "2.0", "method": "textDocument/didClose", "params": { "textDocument": { "uri": "file:///a.spec" } } });
+        assert_eq!(server.handle(&close)[0]["params"]["diagnostics"], json!([]));
+        assert_eq!(request(&mut server, "swiftui-synth/diagnostics", json!({ "uri": "file:///a.spec" }))["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_synthesize_and_render() {
+        let mut server = server();
+        let spec = "Login:{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+        let result = &request(&mut server, "swiftui-synth/synthesize", json!({ "text": spec }))["result"];
+        let screen = &result["screens"][0];
+        assert_eq!(screen["screen"], "Login");
+        assert!(screen["code"].as_str().unwrap().starts_with("struct LoginView: View {"));
+        assert_eq!(screen["violations"], 0);
+        assert_eq!(screen["unresolved"], json!([]));
+        assert_eq!(result["diagnostics"], json!([]));
+        assert!(screen["preview"].as_str().unwrap().contains("Hi"));
+
+        let rendered = request(&mut server, "swiftui-synth/render", json!({ "layout": screen["layout"] }));
+        assert!(rendered["result"]["code"].as_str().unwrap().starts_with("VStack {\n    Text(\"Hi\")"));
+        let broken = request(&mut server, "swiftui-synth/synthesize", json!({ "text": "{(width:390):{" }));
+        assert_eq!(broken["result"]["screens"], json!([]));
+        assert!(!broken["result"]["diagnostics"].as_array().unwrap().is_empty());
+        assert_eq!(request(&mut server, "swiftui-synth/render", json!({ "layout": { "Grid": [] } }))["error"]["code"], INVALID_PARAMS);
+
+        // Lints of the layout come with the diagnostics, named by screen
+        let fixed = request(&mut server, "swiftui-synth/synthesize", json!({ "text": "Login:{(width:390,height:844):{title:\"Two\\nlines\"@(h:68)}}" }));
+        let lint = &fixed["result"]["diagnostics"][0];
+        assert_eq!((&lint["code"], lint["severity"].as_i64()), (&json!("W0004"), Some(2)));
+        assert!(lint["message"].as_str().unwrap().starts_with("LoginView: Text \"Two\nlines\" has a fixed height"));
+    }
+
+    #[test]
+    fn test_syntheses_are_answered_from_the_pool() {
+        let mut server = server();
+        let mut input = io::Cursor::new(Vec::new());
+        let spec = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+        for (id, method) in [(1, "swiftui-synth/synthesize"), (2, "swiftui-synth/diagnostics"), (3, "swiftui-synth/synthesize")] {
+            write_message(input.get_mut(), &json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": { "text": spec } })).unwrap();
+        }
+        let mut output = Vec::new();
+        assert!(!serve(&mut server, &mut input, &mut output).unwrap());
+        // Every request is answered before `serve` returns, in whatever order they finish
+        let mut output = io::Cursor::new(output);
+        let mut replies = Vec::new();
+        while let Some(reply) = read_message(&mut output).unwrap() {
+            replies.push(reply);
+        }
+        replies.sort_by_key(|reply| reply["id"].as_i64());
+        assert_eq!(replies.iter().map(|reply| reply["id"].as_i64().unwrap()).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(replies[0]["result"], replies[2]["result"]);
+        assert_eq!(replies[0]["result"]["screens"][0]["violations"], 0);
+        assert_eq!(replies[1]["result"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut server = server();
+        assert_eq!(request(&mut server, "initialize", json!({}))["result"]["capabilities"]["textDocumentSync"], 1);
+        assert_eq!(request(&mut server, "textDocument/hover", json!({}))["error"]["code"], METHOD_NOT_FOUND);
+        let mut input = io::Cursor::new(Vec::new());
+        for message in [json!({ "jsonrpc": "2.0", "id": 1, "method": "shutdown" }), json!({ "jsonrpc": "2.0", "method": "exit" })] {
+            write_message(input.get_mut(), &message).unwrap();
+        }
+        let mut output = Vec::new();
+        assert!(serve(&mut server, &mut input, &mut output).unwrap());
+        assert!(String::from_utf8(output).unwrap().ends_with("{\"id\":1,\"jsonrpc\":\"2.0\",\"result\":null}"));
+    }
+}
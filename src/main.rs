@@ -1,65 +1,1999 @@
-mod ast;
-mod input;
-mod synthesis;
-mod output;
-mod utils;
-
-use clap::Parser;
-use std::fs::{self, File};
-use std::io::Write;
-use std::time::Instant;
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use swiftui_synth::ast::ir::{background, modifier};
+use swiftui_synth::ast::{diff, Example, IR};
+use swiftui_synth::components::{ComponentLibrary, COMPONENTS_FILE_NAME};
+use swiftui_synth::config::Config;
+use swiftui_synth::diagnostics::{Diagnostic, Severity};
+use swiftui_synth::input::parser::{ParseOptions, Spec};
+use swiftui_synth::input::{annotations, lexer, minimize, preprocess, sketch, storyboard, xd};
+use swiftui_synth::layout::{self, calibrate, spacing, Insets, Metrics, Size};
+use swiftui_synth::lsp;
+use swiftui_synth::platform::Platform;
+use swiftui_synth::output::picker::{self, Candidate};
+use swiftui_synth::output::{png, preview};
+use swiftui_synth::output::backend::Registry;
+use swiftui_synth::output::template::{TemplateBackend, Templates};
+use swiftui_synth::output::assets;
+use swiftui_synth::output::build::{self, Manifest};
+use swiftui_synth::output::clipboard;
+use swiftui_synth::output::examples;
+use swiftui_synth::output::format;
+use swiftui_synth::output::merge::{self, Conflict, Syntax};
+use swiftui_synth::output::package::Package;
+use swiftui_synth::output::provenance::{self, Provenance};
+use swiftui_synth::output::snapshot;
+use swiftui_synth::output::render::{self, AppStyle, RenderOptions};
+use swiftui_synth::output::swiftc::{SourceFile, Toolchain};
+use swiftui_synth::output::tree::{self, TreeFormat};
+use swiftui_synth::output::ui_tests;
+use swiftui_synth::output::xcode;
+use swiftui_synth::synthesis::cache::{self, Cache};
+use swiftui_synth::synthesis::search::{self, Budget, CancelToken, Exhausted, Outcome, SearchStats};
+use swiftui_synth::synthesis::a11y::Rgb;
+use swiftui_synth::synthesis::bench;
+use swiftui_synth::synthesis::conflict;
+use swiftui_synth::synthesis::flow;
+use swiftui_synth::synthesis::incremental::Previous;
+#[cfg(feature = "llm")]
+use swiftui_synth::synthesis::llm;
+use swiftui_synth::synthesis::ranker::{self, Ranker};
+use swiftui_synth::synthesis::refine::{self, Constraint};
+use swiftui_synth::synthesis::strategy;
+use swiftui_synth::utils::logging::{self, LogFormat};
+use swiftui_synth::utils::profiler::{self, Phase, Profiler};
+use swiftui_synth::{input, synthesis};
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tracing::{debug, error, info, info_span, warn};
 
 #[derive(Parser, Debug)]
-#[command(name = "swiftui-synth", about = "Synthesizes SwiftUI layouts from examples")]
+#[command(name = "swiftui-synth", about = "Synthesizes SwiftUI layouts from examples", args_conflicts_with_subcommands = true)]
 struct Cli {
-    /// Examples in the format {(width:390,height:844):{title:"Hello",button:"Click"}}
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    synth: SynthArgs,
+
+    /// Serve spec diagnostics, synthesis and rendering to an editor as JSON-RPC over stdio
+    #[arg(long, conflicts_with = "input")]
+    lsp: bool,
+
+    /// Also show debug events on stderr (`-vv`: trace events too)
+    #[arg(long, short = 'v', action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// How events are written on stderr: `text`, or `json` lines for log collectors
+    #[arg(long, value_name = "FORMAT", default_value = "text", global = true)]
+    log_format: LogFormat,
+}
+
+#[derive(Args, Clone, Debug)]
+struct SynthArgs {
+    /// Examples in the format {(width:390,height:844):{title:"Hello",button:"Click"}}; `-` reads stdin
     #[arg(long, group = "input")]
     examples: Option<String>,
 
-    /// File containing the examples
+    /// File containing the examples; `-` reads stdin
     #[arg(long, group = "input")]
     examples_file: Option<String>,
 
-    /// Optional output file to save the synthesized SwiftUI code
-    #[arg(long)]
+    /// Screenshot (PNG or JPEG) to synthesize the layout of, from the boxes labeled in --annotations or, built with the ocr feature, its text
+    #[arg(long, value_name = "IMAGE", group = "input")]
+    screenshot: Option<PathBuf>,
+
+    /// Labeled bounding boxes of the --screenshot, as JSON (a list of boxes, or COCO) or CSV
+    #[arg(long, value_name = "FILE", requires = "screenshot")]
+    annotations: Option<PathBuf>,
+
+    /// Pixels per point of the --screenshot (default: that of the device of its size, or 1)
+    #[arg(long, value_name = "N", requires = "screenshot")]
+    scale: Option<f64>,
+
+    /// Optional output file to save the synthesized SwiftUI code; `-` writes only the code to stdout
+    #[arg(long, short = 'o', conflicts_with = "out_dir")]
     output: Option<String>,
+
+    /// Write each screen to its own file in this directory, e.g. `LoginView.swift`
+    #[arg(long)]
+    out_dir: Option<PathBuf>,
+
+    /// Write a Swift package at DIR, named after it, with the screens in its library target and a test target
+    #[arg(long, value_name = "DIR", conflicts_with_all = ["output", "out_dir", "app"])]
+    emit_package: Option<PathBuf>,
+
+    /// Also write an App.swift entry point showing the screens in a NavigationStack (`stack`) or TabView (`tabs`)
+    #[arg(long, requires = "out_dir")]
+    app: Option<AppStyle>,
+
+    /// Print each screen's layout as a Graphviz (`dot`) or Mermaid (`mermaid`) diagram instead of its code
+    #[arg(long, value_name = "FORMAT", conflicts_with_all = ["json", "refine"])]
+    emit_tree: Option<TreeFormat>,
+
+    /// Write an XCTest file of swift-snapshot-testing tests, one per screen and example size, to FILE
+    #[arg(long, value_name = "FILE")]
+    emit_tests: Option<PathBuf>,
+
+    /// Module the tests import the views from with `@testable import` (default: the --emit-package name)
+    #[arg(long, value_name = "NAME", requires = "emit_tests")]
+    test_module: Option<String>,
+
+    /// Give texts, buttons and images an .accessibilityIdentifier derived from their content, e.g. `button.sign-in`
+    #[arg(long)]
+    accessibility_ids: bool,
+
+    /// Give buttons `.sensoryFeedback` of KIND (default: impact) when tapped, triggered by a state
+    /// variable each toggles; also the config's [modifiers] haptics
+    #[arg(long, value_name = "KIND", num_args = 0..=1, default_missing_value = "impact", value_parser = clap::builder::PossibleValuesParser::new(render::SENSORY_FEEDBACKS.iter().copied()))]
+    haptics: Option<String>,
+
+    /// Write an XCUITest skeleton to FILE that finds each screen's views by identifier and taps its buttons (implies --accessibility-ids)
+    #[arg(long, value_name = "FILE")]
+    emit_ui_tests: Option<PathBuf>,
+
+    /// Render the SwiftUI code from the Tera templates in DIR, one per kind of view, e.g. `button.tera`; kinds without one use the built-in template
+    #[arg(long, value_name = "DIR")]
+    template_dir: Option<PathBuf>,
+
+    /// Namespace of the page classes with --target xaml (default: App)
+    #[arg(long, value_name = "NAME")]
+    namespace: Option<String>,
+
+    /// Snap the padding and stack spacing to the spacing scale, and spell them out in SwiftUI code
+    #[arg(long)]
+    snap_spacing: bool,
+
+    /// Draw each screen's simulated layout to FILE as a picture; FORMAT is `png`
+    #[arg(long, num_args = 2, value_names = ["FORMAT", "FILE"])]
+    preview: Option<Vec<String>>,
+
+    /// Which example's screen size the --preview picture is drawn at, counting from 1
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "preview", value_parser = clap::value_parser!(u64).range(1..))]
+    preview_example: u64,
+
+    /// Add an image set for each image the views show, and a color set for each color token, to
+    /// the asset catalog at DIR (e.g. `Assets.xcassets`); sets that exist are kept
+    #[arg(long, value_name = "DIR")]
+    assets: Option<PathBuf>,
+
+    /// Also copy the generated code to the system clipboard
+    #[arg(long)]
+    copy: bool,
+
+    /// Print only the generated code (no banner, timing or save messages)
+    #[arg(long, short = 'q')]
+    quiet: bool,
+
+    /// Platform the views are for: ios (default), macos, tvos or watchos (overrides the config file)
+    #[arg(long)]
+    platform: Option<Platform>,
+
+    /// Output target (overrides the config file)
+    #[arg(long)]
+    target: Option<String>,
+
+    /// Config file to use instead of the nearest swiftui-synth.toml
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Component library to draw views from instead of the nearest components.toml
+    #[arg(long)]
+    components: Option<PathBuf>,
+
+    /// Print the result and any diagnostics as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Stop searching after this long and print the best layout so far, e.g. `5s` or `500ms`
+    #[arg(long, value_parser = search::parse_duration)]
+    timeout: Option<Duration>,
+
+    /// Maximum stack nesting depth to search
+    #[arg(long)]
+    max_depth: Option<usize>,
+
+    /// Maximum number of candidate layouts to try
+    #[arg(long)]
+    max_candidates: Option<usize>,
+
+    /// Memory the search's record of the candidates it tried may take, e.g. `512MB`
+    #[arg(long, value_name = "SIZE", value_parser = search::parse_size)]
+    max_memory: Option<usize>,
+
+    /// Explore candidate layouts in an order shuffled by this seed; the same seed gives the same output
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Find up to N layouts satisfying the examples and pick one interactively (in a terminal)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    top_n: Option<u64>,
+
+    /// Synthesis algorithm: enumerative (default), template, constraint, beam, genetic or assignment
+    #[arg(long)]
+    strategy: Option<String>,
+
+    /// Partial layouts the beam strategy keeps at each step (default 32)
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    beam_width: Option<u64>,
+
+    /// Order the --top-n layouts by a model written by `train-ranker`
+    #[arg(long)]
+    ranker: Option<PathBuf>,
+
+    /// Ask a language model (openai or ollama) to order the --top-n layouts
+    #[cfg(feature = "llm")]
+    #[arg(long, value_parser = ["openai", "ollama"])]
+    llm: Option<String>,
+
+    /// Append the layouts offered by the --top-n picker, and which was picked, to this log
+    #[arg(long)]
+    feedback_log: Option<PathBuf>,
+
+    /// Report candidate counts, cache hits, per-phase timings and peak memory
+    #[arg(long)]
+    stats: bool,
+
+    /// Explain why each structural decision in the layout was made
+    #[arg(long)]
+    explain: bool,
+
+    /// Always search, ignoring and not updating the result cache
+    #[arg(long)]
+    no_cache: bool,
+
+    /// File of feedback on earlier results, one per line, e.g. `button should be at the top`
+    #[arg(long)]
+    reject: Option<PathBuf>,
+
+    /// After showing the layout, ask what is wrong with it and synthesize again
+    #[arg(long)]
+    refine: bool,
+
+    /// Reject unknown element keys and unquoted values instead of warning about them
+    #[arg(long)]
+    strict: bool,
+
+    /// Treat warnings about the examples, such as input that is ignored, as errors
+    #[arg(long)]
+    deny_warnings: bool,
+
+    /// Check the generated code with a compiler before saving it; `swift` runs swiftc when it is installed
+    #[arg(long, value_parser = ["swift"])]
+    validate: Option<String>,
+
+    /// Pipe the generated code through COMMAND (stdin to stdout), else the config's [format] command,
+    /// else the built-in pretty-printer (also `builtin`), which wraps lines longer than format.line_width
+    #[arg(long, value_name = "COMMAND", num_args = 0..=1, default_missing_value = "")]
+    post_format: Option<String>,
+
+    /// Replace the generated section of an existing file even if it was edited by hand, and
+    /// overwrite files without swiftui-synth markers
+    #[arg(long)]
+    force: bool,
+
+    /// Whether screen files end with an Xcode `#Preview` of the view (set by `serve --xcode`)
+    #[arg(skip)]
+    previews: bool,
 }
 
-fn main() -> Result<(), String> {
-    let args = Cli::parse();
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Synthesize a layout (same as the top-level options, with the spec as a positional argument)
+    Synth {
+        /// Spec file to read, or `-` for stdin
+        #[arg(group = "input")]
+        spec: Option<String>,
 
-    // Get examples from either the command line or a file
-    let examples_str = match (args.examples, args.examples_file) {
-        (Some(e), None) => e,
-        (None, Some(f)) => fs::read_to_string(&f)
-            .map_err(|e| format!("Failed to read examples file '{}': {}", f, e))?,
-        _ => return Err("Please provide either --examples or --examples-file".to_string()),
+        #[command(flatten)]
+        args: Box<SynthArgs>,
+    },
+    /// Show structural changes between two layouts (.swift renders or example spec files)
+    Diff {
+        /// The previous layout
+        old: String,
+        /// The new layout
+        new: String,
+    },
+    /// Check that existing SwiftUI code still satisfies the examples
+    Check {
+        /// Examples to check against
+        #[arg(long, group = "input")]
+        examples: Option<String>,
+
+        /// File containing the examples; `-` reads stdin
+        #[arg(long, group = "input")]
+        examples_file: Option<String>,
+
+        /// SwiftUI file to check
+        #[arg(long)]
+        against: String,
+    },
+    /// Write examples of existing SwiftUI code, laid out at the given screen sizes, as a spec
+    Examples {
+        /// SwiftUI file to lay out
+        #[arg(long)]
+        from: String,
+
+        /// Screen sizes to lay it out at, e.g. `390x844,1024x768` or device names
+        #[arg(long, required = true, value_delimiter = ',', value_parser = examples::parse_size)]
+        sizes: Vec<(f64, f64)>,
+    },
+    /// Fit the layout model's constants to frames observed on a simulator, printing them as a `[layout]` config section
+    Calibrate {
+        /// JSON of the frames the calibration harness printed; `-` reads stdin
+        #[arg(required_unless_present = "harness")]
+        observations: Option<String>,
+
+        /// Print the Swift harness that observes the frames, instead of fitting
+        #[arg(long, conflicts_with = "observations")]
+        harness: bool,
+    },
+    /// Synthesize a corpus of benchmark specs and report the solve rate, times and candidates
+    Bench {
+        /// Directory of `<name>.spec` files, each with an optional expected `<name>.swift`
+        #[arg(default_value = "benches/specs")]
+        dir: PathBuf,
+
+        /// Synthesis algorithm: enumerative (default), template, constraint, beam, genetic or assignment
+        #[arg(long)]
+        strategy: Option<String>,
+
+        /// Print the results as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Train a model ordering the --top-n layouts on a log of the layouts people picked (see --feedback-log)
+    TrainRanker {
+        /// Feedback log, one JSON object per line; `-` reads stdin
+        #[arg(default_value = "-")]
+        log: String,
+
+        /// File to write the model to; it is printed otherwise
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Shrink a spec whose synthesis fails (or is slow) to the smallest spec that still does
+    Minimize {
+        /// Spec file; `-` reads stdin
+        #[arg(default_value = "-")]
+        spec: String,
+
+        /// Reproduce synthesis taking at least this long, e.g. `2s`, instead of failing
+        #[arg(long, value_parser = search::parse_duration)]
+        slower_than: Option<Duration>,
+
+        /// Synthesis algorithm: enumerative (default), template, constraint, beam, genetic or assignment
+        #[arg(long)]
+        strategy: Option<String>,
+
+        /// File to write the minimal spec to; it is printed otherwise
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Measure how far the synthesized views land from the frames the examples state
+    Fidelity {
+        /// Spec file; `-` reads stdin
+        #[arg(group = "input")]
+        spec: Option<String>,
+
+        /// Examples to synthesize and measure, instead of a spec file
+        #[arg(long, group = "input")]
+        examples: Option<String>,
+
+        /// Largest distance in points a stated coordinate may be off by and still pass
+        #[arg(long, default_value_t = 2.0)]
+        threshold: f64,
+
+        /// Synthesis algorithm: enumerative (default), template, constraint, beam, genetic or assignment
+        #[arg(long)]
+        strategy: Option<String>,
+    },
+    /// Audit a layout for problems with accessibility
+    Audit {
+        #[command(subcommand)]
+        action: AuditAction,
+    },
+    /// Manage the synthesis result cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+    /// Regenerate the views whenever the spec changes, for live Xcode Previews
+    Serve {
+        /// Spec file to watch
+        spec: String,
+
+        /// Folder in the Xcode project to keep the screens' files (with a #Preview each) up to date in
+        #[arg(long, value_name = "DIR")]
+        xcode: PathBuf,
+
+        /// How often to check the spec for changes, e.g. `300ms`
+        #[arg(long, default_value = "300ms", value_parser = search::parse_duration)]
+        interval: Duration,
+
+        #[command(flatten)]
+        args: Box<SynthArgs>,
+    },
+    /// Add synthesized views to an Xcode project or Swift package
+    Xcode {
+        #[command(subcommand)]
+        action: XcodeAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum XcodeAction {
+    /// Synthesize the spec's screens into a group of the project and compile them in its target
+    Add {
+        /// Spec file to read, or `-` for stdin
+        #[arg(group = "input")]
+        spec: Option<String>,
+
+        /// The .xcodeproj to add the files to
+        #[arg(long, required_unless_present = "package", conflicts_with = "package")]
+        project: Option<PathBuf>,
+
+        /// Directory of a Swift package (with Package.swift) to add the files to instead
+        #[arg(long)]
+        package: Option<PathBuf>,
+
+        /// Group to put the files in, e.g. `MyApp/Generated`; missing groups are created as folders
+        #[arg(long, default_value = "Generated")]
+        group: String,
+
+        /// Target to compile the files in (default: the project's first target, or the package's only one)
+        #[arg(long)]
+        app_target: Option<String>,
+
+        #[command(flatten)]
+        args: Box<SynthArgs>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum AuditAction {
+    /// Report views without a label for VoiceOver, low-contrast color tokens and small touch targets
+    A11y {
+        /// Spec file; `-` reads stdin
+        #[arg(group = "input")]
+        spec: Option<String>,
+
+        /// Examples to synthesize and audit, instead of a spec file
+        #[arg(long, group = "input")]
+        examples: Option<String>,
+
+        /// SwiftUI file to audit instead of the synthesized layout, laid out at the examples' sizes
+        #[arg(long)]
+        against: Option<String>,
+
+        /// JSON design token file whose colors to check for contrast (default: the config's token files)
+        #[arg(long = "tokens", value_name = "FILE")]
+        tokens: Vec<PathBuf>,
+
+        /// Synthesis algorithm: enumerative (default), template, constraint, beam, genetic or assignment
+        #[arg(long)]
+        strategy: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum CacheAction {
+    /// Delete all cached results
+    Clear,
+}
+
+/// Why a run failed. `Reported` means diagnostics were already printed.
+enum CliError {
+    Message(String),
+    Reported,
+}
+
+impl From<String> for CliError {
+    fn from(message: String) -> Self {
+        CliError::Message(message)
+    }
+}
+
+impl From<&str> for CliError {
+    fn from(message: &str) -> Self {
+        CliError::Message(message.to_string())
+    }
+}
+
+/// Shape of the `--json` output.
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    elapsed_ms: Option<f64>,
+    /// Set when the search budget ran out, e.g. "timeout reached".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exhausted: Option<String>,
+    /// True when the layout came from the result cache.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    cached: bool,
+    /// Decision trace, with `--explain`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanation: Option<&'a [String]>,
+    /// Work done by the run, with `--stats`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<&'a Stats<'a>>,
+    /// Code of the other satisfying layouts, cheapest first, with `--top-n`.
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    alternatives: &'a [String],
+    /// What a best-effort layout still gets wrong, e.g. "example 2: ...".
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    unresolved: &'a [String],
+    /// The fewest examples that contradict each other, when no layout satisfies them all.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conflict: Option<&'a str>,
+    /// Name of the screen, for specs of several named screens (one report each).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    screen: Option<&'a str>,
+    diagnostics: &'a [Diagnostic],
+}
+
+/// Shape of the `--stats` report.
+#[derive(Serialize)]
+struct Stats<'a> {
+    #[serde(flatten)]
+    search: SearchStats,
+    cache_hits: usize,
+    phases: &'a [Phase],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    peak_memory_bytes: Option<u64>,
+}
+
+impl std::fmt::Display for Stats<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = &self.search;
+        writeln!(f, "Synthesis statistics:")?;
+        writeln!(f, "  candidates: {} enumerated, {} pruned, {} scored", s.enumerated, s.pruned, s.scored)?;
+        writeln!(f, "  simulations: {}", s.simulated)?;
+        if s.memory_bytes > 0 {
+            writeln!(f, "  candidates seen: {:.1} MiB", s.memory_bytes as f64 / (1024.0 * 1024.0))?;
+        }
+        if s.subtrees_solved > 0 {
+            writeln!(f, "  subtrees: {} solved, {} reused", s.subtrees_solved, s.subtrees_reused)?;
+        }
+        writeln!(f, "  cache hits: {}", self.cache_hits)?;
+        for phase in self.phases {
+            writeln!(f, "  {:<8} {:.2?}", format!("{}:", phase.name), phase.elapsed)?;
+        }
+        if let Some(bytes) = self.peak_memory_bytes {
+            writeln!(f, "  peak memory: {:.1} MiB", bytes as f64 / (1024.0 * 1024.0))?;
+        }
+        Ok(())
+    }
+}
+
+fn print_json(report: &JsonReport) {
+    println!("{}", serde_json::to_string_pretty(report).expect("JSON report is serializable"));
+}
+
+// Prints parse diagnostics (rendered with carets, or as JSON) and marks the run as failed.
+fn report_diagnostics(diagnostics: &[Diagnostic], source: &str, origin: &str, json: bool) -> CliError {
+    if json {
+        print_json(&JsonReport { status: "error", code: None, elapsed_ms: None, exhausted: None, cached: false, explanation: None, stats: None, alternatives: &[], unresolved: &[], conflict: None, screen: None, diagnostics });
+    } else {
+        for diagnostic in diagnostics {
+            eprint!("{}", diagnostic.render(source, origin));
+        }
+    }
+    CliError::Reported
+}
+
+// Cancelled by Ctrl-C once `interrupt` installed its handler
+static INTERRUPT: OnceLock<CancelToken> = OnceLock::new();
+
+// The token Ctrl-C cancels: searches stop and show the best layout found so far. A second
+// Ctrl-C quits at once.
+fn interrupt() -> &'static CancelToken {
+    INTERRUPT.get_or_init(|| {
+        let token = CancelToken::default();
+        let installed = signal_hook::flag::register_conditional_shutdown(signal_hook::consts::SIGINT, 130, token.flag())
+            .and_then(|_| signal_hook::flag::register(signal_hook::consts::SIGINT, token.flag()));
+        if let Err(e) = installed {
+            warn!("cannot handle Ctrl-C ({}); it will stop the run without output", e);
+        }
+        token
+    })
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    logging::init(logging::level(cli.verbose), cli.log_format);
+    match run(cli) {
+        // Like a shell's, the status of a run cut short by Ctrl-C is 128 + SIGINT
+        Ok(()) if INTERRUPT.get().is_some_and(CancelToken::is_cancelled) => ExitCode::from(130),
+        Ok(()) => ExitCode::SUCCESS,
+        Err(CliError::Message(message)) => {
+            error!("{}", message);
+            ExitCode::FAILURE
+        }
+        Err(CliError::Reported) => ExitCode::FAILURE,
+    }
+}
+
+fn run(cli: Cli) -> Result<(), CliError> {
+    if cli.lsp {
+        return run_lsp(&cli.synth);
+    }
+    match cli.command {
+        Some(Command::Synth { spec, mut args }) => {
+            args.examples_file = spec.or(args.examples_file);
+            run_synth(*args, None).map(|_| ())
+        }
+        Some(Command::Diff { old, new }) => run_diff(&old, &new),
+        Some(Command::Check { examples, examples_file, against }) => run_check(examples, examples_file, &against),
+        Some(Command::Examples { from, sizes }) => run_examples(&from, &sizes),
+        Some(Command::Calibrate { observations, harness }) => run_calibrate(observations, harness),
+        Some(Command::Bench { dir, strategy, json }) => run_bench(&dir, strategy, json),
+        Some(Command::TrainRanker { log, output }) => run_train_ranker(&log, output),
+        Some(Command::Minimize { spec, slower_than, strategy, output }) => run_minimize(spec, slower_than, strategy, output),
+        Some(Command::Fidelity { spec, examples, threshold, strategy }) => run_fidelity(examples, spec, threshold, strategy),
+        Some(Command::Audit { action: AuditAction::A11y { spec, examples, against, tokens, strategy } }) => {
+            run_audit_a11y(examples, spec, against, tokens, strategy)
+        }
+        Some(Command::Cache { action: CacheAction::Clear }) => {
+            let cache = Cache::new(cache::default_dir().ok_or("Could not determine the cache directory")?);
+            let removed = cache.clear()?;
+            println!("Removed {} cached results from {}", removed, cache.dir().display());
+            Ok(())
+        }
+        Some(Command::Serve { spec, xcode, interval, args }) => run_serve(spec, xcode, interval, *args),
+        Some(Command::Xcode { action: XcodeAction::Add { spec, project, package, group, app_target, mut args } }) => {
+            args.examples_file = spec.or(args.examples_file);
+            run_xcode_add(project, package, &group, app_target.as_deref(), *args)
+        }
+        None => run_synth(cli.synth, None).map(|_| ()),
+    }
+}
+
+fn read_stdin() -> Result<String, String> {
+    let mut buffer = String::new();
+    io::stdin()
+        .read_to_string(&mut buffer)
+        .map_err(|e| format!("Failed to read examples from stdin: {}", e))?;
+    Ok(buffer)
+}
+
+// Writes the generated code for pipelines; a closed pipe (e.g. `| head`) is not an error.
+fn write_stdout(text: &str) -> Result<(), CliError> {
+    match writeln!(io::stdout().lock(), "{}", text) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            Err(format!("Failed to write to stdout: {}", e).into())
+        }
+        _ => Ok(()),
+    }
+}
+
+// Appends `text` to the file at `path`, creating it if need be
+fn append_file(path: &Path, text: &str) -> Result<(), CliError> {
+    fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(text.as_bytes()))
+        .map_err(|e| format!("Failed to append to '{}': {}", path.display(), e).into())
+}
+
+// Gets examples from the command line, a file, or stdin (`-`), along with their origin for diagnostics.
+fn read_examples(examples: Option<String>, examples_file: Option<String>) -> Result<(String, String), CliError> {
+    match (examples, examples_file) {
+        (Some(e), None) | (None, Some(e)) if e == "-" => Ok((read_stdin()?, "<stdin>".to_string())),
+        (Some(e), None) => Ok((e, "<examples>".to_string())),
+        (None, Some(f)) => match import_design_file(&f)? {
+            Some(spec) => Ok((spec, f)),
+            None => Ok((
+                fs::read_to_string(&f).map_err(|e| format!("Failed to read examples file '{}': {}", f, e))?,
+                f,
+            )),
+        },
+        _ => Err("Please provide either --examples or --examples-file".into()),
+    }
+}
+
+// Converts a storyboard, XIB, Sketch or XD file to a spec of its screens, warning of the views
+// left out; other files are specs, for which this gives `None`
+fn import_design_file(path: &str) -> Result<Option<String>, String> {
+    let extension = Path::new(path).extension().map(|extension| extension.to_string_lossy().to_lowercase());
+    if !matches!(extension.as_deref(), Some("storyboard" | "xib" | "sketch" | "json" | "xd")) {
+        return Ok(None);
+    }
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+    let import = match extension.as_deref() {
+        Some("sketch" | "json") => sketch::import(&bytes),
+        Some("xd") => xd::import(&bytes),
+        _ => {
+            let name = Path::new(path).file_stem().unwrap_or_default().to_string_lossy();
+            storyboard::import(&String::from_utf8_lossy(&bytes), &name)
+        }
     };
+    let import = import.map_err(|e| format!("Failed to import '{}': {}", path, e))?;
+    for warning in &import.warnings {
+        warn!("{}", warning);
+    }
+    Ok(Some(import.spec))
+}
 
-    // Parse examples
-    let examples = input::parser::parse_examples(&examples_str)
-        .map_err(|e| format!("Failed to parse examples: {}", e))?;
+// Converts a screenshot's labeled boxes, or without them the boxes of its text, to a spec of
+// its screen, warning of the boxes left out
+fn import_screenshot(image: &Path, annotations: Option<&Path>, scale: Option<f64>) -> Result<String, String> {
+    let bytes = fs::read(image).map_err(|e| format!("Failed to read screenshot '{}': {}", image.display(), e))?;
+    let size = annotations::image_size(&bytes).map_err(|e| format!("Failed to read screenshot '{}': {}", image.display(), e))?;
+    let image_name = image.file_name().unwrap_or_default().to_string_lossy();
+    let (boxes, source) = match annotations {
+        Some(path) => {
+            let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read annotations '{}': {}", path.display(), e))?;
+            let boxes = match path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("csv")) {
+                true => annotations::parse_csv(&contents, &image_name),
+                false => annotations::parse_json(&contents, &image_name),
+            };
+            (boxes, format!("annotations '{}'", path.display()))
+        }
+        None => (Ok(annotations::read_text(image)?), format!("screenshot '{}'", image.display())),
+    };
+    let import = boxes
+        .and_then(|boxes| annotations::import(size, &boxes, &image_name, scale))
+        .map_err(|e| format!("Failed to import {}: {}", source, e))?;
+    for warning in &import.warnings {
+        warn!("{}", warning);
+    }
+    Ok(import.spec)
+}
+
+fn current_dir() -> Result<PathBuf, String> {
+    std::env::current_dir().map_err(|e| format!("Failed to read current directory: {}", e))
+}
+
+// Loads the component library from `--components`, the config file, or `components.toml`
+// in the current directory; without any of these the library is empty.
+fn load_components(explicit: Option<&Path>, config: &Config, cwd: &Path) -> Result<ComponentLibrary, String> {
+    let path = explicit
+        .map(Path::to_path_buf)
+        .or_else(|| config.components_file())
+        .or_else(|| Some(cwd.join(COMPONENTS_FILE_NAME)).filter(|path| path.is_file()));
+    match path {
+        Some(path) => ComponentLibrary::load(&path),
+        None => Ok(ComponentLibrary::default()),
+    }
+}
+
+// Synthesizes the spec's screens and prints or saves them; returns the paths it saved, and
+// those of up-to-date screens it left as they were
+// `previous`, kept by `serve` between runs, lets an edit that only changes labels skip the search
+fn run_synth(mut args: SynthArgs, mut previous: Option<&mut Previous>) -> Result<(Vec<PathBuf>, Vec<PathBuf>), CliError> {
+    // Every option that changes what is written, for the build manifest of --out-dir
+    let described_args = format!(
+        "{:?}",
+        SynthArgs { force: false, no_cache: false, quiet: false, json: false, explain: false, stats: false, ..args.clone() }
+    );
+    let cwd = current_dir()?;
+    let mut config = Config::resolve(args.config.as_deref(), &cwd)?;
+    if let Some(platform) = args.platform {
+        config.platform = Some(platform.to_string());
+    }
+    let registry = Registry::with_plugins(&config.plugin_files())?;
+    let mut backend = registry.resolve(args.target.as_deref().or(config.target.as_deref()).unwrap_or("swiftui"))?;
+    // User templates take the place of the built-in SwiftUI renderer
+    let template_backend;
+    if let Some(dir) = args.template_dir.clone().or_else(|| config.template_dir()) {
+        if backend.name() != "swiftui" {
+            return Err(format!("--template-dir renders SwiftUI; it cannot be used with --target {}", backend.name()).into());
+        }
+        template_backend = TemplateBackend { templates: Templates::load(&dir)? };
+        backend = &template_backend;
+    }
+    let target = backend.name();
+    // Options for Swift's and Xcode's tooling, and those for SwiftUI views alone
+    let swift_only = args.emit_ui_tests.is_some() || args.assets.is_some() || args.validate.is_some() || args.post_format.is_some();
+    if !backend.is_swift() && swift_only {
+        return Err(format!("--target {} cannot be combined with --emit-ui-tests, --assets, --validate or --post-format", target).into());
+    }
+    if target != "swiftui" && (args.app.is_some() || args.emit_tests.is_some() || args.emit_package.is_some()) {
+        return Err(format!("--target {} cannot be combined with --app, --emit-tests or --emit-package", target).into());
+    }
+    if args.previews && target != "swiftui" {
+        return Err(format!("serve shows SwiftUI previews; it cannot be used with --target {}", target).into());
+    }
+    // AppKit view controllers are for a Mac app, whatever the config's platform
+    if let Some(platform) = backend.platform() {
+        match config.platform {
+            None => config.platform = Some(platform.to_string()),
+            Some(_) if config.platform() != platform => {
+                return Err(format!("--target {} is for {}, but the platform is {}", target, platform, config.platform()).into());
+            }
+            Some(_) => {}
+        }
+    }
+    // A package keeps the views in its library's folder, public so that apps can use them
+    let package = args.emit_package.as_deref().map(Package::new).transpose()?;
+    if let Some(package) = &package {
+        args.out_dir = Some(package.sources_dir());
+    }
+    let render_options = RenderOptions {
+        public: package.is_some(),
+        // UI tests find the views by their identifiers
+        accessibility_identifiers: args.accessibility_ids || args.emit_ui_tests.is_some() || config.render_options().accessibility_identifiers,
+        namespace: args.namespace.clone(),
+        haptics: args.haptics.clone().or_else(|| config.render_options().haptics),
+        ..config.render_options()
+    };
+    let components = load_components(args.components.as_deref(), &config, &cwd)?;
+    let mut metrics = config.metrics();
+    components.apply_metrics(&mut metrics);
+    let snap_spacing = args.snap_spacing || config.spacing.snap == Some(true);
+    let code_metrics = code_metrics(&metrics, &config, snap_spacing)?;
+    if let Some([format, _]) = args.preview.as_deref() {
+        if format != "png" {
+            return Err(format!("Unknown preview format '{}': expected png", format).into());
+        }
+    }
+    // Test classes are named after their files, e.g. `LoginViewTests.swift`
+    let class_name = |path: &Path| path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+    let test_class = args.emit_tests.as_deref().map(class_name);
+    let ui_test_class = args.emit_ui_tests.as_deref().map(class_name);
+    for class in test_class.iter().chain(&ui_test_class) {
+        snapshot::validate_class_name(class)?;
+    }
+
+    let (examples_str, origin) = match &args.screenshot {
+        Some(image) => {
+            let origin = args.annotations.as_deref().unwrap_or(image).display().to_string();
+            (import_screenshot(image, args.annotations.as_deref(), args.scale)?, origin)
+        }
+        None => read_examples(args.examples, args.examples_file)?,
+    };
+    let mut profiler = Profiler::new();
+
+    // Parse the screens' examples; registered component names are accepted as element keys
+    let options = ParseOptions { components, strict: args.strict, platform: config.platform() };
+    let Spec { mut screens, flows, mut warnings } = info_span!("parse", origin = %origin)
+        .in_scope(|| profiler.time("parse", || input::parser::parse_spec_with(&examples_str, &options)))
+        .map_err(|errors| report_diagnostics(&errors, &examples_str, &origin, args.json))?;
+    debug!(screens = screens.len(), flows = flows.len(), "parsed the spec");
+    for screen in &screens {
+        warnings.extend(synthesis::swiftui::ignored_input(&screen.examples));
+    }
+    if args.deny_warnings && !warnings.is_empty() {
+        let errors: Vec<Diagnostic> = warnings.into_iter().map(|warning| Diagnostic { severity: Severity::Error, ..warning }).collect();
+        return Err(report_diagnostics(&errors, &examples_str, &origin, args.json));
+    }
+    if target != "swiftui" && !flows.is_empty() {
+        return Err(format!("--target {} does not support flows between screens; synthesize each screen on its own", target).into());
+    }
+    // With --json they are part of each report instead
+    if !args.json {
+        for warning in &warnings {
+            eprint!("{}", warning.render(&examples_str, &origin));
+        }
+    }
+
+    // Generated files record the examples as written, before feedback constrains them
+    let specified: Vec<Vec<Example>> = screens.iter().map(|screen| screen.examples.clone()).collect();
+
+    // Feedback on earlier results constrains where views may go
+    if let Some(path) = &args.reject {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read feedback file '{}': {}", path.display(), e))?;
+        let constraints = refine::parse_constraints(&contents)
+            .map_err(|e| format!("Invalid feedback file '{}': {}", path.display(), e))?;
+        for screen in &mut screens {
+            for constraint in &constraints {
+                refine::constrain(&mut screen.examples, constraint);
+            }
+        }
+    }
+
+    // `-o -` means "code only, on stdout", which is the same as --quiet without a file
+    let to_stdout = args.output.as_deref() == Some("-");
+    let quiet = args.quiet || to_stdout || args.emit_tree.is_some();
+    let output_path = args.output.filter(|path| path != "-");
+    if screens.len() > 1 && output_path.is_some() {
+        return Err("The spec defines several screens; write them to separate files with --out-dir".into());
+    }
+    let interactive = !args.json && !quiet && io::stdin().is_terminal() && io::stdout().is_terminal();
+    if args.refine && (!interactive || origin == "<stdin>") {
+        return Err("--refine asks for feedback in a terminal; it cannot be combined with --json, --quiet, -o - or examples on stdin".into());
+    }
+
+    // Search for a layout within the budget; CLI flags override the config file
+    let defaults = config.budget();
+    let budget = Budget {
+        timeout: args.timeout.or(defaults.timeout),
+        max_depth: args.max_depth.unwrap_or(defaults.max_depth),
+        max_candidates: args.max_candidates.unwrap_or(defaults.max_candidates),
+        seed: args.seed.or(defaults.seed),
+        top_n: args.top_n.map_or(defaults.top_n, |n| n as usize),
+        beam_width: args.beam_width.map_or(defaults.beam_width, |n| n as usize),
+        max_memory: args.max_memory.or(defaults.max_memory),
+        cancel: interrupt().clone(),
+    };
+    let strategy_name = args.strategy.as_deref().or(config.synthesis.strategy.as_deref()).unwrap_or(strategy::STRATEGIES[0]);
+    let strategy = strategy::by_name(strategy_name, metrics.clone(), config.cost.clone())?;
+    let ranker = match args.ranker.clone().or_else(|| config.ranker_file()) {
+        Some(path) => {
+            let json = fs::read_to_string(&path).map_err(|e| format!("Failed to read ranker '{}': {}", path.display(), e))?;
+            Some(Ranker::parse(&json).map_err(|e| format!("Invalid ranker '{}': {}", path.display(), e))?)
+        }
+        None => None,
+    };
+    let feedback_log = args.feedback_log.clone().or_else(|| config.feedback_log_file());
+    #[cfg(feature = "llm")]
+    let llm = match args.llm.as_deref().or(config.llm.provider.as_deref()) {
+        Some(provider) => Some(llm::Client::new(provider.parse()?, &config.llm)),
+        None => None,
+    };
+    #[cfg(not(feature = "llm"))]
+    if config.llm.provider.is_some() {
+        return Err("[llm] provider needs swiftui-synth built with the llm feature".to_string().into());
+    }
+    // With --post-format, code goes through the formatter before it is shown or saved. A
+    // formatter that fails is reported once, and the built-in pretty-printer used instead.
+    let command = match args.post_format.as_deref() {
+        Some("") => config.format.command.clone(),
+        Some("builtin") | None => None,
+        Some(command) => Some(command.to_string()),
+    };
+    let command_failed = std::cell::Cell::new(false);
+    let post_format = |code: String| match (&args.post_format, &command) {
+        (None, _) => code,
+        (Some(_), Some(command)) if !command_failed.get() => format::run_command(command, &code).unwrap_or_else(|e| {
+            warn!("{}; using the built-in pretty-printer", e);
+            command_failed.set(true);
+            format::pretty_print(&code, &config.format_options())
+        }),
+        (Some(_), _) => format::pretty_print(&code, &config.format_options()),
+    };
+
+    // What the search depends on besides the examples
+    let settings = cache::fingerprint(&[], &budget, strategy.name(), &metrics, &config.cost);
 
-    // Synthesize layout
-    let start = Instant::now();
-    let ir = synthesis::swiftui::synthesize_layout(examples)
-        .ok_or("No matching layout found for the given examples")?;
-    let duration = start.elapsed();
+    // With --out-dir, screens whose inputs are as the folder's build manifest records are
+    // not synthesized or written again, unless --force is given
+    let mut manifest = args.out_dir.as_deref().map(|dir| {
+        Manifest::load(dir).unwrap_or_else(|e| {
+            warn!("{}; rebuilding every screen", e);
+            Manifest::default()
+        })
+    });
+    let build_settings = format!("{}\n{}\n{:?}\n{:?}\n{:?}", settings, described_args, config, options.components, code_metrics);
+    let flows_text = if flows.is_empty() { String::new() } else { format!("{:?}", flows) };
+    let expansion = preprocess::expand(&lexer::strip_comments(&examples_str)).ok();
+    let screen_ranges = expansion.as_ref().map(build::screen_ranges).unwrap_or_default();
+    let mut built = Vec::new();
 
-    // Render SwiftUI code
-    let swiftui_code = output::render::render_swiftui(&ir);
+    let mut views = Vec::new();
+    let several = screens.len() > 1;
+    for (index, screen) in screens.iter_mut().enumerate() {
+        let (name, examples) = (screen.name.as_deref(), &mut screen.examples);
+        let _span = info_span!("synthesize", screen = %render::view_name(name)).entered();
+        let mut inputs = Default::default();
+        let mut up_to_date;
+        let (view, swiftui_code) = loop {
+            let start = Instant::now();
+            let mut unchanged = None;
+            if let (Some(manifest), Some(dir)) = (&manifest, &args.out_dir) {
+                let uses = match (&expansion, screen_ranges.get(index)) {
+                    (Some(expansion), Some(range)) => expansion.uses(range.clone()),
+                    _ => Vec::new(),
+                };
+                let view_name = render::view_name(name);
+                inputs = build::inputs(examples, &uses, &flows_text, &build_settings);
+                match manifest.changed(&view_name, &inputs, dir) {
+                    None if !args.force => unchanged = manifest.screens[&view_name].ir.clone(),
+                    Some(changed) if manifest.screens.contains_key(&view_name) && !args.json && !quiet => {
+                        println!("Rebuilding {}: {} changed", view_name, changed.join(", "));
+                    }
+                    _ => {}
+                }
+            }
+            up_to_date = unchanged.is_some();
+            let built_before = unchanged.map(|ir| Outcome {
+                ir,
+                violations: 0,
+                candidates: 0,
+                exhausted: None,
+                trace: vec!["Kept the layout built before: nothing this screen depends on has changed".to_string()],
+                stats: SearchStats::default(),
+                alternatives: Vec::new(),
+            });
+            let cache = cache::default_dir().filter(|_| !args.no_cache).map(Cache::new);
+            let key = cache::fingerprint(examples, &budget, strategy.name(), &metrics, &config.cost);
+            let reused = match built_before {
+                Some(_) => None,
+                None => previous.as_ref().and_then(|previous| previous.reuse(name, &settings, examples, &metrics)),
+            };
+            let cached = match (&built_before, &reused) {
+                (None, None) => profiler.time("cache", || cache.as_ref().and_then(|cache| cache.get(&key))),
+                _ => None,
+            };
+            let from_cache = cached.is_some();
+            let source = match (&built_before, &reused) {
+                (Some(_), _) => "build manifest",
+                (_, Some(_)) => "previous layout",
+                _ if from_cache => "cache",
+                _ => "search",
+            };
+            let mut outcome = match built_before.or(reused).or(cached) {
+                Some(outcome) => outcome,
+                None => {
+                    let outcome = profiler
+                        .time("search", || strategy.synthesize(examples, &budget))
+                        .ok_or("No matching layout found for the given examples")?;
+                    if let Some(Err(e)) = profiler.time("cache", || cache.as_ref().map(|cache| cache.put(&key, &outcome))) {
+                        warn!("{}", e);
+                    }
+                    outcome
+                }
+            };
+            debug!(source, candidates = outcome.candidates, violations = outcome.violations, "found a layout");
+            if let Some(previous) = previous.as_mut() {
+                previous.record(name, &settings, examples, &outcome);
+            }
+            // The learned ranker, if any, decides which of the layouts satisfying every example
+            // comes first
+            if let Some(ranker) = ranker.as_ref().filter(|_| outcome.violations == 0 && !outcome.alternatives.is_empty()) {
+                let mut layouts: Vec<IR> = std::iter::once(outcome.ir.clone()).chain(outcome.alternatives.drain(..)).collect();
+                ranker.rank(&mut layouts, examples, &metrics);
+                outcome.trace.push(format!("Ranker put the {} layouts satisfying every example in the order they are most likely to be picked", layouts.len()));
+                outcome.ir = layouts.remove(0);
+                outcome.alternatives = layouts;
+            }
+            // Then the language model, if asked, may reorder them; it is never required
+            #[cfg(feature = "llm")]
+            if let Some(client) = llm.as_ref().filter(|_| outcome.violations == 0 && !outcome.alternatives.is_empty()) {
+                let mut layouts: Vec<IR> = std::iter::once(outcome.ir.clone()).chain(outcome.alternatives.drain(..)).collect();
+                let codes: Vec<String> = layouts.iter().map(render::render_swiftui).collect();
+                match profiler.time("llm", || client.hint(&examples_str, &codes)) {
+                    Ok(hint) => {
+                        layouts = hint.order.iter().map(|&i| layouts[i].clone()).collect();
+                        let reason = hint.reason.map_or(String::new(), |reason| format!(": {}", reason));
+                        outcome.trace.push(format!("The language model ({}) ordered the {} verified layouts{}", client.model, layouts.len(), reason));
+                    }
+                    Err(e) if !args.json => warn!("no hint from the language model ({}); keeping the search's order", e),
+                    Err(_) => {}
+                }
+                outcome.ir = layouts.remove(0);
+                outcome.alternatives = layouts;
+            }
+            let duration = start.elapsed();
+            // Patterns that satisfy the examples but make poor SwiftUI, named by screen
+            let mut lints = synthesis::lint::lint(&outcome.ir, examples, &metrics, &config.lint_options());
+            for lint in &mut lints {
+                if let Some(name) = name {
+                    lint.message = format!("{}: {}", render::view_name(Some(name)), lint.message);
+                }
+                if !args.json {
+                    eprint!("{}", lint.render(&examples_str, &origin));
+                }
+            }
+            // Buttons that navigate become links; built-in views are swapped for the team's
+            // components registered for their role
+            let mut ir = options.components.substitute(&flow::wire(&outcome.ir, name, &flows));
+            // A best-effort layout comes with the examples' requirements it still breaks
+            let unresolved: Vec<String> = match outcome.violations {
+                0 => Vec::new(),
+                _ => synthesis::verify::verify(&outcome.ir, examples, &metrics).iter().map(ToString::to_string).collect(),
+            };
+            let problems: String = unresolved.iter().map(|problem| format!("\n  - {}", problem)).collect();
+            match outcome.exhausted {
+                _ if args.json => {}
+                Some(Exhausted::Cancelled) => warn!(
+                    "synthesis cancelled after {} candidates; showing the best layout so far ({} unresolved problems){}",
+                    outcome.candidates, outcome.violations, problems
+                ),
+                Some(reason) => warn!(
+                    "synthesis budget exhausted ({} after {} candidates); showing the best layout so far ({} unresolved problems){}",
+                    reason, outcome.candidates, outcome.violations, problems
+                ),
+                None if outcome.violations > 0 => warn!(
+                    "the {} strategy found no layout satisfying every example; showing the closest ({} unresolved problems){}",
+                    strategy.name(),
+                    outcome.violations,
+                    problems
+                ),
+                None => {}
+            }
+            // When the search ran to completion without satisfying them, the fewest examples that
+            // already contradict each other say what to fix
+            let conflict = match outcome.violations {
+                0 => None,
+                _ if outcome.exhausted.is_some() || examples.len() < 2 => None,
+                _ => profiler.time("conflict", || {
+                    conflict::minimal_conflict(examples, &metrics, |examples| strategy.synthesize(examples, &budget))
+                }),
+            }
+            .map(|conflict| conflict.to_string());
+            if let Some(conflict) = conflict.as_ref().filter(|_| !args.json) {
+                info!("{}", conflict);
+            }
 
-    // Output the result
-    println!("Synthesized SwiftUI layout in {:.2?}:\n{}", duration, swiftui_code);
+            // Render SwiftUI code for the best layout and, with --top-n, the runners-up
+            let alternatives: Vec<IR> =
+                outcome.alternatives.iter().map(|ir| options.components.substitute(&flow::wire(ir, name, &flows))).collect();
+            let (mut swiftui_code, mut alternative_codes) = info_span!("render").in_scope(|| profiler.time("render", || {
+                // Content the examples place under the status bar or home indicator ignores the safe area
+                let options = RenderOptions {
+                    ignores_safe_area: layout::ignores_safe_area(examples),
+                    view_name: render::view_name(name),
+                    metrics: code_metrics.clone(),
+                    explicit_spacing: snap_spacing,
+                    text_styles: layout::text_style::classify(examples),
+                    image_sizing: layout::image_sizing::sizing(examples),
+                    view_modifiers: modifier::stated(examples),
+                    background: background::stated(examples),
+                    ..render_options.clone()
+                };
+                let options = RenderOptions { text_wrapping: layout::text_style::wrapping(examples, &options.text_styles, &options.text_font), ..options };
+                // Text that outgrows the screen at a larger Dynamic Type size scrolls or shrinks
+                let render = |ir: &IR| {
+                    let text_fit = layout::text_fit(ir, examples, &metrics);
+                    backend.try_render(ir, &RenderOptions { text_fit, ..options.clone() })
+                };
+                for message in backend.left_out(&ir, &options) {
+                    warn!("{}", message);
+                }
+                Ok::<_, String>((render(&ir)?, alternatives.iter().map(render).collect::<Result<Vec<_>, _>>()?))
+            }))?;
+            if interactive && !alternative_codes.is_empty() {
+                let (picked, code) = choose(&ir, swiftui_code, &alternatives, alternative_codes, examples, &metrics)?;
+                // The layouts offered and the one picked, as training data for `train-ranker`
+                if let Some(path) = &feedback_log {
+                    let offered: Vec<&IR> = std::iter::once(&outcome.ir).chain(&outcome.alternatives).collect();
+                    let index = std::iter::once(&ir).chain(&alternatives).position(|ir| *ir == picked).unwrap_or(0);
+                    append_file(path, &ranker::feedback(&offered, index, examples, &metrics))?;
+                }
+                swiftui_code = code;
+                alternative_codes = Vec::new();
+                if picked != ir && args.explain {
+                    info!("the explanation below describes the cheapest layout, not the one picked");
+                }
+                ir = picked;
+            }
+            // A named screen, or any screen written to its own file, becomes a view struct
+            if target == "swiftui" && (name.is_some() || args.out_dir.is_some()) {
+                swiftui_code = render::render_view(&render::view_name(name), &swiftui_code, &render_options);
+            }
+            swiftui_code = post_format(swiftui_code);
+            alternative_codes = alternative_codes.into_iter().map(&post_format).collect();
+            let stats = args.stats.then(|| Stats {
+                // A cached result did no search work in this run
+                search: if from_cache { SearchStats::default() } else { outcome.stats },
+                cache_hits: usize::from(from_cache),
+                phases: profiler.phases(),
+                peak_memory_bytes: profiler::peak_memory_bytes(),
+            });
+
+            // Output the result
+            if args.json {
+                print_json(&JsonReport {
+                    status: "ok",
+                    code: Some(&swiftui_code),
+                    elapsed_ms: Some(duration.as_secs_f64() * 1000.0),
+                    exhausted: outcome.exhausted.map(|reason| reason.to_string()),
+                    cached: from_cache,
+                    explanation: args.explain.then_some(outcome.trace.as_slice()),
+                    stats: stats.as_ref(),
+                    alternatives: &alternative_codes,
+                    unresolved: &unresolved,
+                    conflict: conflict.as_deref(),
+                    screen: name,
+                    diagnostics: &[warnings.as_slice(), lints.as_slice()].concat(),
+                });
+            } else if let Some(format) = args.emit_tree {
+                let title = name.map(|name| render::view_name(Some(name)));
+                let separator = if views.is_empty() { "" } else { "\n" };
+                write_stdout(&format!("{}{}", separator, tree::render_tree(&ir, title.as_deref(), format)))?;
+            } else if up_to_date {
+                // Listed as up to date once the files are saved
+            } else if !quiet {
+                let source = if from_cache { " (cached)" } else { "" };
+                let what = name.map_or(format!("{} layout", backend.language()), |name| backend.type_name(&render::view_name(Some(name))));
+                println!("Synthesized {} in {:.2?}{}:\n{}", what, duration, source, swiftui_code);
+            } else if output_path.is_none() && args.out_dir.is_none() {
+                write_stdout(&swiftui_code)?;
+            }
+
+            // The explanation goes to stderr when stdout carries only the code
+            if args.explain && !args.json {
+                let explanation: String = outcome.trace.iter().map(|line| format!("  - {}\n", line)).collect();
+                if quiet {
+                    eprint!("Why this layout:\n{}", explanation);
+                } else {
+                    print!("\nWhy this layout:\n{}", explanation);
+                }
+            }
+            if let Some(stats) = stats.filter(|_| !args.json) {
+                if quiet {
+                    eprint!("{}", stats);
+                } else {
+                    print!("\n{}", stats);
+                }
+            }
+
+            // With --refine, feedback on the layout becomes a constraint for another round
+            if !args.refine {
+                break (ir, swiftui_code);
+            }
+            match ask_feedback()? {
+                Some(constraint) => {
+                    eprintln!("Added constraint: {}", constraint);
+                    refine::constrain(examples, &constraint);
+                }
+                None => break (ir, swiftui_code),
+            }
+        };
+        // With --preview, a picture of the screen as laid out for the chosen example; with
+        // several screens, each picture is named after its view, e.g. `preview-LoginView.png`
+        if let Some([_, path]) = args.preview.as_deref() {
+            let example = examples.get(args.preview_example as usize - 1).ok_or_else(|| {
+                format!("--preview-example {} is out of range: the screen has {} examples", args.preview_example, examples.len())
+            })?;
+            let metrics = metrics.scaled(layout::dynamic_type(example).scale());
+            let size = layout::screen_for(&view, example, &metrics).ok_or("The example to preview needs width and height dimensions")?;
+            let insets = if layout::ignores_safe_area(examples) { Insets::default() } else { layout::safe_area(example) };
+            let simulated = layout::simulate_within(&view, size, insets, &metrics).with_direction(layout::layout_direction(example));
+            let path = match several {
+                false => PathBuf::from(path),
+                true => {
+                    let path = Path::new(path);
+                    let stem = path.file_stem().map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+                    path.with_file_name(format!("{}-{}.png", stem, render::view_name(name)))
+                }
+            };
+            fs::write(&path, png::render_png(&simulated)?).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+            if !args.json && !quiet {
+                println!("Drew {} to {}", render::view_name(name), path.display());
+            }
+        }
+        views.push((name, view, swiftui_code));
+        built.push((inputs, up_to_date));
+    }
+
+    // Flows push routes, which the views' NavigationStack resolves to the destination screens
+    let routes = (!flows.is_empty())
+        .then(|| post_format(render::render_routes(&flow::destinations(&flows), &render_options)));
+    if let Some(routes) = routes.as_ref().filter(|_| !args.json && args.out_dir.is_none() && args.emit_tree.is_none()) {
+        if quiet {
+            write_stdout(&format!("\n{}", routes))?;
+        } else {
+            println!("\nNavigation routes:\n{}", routes);
+        }
+    }
+    // The code as printed, views then routes; a missing clipboard does not stop the run
+    if args.copy {
+        let mut codes: Vec<&str> = views.iter().map(|(_, _, code)| code.as_str()).collect();
+        codes.extend(routes.as_deref());
+        match clipboard::copy(&codes.join("\n\n")) {
+            Ok(()) if !args.json && !quiet => println!("Copied the code to the clipboard"),
+            Ok(()) => {}
+            Err(e) => warn!("{}", e),
+        }
+    }
+
+    // Placeholders for the assets the code names, to be replaced with the real artwork
+    if let Some(dir) = &args.assets {
+        let mut images: Vec<String> = Vec::new();
+        for image in views.iter().flat_map(|(_, ir, _)| assets::referenced_images(ir)) {
+            if !images.contains(&image) {
+                images.push(image);
+            }
+        }
+        let created = assets::write_catalog(dir, &images, &load_colors(&config.token_files())?)?;
+        if !args.json && !quiet && !created.is_empty() {
+            println!("Added {} to {}", created.join(", "), dir.display());
+        }
+    }
+
+    // Each screen as its own view struct, plus the routes and, with --app, App.swift, each
+    // headed by where it came from: a screen's file by its examples, the others by all of them
+    let header = |examples: &[Example]| Provenance::new(examples, strategy.name(), std::env::args().skip(1)).header();
+    let headers: Vec<String> = specified.iter().map(|examples| header(examples)).collect();
+    let shared_header = header(&specified.concat());
+    let (files, view_files) = {
+        let per_view: Vec<Vec<SourceFile>> = views
+            .iter()
+            .zip(&headers)
+            .map(|((name, ir, code), header)| {
+                let view_name = render::view_name(*name);
+                let code = match target == "swiftui" && name.is_none() && args.out_dir.is_none() {
+                    true => render::render_view(&view_name, code, &render_options),
+                    false => code.clone(),
+                };
+                let code = match args.previews {
+                    true => format!("{}\n\n{}", code, render::render_preview(&view_name, !flows.is_empty(), &render_options)),
+                    false => code,
+                };
+                let options = RenderOptions { view_name, ..render_options.clone() };
+                let files = backend.files(ir, &code, header, &options);
+                files.into_iter().map(|(name, code)| SourceFile { name, code, ir: Some(ir.clone()) }).collect::<Vec<_>>()
+            })
+            .collect();
+        let view_files: Vec<Vec<String>> = per_view.iter().map(|files| files.iter().map(|file| file.name.clone()).collect()).collect();
+        let mut files: Vec<SourceFile> = per_view.into_iter().flatten().collect();
+        if let Some(routes) = &routes {
+            let code = format!("{}\nimport SwiftUI\n\n{}\n", shared_header, routes);
+            files.push(SourceFile { name: "Route.swift".to_string(), code, ir: None });
+        }
+        if let Some(style) = args.app {
+            let names: Vec<Option<&str>> = views.iter().map(|(name, _, _)| *name).collect();
+            let app = post_format(render::render_app(&names, style, routes.is_some(), &render_options));
+            files.push(SourceFile { name: "App.swift".to_string(), code: format!("{}\n{}\n", shared_header, app), ir: None });
+        }
+        (files, view_files)
+    };
+
+    // With --validate, nothing is saved unless the compiler accepts the code
+    if args.validate.is_some() {
+        match Toolchain::find() {
+            Some(toolchain) => {
+                let errors = profiler.time("validate", || toolchain.check(&files))?;
+                if !errors.is_empty() {
+                    return Err(report_diagnostics(&errors, &examples_str, &origin, args.json));
+                }
+                if !args.json && !quiet {
+                    let how = if toolchain.sdk.is_some() { "typechecks" } else { "parses" };
+                    println!("Validated with {}: the generated code {}", toolchain.swiftc.display(), how);
+                }
+            }
+            None => warn!("--validate swift needs swiftc on the PATH (or $SWIFTC); the code was not validated"),
+        }
+    }
+
+    // With --emit-tests, a snapshot of each screen at each size its examples give
+    if let (Some(path), Some(class)) = (&args.emit_tests, &test_class) {
+        let sizes = views.iter().zip(&specified).map(|((name, ir, _), examples)| {
+            let sizes = examples.iter().filter_map(|example| layout::screen_for(ir, example, &metrics)).collect();
+            (render::view_name(*name), sizes)
+        });
+        let module = args.test_module.as_deref().or(package.as_ref().map(|package| package.name.as_str()));
+        let tests = snapshot::render_snapshot_tests(class, module, &sizes.collect::<Vec<_>>());
+        if let Some(conflict) = save(path, &format!("{}\n{}\n", shared_header, tests), args.force)? {
+            return Err(conflict_error(&[(&path.display().to_string(), conflict)]));
+        }
+        if !args.json && !quiet {
+            println!("Saved snapshot tests to {}", path.display());
+        }
+    }
+
+    // With --emit-ui-tests, a UI test per screen, in the order of the spec
+    if let (Some(path), Some(class)) = (&args.emit_ui_tests, &ui_test_class) {
+        let screens: Vec<(String, &IR)> = views.iter().map(|(name, ir, _)| (render::view_name(*name), ir)).collect();
+        let tests = ui_tests::render_ui_tests(class, &screens);
+        if let Some(conflict) = save(path, &format!("{}\n{}\n", shared_header, tests), args.force)? {
+            return Err(conflict_error(&[(&path.display().to_string(), conflict)]));
+        }
+        if !args.json && !quiet {
+            println!("Saved UI tests to {}", path.display());
+        }
+    }
 
     // Save to file if --output is specified
-    if let Some(output_path) = args.output {
-        let mut file = File::create(&output_path)
-            .map_err(|e| format!("Failed to create output file '{}': {}", output_path, e))?;
-        file.write_all(swiftui_code.as_bytes())
-            .map_err(|e| format!("Failed to write to output file '{}': {}", output_path, e))?;
-        println!("Saved SwiftUI layout to {}", output_path);
+    if let (Some(output_path), [(_, _, swiftui_code)]) = (output_path, views.as_slice()) {
+        let code = format!("{}\n{}", backend.header(&headers[0]), swiftui_code);
+        if let Some(conflict) = save(Path::new(&output_path), &code, args.force)? {
+            return Err(conflict_error(&[(output_path.as_str(), conflict)]));
+        }
+        if !args.json && !quiet {
+            println!("Saved {} layout to {}", backend.language(), output_path);
+        }
+        return Ok((vec![PathBuf::from(output_path)], Vec::new()));
+    }
+
+    // With --out-dir, each screen gets its own file, plus App.swift with --app
+    if let Some(dir) = &args.out_dir {
+        fs::create_dir_all(dir).map_err(|e| format!("Failed to create output directory '{}': {}", dir.display(), e))?;
+        // The files of screens that are up to date are left as they are
+        let kept: Vec<&str> = view_files
+            .iter()
+            .zip(&built)
+            .filter(|(_, (_, up_to_date))| *up_to_date)
+            .flat_map(|(files, _)| files.iter().map(String::as_str))
+            .collect();
+        let (mut saved, mut conflicts) = (Vec::new(), Vec::new());
+        for file in files.iter().filter(|file| !kept.contains(&file.name.as_str())) {
+            match save(&dir.join(&file.name), &file.code, args.force)? {
+                Some(conflict) => conflicts.push((file.name.as_str(), conflict)),
+                None => saved.push(file.name.as_str()),
+            }
+        }
+        if !args.json && !quiet && !saved.is_empty() {
+            println!("Saved {} to {}", saved.join(", "), dir.display());
+        }
+        if !args.json && !quiet && !kept.is_empty() {
+            println!("Up to date: {}", kept.join(", "));
+        }
+        // A screen is recorded as built once all of its files are saved, and not by a run cut
+        // short, whose layouts are only the best found in the time it had
+        if let Some(mut manifest) = manifest.take().filter(|_| !budget.cancel.is_cancelled()) {
+            let previous_screens = std::mem::take(&mut manifest.screens);
+            for (((name, ir, _), files), (inputs, up_to_date)) in views.iter().zip(&view_files).zip(built) {
+                let view_name = render::view_name(*name);
+                let entry = match up_to_date {
+                    true => previous_screens.get(&view_name).cloned(),
+                    false if files.iter().all(|file| saved.contains(&file.as_str())) => {
+                        let files = files.iter().map(|file| (file.clone(), build::file_hash(&dir.join(file)))).collect();
+                        Some(build::Entry { inputs, ir: Some(ir.clone()), files })
+                    }
+                    false => None,
+                };
+                manifest.screens.extend(entry.map(|entry| (view_name, entry)));
+            }
+            if let Err(e) = manifest.save(dir) {
+                warn!("{}", e);
+            }
+        }
+        if let Some(package) = &package {
+            let views: Vec<String> = views.iter().map(|(name, _, _)| render::view_name(*name)).collect();
+            let created = scaffold(package, &views, config.platform())?;
+            if !args.json && !quiet && !created.is_empty() {
+                println!("Created {} in {}", created.join(", "), package.dir.display());
+            }
+        }
+        if !conflicts.is_empty() {
+            return Err(conflict_error(&conflicts));
+        }
+        return Ok((saved.iter().map(|name| dir.join(name)).collect(), kept.iter().map(|name| dir.join(name)).collect()));
+    }
+
+    Ok((Vec::new(), Vec::new()))
+}
+
+// Writes the package's manifest and placeholder tests, unless they exist: they are the
+// team's to edit. Returns the files created, relative to the package.
+fn scaffold(package: &Package, views: &[String], platform: Platform) -> Result<Vec<String>, CliError> {
+    let mut created = Vec::new();
+    for (path, contents) in [(package.dir.join("Package.swift"), package.manifest(platform)), (package.tests_file(), package.tests(views))] {
+        if path.exists() {
+            continue;
+        }
+        let parent = path.parent().expect("package files are in the package");
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        fs::write(&path, contents).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+        created.push(path.strip_prefix(&package.dir).unwrap_or(&path).display().to_string());
+    }
+    Ok(created)
+}
+
+// Answers an editor's requests on stdin until it exits, with the config and components a
+// synthesis from the current directory would use
+fn run_lsp(args: &SynthArgs) -> Result<(), CliError> {
+    let cwd = current_dir()?;
+    let config = Config::resolve(args.config.as_deref(), &cwd)?;
+    let components = load_components(args.components.as_deref(), &config, &cwd)?;
+    let mut server = lsp::Server::new(config, components);
+    let shut_down = lsp::serve(&mut server, &mut io::stdin().lock(), &mut io::stdout())
+        .map_err(|e| format!("Failed to talk to the editor: {}", e))?;
+    // An exit without a shutdown request first is an error, as the protocol specifies
+    if shut_down { Ok(()) } else { Err(CliError::Reported) }
+}
+
+// Regenerates the views into `dir` each time the spec is saved, until interrupted. Each save
+// rewrites the files, which makes Xcode refresh the previews of the ones open in the canvas.
+fn run_serve(spec: String, dir: PathBuf, interval: Duration, mut args: SynthArgs) -> Result<(), CliError> {
+    if spec == "-" {
+        return Err("serve watches a spec file; it cannot read stdin".into());
+    }
+    if args.output.is_some() || args.out_dir.is_some() || args.emit_package.is_some() || args.refine {
+        return Err("serve writes into the --xcode folder; it cannot be combined with --output, --out-dir, --emit-package or --refine".into());
+    }
+    args.examples_file = Some(spec.clone());
+    args.out_dir = Some(dir.clone());
+    args.quiet = true;
+    args.previews = true;
+    println!("Watching {} and writing the views to {} (Ctrl-C to stop)", spec, dir.display());
+
+    // The length catches edits within the modification time's resolution
+    let stamp = || fs::metadata(&spec).and_then(|metadata| Ok((metadata.modified()?, metadata.len())));
+    stamp().map_err(|e| format!("Failed to read '{}': {}", spec, e))?;
+    let (mut last, mut missing) = (None, false);
+    let mut previous = Previous::default();
+    loop {
+        if interrupt().is_cancelled() {
+            println!("Stopped watching {}", spec);
+            return Ok(());
+        }
+        match stamp() {
+            Ok(stamp) if last != Some(stamp) => {
+                (last, missing) = (Some(stamp), false);
+                let start = Instant::now();
+                // A spec with mistakes is reported, and the last good views kept, until it is fixed
+                match run_synth(args.clone(), Some(&mut previous)) {
+                    Ok((saved, _)) => {
+                        let names: Vec<String> = saved.iter().filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned())).collect();
+                        match names.is_empty() {
+                            true => println!("Every screen is up to date ({:.2?})", start.elapsed()),
+                            false => println!("Updated {} in {:.2?}", names.join(", "), start.elapsed()),
+                        }
+                    }
+                    Err(CliError::Message(message)) => error!("{}", message),
+                    Err(CliError::Reported) => {}
+                }
+            }
+            Ok(_) => {}
+            // Editors that save by replacing the file briefly remove it
+            Err(e) if !missing => {
+                warn!("cannot read {}: {}; waiting for it to come back", spec, e);
+                missing = true;
+            }
+            Err(_) => {}
+        }
+        std::thread::sleep(interval);
+    }
+}
+
+// Synthesizes into the folder of an Xcode project's group, or a package target's sources,
+// and registers the new files with the project
+fn run_xcode_add(project: Option<PathBuf>, package: Option<PathBuf>, group: &str, target: Option<&str>, mut args: SynthArgs) -> Result<(), CliError> {
+    if args.output.is_some() || args.out_dir.is_some() || args.emit_package.is_some() {
+        return Err("xcode add writes into the project's group; it cannot be combined with --output, --out-dir or --emit-package".into());
+    }
+    let config = Config::resolve(args.config.as_deref(), &current_dir()?)?;
+    let registry = Registry::with_plugins(&config.plugin_files())?;
+    let backend = registry.resolve(args.target.as_deref().or(config.target.as_deref()).unwrap_or("swiftui"))?;
+    if !backend.is_swift() {
+        return Err(format!("xcode add adds Swift files to the project; it cannot be used with --target {}", backend.name()).into());
+    }
+    let quiet = args.quiet || args.json;
+    let Some(xcodeproj) = project else {
+        // SwiftPM compiles every file under a target's directory
+        let package = package.expect("clap requires --project or --package");
+        let sources = xcode::package_sources(&package, target)?;
+        args.out_dir = Some(xcode::group_names(group).fold(sources, |dir, name| dir.join(name)));
+        run_synth(args, None)?;
+        return Ok(());
+    };
+    let mut xcode_project = xcode::Project::load(&xcodeproj)?;
+    let location = xcode_project.locate(group)?;
+    let root = xcodeproj.parent().filter(|dir| !dir.as_os_str().is_empty()).unwrap_or(Path::new("."));
+    args.out_dir = Some(root.join(&location.dir));
+    let (saved, kept) = run_synth(args, None)?;
+    if location.synchronized {
+        if !quiet {
+            println!("{} is a synchronized folder; Xcode picks up the files by itself", group);
+        }
+        return Ok(());
+    }
+
+    // Only Swift sources are compiled; the project is written back only if it changed
+    let names: Vec<String> = saved.iter().chain(&kept).filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned())).collect();
+    let names: Vec<&str> = names.iter().map(String::as_str).filter(|name| name.ends_with(".swift")).collect();
+    let added = xcode_project.add_files(group, &names, target)?;
+    if !added.files.is_empty() {
+        let path = xcodeproj.join("project.pbxproj");
+        fs::write(&path, xcode_project.text()).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    }
+    if !quiet {
+        match added.files.is_empty() {
+            true => println!("{} already in group {} of target {}", names.join(", "), group, added.target),
+            false => println!("Added {} to group {} of target {} in {}", added.files.join(", "), group, added.target, xcodeproj.display()),
+        }
+    }
+    Ok(())
+}
+
+// Writes `code` as the generated section of the file at `path`, keeping the code around it.
+// The file is left alone, and the conflict returned, if it was edited where it should not be.
+fn save(path: &Path, code: &str, force: bool) -> Result<Option<Conflict>, CliError> {
+    let existing = match fs::read_to_string(path) {
+        Ok(existing) => Some(existing),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(format!("Failed to read '{}': {}", path.display(), e).into()),
+    };
+    let contents = match merge::merge_with(existing.as_deref(), code, force, Syntax::for_path(path)) {
+        Ok(contents) => contents,
+        Err(conflict) => return Ok(Some(conflict)),
+    };
+    fs::write(path, contents).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+    Ok(None)
+}
+
+fn conflict_error(conflicts: &[(&str, Conflict)]) -> CliError {
+    let files: Vec<String> = conflicts.iter().map(|(name, conflict)| format!("'{}' ({})", name, conflict)).collect();
+    format!(
+        "Not overwriting {}; move hand edits outside the swiftui-synth:begin/end markers, or pass --force to replace the generated code",
+        files.join(", ")
+    )
+    .into()
+}
+
+// Asks what is wrong with the layout just shown. Returns `None` once the user accepts it.
+fn ask_feedback() -> Result<Option<Constraint>, CliError> {
+    loop {
+        eprint!("\nWhat should change? (e.g. `button should be at the top`; press Enter to accept) ");
+        let mut line = String::new();
+        let read = io::stdin().read_line(&mut line).map_err(|e| format!("Failed to read feedback: {}", e))?;
+        if read == 0 || line.trim().is_empty() {
+            return Ok(None);
+        }
+        match line.parse() {
+            Ok(constraint) => return Ok(Some(constraint)),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+}
+
+// Lets the user pick between the cheapest layout and its alternatives, previewed on the
+// first example's screen. Returns the chosen layout and its code.
+fn choose(
+    best: &IR,
+    best_code: String,
+    alternatives: &[IR],
+    alternative_codes: Vec<String>,
+    examples: &[Example],
+    metrics: &Metrics,
+) -> Result<(IR, String), CliError> {
+    let screen = examples.first().and_then(layout::screen_size).unwrap_or(Size::new(390.0, 844.0));
+    let safe_area = match examples.first() {
+        Some(example) if !layout::ignores_safe_area(examples) => layout::safe_area(example),
+        _ => Insets::default(),
+    };
+    let layouts: Vec<&IR> = std::iter::once(best).chain(alternatives).collect();
+    let candidates: Vec<Candidate> = std::iter::once(best_code)
+        .chain(alternative_codes)
+        .zip(&layouts)
+        .map(|(code, ir)| Candidate { code, preview: preview::preview(ir, screen, safe_area, metrics, 32) })
+        .collect();
+    let label = examples.first().and_then(layout::describe_screen).unwrap_or_else(|| format!("{}x{}", screen.width, screen.height));
+    let index = picker::pick(&candidates, &label)
+        .map_err(|e| format!("Interactive picker failed: {}", e))?
+        .ok_or("No layout selected")?;
+    Ok((layouts[index].clone(), candidates[index].code.clone()))
+}
+
+// Loads a layout from disk: `.swift` files go through the reverse parser,
+// anything else is treated as an example spec and synthesized.
+fn load_layout(path: &str, components: &ComponentLibrary, platform: Platform) -> Result<IR, CliError> {
+    let contents = fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+    if Path::new(path).extension().is_some_and(|ext| ext == "swift") {
+        Ok(input::swift::parse_swiftui(&contents)
+            .map_err(|e| format!("Failed to parse Swift file '{}': {}", path, e))?)
+    } else {
+        let options = ParseOptions { components: components.clone(), strict: false, platform };
+        let examples = input::parser::parse_examples_all(&contents, &options)
+            .map_err(|errors| report_diagnostics(&errors, &contents, path, false))?;
+        let ir = synthesis::swiftui::synthesize_layout(examples)
+            .ok_or_else(|| format!("No matching layout found for the examples in '{}'", path))?;
+        Ok(components.substitute(&ir))
     }
+}
 
+fn run_diff(old_path: &str, new_path: &str) -> Result<(), CliError> {
+    let cwd = current_dir()?;
+    let config = Config::resolve(None, &cwd)?;
+    let components = load_components(None, &config, &cwd)?;
+    let old = load_layout(old_path, &components, config.platform())?;
+    let new = load_layout(new_path, &components, config.platform())?;
+    let changes = diff::diff(&old, &new);
+    if changes.is_empty() {
+        println!("No structural changes");
+    }
+    for change in changes {
+        println!("{}", change);
+    }
     Ok(())
 }
+
+// Lays out a SwiftUI file at each of `sizes` and prints where its views land as a spec
+fn run_examples(from: &str, sizes: &[(f64, f64)]) -> Result<(), CliError> {
+    let cwd = current_dir()?;
+    let config = Config::resolve(None, &cwd)?;
+    let components = load_components(None, &config, &cwd)?;
+    let mut metrics = config.metrics();
+    components.apply_metrics(&mut metrics);
+
+    let code = fs::read_to_string(from).map_err(|e| format!("Failed to read '{}': {}", from, e))?;
+    let ir = input::swift::parse_swiftui(&code).map_err(|e| format!("Failed to parse Swift file '{}': {}", from, e))?;
+    // Role components in the code are written as the built-in elements they stand for
+    let ir = components.restore(&ir);
+    write_stdout(&examples::generate(&ir, examples::view_name(&code), sizes, &metrics))
+}
+
+// Prints the calibration harness, or fits the layout constants to the frames it observed and
+// prints them as config
+fn run_calibrate(observations: Option<String>, harness: bool) -> Result<(), CliError> {
+    if harness {
+        return write_stdout(&calibrate::harness()?);
+    }
+    let path = observations.unwrap_or_else(|| "-".to_string());
+    let json = match path.as_str() {
+        "-" => read_stdin()?,
+        path => fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path, e))?,
+    };
+    let observations = calibrate::parse_observations(&json)?;
+    let config = Config::resolve(None, &current_dir()?)?;
+    let fit = calibrate::fit(&observations, &config.metrics());
+    let frames: usize = observations.iter().map(|observation| observation.frames.len()).sum();
+    eprintln!(
+        "Fitted to {} frames of {} samples: the model is now {:.1}pt from them on average, from {:.1}pt",
+        frames,
+        observations.len(),
+        fit.after,
+        fit.before
+    );
+    write_stdout(&fit.config())
+}
+
+// Synthesizes every benchmark of `dir` and prints how each went, with the solve rate
+fn run_bench(dir: &Path, strategy: Option<String>, json: bool) -> Result<(), CliError> {
+    let cwd = current_dir()?;
+    let config = Config::resolve(None, &cwd)?;
+    let components = load_components(None, &config, &cwd)?;
+    let mut metrics = config.metrics();
+    components.apply_metrics(&mut metrics);
+
+    let benchmarks = bench::load(dir)?;
+    let options = ParseOptions { components, strict: false, platform: config.platform() };
+    let strategy_name = strategy.as_deref().or(config.synthesis.strategy.as_deref()).unwrap_or(strategy::STRATEGIES[0]);
+    let strategy = strategy::by_name(strategy_name, metrics, config.cost.clone())?;
+    let budget = config.budget();
+    let runs: Vec<bench::Run> = benchmarks.iter().map(|benchmark| bench::run(benchmark, strategy.as_ref(), &budget, &options)).collect();
+    if json {
+        let solved = runs.iter().filter(|run| run.status == bench::Status::Solved).count();
+        let report = serde_json::json!({ "strategy": strategy_name, "solved": solved, "total": runs.len(), "benchmarks": runs });
+        write_stdout(&serde_json::to_string_pretty(&report).expect("bench report is serializable"))
+    } else {
+        write_stdout(&bench::report(&runs))
+    }
+}
+
+// Fits a ranking model to a feedback log and prints or saves it as JSON
+fn run_train_ranker(path: &str, output: Option<PathBuf>) -> Result<(), CliError> {
+    let log = match path {
+        "-" => read_stdin()?,
+        path => fs::read_to_string(path).map_err(|e| format!("Failed to read feedback log '{}': {}", path, e))?,
+    };
+    let log = ranker::parse_log(&log).map_err(|e| format!("Invalid feedback log '{}': {}", path, e))?;
+    let model = Ranker::train(&log)?;
+    let json = serde_json::to_string_pretty(&model).expect("ranker is serializable");
+    eprintln!(
+        "Trained on {} layouts ({} picked); the model classifies {:.0}% of them correctly",
+        log.len(),
+        log.iter().filter(|line| line.accepted).count(),
+        model.accuracy(&log) * 100.0
+    );
+    match output {
+        Some(path) => fs::write(&path, json + "\n").map_err(|e| format!("Failed to write '{}': {}", path.display(), e).into()),
+        None => write_stdout(&json),
+    }
+}
+
+// Delta-debugs the spec down to the fewest screens, examples, elements and frames with which
+// synthesis still fails, or is still slower than `slower_than`, and prints or saves it
+fn run_minimize(spec: String, slower_than: Option<Duration>, strategy: Option<String>, output: Option<PathBuf>) -> Result<(), CliError> {
+    let cwd = current_dir()?;
+    let config = Config::resolve(None, &cwd)?;
+    let components = load_components(None, &config, &cwd)?;
+    let mut metrics = config.metrics();
+    components.apply_metrics(&mut metrics);
+
+    let (examples_str, origin) = read_examples(None, Some(spec))?;
+    let options = ParseOptions { components, strict: false, platform: config.platform() };
+    input::parser::parse_spec_with(&examples_str, &options).map_err(|errors| report_diagnostics(&errors, &examples_str, &origin, false))?;
+    let strategy_name = strategy.as_deref().or(config.synthesis.strategy.as_deref()).unwrap_or(strategy::STRATEGIES[0]);
+    let strategy = strategy::by_name(strategy_name, metrics.clone(), config.cost.clone())?;
+    let budget = config.budget();
+    // The problems synthesis leaves in `text`, without their example numbers, and how long it
+    // took; `None` when the text no longer parses
+    let synthesize = |text: &str| {
+        let Spec { screens, .. } = input::parser::parse_spec_with(text, &options).ok()?;
+        let start = Instant::now();
+        let problems: Vec<String> = screens
+            .iter()
+            .flat_map(|screen| match strategy.synthesize(&screen.examples, &budget) {
+                Some(outcome) => synthesis::verify::verify(&outcome.ir, &screen.examples, &metrics).into_iter().map(|v| v.message).collect(),
+                None => vec!["no layout found".to_string()],
+            })
+            .collect();
+        Some((problems, start.elapsed()))
+    };
+    let spec = minimize::SpecText::parse(&examples_str).map_err(|e| format!("Failed to split '{}' into examples: {}", origin, e))?;
+    let (original, _) = synthesize(&spec.to_string()).ok_or_else(|| format!("Failed to split '{}' into examples", origin))?;
+    // A smaller spec reproduces the failure when it fails with one of the same problems, not
+    // just any; slowness is reproduced by time alone
+    let reproduces = |text: &str| match synthesize(text) {
+        Some((_, elapsed)) if slower_than.is_some() => slower_than.is_some_and(|limit| elapsed >= limit),
+        Some((problems, _)) => problems.iter().any(|problem| original.contains(problem)),
+        None => false,
+    };
+    if !reproduces(&spec.to_string()) {
+        return Err(match slower_than {
+            Some(limit) => format!("Synthesizing '{}' takes less than {:?}; there is nothing to minimize", origin, limit),
+            None => format!("Synthesis satisfies every example of '{}'; there is nothing to minimize", origin),
+        }
+        .into());
+    }
+    let (minimal, tries) = minimize::minimize(&spec, reproduces);
+    eprintln!(
+        "Reduced {} examples with {} elements to {} with {} after trying {} specs",
+        spec.examples(),
+        spec.elements(),
+        minimal.examples(),
+        minimal.elements(),
+        tries
+    );
+    match output {
+        Some(path) => fs::write(&path, minimal.to_string()).map_err(|e| format!("Failed to write '{}': {}", path.display(), e).into()),
+        None => write_stdout(minimal.to_string().trim_end()),
+    }
+}
+
+// Synthesizes each screen of the spec and reports, per example, how far each view with a
+// stated frame is from it; fails if any is further than `threshold` points
+fn run_fidelity(examples: Option<String>, spec: Option<String>, threshold: f64, strategy: Option<String>) -> Result<(), CliError> {
+    let cwd = current_dir()?;
+    let config = Config::resolve(None, &cwd)?;
+    let components = load_components(None, &config, &cwd)?;
+    let mut metrics = config.metrics();
+    components.apply_metrics(&mut metrics);
+
+    let (examples_str, origin) = read_examples(examples, spec)?;
+    let options = ParseOptions { components, strict: false, platform: config.platform() };
+    let Spec { screens, .. } = input::parser::parse_spec_with(&examples_str, &options)
+        .map_err(|errors| report_diagnostics(&errors, &examples_str, &origin, false))?;
+    let strategy_name = strategy.as_deref().or(config.synthesis.strategy.as_deref()).unwrap_or(strategy::STRATEGIES[0]);
+    let strategy = strategy::by_name(strategy_name, metrics.clone(), config.cost.clone())?;
+
+    let mut failed = 0;
+    for (index, screen) in screens.iter().enumerate() {
+        let view = render::view_name(screen.name.as_deref());
+        let outcome = strategy.synthesize(&screen.examples, &config.budget()).ok_or("No matching layout found for the given examples")?;
+        // Frames are stated for the built-in views, which are what the layout was ranked with
+        let measurements = synthesis::fidelity::measure(&outcome.ir, &screen.examples, &metrics);
+        if index > 0 {
+            println!();
+        }
+        if measurements.is_empty() {
+            println!("{}: no stated frames to compare", view);
+            continue;
+        }
+        println!("{}:\n{}", view, synthesis::fidelity::report(&measurements, &screen.examples, threshold));
+        failed += measurements.iter().filter(|m| !m.passes(threshold)).count();
+    }
+    if failed > 0 {
+        eprintln!("{} of the views with a stated frame are more than {}pt from it", failed, threshold);
+        return Err(CliError::Reported);
+    }
+    Ok(())
+}
+
+// Audits each screen's synthesized layout, or the layout of existing code, for accessibility;
+// fails if any finding is an error
+fn run_audit_a11y(
+    examples: Option<String>,
+    spec: Option<String>,
+    against: Option<String>,
+    tokens: Vec<PathBuf>,
+    strategy: Option<String>,
+) -> Result<(), CliError> {
+    let cwd = current_dir()?;
+    let config = Config::resolve(None, &cwd)?;
+    let components = load_components(None, &config, &cwd)?;
+    let mut metrics = config.metrics();
+    components.apply_metrics(&mut metrics);
+
+    let token_files = if tokens.is_empty() { config.token_files() } else { tokens };
+    let mut colors = load_colors(&token_files)?;
+
+    let (examples_str, origin) = read_examples(examples, spec)?;
+    let options = ParseOptions { components, strict: false, platform: config.platform() };
+    let Spec { screens, .. } = input::parser::parse_spec_with(&examples_str, &options)
+        .map_err(|errors| report_diagnostics(&errors, &examples_str, &origin, false))?;
+    let strategy_name = strategy.as_deref().or(config.synthesis.strategy.as_deref()).unwrap_or(strategy::STRATEGIES[0]);
+    let strategy = strategy::by_name(strategy_name, metrics.clone(), config.cost.clone())?;
+
+    let mut layouts = Vec::new();
+    match &against {
+        Some(path) => {
+            let code = fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path, e))?;
+            let ir = input::swift::parse_swiftui(&code).map_err(|e| format!("Failed to parse Swift file '{}': {}", path, e))?;
+            let examples: Vec<Example> = screens.into_iter().flat_map(|screen| screen.examples).collect();
+            layouts.push((path.clone(), ir, examples));
+        }
+        None => {
+            for screen in screens {
+                let outcome = strategy.synthesize(&screen.examples, &config.budget()).ok_or("No matching layout found for the given examples")?;
+                layouts.push((render::view_name(screen.name.as_deref()), outcome.ir, screen.examples));
+            }
+        }
+    }
+
+    let mut errors = 0;
+    for (index, (name, ir, examples)) in layouts.iter().enumerate() {
+        let findings = synthesis::a11y::audit(ir, examples, &metrics, &colors, &config.lint_options());
+        if index > 0 {
+            println!();
+        }
+        println!("{}:\n{}", name, synthesis::a11y::report(&findings));
+        errors += findings.iter().filter(|finding| finding.severity == Severity::Error).count();
+        // Token colors are not per view, so are reported with the first only
+        colors.clear();
+    }
+    if errors > 0 {
+        eprintln!("The audit found {} accessibility error{}", errors, if errors == 1 { "" } else { "s" });
+        return Err(CliError::Reported);
+    }
+    Ok(())
+}
+
+// The metrics the code is written with: when snapping, the padding and stack spacing snapped
+// to the spacing scale, which is the config's, else the steps of the spacing tokens, else the
+// default one. Values too far from any step are kept, with a warning.
+fn code_metrics(metrics: &Metrics, config: &Config, snap: bool) -> Result<Metrics, CliError> {
+    let mut metrics = metrics.clone();
+    if !snap {
+        return Ok(metrics);
+    }
+    let mut tokens = Vec::new();
+    if config.spacing.scale.is_empty() {
+        for file in config.token_files() {
+            let json = fs::read_to_string(&file).map_err(|e| format!("Failed to read token file '{}': {}", file.display(), e))?;
+            let spacing = spacing::load_spacing(&json).map_err(|e| format!("Failed to parse token file '{}': {}", file.display(), e))?;
+            tokens.extend(spacing.into_iter().map(|(_, points)| points));
+        }
+    }
+    let scale = config.spacing_scale(&tokens)?;
+    for miss in spacing::snap_metrics(&mut metrics, &scale) {
+        warn!("{}", miss);
+    }
+    Ok(metrics)
+}
+
+// The color tokens of the design token files, in order
+fn load_colors(files: &[PathBuf]) -> Result<Vec<(String, Rgb)>, CliError> {
+    let mut colors = Vec::new();
+    for file in files {
+        let json = fs::read_to_string(file).map_err(|e| format!("Failed to read token file '{}': {}", file.display(), e))?;
+        colors.extend(synthesis::a11y::load_colors(&json).map_err(|e| format!("Failed to parse token file '{}': {}", file.display(), e))?);
+    }
+    Ok(colors)
+}
+
+fn run_check(examples: Option<String>, examples_file: Option<String>, against: &str) -> Result<(), CliError> {
+    let cwd = current_dir()?;
+    let config = Config::resolve(None, &cwd)?;
+    let components = load_components(None, &config, &cwd)?;
+    let mut metrics = config.metrics();
+    components.apply_metrics(&mut metrics);
+
+    let (examples_str, origin) = read_examples(examples, examples_file)?;
+    let options = ParseOptions { components, strict: false, platform: config.platform() };
+    let examples = input::parser::parse_examples_all(&examples_str, &options)
+        .map_err(|errors| report_diagnostics(&errors, &examples_str, &origin, false))?;
+    let code = fs::read_to_string(against).map_err(|e| format!("Failed to read '{}': {}", against, e))?;
+    let ir = input::swift::parse_swiftui(&code)
+        .map_err(|e| format!("Failed to parse Swift file '{}': {}", against, e))?;
+
+    // Code generated from other examples may still satisfy these, but is worth regenerating
+    if let Some(origin) = Provenance::read(&code).filter(|origin| origin.examples != provenance::fingerprint(&examples)) {
+        eprintln!(
+            "Warning: {} was generated from different examples (fingerprint {}, these are {}); regenerate it with `{}`",
+            against,
+            origin.examples,
+            provenance::fingerprint(&examples),
+            origin.command
+        );
+    }
+
+    // Role components in the code stand for the built-in elements named in the examples
+    let ir = options.components.restore(&ir);
+    let violations = synthesis::verify::verify(&ir, &examples, &metrics);
+    if violations.is_empty() {
+        println!("{} satisfies all {} examples", against, examples.len());
+        return Ok(());
+    }
+    for violation in &violations {
+        println!("{}", violation);
+    }
+    eprintln!("{} does not satisfy the examples ({} problems)", against, violations.len());
+    Err(CliError::Reported)
+}
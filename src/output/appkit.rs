@@ -0,0 +1,413 @@
+// File: src/output/appkit.rs
+// AppKit renderer (`--target appkit`), for macOS apps not written in SwiftUI: each screen
+// becomes an NSViewController whose view is built in code from nested NSStackViews.
+use crate::ast::ir::modifier::{component, Modifier};
+use crate::ast::IR;
+use crate::layout::image_sizing::ContentMode;
+use crate::layout::text_style::{TextAlignment, Wrapping};
+use crate::layout::Metrics;
+use crate::output::render::{accessibility_identifier, string_literal, RenderOptions};
+use crate::synthesis::verify::describe;
+
+/// Name of the view controller generated for a view, e.g. `LoginView` becomes
+/// `LoginViewController`.
+pub fn controller_name(view_name: &str) -> String {
+    format!("{}Controller", view_name)
+}
+
+/// `NSFont.TextStyle` closest to a SwiftUI text style such as `title`; `.body` if none is.
+pub fn text_style(swiftui: &str) -> &'static str {
+    match swiftui {
+        "largeTitle" => "largeTitle",
+        "title" => "title1",
+        "title2" => "title2",
+        "title3" => "title3",
+        "headline" => "headline",
+        "subheadline" => "subheadline",
+        "callout" => "callout",
+        "footnote" => "footnote",
+        "caption" => "caption1",
+        "caption2" => "caption2",
+        _ => "body",
+    }
+}
+
+// Builds the statements creating each view, children before the stacks holding them
+struct Builder<'a> {
+    options: &'a RenderOptions,
+    metrics: &'a Metrics,
+    lines: Vec<String>,
+    names: Vec<String>,
+    identifiers: Vec<String>,
+    // Each action method with the type of what sends it
+    actions: Vec<(String, &'static str)>,
+}
+
+impl Builder<'_> {
+    // A variable name for a view showing `content`, e.g. `signInButton`, unique in the method
+    fn name(&mut self, content: &str, suffix: &str) -> String {
+        let words: Vec<String> = content
+            .split(|ch: char| !ch.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .enumerate()
+            .map(|(index, word)| {
+                let word = word.to_lowercase();
+                match index {
+                    0 => word,
+                    _ => word[..1].to_uppercase() + &word[1..],
+                }
+            })
+            .collect();
+        let stem = match words.concat() {
+            stem if stem.starts_with(|ch: char| ch.is_ascii_alphabetic()) => format!("{}{}", stem, suffix),
+            _ => suffix[..1].to_lowercase() + &suffix[1..],
+        };
+        let name = (1..)
+            .map(|n| if n == 1 { stem.clone() } else { format!("{}{}", stem, n) })
+            .find(|name| !self.names.contains(name))
+            .expect("some number is free");
+        self.names.push(name.clone());
+        name
+    }
+
+    // A label showing `text` in the `NSFont.TextStyle` named `style`
+    fn label(&mut self, text: &str, style: &str) -> String {
+        let name = self.name(text, "Label");
+        self.lines.push(format!("let {} = NSTextField(labelWithString: {})", name, string_literal(text)));
+        self.lines.push(format!("{}.font = .preferredFont(forTextStyle: .{})", name, style));
+        name
+    }
+
+    fn view(&mut self, ir: &IR) -> String {
+        let identifier = match self.options.accessibility_identifiers {
+            true => accessibility_identifier(ir, &mut self.identifiers),
+            false => None,
+        };
+        let name = match ir {
+            IR::VStack(children) | IR::HStack(children) => {
+                let vertical = matches!(ir, IR::VStack(_));
+                let views: Vec<String> = children.iter().map(|child| self.view(child)).collect();
+                let name = self.name("", "Stack");
+                self.lines.push(format!("let {} = NSStackView(views: [{}])", name, views.join(", ")));
+                let (orientation, alignment) = if vertical { ("vertical", "centerX") } else { ("horizontal", "centerY") };
+                self.lines.push(format!("{}.orientation = .{}", name, orientation));
+                self.lines.push(format!("{}.alignment = .{}", name, alignment));
+                self.lines.push(format!("{}.spacing = {}", name, self.metrics.stack_spacing));
+                if self.options.padding {
+                    self.lines.push(format!(
+                        "{name}.edgeInsets = NSEdgeInsets(top: {inset}, left: {inset}, bottom: {inset}, right: {inset})",
+                        name = name,
+                        inset = self.metrics.padding
+                    ));
+                }
+                name
+            }
+            IR::Text(text) => {
+                let name = self.label(text, text_style(self.options.text_style(text)));
+                match self.options.text_wrapping.get(text) {
+                    Some(Wrapping::Multiline(alignment)) => {
+                        let alignment = match alignment {
+                            TextAlignment::Leading => "left",
+                            TextAlignment::Center => "center",
+                            TextAlignment::Trailing => "right",
+                        };
+                        self.lines.push(format!("{}.maximumNumberOfLines = 0", name));
+                        self.lines.push(format!("{}.alignment = .{}", name, alignment));
+                    }
+                    Some(Wrapping::SingleLine) => {
+                        self.lines.push(format!("{}.maximumNumberOfLines = 1", name));
+                        self.lines.push(format!("{}.lineBreakMode = .byTruncatingTail", name));
+                    }
+                    None => {}
+                }
+                name
+            }
+            IR::Button(title) => {
+                let name = self.name(title, "Button");
+                let action = format!("{}Clicked", name);
+                self.lines.push(format!("let {} = NSButton(title: {}, target: self, action: #selector({}))", name, string_literal(title), action));
+                self.actions.push((action, "NSButton"));
+                name
+            }
+            IR::Image(image) => {
+                let name = self.name(image, "ImageView");
+                self.lines.push(format!("let {} = NSImageView(image: NSImage(named: {}) ?? NSImage())", name, string_literal(image)));
+                if let Some(sizing) = self.options.image_sizing.get(image) {
+                    // NSImageView only scales to fit; its layer fills the frame instead
+                    match sizing.mode {
+                        ContentMode::Fit => self.lines.push(format!("{}.imageScaling = .scaleProportionallyUpOrDown", name)),
+                        ContentMode::Fill => {
+                            self.lines.push(format!("{}.wantsLayer = true", name));
+                            self.lines.push(format!("{}.layer?.contentsGravity = .resizeAspectFill", name));
+                            self.lines.push(format!("{}.layer?.masksToBounds = true", name));
+                        }
+                    }
+                    for (anchor, length) in [("width", sizing.width), ("height", sizing.height)] {
+                        if let Some(length) = length {
+                            self.lines.push(format!("{}.{}Anchor.constraint(equalToConstant: {}).isActive = true", name, anchor, length));
+                        }
+                    }
+                }
+                name
+            }
+            // An empty view that gives way before any other, taking up the space left over
+            IR::Spacer => {
+                let name = self.name("", "Spacer");
+                self.lines.push(format!("let {} = NSView()", name));
+                self.lines.push(format!("{}.setContentHuggingPriority(.init(1), for: .horizontal)", name));
+                self.lines.push(format!("{}.setContentHuggingPriority(.init(1), for: .vertical)", name));
+                name
+            }
+            IR::Custom { name: view, args } => {
+                let name = self.name(view, "");
+                self.lines.push(format!("let {} = {}({})", name, view, args));
+                name
+            }
+        };
+        if let Some(identifier) = identifier {
+            self.lines.push(format!("{}.setAccessibilityIdentifier({})", name, string_literal(&identifier)));
+        }
+        self.decorate(&name, self.options.view_modifiers.get(ir).map_or(&[], Vec::as_slice));
+        name
+    }
+
+    // Draws a view's stated corners, border and shadow with its layer, adds its overlays, and
+    // recognizes its taps and long presses
+    fn decorate(&mut self, name: &str, modifiers: &[Modifier]) {
+        // Animations are left to the app, as AppKit has no declarative form of them, and so are
+        // swipes, which a mouse does not make
+        let drawn = modifiers.iter().any(|modifier| matches!(modifier, Modifier::CornerRadius(_) | Modifier::Border { .. } | Modifier::Shadow { .. } | Modifier::Overlay { .. }));
+        if drawn {
+            self.lines.push(format!("{}.wantsLayer = true", name));
+        }
+        for modifier in modifiers {
+            match modifier {
+                Modifier::CornerRadius(radius) => {
+                    self.lines.push(format!("{}.layer?.cornerRadius = {}", name, radius));
+                    self.lines.push(format!("{}.layer?.masksToBounds = true", name));
+                }
+                Modifier::Border { width, color } => {
+                    let color = match color {
+                        Some(rgb) => format!("NSColor(srgbRed: {}, green: {}, blue: {}, alpha: 1)", component(rgb.r), component(rgb.g), component(rgb.b)),
+                        None => "NSColor.secondaryLabelColor".to_string(),
+                    };
+                    self.lines.push(format!("{}.layer?.borderWidth = {}", name, width));
+                    self.lines.push(format!("{}.layer?.borderColor = {}.cgColor", name, color));
+                }
+                Modifier::Shadow { radius, x, y } => {
+                    let shadow = format!("{}Shadow", name);
+                    self.lines.push(format!("let {} = NSShadow()", shadow));
+                    self.lines.push(format!("{}.shadowBlurRadius = {}", shadow, radius));
+                    // AppKit's y axis points up
+                    self.lines.push(format!("{}.shadowOffset = NSSize(width: {}, height: {})", shadow, x, -y));
+                    self.lines.push(format!("{}.shadow = {}", name, shadow));
+                }
+                Modifier::Animation(_) | Modifier::Transition(_) | Modifier::Swipe { .. } => {}
+                Modifier::Tap(handler) | Modifier::LongPress(handler) => {
+                    let recognizer = match modifier {
+                        Modifier::Tap(_) => "NSClickGestureRecognizer",
+                        _ => "NSPressGestureRecognizer",
+                    };
+                    self.lines.push(format!("{}.addGestureRecognizer({}(target: self, action: #selector({})))", name, recognizer, handler));
+                    // Taps and long presses may share a handler, so it takes either recognizer
+                    if !self.actions.iter().any(|(action, _)| action == handler) {
+                        self.actions.push((handler.clone(), "NSGestureRecognizer"));
+                    }
+                }
+                Modifier::Overlay { alignment, view } => {
+                    // A text is set in the caption style of a badge
+                    let child = match view {
+                        IR::Text(text) => self.label(text, "caption1"),
+                        view => self.view(view),
+                    };
+                    self.lines.push(format!("{}.translatesAutoresizingMaskIntoConstraints = false", child));
+                    self.lines.push(format!("{}.addSubview({})", name, child));
+                    // `topTrailing` pins the child's top and trailing edges to the view's
+                    let lower = alignment.to_lowercase();
+                    let vertical = if lower.starts_with("top") { "top" } else if lower.starts_with("bottom") { "bottom" } else { "centerY" };
+                    let horizontal = if lower.ends_with("leading") { "leading" } else if lower.ends_with("trailing") { "trailing" } else { "centerX" };
+                    self.lines.push(format!(
+                        "NSLayoutConstraint.activate([{child}.{v}Anchor.constraint(equalTo: {name}.{v}Anchor), {child}.{h}Anchor.constraint(equalTo: {name}.{h}Anchor)])",
+                        child = child,
+                        name = name,
+                        v = vertical,
+                        h = horizontal
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// The stated modifiers of `ir`'s views that a controller leaves out, one message each:
+/// animations and transitions, as AppKit has no declarative form of them, and swipes, which a
+/// mouse does not make.
+pub fn dropped_modifiers(ir: &IR, options: &RenderOptions) -> Vec<String> {
+    let mut dropped = Vec::new();
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => {
+            for child in children {
+                dropped.extend(dropped_modifiers(child, options));
+            }
+        }
+        view => {
+            for modifier in options.view_modifiers.get(view).map_or(&[][..], Vec::as_slice) {
+                let what = match modifier {
+                    Modifier::Animation(animation) => format!("the {} animation", animation),
+                    Modifier::Transition(transition) => format!("the {} transition", transition),
+                    Modifier::Swipe { direction, handler } => format!("the {} swipe calling {}()", direction, handler),
+                    _ => continue,
+                };
+                dropped.push(format!("AppKit code leaves out {} of {}", what, describe(view)));
+            }
+        }
+    }
+    dropped
+}
+
+/// Renders `ir` as an `NSViewController` subclass named `name` whose `loadView()` builds the
+/// layout, with an empty action method for each button to fill in. A root view that is not a
+/// stack is put in one, so the controller's view always lays its content out.
+pub fn render_view_controller(name: &str, ir: &IR, options: &RenderOptions, metrics: &Metrics) -> String {
+    let root = match ir {
+        IR::VStack(_) | IR::HStack(_) => ir.clone(),
+        _ => IR::VStack(vec![ir.clone()]),
+    };
+    let mut builder = Builder { options, metrics, lines: Vec::new(), names: Vec::new(), identifiers: Vec::new(), actions: Vec::new() };
+    let view = builder.view(&root);
+    builder.lines.push(format!("view = {}", view));
+
+    let pad = " ".repeat(options.indent_width);
+    let body: Vec<String> = builder.lines.iter().map(|line| format!("{}{}{}", pad, pad, line)).collect();
+    let access = if options.public { "public " } else { "" };
+    let mut code = format!(
+        "{}final class {}: NSViewController {{\n{}{}override func loadView() {{\n{}\n{}}}\n",
+        access,
+        name,
+        pad,
+        access,
+        body.join("\n"),
+        pad
+    );
+    for (action, sender) in &builder.actions {
+        code.push_str(&format!("\n{}@objc private func {}(_ sender: {}) {{\n{}}}\n", pad, action, sender, pad));
+    }
+    code.push('}');
+    code
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dropped_modifiers_are_named() {
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::HStack(vec![IR::Image("bell".to_string())])]);
+        let options = RenderOptions {
+            view_modifiers: [
+                (IR::Text("Hi".to_string()), vec![Modifier::CornerRadius(4.0), Modifier::Animation("spring".to_string()), Modifier::Transition("slide".to_string())]),
+                (IR::Image("bell".to_string()), vec![Modifier::Tap("ring".to_string()), Modifier::Swipe { direction: "left".to_string(), handler: "mute".to_string() }]),
+            ]
+            .into(),
+            ..RenderOptions::default()
+        };
+        assert_eq!(
+            dropped_modifiers(&ir, &options),
+            [
+                "AppKit code leaves out the spring animation of Text \"Hi\"",
+                "AppKit code leaves out the slide transition of Text \"Hi\"",
+                "AppKit code leaves out the left swipe calling mute() of Image \"bell\"",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_view_controller() {
+        let ir = IR::VStack(vec![
+            IR::Text("Welcome back".to_string()),
+            IR::Spacer,
+            IR::HStack(vec![IR::Image("logo".to_string()), IR::Button("Sign in".to_string())]),
+        ]);
+        let code = render_view_controller("LoginViewController", &ir, &RenderOptions::default(), &Metrics::default());
+        assert_eq!(
+            code,
+            "final class LoginViewController: NSViewController {
+    override func loadView() {
+        let welcomeBackLabel = NSTextField(labelWithString: \"Welcome back\")
+        welcomeBackLabel.font = .preferredFont(forTextStyle: .title1)
+        let spacer = NSView()
+        spacer.setContentHuggingPriority(.init(1), for: .horizontal)
+        spacer.setContentHuggingPriority(.init(1), for: .vertical)
+        let logoImageView = NSImageView(image: NSImage(named: \"logo\") ?? NSImage())
+        let signInButton = NSButton(title: \"Sign in\", target: self, action: #selector(signInButtonClicked))
+        let stack = NSStackView(views: [logoImageView, signInButton])
+        stack.orientation = .horizontal
+        stack.alignment = .centerY
+        stack.spacing = 8
+        stack.edgeInsets = NSEdgeInsets(top: 16, left: 16, bottom: 16, right: 16)
+        let stack2 = NSStackView(views: [welcomeBackLabel, spacer, stack])
+        stack2.orientation = .vertical
+        stack2.alignment = .centerX
+        stack2.spacing = 8
+        stack2.edgeInsets = NSEdgeInsets(top: 16, left: 16, bottom: 16, right: 16)
+        view = stack2
+    }
+
+    @objc private func signInButtonClicked(_ sender: NSButton) {
+    }
+}"
+        );
+    }
+
+    #[test]
+    fn test_names_identifiers_and_root_views() {
+        let ir = IR::Button("+".to_string());
+        let options = RenderOptions { padding: false, public: true, accessibility_identifiers: true, ..RenderOptions::default() };
+        let code = render_view_controller("AddViewController", &ir, &options, &Metrics::default());
+        assert!(code.starts_with("public final class AddViewController: NSViewController {\n    public override func loadView() {\n"), "{}", code);
+        assert!(code.contains("let button = NSButton(title: \"+\", target: self, action: #selector(buttonClicked))\n        button.setAccessibilityIdentifier(\"button\")\n"), "{}", code);
+        assert!(code.contains("let stack = NSStackView(views: [button])\n") && !code.contains("edgeInsets"), "{}", code);
+
+        let repeated = IR::HStack(vec![IR::Text("Hi".to_string()), IR::Text("Hi".to_string()), IR::Custom { name: "ChartView".to_string(), args: "data: points".to_string() }]);
+        let code = render_view_controller("C", &repeated, &RenderOptions::default(), &Metrics::default());
+        assert!(code.contains("let hiLabel = ") && code.contains("let hiLabel2 = ") && code.contains("let chartview = ChartView(data: points)"), "{}", code);
+        let wrapping = [("Hi".to_string(), Wrapping::Multiline(TextAlignment::Trailing))].into();
+        let code = render_view_controller("C", &IR::Text("Hi".to_string()), &RenderOptions { text_wrapping: wrapping, ..RenderOptions::default() }, &Metrics::default());
+        assert!(code.contains("hiLabel.maximumNumberOfLines = 0\n        hiLabel.alignment = .right\n"), "{}", code);
+        let sizing = crate::layout::image_sizing::ImageSizing { mode: ContentMode::Fill, width: Some(120.0), fills_width: false, height: Some(80.0) };
+        let options = RenderOptions { image_sizing: [("photo".to_string(), sizing)].into(), ..RenderOptions::default() };
+        let code = render_view_controller("C", &IR::Image("photo".to_string()), &options, &Metrics::default());
+        assert!(
+            code.contains("photoImageView.layer?.masksToBounds = true\n        photoImageView.widthAnchor.constraint(equalToConstant: 120).isActive = true\n        photoImageView.heightAnchor"),
+            "{}",
+            code
+        );
+        let options = RenderOptions { view_modifiers: [(IR::Button("Go".to_string()), vec![Modifier::CornerRadius(8.0), Modifier::Shadow { radius: 4.0, x: 0.0, y: 2.0 }])].into(), ..RenderOptions::default() };
+        let code = render_view_controller("C", &IR::Button("Go".to_string()), &options, &Metrics::default());
+        assert!(code.contains("goButton.layer?.cornerRadius = 8\n        goButton.layer?.masksToBounds = true\n"), "{}", code);
+        assert!(code.contains("goButtonShadow.shadowOffset = NSSize(width: 0, height: -2)\n        goButton.shadow = goButtonShadow\n"), "{}", code);
+        let badge = Modifier::Overlay { alignment: "topTrailing".to_string(), view: IR::Text("3".to_string()) };
+        let options = RenderOptions { view_modifiers: [(IR::Image("bell".to_string()), vec![badge])].into(), ..RenderOptions::default() };
+        let code = render_view_controller("C", &IR::Image("bell".to_string()), &options, &Metrics::default());
+        assert!(code.contains("bellImageView.addSubview(label)\n"), "{}", code);
+        assert_eq!(code.matches("label.font = ").count(), 1, "{}", code);
+        assert!(code.contains("label.font = .preferredFont(forTextStyle: .caption1)\n"), "{}", code);
+        assert!(
+            code.contains("label.topAnchor.constraint(equalTo: bellImageView.topAnchor), label.trailingAnchor.constraint(equalTo: bellImageView.trailingAnchor)"),
+            "{}",
+            code
+        );
+        let gestures = vec![Modifier::Tap("openProfile".to_string()), Modifier::Swipe { direction: "left".to_string(), handler: "archive".to_string() }];
+        let options = RenderOptions {
+            view_modifiers: [(IR::Image("bell".to_string()), gestures), (IR::Text("Me".to_string()), vec![Modifier::LongPress("openProfile".to_string())])].into(),
+            ..RenderOptions::default()
+        };
+        let ir = IR::VStack(vec![IR::Image("bell".to_string()), IR::Text("Me".to_string())]);
+        let code = render_view_controller("C", &ir, &options, &Metrics::default());
+        assert!(code.contains("bellImageView.addGestureRecognizer(NSClickGestureRecognizer(target: self, action: #selector(openProfile)))\n"), "{}", code);
+        assert!(code.contains("meLabel.addGestureRecognizer(NSPressGestureRecognizer(target: self, action: #selector(openProfile)))\n"), "{}", code);
+        assert_eq!(code.matches("@objc private func openProfile(_ sender: NSGestureRecognizer) {\n    }\n").count(), 1, "{}", code);
+        assert!(!code.contains("wantsLayer") && !code.contains("archive"), "{}", code);
+        assert_eq!(text_style("unknown"), "body");
+    }
+}
@@ -0,0 +1,125 @@
+// File: src/output/assets.rs
+// Asset catalog skeleton (`--assets`): an image set for every image the generated code names
+// and a color set for every color token, so the code finds real assets as soon as it compiles.
+use std::fs;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::ast::IR;
+use crate::synthesis::a11y::Rgb;
+
+/// Names of the images `ir` shows, each once, in the order they appear.
+pub fn referenced_images(ir: &IR) -> Vec<String> {
+    fn walk(ir: &IR, out: &mut Vec<String>) {
+        match ir {
+            IR::VStack(children) | IR::HStack(children) => children.iter().for_each(|child| walk(child, out)),
+            IR::Image(name) if !out.contains(name) => out.push(name.clone()),
+            _ => {}
+        }
+    }
+    let mut images = Vec::new();
+    walk(ir, &mut images);
+    images
+}
+
+/// Asset name of a color token: its dotted path in lowerCamelCase, as Xcode would name the
+/// generated `Color` symbol, e.g. `color.text-primary` is `colorTextPrimary`.
+pub fn color_name(token: &str) -> String {
+    let mut name = String::new();
+    for (index, part) in token.split(['.', '-', '_', '/', ' ']).filter(|part| !part.is_empty()).enumerate() {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            match index {
+                0 => name.extend(first.to_lowercase()),
+                _ => name.extend(first.to_uppercase()),
+            }
+            name.push_str(chars.as_str());
+        }
+    }
+    name
+}
+
+fn info() -> Value {
+    json!({ "author": "xcode", "version": 1 })
+}
+
+/// `Contents.json` of an image set with an empty slot per scale, to drop the artwork into.
+pub fn imageset_contents() -> String {
+    let images: Vec<Value> = ["1x", "2x", "3x"].iter().map(|scale| json!({ "idiom": "universal", "scale": scale })).collect();
+    contents(json!({ "images": images, "info": info() }))
+}
+
+/// `Contents.json` of a color set holding `color` for every appearance.
+pub fn colorset_contents(color: &Rgb) -> String {
+    let component = |value: u8| format!("0x{:02X}", value);
+    let components = json!({ "alpha": "1.000", "blue": component(color.b), "green": component(color.g), "red": component(color.r) });
+    contents(json!({
+        "colors": [{ "color": { "color-space": "srgb", "components": components }, "idiom": "universal" }],
+        "info": info(),
+    }))
+}
+
+fn contents(value: Value) -> String {
+    format!("{}\n", serde_json::to_string_pretty(&value).expect("JSON values serialize"))
+}
+
+/// Adds an image set per name of `images` and a color set per token of `colors` to the
+/// catalog at `dir`, creating it if needed. Sets that exist are left alone: they may hold
+/// the real artwork. Returns the folders created, e.g. `logo.imageset`.
+pub fn write_catalog(dir: &Path, images: &[String], colors: &[(String, Rgb)]) -> Result<Vec<String>, String> {
+    let write = |path: &Path, contents: String| {
+        let parent = path.parent().expect("Contents.json is in a folder");
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create '{}': {}", parent.display(), e))?;
+        fs::write(path, contents).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+    };
+    let root = dir.join("Contents.json");
+    if !root.exists() {
+        write(&root, contents(json!({ "info": info() })))?;
+    }
+    let sets = images
+        .iter()
+        .map(|name| (format!("{}.imageset", name), imageset_contents()))
+        .chain(colors.iter().map(|(token, color)| (format!("{}.colorset", color_name(token)), colorset_contents(color))));
+    let mut created = Vec::new();
+    for (folder, contents) in sets {
+        if dir.join(&folder).exists() || created.contains(&folder) {
+            continue;
+        }
+        write(&dir.join(&folder).join("Contents.json"), contents)?;
+        created.push(folder);
+    }
+    Ok(created)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_referenced_images_and_color_names() {
+        let image = |name: &str| IR::Image(name.to_string());
+        let ir = IR::VStack(vec![image("logo"), IR::HStack(vec![image("avatar"), image("logo")]), IR::Text("Hi".to_string())]);
+        assert_eq!(referenced_images(&ir), vec!["logo", "avatar"]);
+        assert_eq!(color_name("color.text-primary"), "colorTextPrimary");
+        assert_eq!(color_name("Brand_accent"), "brandAccent");
+    }
+
+    #[test]
+    fn test_write_catalog_keeps_existing_sets() {
+        let dir = std::env::temp_dir().join(format!("swiftui-synth-assets-{}", std::process::id())).join("Assets.xcassets");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("avatar.imageset")).unwrap();
+        let colors = vec![("accent".to_string(), Rgb { r: 0, g: 122, b: 255 })];
+        let created = write_catalog(&dir, &["logo".to_string(), "avatar".to_string()], &colors).unwrap();
+        assert_eq!(created, vec!["logo.imageset", "accent.colorset"]);
+        assert!(dir.join("Contents.json").is_file());
+        assert!(!dir.join("avatar.imageset/Contents.json").exists());
+        let color = fs::read_to_string(dir.join("accent.colorset/Contents.json")).unwrap();
+        assert!(color.contains("\"blue\": \"0xFF\"") && color.contains("\"green\": \"0x7A\""), "{}", color);
+        assert!(fs::read_to_string(dir.join("logo.imageset/Contents.json")).unwrap().contains("\"scale\": \"3x\""));
+
+        assert_eq!(write_catalog(&dir, &["logo".to_string()], &colors).unwrap(), Vec::<String>::new());
+        fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+}
@@ -0,0 +1,314 @@
+// File: src/output/backend.rs
+// Output targets (`--target`). A backend renders layouts as the code of one UI framework and
+// names the files a screen is saved as; the registry holds the built-in backends and any
+// plugins, so a target is added without changing the SwiftUI renderer.
+use std::path::Path;
+
+use crate::ast::IR;
+use crate::output::render::{self, RenderOptions};
+use crate::output::{appkit, vue, xaml};
+use crate::platform::Platform;
+
+/// An output target. Backends are shared between threads, so they hold no interior `Rc`
+/// or `RefCell`.
+pub trait RenderBackend: Send + Sync {
+    /// Name given to `--target`, e.g. `swiftui`.
+    fn name(&self) -> &str;
+
+    /// Renders a screen's layout; the screen is `options.view_name`.
+    fn render(&self, ir: &IR, options: &RenderOptions) -> String;
+
+    /// [`render`](RenderBackend::render) for backends that can fail, such as plugins.
+    fn try_render(&self, ir: &IR, options: &RenderOptions) -> Result<String, String> {
+        Ok(self.render(ir, options))
+    }
+
+    /// Name of the framework in messages, e.g. `SwiftUI`.
+    fn language(&self) -> &str {
+        self.name()
+    }
+
+    /// Name of the type a screen's code declares, from its view name.
+    fn type_name(&self, view_name: &str) -> String {
+        view_name.to_string()
+    }
+
+    /// Extension of the file a screen is saved as.
+    fn extension(&self) -> &str {
+        self.name()
+    }
+
+    /// `header`, the comment that heads generated files in `//` form, as this target's files
+    /// write it.
+    fn header(&self, header: &str) -> String {
+        header.to_string()
+    }
+
+    /// The files a screen is saved as, by name: its `code`, as rendered, under `header`.
+    fn files(&self, _ir: &IR, code: &str, header: &str, options: &RenderOptions) -> Vec<(String, String)> {
+        let name = format!("{}.{}", self.type_name(&options.view_name), self.extension());
+        vec![(name, format!("{}\n{}\n", self.header(header), code))]
+    }
+
+    /// Whether the code is Swift, which the options for Swift's and Xcode's tooling need.
+    fn is_swift(&self) -> bool {
+        false
+    }
+
+    /// The only platform the code runs on, if the target has one.
+    fn platform(&self) -> Option<Platform> {
+        None
+    }
+
+    /// Whether the reverse parser ([`crate::input::swift::parse_swiftui`]) reads the code back
+    /// as the layout it was rendered from.
+    fn round_trips(&self) -> bool {
+        false
+    }
+
+    /// What of the screen the code leaves out, one message each, to warn about.
+    fn left_out(&self, _ir: &IR, _options: &RenderOptions) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// SwiftUI views, the default target.
+pub struct SwiftUi;
+
+impl RenderBackend for SwiftUi {
+    fn name(&self) -> &str {
+        "swiftui"
+    }
+
+    fn render(&self, ir: &IR, options: &RenderOptions) -> String {
+        render::render_swiftui_with(ir, options)
+    }
+
+    fn language(&self) -> &str {
+        "SwiftUI"
+    }
+
+    fn extension(&self) -> &str {
+        "swift"
+    }
+
+    fn files(&self, _: &IR, code: &str, header: &str, options: &RenderOptions) -> Vec<(String, String)> {
+        vec![(format!("{}.swift", options.view_name), format!("{}\nimport SwiftUI\n\n{}\n", header, code))]
+    }
+
+    fn is_swift(&self) -> bool {
+        true
+    }
+
+    fn round_trips(&self) -> bool {
+        true
+    }
+}
+
+/// AppKit view controllers, for Mac apps not written in SwiftUI.
+pub struct AppKit;
+
+impl RenderBackend for AppKit {
+    fn name(&self) -> &str {
+        "appkit"
+    }
+
+    fn render(&self, ir: &IR, options: &RenderOptions) -> String {
+        appkit::render_view_controller(&self.type_name(&options.view_name), ir, options, &options.metrics)
+    }
+
+    fn left_out(&self, ir: &IR, options: &RenderOptions) -> Vec<String> {
+        appkit::dropped_modifiers(ir, options)
+    }
+
+    fn language(&self) -> &str {
+        "AppKit"
+    }
+
+    fn type_name(&self, view_name: &str) -> String {
+        appkit::controller_name(view_name)
+    }
+
+    fn extension(&self) -> &str {
+        "swift"
+    }
+
+    fn files(&self, _: &IR, code: &str, header: &str, options: &RenderOptions) -> Vec<(String, String)> {
+        vec![(format!("{}.swift", self.type_name(&options.view_name)), format!("{}\nimport AppKit\n\n{}\n", header, code))]
+    }
+
+    fn is_swift(&self) -> bool {
+        true
+    }
+
+    fn platform(&self) -> Option<Platform> {
+        Some(Platform::Macos)
+    }
+}
+
+/// .NET MAUI pages and their code-behind.
+pub struct Xaml;
+
+impl Xaml {
+    fn namespace(options: &RenderOptions) -> &str {
+        options.namespace.as_deref().unwrap_or(xaml::DEFAULT_NAMESPACE)
+    }
+}
+
+impl RenderBackend for Xaml {
+    fn name(&self) -> &str {
+        "xaml"
+    }
+
+    fn render(&self, ir: &IR, options: &RenderOptions) -> String {
+        xaml::render_page(&self.type_name(&options.view_name), Xaml::namespace(options), ir, options, &options.metrics)
+    }
+
+    fn language(&self) -> &str {
+        "XAML"
+    }
+
+    fn type_name(&self, view_name: &str) -> String {
+        xaml::page_name(view_name)
+    }
+
+    fn header(&self, header: &str) -> String {
+        xaml::comment_header(header)
+    }
+
+    // The page, then its code-behind with a handler per button to fill in
+    fn files(&self, ir: &IR, code: &str, header: &str, options: &RenderOptions) -> Vec<(String, String)> {
+        let page = self.type_name(&options.view_name);
+        let code_behind = xaml::render_code_behind(&page, Xaml::namespace(options), ir, options);
+        vec![
+            (format!("{}.xaml", page), format!("{}\n{}\n", self.header(header), code)),
+            (format!("{}.xaml.cs", page), format!("{}\n{}\n", header, code_behind)),
+        ]
+    }
+}
+
+/// Vue single-file components, for web prototypes.
+pub struct Vue;
+
+impl RenderBackend for Vue {
+    fn name(&self) -> &str {
+        "vue"
+    }
+
+    fn render(&self, ir: &IR, options: &RenderOptions) -> String {
+        vue::render_component(ir, options, &options.metrics)
+    }
+
+    fn language(&self) -> &str {
+        "Vue"
+    }
+
+    fn header(&self, header: &str) -> String {
+        vue::comment_header(header)
+    }
+}
+
+/// The targets a run can render to, by name.
+pub struct Registry {
+    backends: Vec<Box<dyn RenderBackend>>,
+}
+
+impl Registry {
+    /// The built-in targets, SwiftUI first. Targets compiled into the tool are added here.
+    pub fn builtin() -> Registry {
+        Registry { backends: vec![Box::new(SwiftUi), Box::new(AppKit), Box::new(Xaml), Box::new(Vue)] }
+    }
+
+    /// The built-in targets and those of the plugins at `paths`.
+    pub fn with_plugins(paths: &[impl AsRef<Path>]) -> Result<Registry, String> {
+        let mut registry = Registry::builtin();
+        for path in paths {
+            registry.register(load_plugin(path.as_ref())?)?;
+        }
+        Ok(registry)
+    }
+
+    /// Adds `backend`, unless a target of its name exists.
+    pub fn register(&mut self, backend: Box<dyn RenderBackend>) -> Result<(), String> {
+        if self.get(backend.name()).is_some() {
+            return Err(format!("A target named '{}' already exists", backend.name()));
+        }
+        self.backends.push(backend);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&dyn RenderBackend> {
+        self.backends.iter().find(|backend| backend.name() == name).map(|backend| backend.as_ref())
+    }
+
+    /// The backend named `name`, or an error listing the targets there are.
+    pub fn resolve(&self, name: &str) -> Result<&dyn RenderBackend, String> {
+        self.get(name)
+            .ok_or_else(|| format!("Unsupported target '{}': expected one of {}", name, self.names().join(", ")))
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.backends.iter().map(|backend| backend.name()).collect()
+    }
+}
+
+/// Loads the WebAssembly backend plugin at `path`.
+#[cfg(feature = "wasm-plugins")]
+pub fn load_plugin(path: &Path) -> Result<Box<dyn RenderBackend>, String> {
+    Ok(Box::new(crate::output::plugin::WasmBackend::load(path)?))
+}
+
+/// Loads the WebAssembly backend plugin at `path`, which takes the `wasm-plugins` feature.
+#[cfg(not(feature = "wasm-plugins"))]
+pub fn load_plugin(path: &Path) -> Result<Box<dyn RenderBackend>, String> {
+    Err(format!("Backend plugin '{}' needs swiftui-synth built with the wasm-plugins feature", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Shouting;
+
+    impl RenderBackend for Shouting {
+        fn name(&self) -> &str {
+            "shout"
+        }
+
+        fn render(&self, ir: &IR, _: &RenderOptions) -> String {
+            format!("{:?}", ir).to_uppercase()
+        }
+    }
+
+    #[test]
+    fn test_registry_holds_the_builtin_targets_and_more() {
+        let mut registry = Registry::builtin();
+        assert_eq!(registry.names(), vec!["swiftui", "appkit", "xaml", "vue"]);
+        registry.register(Box::new(Shouting)).unwrap();
+        assert_eq!(registry.register(Box::new(Shouting)).unwrap_err(), "A target named 'shout' already exists");
+        assert!(registry.resolve("flutter").err().unwrap().ends_with("expected one of swiftui, appkit, xaml, vue, shout"));
+
+        let shout = registry.resolve("shout").unwrap();
+        let options = RenderOptions { view_name: "LoginView".to_string(), ..RenderOptions::default() };
+        let code = shout.try_render(&IR::Text("hi".to_string()), &options).unwrap();
+        assert_eq!(code, "TEXT(\"HI\")");
+        assert_eq!(shout.files(&IR::Spacer, &code, "// Generated", &options), vec![("LoginView.shout".to_string(), "// Generated\nTEXT(\"HI\")\n".to_string())]);
+    }
+
+    #[test]
+    fn test_builtin_files() {
+        let registry = Registry::builtin();
+        let options = RenderOptions { view_name: "LoginView".to_string(), ..RenderOptions::default() };
+        let ir = IR::Button("Go".to_string());
+        let names = |target: &str| {
+            let backend = registry.resolve(target).unwrap();
+            let code = backend.render(&ir, &options);
+            backend.files(&ir, &code, "// Generated", &options).into_iter().map(|(name, _)| name).collect::<Vec<_>>()
+        };
+        assert_eq!(names("swiftui"), vec!["LoginView.swift"]);
+        assert_eq!(names("appkit"), vec!["LoginViewController.swift"]);
+        assert_eq!(names("xaml"), vec!["LoginPage.xaml", "LoginPage.xaml.cs"]);
+        assert_eq!(names("vue"), vec!["LoginView.vue"]);
+        assert!(registry.get("appkit").unwrap().is_swift() && !registry.get("vue").unwrap().is_swift());
+    }
+}
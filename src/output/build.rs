@@ -0,0 +1,183 @@
+// File: src/output/build.rs
+// Incremental builds for `--out-dir`: a manifest in the folder records the fingerprint of
+// every input each screen's files were generated from (its examples, the `let` bindings it
+// uses, the flows and the settings), so a later run rebuilds only screens whose inputs changed.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::ast::{Example, IR};
+use crate::input::parser::split_examples;
+use crate::input::preprocess::{Bound, Expansion};
+use crate::output::provenance;
+use crate::synthesis::cache::fnv1a;
+
+/// Name of the manifest in the output folder.
+pub const MANIFEST: &str = ".swiftui-synth-build.json";
+
+/// What a screen's files were last built from.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct Entry {
+    /// Fingerprints of the inputs, by name: `examples`, `settings`, `flows` and `let <name>`.
+    pub inputs: BTreeMap<String, String>,
+    /// The layout built, to render the screen again without searching.
+    pub ir: Option<IR>,
+    /// The files written for the screen, relative to the folder, with the fingerprints of
+    /// their contents, so files edited or removed since are written again.
+    pub files: BTreeMap<String, String>,
+}
+
+/// The screens built into a folder, by view name.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct Manifest {
+    pub screens: BTreeMap<String, Entry>,
+}
+
+fn hash(text: &str) -> String {
+    format!("{:016x}", fnv1a(text.as_bytes()))
+}
+
+/// Fingerprint of the contents of the file at `path`; empty if it cannot be read.
+pub fn file_hash(path: &Path) -> String {
+    fs::read_to_string(path).map(|contents| hash(&contents)).unwrap_or_default()
+}
+
+impl Manifest {
+    /// The manifest in `dir`; an empty one if there is none yet.
+    pub fn load(dir: &Path) -> Result<Manifest, String> {
+        let path = dir.join(MANIFEST);
+        match fs::read_to_string(&path) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Invalid build manifest '{}': {}", path.display(), e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(e) => Err(format!("Failed to read '{}': {}", path.display(), e)),
+        }
+    }
+
+    pub fn save(&self, dir: &Path) -> Result<(), String> {
+        let path = dir.join(MANIFEST);
+        let json = serde_json::to_string_pretty(self).expect("manifest serializes");
+        fs::write(&path, json + "\n").map_err(|e| format!("Failed to write '{}': {}", path.display(), e))
+    }
+
+    /// The inputs of screen `view` that changed since it was built into `dir`, or `None`
+    /// if it is up to date: every input is as recorded and its files are as written. A
+    /// screen never built has `examples` changed.
+    pub fn changed(&self, view: &str, inputs: &BTreeMap<String, String>, dir: &Path) -> Option<Vec<String>> {
+        let Some(entry) = self.screens.get(view).filter(|entry| entry.ir.is_some()) else {
+            return Some(vec!["examples".to_string()]);
+        };
+        let names = entry.inputs.keys().chain(inputs.keys().filter(|name| !entry.inputs.contains_key(*name)));
+        let changed: Vec<String> = names.filter(|name| entry.inputs.get(*name) != inputs.get(*name)).cloned().collect();
+        if !changed.is_empty() {
+            return Some(changed);
+        }
+        let edited: Vec<String> =
+            entry.files.iter().filter(|(file, written)| file_hash(&dir.join(file)) != **written).map(|(file, _)| format!("file {}", file)).collect();
+        (!edited.is_empty()).then_some(edited)
+    }
+}
+
+/// Fingerprints of what a screen's files depend on: its `examples`, the `bindings` they use,
+/// the spec's `flows` (as text, empty for none) and the `settings` of the run.
+pub fn inputs(examples: &[Example], bindings: &[&Bound], flows: &str, settings: &str) -> BTreeMap<String, String> {
+    let mut inputs = BTreeMap::new();
+    inputs.insert("examples".to_string(), provenance::fingerprint(examples));
+    inputs.insert("settings".to_string(), hash(settings));
+    if !flows.is_empty() {
+        inputs.insert("flows".to_string(), hash(flows));
+    }
+    for bound in bindings {
+        inputs.insert(format!("let {}", bound.name), hash(&bound.text));
+    }
+    inputs
+}
+
+/// Where each screen of an expanded spec lies in its text, in order: the screen name, if
+/// any, through its last example. The `flows:` block is not a screen.
+pub fn screen_ranges(expansion: &Expansion) -> Vec<Range<usize>> {
+    let text = &expansion.text;
+    let offset = |block: &str| block.as_ptr() as usize - text.as_ptr() as usize;
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut in_flows = false;
+    for block in split_examples(text.trim()) {
+        let range = offset(block)..offset(block) + block.len();
+        match block.strip_suffix(':') {
+            Some(name) => {
+                in_flows = name.trim() == "flows";
+                if !in_flows {
+                    ranges.push(range);
+                }
+            }
+            None if in_flows => in_flows = false,
+            // An example belongs to the screen before it, or makes up the unnamed screen
+            None => match ranges.last_mut() {
+                Some(last) => last.end = range.end,
+                None => ranges.push(range),
+            },
+        }
+    }
+    ranges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::lexer::strip_comments;
+    use crate::input::parser::{parse_spec_with, ParseOptions};
+    use crate::input::preprocess::expand;
+
+    const SPEC: &str = "let brand = \"Acme\"\nlet header = { title:brand }\n\
+                        Login:{(width:390,height:844):{@include header, button:\"Sign in\"}}\n\
+                        Home:{(width:390,height:844):{title:\"Home\"}} {(width:320,height:568):{title:\"Home\"}}";
+
+    #[test]
+    fn test_screens_use_their_bindings() {
+        let expansion = expand(&strip_comments(SPEC)).unwrap();
+        let ranges = screen_ranges(&expansion);
+        assert_eq!(ranges.len(), 2);
+        assert!(expansion.text[ranges[1].clone()].starts_with("Home:") && expansion.text[ranges[1].clone()].ends_with("\"Home\"}}"));
+        let names = |range: &Range<usize>| expansion.uses(range.clone()).iter().map(|bound| bound.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&ranges[0]), ["brand", "header"]);
+        assert!(names(&ranges[1]).is_empty());
+    }
+
+    #[test]
+    fn test_only_changed_screens_rebuild() {
+        let dir = std::env::temp_dir().join(format!("swiftui-synth-build-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let spec = |brand: &str| {
+            let text = SPEC.replace("Acme", brand);
+            let expansion = expand(&strip_comments(&text)).unwrap();
+            let screens = parse_spec_with(&text, &ParseOptions::default()).unwrap().screens;
+            let ranges = screen_ranges(&expansion);
+            screens
+                .iter()
+                .zip(&ranges)
+                .map(|(screen, range)| inputs(&screen.examples, &expansion.uses(range.clone()), "", "settings"))
+                .collect::<Vec<_>>()
+        };
+        let before = spec("Acme");
+        let mut manifest = Manifest::default();
+        for (view, inputs) in ["LoginView", "HomeView"].iter().zip(&before) {
+            let file = format!("{}.swift", view);
+            fs::write(dir.join(&file), "struct View {}").unwrap();
+            let files = BTreeMap::from([(file.clone(), file_hash(&dir.join(&file)))]);
+            manifest.screens.insert(view.to_string(), Entry { inputs: inputs.clone(), ir: Some(IR::Spacer), files });
+        }
+        manifest.save(&dir).unwrap();
+        let manifest = Manifest::load(&dir).unwrap();
+
+        let after = spec("Acme Inc");
+        assert_eq!(manifest.changed("LoginView", &after[0], &dir), Some(vec!["examples".to_string(), "let brand".to_string(), "let header".to_string()]));
+        assert_eq!(manifest.changed("HomeView", &after[1], &dir), None);
+        assert_eq!(manifest.changed("SettingsView", &after[1], &dir), Some(vec!["examples".to_string()]));
+        fs::write(dir.join("HomeView.swift"), "struct View { /* edited */ }").unwrap();
+        assert_eq!(manifest.changed("HomeView", &after[1], &dir), Some(vec!["file HomeView.swift".to_string()]));
+        fs::remove_file(dir.join("HomeView.swift")).unwrap();
+        assert_eq!(manifest.changed("HomeView", &after[1], &dir), Some(vec!["file HomeView.swift".to_string()]));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,11 @@
+// File: src/output/clipboard.rs
+// Puts generated code on the system clipboard (`--copy`), ready to paste into Xcode.
+
+/// Replaces the clipboard's contents with `text`. Fails where there is no clipboard, such as
+/// over SSH or on a machine without a display.
+pub fn copy(text: &str) -> Result<(), String> {
+    let mut clipboard = arboard::Clipboard::new().map_err(|e| format!("Could not open the clipboard: {}", e))?;
+    // On X11 the text stays available after exit only if a clipboard manager takes it over,
+    // which dropping the clipboard waits for
+    clipboard.set_text(text).map_err(|e| format!("Could not copy to the clipboard: {}", e))
+}
@@ -0,0 +1,112 @@
+// File: src/output/examples.rs
+// Example generation (`examples --from View.swift`), the inverse of synthesis: lays out an
+// existing view at several screen sizes and writes where each of its views lands as the
+// examples of a spec, to bootstrap specs from existing screens or build benchmark suites.
+use crate::ast::IR;
+use crate::input::import::{device_safe_area, screen_name, write_example, write_screen, Element, Example};
+use crate::layout::{self, devices, Metrics, Size};
+
+/// Reads a screen size, as `390x844` or the name of a device (held in portrait).
+pub fn parse_size(value: &str) -> Result<(f64, f64), String> {
+    if let Some(device) = devices::find(value) {
+        return Ok((device.width, device.height));
+    }
+    let size = value.split_once('x').and_then(|(width, height)| Some((width.trim().parse::<f64>().ok()?, height.trim().parse::<f64>().ok()?)));
+    match size {
+        Some((width, height)) if width > 0.0 && height > 0.0 => Ok((width, height)),
+        _ => Err(format!("Invalid size '{}': expected WIDTHxHEIGHT, e.g. 390x844, or a device name", value)),
+    }
+}
+
+/// The name of the first view `struct` declared in `code`, e.g. `LoginView`.
+pub fn view_name(code: &str) -> Option<&str> {
+    let mut words = code.split(|ch: char| !(ch.is_alphanumeric() || ch == '_')).filter(|word| !word.is_empty());
+    words.find(|word| *word == "struct")?;
+    words.next()
+}
+
+// The spec element a leaf view is, as its key and content
+fn element(node: &IR) -> Option<(&'static str, String)> {
+    match node {
+        IR::Text(text) => Some(("title", text.clone())),
+        IR::Button(title) => Some(("button", title.clone())),
+        IR::Image(name) => Some(("Image", name.clone())),
+        IR::Custom { name, args } => Some(("Custom", format!("{}({})", name, args))),
+        _ => None,
+    }
+}
+
+/// The example of `ir` laid out on a screen of `width` by `height` points, with the safe area
+/// of the device of that size: every view with the frame it gets, rounded to tenths of a point.
+/// Images only state where they are: their size is the placeholder one of an asset the view
+/// does not give, which stated would ask for an image framed at that size.
+fn example(ir: &IR, (width, height): (f64, f64), metrics: &Metrics) -> Example {
+    let insets = device_safe_area(width, height).unwrap_or_default();
+    let simulated = layout::simulate_within(ir, Size::new(width, height), insets, metrics);
+    let round = |value: f64| (value * 10.0).round() / 10.0;
+    let elements = simulated
+        .leaves()
+        .filter_map(|frame| {
+            let (key, content) = element(&frame.node)?;
+            let rect = &frame.rect;
+            let mut element = Element::new(key, Some(&content), (round(rect.x), round(rect.y), round(rect.width), round(rect.height)));
+            if matches!(frame.node, IR::Image(_)) {
+                (element.width, element.height) = (None, None);
+            }
+            Some(element)
+        })
+        .collect();
+    Example { width, height, insets, elements }
+}
+
+/// Spec text with an example of `ir` at each of `sizes`; a screen named after `view` when
+/// there is one (`LoginView` gives `Login:`), and unnamed examples otherwise.
+pub fn generate(ir: &IR, view: Option<&str>, sizes: &[(f64, f64)], metrics: &Metrics) -> String {
+    let examples: Vec<Example> = sizes.iter().map(|size| example(ir, *size, metrics)).collect();
+    match view {
+        Some(view) => write_screen(&screen_name(view.strip_suffix("View").unwrap_or(view)), examples),
+        None => examples.into_iter().map(write_example).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::{parse_spec_with, ParseOptions};
+    use crate::synthesis::verify::verify;
+
+    #[test]
+    fn test_sizes() {
+        assert_eq!(parse_size("1024x768").unwrap(), (1024.0, 768.0));
+        assert_eq!(parse_size("iphone se").unwrap(), (375.0, 667.0));
+        assert!(parse_size("wide").unwrap_err().contains("expected WIDTHxHEIGHT"));
+        assert!(parse_size("0x10").is_err());
+    }
+
+    #[test]
+    fn test_generated_examples_are_satisfied_by_their_view() {
+        let code = "struct LoginView: View {\n    var body: some View {\n        VStack {\n            Text(\"Welcome\")\n            Spacer()\n            Button(\"Sign in\") {}\n        }\n    }\n}";
+        let ir = crate::input::swift::parse_swiftui(code).unwrap();
+        let metrics = Metrics::default();
+        let spec = generate(&ir, view_name(code), &[(390.0, 844.0), (1024.0, 768.0)], &metrics);
+        assert!(spec.starts_with("Login:{(width:390,height:844,safe_top:47,safe_bottom:34):{title:\"Welcome\"@("), "{}", spec);
+        assert!(spec.contains("}}{(width:1024,height:768):{"), "{}", spec);
+
+        // The examples read back as a spec the view satisfies
+        let parsed = parse_spec_with(&spec, &ParseOptions::default()).unwrap();
+        assert_eq!(parsed.screens[0].name.as_deref(), Some("Login"));
+        assert_eq!(verify(&ir, &parsed.screens[0].examples, &metrics), vec![]);
+        assert!(generate(&ir, None, &[(320.0, 480.0)], &metrics).starts_with("{(width:320,height:480):{"));
+    }
+
+    #[test]
+    fn test_images_keep_their_size_unstated() {
+        let code = "VStack {\n    Image(\"logo\")\n    Text(\"Welcome\")\n    Spacer()\n}";
+        let ir = crate::input::swift::parse_swiftui(code).unwrap();
+        let spec = generate(&ir, None, &[(390.0, 844.0)], &Metrics::default());
+        assert!(spec.contains("Image:\"logo\"@(x:165,y:63)"), "{}", spec);
+        // Fed back, the image is emitted as it was written, not resized
+        let examples = &parse_spec_with(&spec, &ParseOptions::default()).unwrap().screens[0].examples;
+        assert!(crate::layout::image_sizing::sizing(examples).is_empty());
+    }
+}
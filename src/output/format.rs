@@ -0,0 +1,180 @@
+// File: src/output/format.rs
+// Post-processing of generated code (`--post-format`): through the team's formatter command,
+// or the built-in pretty-printer, which wraps lines longer than the configured width.
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// How the built-in pretty-printer lays out code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatOptions {
+    /// Longest line, in characters, before a line is wrapped.
+    pub line_width: usize,
+    /// Spaces per indentation level of wrapped modifiers and arguments.
+    pub indent_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions { line_width: 100, indent_width: 4 }
+    }
+}
+
+/// Pipes `code` through the shell command `command`, which reads the code on stdin and writes
+/// it formatted to stdout, e.g. `swift-format` or `swiftformat stdin`.
+pub fn run_command(command: &str, code: &str) -> Result<String, String> {
+    let (shell, flag) = if cfg!(windows) { ("cmd", "/C") } else { ("sh", "-c") };
+    let mut child = Command::new(shell)
+        .args([flag, command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run formatter '{}': {}", command, e))?;
+    // A formatter that exits without reading its input is reported by its status below
+    let _ = child.stdin.take().expect("stdin is piped").write_all(code.as_bytes());
+    let output = child.wait_with_output().map_err(|e| format!("Failed to run formatter '{}': {}", command, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("Formatter '{}' failed ({}): {}", command, output.status, stderr.trim()));
+    }
+    let formatted = String::from_utf8(output.stdout).map_err(|_| format!("Formatter '{}' wrote invalid UTF-8", command))?;
+    if formatted.trim().is_empty() {
+        return Err(format!("Formatter '{}' printed no code", command));
+    }
+    Ok(formatted.trim_end().to_string())
+}
+
+/// Wraps every line of `code` longer than the line width: a chain of modifiers is split with
+/// one modifier per line, else the arguments of a call go one per line. Lines that cannot be
+/// split, such as a long string literal, are left as they are.
+pub fn pretty_print(code: &str, options: &FormatOptions) -> String {
+    let mut lines = Vec::new();
+    for line in code.lines() {
+        wrap(line.trim_end(), options, &mut lines);
+    }
+    lines.join("\n")
+}
+
+// Byte offsets in `text` at depth 0, outside string literals, where `split` says to split
+fn top_level(text: &str, split: impl Fn(usize, char, i32) -> bool) -> Vec<usize> {
+    let (mut depth, mut in_quotes, mut escaped) = (0i32, false, false);
+    let mut offsets = Vec::new();
+    for (i, ch) in text.char_indices() {
+        match ch {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ if in_quotes => {}
+            '(' | '[' | '{' => {
+                if split(i, ch, depth) {
+                    offsets.push(i);
+                }
+                depth += 1;
+            }
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if split(i, ch, depth) {
+                    offsets.push(i);
+                }
+            }
+            _ if split(i, ch, depth) => offsets.push(i),
+            _ => {}
+        }
+    }
+    offsets
+}
+
+fn wrap(line: &str, options: &FormatOptions, out: &mut Vec<String>) {
+    if line.chars().count() <= options.line_width {
+        out.push(line.to_string());
+        return;
+    }
+    let body = line.trim_start();
+    let pad = &line[..line.len() - body.len()];
+    let inner_pad = format!("{}{}", pad, " ".repeat(options.indent_width));
+
+    // `Text("Hi").font(.title).padding()`: a modifier starts at each top-level `.` after a call
+    let modifiers = top_level(body, |i, ch, depth| ch == '.' && depth == 0 && body[..i].trim_end().ends_with([')', '}']));
+    if let Some(&first) = modifiers.first() {
+        wrap(&format!("{}{}", pad, body[..first].trim_end()), options, out);
+        let ends = modifiers.iter().skip(1).copied().chain(std::iter::once(body.len()));
+        for (start, end) in modifiers.iter().zip(ends) {
+            wrap(&format!("{}{}", inner_pad, body[*start..end].trim()), options, out);
+        }
+        return;
+    }
+
+    // `RatingView(value: 4, max: 5)`: the arguments of the first call go one per line
+    let Some(open) = top_level(body, |_, ch, depth| ch == '(' && depth == 0).first().copied() else {
+        out.push(line.to_string());
+        return;
+    };
+    let Some(close) = top_level(body, |i, ch, depth| ch == ')' && depth == 0 && i > open).first().copied() else {
+        out.push(line.to_string());
+        return;
+    };
+    // A single argument is as long on a line of its own
+    let args = &body[open + 1..close];
+    let commas = top_level(args, |_, ch, depth| ch == ',' && depth == 0);
+    if commas.is_empty() {
+        out.push(line.to_string());
+        return;
+    }
+    out.push(format!("{}{}", pad, &body[..=open]));
+    let starts = std::iter::once(0).chain(commas.iter().map(|comma| comma + 1));
+    let ends = commas.iter().copied().chain(std::iter::once(args.len()));
+    let count = commas.len() + 1;
+    for (index, (start, end)) in starts.zip(ends).enumerate() {
+        let separator = if index + 1 < count { "," } else { "" };
+        wrap(&format!("{}{}{}", inner_pad, args[start..end].trim(), separator), options, out);
+    }
+    out.push(format!("{}{}", pad, &body[close..]));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn narrow(line_width: usize) -> FormatOptions {
+        FormatOptions { line_width, ..FormatOptions::default() }
+    }
+
+    #[test]
+    fn test_short_lines_are_kept() {
+        let code = "VStack {\n    Text(\"Hi\")   \n        .font(.title)\n}";
+        assert_eq!(pretty_print(code, &FormatOptions::default()), "VStack {\n    Text(\"Hi\")\n        .font(.title)\n}");
+    }
+
+    #[test]
+    fn test_modifier_chains_wrap_one_per_line() {
+        let code = "    Text(\"Hello, world\").font(.title).foregroundStyle(.secondary).padding()";
+        assert_eq!(
+            pretty_print(code, &narrow(40)),
+            "    Text(\"Hello, world\")\n        .font(.title)\n        .foregroundStyle(.secondary)\n        .padding()"
+        );
+        // A `.` inside arguments, a string or a name such as `Route.home` does not start one
+        let link = "NavigationLink(\"Go to Mr. Smith\", value: Route.home)";
+        assert_eq!(pretty_print(link, &narrow(30)), "NavigationLink(\n    \"Go to Mr. Smith\",\n    value: Route.home\n)");
+    }
+
+    #[test]
+    fn test_long_calls_wrap_their_arguments() {
+        let code = "        RatingView(value: 4, maximum: 5, tint: Color(red: 1, green: 0.5, blue: 0)) // stars";
+        assert_eq!(
+            pretty_print(code, &narrow(60)),
+            "        RatingView(\n            value: 4,\n            maximum: 5,\n            tint: Color(red: 1, green: 0.5, blue: 0)\n        ) // stars"
+        );
+        // Nothing to split: kept whole
+        let text = "Text(\"A very long sentence that cannot be wrapped\")";
+        assert_eq!(pretty_print(text, &narrow(20)), text);
+        assert_eq!(pretty_print("Spacer()", &narrow(4)), "Spacer()");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_formatter_commands() {
+        assert_eq!(run_command("tr a-z A-Z", "Text(\"hi\")\n").unwrap(), "TEXT(\"HI\")");
+        assert!(run_command("exit 3", "Text(\"hi\")").unwrap_err().contains("Formatter 'exit 3' failed"));
+        assert!(run_command("true", "Text(\"hi\")").unwrap_err().contains("printed no code"));
+    }
+}
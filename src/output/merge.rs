@@ -0,0 +1,175 @@
+// File: src/output/merge.rs
+// Regenerating into files that already exist. Generated code sits between
+// `// swiftui-synth:begin <hash>` and `// swiftui-synth:end` markers; only that section is
+// rewritten, so code added around it survives, and the hash tells when it was edited by hand.
+// In XAML and Vue components the markers are XML comments instead.
+use std::fmt;
+use std::path::Path;
+
+use crate::synthesis::cache::fnv1a;
+
+/// Line opening the generated section, followed by the hash of its contents.
+pub const BEGIN_MARKER: &str = "// swiftui-synth:begin";
+/// Line closing the generated section.
+pub const END_MARKER: &str = "// swiftui-synth:end";
+
+/// How the markers are commented.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Syntax {
+    /// `// swiftui-synth:begin`, as in Swift and C#.
+    #[default]
+    Slashes,
+    /// `<!-- swiftui-synth:begin -->`, as in XAML and Vue components.
+    Xml,
+}
+
+impl Syntax {
+    /// The syntax of the file at `path`: XML comments in a `.xaml` or `.vue` file, `//` in any
+    /// other.
+    pub fn for_path(path: &Path) -> Syntax {
+        match path.extension().is_some_and(|extension| extension == "xaml" || extension == "vue") {
+            true => Syntax::Xml,
+            false => Syntax::Slashes,
+        }
+    }
+
+    fn begin(&self, hash: &str) -> String {
+        match self {
+            Syntax::Slashes => format!("{} {}", BEGIN_MARKER, hash),
+            Syntax::Xml => format!("<!-- swiftui-synth:begin {} -->", hash),
+        }
+    }
+
+    fn end(&self) -> &'static str {
+        match self {
+            Syntax::Slashes => END_MARKER,
+            Syntax::Xml => "<!-- swiftui-synth:end -->",
+        }
+    }
+
+    // The hash a begin marker line records; `None` if the line is not one
+    fn recorded<'a>(&self, line: &'a str) -> Option<&'a str> {
+        let line = line.trim();
+        match self {
+            Syntax::Slashes => line.strip_prefix(BEGIN_MARKER),
+            Syntax::Xml => line.strip_prefix("<!-- swiftui-synth:begin")?.strip_suffix("-->"),
+        }
+        .map(str::trim)
+    }
+}
+
+/// Why an existing file was left alone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conflict {
+    /// The generated section no longer matches the hash it was written with.
+    Edited,
+    /// The file has no complete pair of markers, so it is not known which part was generated.
+    Unmarked,
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Conflict::Edited => write!(f, "its generated section was edited by hand"),
+            Conflict::Unmarked => write!(f, "it has no swiftui-synth:begin/end markers"),
+        }
+    }
+}
+
+// Hash of a section's lines; trailing whitespace and line endings do not count as edits
+fn hash<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    let text: Vec<&str> = lines.map(str::trim_end).collect();
+    format!("{:016x}", fnv1a(text.join("\n").as_bytes()))
+}
+
+/// `code` as a generated section: between the markers, the first of which records its hash.
+pub fn section(code: &str) -> String {
+    section_with(code, Syntax::Slashes)
+}
+
+/// [`section`] with markers in `syntax`.
+pub fn section_with(code: &str, syntax: Syntax) -> String {
+    let code = code.trim_end();
+    format!("{}\n{}\n{}", syntax.begin(&hash(code.lines())), code, syntax.end())
+}
+
+/// The contents to write to a file holding `existing` (`None` if there is no file yet): its
+/// generated section replaced by `code`, and everything around the markers kept. A section
+/// edited since it was written, or a file without markers, is a conflict unless `force` is
+/// set, in which case the section (or the whole file) is replaced anyway.
+pub fn merge(existing: Option<&str>, code: &str, force: bool) -> Result<String, Conflict> {
+    merge_with(existing, code, force, Syntax::Slashes)
+}
+
+/// [`merge`] for a file whose markers are in `syntax`.
+pub fn merge_with(existing: Option<&str>, code: &str, force: bool, syntax: Syntax) -> Result<String, Conflict> {
+    let Some(existing) = existing else {
+        return Ok(format!("{}\n", section_with(code, syntax)));
+    };
+    let lines: Vec<&str> = existing.split_inclusive('\n').collect();
+    let begin = lines.iter().position(|line| syntax.recorded(line).is_some());
+    let end = begin.and_then(|begin| lines[begin..].iter().position(|line| line.trim() == syntax.end()).map(|end| begin + end));
+    let (Some(begin), Some(end)) = (begin, end) else {
+        return if force { Ok(format!("{}\n", section_with(code, syntax))) } else { Err(Conflict::Unmarked) };
+    };
+    let recorded = syntax.recorded(lines[begin]).unwrap_or_default();
+    if recorded != hash(lines[begin + 1..end].iter().copied()) && !force {
+        return Err(Conflict::Edited);
+    }
+    // The end marker keeps its line break, if any
+    let line_break = &lines[end][lines[end].trim_end().len()..];
+    Ok(format!("{}{}{}{}", lines[..begin].concat(), section_with(code, syntax), line_break, lines[end + 1..].concat()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATED: &str = "import SwiftUI\n\nstruct ContentView: View {\n    var body: some View {\n        Text(\"Hi\")\n    }\n}\n";
+    const REGENERATED: &str = "import SwiftUI\n\nstruct ContentView: View {\n    var body: some View {\n        Text(\"Hello\")\n    }\n}\n";
+
+    #[test]
+    fn test_new_files_get_a_marked_section() {
+        let file = merge(None, GENERATED, false).unwrap();
+        assert!(file.starts_with("// swiftui-synth:begin "));
+        assert!(file.ends_with("    }\n}\n// swiftui-synth:end\n"));
+        // Regenerating an untouched file gives the new code alone
+        assert_eq!(merge(Some(&file), REGENERATED, false).unwrap(), merge(None, REGENERATED, false).unwrap());
+    }
+
+    #[test]
+    fn test_code_around_the_markers_is_kept() {
+        let file = format!("// Copyright Acme\n{}\nextension ContentView {{\n    var title: String {{ \"Hi\" }}\n}}\n", section(GENERATED));
+        let merged = merge(Some(&file), REGENERATED, false).unwrap();
+        assert_eq!(merged, format!("// Copyright Acme\n{}\nextension ContentView {{\n    var title: String {{ \"Hi\" }}\n}}\n", section(REGENERATED)));
+        // Line endings and trailing whitespace inside the section are not edits
+        let crlf = file.replace('\n', "\r\n").replace("Text(\"Hi\")", "Text(\"Hi\")   ");
+        assert!(merge(Some(&crlf), REGENERATED, false).unwrap().contains("Text(\"Hello\")"));
+    }
+
+    #[test]
+    fn test_edits_inside_the_section_conflict() {
+        let file = merge(None, GENERATED, false).unwrap().replace("Text(\"Hi\")", "Text(\"Hi\").bold()");
+        assert_eq!(merge(Some(&file), REGENERATED, false), Err(Conflict::Edited));
+        assert_eq!(merge(Some(&file), REGENERATED, true).unwrap(), merge(None, REGENERATED, false).unwrap());
+
+        assert_eq!(merge(Some(GENERATED), REGENERATED, false), Err(Conflict::Unmarked));
+        let unterminated = format!("{} 0\n{}", BEGIN_MARKER, GENERATED);
+        assert_eq!(merge(Some(&unterminated), REGENERATED, false), Err(Conflict::Unmarked));
+        assert_eq!(merge(Some(GENERATED), REGENERATED, true).unwrap(), merge(None, REGENERATED, false).unwrap());
+    }
+
+    #[test]
+    fn test_xaml_markers_are_xml_comments() {
+        assert_eq!(Syntax::for_path(Path::new("Pages/LoginPage.xaml")), Syntax::Xml);
+        assert_eq!(Syntax::for_path(Path::new("LoginPage.xaml.cs")), Syntax::Slashes);
+        assert_eq!(Syntax::for_path(Path::new("LoginView.vue")), Syntax::Xml);
+        let page = "<ContentPage>\n    <Label Text=\"Hi\" />\n</ContentPage>";
+        let file = merge_with(None, page, false, Syntax::Xml).unwrap();
+        assert!(file.starts_with("<!-- swiftui-synth:begin ") && file.ends_with("</ContentPage>\n<!-- swiftui-synth:end -->\n"), "{}", file);
+        let regenerated = page.replace("Hi", "Hello");
+        assert_eq!(merge_with(Some(&file), &regenerated, false, Syntax::Xml).unwrap(), merge_with(None, &regenerated, false, Syntax::Xml).unwrap());
+        let edited = file.replace("Hi", "Hey");
+        assert_eq!(merge_with(Some(&edited), &regenerated, false, Syntax::Xml), Err(Conflict::Edited));
+    }
+}
@@ -1 +1,24 @@
 pub mod render;
+pub mod preview;
+pub mod picker;
+pub mod swiftc;
+pub mod format;
+pub mod merge;
+pub mod provenance;
+pub mod xcode;
+pub mod package;
+pub mod clipboard;
+pub mod tree;
+pub mod png;
+pub mod assets;
+pub mod snapshot;
+pub mod ui_tests;
+pub mod appkit;
+pub mod xaml;
+pub mod vue;
+pub mod backend;
+pub mod template;
+pub mod examples;
+pub mod build;
+#[cfg(feature = "wasm-plugins")]
+pub mod plugin;
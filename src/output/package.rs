@@ -0,0 +1,119 @@
+// File: src/output/package.rs
+// A Swift package around the generated views (`--emit-package`): a library target holding
+// them, and a test target with a placeholder test per view to record snapshots in.
+use std::path::{Path, PathBuf};
+
+use crate::platform::Platform;
+
+/// Layout of the package at `dir`, named after its last component.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Package {
+    pub dir: PathBuf,
+    pub name: String,
+}
+
+impl Package {
+    /// The package at `dir`. Its name is also the module apps import, so it must be a Swift
+    /// identifier.
+    pub fn new(dir: &Path) -> Result<Package, String> {
+        let name = dir.file_name().map(|name| name.to_string_lossy().into_owned()).unwrap_or_default();
+        let valid = name.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_')
+            && name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+        if !valid {
+            return Err(format!("Package name '{}' is not a valid Swift module name; use letters, digits and underscores", name));
+        }
+        Ok(Package { dir: dir.to_path_buf(), name })
+    }
+
+    /// Where the views go: `Sources/<name>`.
+    pub fn sources_dir(&self) -> PathBuf {
+        self.dir.join("Sources").join(&self.name)
+    }
+
+    /// `Tests/<name>Tests/<name>Tests.swift`.
+    pub fn tests_file(&self) -> PathBuf {
+        let tests = format!("{}Tests", self.name);
+        self.dir.join("Tests").join(&tests).join(format!("{}.swift", tests))
+    }
+
+    /// `Package.swift`, declaring the library and its tests, for `platform`.
+    pub fn manifest(&self, platform: Platform) -> String {
+        format!(
+            "// swift-tools-version:5.9
+import PackageDescription
+
+let package = Package(
+    name: \"{name}\",
+    platforms: {platforms},
+    products: [
+        .library(name: \"{name}\", targets: [\"{name}\"]),
+    ],
+    targets: [
+        .target(name: \"{name}\"),
+        .testTarget(name: \"{name}Tests\", dependencies: [\"{name}\"]),
+    ]
+)
+",
+            name = self.name,
+            platforms = platform.package_platforms()
+        )
+    }
+
+    /// A test per view in `views` that builds it, with a note where to record its snapshot.
+    pub fn tests(&self, views: &[String]) -> String {
+        let tests: Vec<String> = views
+            .iter()
+            .map(|view| {
+                format!(
+                    "    func test{view}() {{
+        let view = {view}()
+        // Record a snapshot here, e.g. with swift-snapshot-testing:
+        // assertSnapshot(of: view, as: .image(layout: .device(config: .iPhone13)))
+        _ = view.body
+    }}",
+                    view = view
+                )
+            })
+            .collect();
+        format!(
+            "import SwiftUI
+import XCTest
+@testable import {name}
+
+final class {name}Tests: XCTestCase {{
+{tests}
+}}
+",
+            name = self.name,
+            tests = tests.join("\n\n")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_layout() {
+        let package = Package::new(Path::new("out/MyViews")).unwrap();
+        assert_eq!(package.name, "MyViews");
+        assert_eq!(package.sources_dir(), Path::new("out/MyViews/Sources/MyViews"));
+        assert_eq!(package.tests_file(), Path::new("out/MyViews/Tests/MyViewsTests/MyViewsTests.swift"));
+        let manifest = package.manifest(Platform::Ios);
+        assert!(manifest.starts_with("// swift-tools-version:5.9\n"));
+        assert!(manifest.contains("    platforms: [.iOS(.v17), .macOS(.v14)],\n"));
+        assert!(manifest.contains(".testTarget(name: \"MyViewsTests\", dependencies: [\"MyViews\"]),"));
+        assert!(package.manifest(Platform::Watchos).contains("    platforms: [.watchOS(.v10)],\n"));
+        assert!(Package::new(Path::new("my-views")).unwrap_err().contains("not a valid Swift module name"));
+        assert!(Package::new(Path::new("2Views")).is_err());
+    }
+
+    #[test]
+    fn test_placeholder_tests() {
+        let tests = Package::new(Path::new("MyViews")).unwrap().tests(&["LoginView".to_string(), "HomeView".to_string()]);
+        assert!(tests.contains("@testable import MyViews\n\nfinal class MyViewsTests: XCTestCase {\n    func testLoginView() {\n        let view = LoginView()\n"));
+        assert!(tests.contains("    }\n\n    func testHomeView() {"));
+        assert!(tests.ends_with("        _ = view.body\n    }\n}\n"));
+    }
+}
@@ -0,0 +1,131 @@
+// File: src/output/picker.rs
+// Terminal UI for choosing between ranked candidate layouts, code and preview side by side.
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Paragraph};
+use ratatui::Frame;
+use std::io;
+
+/// One layout on offer: its rendered code and an ASCII preview from [`super::preview`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    pub code: String,
+    pub preview: String,
+}
+
+/// What a key press asks the picker to do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Action {
+    Select(usize),
+    Cancel,
+}
+
+/// Browsing state: which candidate is shown.
+#[derive(Clone, Debug, Default)]
+struct Picker {
+    selected: usize,
+}
+
+impl Picker {
+    fn handle(&mut self, key: KeyCode, count: usize) -> Option<Action> {
+        match key {
+            KeyCode::Down | KeyCode::Right | KeyCode::Char('j') | KeyCode::Char('l') => {
+                self.selected = (self.selected + 1) % count;
+            }
+            KeyCode::Up | KeyCode::Left | KeyCode::Char('k') | KeyCode::Char('h') => {
+                self.selected = (self.selected + count - 1) % count;
+            }
+            KeyCode::Enter => return Some(Action::Select(self.selected)),
+            KeyCode::Esc | KeyCode::Char('q') => return Some(Action::Cancel),
+            _ => {}
+        }
+        None
+    }
+
+    fn draw(&self, frame: &mut Frame, candidates: &[Candidate], screen: &str) {
+        let [header, body, footer] =
+            Layout::vertical([Constraint::Length(1), Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [code, preview] = Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(body);
+        let candidate = &candidates[self.selected];
+
+        let rank = if self.selected == 0 { " (cheapest)" } else { "" };
+        let title = format!("Candidate {} of {}{}", self.selected + 1, candidates.len(), rank);
+        frame.render_widget(Line::styled(title, Style::new().add_modifier(Modifier::BOLD)), header);
+        frame.render_widget(Paragraph::new(candidate.code.as_str()).block(Block::bordered().title("Code")), code);
+        let preview_title = format!("Preview {}", screen);
+        frame.render_widget(Paragraph::new(candidate.preview.as_str()).block(Block::bordered().title(preview_title)), preview);
+        frame.render_widget(Line::raw("←/→ or ↑/↓ browse · Enter select · Esc cancel"), footer);
+    }
+}
+
+/// Shows `candidates` full-screen until the user picks one, returning its index,
+/// or `None` if they cancel. `screen` names the previewed size, e.g. `390x844`.
+pub fn pick(candidates: &[Candidate], screen: &str) -> io::Result<Option<usize>> {
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+    let mut terminal = ratatui::try_init()?;
+    let mut picker = Picker::default();
+    // The terminal is restored whether or not the loop fails
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| picker.draw(frame, candidates, screen)) {
+            break Err(e);
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match picker.handle(key.code, candidates.len()) {
+                Some(Action::Select(index)) => break Ok(Some(index)),
+                Some(Action::Cancel) => break Ok(None),
+                None => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e),
+        }
+    };
+    ratatui::try_restore()?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn candidates() -> Vec<Candidate> {
+        ["VStack {", "HStack {"]
+            .iter()
+            .map(|code| Candidate { code: code.to_string(), preview: "+--+".to_string() })
+            .collect()
+    }
+
+    #[test]
+    fn test_keys_browse_and_select() {
+        let mut picker = Picker::default();
+        assert_eq!(picker.handle(KeyCode::Up, 3), None);
+        assert_eq!(picker.selected, 2);
+        picker.handle(KeyCode::Right, 3);
+        picker.handle(KeyCode::Char('j'), 3);
+        assert_eq!(picker.handle(KeyCode::Enter, 3), Some(Action::Select(1)));
+        assert_eq!(picker.handle(KeyCode::Esc, 3), Some(Action::Cancel));
+    }
+
+    #[test]
+    fn test_draws_code_beside_preview() {
+        let mut terminal = Terminal::new(TestBackend::new(60, 8)).unwrap();
+        let mut picker = Picker::default();
+        picker.handle(KeyCode::Down, 2);
+        terminal.draw(|frame| picker.draw(frame, &candidates(), "390x844")).unwrap();
+        let rows: Vec<String> = terminal
+            .backend()
+            .buffer()
+            .content()
+            .chunks(60)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect())
+            .collect();
+        assert!(rows[0].starts_with("Candidate 2 of 2"));
+        assert!(rows[1].contains("Code") && rows[1].contains("Preview 390x844"));
+        assert!(rows[2].contains("│HStack {") && rows[2].contains("│+--+"));
+    }
+}
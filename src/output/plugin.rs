@@ -0,0 +1,122 @@
+// File: src/output/plugin.rs
+// Output targets loaded from WebAssembly modules (`[backends] plugins`), with the `wasm-plugins`
+// feature. A plugin is named after its file: `dsl.wasm` adds `--target dsl`.
+//
+// The ABI: the module exports its `memory`, `alloc(len: i32) -> i32`, which reserves `len`
+// bytes and returns their address, and `render(ptr: i32, len: i32) -> i64`. The host writes a
+// UTF-8 JSON request to memory from `alloc` and calls `render` with it; the result holds the
+// address of the UTF-8 code rendered in its high 32 bits and its length in the low ones. The
+// request is `{"ir": ..., "options": {...}}`, where the IR is as serde writes it, e.g.
+// `{"VStack":[{"Text":"Hi"},"Spacer"]}`, and the options are `view_name`, `indent_width`,
+// `text_font`, `text_styles` (the styles of particular texts, by content), `padding`,
+// `accessibility_identifiers`, `public` and `platform`.
+use std::path::Path;
+use std::sync::Mutex;
+
+use serde_json::json;
+use wasmi::{Engine, Instance, Linker, Memory, Module, Store, TypedFunc};
+
+use crate::ast::IR;
+use crate::output::backend::RenderBackend;
+use crate::output::render::RenderOptions;
+
+/// A target rendered by a WebAssembly module.
+pub struct WasmBackend {
+    name: String,
+    store: Mutex<Store<()>>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    render: TypedFunc<(i32, i32), i64>,
+}
+
+impl WasmBackend {
+    /// Loads the plugin at `path`, checking that it has the ABI's exports.
+    pub fn load(path: &Path) -> Result<WasmBackend, String> {
+        let failed = |e: &dyn std::fmt::Display| format!("Invalid backend plugin '{}': {}", path.display(), e);
+        let name = path.file_stem().map(|stem| stem.to_string_lossy().into_owned()).unwrap_or_default();
+        let bytes = std::fs::read(path).map_err(|e| format!("Failed to read backend plugin '{}': {}", path.display(), e))?;
+        let engine = Engine::default();
+        let module = Module::new(&engine, &bytes).map_err(|e| failed(&e))?;
+        let mut store = Store::new(&engine, ());
+        let instance: Instance = Linker::<()>::new(&engine)
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|e| failed(&e))?;
+        let memory = instance.get_memory(&store, "memory").ok_or_else(|| failed(&"it exports no memory"))?;
+        let alloc = instance.get_typed_func(&store, "alloc").map_err(|e| failed(&format!("alloc: {}", e)))?;
+        let render = instance.get_typed_func(&store, "render").map_err(|e| failed(&format!("render: {}", e)))?;
+        Ok(WasmBackend { name, store: Mutex::new(store), memory, alloc, render })
+    }
+}
+
+/// The JSON request a plugin renders `ir` from.
+pub fn request(ir: &IR, options: &RenderOptions) -> String {
+    json!({
+        "ir": ir,
+        "options": {
+            "view_name": options.view_name,
+            "indent_width": options.indent_width,
+            "text_font": options.text_font,
+            "text_styles": options.text_styles,
+            "padding": options.padding,
+            "accessibility_identifiers": options.accessibility_identifiers,
+            "public": options.public,
+            "platform": options.platform.name(),
+        },
+    })
+    .to_string()
+}
+
+impl RenderBackend for WasmBackend {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn render(&self, ir: &IR, options: &RenderOptions) -> String {
+        self.try_render(ir, options).unwrap_or_else(|e| format!("// {}", e))
+    }
+
+    fn try_render(&self, ir: &IR, options: &RenderOptions) -> Result<String, String> {
+        let failed = |e: &dyn std::fmt::Display| format!("The {} plugin failed: {}", self.name, e);
+        let request = request(ir, options);
+        let mut store = self.store.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let len = i32::try_from(request.len()).map_err(|e| failed(&e))?;
+        let ptr = self.alloc.call(&mut *store, len).map_err(|e| failed(&e))?;
+        self.memory.write(&mut *store, ptr as u32 as usize, request.as_bytes()).map_err(|e| failed(&e))?;
+        let result = self.render.call(&mut *store, (ptr, len)).map_err(|e| failed(&e))? as u64;
+        let (ptr, len) = ((result >> 32) as usize, (result & 0xffff_ffff) as usize);
+        let mut code = vec![0; len];
+        self.memory.read(&*store, ptr, &mut code).map_err(|e| failed(&e))?;
+        String::from_utf8(code).map_err(|_| failed(&"the code is not UTF-8"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plugins").join(name)
+    }
+
+    #[test]
+    fn test_plugin_renders_the_request() {
+        // The fixture echoes its input
+        let backend = WasmBackend::load(&fixture("echo.wasm")).unwrap();
+        assert_eq!(backend.name(), "echo");
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::Spacer]);
+        let options = RenderOptions { view_name: "LoginView".to_string(), ..RenderOptions::default() };
+        let code = backend.try_render(&ir, &options).unwrap();
+        assert_eq!(code, request(&ir, &options));
+        assert!(code.starts_with(r#"{"ir":{"VStack":[{"Text":"Hi"},"Spacer"]},"options":{"#), "{}", code);
+        // Memory grows for requests larger than a page
+        let long = IR::Text("x".repeat(100_000));
+        assert_eq!(backend.try_render(&long, &options).unwrap(), request(&long, &options));
+    }
+
+    #[test]
+    fn test_modules_without_the_abi_are_rejected() {
+        let error = WasmBackend::load(&fixture("echo.wat")).err().unwrap();
+        assert!(error.starts_with("Invalid backend plugin"), "{}", error);
+    }
+}
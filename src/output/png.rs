@@ -0,0 +1,212 @@
+// File: src/output/png.rs
+// Picture of a simulated layout (`--preview png`): boxes, text and image placeholders drawn
+// at the frames the layout engine computed, so a layout can be reviewed without Xcode.
+use embedded_graphics::mono_font::{ascii::FONT_10X20, MonoTextStyle};
+use embedded_graphics::pixelcolor::Rgb888;
+use embedded_graphics::prelude::{Dimensions, DrawTarget, Drawable, Point, RgbColor};
+use embedded_graphics::primitives::Rectangle;
+use embedded_graphics::text::{Baseline, Text};
+use embedded_graphics::Pixel;
+use tiny_skia::{Color, Paint, PathBuilder, Pixmap, Rect as SkRect, Stroke, StrokeDash, Transform};
+
+use crate::ast::IR;
+use crate::layout::{Layout, Rect};
+
+/// Pixels per point: the previews are drawn at Retina (@2x) resolution.
+pub const SCALE: f64 = 2.0;
+
+// Size of a glyph of the bitmap font, in pixels before zooming
+const GLYPH_WIDTH: f64 = 10.0;
+const GLYPH_HEIGHT: f64 = 20.0;
+
+// iOS system colors
+const BACKGROUND: (u8, u8, u8) = (255, 255, 255);
+const SAFE_AREA: (u8, u8, u8) = (242, 242, 247);
+const STACK: (u8, u8, u8) = (199, 199, 204);
+const LABEL: (u8, u8, u8) = (0, 0, 0);
+const ACCENT: (u8, u8, u8) = (0, 122, 255);
+const PLACEHOLDER: (u8, u8, u8) = (229, 229, 234);
+const PLACEHOLDER_EDGE: (u8, u8, u8) = (142, 142, 147);
+const CUSTOM: (u8, u8, u8) = (175, 82, 222);
+
+fn paint((r, g, b): (u8, u8, u8)) -> Paint<'static> {
+    let mut paint = Paint::default();
+    paint.set_color(Color::from_rgba8(r, g, b, 255));
+    paint.anti_alias = true;
+    paint
+}
+
+// `rect`, in points, as pixels; `None` if it has no area
+fn pixels(rect: &Rect) -> Option<SkRect> {
+    let scale = |value: f64| (value * SCALE) as f32;
+    SkRect::from_xywh(scale(rect.x), scale(rect.y), scale(rect.width), scale(rect.height))
+}
+
+fn outline(pixmap: &mut Pixmap, rect: SkRect, color: (u8, u8, u8), dashed: bool) {
+    let path = PathBuilder::from_rect(rect);
+    let stroke = Stroke { width: SCALE as f32, dash: if dashed { StrokeDash::new(vec![6.0, 4.0], 0.0) } else { None }, ..Stroke::default() };
+    pixmap.stroke_path(&path, &paint(color), &stroke, Transform::identity(), None);
+}
+
+// Draws the font's glyphs onto the pixmap, each font pixel as a `zoom`-wide square
+struct Glyphs<'a> {
+    pixmap: &'a mut Pixmap,
+    origin: (f32, f32),
+    zoom: f32,
+    clip: SkRect,
+}
+
+impl Dimensions for Glyphs<'_> {
+    fn bounding_box(&self) -> Rectangle {
+        Rectangle::new(Point::zero(), embedded_graphics::geometry::Size::new(self.pixmap.width(), self.pixmap.height()))
+    }
+}
+
+impl DrawTarget for Glyphs<'_> {
+    type Color = Rgb888;
+    type Error = std::convert::Infallible;
+
+    fn draw_iter<I: IntoIterator<Item = Pixel<Rgb888>>>(&mut self, pixels: I) -> Result<(), Self::Error> {
+        for Pixel(point, color) in pixels {
+            let x = self.origin.0 + point.x as f32 * self.zoom;
+            let y = self.origin.1 + point.y as f32 * self.zoom;
+            let Some(square) = SkRect::from_xywh(x, y, self.zoom, self.zoom) else { continue };
+            if square.left() < self.clip.left() || square.right() > self.clip.right() {
+                continue;
+            }
+            let mut paint = paint((color.r(), color.g(), color.b()));
+            paint.anti_alias = false;
+            self.pixmap.fill_rect(square, &paint, Transform::identity(), None);
+        }
+        Ok(())
+    }
+}
+
+// Writes `text` in `rect`, as large as the frame fits it whole, centered or from the leading
+// edge. Text too wide for the frame even at the smallest size is cut short with `..`.
+fn label(pixmap: &mut Pixmap, text: &str, rect: SkRect, color: (u8, u8, u8), centered: bool) {
+    let mut text: String = text.chars().map(|ch| if ch.is_ascii() { ch } else { '?' }).collect();
+    let tallest = rect.height() as f64 / GLYPH_HEIGHT;
+    let widest = rect.width() as f64 / (GLYPH_WIDTH * text.len().max(1) as f64);
+    let zoom = tallest.min(widest).floor().max(1.0) as f32;
+    let fits = ((rect.width() / (GLYPH_WIDTH as f32 * zoom)).floor() as usize).max(1);
+    if text.len() > fits {
+        text.truncate(fits.saturating_sub(2));
+        text.push_str("..");
+    }
+    let width = text.len() as f32 * GLYPH_WIDTH as f32 * zoom;
+    let x = if centered { rect.left() + (rect.width() - width).max(0.0) / 2.0 } else { rect.left() };
+    let y = rect.top() + (rect.height() - GLYPH_HEIGHT as f32 * zoom).max(0.0) / 2.0;
+    let mut glyphs = Glyphs { pixmap, origin: (x.round(), y.round()), zoom, clip: rect };
+    let style = MonoTextStyle::new(&FONT_10X20, Rgb888::new(color.0, color.1, color.2));
+    let _ = Text::with_baseline(&text, Point::zero(), style, Baseline::Top).draw(&mut glyphs);
+}
+
+/// Draws `layout`, returning the picture as PNG. The safe area is shaded and stacks outlined;
+/// text is written in black, buttons in the accent color, images are crossed-out placeholders
+/// labelled with their name, and custom views dashed boxes labelled with theirs.
+pub fn render_png(layout: &Layout) -> Result<Vec<u8>, String> {
+    let (width, height) = ((layout.screen.width * SCALE).round() as u32, (layout.screen.height * SCALE).round() as u32);
+    let mut pixmap = Pixmap::new(width.max(1), height.max(1)).ok_or("The screen is too large to draw")?;
+    let (r, g, b) = BACKGROUND;
+    pixmap.fill(Color::from_rgba8(r, g, b, 255));
+    if let Some(screen) = pixels(&Rect::new(0.0, 0.0, layout.screen.width, layout.screen.height)) {
+        let bounds = pixels(&layout.bounds());
+        for band in [
+            SkRect::from_ltrb(screen.left(), screen.top(), screen.right(), bounds.map_or(screen.top(), |b| b.top())),
+            SkRect::from_ltrb(screen.left(), bounds.map_or(screen.bottom(), |b| b.bottom()), screen.right(), screen.bottom()),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            pixmap.fill_rect(band, &paint(SAFE_AREA), Transform::identity(), None);
+        }
+    }
+
+    for frame in &layout.frames {
+        let Some(rect) = pixels(&frame.rect) else { continue };
+        match &frame.node {
+            IR::VStack(_) | IR::HStack(_) => outline(&mut pixmap, rect, STACK, true),
+            IR::Spacer => {}
+            IR::Text(text) => label(&mut pixmap, text, rect, LABEL, false),
+            IR::Button(title) => {
+                outline(&mut pixmap, rect, ACCENT, false);
+                label(&mut pixmap, title, rect, ACCENT, true);
+            }
+            IR::Image(name) => {
+                pixmap.fill_rect(rect, &paint(PLACEHOLDER), Transform::identity(), None);
+                let mut cross = PathBuilder::new();
+                cross.move_to(rect.left(), rect.top());
+                cross.line_to(rect.right(), rect.bottom());
+                cross.move_to(rect.right(), rect.top());
+                cross.line_to(rect.left(), rect.bottom());
+                if let Some(cross) = cross.finish() {
+                    pixmap.stroke_path(&cross, &paint(PLACEHOLDER_EDGE), &Stroke::default(), Transform::identity(), None);
+                }
+                outline(&mut pixmap, rect, PLACEHOLDER_EDGE, false);
+                label(&mut pixmap, name, rect, LABEL, true);
+            }
+            IR::Custom { name, .. } => {
+                outline(&mut pixmap, rect, CUSTOM, true);
+                label(&mut pixmap, name, rect, CUSTOM, true);
+            }
+        }
+    }
+    pixmap.encode_png().map_err(|e| format!("Failed to encode the preview: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::{simulate_within, Insets, Metrics, Size};
+
+    fn draw(ir: &IR, safe_area: Insets) -> (Layout, Pixmap) {
+        let layout = simulate_within(ir, Size::new(390.0, 844.0), safe_area, &Metrics::default());
+        let png = render_png(&layout).unwrap();
+        (layout, Pixmap::decode_png(&png).unwrap())
+    }
+
+    fn color(pixmap: &Pixmap, x: f64, y: f64) -> (u8, u8, u8) {
+        let pixel = pixmap.pixel((x * SCALE) as u32, (y * SCALE) as u32).unwrap();
+        (pixel.red(), pixel.green(), pixel.blue())
+    }
+
+    fn count(pixmap: &Pixmap, rect: &Rect, wanted: (u8, u8, u8)) -> usize {
+        let rect = pixels(rect).unwrap();
+        let mut found = 0;
+        for y in rect.top() as u32..rect.bottom() as u32 {
+            for x in rect.left() as u32..rect.right() as u32 {
+                let pixel = pixmap.pixel(x, y).unwrap();
+                found += usize::from((pixel.red(), pixel.green(), pixel.blue()) == wanted);
+            }
+        }
+        found
+    }
+
+    #[test]
+    fn test_frames_are_drawn_at_retina_resolution() {
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::Spacer, IR::Button("Go".to_string())]);
+        let (layout, pixmap) = draw(&ir, Insets::new(47.0, 34.0, 0.0, 0.0));
+        assert_eq!((pixmap.width(), pixmap.height()), (780, 1688));
+        assert_eq!(color(&pixmap, 195.0, 10.0), SAFE_AREA);
+        assert_eq!(color(&pixmap, 195.0, 840.0), SAFE_AREA);
+        assert_eq!(color(&pixmap, 195.0, 400.0), BACKGROUND);
+
+        // The title is written in its frame, the button's label in the accent color
+        let text = &layout.frames[1].rect;
+        assert!(count(&pixmap, text, LABEL) > 20);
+        let button = &layout.frames[3].rect;
+        assert!(count(&pixmap, button, ACCENT) > 20);
+        assert_eq!(count(&pixmap, button, LABEL), 0);
+    }
+
+    #[test]
+    fn test_images_and_custom_views_are_placeholders() {
+        let ir = IR::HStack(vec![IR::Image("logo".to_string()), IR::Custom { name: "ChartView".to_string(), args: String::new() }]);
+        let (layout, pixmap) = draw(&ir, Insets::default());
+        let image = &layout.frames[1].rect;
+        assert!(count(&pixmap, image, PLACEHOLDER) > 100);
+        assert!(count(&pixmap, image, PLACEHOLDER_EDGE) > 0);
+        assert!(count(&pixmap, &layout.frames[2].rect, CUSTOM) > 20);
+    }
+}
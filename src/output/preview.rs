@@ -0,0 +1,82 @@
+// File: src/output/preview.rs
+// Character-cell sketch of a simulated layout, for comparing candidates in a terminal.
+use crate::ast::IR;
+use crate::layout::{self, Insets, Metrics, Size};
+
+// Terminal cells are about twice as tall as they are wide.
+const CELL_ASPECT: f64 = 2.0;
+
+fn label(node: &IR) -> String {
+    match node {
+        IR::Text(s) => s.clone(),
+        IR::Button(s) => format!("[{}]", s),
+        IR::Image(s) => format!("<{}>", s),
+        IR::Custom { name, .. } => name.clone(),
+        _ => String::new(),
+    }
+}
+
+/// Draws `ir` laid out on `screen`, inside `safe_area`, as a `columns`-wide grid inside a border. Each leaf view
+/// is boxed where it lands, or drawn as its bare label when its box could not hold it; text is
+/// labelled as is, buttons as `[label]` and images as `<name>`. Spacers are left blank.
+pub fn preview(ir: &IR, screen: Size, safe_area: Insets, metrics: &Metrics, columns: usize) -> String {
+    let columns = columns.max(4);
+    let rows = ((columns as f64 * screen.height / screen.width / CELL_ASPECT).round() as usize).max(2);
+    let (sx, sy) = (columns as f64 / screen.width, rows as f64 / screen.height);
+    let mut grid = vec![vec![' '; columns]; rows];
+    let col = |x: f64| ((x * sx).round().max(0.0) as usize).min(columns - 1);
+    let row = |y: f64| ((y * sy).round().max(0.0) as usize).min(rows - 1);
+
+    for frame in layout::simulate_within(ir, screen, safe_area, metrics).leaves() {
+        let rect = frame.rect;
+        let (c0, r0) = (col(rect.x), row(rect.y));
+        let c1 = col(rect.max_x()).saturating_sub(1).max(c0);
+        let r1 = row(rect.max_y()).saturating_sub(1).max(r0);
+        let text: Vec<char> = label(&frame.node).chars().collect();
+        if r1 > r0 && c1 > c0 + text.len() {
+            for r in [r0, r1] {
+                grid[r][c0..=c1].fill('-');
+            }
+            for line in &mut grid[r0..=r1] {
+                line[c0] = '|';
+                line[c1] = '|';
+            }
+            for (r, c) in [(r0, c0), (r0, c1), (r1, c0), (r1, c1)] {
+                grid[r][c] = '+';
+            }
+            let mid = if r1 > r0 + 1 { r0 + (r1 - r0) / 2 } else { r0 };
+            for (c, ch) in (c0 + 1..c1).zip(text) {
+                grid[mid][c] = ch;
+            }
+        } else {
+            // Centered on the view, shifted back inside the grid if it would run off the edge
+            let start = (c0 + c1).div_ceil(2).saturating_sub(text.len() / 2).min(columns.saturating_sub(text.len()));
+            for (c, ch) in (start..columns).zip(text) {
+                grid[r0][c] = ch;
+            }
+        }
+    }
+
+    let border = format!("+{}+", "-".repeat(columns));
+    let mut lines = vec![border.clone()];
+    lines.extend(grid.iter().map(|line| format!("|{}|", line.iter().collect::<String>())));
+    lines.push(border);
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview_boxes_leaves_where_they_land() {
+        let ir = IR::VStack(vec![IR::Text("OK".to_string()), IR::Spacer, IR::Button("Go".to_string())]);
+        let sketch = preview(&ir, Size::new(390.0, 844.0), Insets::default(), &Metrics::default(), 20);
+        let lines: Vec<&str> = sketch.lines().collect();
+        assert_eq!(lines.len(), 22 + 2);
+        assert_eq!(lines[0], format!("+{}+", "-".repeat(20)));
+        assert_eq!(lines[1], "|        +OK+        |");
+        assert_eq!(lines[21], "|        [Go]        |");
+        assert!(lines[5..20].iter().all(|line| line.trim_matches('|').trim().is_empty()));
+    }
+}
@@ -0,0 +1,114 @@
+// File: src/output/provenance.rs
+// The header comment at the top of generated files: which version of the tool wrote the
+// code, from which examples and how, so it can be traced back to its spec and found stale.
+use crate::ast::Example;
+use crate::synthesis::cache::fnv1a;
+
+const GENERATED_BY: &str = "// Generated by swiftui-synth ";
+const STRATEGY: &str = " with the ";
+const EXAMPLES: &str = "// Examples: ";
+const COMMAND: &str = "// Command: ";
+
+/// Where a generated file came from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Provenance {
+    /// Version of swiftui-synth that wrote the file.
+    pub version: String,
+    pub strategy: String,
+    /// [`fingerprint`] of the examples the code was synthesized from.
+    pub examples: String,
+    /// The command line that wrote the file, quoted for a POSIX shell.
+    pub command: String,
+}
+
+/// Stable fingerprint of parsed examples: reformatting a spec does not change it, editing
+/// its dimensions or elements does.
+pub fn fingerprint(examples: &[Example]) -> String {
+    let json = serde_json::to_string(examples).expect("examples are serializable");
+    format!("{:016x}", fnv1a(json.as_bytes()))
+}
+
+// `arg` as a POSIX shell word: bare when that is safe, else quoted
+fn quote(arg: &str) -> String {
+    let plain = |ch: char| ch.is_ascii_alphanumeric() || "_-./:=@,+%".contains(ch);
+    if !arg.is_empty() && arg.chars().all(plain) {
+        arg.to_string()
+    } else if arg.contains(['\n', '\r', '\t']) {
+        // Keeps the command on one comment line
+        let escaped: String = arg
+            .chars()
+            .map(|ch| match ch {
+                '\n' => "\\n".to_string(),
+                '\r' => "\\r".to_string(),
+                '\t' => "\\t".to_string(),
+                '\\' | '\'' => format!("\\{}", ch),
+                _ => ch.to_string(),
+            })
+            .collect();
+        format!("$'{}'", escaped)
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+impl Provenance {
+    /// Provenance of code synthesized from `examples` with `strategy` by this run, whose
+    /// arguments after the program name are `args`.
+    pub fn new(examples: &[Example], strategy: &str, args: impl IntoIterator<Item = String>) -> Provenance {
+        let command: Vec<String> = std::iter::once("swiftui-synth".to_string()).chain(args.into_iter().map(|arg| quote(&arg))).collect();
+        Provenance {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            strategy: strategy.to_string(),
+            examples: fingerprint(examples),
+            command: command.join(" "),
+        }
+    }
+
+    /// The header comment, one line per field.
+    pub fn header(&self) -> String {
+        format!(
+            "{}{}{}{} strategy\n{}{}\n{}{}",
+            GENERATED_BY, self.version, STRATEGY, self.strategy, EXAMPLES, self.examples, COMMAND, self.command
+        )
+    }
+
+    /// Reads the header back from generated `code`; `None` if it has none.
+    pub fn read(code: &str) -> Option<Provenance> {
+        let mut lines = code.lines().map(str::trim_end).skip_while(|line| !line.starts_with(GENERATED_BY));
+        let (version, strategy) = lines.next()?[GENERATED_BY.len()..].strip_suffix(" strategy")?.split_once(STRATEGY)?;
+        let examples = lines.next()?.strip_prefix(EXAMPLES)?;
+        let command = lines.next()?.strip_prefix(COMMAND)?;
+        Some(Provenance { version: version.to_string(), strategy: strategy.to_string(), examples: examples.to_string(), command: command.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::{parse_examples_all, ParseOptions};
+
+    fn examples(spec: &str) -> Vec<Example> {
+        parse_examples_all(spec, &ParseOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_formatting() {
+        let spec = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+        let reformatted = "{ (width: 390, height: 844): {\n    title: \"Hi\",\n    button: \"Go\"\n} }";
+        assert_eq!(fingerprint(&examples(spec)), fingerprint(&examples(reformatted)));
+        assert_ne!(fingerprint(&examples(spec)), fingerprint(&examples(&spec.replace("Go", "Next"))));
+    }
+
+    #[test]
+    fn test_header_round_trips() {
+        let args = ["synth", "login.spec", "--examples", "{title:\"It's\"}", "--out-dir", "Views", "", "a\nb"].map(String::from);
+        let provenance = Provenance::new(&examples("{(width:390,height:844):{title:\"Hi\"}}"), "enumerative", args);
+        assert_eq!(provenance.command, "swiftui-synth synth login.spec --examples '{title:\"It'\\''s\"}' --out-dir Views '' $'a\\nb'");
+        let header = provenance.header();
+        assert_eq!(header.lines().count(), 3);
+        assert!(header.starts_with(&format!("// Generated by swiftui-synth {} with the enumerative strategy\n// Examples: ", env!("CARGO_PKG_VERSION"))));
+        let code = format!("// swiftui-synth:begin 0\n{}\nimport SwiftUI\n", header);
+        assert_eq!(Provenance::read(&code), Some(provenance));
+        assert_eq!(Provenance::read("import SwiftUI\n"), None);
+    }
+}
@@ -1,5 +1,14 @@
 // File: src/output/render.rs
-use crate::ast::IR;
+use std::collections::BTreeMap;
+
+use crate::ast::ir::background::Background;
+use crate::ast::ir::modifier::{self, Modifier};
+use crate::ast::{Value, IR};
+use crate::layout::image_sizing::ImageSizing;
+use crate::layout::text_style::Wrapping;
+use crate::layout::{Metrics, TextFit};
+use crate::platform::Platform;
+use crate::synthesis::flow::route_case;
 
 // Helper function to normalize whitespace for consistent string comparisons
 // Removes trailing whitespace from each line and ensures single \n line endings.
@@ -10,12 +19,275 @@ fn normalize_whitespace_internal(s: &str) -> String {
         .join("\n")
 }
 
-pub fn render_swiftui(ir: &IR) -> String {
-    fn render(ir: &IR, indent: usize) -> String {
-        let pad = " ".repeat(indent * 4);
+/// SwiftUI's kinds of sensory feedback, as `.sensoryFeedback` names them.
+pub const SENSORY_FEEDBACKS: &[&str] =
+    &["impact", "selection", "success", "warning", "error", "increase", "decrease", "start", "stop", "alignment", "levelChange"];
+
+/// Style knobs for the emitted code. The defaults reproduce the classic output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RenderOptions {
+    /// Spaces per indentation level.
+    pub indent_width: usize,
+    /// Text style used for `Text` views, e.g. `title` renders `.font(.title)`.
+    pub text_font: String,
+    /// Text styles of particular texts, by their content, in place of `text_font` (see
+    /// [`crate::layout::text_style::classify`]).
+    pub text_styles: BTreeMap<String, String>,
+    /// Whether particular texts wrap or stay on one line, by their content (see
+    /// [`crate::layout::text_style::wrapping`]); others get no line modifiers.
+    pub text_wrapping: BTreeMap<String, Wrapping>,
+    /// How particular images are scaled and framed, by their name (see
+    /// [`crate::layout::image_sizing::sizing`]); others are emitted at their asset's size.
+    pub image_sizing: BTreeMap<String, ImageSizing>,
+    /// Corner radii, borders and shadows the examples state for particular views, by the view
+    /// (see [`crate::ast::ir::modifier::stated`]).
+    pub view_modifiers: BTreeMap<IR, Vec<Modifier>>,
+    /// Gradient or material behind the root view, which then fills the screen (see
+    /// [`crate::ast::ir::background::stated`]).
+    pub background: Option<Background>,
+    /// Animation of views with a transition but no animation of their own, e.g. `spring`.
+    pub animation: String,
+    /// Sensory feedback buttons give when tapped, one of [`SENSORY_FEEDBACKS`] such as
+    /// `impact`; none without it.
+    pub haptics: Option<String>,
+    /// Whether stacks, texts and buttons get a `.padding()` modifier.
+    pub padding: bool,
+    /// Whether stacks and paddings spell out the stack spacing and padding of `metrics`, as
+    /// `VStack(spacing: 12)` and `.padding(16)`, rather than leave them to the system. Set when
+    /// they are snapped to a spacing scale (see [`crate::layout::spacing`]).
+    pub explicit_spacing: bool,
+    /// Whether the root view gets `.ignoresSafeArea()`, for layouts whose content extends
+    /// under the status bar or home indicator (see [`crate::layout::ignores_safe_area`]).
+    pub ignores_safe_area: bool,
+    /// Whether the root view goes in a `ScrollView` and texts get `.minimumScaleFactor`, for
+    /// layouts that outgrow the screen at large Dynamic Type sizes (see [`crate::layout::text_fit`]).
+    pub text_fit: TextFit,
+    /// Whether view structs and routes are declared `public`, for code compiled into a
+    /// library that apps import.
+    pub public: bool,
+    /// Whether texts, buttons and images get an `.accessibilityIdentifier` for UI tests to
+    /// find them by (see [`accessibility_identifier`]).
+    pub accessibility_identifiers: bool,
+    /// Platform the code is for, which picks some idioms: on watchOS a vertical layout
+    /// scrolls with the Digital Crown, on tvOS rows of buttons are focus sections.
+    pub platform: Platform,
+    /// Name of the view the code is for, e.g. `LoginView`. Targets whose screens are other
+    /// types name them after it, e.g. `LoginViewController`.
+    pub view_name: String,
+    /// Layout constants, for targets that spell out sizes and spacing SwiftUI leaves to the
+    /// system.
+    pub metrics: Metrics,
+    /// Namespace of the generated types, for targets that declare one (`--namespace`).
+    pub namespace: Option<String>,
+}
+
+impl RenderOptions {
+    /// The text style of a `Text` showing `text`.
+    pub fn text_style(&self, text: &str) -> &str {
+        self.text_styles.get(text).unwrap_or(&self.text_font)
+    }
+
+    /// The line modifiers of a `Text` showing `text`, without their leading dot.
+    pub fn line_modifiers(&self, text: &str) -> Vec<String> {
+        self.text_wrapping.get(text).map(Wrapping::modifiers).unwrap_or_default()
+    }
+
+    /// The arguments of a stack, `(spacing: 12)` when spacing is spelled out, else none.
+    pub fn stack_arguments(&self) -> String {
+        if self.explicit_spacing { format!("(spacing: {})", self.metrics.stack_spacing) } else { String::new() }
+    }
+
+    /// The argument of `.padding`, `16` when padding is spelled out, else none.
+    pub fn padding_length(&self) -> String {
+        if self.explicit_spacing { self.metrics.padding.to_string() } else { String::new() }
+    }
+
+    /// The sizing modifiers of an `Image` of the asset `name`, without their leading dot.
+    pub fn image_modifiers(&self, name: &str) -> Vec<String> {
+        self.image_sizing.get(name).map(ImageSizing::modifiers).unwrap_or_default()
+    }
+
+    /// The corner radius, border and shadow modifiers of `view`, without their leading dot.
+    pub fn decorations(&self, view: &IR) -> Vec<String> {
+        self.view_modifiers.get(view).map_or(Vec::new(), |modifiers| modifier::swiftui(modifiers, &self.animation))
+    }
+
+    /// The transition `view` is inserted with once the screen appears, if it has one.
+    pub fn transition(&self, view: &IR) -> Option<&str> {
+        self.view_modifiers.get(view).and_then(|modifiers| modifier::transition(modifiers))
+    }
+
+    /// The action and `.sensoryFeedback` modifier, without its leading dot, of a button titled
+    /// `title` when buttons give feedback: the action toggles the state variable that
+    /// triggers it (see [`feedback_trigger`]).
+    pub fn feedback(&self, title: &str, taken: &mut Vec<String>) -> Option<(String, String)> {
+        let kind = self.haptics.as_ref()?;
+        let trigger = feedback_trigger(title, taken);
+        Some((format!("{}.toggle()", trigger), format!("sensoryFeedback(.{}, trigger: {})", kind, trigger)))
+    }
+
+    /// Whether any view of `ir` animates once the screen appears.
+    pub fn animates(&self, ir: &IR) -> bool {
         match ir {
+            IR::VStack(children) | IR::HStack(children) => children.iter().any(|child| self.animates(child)),
+            leaf => self.view_modifiers.get(leaf).is_some_and(|modifiers| modifier::animates(modifiers)),
+        }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            indent_width: 4,
+            text_font: "title".to_string(),
+            text_styles: BTreeMap::new(),
+            text_wrapping: BTreeMap::new(),
+            image_sizing: BTreeMap::new(),
+            view_modifiers: BTreeMap::new(),
+            background: None,
+            animation: "default".to_string(),
+            haptics: None,
+            padding: true,
+            explicit_spacing: false,
+            ignores_safe_area: false,
+            text_fit: TextFit::default(),
+            public: false,
+            accessibility_identifiers: false,
+            platform: Platform::default(),
+            view_name: view_name(None),
+            metrics: Metrics::default(),
+            namespace: None,
+        }
+    }
+}
+
+/// Swift string literal for `value`, quoted. Quotes, backslashes, line breaks, tabs and other
+/// control characters are escaped, so the literal stays on one line; everything else is
+/// written as is, emoji included.
+pub fn string_literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\r' => literal.push_str("\\r"),
+            '\0' => literal.push_str("\\0"),
+            _ if ch.is_control() => literal.push_str(&format!("\\u{{{:X}}}", ch as u32)),
+            _ => literal.push(ch),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Swift literal for a value given to a component or custom view: `4`, `4.5`, `true`, a
+/// string literal, or an array of these such as `["a", "b"]`. Dictionaries and wildcards have
+/// none.
+pub fn value_literal(value: &Value) -> Option<String> {
+    match value {
+        Value::Int(n) => Some(n.to_string()),
+        // `{:?}` keeps the decimal point, so `4.0` stays a `Double`
+        Value::Float(x) => Some(format!("{:?}", x)),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::String(s) => Some(string_literal(s)),
+        Value::Array(items) => {
+            let items: Option<Vec<String>> = items.iter().map(value_literal).collect();
+            Some(format!("[{}]", items?.join(", ")))
+        }
+        Value::Dict(_) | Value::Any => None,
+    }
+}
+
+/// Deterministic identifier of a text, button or image: its kind and its content in kebab
+/// case, e.g. `button.sign-in`. One already in `taken` gets a number, `text.hi.2`; the result
+/// is added to `taken`. Stacks, spacers and custom views have none.
+pub fn accessibility_identifier(ir: &IR, taken: &mut Vec<String>) -> Option<String> {
+    let (kind, content) = match ir {
+        IR::Text(text) => ("text", text),
+        IR::Button(title) => ("button", title),
+        IR::Image(name) => ("image", name),
+        _ => return None,
+    };
+    let words: Vec<String> = content
+        .split(|ch: char| !ch.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+    let base = match words.is_empty() {
+        true => kind.to_string(),
+        false => format!("{}.{}", kind, words.join("-")),
+    };
+    let identifier = (1..)
+        .map(|n| if n == 1 { base.clone() } else { format!("{}.{}", base, n) })
+        .find(|identifier| !taken.contains(identifier))
+        .expect("some number is free");
+    taken.push(identifier.clone());
+    Some(identifier)
+}
+
+/// The state variable a button titled `title` toggles to trigger its feedback: its title in
+/// camel case, then `Tapped`, e.g. `signInTapped`. One already in `taken` gets a number,
+/// `signInTapped2`; the result is added to `taken`.
+pub fn feedback_trigger(title: &str, taken: &mut Vec<String>) -> String {
+    let words: Vec<String> = title
+        .split(|ch: char| !ch.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .enumerate()
+        .map(|(index, word)| {
+            let word = word.to_lowercase();
+            match index {
+                0 => word,
+                _ => word[..1].to_uppercase() + &word[1..],
+            }
+        })
+        .collect();
+    let base = match words.concat() {
+        stem if stem.starts_with(|ch: char| ch.is_ascii_alphabetic()) => format!("{}Tapped", stem),
+        _ => "buttonTapped".to_string(),
+    };
+    let trigger = (1..)
+        .map(|n| if n == 1 { base.clone() } else { format!("{}{}", base, n) })
+        .find(|trigger| !taken.contains(trigger))
+        .expect("some number is free");
+    taken.push(trigger.clone());
+    trigger
+}
+
+// The state variables triggering the feedback of the buttons of rendered `code`
+fn feedback_triggers(code: &str) -> Vec<String> {
+    let mut triggers: Vec<String> = Vec::new();
+    for line in code.lines().map(str::trim).filter(|line| line.starts_with(".sensoryFeedback(")) {
+        let Some(trigger) = line.rsplit_once("trigger: ").and_then(|(_, rest)| rest.strip_suffix(')')) else { continue };
+        if !triggers.iter().any(|given| given == trigger) {
+            triggers.push(trigger.to_string());
+        }
+    }
+    triggers
+}
+
+pub fn render_swiftui(ir: &IR) -> String {
+    render_swiftui_with(ir, &RenderOptions::default())
+}
+
+pub fn render_swiftui_with(ir: &IR, options: &RenderOptions) -> String {
+    fn render(ir: &IR, indent: usize, options: &RenderOptions, taken: &mut Vec<String>) -> String {
+        let pad = " ".repeat(indent * options.indent_width);
+        let modifier_pad = " ".repeat(options.indent_width);
+        let padding = |pad: &str| if options.padding { format!("{}{}.padding({})\n", pad, modifier_pad, options.padding_length()) } else { String::new() };
+        let identifier = match options.accessibility_identifiers {
+            true => accessibility_identifier(ir, taken)
+                .map_or(String::new(), |id| format!("{}{}.accessibilityIdentifier({})\n", pad, modifier_pad, string_literal(&id))),
+            false => String::new(),
+        };
+        let lines = |modifiers: Vec<String>| modifiers.iter().map(|modifier| format!("{}{}.{}\n", pad, modifier_pad, modifier)).collect::<String>();
+        // Corners, borders and shadows go around the padding
+        let decorations = lines(options.decorations(ir));
+        let code = match ir {
             IR::VStack(children) => {
-                let mut s = format!("{}VStack {{\n", pad);
+                let mut s = format!("{}VStack{} {{\n", pad, options.stack_arguments());
                 for child in children {
                     // Ensure Spacer and Image are not further indented inside VStack/HStack rendering
                     let child_indent = match child {
@@ -26,17 +298,16 @@ pub fn render_swiftui(ir: &IR) -> String {
                      if matches!(child, IR::Spacer) && !s.ends_with("{\n") && !s.ends_with("\n\n") {
                         // s.push('\n'); // Avoid double newlines if Spacer follows another element directly
                      }
-                    s.push_str(&render(child, child_indent));
+                    s.push_str(&render(child, child_indent, options, taken));
                 }
                 s.push_str(&format!("{}}}\n", pad));
-                s.push_str(&format!("{}.padding()", pad)); // Add padding modifier to the Stack
-                if indent == 0 { // Add final newline only for the top-level element
-                    s.push('\n');
+                if options.padding {
+                    s.push_str(&format!("{}.padding({})\n", pad, options.padding_length())); // Add padding modifier to the Stack
                 }
                 s
             }
             IR::HStack(children) => {
-                let mut s = format!("{}HStack {{\n", pad);
+                let mut s = format!("{}HStack{} {{\n", pad, options.stack_arguments());
                 for child in children {
                      let child_indent = match child {
                         IR::Spacer | IR::Image(_) => indent + 1,
@@ -46,38 +317,253 @@ pub fn render_swiftui(ir: &IR) -> String {
                     // if matches!(child, IR::Spacer) && !s.ends_with("{\n") && !s.ends_with("\n\n") {
                        // s.push('\n');
                     // }
-                    s.push_str(&render(child, child_indent));
+                    s.push_str(&render(child, child_indent, options, taken));
                 }
                 s.push_str(&format!("{}}}\n", pad));
-                s.push_str(&format!("{}.padding()", pad)); // Add padding modifier to the Stack
-                if indent == 0 { // Add final newline only for the top-level element
-                     s.push('\n');
+                if options.padding {
+                    s.push_str(&format!("{}.padding({})\n", pad, options.padding_length())); // Add padding modifier to the Stack
+                }
+                // The Siri Remote moves focus along a row of buttons before leaving it
+                if options.platform == Platform::Tvos && children.iter().any(|child| matches!(child, IR::Button(_))) {
+                    s.push_str(&format!("{}.focusSection()\n", pad));
                 }
                 s
             }
             IR::Text(text) => format!(
                 // Ensure modifiers are indented relative to the Text element
-                "{}Text(\"{}\")\n{}    .font(.title)\n{}    .padding()\n",
-                pad, text.replace("\"", "\\\""),
-                pad, // Indentation for first modifier
-                pad  // Indentation for second modifier
-            ),
-            IR::Button(label) => format!(
-                 // Ensure modifiers are indented relative to the Button element
-                "{}Button(\"{}\") {{ }}\n{}    .padding()\n",
-                pad, label.replace("\"", "\\\""),
-                pad // Indentation for modifier
+                "{}Text({})\n{}{}.font(.{})\n{}{}{}{}{}",
+                pad, string_literal(text),
+                pad, modifier_pad, options.text_style(text), // Indentation for first modifier
+                lines(options.line_modifiers(text)),
+                options.text_fit.minimum_scale_factor.map_or(String::new(), |factor| {
+                    format!("{}{}.minimumScaleFactor({})\n", pad, modifier_pad, factor)
+                }),
+                identifier,
+                padding(&pad),
+                decorations
             ),
+            IR::Button(label) => {
+                let (action, feedback) = match options.feedback(label, taken) {
+                    Some((action, feedback)) => (format!("{} ", action), lines(vec![feedback])),
+                    None => (String::new(), String::new()),
+                };
+                format!(
+                    // Ensure modifiers are indented relative to the Button element
+                    "{}Button({}) {{ {}}}\n{}{}{}{}",
+                    pad, string_literal(label), action,
+                    identifier,
+                    padding(&pad),
+                    feedback,
+                    decorations
+                )
+            }
             IR::Image(name) => format!(
                 // Image usually doesn't have padding/font modifiers directly in this simple case
-                "{}Image(\"{}\")\n",
-                pad, name.replace("\"", "\\\"")
+                "{}Image({})\n{}{}{}",
+                pad, string_literal(name),
+                lines(options.image_modifiers(name)),
+                identifier,
+                decorations
             ),
             IR::Spacer => format!("{}Spacer()\n", pad),
+            IR::Custom { name, args } => format!("{}{}({})\n{}", pad, name, args, decorations),
+        };
+        // A view with a transition is inserted once the screen appears
+        match options.transition(ir) {
+            Some(_) => format!(
+                "{}if {} {{\n{}{}}}\n",
+                pad,
+                modifier::APPEAR_STATE,
+                code.lines().map(|line| format!("{}{}\n", modifier_pad, line)).collect::<String>(),
+                pad
+            ),
+            None => code,
+        }
+    }
+    wrap_root(ir, &render(ir, 0, options, &mut Vec::new()), options)
+}
+
+// The modifier of a root view whose views animate, turning their state on once it appears
+fn appear() -> String {
+    format!(".onAppear {{ {} = true }}", modifier::APPEAR_STATE)
+}
+
+/// `code`, the root view `ir` rendered at column 0, in what the options give a whole screen:
+/// a `ScrollView`, a background, `.ignoresSafeArea()` and, when its views animate, the
+/// `.onAppear` that starts them.
+pub fn wrap_root(ir: &IR, code: &str, options: &RenderOptions) -> String {
+    // A watch screen is too small for most layouts; the Digital Crown scrolls a vertical one
+    let scrolls = options.text_fit.scrolls || (options.platform == Platform::Watchos && matches!(ir, IR::VStack(_)));
+    let mut code = match scrolls {
+        true => {
+            let pad = " ".repeat(options.indent_width);
+            let lines: Vec<String> = code.lines().map(|line| if line.is_empty() { String::new() } else { format!("{}{}", pad, line) }).collect();
+            format!("ScrollView {{\n{}\n}}\n", lines.join("\n"))
         }
+        false => format!("{}\n", code.trim_end()),
+    };
+    // Containers take their modifiers at their own indentation, other views one level deeper
+    let container = scrolls || matches!(ir, IR::VStack(_) | IR::HStack(_));
+    let pad = if container { String::new() } else { " ".repeat(options.indent_width) };
+    if let Some(background) = &options.background {
+        // The root fills the screen so the background does
+        code.push_str(&format!("{}.frame(maxWidth: .infinity, maxHeight: .infinity)\n", pad));
+        code.push_str(&format!("{}.{}\n", pad, background.swiftui()));
+    }
+    if options.ignores_safe_area {
+        code.push_str(&format!("{}.ignoresSafeArea()\n", pad));
+    }
+    if options.animates(ir) {
+        code.push_str(&format!("{}{}\n", pad, appear()));
     }
     // Normalize the final output to ensure consistent line endings and no trailing whitespace
-    normalize_whitespace_internal(&render(ir, 0))
+    normalize_whitespace_internal(&code)
+}
+
+/// Name of the view struct generated for a screen: `Login` becomes `LoginView`, and an
+/// unnamed screen `ContentView`.
+pub fn view_name(screen: Option<&str>) -> String {
+    match screen {
+        Some(name) if name.ends_with("View") => name.to_string(),
+        Some(name) => format!("{}View", name),
+        None => "ContentView".to_string(),
+    }
+}
+
+// Indents every non-empty line of `code` by `levels` indentation levels.
+fn indent(code: &str, levels: usize, options: &RenderOptions) -> String {
+    let pad = " ".repeat(levels * options.indent_width);
+    code.lines()
+        .map(|line| if line.is_empty() { String::new() } else { format!("{}{}", pad, line) })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Wraps rendered layout code in a view struct named `name`, as it appears in its own file.
+pub fn render_view(name: &str, body: &str, options: &RenderOptions) -> String {
+    let pad = " ".repeat(options.indent_width);
+    // A public view also needs a public initializer to be created from another module
+    let (access, init) = match options.public {
+        true => ("public ", format!("{}public init() {{}}\n\n", pad)),
+        false => ("", String::new()),
+    };
+    // The state the views' animations are driven by, which the root turns on, and the state
+    // each button toggles to give feedback
+    let mut states = match body.contains(&appear()) {
+        true => vec![modifier::APPEAR_STATE.to_string()],
+        false => Vec::new(),
+    };
+    states.extend(feedback_triggers(body));
+    let state: String = match states.is_empty() {
+        true => String::new(),
+        false => format!("{}\n", states.iter().map(|state| format!("{}@State private var {} = false\n", pad, state)).collect::<String>()),
+    };
+    // A stub of each handler method the views' gestures call, for the app to fill in
+    let handlers: String = modifier::handlers(body)
+        .iter()
+        .map(|(handler, gesture)| format!("\n\n{}private func {}() {{\n{}{}// Handle the {}\n{}}}", pad, handler, pad, pad, gesture, pad))
+        .collect();
+    format!(
+        "{}struct {}: View {{\n{}{}{}{}var body: some View {{\n{}\n{}}}{}\n}}",
+        access,
+        name,
+        state,
+        init,
+        pad,
+        access,
+        indent(body, 2, options),
+        pad,
+        handlers
+    )
+}
+
+/// Renders an Xcode preview of the view `name`. With `routes`, it is shown in a
+/// `NavigationStack` that resolves them, so its links can be followed in the canvas.
+pub fn render_preview(name: &str, routes: bool, options: &RenderOptions) -> String {
+    let pad = " ".repeat(options.indent_width);
+    let view = match routes {
+        true => format!("NavigationStack {{\n{}\n}}", indent(&format!("{}()\n{}.routeDestinations()", name, pad), 1, options)),
+        false => format!("{}()", name),
+    };
+    format!("#Preview {{\n{}\n}}", indent(&view, 1, options))
+}
+
+/// How the generated `App.swift` presents the screens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AppStyle {
+    /// The first screen at the root of a `NavigationStack`.
+    Stack,
+    /// Every screen as a tab of a `TabView`, labelled with its name.
+    Tabs,
+}
+
+impl std::str::FromStr for AppStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "stack" => Ok(AppStyle::Stack),
+            "tabs" => Ok(AppStyle::Tabs),
+            other => Err(format!("Unknown app style '{}': expected stack or tabs", other)),
+        }
+    }
+}
+
+/// Renders the `Route` enum navigation links push, one case per destination screen, and a
+/// `routeDestinations()` modifier showing each route's screen (see [`flow`](crate::synthesis::flow)).
+pub fn render_routes(destinations: &[&str], options: &RenderOptions) -> String {
+    let cases: Vec<String> = destinations.iter().map(|screen| format!("case {}", route_case(screen))).collect();
+    let arms: Vec<String> = destinations
+        .iter()
+        .map(|screen| format!("case .{}:\n{}()", route_case(screen), indent(&view_name(Some(screen)), 1, options)))
+        .collect();
+    let switch = format!("switch route {{\n{}\n}}", arms.join("\n"));
+    let destination = format!("navigationDestination(for: Route.self) {{ route in\n{}\n}}", indent(&switch, 1, options));
+    let access = if options.public { "public " } else { "" };
+    let modifier = format!("{}func routeDestinations() -> some View {{\n{}\n}}", access, indent(&destination, 1, options));
+    format!(
+        "{}enum Route: Hashable {{\n{}\n}}\n\nextension View {{\n{}\n}}",
+        access,
+        indent(&cases.join("\n"), 1, options),
+        indent(&modifier, 1, options)
+    )
+}
+
+/// Renders an `App.swift` entry point showing the given screens (see [`view_name`]). With
+/// `routes`, each stack resolves the routes of [`render_routes`], held in a `NavigationPath`.
+pub fn render_app(screens: &[Option<&str>], style: AppStyle, routes: bool, options: &RenderOptions) -> String {
+    let pad = " ".repeat(options.indent_width);
+    let stack = |screen: Option<&str>, path: &str| {
+        let destinations = if routes { format!("\n{}.routeDestinations()", pad) } else { String::new() };
+        format!("NavigationStack{} {{\n{}\n}}", path, indent(&format!("{}(){}", view_name(screen), destinations), 1, options))
+    };
+    let root = match style {
+        AppStyle::Stack => stack(screens.first().copied().flatten(), if routes { "(path: $path)" } else { "" }),
+        AppStyle::Tabs => {
+            let tabs: Vec<String> = screens
+                .iter()
+                .map(|screen| {
+                    let label = screen.unwrap_or("Content");
+                    let view = if routes { stack(*screen, "") } else { format!("{}()", view_name(*screen)) };
+                    format!("{}\n{}.tabItem {{ Text(\"{}\") }}", view, pad, label)
+                })
+                .collect();
+            format!("TabView {{\n{}\n}}", indent(&tabs.join("\n"), 1, options))
+        }
+    };
+    let scene = format!("WindowGroup {{\n{}\n}}", indent(&root, 1, options));
+    let path = if routes && style == AppStyle::Stack {
+        format!("{}@State private var path = NavigationPath()\n\n", pad)
+    } else {
+        String::new()
+    };
+    format!(
+        "import SwiftUI\n\n@main\nstruct SynthesizedApp: App {{\n{}{}var body: some Scene {{\n{}\n{}}}\n}}",
+        path,
+        pad,
+        indent(&scene, 2, options),
+        pad
+    )
 }
 
 #[cfg(test)]
@@ -139,6 +625,60 @@ mod tests {
         assert_eq!(render_swiftui(&ir), expected);
     }
 
+    #[test]
+    fn test_render_view_and_app() {
+        let body = render_swiftui(&IR::VStack(vec![IR::Text("Hi".to_string())]));
+        assert_eq!(view_name(Some("Login")), "LoginView");
+        assert_eq!(view_name(Some("HomeView")), "HomeView");
+        assert_eq!(view_name(None), "ContentView");
+        let view = render_view("LoginView", &body, &RenderOptions::default());
+        assert!(view.starts_with("struct LoginView: View {\n    var body: some View {\n        VStack {\n"));
+        assert!(view.ends_with("        .padding()\n    }\n}"));
+        assert_eq!(crate::input::swift::parse_swiftui(&view).unwrap(), IR::VStack(vec![IR::Text("Hi".to_string())]));
+        let public = RenderOptions { public: true, ..RenderOptions::default() };
+        let view = render_view("LoginView", &body, &public);
+        assert!(view.starts_with("public struct LoginView: View {\n    public init() {}\n\n    public var body: some View {\n        VStack {\n"));
+        assert_eq!(crate::input::swift::parse_swiftui(&view).unwrap(), IR::VStack(vec![IR::Text("Hi".to_string())]));
+
+        assert_eq!(render_preview("LoginView", false, &RenderOptions::default()), "#Preview {\n    LoginView()\n}");
+        assert_eq!(
+            render_preview("LoginView", true, &RenderOptions::default()),
+            "#Preview {\n    NavigationStack {\n        LoginView()\n            .routeDestinations()\n    }\n}"
+        );
+
+        let screens = [Some("Login"), Some("Home")];
+        let app = render_app(&screens, AppStyle::Stack, false, &RenderOptions::default());
+        assert!(app.contains("@main\nstruct SynthesizedApp: App {"));
+        assert!(app.contains("            NavigationStack {\n                LoginView()\n            }\n"));
+        let app = render_app(&screens, AppStyle::Tabs, false, &RenderOptions::default());
+        assert!(app.contains("                HomeView()\n                    .tabItem { Text(\"Home\") }\n"));
+    }
+
+    #[test]
+    fn test_render_routes() {
+        let expected = "enum Route: Hashable {
+    case home
+}
+
+extension View {
+    func routeDestinations() -> some View {
+        navigationDestination(for: Route.self) { route in
+            switch route {
+            case .home:
+                HomeView()
+            }
+        }
+    }
+}";
+        assert_eq!(render_routes(&["Home"], &RenderOptions::default()), expected);
+        let public = expected.replace("enum Route", "public enum Route").replace("    func route", "    public func route");
+        assert_eq!(render_routes(&["Home"], &RenderOptions { public: true, ..RenderOptions::default() }), public);
+
+        let app = render_app(&[Some("Login"), Some("Home")], AppStyle::Stack, true, &RenderOptions::default());
+        assert!(app.contains("struct SynthesizedApp: App {\n    @State private var path = NavigationPath()\n\n    var body"));
+        assert!(app.contains("NavigationStack(path: $path) {\n                LoginView()\n                    .routeDestinations()\n"));
+    }
+
     #[test]
     fn test_render_image() {
         let ir = IR::Image("icon".to_string());
@@ -148,6 +688,23 @@ mod tests {
         assert_eq!(render_swiftui(&ir), expected);
     }
 
+    #[test]
+    fn test_string_literals_are_escaped() {
+        assert_eq!(string_literal("Welcome\nback, \"you\" 👋"), r#""Welcome\nback, \"you\" 👋""#);
+        assert_eq!(string_literal("C:\\tmp\t\u{7}"), r#""C:\\tmp\t\u{7}""#);
+        let ir = IR::VStack(vec![IR::Text("Line one\nLine two".to_string()), IR::Button("Go".to_string())]);
+        assert!(render_swiftui(&ir).contains("    Text(\"Line one\\nLine two\")\n"));
+    }
+
+    #[test]
+    fn test_value_literals() {
+        let tags = Value::Array(vec![Value::String("new".to_string()), Value::String("sale".to_string())]);
+        assert_eq!(value_literal(&tags).as_deref(), Some(r#"["new", "sale"]"#));
+        assert_eq!(value_literal(&Value::Float(4.0)).as_deref(), Some("4.0"));
+        assert_eq!(value_literal(&Value::Array(vec![Value::Int(1), Value::Bool(false)])).as_deref(), Some("[1, false]"));
+        assert_eq!(value_literal(&Value::Any), None);
+    }
+
     #[test]
     fn test_render_title_only() {
         let ir = IR::VStack(vec![
@@ -211,7 +768,220 @@ mod tests {
         assert_eq!(rendered, expected);
     }
 
+    #[test]
+    fn test_render_custom_verbatim() {
+        let ir = IR::VStack(vec![
+            IR::Custom { name: "MyChartView".to_string(), args: "data: points".to_string() },
+            IR::Spacer,
+        ]);
+        let expected = normalize_whitespace(
+            "VStack {
+    MyChartView(data: points)
+    Spacer()
+}
+.padding()"
+        );
+        assert_eq!(render_swiftui(&ir), expected);
+    }
+
+    #[test]
+    fn test_texts_take_their_own_style_and_wrapping() {
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::Text("Fine print".to_string())]);
+        let text_styles = BTreeMap::from([("Fine print".to_string(), "caption".to_string())]);
+        let options = RenderOptions { text_styles, padding: false, ..RenderOptions::default() };
+        let code = render_swiftui_with(&ir, &options);
+        assert!(code.contains("Text(\"Hi\")\n        .font(.title)\n    Text(\"Fine print\")\n        .font(.caption)"), "{}", code);
+        assert_eq!(options.text_style("Hi"), "title");
+
+        let text_wrapping = BTreeMap::from([("Hi".to_string(), Wrapping::SingleLine)]);
+        let code = render_swiftui_with(&ir, &RenderOptions { text_wrapping, ..options });
+        assert!(code.contains("    Text(\"Hi\")\n        .font(.title)\n        .lineLimit(1)\n        .truncationMode(.tail)\n    Text(\"Fine print\")\n        .font(.caption)\n}"), "{}", code);
+    }
+
+    #[test]
+    fn test_images_with_a_stated_size_are_framed() {
+        use crate::layout::image_sizing::ContentMode;
+        let ir = IR::VStack(vec![IR::Image("hero".to_string()), IR::Image("icon".to_string())]);
+        let hero = ImageSizing { mode: ContentMode::Fill, width: Some(390.0), fills_width: false, height: Some(200.0) };
+        let options = RenderOptions { image_sizing: BTreeMap::from([("hero".to_string(), hero)]), accessibility_identifiers: true, ..RenderOptions::default() };
+        let code = render_swiftui_with(&ir, &options);
+        assert!(
+            code.contains(concat!(
+                "    Image(\"hero\")\n        .resizable()\n        .scaledToFill()\n        .frame(width: 390, height: 200)\n        .clipped()\n",
+                "        .accessibilityIdentifier(\"image.hero\")\n    Image(\"icon\")\n"
+            )),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_stated_decorations_go_around_the_padding() {
+        let ir = IR::VStack(vec![IR::Button("Buy".to_string()), IR::Text("Buy".to_string())]);
+        let modifiers = vec![Modifier::CornerRadius(12.0), Modifier::Shadow { radius: 4.0, x: 0.0, y: 0.0 }];
+        let options = RenderOptions { view_modifiers: BTreeMap::from([(IR::Button("Buy".to_string()), modifiers)]), ..RenderOptions::default() };
+        let code = render_swiftui_with(&ir, &options);
+        assert!(
+            code.contains("    Button(\"Buy\") { }\n        .padding()\n        .clipShape(RoundedRectangle(cornerRadius: 12))\n        .shadow(radius: 4)\n    Text(\"Buy\")\n"),
+            "{}",
+            code
+        );
+    }
+
+    #[test]
+    fn test_a_background_fills_the_screen() {
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string())]);
+        let options = RenderOptions { background: Some(Background::Material("ultraThinMaterial".to_string())), ignores_safe_area: true, ..RenderOptions::default() };
+        let code = render_swiftui_with(&ir, &options);
+        assert!(code.ends_with("}\n.padding()\n.frame(maxWidth: .infinity, maxHeight: .infinity)\n.background(.ultraThinMaterial)\n.ignoresSafeArea()"), "{}", code);
+    }
+
+    #[test]
+    fn test_views_animate_once_the_screen_appears() {
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::Button("Go".to_string())]);
+        let view_modifiers = BTreeMap::from([
+            (IR::Text("Hi".to_string()), vec![Modifier::Transition("opacity".to_string())]),
+            (IR::Button("Go".to_string()), vec![Modifier::Animation("bouncy".to_string())]),
+        ]);
+        let options = RenderOptions { view_modifiers, animation: "spring".to_string(), ..RenderOptions::default() };
+        let code = render_swiftui_with(&ir, &options);
+        assert!(
+            code.contains("    if appeared {\n        Text(\"Hi\")\n            .font(.title)\n            .padding()\n            .transition(.opacity.animation(.spring))\n    }\n"),
+            "{}",
+            code
+        );
+        assert!(code.contains("        .animation(.bouncy, value: appeared)\n}\n.padding()\n.onAppear { appeared = true }"), "{}", code);
+        let view = render_view("HelloView", &code, &options);
+        assert!(view.starts_with("struct HelloView: View {\n    @State private var appeared = false\n\n    var body: some View {\n"), "{}", view);
+        assert!(!render_view("HelloView", &render_swiftui(&ir), &options).contains("@State"));
+    }
+
+    #[test]
+    fn test_buttons_give_haptic_feedback() {
+        let ir = IR::HStack(vec![IR::Button("Sign in".to_string()), IR::Button("Sign in".to_string()), IR::Button("+".to_string())]);
+        let options = RenderOptions { haptics: Some("impact".to_string()), padding: false, ..RenderOptions::default() };
+        let code = render_swiftui_with(&ir, &options);
+        assert!(
+            code.contains("    Button(\"Sign in\") { signInTapped.toggle() }\n        .sensoryFeedback(.impact, trigger: signInTapped)\n    Button(\"Sign in\") { signInTapped2.toggle() }\n"),
+            "{}",
+            code
+        );
+        assert!(code.contains(".sensoryFeedback(.impact, trigger: buttonTapped)"), "{}", code);
+        let view = render_view("LoginView", &code, &options);
+        assert!(
+            view.starts_with("struct LoginView: View {\n    @State private var signInTapped = false\n    @State private var signInTapped2 = false\n    @State private var buttonTapped = false\n\n    var body"),
+            "{}",
+            view
+        );
+        assert_eq!(render_swiftui(&IR::Button("Go".to_string())), "Button(\"Go\") { }\n    .padding()");
+    }
+
+    #[test]
+    fn test_gestures_call_handler_stubs() {
+        let ir = IR::VStack(vec![IR::Image("avatar".to_string()), IR::Text("Inbox".to_string())]);
+        let view_modifiers = BTreeMap::from([
+            (IR::Image("avatar".to_string()), vec![Modifier::Tap("openProfile".to_string())]),
+            (IR::Text("Inbox".to_string()), vec![Modifier::LongPress("showMenu".to_string()), Modifier::Tap("openProfile".to_string())]),
+        ]);
+        let options = RenderOptions { view_modifiers, ..RenderOptions::default() };
+        let code = render_swiftui_with(&ir, &options);
+        assert!(code.contains("    Image(\"avatar\")\n        .onTapGesture { openProfile() }\n"), "{}", code);
+        let view = render_view("InboxView", &code, &options);
+        assert!(
+            view.ends_with(
+                "    }\n\n    private func openProfile() {\n        // Handle the tap\n    }\n\n    private func showMenu() {\n        // Handle the long press\n    }\n}"
+            ),
+            "{}",
+            view
+        );
+    }
+
+    #[test]
+    fn test_render_with_options() {
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::Button("Go".to_string())]);
+        let options = RenderOptions { indent_width: 2, text_font: "headline".to_string(), padding: false, ..RenderOptions::default() };
+        let expected = normalize_whitespace(
+            "VStack {
+  Text(\"Hi\")
+    .font(.headline)
+  Button(\"Go\") { }
+}"
+        );
+        assert_eq!(render_swiftui_with(&ir, &options), expected);
+        let options = RenderOptions { ignores_safe_area: true, ..options };
+        assert_eq!(render_swiftui_with(&ir, &options), format!("{}\n.ignoresSafeArea()", expected));
+
+        let text_fit = TextFit { scrolls: true, minimum_scale_factor: Some(0.75) };
+        let options = RenderOptions { ignores_safe_area: false, text_fit, ..options };
+        let expected = normalize_whitespace(
+            "ScrollView {
+  VStack {
+    Text(\"Hi\")
+      .font(.headline)
+      .minimumScaleFactor(0.75)
+    Button(\"Go\") { }
+  }
+}"
+        );
+        assert_eq!(render_swiftui_with(&ir, &options), expected);
+    }
+
      #[test]
+    fn test_render_platform_idioms() {
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::HStack(vec![IR::Button("Yes".to_string()), IR::Button("No".to_string())])]);
+        let watch = RenderOptions { padding: false, text_font: "headline".to_string(), platform: Platform::Watchos, ..RenderOptions::default() };
+        let expected = normalize_whitespace(
+            "ScrollView {
+    VStack {
+        Text(\"Hi\")
+            .font(.headline)
+        HStack {
+            Button(\"Yes\") { }
+            Button(\"No\") { }
+        }
+    }
+}"
+        );
+        assert_eq!(render_swiftui_with(&ir, &watch), expected);
+        // The crown scrolls vertically, so only a vertical layout is put in a ScrollView
+        assert!(!render_swiftui_with(&IR::HStack(vec![IR::Text("Hi".to_string())]), &watch).contains("ScrollView"));
+
+        let tv = RenderOptions { padding: false, platform: Platform::Tvos, ..RenderOptions::default() };
+        let code = render_swiftui_with(&ir, &tv);
+        assert!(code.contains("        Button(\"No\") { }\n    }\n    .focusSection()\n}"), "{}", code);
+        assert_eq!(code.matches(".focusSection()").count(), 1);
+    }
+
+    #[test]
+    fn test_render_accessibility_identifiers() {
+        let ir = IR::VStack(vec![
+            IR::Image("logo".to_string()),
+            IR::Text("Hi".to_string()),
+            IR::HStack(vec![IR::Button("Sign in".to_string()), IR::Text("hi!".to_string())]),
+        ]);
+        let options = RenderOptions { indent_width: 2, padding: false, accessibility_identifiers: true, ..RenderOptions::default() };
+        let expected = normalize_whitespace(
+            "VStack {
+  Image(\"logo\")
+    .accessibilityIdentifier(\"image.logo\")
+  Text(\"Hi\")
+    .font(.title)
+    .accessibilityIdentifier(\"text.hi\")
+  HStack {
+    Button(\"Sign in\") { }
+      .accessibilityIdentifier(\"button.sign-in\")
+    Text(\"hi!\")
+      .font(.title)
+      .accessibilityIdentifier(\"text.hi.2\")
+  }
+}"
+        );
+        assert_eq!(render_swiftui_with(&ir, &options), expected);
+        assert_eq!(accessibility_identifier(&IR::Button("+".to_string()), &mut Vec::new()).as_deref(), Some("button"));
+        assert_eq!(accessibility_identifier(&IR::Spacer, &mut Vec::new()), None);
+    }
+
+    #[test]
     fn test_render_image_in_vstack() {
         let ir = IR::VStack(vec![IR::Image("icon".to_string()), IR::Spacer]);
          let expected = normalize_whitespace(
@@ -223,4 +993,20 @@ mod tests {
         );
         assert_eq!(render_swiftui(&ir), expected);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_render_nested_stack_padding_on_own_line() {
+        let ir = IR::VStack(vec![IR::HStack(vec![IR::Spacer]), IR::Spacer]);
+        let expected = normalize_whitespace(
+            "VStack {
+    HStack {
+        Spacer()
+    }
+    .padding()
+    Spacer()
+}
+.padding()"
+        );
+        assert_eq!(render_swiftui(&ir), expected);
+    }
+}
@@ -0,0 +1,85 @@
+// File: src/output/snapshot.rs
+// XCTest file of snapshot tests for the generated views (`--emit-tests`), written against
+// swift-snapshot-testing: each view is rendered at every screen size its examples give.
+use crate::layout::Size;
+
+/// Checks that `name` can be the test class: a Swift identifier.
+pub fn validate_class_name(name: &str) -> Result<(), String> {
+    let valid = name.starts_with(|ch: char| ch.is_ascii_alphabetic() || ch == '_') && name.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+    match valid {
+        true => Ok(()),
+        false => Err(format!("Test class name '{}' is not a Swift identifier; name the file e.g. LoginViewTests.swift", name)),
+    }
+}
+
+fn dimension(value: f64) -> String {
+    value.to_string().replace('.', "_")
+}
+
+/// The test file: class `class`, importing `module` (the views' module) if given, with a test
+/// per view of `views` and distinct size of its screens, e.g. `testLoginView_390x844`. Each
+/// records a snapshot on its first run and compares against it afterwards.
+pub fn render_snapshot_tests(class: &str, module: Option<&str>, views: &[(String, Vec<Size>)]) -> String {
+    let mut tests = Vec::new();
+    for (view, sizes) in views {
+        let mut seen: Vec<&Size> = Vec::new();
+        for size in sizes {
+            if seen.contains(&size) {
+                continue;
+            }
+            seen.push(size);
+            tests.push(format!(
+                "    func test{view}_{w}x{h}() {{
+        assertSnapshot(of: {view}(), as: .image(layout: .fixed(width: {width}, height: {height})))
+    }}",
+                view = view,
+                w = dimension(size.width),
+                h = dimension(size.height),
+                width = size.width,
+                height = size.height
+            ));
+        }
+    }
+    let import = module.map_or(String::new(), |module| format!("@testable import {}\n", module));
+    format!(
+        "import SnapshotTesting
+import SwiftUI
+import XCTest
+{import}
+final class {class}: XCTestCase {{
+{tests}
+}}",
+        import = import,
+        class = class,
+        tests = tests.join("\n\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one_test_per_view_and_size() {
+        let views = vec![
+            ("LoginView".to_string(), vec![Size::new(390.0, 844.0), Size::new(320.0, 568.0), Size::new(390.0, 844.0)]),
+            ("HomeView".to_string(), vec![Size::new(852.0, 393.5)]),
+        ];
+        let tests = render_snapshot_tests("ScreenTests", Some("MyApp"), &views);
+        assert!(tests.starts_with("import SnapshotTesting\nimport SwiftUI\nimport XCTest\n@testable import MyApp\n\nfinal class ScreenTests: XCTestCase {\n"));
+        assert!(tests.contains(
+            "    func testLoginView_390x844() {\n        assertSnapshot(of: LoginView(), as: .image(layout: .fixed(width: 390, height: 844)))\n    }\n\n    func testLoginView_320x568() {"
+        ));
+        assert!(tests.contains("    func testHomeView_852x393_5() {\n        assertSnapshot(of: HomeView(), as: .image(layout: .fixed(width: 852, height: 393.5)))"));
+        assert_eq!(tests.matches("func test").count(), 3);
+        assert!(tests.ends_with("    }\n}"));
+
+        assert!(render_snapshot_tests("ScreenTests", None, &views).starts_with("import SnapshotTesting\nimport SwiftUI\nimport XCTest\n\nfinal class"));
+    }
+
+    #[test]
+    fn test_class_names() {
+        assert!(validate_class_name("LoginViewTests").is_ok());
+        assert!(validate_class_name("login-tests").unwrap_err().contains("not a Swift identifier"));
+    }
+}
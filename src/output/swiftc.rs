@@ -0,0 +1,191 @@
+// File: src/output/swiftc.rs
+// Checks generated code with the Swift compiler, when one is installed (`--validate swift`),
+// and reports what it rejects against the layout nodes the code was rendered from.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::ast::IR;
+use crate::diagnostics::Diagnostic;
+use crate::synthesis::verify;
+use crate::utils::process::on_path;
+
+/// A generated Swift file, with the layout its view was rendered from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SourceFile {
+    pub name: String,
+    pub code: String,
+    /// `None` for files that are not a screen's view, such as `App.swift`.
+    pub ir: Option<IR>,
+}
+
+/// A Swift compiler, and the SDK to typecheck SwiftUI code against if one is installed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Toolchain {
+    pub swiftc: PathBuf,
+    /// Without an SDK declaring SwiftUI the code can only be parsed.
+    pub sdk: Option<PathBuf>,
+}
+
+impl Toolchain {
+    /// Finds `swiftc` at `$SWIFTC` or on the `PATH`, and the iOS simulator SDK through `xcrun`
+    /// (or `$SDKROOT`). `None` if there is no compiler.
+    pub fn find() -> Option<Toolchain> {
+        let swiftc = std::env::var_os("SWIFTC").map(PathBuf::from).or_else(|| on_path("swiftc"))?;
+        let sdk = std::env::var_os("SDKROOT").map(PathBuf::from).or_else(|| {
+            let output = Command::new("xcrun").args(["--sdk", "iphonesimulator", "--show-sdk-path"]).output().ok()?;
+            let path = String::from_utf8(output.stdout).ok()?;
+            output.status.success().then(|| PathBuf::from(path.trim()))
+        });
+        Some(Toolchain { swiftc, sdk })
+    }
+
+    /// Arguments for checking the files in `paths`: `-typecheck` against the SDK, else `-parse`.
+    fn args(&self, paths: &[PathBuf]) -> Vec<String> {
+        let mut args: Vec<String> = match &self.sdk {
+            Some(sdk) => {
+                let arch = if std::env::consts::ARCH == "aarch64" { "arm64" } else { std::env::consts::ARCH };
+                let target = format!("{}-apple-ios17.0-simulator", arch);
+                vec!["-typecheck".into(), "-sdk".into(), sdk.display().to_string(), "-target".into(), target]
+            }
+            None => vec!["-parse".into()],
+        };
+        args.extend(["-parse-as-library", "-diagnostic-style", "llvm"].map(String::from));
+        args.extend(paths.iter().map(|path| path.display().to_string()));
+        args
+    }
+
+    /// Compiles `files` together in a scratch directory and returns an error for each problem
+    /// the compiler reports, which is empty if the code is accepted.
+    pub fn check(&self, files: &[SourceFile]) -> Result<Vec<Diagnostic>, String> {
+        let dir = std::env::temp_dir().join(format!("swiftui-synth-validate-{}", std::process::id()));
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create '{}': {}", dir.display(), e))?;
+        let mut paths = Vec::new();
+        for file in files {
+            let path = dir.join(&file.name);
+            fs::write(&path, &file.code).map_err(|e| format!("Failed to write '{}': {}", path.display(), e))?;
+            paths.push(path);
+        }
+        let output = Command::new(&self.swiftc).args(self.args(&paths)).output();
+        let _ = fs::remove_dir_all(&dir);
+        let output = output.map_err(|e| format!("Failed to run '{}': {}", self.swiftc.display(), e))?;
+        let diagnostics = compiler_errors(&String::from_utf8_lossy(&output.stderr), &dir, files);
+        if diagnostics.is_empty() && !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("'{}' failed: {}", self.swiftc.display(), stderr.trim()));
+        }
+        Ok(diagnostics)
+    }
+}
+
+// Text the code of a node starts with, on the line it begins
+fn head(node: &IR) -> String {
+    match node {
+        IR::VStack(_) => "VStack".to_string(),
+        IR::HStack(_) => "HStack".to_string(),
+        IR::Text(_) => "Text(".to_string(),
+        IR::Button(_) => "Button(".to_string(),
+        IR::Image(_) => "Image(".to_string(),
+        IR::Spacer => "Spacer(".to_string(),
+        IR::Custom { name, .. } => format!("{}(", name),
+    }
+}
+
+/// The line (from 0) each node of `ir` starts on in `code`, in the order they are rendered.
+/// Lines before the layout, such as `import SwiftUI` or the view struct, are skipped.
+pub fn node_lines<'a>(ir: &'a IR, code: &str) -> Vec<(usize, &'a IR)> {
+    fn visit<'a>(node: &'a IR, lines: &[&str], next: &mut usize, found: &mut Vec<(usize, &'a IR)>) -> bool {
+        let head = head(node);
+        let Some(offset) = lines[*next..].iter().position(|line| line.trim_start().starts_with(&head)) else {
+            return false;
+        };
+        found.push((*next + offset, node));
+        *next += offset + 1;
+        match node {
+            IR::VStack(children) | IR::HStack(children) => children.iter().all(|child| visit(child, lines, next, found)),
+            _ => true,
+        }
+    }
+    let lines: Vec<&str> = code.lines().collect();
+    let mut found = Vec::new();
+    visit(ir, &lines, &mut 0, &mut found);
+    found
+}
+
+// Reads `path:line:column: error: message` lines, for files in `dir`, into diagnostics
+fn compiler_errors(output: &str, dir: &Path, files: &[SourceFile]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for line in output.lines() {
+        let Some((location, message)) = line.split_once(": error: ") else { continue };
+        let mut parts = location.rsplitn(3, ':');
+        let (Some(column), Some(line_no), Some(path)) = (parts.next(), parts.next(), parts.next()) else { continue };
+        let (Ok(column), Ok(line_no)) = (column.parse::<usize>(), line_no.parse::<usize>()) else { continue };
+        let name = Path::new(path).strip_prefix(dir).map_or(path.to_string(), |name| name.display().to_string());
+        // The innermost node whose code starts at or before the line
+        let file = files.iter().find(|file| file.name == name);
+        let node = file.and_then(|file| {
+            let ir = file.ir.as_ref()?;
+            node_lines(ir, &file.code).into_iter().rev().find(|(start, _)| *start < line_no).map(|(_, node)| node)
+        });
+        let location = match node {
+            Some(node) => format!("{}:{}:{}, in {}", name, line_no, column, verify::describe(node)),
+            None => format!("{}:{}:{}", name, line_no, column),
+        };
+        diagnostics.push(Diagnostic::error("E0030", format!("swiftc rejected the generated code at {}: {}", location, message)));
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::render::{render_swiftui, render_view, RenderOptions};
+
+    fn view() -> (IR, String) {
+        let ir = IR::VStack(vec![
+            IR::Text("Hi".to_string()),
+            IR::HStack(vec![IR::Image("logo".to_string()), IR::Spacer]),
+            IR::Button("Go".to_string()),
+        ]);
+        let code = format!("import SwiftUI\n\n{}\n", render_view("LoginView", &render_swiftui(&ir), &RenderOptions::default()));
+        (ir, code)
+    }
+
+    #[test]
+    fn test_node_lines() {
+        let (ir, code) = view();
+        let lines: Vec<(usize, String)> = node_lines(&ir, &code).into_iter().map(|(line, node)| (line, verify::describe(node))).collect();
+        let heads: Vec<&str> = lines.iter().map(|(line, _)| code.lines().nth(*line).unwrap().trim()).collect();
+        assert_eq!(heads, vec!["VStack {", "Text(\"Hi\")", "HStack {", "Image(\"logo\")", "Spacer()", "Button(\"Go\") { }"]);
+        assert_eq!(lines[1].1, "Text \"Hi\"");
+    }
+
+    #[test]
+    fn test_compiler_errors_name_the_node() {
+        let (ir, code) = view();
+        let dir = Path::new("/tmp/check");
+        let button_line = code.lines().position(|line| line.contains("Button")).unwrap() + 1;
+        let files = [SourceFile { name: "LoginView.swift".to_string(), code, ir: Some(ir) }];
+        let output = format!(
+            "/tmp/check/LoginView.swift:{}:20: error: expected '}}' in closure\n  Button(\"Go\") {{\n/tmp/check/LoginView.swift:1:8: warning: unused\n/tmp/check/App.swift:3:1: error: expected declaration\n",
+            button_line
+        );
+        let messages: Vec<String> = compiler_errors(&output, dir, &files).into_iter().map(|d| d.message).collect();
+        assert_eq!(
+            messages,
+            vec![
+                format!("swiftc rejected the generated code at LoginView.swift:{}:20, in Button \"Go\": expected '}}' in closure", button_line),
+                "swiftc rejected the generated code at App.swift:3:1: expected declaration".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parses_without_an_sdk() {
+        let toolchain = Toolchain { swiftc: PathBuf::from("swiftc"), sdk: None };
+        let args = toolchain.args(&[PathBuf::from("A.swift")]);
+        assert_eq!(args, ["-parse", "-parse-as-library", "-diagnostic-style", "llvm", "A.swift"]);
+        let toolchain = Toolchain { sdk: Some(PathBuf::from("/sdk")), ..toolchain };
+        assert_eq!(toolchain.args(&[])[..3], ["-typecheck", "-sdk", "/sdk"]);
+    }
+}
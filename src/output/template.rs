@@ -0,0 +1,369 @@
+// File: src/output/template.rs
+// SwiftUI rendered from a team's own Tera templates (`--template-dir`), one per kind of view.
+// The crate walks the layout and hands each template its view's data and its children's code,
+// indented; kinds without a template use the built-in one, which matches the default output.
+// The screen's own containers, its `ScrollView` and `.ignoresSafeArea()`, are added around the
+// root's code as the default renderer adds them.
+use std::fs;
+use std::path::Path;
+
+use serde::Serialize;
+use tera::{Context, Tera};
+
+use crate::ast::ir::modifier::APPEAR_STATE;
+use crate::ast::IR;
+use crate::output::backend::{RenderBackend, SwiftUi};
+use crate::output::render::{accessibility_identifier, string_literal, wrap_root, RenderOptions};
+
+/// Kinds of view a template renders, named as the template files are, e.g. `vstack.tera`.
+pub const KINDS: &[&str] = &["vstack", "hstack", "text", "button", "image", "spacer", "custom"];
+
+/// The built-in template of `kind`.
+pub fn builtin(kind: &str) -> &'static str {
+    match kind {
+        "vstack" | "hstack" => concat!(
+            "{% if kind == \"vstack\" %}VStack{% else %}HStack{% endif %}{{ stack_arguments }} {\n",
+            "{{ content }}\n",
+            "}\n",
+            "{% if padding %}.padding({{ padding_length }})\n{% endif %}",
+            // The Siri Remote moves focus along a row of buttons before leaving it
+            "{% if kind == \"hstack\" and platform == \"tvos\" and \"button\" in child_kinds %}.focusSection(){% endif %}"
+        ),
+        "text" => concat!(
+            "Text({{ literal }})\n",
+            "{{ indent }}.font(.{{ text_font }})\n",
+            "{% for modifier in line_modifiers %}{{ indent }}.{{ modifier }}\n{% endfor %}",
+            "{% if minimum_scale_factor %}{{ indent }}.minimumScaleFactor({{ minimum_scale_factor }})\n{% endif %}",
+            "{% if identifier %}{{ indent }}.accessibilityIdentifier(\"{{ identifier }}\")\n{% endif %}",
+            "{% if padding %}{{ indent }}.padding({{ padding_length }})\n{% endif %}",
+            "{% for modifier in decorations %}{{ indent }}.{{ modifier }}\n{% endfor %}"
+        ),
+        "button" => concat!(
+            "Button({{ literal }}) { {% if action %}{{ action }} {% endif %}}\n",
+            "{% if identifier %}{{ indent }}.accessibilityIdentifier(\"{{ identifier }}\")\n{% endif %}",
+            "{% if padding %}{{ indent }}.padding({{ padding_length }})\n{% endif %}",
+            "{% if feedback %}{{ indent }}.{{ feedback }}\n{% endif %}",
+            "{% for modifier in decorations %}{{ indent }}.{{ modifier }}\n{% endfor %}"
+        ),
+        "image" => concat!(
+            "Image({{ literal }})\n",
+            "{% for modifier in image_modifiers %}{{ indent }}.{{ modifier }}\n{% endfor %}",
+            "{% if identifier %}{{ indent }}.accessibilityIdentifier(\"{{ identifier }}\")\n{% endif %}",
+            "{% for modifier in decorations %}{{ indent }}.{{ modifier }}\n{% endfor %}"
+        ),
+        "spacer" => "Spacer()",
+        _ => "{{ name }}({{ args }})\n{% for modifier in decorations %}{{ indent }}.{{ modifier }}\n{% endfor %}",
+    }
+}
+
+// What a template sees of its view
+#[derive(Serialize)]
+struct Node<'a> {
+    kind: &'a str,
+    /// The text, button title, image name or custom view name.
+    value: Option<&'a str>,
+    /// `value` as a Swift string literal.
+    literal: Option<String>,
+    name: Option<&'a str>,
+    args: Option<&'a str>,
+    identifier: Option<String>,
+    /// A button's action when buttons give feedback, toggling the state that triggers it.
+    action: Option<String>,
+    /// A button's `.sensoryFeedback` modifier, without its leading dot.
+    feedback: Option<String>,
+    /// The children's code, each as rendered at column 0, and all of them indented a level.
+    children: Vec<String>,
+    child_kinds: Vec<&'a str>,
+    content: String,
+    depth: usize,
+    /// Position among the parent stack's children.
+    index: usize,
+    root: bool,
+    indent: String,
+    padding: bool,
+    /// `(spacing: 12)` when the stack spacing is spelled out, else empty.
+    stack_arguments: String,
+    /// The argument of `.padding`, `16` when the padding is spelled out, else empty.
+    padding_length: String,
+    text_font: &'a str,
+    /// A text's `lineLimit` and alignment or truncation modifiers, without their leading dot.
+    line_modifiers: Vec<String>,
+    /// An image's `resizable`, content mode and frame modifiers, without their leading dot.
+    image_modifiers: Vec<String>,
+    /// The corner radius, border and shadow modifiers stated for the view, without their leading dot.
+    decorations: Vec<String>,
+    minimum_scale_factor: Option<f64>,
+    platform: &'a str,
+    ignores_safe_area: bool,
+}
+
+/// The templates of a template directory, with the built-in ones for the kinds it leaves out.
+pub struct Templates {
+    tera: Tera,
+    /// Whether any template was read from a directory rather than built in.
+    custom: bool,
+}
+
+impl Templates {
+    /// Reads `<kind>.tera` files from `dir`. Any other `.tera` file is an error, as it would
+    /// be a typo that leaves the built-in template in use.
+    pub fn load(dir: &Path) -> Result<Templates, String> {
+        let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read template directory '{}': {}", dir.display(), e))?;
+        let mut tera = Tera::default();
+        let mut found = Vec::new();
+        for entry in entries {
+            let path = entry.map_err(|e| format!("Failed to read template directory '{}': {}", dir.display(), e))?.path();
+            if path.extension().is_none_or(|extension| extension != "tera") {
+                continue;
+            }
+            let kind = path.file_stem().unwrap_or_default().to_string_lossy().into_owned();
+            if !KINDS.contains(&kind.as_str()) {
+                return Err(format!("Unknown template '{}': expected one of {} with the .tera extension", path.display(), KINDS.join(", ")));
+            }
+            let source = fs::read_to_string(&path).map_err(|e| format!("Failed to read template '{}': {}", path.display(), e))?;
+            tera.add_raw_template(&kind, &source).map_err(|e| format!("Invalid template '{}': {}", path.display(), error_chain(&e)))?;
+            found.push(kind);
+        }
+        for kind in KINDS.iter().filter(|kind| !found.iter().any(|found| found == *kind)) {
+            tera.add_raw_template(kind, builtin(kind)).expect("built-in templates parse");
+        }
+        Ok(Templates { custom: !found.is_empty(), tera })
+    }
+
+    /// Only the built-in templates.
+    pub fn builtin() -> Templates {
+        let mut tera = Tera::default();
+        for kind in KINDS {
+            tera.add_raw_template(kind, builtin(kind)).expect("built-in templates parse");
+        }
+        Templates { tera, custom: false }
+    }
+
+    /// Renders `ir` with the templates.
+    pub fn render(&self, ir: &IR, options: &RenderOptions) -> Result<String, String> {
+        Ok(wrap_root(ir, &self.node(ir, 0, 0, options, &mut Vec::new())?, options))
+    }
+
+    fn node(&self, ir: &IR, depth: usize, index: usize, options: &RenderOptions, taken: &mut Vec<String>) -> Result<String, String> {
+        let indent = " ".repeat(options.indent_width);
+        let identifier = options.accessibility_identifiers.then(|| accessibility_identifier(ir, taken)).flatten();
+        let (action, feedback) = match ir {
+            IR::Button(title) => options.feedback(title, taken).unzip(),
+            _ => (None, None),
+        };
+        let mut children = Vec::new();
+        let mut child_kinds = Vec::new();
+        let (kind, value, name, args) = match ir {
+            IR::VStack(items) | IR::HStack(items) => {
+                for (index, child) in items.iter().enumerate() {
+                    child_kinds.push(kind(child));
+                    children.push(self.node(child, depth + 1, index, options, taken)?);
+                }
+                (kind(ir), None, None, None)
+            }
+            IR::Text(text) => (kind(ir), Some(text.as_str()), None, None),
+            IR::Button(title) => (kind(ir), Some(title.as_str()), None, None),
+            IR::Image(image) => (kind(ir), Some(image.as_str()), None, None),
+            IR::Spacer => (kind(ir), None, None, None),
+            IR::Custom { name, args } => (kind(ir), Some(name.as_str()), Some(name.as_str()), Some(args.as_str())),
+        };
+        let content = children
+            .iter()
+            .flat_map(|child| child.lines())
+            .map(|line| if line.is_empty() { String::new() } else { format!("{}{}", indent, line) })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let node = Node {
+            kind,
+            value,
+            literal: value.filter(|_| kind != "custom").map(string_literal),
+            name,
+            args,
+            identifier,
+            action,
+            feedback,
+            children,
+            child_kinds,
+            content,
+            depth,
+            index,
+            root: depth == 0,
+            indent,
+            padding: options.padding,
+            stack_arguments: options.stack_arguments(),
+            padding_length: options.padding_length(),
+            text_font: match ir {
+                IR::Text(text) => options.text_style(text),
+                _ => &options.text_font,
+            },
+            line_modifiers: match ir {
+                IR::Text(text) => options.line_modifiers(text),
+                _ => Vec::new(),
+            },
+            image_modifiers: match ir {
+                IR::Image(name) => options.image_modifiers(name),
+                _ => Vec::new(),
+            },
+            decorations: options.decorations(ir),
+            minimum_scale_factor: options.text_fit.minimum_scale_factor,
+            platform: options.platform.name(),
+            ignores_safe_area: options.ignores_safe_area,
+        };
+        let context = Context::from_serialize(&node).map_err(|e| error_chain(&e))?;
+        let code = self.tera.render(kind, &context).map_err(|e| format!("Template '{}.tera' failed: {}", kind, error_chain(&e)))?;
+        // Trailing whitespace, such as a line left by a false `if`, is not part of the code
+        let code = code.lines().map(str::trim_end).collect::<Vec<_>>().join("\n").trim_end().to_string();
+        // A view with a transition is inserted once the screen appears
+        Ok(match options.transition(ir) {
+            Some(_) => format!("if {} {{\n{}\n}}", APPEAR_STATE, code.lines().map(|line| format!("{}{}", " ".repeat(options.indent_width), line)).collect::<Vec<_>>().join("\n")),
+            None => code,
+        })
+    }
+}
+
+/// The kind of `ir`, which names its template.
+pub fn kind(ir: &IR) -> &'static str {
+    match ir {
+        IR::VStack(_) => "vstack",
+        IR::HStack(_) => "hstack",
+        IR::Text(_) => "text",
+        IR::Button(_) => "button",
+        IR::Image(_) => "image",
+        IR::Spacer => "spacer",
+        IR::Custom { .. } => "custom",
+    }
+}
+
+// Tera's message with the causes it wraps, which say what went wrong
+fn error_chain(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    message
+}
+
+/// The SwiftUI target with user templates: files and the rest are as the built-in one's.
+pub struct TemplateBackend {
+    pub templates: Templates,
+}
+
+impl RenderBackend for TemplateBackend {
+    fn name(&self) -> &str {
+        SwiftUi.name()
+    }
+
+    fn render(&self, ir: &IR, options: &RenderOptions) -> String {
+        self.try_render(ir, options).unwrap_or_else(|e| format!("// {}", e))
+    }
+
+    fn try_render(&self, ir: &IR, options: &RenderOptions) -> Result<String, String> {
+        self.templates.render(ir, options)
+    }
+
+    fn language(&self) -> &str {
+        SwiftUi.language()
+    }
+
+    fn extension(&self) -> &str {
+        "swift"
+    }
+
+    fn files(&self, ir: &IR, code: &str, header: &str, options: &RenderOptions) -> Vec<(String, String)> {
+        SwiftUi.files(ir, code, header, options)
+    }
+
+    fn is_swift(&self) -> bool {
+        true
+    }
+
+    // Templates of the user's may write views the reverse parser does not know
+    fn round_trips(&self) -> bool {
+        !self.templates.custom
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::render::render_swiftui_with;
+
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("swiftui-synth-templates-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_builtin_templates_match_the_renderer() {
+        use crate::ast::ir::modifier::Modifier;
+        use crate::layout::image_sizing::{ContentMode, ImageSizing};
+        use crate::layout::text_style::{TextAlignment, Wrapping};
+        let ir = IR::VStack(vec![
+            IR::HStack(vec![IR::Button("A".to_string()), IR::Button("B".to_string())]),
+            IR::Text("Say \"hi\"".to_string()),
+            IR::HStack(vec![IR::Image("logo".to_string()), IR::Spacer, IR::Button("Go".to_string())]),
+            IR::Custom { name: "AvatarView".to_string(), args: "size: 40".to_string() },
+        ]);
+        for options in [
+            RenderOptions::default(),
+            RenderOptions { platform: crate::platform::Platform::Watchos, ignores_safe_area: true, ..RenderOptions::default() },
+            RenderOptions { text_fit: crate::layout::TextFit { scrolls: true, minimum_scale_factor: Some(0.8) }, ..RenderOptions::default() },
+            RenderOptions { padding: false, accessibility_identifiers: true, indent_width: 2, text_font: "headline".to_string(), ..RenderOptions::default() },
+            RenderOptions { haptics: Some("selection".to_string()), accessibility_identifiers: true, ..RenderOptions::default() },
+            RenderOptions {
+                explicit_spacing: true,
+                metrics: crate::layout::Metrics { padding: 12.0, stack_spacing: 24.0, ..crate::layout::Metrics::default() },
+                ..RenderOptions::default()
+            },
+            RenderOptions {
+                text_styles: [("Say \"hi\"".to_string(), "caption".to_string())].into(),
+                text_wrapping: [("Say \"hi\"".to_string(), Wrapping::Multiline(TextAlignment::Leading))].into(),
+                image_sizing: [("logo".to_string(), ImageSizing { mode: ContentMode::Fit, width: None, fills_width: false, height: Some(40.0) })].into(),
+                accessibility_identifiers: true,
+                view_modifiers: [
+                    (IR::Image("logo".to_string()), vec![Modifier::CornerRadius(8.0)]),
+                    (IR::Button("Go".to_string()), vec![Modifier::Border { width: 1.0, color: None }]),
+                    (IR::Text("Say \"hi\"".to_string()), vec![Modifier::Shadow { radius: 2.0, x: 0.0, y: 1.0 }, Modifier::Transition("slide".to_string())]),
+                    (IR::Button("A".to_string()), vec![Modifier::Animation("spring".to_string())]),
+                    (IR::Custom { name: "AvatarView".to_string(), args: "size: 40".to_string() }, vec![Modifier::CornerRadius(20.0)]),
+                ]
+                .into(),
+                ..RenderOptions::default()
+            },
+        ] {
+            assert_eq!(Templates::builtin().render(&ir, &options).unwrap(), render_swiftui_with(&ir, &options));
+        }
+        let options = RenderOptions { platform: crate::platform::Platform::Tvos, ignores_safe_area: true, ..RenderOptions::default() };
+        for ir in [ir.clone(), IR::Text("Hi".to_string())] {
+            assert_eq!(Templates::builtin().render(&ir, &options).unwrap(), render_swiftui_with(&ir, &options));
+        }
+    }
+
+    #[test]
+    fn test_user_templates_replace_the_builtin_ones() {
+        let dir = temp_dir("user");
+        fs::write(dir.join("button.tera"), "PrimaryButton({{ literal }}, action: {})\n{% if root %}.frame(maxWidth: .infinity){% endif %}").unwrap();
+        fs::write(dir.join("vstack.tera"), "Card {\n{{ content }}\n}\n{{ indent }}// {{ children | length }} views").unwrap();
+        fs::write(dir.join("notes.txt"), "not a template").unwrap();
+        let templates = Templates::load(&dir).unwrap();
+        let ir = IR::VStack(vec![IR::Button("Go".to_string()), IR::Spacer]);
+        assert_eq!(
+            templates.render(&ir, &RenderOptions::default()).unwrap(),
+            "Card {\n    PrimaryButton(\"Go\", action: {})\n    Spacer()\n}\n    // 2 views"
+        );
+
+        fs::write(dir.join("buton.tera"), "").unwrap();
+        assert!(Templates::load(&dir).err().unwrap().contains("Unknown template"));
+        fs::remove_file(dir.join("buton.tera")).unwrap();
+        fs::write(dir.join("text.tera"), "{% if %}").unwrap();
+        assert!(Templates::load(&dir).err().unwrap().starts_with("Invalid template"));
+        fs::write(dir.join("text.tera"), "{{ missing }}").unwrap();
+        let error = Templates::load(&dir).unwrap().render(&IR::Text("Hi".to_string()), &RenderOptions::default()).unwrap_err();
+        assert!(error.starts_with("Template 'text.tera' failed") && error.contains("missing"), "{}", error);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,126 @@
+// File: src/output/tree.rs
+// The synthesized layout as a graph diagram (`--emit-tree`), for documentation and for seeing
+// the shape of deeply nested stacks at a glance.
+use crate::ast::IR;
+use crate::synthesis::verify;
+
+/// Diagram language of `--emit-tree`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TreeFormat {
+    /// Graphviz, e.g. `swiftui-synth ... --emit-tree dot | dot -Tsvg`.
+    Dot,
+    /// Mermaid, which Markdown on GitHub renders in a ```mermaid block.
+    Mermaid,
+}
+
+impl std::str::FromStr for TreeFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(TreeFormat::Dot),
+            "mermaid" => Ok(TreeFormat::Mermaid),
+            other => Err(format!("Unknown tree format '{}': expected dot or mermaid", other)),
+        }
+    }
+}
+
+// Each node of `ir` with its id, label and the id of its parent, parents first
+fn nodes(ir: &IR) -> Vec<(usize, String, Option<usize>)> {
+    fn visit(node: &IR, parent: Option<usize>, out: &mut Vec<(usize, String, Option<usize>)>) {
+        let id = out.len();
+        out.push((id, verify::describe(node), parent));
+        if let IR::VStack(children) | IR::HStack(children) = node {
+            for child in children {
+                visit(child, Some(id), out);
+            }
+        }
+    }
+    let mut out = Vec::new();
+    visit(ir, None, &mut out);
+    out
+}
+
+/// Renders `ir` as a top-down tree, titled `title` (a view name) if given.
+pub fn render_tree(ir: &IR, title: Option<&str>, format: TreeFormat) -> String {
+    let mut lines = Vec::new();
+    match format {
+        TreeFormat::Dot => {
+            lines.push(format!("digraph {} {{", title.unwrap_or("Layout")));
+            lines.push("    node [shape=box];".to_string());
+            for (id, label, parent) in nodes(ir) {
+                lines.push(format!("    n{} [label=\"{}\"];", id, label.replace('\\', "\\\\").replace('"', "\\\"")));
+                if let Some(parent) = parent {
+                    lines.push(format!("    n{} -> n{};", parent, id));
+                }
+            }
+            lines.push("}".to_string());
+        }
+        TreeFormat::Mermaid => {
+            if let Some(title) = title {
+                lines.extend(["---".to_string(), format!("title: {}", title), "---".to_string()]);
+            }
+            lines.push("graph TD".to_string());
+            for (id, label, parent) in nodes(ir) {
+                // Quotes end a Mermaid label; `#quot;` is its entity for one
+                let node = format!("n{}[\"{}\"]", id, label.replace('"', "#quot;"));
+                match parent {
+                    Some(parent) => lines.push(format!("    n{} --> {}", parent, node)),
+                    None => lines.push(format!("    {}", node)),
+                }
+            }
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layout() -> IR {
+        IR::VStack(vec![IR::Text("Say \"hi\"".to_string()), IR::HStack(vec![IR::Image("logo".to_string()), IR::Spacer]), IR::Button("Go".to_string())])
+    }
+
+    #[test]
+    fn test_dot() {
+        assert_eq!(
+            render_tree(&layout(), Some("LoginView"), TreeFormat::Dot),
+            r#"digraph LoginView {
+    node [shape=box];
+    n0 [label="VStack"];
+    n1 [label="Text \"Say \"hi\"\""];
+    n0 -> n1;
+    n2 [label="HStack"];
+    n0 -> n2;
+    n3 [label="Image \"logo\""];
+    n2 -> n3;
+    n4 [label="Spacer"];
+    n2 -> n4;
+    n5 [label="Button \"Go\""];
+    n0 -> n5;
+}"#
+        );
+        assert!(render_tree(&IR::Text("a\\b".to_string()), None, TreeFormat::Dot).contains(r#"[label="Text \"a\\b\""];"#));
+        assert!(render_tree(&IR::Spacer, None, TreeFormat::Dot).starts_with("digraph Layout {"));
+    }
+
+    #[test]
+    fn test_mermaid() {
+        assert_eq!(
+            render_tree(&layout(), Some("LoginView"), TreeFormat::Mermaid),
+            r#"---
+title: LoginView
+---
+graph TD
+    n0["VStack"]
+    n0 --> n1["Text #quot;Say #quot;hi#quot;#quot;"]
+    n0 --> n2["HStack"]
+    n2 --> n3["Image #quot;logo#quot;"]
+    n2 --> n4["Spacer"]
+    n0 --> n5["Button #quot;Go#quot;"]"#
+        );
+        assert_eq!(render_tree(&IR::Spacer, None, TreeFormat::Mermaid), "graph TD\n    n0[\"Spacer\"]");
+        assert!("svg".parse::<TreeFormat>().unwrap_err().contains("expected dot or mermaid"));
+    }
+}
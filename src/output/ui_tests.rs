@@ -0,0 +1,112 @@
+// File: src/output/ui_tests.rs
+// XCUITest skeleton for the generated views (`--emit-ui-tests`): finds each text, image and
+// button by the accessibility identifier the renderer gives it, as a start for end-to-end tests.
+use crate::ast::IR;
+use crate::output::render::{accessibility_identifier, string_literal};
+
+// Identified views of `ir` in the order the renderer numbers them, with the XCUIElement
+// query each is found by
+fn elements(ir: &IR, taken: &mut Vec<String>, out: &mut Vec<(&'static str, String)>) {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => children.iter().for_each(|child| elements(child, taken, out)),
+        _ => {
+            let query = match ir {
+                IR::Text(_) => "staticTexts",
+                IR::Button(_) => "buttons",
+                IR::Image(_) => "images",
+                _ => return,
+            };
+            if let Some(identifier) = accessibility_identifier(ir, taken) {
+                out.push((query, identifier));
+            }
+        }
+    }
+}
+
+/// The test file: class `class` launching the app, with a test per view of `views` that
+/// asserts its texts and images exist and then taps each of its buttons. Views the app does
+/// not start on need navigating to first, which is left as a note.
+pub fn render_ui_tests(class: &str, views: &[(String, &IR)]) -> String {
+    let tests: Vec<String> = views
+        .iter()
+        .enumerate()
+        .map(|(index, (view, ir))| {
+            let mut found = Vec::new();
+            elements(ir, &mut Vec::new(), &mut found);
+            let mut lines = Vec::new();
+            if index > 0 {
+                lines.push(format!("// Navigate from the first screen to {} here", view));
+            }
+            let element = |query: &str, identifier: &str| format!("app.{}[{}]", query, string_literal(identifier));
+            let (buttons, others): (Vec<_>, Vec<_>) = found.iter().partition(|(query, _)| *query == "buttons");
+            for (position, (query, identifier)) in others.iter().chain(&buttons).enumerate() {
+                // The first element waits for the screen to appear
+                let check = if position == 0 { "waitForExistence(timeout: 5)" } else { "exists" };
+                lines.push(format!("XCTAssertTrue({}.{})", element(query, identifier), check));
+            }
+            for (query, identifier) in &buttons {
+                lines.push(format!("{}.tap()", element(query, identifier)));
+            }
+            if found.is_empty() {
+                lines.push(format!("// {} has no texts, images or buttons to find", view));
+            }
+            let body: Vec<String> = lines.iter().map(|line| format!("        {}", line)).collect();
+            format!("    func test{}() throws {{\n{}\n    }}", view, body.join("\n"))
+        })
+        .collect();
+    format!(
+        "import XCTest
+
+final class {class}: XCTestCase {{
+    let app = XCUIApplication()
+
+    override func setUpWithError() throws {{
+        continueAfterFailure = false
+        app.launch()
+    }}
+
+{tests}
+}}",
+        class = class,
+        tests = tests.join("\n\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::render::{render_swiftui_with, RenderOptions};
+
+    #[test]
+    fn test_finds_views_by_their_rendered_identifiers() {
+        let login = IR::VStack(vec![
+            IR::Text("Welcome".to_string()),
+            IR::Button("Sign in".to_string()),
+            IR::HStack(vec![IR::Image("logo".to_string()), IR::Text("Welcome".to_string())]),
+        ]);
+        let home = IR::VStack(vec![IR::Spacer]);
+        let tests = render_ui_tests("AppUITests", &[("LoginView".to_string(), &login), ("HomeView".to_string(), &home)]);
+        assert!(tests.starts_with("import XCTest\n\nfinal class AppUITests: XCTestCase {\n    let app = XCUIApplication()\n"));
+        assert!(tests.contains(
+            "    func testLoginView() throws {
+        XCTAssertTrue(app.staticTexts[\"text.welcome\"].waitForExistence(timeout: 5))
+        XCTAssertTrue(app.images[\"image.logo\"].exists)
+        XCTAssertTrue(app.staticTexts[\"text.welcome.2\"].exists)
+        XCTAssertTrue(app.buttons[\"button.sign-in\"].exists)
+        app.buttons[\"button.sign-in\"].tap()
+    }"
+        ));
+        assert!(tests.contains(
+            "    func testHomeView() throws {
+        // Navigate from the first screen to HomeView here
+        // HomeView has no texts, images or buttons to find
+    }\n}"
+        ));
+
+        // Every identifier the tests use is one the code sets
+        let code = render_swiftui_with(&login, &RenderOptions { accessibility_identifiers: true, ..RenderOptions::default() });
+        for identifier in ["text.welcome", "text.welcome.2", "image.logo", "button.sign-in"] {
+            assert!(code.contains(&format!(".accessibilityIdentifier(\"{}\")", identifier)), "{}", code);
+        }
+    }
+}
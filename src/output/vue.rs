@@ -0,0 +1,274 @@
+// File: src/output/vue.rs
+// Vue renderer (`--target vue`), for web prototypes of a screen: a single-file component whose
+// scoped CSS lays stacks out with flexbox the way SwiftUI's stacks are laid out.
+use crate::ast::IR;
+use crate::layout::Metrics;
+use crate::output::render::{accessibility_identifier, RenderOptions};
+
+/// `value` as HTML text or, with `quote`, a quoted attribute value. Braces are escaped too,
+/// so text never reads as a Vue `{{ }}` interpolation.
+pub fn escape(value: &str, quote: bool) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' if quote => escaped.push_str("&quot;"),
+            '{' => escaped.push_str("&#123;"),
+            _ => escaped.push(ch),
+        }
+    }
+    match quote {
+        true => format!("\"{}\"", escaped),
+        false => escaped,
+    }
+}
+
+/// The header comment of a generated file, from its `//` form.
+pub fn comment_header(header: &str) -> String {
+    header
+        .lines()
+        .map(|line| format!("<!-- {} -->", line.strip_prefix("// ").unwrap_or(line)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// Classes of the component's style, in the order their rules are written
+const CLASSES: &[&str] = &["screen", "vstack", "hstack", "stretch", "grow", "text", "image", "spacer"];
+
+fn has_spacer(children: &[IR]) -> bool {
+    children.iter().any(|child| matches!(child, IR::Spacer))
+}
+
+struct Writer<'a> {
+    options: &'a RenderOptions,
+    lines: Vec<String>,
+    classes: Vec<&'static str>,
+    handlers: Vec<String>,
+    components: Vec<String>,
+    identifiers: Vec<String>,
+}
+
+impl Writer<'_> {
+    fn class(&mut self, names: &[&'static str]) -> String {
+        for name in names {
+            if !self.classes.contains(name) {
+                self.classes.push(name);
+            }
+        }
+        format!("class=\"{}\"", names.join(" "))
+    }
+
+    // A click handler for the button titled `title`, e.g. `onSignInClick`, each distinct
+    fn handler(&mut self, title: &str) -> String {
+        let words: String = title
+            .split(|ch: char| !ch.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word[..1].to_uppercase() + &word[1..].to_lowercase())
+            .collect();
+        let stem = match words.starts_with(|ch: char| ch.is_ascii_alphabetic()) {
+            true => format!("on{}", words),
+            false => "onButton".to_string(),
+        };
+        let name = (1..)
+            .map(|n| if n == 1 { format!("{}Click", stem) } else { format!("{}{}Click", stem, n) })
+            .find(|name| !self.handlers.contains(name))
+            .expect("some number is free");
+        self.handlers.push(name.clone());
+        name
+    }
+
+    // Writes `ir` at `depth`; `parent_vertical` is the axis of the stack holding it, if any
+    fn element(&mut self, ir: &IR, depth: usize, parent_vertical: Option<bool>) {
+        let pad = " ".repeat(depth * self.options.indent_width);
+        let test_id = match self.options.accessibility_identifiers.then(|| accessibility_identifier(ir, &mut self.identifiers)).flatten() {
+            Some(identifier) => format!(" data-testid={}", escape(&identifier, true)),
+            None => String::new(),
+        };
+        let element = match ir {
+            IR::VStack(children) | IR::HStack(children) => {
+                let vertical = matches!(ir, IR::VStack(_));
+                let mut classes = vec![if vertical { "vstack" } else { "hstack" }];
+                match parent_vertical {
+                    None => classes.insert(0, "screen"),
+                    // A stack with a spacer takes all the room along its axis, as in SwiftUI
+                    Some(parent) if has_spacer(children) => classes.push(if parent == vertical { "grow" } else { "stretch" }),
+                    Some(_) => {}
+                }
+                let class = self.class(&classes);
+                self.lines.push(format!("{}<div {}{}>", pad, class, test_id));
+                for child in children {
+                    self.element(child, depth + 1, Some(vertical));
+                }
+                self.lines.push(format!("{}</div>", pad));
+                return;
+            }
+            IR::Text(text) => format!("<p {}{}>{}</p>", self.class(&["text"]), test_id, escape(text, false)),
+            IR::Button(title) => {
+                let handler = self.handler(title);
+                format!("<button type=\"button\" @click=\"{}\"{}>{}</button>", handler, test_id, escape(title, false))
+            }
+            // Vite resolves `@/` to the app's `src` folder
+            IR::Image(image) => {
+                let src = escape(&format!("@/assets/{}.png", image), true);
+                format!("<img {} src={} alt={}{} />", self.class(&["image"]), src, escape(image, true), test_id)
+            }
+            IR::Spacer => format!("<div {}{}></div>", self.class(&["spacer"]), test_id),
+            // Custom views are components of the same name; their SwiftUI arguments do not carry over
+            IR::Custom { name, .. } => {
+                if !self.components.contains(name) {
+                    self.components.push(name.clone());
+                }
+                format!("<{}{} />", name, test_id)
+            }
+        };
+        self.lines.push(format!("{}{}", pad, element));
+    }
+}
+
+/// Renders `ir` as a Vue single-file component: its template, a `<script setup>` with an empty
+/// handler per button and an import per custom view, and scoped CSS with a rule per class the
+/// template uses. The root fills the window, as a SwiftUI screen does.
+pub fn render_component(ir: &IR, options: &RenderOptions, metrics: &Metrics) -> String {
+    let root = match ir {
+        IR::VStack(_) | IR::HStack(_) => ir.clone(),
+        _ => IR::VStack(vec![ir.clone()]),
+    };
+    let mut writer = Writer { options, lines: Vec::new(), classes: Vec::new(), handlers: Vec::new(), components: Vec::new(), identifiers: Vec::new() };
+    writer.element(&root, 1, None);
+    let pad = " ".repeat(options.indent_width);
+
+    let mut sections = vec![format!("<template>\n{}\n</template>", writer.lines.join("\n"))];
+    let mut script: Vec<String> = writer.components.iter().map(|name| format!("import {} from './{}.vue'", name, name)).collect();
+    if !script.is_empty() && !writer.handlers.is_empty() {
+        script.push(String::new());
+    }
+    script.extend(writer.handlers.iter().map(|handler| format!("function {}() {{}}", handler)));
+    if !script.is_empty() {
+        sections.push(format!("<script setup>\n{}\n</script>", script.join("\n")));
+    }
+
+    let padding = match options.padding {
+        true => format!("\n{}padding: {}px;", pad, metrics.padding),
+        false => String::new(),
+    };
+    let stack = |direction: &str| {
+        format!(
+            "display: flex;\n{pad}flex-direction: {};\n{pad}align-items: center;\n{pad}gap: {}px;{}",
+            direction,
+            metrics.stack_spacing,
+            padding,
+            pad = pad
+        )
+    };
+    let rules: Vec<String> = CLASSES
+        .iter()
+        .filter(|class| writer.classes.contains(class))
+        .map(|class| {
+            let declarations = match *class {
+                "screen" => format!("min-height: 100vh;\n{pad}box-sizing: border-box;\n{pad}justify-content: center;", pad = pad),
+                "vstack" => stack("column"),
+                "hstack" => stack("row"),
+                "stretch" => "align-self: stretch;".to_string(),
+                "grow" => "flex-grow: 1;".to_string(),
+                "text" => format!("margin: 0;\n{}font-size: {}px;", pad, metrics.title_font_size),
+                "image" => format!("width: {0}px;\n{1}height: {0}px;\n{1}object-fit: contain;", metrics.image_size, pad),
+                _ => format!("flex: 1 0 {}px;", metrics.spacer_min_length),
+            };
+            format!(".{} {{\n{}{}\n}}", class, pad, declarations)
+        })
+        .collect();
+    sections.push(format!("<style scoped>\n{}\n</style>", rules.join("\n\n")));
+    sections.join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_component() {
+        let ir = IR::VStack(vec![
+            IR::Text("Say {{ hi }} & go".to_string()),
+            IR::HStack(vec![IR::Image("logo".to_string()), IR::Spacer, IR::Button("Sign in".to_string())]),
+        ]);
+        let component = render_component(&ir, &RenderOptions { indent_width: 2, ..RenderOptions::default() }, &Metrics::default());
+        assert_eq!(
+            component,
+            r#"<template>
+  <div class="screen vstack">
+    <p class="text">Say &#123;&#123; hi }} &amp; go</p>
+    <div class="hstack stretch">
+      <img class="image" src="@/assets/logo.png" alt="logo" />
+      <div class="spacer"></div>
+      <button type="button" @click="onSignInClick">Sign in</button>
+    </div>
+  </div>
+</template>
+
+<script setup>
+function onSignInClick() {}
+</script>
+
+<style scoped>
+.screen {
+  min-height: 100vh;
+  box-sizing: border-box;
+  justify-content: center;
+}
+
+.vstack {
+  display: flex;
+  flex-direction: column;
+  align-items: center;
+  gap: 8px;
+  padding: 16px;
+}
+
+.hstack {
+  display: flex;
+  flex-direction: row;
+  align-items: center;
+  gap: 8px;
+  padding: 16px;
+}
+
+.stretch {
+  align-self: stretch;
+}
+
+.text {
+  margin: 0;
+  font-size: 28px;
+}
+
+.image {
+  width: 60px;
+  height: 60px;
+  object-fit: contain;
+}
+
+.spacer {
+  flex: 1 0 8px;
+}
+</style>"#
+        );
+    }
+
+    #[test]
+    fn test_custom_views_handlers_and_test_ids() {
+        let ir = IR::HStack(vec![
+            IR::Custom { name: "AvatarView".to_string(), args: "user: me".to_string() },
+            IR::Button("+".to_string()),
+            IR::Button("+".to_string()),
+        ]);
+        let options = RenderOptions { padding: false, accessibility_identifiers: true, ..RenderOptions::default() };
+        let component = render_component(&ir, &options, &Metrics::default());
+        assert!(component.contains("        <AvatarView />\n"), "{}", component);
+        assert!(component.contains("<button type=\"button\" @click=\"onButton2Click\" data-testid=\"button.2\">+</button>"), "{}", component);
+        assert!(component.contains("<script setup>\nimport AvatarView from './AvatarView.vue'\n\nfunction onButtonClick() {}\nfunction onButton2Click() {}\n</script>"), "{}", component);
+        assert!(!component.contains("padding") && !component.contains(".text"), "{}", component);
+        assert_eq!(comment_header("// Generated by swiftui-synth\n// Command: swiftui-synth --target vue"), "<!-- Generated by swiftui-synth -->\n<!-- Command: swiftui-synth --target vue -->");
+    }
+}
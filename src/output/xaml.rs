@@ -0,0 +1,260 @@
+// File: src/output/xaml.rs
+// .NET MAUI renderer (`--target xaml`): each screen becomes a XAML `ContentPage` of stack
+// layouts, or grids where a spacer has to take up the space left over, and its code-behind.
+use crate::ast::IR;
+use crate::layout::Metrics;
+use crate::output::render::{accessibility_identifier, RenderOptions};
+
+/// Namespace of the page classes unless `--namespace` names another.
+pub const DEFAULT_NAMESPACE: &str = "App";
+
+/// Name of the page generated for a view: `LoginView` becomes `LoginPage`, and the unnamed
+/// screen's `ContentView` the `MainPage` of a new MAUI app.
+pub fn page_name(view_name: &str) -> String {
+    match view_name {
+        "ContentView" => "MainPage".to_string(),
+        _ => format!("{}Page", view_name.strip_suffix("View").unwrap_or(view_name)),
+    }
+}
+
+/// `value` as a XAML attribute value, quoted.
+pub fn attribute(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for ch in value.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\n' => escaped.push_str("&#10;"),
+            _ => escaped.push(ch),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// The header comment of a generated file, from its `//` form. The command line is left out:
+/// its `--` flags cannot appear in an XML comment.
+pub fn comment_header(header: &str) -> String {
+    header
+        .lines()
+        .filter_map(|line| line.strip_prefix("// "))
+        .filter(|line| !line.starts_with("Command: "))
+        .map(|line| format!("<!-- {} -->", line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Names of the `Clicked` handlers of `ir`'s buttons in order, e.g. `OnSignInClicked`, each
+/// distinct.
+pub fn handlers(ir: &IR) -> Vec<String> {
+    fn walk(ir: &IR, out: &mut Vec<String>) {
+        match ir {
+            IR::VStack(children) | IR::HStack(children) => children.iter().for_each(|child| walk(child, out)),
+            IR::Button(title) => {
+                let words: String = title
+                    .split(|ch: char| !ch.is_ascii_alphanumeric())
+                    .filter(|word| !word.is_empty())
+                    .map(|word| word[..1].to_uppercase() + &word[1..].to_lowercase())
+                    .collect();
+                let stem = match words.starts_with(|ch: char| ch.is_ascii_alphabetic()) {
+                    true => format!("On{}", words),
+                    false => "OnButton".to_string(),
+                };
+                let name = (1..)
+                    .map(|n| if n == 1 { format!("{}Clicked", stem) } else { format!("{}{}Clicked", stem, n) })
+                    .find(|name| !out.contains(name))
+                    .expect("some number is free");
+                out.push(name);
+            }
+            _ => {}
+        }
+    }
+    let mut names = Vec::new();
+    walk(ir, &mut names);
+    names
+}
+
+fn has_custom(ir: &IR) -> bool {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => children.iter().any(has_custom),
+        IR::Custom { .. } => true,
+        _ => false,
+    }
+}
+
+struct Writer<'a> {
+    options: &'a RenderOptions,
+    metrics: &'a Metrics,
+    handlers: std::vec::IntoIter<String>,
+    identifiers: Vec<String>,
+    lines: Vec<String>,
+}
+
+impl Writer<'_> {
+    // Writes `ir` at `depth` with `placement`, the attributes its parent layout gives it
+    fn element(&mut self, ir: &IR, depth: usize, placement: &str) {
+        let pad = " ".repeat(depth * self.options.indent_width);
+        let mut attributes = placement.to_string();
+        if let Some(identifier) = self.options.accessibility_identifiers.then(|| accessibility_identifier(ir, &mut self.identifiers)).flatten() {
+            attributes.push_str(&format!(" AutomationId={}", attribute(&identifier)));
+        }
+        let element = match ir {
+            IR::VStack(children) | IR::HStack(children) => {
+                let vertical = matches!(ir, IR::VStack(_));
+                let padding = match self.options.padding {
+                    true => format!(" Padding=\"{}\"", self.metrics.padding),
+                    false => String::new(),
+                };
+                let spacing = self.metrics.stack_spacing;
+                // Stack layouts give no child the space left over, so a spacer needs a grid row
+                // or column of `*` size
+                let grid = children.iter().any(|child| matches!(child, IR::Spacer));
+                let (open, close) = match (grid, vertical) {
+                    (true, _) => {
+                        let sizes: Vec<&str> = children.iter().map(|child| if matches!(child, IR::Spacer) { "*" } else { "Auto" }).collect();
+                        let (definitions, spacing_name) = if vertical { ("RowDefinitions", "RowSpacing") } else { ("ColumnDefinitions", "ColumnSpacing") };
+                        (format!("Grid {}=\"{}\" {}=\"{}\"", definitions, sizes.join(","), spacing_name, spacing), "Grid")
+                    }
+                    (false, true) => (format!("VerticalStackLayout Spacing=\"{}\"", spacing), "VerticalStackLayout"),
+                    (false, false) => (format!("HorizontalStackLayout Spacing=\"{}\"", spacing), "HorizontalStackLayout"),
+                };
+                self.lines.push(format!("{}<{}{}{}>", pad, open, padding, attributes));
+                for (index, child) in children.iter().enumerate() {
+                    // Children sit centered across the stack, as in a SwiftUI stack
+                    let mut placement = match vertical {
+                        true => " HorizontalOptions=\"Center\"".to_string(),
+                        false => " VerticalOptions=\"Center\"".to_string(),
+                    };
+                    if grid {
+                        placement = format!(" Grid.{}=\"{}\"{}", if vertical { "Row" } else { "Column" }, index, placement);
+                    }
+                    match child {
+                        IR::Spacer if grid => {}
+                        _ => self.element(child, depth + 1, &placement),
+                    }
+                }
+                self.lines.push(format!("{}</{}>", pad, close));
+                return;
+            }
+            IR::Text(text) => format!("Label Text={} FontSize=\"{}\"", attribute(text), self.metrics.title_font_size),
+            IR::Button(title) => {
+                let handler = self.handlers.next().expect("a handler per button");
+                format!("Button Text={} Clicked=\"{}\"", attribute(title), handler)
+            }
+            IR::Image(image) => {
+                // MAUI builds every image in Resources/Images as a PNG of the same name
+                let size = self.metrics.image_size;
+                format!("Image Source={} WidthRequest=\"{}\" HeightRequest=\"{}\"", attribute(&format!("{}.png", image)), size, size)
+            }
+            IR::Spacer => format!("BoxView Color=\"Transparent\" MinimumHeightRequest=\"{0}\" MinimumWidthRequest=\"{0}\"", self.metrics.spacer_min_length),
+            // Custom views come from the app's namespace; their SwiftUI arguments do not carry over
+            IR::Custom { name, .. } => format!("local:{}", name),
+        };
+        self.lines.push(format!("{}<{}{} />", pad, element, attributes));
+    }
+}
+
+/// Renders `ir` as a XAML `ContentPage` whose class is `namespace.page`. A root view that is
+/// not a stack is put in one, so the page's content is always a layout.
+pub fn render_page(page: &str, namespace: &str, ir: &IR, options: &RenderOptions, metrics: &Metrics) -> String {
+    let root = match ir {
+        IR::VStack(_) | IR::HStack(_) => ir.clone(),
+        _ => IR::VStack(vec![ir.clone()]),
+    };
+    let mut writer = Writer { options, metrics, handlers: handlers(&root).into_iter(), identifiers: Vec::new(), lines: Vec::new() };
+    writer.element(&root, 1, "");
+    let align = " ".repeat("<ContentPage ".len());
+    let local = match has_custom(&root) {
+        true => format!("\n{}xmlns:local=\"clr-namespace:{}\"", align, namespace),
+        false => String::new(),
+    };
+    format!(
+        "<ContentPage xmlns=\"http://schemas.microsoft.com/dotnet/2021/maui\"\n{align}xmlns:x=\"http://schemas.microsoft.com/winfx/2009/xaml\"{local}\n{align}x:Class=\"{namespace}.{page}\">\n{content}\n</ContentPage>",
+        align = align,
+        local = local,
+        namespace = namespace,
+        page = page,
+        content = writer.lines.join("\n")
+    )
+}
+
+/// The page's C# code-behind: its constructor and an empty handler per button of `ir` to
+/// fill in.
+pub fn render_code_behind(page: &str, namespace: &str, ir: &IR, options: &RenderOptions) -> String {
+    let pad = " ".repeat(options.indent_width);
+    let mut members = vec![format!("{pad}public {page}()\n{pad}{{\n{pad}{pad}InitializeComponent();\n{pad}}}", pad = pad, page = page)];
+    for handler in handlers(ir) {
+        members.push(format!("{pad}private void {}(object sender, EventArgs e)\n{pad}{{\n{pad}}}", handler, pad = pad));
+    }
+    format!("namespace {};\n\npublic partial class {} : ContentPage\n{{\n{}\n}}", namespace, page, members.join("\n\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_page_with_stacks_and_grids() {
+        let ir = IR::VStack(vec![
+            IR::Text("Fish & Chips".to_string()),
+            IR::HStack(vec![IR::Image("logo".to_string()), IR::Button("Sign in".to_string())]),
+            IR::Spacer,
+            IR::Button("Sign in".to_string()),
+        ]);
+        let page = render_page("LoginPage", "MyApp", &ir, &RenderOptions::default(), &Metrics::default());
+        assert_eq!(
+            page,
+            r#"<ContentPage xmlns="http://schemas.microsoft.com/dotnet/2021/maui"
+             xmlns:x="http://schemas.microsoft.com/winfx/2009/xaml"
+             x:Class="MyApp.LoginPage">
+    <Grid RowDefinitions="Auto,Auto,*,Auto" RowSpacing="8" Padding="16">
+        <Label Text="Fish &amp; Chips" FontSize="28" Grid.Row="0" HorizontalOptions="Center" />
+        <HorizontalStackLayout Spacing="8" Padding="16" Grid.Row="1" HorizontalOptions="Center">
+            <Image Source="logo.png" WidthRequest="60" HeightRequest="60" VerticalOptions="Center" />
+            <Button Text="Sign in" Clicked="OnSignInClicked" VerticalOptions="Center" />
+        </HorizontalStackLayout>
+        <Button Text="Sign in" Clicked="OnSignIn2Clicked" Grid.Row="3" HorizontalOptions="Center" />
+    </Grid>
+</ContentPage>"#
+        );
+        assert_eq!(
+            render_code_behind("LoginPage", "MyApp", &ir, &RenderOptions::default()),
+            "namespace MyApp;
+
+public partial class LoginPage : ContentPage
+{
+    public LoginPage()
+    {
+        InitializeComponent();
+    }
+
+    private void OnSignInClicked(object sender, EventArgs e)
+    {
+    }
+
+    private void OnSignIn2Clicked(object sender, EventArgs e)
+    {
+    }
+}"
+        );
+    }
+
+    #[test]
+    fn test_custom_views_identifiers_and_names() {
+        let ir = IR::HStack(vec![IR::Custom { name: "AvatarView".to_string(), args: "user: me".to_string() }, IR::Button("+".to_string())]);
+        let options = RenderOptions { padding: false, accessibility_identifiers: true, ..RenderOptions::default() };
+        let page = render_page("MainPage", DEFAULT_NAMESPACE, &ir, &options, &Metrics::default());
+        assert!(page.contains("xmlns:local=\"clr-namespace:App\"\n             x:Class=\"App.MainPage\">"), "{}", page);
+        assert!(page.contains("<local:AvatarView VerticalOptions=\"Center\" />"), "{}", page);
+        assert!(page.contains("<Button Text=\"+\" Clicked=\"OnButtonClicked\" VerticalOptions=\"Center\" AutomationId=\"button\" />"), "{}", page);
+        assert!(!page.contains("Padding"), "{}", page);
+
+        assert_eq!(page_name("ContentView"), "MainPage");
+        assert_eq!(page_name("LoginView"), "LoginPage");
+        assert_eq!(page_name("Settings"), "SettingsPage");
+        assert_eq!(comment_header("// Generated by swiftui-synth\n// Command: swiftui-synth --target xaml"), "<!-- Generated by swiftui-synth -->");
+    }
+}
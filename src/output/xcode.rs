@@ -0,0 +1,531 @@
+// File: src/output/xcode.rs
+// Registers generated files with an Xcode project (`xcode add`). The `project.pbxproj` is
+// edited in place: new objects and list entries are inserted as text where Xcode would put
+// them, so the rest of the file, comments and ordering included, is left exactly as it was.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::synthesis::cache::fnv1a;
+
+/// A value of the property list a `project.pbxproj` is written in, with its byte range.
+#[derive(Clone, Debug, PartialEq)]
+struct Node {
+    value: Value,
+    start: usize,
+    end: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Dict(Vec<(String, Node)>),
+    Array(Vec<Node>),
+    String(String),
+}
+
+impl Node {
+    fn get(&self, key: &str) -> Option<&Node> {
+        match &self.value {
+            Value::Dict(entries) => entries.iter().find(|(name, _)| name == key).map(|(_, node)| node),
+            _ => None,
+        }
+    }
+
+    fn str(&self) -> Option<&str> {
+        match &self.value {
+            Value::String(text) => Some(text),
+            _ => None,
+        }
+    }
+
+    fn items(&self) -> &[Node] {
+        match &self.value {
+            Value::Array(items) => items,
+            _ => &[],
+        }
+    }
+
+    fn str_of(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(Node::str)
+    }
+}
+
+fn is_bare(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || "_$/:.-".contains(ch)
+}
+
+// `text` as a property list string: bare when Xcode would leave it bare, else quoted
+fn quote(text: &str) -> String {
+    if !text.is_empty() && text.chars().all(is_bare) {
+        text.to_string()
+    } else {
+        format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn err(&self, message: &str) -> String {
+        let line = self.text[..self.pos].matches('\n').count() + 1;
+        format!("{} at line {}", message, line)
+    }
+
+    // Skips whitespace and `/* */` and `//` comments
+    fn skip(&mut self) {
+        loop {
+            let rest = &self.text[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if trimmed.starts_with("/*") {
+                self.pos += trimmed.find("*/").map_or(trimmed.len(), |end| end + 2);
+            } else if trimmed.starts_with("//") {
+                self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+            } else {
+                return;
+            }
+        }
+    }
+
+    fn expect(&mut self, ch: char) -> Result<(), String> {
+        self.skip();
+        if !self.text[self.pos..].starts_with(ch) {
+            return Err(self.err(&format!("Expected '{}'", ch)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.skip();
+        let rest = &self.text[self.pos..];
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let (mut value, mut escaped) = (String::new(), false);
+            for (i, ch) in quoted.char_indices() {
+                match ch {
+                    _ if escaped => {
+                        value.push(match ch {
+                            'n' => '\n',
+                            't' => '\t',
+                            _ => ch,
+                        });
+                        escaped = false;
+                    }
+                    '\\' => escaped = true,
+                    '"' => {
+                        self.pos += i + 2;
+                        return Ok(value);
+                    }
+                    _ => value.push(ch),
+                }
+            }
+            return Err(self.err("Unterminated string"));
+        }
+        let len = rest.find(|ch| !is_bare(ch)).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.err("Expected a value"));
+        }
+        self.pos += len;
+        Ok(rest[..len].to_string())
+    }
+
+    fn value(&mut self) -> Result<Node, String> {
+        self.skip();
+        let start = self.pos;
+        let value = match self.text[self.pos..].chars().next() {
+            Some('{') => {
+                self.pos += 1;
+                let mut entries = Vec::new();
+                loop {
+                    self.skip();
+                    if self.text[self.pos..].starts_with('}') {
+                        self.pos += 1;
+                        break;
+                    }
+                    let key = self.string()?;
+                    self.expect('=')?;
+                    let value = self.value()?;
+                    self.expect(';')?;
+                    entries.push((key, value));
+                }
+                Value::Dict(entries)
+            }
+            Some('(') => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    self.skip();
+                    if self.text[self.pos..].starts_with(')') {
+                        self.pos += 1;
+                        break;
+                    }
+                    items.push(self.value()?);
+                    self.skip();
+                    match self.text[self.pos..].chars().next() {
+                        Some(',') => self.pos += 1,
+                        Some(')') => {}
+                        _ => return Err(self.err("Expected ',' or ')'")),
+                    }
+                }
+                Value::Array(items)
+            }
+            _ => Value::String(self.string()?),
+        };
+        Ok(Node { value, start, end: self.pos })
+    }
+}
+
+/// Where the files of a group end up, and whether Xcode picks them up by itself.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupLocation {
+    /// Directory of the group, relative to the directory holding the `.xcodeproj`.
+    pub dir: PathBuf,
+    /// The group is (inside) a folder Xcode keeps in sync with the disk, so files written
+    /// there need no registering.
+    pub synchronized: bool,
+}
+
+/// What [`Project::add_files`] changed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Added {
+    /// Target the files are compiled in.
+    pub target: String,
+    /// Files that were not in the project before.
+    pub files: Vec<String>,
+}
+
+/// The contents of a `project.pbxproj`.
+#[derive(Clone, Debug)]
+pub struct Project {
+    text: String,
+    root: Node,
+}
+
+impl Project {
+    pub fn parse(text: &str) -> Result<Project, String> {
+        let mut reader = Reader { text, pos: 0 };
+        let root = reader.value()?;
+        reader.skip();
+        if reader.pos < text.len() {
+            return Err(reader.err("Unexpected text after the project"));
+        }
+        if root.get("objects").is_none() || root.str_of("rootObject").is_none() {
+            return Err("Not an Xcode project: no objects or rootObject".to_string());
+        }
+        Ok(Project { text: text.to_string(), root })
+    }
+
+    /// Reads `<project>.xcodeproj/project.pbxproj`.
+    pub fn load(xcodeproj: &Path) -> Result<Project, String> {
+        let path = xcodeproj.join("project.pbxproj");
+        let text = fs::read_to_string(&path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+        Project::parse(&text).map_err(|e| format!("Invalid project file '{}': {}", path.display(), e))
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    fn objects(&self) -> &Node {
+        self.root.get("objects").expect("checked when parsed")
+    }
+
+    fn object(&self, id: &str) -> Option<&Node> {
+        self.objects().get(id)
+    }
+
+    fn main_group(&self) -> Result<&str, String> {
+        let project = self.root.str_of("rootObject").and_then(|id| self.object(id));
+        project.and_then(|project| project.str_of("mainGroup")).ok_or_else(|| "The project has no main group".to_string())
+    }
+
+    // Name a group or file is shown with in Xcode
+    fn display_name<'a>(&'a self, id: &str) -> Option<&'a str> {
+        let object = self.object(id)?;
+        object.str_of("name").or_else(|| object.str_of("path"))
+    }
+
+    // The child of group `parent` named `name`, if it is a group
+    fn child_group(&self, parent: &str, name: &str) -> Option<&str> {
+        let children = self.object(parent)?.get("children")?.items();
+        children.iter().filter_map(Node::str).find(|&id| {
+            let isa = self.object(id).and_then(|object| object.str_of("isa"));
+            matches!(isa, Some("PBXGroup" | "PBXFileSystemSynchronizedRootGroup")) && self.display_name(id) == Some(name)
+        })
+    }
+
+    /// Where the files of `group`, a path of group names such as `App/Generated`, are kept.
+    /// Groups that do not exist yet are folders of the same name.
+    pub fn locate(&self, group: &str) -> Result<GroupLocation, String> {
+        let mut id = self.main_group()?.to_string();
+        let mut dir = PathBuf::from(self.object(&id).and_then(|group| group.str_of("path")).unwrap_or_default());
+        let mut synchronized = false;
+        for name in group_names(group) {
+            match self.child_group(&id, name).filter(|_| !synchronized) {
+                Some(child) => {
+                    let object = self.object(child).expect("children are objects");
+                    synchronized = object.str_of("isa") == Some("PBXFileSystemSynchronizedRootGroup");
+                    // Groups without a path are only shown in Xcode and share their parent's folder
+                    if let Some(path) = object.str_of("path") {
+                        dir.push(path);
+                    }
+                    id = child.to_string();
+                }
+                None => {
+                    dir.push(name);
+                    id = String::new();
+                }
+            }
+        }
+        Ok(GroupLocation { dir, synchronized })
+    }
+
+    // A new object id: 24 hex digits, derived from `key` so the same edit gives the same ids
+    fn new_id(&self, key: &str) -> String {
+        (0u32..)
+            .map(|n| format!("{:016X}{:08X}", fnv1a(key.as_bytes()), n))
+            .find(|id| !self.text.contains(id.as_str()))
+            .expect("ids run out after 2^32 collisions")
+    }
+
+    // Inserts `text` at `at` and reads the project again
+    fn insert(&mut self, at: usize, text: &str) {
+        self.text.insert_str(at, text);
+        *self = Project::parse(&self.text).expect("inserted objects are well formed");
+    }
+
+    // Appends `item` (with its comment) to the array at `path` in object `id`
+    fn append(&mut self, id: &str, key: &str, item: &str) {
+        let array = self.object(id).and_then(|object| object.get(key)).expect("the list exists");
+        let close = array.end - 1;
+        let line_start = self.text[..close].rfind('\n').map_or(0, |i| i + 1);
+        let indent: String = self.text[line_start..].chars().take_while(|ch| *ch == '\t' || *ch == ' ').collect();
+        if self.text[line_start..close].trim().is_empty() {
+            self.insert(line_start, &format!("{}\t{},\n", indent, item));
+        } else {
+            // A list on one line, such as `()`
+            let prefix = if array.items().is_empty() { "" } else { "," };
+            let at = self.text[..close].trim_end().len();
+            self.insert(at, &format!("{}\n{}\t{},\n{}", prefix, indent, item, indent));
+        }
+    }
+
+    // Adds `object` (written out in full) to its section, creating the section if needed
+    fn add_object(&mut self, isa: &str, object: &str) {
+        let end = format!("/* End {} section */", isa);
+        let at = match self.text.find(&end) {
+            Some(at) => at,
+            None => {
+                let objects = self.objects().end - 1;
+                let at = self.text[..objects].rfind('\n').map_or(objects, |i| i + 1);
+                let section = format!("\n/* Begin {} section */\n{}\n", isa, end);
+                self.insert(at, &section);
+                self.text.find(&end).expect("just inserted")
+            }
+        };
+        self.insert(at, object);
+    }
+
+    // The source files build phase of the target named `name`, or of the first target
+    fn sources_phase(&self, name: Option<&str>) -> Result<(String, String), String> {
+        let project = self.root.str_of("rootObject").and_then(|id| self.object(id)).ok_or("The project has no root object")?;
+        let targets: Vec<(&str, &str)> = project
+            .get("targets")
+            .map(Node::items)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(Node::str)
+            .filter_map(|id| Some((id, self.object(id)?.str_of("name")?)))
+            .collect();
+        let (id, target) = match name {
+            Some(name) => targets.iter().find(|(_, target)| *target == name).copied().ok_or_else(|| {
+                let names: Vec<&str> = targets.iter().map(|(_, target)| *target).collect();
+                format!("No target '{}' in the project; it has {}", name, names.join(", "))
+            })?,
+            None => targets.first().copied().ok_or("The project has no targets")?,
+        };
+        let phases = self.object(id).and_then(|target| target.get("buildPhases")).map(Node::items).unwrap_or_default();
+        let phase = phases
+            .iter()
+            .filter_map(Node::str)
+            .find(|phase| self.object(phase).and_then(|object| object.str_of("isa")) == Some("PBXSourcesBuildPhase"))
+            .ok_or_else(|| format!("Target '{}' has no Compile Sources build phase", target))?;
+        Ok((target.to_string(), phase.to_string()))
+    }
+
+    // The id of group `group`, creating it and any missing parents as folders
+    fn ensure_group(&mut self, group: &str) -> Result<String, String> {
+        let mut id = self.main_group()?.to_string();
+        let mut path = String::new();
+        for name in group_names(group) {
+            path = format!("{}/{}", path, name);
+            id = match self.child_group(&id, name) {
+                Some(child) => child.to_string(),
+                None => {
+                    let child = self.new_id(&format!("group{}", path));
+                    let object = format!(
+                        "\t\t{} /* {} */ = {{\n\t\t\tisa = PBXGroup;\n\t\t\tchildren = (\n\t\t\t);\n\t\t\tpath = {};\n\t\t\tsourceTree = \"<group>\";\n\t\t}};\n",
+                        child,
+                        name,
+                        quote(name)
+                    );
+                    self.add_object("PBXGroup", &object);
+                    self.append(&id, "children", &format!("{} /* {} */", child, name));
+                    child
+                }
+            };
+        }
+        Ok(id)
+    }
+
+    /// Adds the Swift files `names` to `group` (created if needed) and to the sources built by
+    /// `target`, the first target if `None`. Files already in the group are not added again.
+    pub fn add_files(&mut self, group: &str, names: &[&str], target: Option<&str>) -> Result<Added, String> {
+        let (target, phase) = self.sources_phase(target)?;
+        let group_id = self.ensure_group(group)?;
+        let mut added = Vec::new();
+        for name in names {
+            let children = self.object(&group_id).and_then(|group| group.get("children")).map(Node::items).unwrap_or_default();
+            let existing = children.iter().filter_map(Node::str).find(|&id| {
+                let object = self.object(id);
+                object.and_then(|object| object.str_of("isa")) == Some("PBXFileReference") && self.display_name(id) == Some(*name)
+            });
+            let file = match existing {
+                Some(file) => file.to_string(),
+                None => {
+                    let file = self.new_id(&format!("file/{}/{}", group, name));
+                    let object = format!(
+                        "\t\t{} /* {} */ = {{isa = PBXFileReference; lastKnownFileType = sourcecode.swift; path = {}; sourceTree = \"<group>\"; }};\n",
+                        file,
+                        name,
+                        quote(name)
+                    );
+                    self.add_object("PBXFileReference", &object);
+                    self.append(&group_id, "children", &format!("{} /* {} */", file, name));
+                    added.push(name.to_string());
+                    file
+                }
+            };
+            let built = self.object(&phase).and_then(|phase| phase.get("files")).map(Node::items).unwrap_or_default().iter().any(|id| {
+                id.str().and_then(|id| self.object(id)).and_then(|build| build.str_of("fileRef")) == Some(file.as_str())
+            });
+            if !built {
+                let build = self.new_id(&format!("build/{}/{}/{}", target, group, name));
+                let object = format!("\t\t{} /* {} in Sources */ = {{isa = PBXBuildFile; fileRef = {} /* {} */; }};\n", build, name, file, name);
+                self.add_object("PBXBuildFile", &object);
+                self.append(&phase, "files", &format!("{} /* {} in Sources */", build, name));
+                if !added.iter().any(|added| added == name) {
+                    added.push(name.to_string());
+                }
+            }
+        }
+        Ok(Added { target, files: added })
+    }
+}
+
+/// The names of the groups in a path such as `MyApp/Generated`.
+pub fn group_names(group: &str) -> impl Iterator<Item = &str> {
+    group.split('/').filter(|name| !name.is_empty())
+}
+
+/// Directory of `target`'s sources in the Swift package at `package`, from which SwiftPM
+/// compiles every file: `Sources/<target>`, the only target there if `None`.
+pub fn package_sources(package: &Path, target: Option<&str>) -> Result<PathBuf, String> {
+    if !package.join("Package.swift").is_file() {
+        return Err(format!("No Package.swift in '{}'", package.display()));
+    }
+    let sources = package.join("Sources");
+    let mut targets: Vec<String> = fs::read_dir(&sources)
+        .map_err(|e| format!("Failed to read '{}': {}", sources.display(), e))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    targets.sort();
+    match target {
+        Some(target) if targets.iter().any(|name| name == target) => Ok(sources.join(target)),
+        Some(target) => Err(format!("No target '{}' in {}; it has {}", target, sources.display(), targets.join(", "))),
+        None => match targets.as_slice() {
+            [target] => Ok(sources.join(target)),
+            _ => Err(format!("The package has several targets ({}); choose one with --app-target", targets.join(", "))),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A project as Xcode writes it: one app target, its sources in the `Demo` group
+    const PROJECT: &str = include_str!("../../tests/fixtures/Demo.xcodeproj/project.pbxproj");
+
+    #[test]
+    fn test_parse_project() {
+        let project = Project::parse(PROJECT).unwrap();
+        assert_eq!(project.main_group().unwrap(), "AA0000000000000000000010");
+        assert_eq!(project.display_name("AA0000000000000000000012"), Some("Products"));
+        let file = project.object("AA0000000000000000000002").unwrap();
+        assert_eq!(file.str_of("sourceTree"), Some("<group>"));
+        assert_eq!(&PROJECT[file.start..file.end], "{isa = PBXFileReference; lastKnownFileType = sourcecode.swift; path = DemoApp.swift; sourceTree = \"<group>\"; }");
+        assert!(Project::parse("{ objects = { }; }").unwrap_err().contains("Not an Xcode project"));
+        assert!(Project::parse("{ objects = { a = (b c); }; }").unwrap_err().contains("Expected ',' or ')' at line 1"));
+    }
+
+    #[test]
+    fn test_locate_groups() {
+        let project = Project::parse(PROJECT).unwrap();
+        assert_eq!(project.locate("Demo/Generated").unwrap(), GroupLocation { dir: PathBuf::from("Demo/Generated"), synchronized: false });
+        // `Products` has only a name, so it shares the project's folder
+        assert_eq!(project.locate("Products/Views").unwrap().dir, PathBuf::from("Views"));
+        let synced = PROJECT.replace("isa = PBXGroup;\n\t\t\tchildren = (\n\t\t\t\tAA0000000000000000000002", "isa = PBXFileSystemSynchronizedRootGroup;\n\t\t\tchildren = (\n\t\t\t\tAA0000000000000000000002");
+        let project = Project::parse(&synced).unwrap();
+        assert_eq!(project.locate("Demo/Generated").unwrap(), GroupLocation { dir: PathBuf::from("Demo/Generated"), synchronized: true });
+    }
+
+    #[test]
+    fn test_add_files_to_a_new_group() {
+        let mut project = Project::parse(PROJECT).unwrap();
+        let added = project.add_files("Demo/Generated", &["LoginView.swift", "HomeView.swift"], None).unwrap();
+        assert_eq!(added, Added { target: "Demo".to_string(), files: vec!["LoginView.swift".to_string(), "HomeView.swift".to_string()] });
+
+        let group = project.child_group("AA0000000000000000000011", "Generated").unwrap().to_string();
+        let children: Vec<&str> = project.object(&group).unwrap().get("children").unwrap().items().iter().filter_map(Node::str).collect();
+        let names: Vec<&str> = children.iter().filter_map(|id| project.display_name(id)).collect();
+        assert_eq!(names, ["LoginView.swift", "HomeView.swift"]);
+        let (_, phase) = project.sources_phase(None).unwrap();
+        assert_eq!(project.object(&phase).unwrap().get("files").unwrap().items().len(), 3);
+        // Written where Xcode would write them, leaving the rest of the file as it was
+        assert!(project.text().contains(&format!(
+            "\t\t\t\tAA0000000000000000000002 /* DemoApp.swift */,\n\t\t\t\t{} /* Generated */,\n\t\t\t);\n\t\t\tpath = Demo;",
+            group
+        )));
+        assert!(project.text().contains("\t\t\tpath = Generated;\n\t\t\tsourceTree = \"<group>\";\n\t\t};\n/* End PBXGroup section */"));
+        assert!(project.text().contains("/* LoginView.swift in Sources */ = {isa = PBXBuildFile; fileRef = "));
+        assert!(project.text().starts_with("// !$*UTF8*$!\n{\n\tarchiveVersion = 1;"));
+
+        // Adding them again changes nothing; the ids are the same every time
+        let text = project.text().to_string();
+        assert!(project.add_files("Demo/Generated", &["LoginView.swift"], Some("Demo")).unwrap().files.is_empty());
+        assert_eq!(project.text(), text);
+        let mut again = Project::parse(PROJECT).unwrap();
+        again.add_files("Demo/Generated", &["LoginView.swift", "HomeView.swift"], None).unwrap();
+        assert_eq!(again.text(), text);
+    }
+
+    #[test]
+    fn test_add_files_errors() {
+        let mut project = Project::parse(PROJECT).unwrap();
+        assert_eq!(project.add_files("Demo", &["A.swift"], Some("Widget")).unwrap_err(), "No target 'Widget' in the project; it has Demo");
+        let no_phase = PROJECT.replace("\t\t\t\tAA0000000000000000000021 /* Sources */,\n", "");
+        let mut project = Project::parse(&no_phase).unwrap();
+        assert!(project.add_files("Demo", &["A.swift"], None).unwrap_err().contains("has no Compile Sources build phase"));
+        // A one-line empty list gets its first entry on a line of its own
+        let empty = PROJECT.replace("files = (\n\t\t\t\tAA0000000000000000000001 /* DemoApp.swift in Sources */,\n\t\t\t);", "files = ();");
+        let mut project = Project::parse(&empty).unwrap();
+        project.add_files("Demo", &["A.swift"], None).unwrap();
+        assert!(project.text().contains("files = (\n\t\t\t\t"), "{}", project.text());
+        assert_eq!(quote("Generated Views"), "\"Generated Views\"");
+    }
+}
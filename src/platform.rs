@@ -0,0 +1,130 @@
+// File: src/platform.rs
+// Apple platforms the generated SwiftUI targets (`--platform`). Each has its own layout
+// constants, device presets, default text style and idioms in the rendered code.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::layout::Metrics;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Platform {
+    /// iPhone and iPad.
+    #[default]
+    Ios,
+    Macos,
+    Tvos,
+    Watchos,
+}
+
+impl Platform {
+    pub const ALL: &'static [Platform] = &[Platform::Ios, Platform::Macos, Platform::Tvos, Platform::Watchos];
+
+    /// Name as written on the command line and in the config, e.g. `watchos`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Platform::Ios => "ios",
+            Platform::Macos => "macos",
+            Platform::Tvos => "tvos",
+            Platform::Watchos => "watchos",
+        }
+    }
+
+    /// Layout constants of the platform's system fonts and `.padding()`. tvOS is laid out
+    /// for viewing across a room, watchOS for a 40-50mm screen.
+    pub fn metrics(&self) -> Metrics {
+        let defaults = Metrics::default();
+        match self {
+            Platform::Ios => defaults,
+            Platform::Macos => Metrics {
+                title_font_size: 22.0,
+                title_line_height: 26.0,
+                body_font_size: 13.0,
+                body_line_height: 16.0,
+                custom_height: 28.0,
+                ..defaults
+            },
+            Platform::Tvos => Metrics {
+                padding: 40.0,
+                stack_spacing: 20.0,
+                title_font_size: 76.0,
+                title_line_height: 92.0,
+                body_font_size: 29.0,
+                body_line_height: 37.0,
+                image_size: 120.0,
+                custom_height: 66.0,
+                spacer_min_length: 16.0,
+                ..defaults
+            },
+            // Text is `.headline`, which is what watch apps title with
+            Platform::Watchos => Metrics {
+                padding: 8.0,
+                stack_spacing: 4.0,
+                title_font_size: 16.0,
+                title_line_height: 21.0,
+                body_font_size: 16.0,
+                body_line_height: 21.0,
+                image_size: 40.0,
+                custom_height: 32.0,
+                spacer_min_length: 4.0,
+                ..defaults
+            },
+        }
+    }
+
+    /// Text style of `Text` views unless the config sets one.
+    pub fn text_font(&self) -> &'static str {
+        match self {
+            Platform::Watchos => "headline",
+            _ => "title",
+        }
+    }
+
+    /// `platforms:` of a Swift package manifest holding the views.
+    pub fn package_platforms(&self) -> &'static str {
+        match self {
+            // The views use nothing iOS-only, so the package builds for the Mac too
+            Platform::Ios | Platform::Macos => "[.iOS(.v17), .macOS(.v14)]",
+            Platform::Tvos => "[.tvOS(.v17)]",
+            Platform::Watchos => "[.watchOS(.v10)]",
+        }
+    }
+}
+
+impl fmt::Display for Platform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Platform {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Platform::ALL.iter().copied().find(|platform| platform.name() == s.to_lowercase()).ok_or_else(|| {
+            let names: Vec<&str> = Platform::ALL.iter().map(Platform::name).collect();
+            format!("Unknown platform '{}': expected one of {}", s, names.join(", "))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_round_trip() {
+        for platform in Platform::ALL {
+            assert_eq!(platform.name().parse::<Platform>().unwrap(), *platform);
+        }
+        assert_eq!("watchOS".parse::<Platform>().unwrap(), Platform::Watchos);
+        assert_eq!("android".parse::<Platform>().unwrap_err(), "Unknown platform 'android': expected one of ios, macos, tvos, watchos");
+    }
+
+    #[test]
+    fn test_metrics_scale_with_the_viewing_distance() {
+        let (watch, phone, tv) = (Platform::Watchos.metrics(), Platform::Ios.metrics(), Platform::Tvos.metrics());
+        assert_eq!(phone, Metrics::default());
+        assert!(watch.padding < phone.padding && phone.padding < tv.padding);
+        assert!(watch.title_font_size < phone.title_font_size && phone.title_font_size < tv.title_font_size);
+    }
+}
@@ -0,0 +1,289 @@
+// File: src/synthesis/a11y.rs
+// Accessibility audit of a layout (`audit a11y`): views VoiceOver cannot name, color tokens
+// whose text would be hard to read, and buttons too small to tap reliably.
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::ast::{Example, IR};
+use crate::diagnostics::Severity;
+use crate::layout::Metrics;
+use crate::synthesis::lint::{small_touch_targets, LintOptions};
+use crate::synthesis::verify::describe;
+
+/// Contrast ratios WCAG 2.1 AA asks of body text, and of large text (at least 18pt, or 14pt bold).
+pub const MIN_CONTRAST: f64 = 4.5;
+pub const MIN_CONTRAST_LARGE: f64 = 3.0;
+
+/// An opaque sRGB color.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    /// Parses `#RGB`, `#RRGGBB` or `#RRGGBBAA`; the alpha is ignored.
+    pub fn parse(hex: &str) -> Option<Rgb> {
+        let digits = hex.strip_prefix('#')?;
+        if !digits.chars().all(|ch| ch.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |at: usize, len: usize| u8::from_str_radix(&digits[at..at + len], 16).ok().map(|v| if len == 1 { v * 17 } else { v });
+        match digits.len() {
+            3 => Some(Rgb { r: channel(0, 1)?, g: channel(1, 1)?, b: channel(2, 1)? }),
+            6 | 8 => Some(Rgb { r: channel(0, 2)?, g: channel(2, 2)?, b: channel(4, 2)? }),
+            _ => None,
+        }
+    }
+
+    /// Relative luminance, from 0 for black to 1 for white.
+    pub fn luminance(&self) -> f64 {
+        let linear = |channel: u8| {
+            let c = channel as f64 / 255.0;
+            if c <= 0.04045 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// Contrast ratio against `other`, from 1 (same luminance) to 21 (black on white).
+    pub fn contrast(&self, other: &Rgb) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+}
+
+impl fmt::Display for Rgb {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+}
+
+/// The color tokens of a JSON token file, named by their dotted path, e.g. `color.text.primary`.
+/// Objects nest freely; a leaf is any string holding a hex color, and a `value` or `$value`
+/// key (as Style Dictionary and the W3C format use) names its parent. Other tokens are skipped.
+pub fn load_colors(json: &str) -> Result<Vec<(String, Rgb)>, String> {
+    fn walk(value: &Value, path: &mut Vec<String>, out: &mut Vec<(String, Rgb)>) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map {
+                    let named = key != "value" && key != "$value";
+                    if named {
+                        path.push(key.clone());
+                    }
+                    walk(value, path, out);
+                    if named {
+                        path.pop();
+                    }
+                }
+            }
+            Value::String(text) => {
+                if let Some(color) = Rgb::parse(text.trim()) {
+                    out.push((path.join("."), color));
+                }
+            }
+            _ => {}
+        }
+    }
+    let value: Value = serde_json::from_str(json).map_err(|e| e.to_string())?;
+    let mut colors = Vec::new();
+    walk(&value, &mut Vec::new(), &mut colors);
+    Ok(colors)
+}
+
+/// One problem the audit found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Which check found it: `button-label`, `image-label`, `contrast` or `touch-target`.
+    pub rule: &'static str,
+    pub message: String,
+    pub help: String,
+}
+
+impl Finding {
+    fn new(severity: Severity, rule: &'static str, message: String, help: impl Into<String>) -> Finding {
+        Finding { severity, rule, message, help: help.into() }
+    }
+}
+
+fn labels(ir: &IR, out: &mut Vec<Finding>) {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => children.iter().for_each(|child| labels(child, out)),
+        IR::Button(title) if title.trim().is_empty() => out.push(Finding::new(
+            Severity::Error,
+            "button-label",
+            "Button has no title for VoiceOver to read".to_string(),
+            "give it a title, or add .accessibilityLabel(\"...\") saying what it does",
+        )),
+        IR::Button(title) if !title.chars().any(char::is_alphanumeric) => out.push(Finding::new(
+            Severity::Warning,
+            "button-label",
+            format!("{} is labelled only with symbols, which VoiceOver reads literally or not at all", describe(ir)),
+            "add .accessibilityLabel(\"...\") saying what it does",
+        )),
+        IR::Image(name) => out.push(Finding::new(
+            Severity::Warning,
+            "image-label",
+            format!("{} has no accessibility label, so VoiceOver reads its asset name \"{}\"", describe(ir), name),
+            format!("add .accessibilityLabel(\"...\"), or use Image(decorative: \"{}\") if it is only decoration", name),
+        )),
+        _ => {}
+    }
+}
+
+// Tokens named for a background are what the others are drawn on
+fn is_background(name: &str) -> bool {
+    name.split(['.', '-', '_', '/']).any(|part| {
+        let part = part.to_lowercase();
+        part.contains("background") || part == "bg"
+    })
+}
+
+/// Audits `ir`, laid out at each of `examples`' sizes. `colors` are the color tokens: every
+/// token not named for a background is checked against every one that is, or against white
+/// (the light-mode system background) if none is. Buttons are measured against the touch
+/// target of `options`.
+pub fn audit(ir: &IR, examples: &[Example], metrics: &Metrics, colors: &[(String, Rgb)], options: &LintOptions) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    labels(ir, &mut findings);
+
+    let white = [("white".to_string(), Rgb { r: 255, g: 255, b: 255 })];
+    let (backgrounds, foregrounds): (Vec<_>, Vec<_>) = colors.iter().partition(|(name, _)| is_background(name));
+    let backgrounds: Vec<&(String, Rgb)> = if backgrounds.is_empty() { white.iter().collect() } else { backgrounds };
+    for (name, color) in &foregrounds {
+        for (background, behind) in &backgrounds {
+            let ratio = color.contrast(behind);
+            if ratio >= MIN_CONTRAST {
+                continue;
+            }
+            let (severity, help) = if ratio < MIN_CONTRAST_LARGE {
+                (Severity::Error, format!("darken or lighten one of them to at least {}:1", MIN_CONTRAST))
+            } else {
+                (Severity::Warning, format!("use it only for large text (18pt, or 14pt bold), or raise it to {}:1", MIN_CONTRAST))
+            };
+            findings.push(Finding::new(
+                severity,
+                "contrast",
+                format!("{} {} on {} {} has a contrast of {:.2}:1, under the {}:1 WCAG AA minimum", name, color, background, behind, ratio, MIN_CONTRAST),
+                help,
+            ));
+        }
+    }
+
+    let minimum = options.min_touch_target;
+    for target in small_touch_targets(ir, examples, metrics, minimum, options.padding) {
+        findings.push(Finding::new(
+            Severity::Error,
+            "touch-target",
+            format!(
+                "{} is {:.0}x{:.0}pt in example {}, under the {}x{}pt minimum touch target",
+                describe(&target.node),
+                target.width,
+                target.height,
+                target.example + 1,
+                minimum,
+                minimum
+            ),
+            format!("give the button .frame(minWidth: {}, minHeight: {})", minimum, minimum),
+        ));
+    }
+    findings
+}
+
+/// One finding per line with its severity and rule, the help indented below it, and a count
+/// of each severity, e.g. `warning  image-label   Image "logo" has no accessibility label...`.
+pub fn report(findings: &[Finding]) -> String {
+    let width = findings.iter().map(|finding| finding.rule.len()).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    for finding in findings {
+        lines.push(format!("{:7}  {:width$}  {}", finding.severity.to_string(), finding.rule, finding.message, width = width));
+        lines.push(format!("{:7}  {:width$}  help: {}", "", "", finding.help, width = width));
+    }
+    let count = |severity| findings.iter().filter(|finding| finding.severity == severity).count();
+    let plural = |n: usize, word: &str| format!("{} {}{}", n, word, if n == 1 { "" } else { "s" });
+    lines.push(match findings.len() {
+        0 => "No accessibility problems found".to_string(),
+        _ => format!("{}, {}", plural(count(Severity::Error), "error"), plural(count(Severity::Warning), "warning")),
+    });
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    fn rules(findings: &[Finding]) -> Vec<(Severity, &'static str)> {
+        findings.iter().map(|finding| (finding.severity, finding.rule)).collect()
+    }
+
+    #[test]
+    fn test_contrast_ratios() {
+        let black = Rgb::parse("#000").unwrap();
+        let white = Rgb::parse("#FFFFFFFF").unwrap();
+        assert_eq!(black.contrast(&white), 21.0);
+        assert_eq!(white.contrast(&white), 1.0);
+        // #767676 is the lightest gray that passes AA on white
+        let gray = Rgb::parse("#767676").unwrap();
+        assert!(gray.contrast(&white) >= MIN_CONTRAST && Rgb::parse("#777777").unwrap().contrast(&white) < MIN_CONTRAST);
+        assert_eq!(gray.to_string(), "#767676");
+        assert_eq!(Rgb::parse("767676"), None);
+        assert_eq!(Rgb::parse("#12345"), None);
+    }
+
+    #[test]
+    fn test_load_colors_flattens_nested_tokens() {
+        let json = r##"{"color": {"text": {"primary": {"$value": "#111111"}}, "background": "#fff"}, "spacing": {"s": 8}, "font": "Inter"}"##;
+        assert_eq!(
+            load_colors(json).unwrap(),
+            vec![("color.background".to_string(), Rgb { r: 255, g: 255, b: 255 }), ("color.text.primary".to_string(), Rgb { r: 17, g: 17, b: 17 })]
+        );
+        assert!(load_colors("{").is_err());
+    }
+
+    #[test]
+    fn test_audit() {
+        let examples = parse_examples("{(width:390,height:844):{Image:\"logo\",button:\"+\",button:\"Go\"}}").unwrap();
+        let ir = IR::VStack(vec![IR::Image("logo".to_string()), IR::Button("+".to_string()), IR::Button("Go".to_string()), IR::Button(" ".to_string())]);
+        let colors = vec![
+            ("text".to_string(), Rgb::parse("#222222").unwrap()),
+            ("hint".to_string(), Rgb::parse("#888888").unwrap()),
+            ("accent".to_string(), Rgb::parse("#FFCC00").unwrap()),
+            ("bg".to_string(), Rgb::parse("#FFFFFF").unwrap()),
+        ];
+        let findings = audit(&ir, &examples, &Metrics::default(), &colors, &LintOptions::default());
+        assert_eq!(
+            rules(&findings),
+            vec![
+                (Severity::Warning, "image-label"),
+                (Severity::Warning, "button-label"),
+                (Severity::Error, "button-label"),
+                (Severity::Warning, "contrast"),
+                (Severity::Error, "contrast"),
+                // A one-character title is narrower than a fingertip even with padding
+                (Severity::Error, "touch-target"),
+                (Severity::Error, "touch-target"),
+            ]
+        );
+        assert!(findings[5].message.starts_with("Button \"+\" is "), "{}", findings[5].message);
+        assert_eq!(findings[3].message, "hint #888888 on bg #FFFFFF has a contrast of 3.54:1, under the 4.5:1 WCAG AA minimum");
+
+        // Without padding only the label is tappable; colors default to white backgrounds
+        let unpadded = LintOptions { padding: false, ..LintOptions::default() };
+        let findings = audit(&IR::VStack(vec![IR::Button("Go".to_string())]), &examples, &Metrics::default(), &colors[..1], &unpadded);
+        assert_eq!(rules(&findings), vec![(Severity::Error, "touch-target")]);
+    }
+
+    #[test]
+    fn test_report() {
+        let findings = vec![Finding::new(Severity::Error, "contrast", "a on b is low".to_string(), "fix it")];
+        assert_eq!(report(&findings), "error    contrast  a on b is low\n                   help: fix it\n1 error, 0 warnings");
+        assert_eq!(report(&[]), "No accessibility problems found");
+    }
+}
@@ -0,0 +1,328 @@
+// File: src/synthesis/assign.rs
+// Slot assignment (`--strategy assignment`): rather than enumerating orderings and nestings,
+// the requested views are assigned to the cells of a grid of rows by solving an integer
+// program, so screens with many views cost a polynomial number of candidates.
+use std::time::Instant;
+
+use crate::ast::{Example, IR};
+use crate::layout::{self, Insets, Metrics, Rect};
+use crate::synthesis::evaluate::{self, CostWeights};
+use crate::synthesis::search::{Budget, Outcome, SearchStats};
+use crate::synthesis::swiftui::synthesize_traced;
+use crate::synthesis::verify::{describe, expected_frames, expected_leaves, verify};
+
+/// The assignment of each row of `cost` to a distinct column minimizing the total cost, as
+/// column indices by row; there must be no more rows than columns.
+///
+/// This is the 0-1 integer program `min Σ cost[i][j]·x[i][j]` subject to every row taking
+/// one column and every column at most one row. Its constraint matrix is totally unimodular,
+/// so the Hungarian algorithm used here finds the integer optimum exactly, in O(n²m).
+pub fn hungarian(cost: &[Vec<f64>]) -> Vec<usize> {
+    let (n, m) = (cost.len(), cost.first().map_or(0, Vec::len));
+    assert!(n <= m, "more rows than columns to assign them to");
+    // Potentials of rows (u) and columns (v), and the row each column is matched to (1-based,
+    // 0 is none); column 0 is a sentinel
+    let (mut u, mut v) = (vec![0.0; n + 1], vec![0.0; m + 1]);
+    let mut matched = vec![0usize; m + 1];
+    let mut way = vec![0usize; m + 1];
+    for row in 1..=n {
+        matched[0] = row;
+        let mut column = 0;
+        let mut min = vec![f64::INFINITY; m + 1];
+        let mut used = vec![false; m + 1];
+        loop {
+            used[column] = true;
+            let current = matched[column];
+            let (mut delta, mut next) = (f64::INFINITY, 0);
+            for j in 1..=m {
+                if used[j] {
+                    continue;
+                }
+                let reduced = cost[current - 1][j - 1] - u[current] - v[j];
+                if reduced < min[j] {
+                    (min[j], way[j]) = (reduced, column);
+                }
+                if min[j] < delta {
+                    (delta, next) = (min[j], j);
+                }
+            }
+            for j in 0..=m {
+                if used[j] {
+                    u[matched[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min[j] -= delta;
+                }
+            }
+            column = next;
+            if matched[column] == 0 {
+                break;
+            }
+        }
+        // Flip the augmenting path
+        while column != 0 {
+            let previous = way[column];
+            matched[column] = matched[previous];
+            column = previous;
+        }
+    }
+    let mut assignment = vec![0; n];
+    for (column, row) in matched.iter().enumerate().skip(1) {
+        if *row > 0 {
+            assignment[row - 1] = column - 1;
+        }
+    }
+    assignment
+}
+
+// Where each requested view is stated to be in each example, as the center point it implies
+// (either coordinate may be unstated); views are matched to stated frames in order
+fn stated_centers(leaves: &[IR], examples: &[Example], metrics: &Metrics) -> Vec<Vec<(Option<f64>, Option<f64>)>> {
+    examples
+        .iter()
+        .map(|example| {
+            let mut stated = expected_frames(example);
+            let screen = layout::screen_for(&IR::VStack(leaves.to_vec()), example, metrics).unwrap_or(layout::Size::new(0.0, 0.0));
+            leaves
+                .iter()
+                .map(|leaf| {
+                    let Some(position) = stated.iter().position(|(node, _)| node == leaf) else { return (None, None) };
+                    let (_, frame) = stated.remove(position);
+                    // Unstated sizes are the view's own
+                    let size = layout::simulate(leaf, screen, metrics).frames[0].rect;
+                    let center = |start: Option<f64>, length: Option<f64>, own: f64| start.map(|start| start + length.unwrap_or(own) / 2.0);
+                    (center(frame.x, frame.width, size.width), center(frame.y, frame.height, size.height))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// The cells of a `rows` by `columns` grid over each example's safe area, in reading order
+fn cells(rows: usize, columns: usize, examples: &[Example], leaves: &[IR], metrics: &Metrics) -> Vec<Vec<Rect>> {
+    let ignores_safe_area = layout::ignores_safe_area(examples);
+    examples
+        .iter()
+        .map(|example| {
+            let screen = layout::screen_for(&IR::VStack(leaves.to_vec()), example, metrics).unwrap_or(layout::Size::new(0.0, 0.0));
+            let insets = if ignores_safe_area { Insets::default() } else { layout::safe_area(example) };
+            let bounds = layout::simulate_within(&IR::Spacer, screen, insets, metrics).bounds();
+            let (width, height) = (bounds.width / columns as f64, bounds.height / rows as f64);
+            (0..rows * columns)
+                .map(|slot| Rect::new(bounds.x + (slot % columns) as f64 * width, bounds.y + (slot / columns) as f64 * height, width, height))
+                .collect()
+        })
+        .collect()
+}
+
+fn stack(vertical: bool, children: Vec<IR>) -> IR {
+    if vertical { IR::VStack(children) } else { IR::HStack(children) }
+}
+
+// Children of a stack from its slots in order: empty slots between, before or after views
+// become one spacer each run, and a stack with only spacers is dropped
+fn line(slots: impl Iterator<Item = Option<IR>>) -> Vec<IR> {
+    let mut children = Vec::new();
+    for slot in slots {
+        match slot {
+            Some(view) => children.push(view),
+            None if children.last() != Some(&IR::Spacer) => children.push(IR::Spacer),
+            None => {}
+        }
+    }
+    if children.iter().all(|child| *child == IR::Spacer) {
+        children.clear();
+    }
+    children
+}
+
+// The layout of a filled grid: a column of rows (`by_rows`) or a row of columns, each line
+// with one view being that view
+fn grid(filled: &[Option<IR>], rows: usize, columns: usize, by_rows: bool) -> IR {
+    let (outer, inner) = if by_rows { (rows, columns) } else { (columns, rows) };
+    let cell = |a: usize, b: usize| if by_rows { filled[a * columns + b].clone() } else { filled[b * columns + a].clone() };
+    let lines = (0..outer).map(|a| {
+        let children = line((0..inner).map(|b| cell(a, b)));
+        match children.as_slice() {
+            [] => None,
+            [view] => Some(view.clone()),
+            _ => Some(stack(!by_rows, children)),
+        }
+    });
+    let children = line(lines);
+    match children.as_slice() {
+        [only @ (IR::VStack(_) | IR::HStack(_))] => only.clone(),
+        _ => stack(by_rows, children),
+    }
+}
+
+/// Synthesizes a layout by assigning the requested views to grid slots.
+///
+/// The heuristic layout from `synthesize_layout` is scored first and returned as is when it
+/// already satisfies the examples, they state no frames and only one layout is wanted.
+/// Otherwise, for each grid of `rows` by `columns` cells with room for every view (and no
+/// more than twice the room), the views are assigned to cells by [`hungarian`], minimizing
+/// the geometric error between each view's stated center and its cell's, in every example,
+/// plus a structure cost: `weights.depth` for a view sharing its row with others, and a
+/// small cost for moving a view from its place in reading order, which alone decides
+/// between cells when no frames are stated. The filled grid becomes a column of rows and a
+/// row of columns, with empty cells as spacers, and each is ranked by its violations and
+/// weighted cost. An `HStack` example keeps its views in order in a single row.
+pub fn solve(examples: &[Example], budget: &Budget, metrics: &Metrics, weights: &CostWeights) -> Option<Outcome> {
+    let (heuristic, mut trace) = synthesize_traced(examples.to_vec())?;
+    let started = Instant::now();
+    let mut stats = SearchStats::default();
+    let mut ranked: Vec<(usize, f64, IR)> = Vec::new();
+    let mut exhausted = None;
+    let score = |ir: IR, ranked: &mut Vec<(usize, f64, IR)>, stats: &mut SearchStats| {
+        stats.enumerated += 1;
+        if ranked.iter().any(|(_, _, seen)| seen.canonicalize() == ir.canonicalize()) {
+            stats.pruned += 1;
+            return;
+        }
+        stats.scored += 1;
+        stats.simulated += examples.len();
+        let violations = verify(&ir, examples, metrics).len();
+        let cost = evaluate::cost(&ir, examples, metrics).total(weights);
+        let rank = ranked.partition_point(|(best, best_cost, _)| (*best, *best_cost) <= (violations, cost));
+        ranked.insert(rank, (violations, cost, ir));
+    };
+    score(heuristic.clone(), &mut ranked, &mut stats);
+
+    let geometry = examples.iter().any(|example| !expected_frames(example).is_empty());
+    let (leaves, ordered) = expected_leaves(&examples[0]);
+    let mut grids = 0;
+    if (ranked[0].0 > 0 || geometry || budget.top_n > 1) && !leaves.is_empty() {
+        let n = leaves.len();
+        let centers = stated_centers(&leaves, examples, metrics);
+        let skeletons: Vec<(usize, usize)> = if ordered {
+            (n..=2 * n).map(|columns| (1, columns)).collect()
+        } else {
+            (1..=2 * n).flat_map(|rows| (1..=2 * n).map(move |columns| (rows, columns))).filter(|(r, c)| (n..=2 * n).contains(&(r * c))).collect()
+        };
+        for (rows, columns) in skeletons {
+            if let Some(reason) = budget.reached(started, &stats) {
+                exhausted = Some(reason);
+                break;
+            }
+            grids += 1;
+            let cells = cells(rows, columns, examples, &leaves, metrics);
+            let slots = rows * columns;
+            let cost: Vec<Vec<f64>> = (0..n)
+                .map(|view| {
+                    (0..slots)
+                        .map(|slot| {
+                            let error: f64 = centers
+                                .iter()
+                                .zip(&cells)
+                                .map(|(centers, cells)| {
+                                    let (cell, (x, y)) = (&cells[slot], centers[view]);
+                                    x.map_or(0.0, |x| (x - (cell.x + cell.width / 2.0)).abs()) + y.map_or(0.0, |y| (y - (cell.y + cell.height / 2.0)).abs())
+                                })
+                                .sum();
+                            let shared = if columns > 1 && rows > 1 { weights.depth } else { 0.0 };
+                            let displaced = 0.01 * (slot as f64 - view as f64 * slots as f64 / n as f64).abs();
+                            weights.geometry * error + shared + displaced
+                        })
+                        .collect()
+                })
+                .collect();
+            let mut filled = vec![None; slots];
+            for (view, slot) in hungarian(&cost).into_iter().enumerate() {
+                filled[slot] = Some(leaves[view].clone());
+            }
+            if ordered && filled.iter().flatten().ne(leaves.iter()) {
+                continue;
+            }
+            score(grid(&filled, rows, columns, true), &mut ranked, &mut stats);
+            if rows > 1 && columns > 1 {
+                score(grid(&filled, rows, columns, false), &mut ranked, &mut stats);
+            }
+        }
+    }
+    if ranked.iter().any(|(violations, _, _)| *violations == 0) {
+        exhausted = None;
+    }
+
+    let mut ranked = ranked.into_iter().take(budget.top_n.max(1));
+    let (violations, _, ir) = ranked.next().expect("the heuristic layout was scored");
+    let alternatives = ranked.filter(|(violations, _, _)| *violations == 0).map(|(_, _, ir)| ir).collect();
+    if ir != heuristic {
+        let reason = match verify(&heuristic, examples, metrics).first() {
+            Some(violation) => violation.to_string(),
+            None => format!("costs more ({})", evaluate::cost(&heuristic, examples, metrics)),
+        };
+        trace = vec![format!("Direct translation {} rejected: {}", describe(&heuristic), reason)];
+    }
+    if grids > 0 {
+        trace.push(format!("Assigned {} views to the cells of {} grids, each an exact minimum-cost assignment", leaves.len(), grids));
+    }
+    match exhausted {
+        Some(reason) => trace.push(format!(
+            "Search stopped ({}) after {} candidates; showing the layout with the fewest problems ({})",
+            reason, stats.scored, violations
+        )),
+        None if violations > 0 => trace.push(format!(
+            "No grid assignment satisfies every example; showing the layout with the fewest problems ({})",
+            violations
+        )),
+        None => {}
+    }
+    Some(Outcome { ir, violations, candidates: stats.scored, exhausted, trace, stats, alternatives })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    #[test]
+    fn test_hungarian_finds_the_optimal_assignment() {
+        let cost = vec![vec![4.0, 1.0, 3.0], vec![2.0, 0.0, 5.0], vec![3.0, 2.0, 2.0]];
+        assert_eq!(hungarian(&cost), [1, 0, 2]);
+        // Fewer rows than columns leave the costliest columns empty
+        let cost = vec![vec![9.0, 1.0, 7.0, 8.0], vec![1.0, 2.0, 9.0, 9.0]];
+        assert_eq!(hungarian(&cost), [1, 0]);
+        assert_eq!(hungarian(&[]), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_grids_become_stacks_with_spacers() {
+        let text = |s: &str| Some(IR::Text(s.to_string()));
+        let filled = [text("A"), None, text("B"), None, None, None, text("C"), text("D"), None];
+        assert_eq!(
+            grid(&filled, 3, 3, true),
+            IR::VStack(vec![
+                IR::HStack(vec![IR::Text("A".to_string()), IR::Spacer, IR::Text("B".to_string())]),
+                IR::Spacer,
+                IR::HStack(vec![IR::Text("C".to_string()), IR::Text("D".to_string()), IR::Spacer]),
+            ])
+        );
+        assert_eq!(grid(&[text("A"), text("B")], 1, 2, true), IR::HStack(vec![IR::Text("A".to_string()), IR::Text("B".to_string())]));
+    }
+
+    #[test]
+    fn test_assignment_follows_the_stated_frames() {
+        // Two rows of two at the top and bottom of the screen
+        let examples = parse_examples(
+            "{(width:390,height:844):{title:\"Name\"@(x:16,y:16),button:\"Edit\"@(x:300,y:16),\
+             button:\"Cancel\"@(x:16,y:760),button:\"Save\"@(x:300,y:760)}}",
+        )
+        .unwrap();
+        let outcome = solve(&examples, &Budget::default(), &Metrics::default(), &CostWeights::default()).unwrap();
+        assert_eq!(outcome.violations, 0);
+        // Either a column of two rows or a row of two columns, split by a spacer
+        let (IR::VStack(children) | IR::HStack(children)) = &outcome.ir else { panic!("{:?}", outcome.ir) };
+        assert!(matches!(children.as_slice(), [IR::HStack(_), IR::Spacer, IR::HStack(_)] | [IR::VStack(_), IR::VStack(_)]), "{:?}", outcome.ir);
+        assert!(outcome.trace.iter().any(|line| line.starts_with("Assigned 4 views")), "{:?}", outcome.trace);
+    }
+
+    #[test]
+    fn test_many_views_need_few_candidates() {
+        let views: Vec<String> = (1..=10).map(|i| format!("title:\"Row {}\"", i)).collect();
+        let examples = parse_examples(&format!("{{(width:390,height:500):{{{}}}}}", views.join(","))).unwrap();
+        let outcome = solve(&examples, &Budget::default(), &Metrics::default(), &CostWeights::default()).unwrap();
+        assert_eq!(outcome.violations, 0, "{:?}", outcome.ir);
+        assert!(outcome.candidates < 200, "{}", outcome.candidates);
+    }
+}
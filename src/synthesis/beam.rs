@@ -0,0 +1,340 @@
+// File: src/synthesis/beam.rs
+// Beam search over stack arrangements (`--strategy beam`): layouts are built one view at a
+// time, and at each step only the `budget.beam_width` partial layouts that fit the examples
+// best so far are expanded, so the work grows with the number of views, not exponentially.
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+use crate::ast::{Example, IR};
+use crate::layout::{self, Insets, Metrics};
+use crate::synthesis::evaluate::{self, CostWeights};
+use crate::synthesis::fidelity;
+use crate::synthesis::intern::LayoutSet;
+use crate::synthesis::search::{Budget, Exhausted, Outcome, SearchStats};
+use crate::synthesis::swiftui::synthesize_traced;
+use crate::synthesis::verify::{describe, expected_frames, expected_leaves, verify};
+
+// A layout with some of the views placed: the children of the root stack so far, the last
+// of which may be a stack of the other axis that later views can join
+#[derive(Clone, Debug)]
+struct Partial {
+    vertical: bool,
+    children: Vec<IR>,
+    remaining: Vec<IR>,
+}
+
+fn stack(vertical: bool, children: Vec<IR>) -> IR {
+    if vertical { IR::VStack(children) } else { IR::HStack(children) }
+}
+
+impl Partial {
+    fn ir(&self) -> IR {
+        stack(self.vertical, self.children.clone())
+    }
+
+    // The layout completed in a few quick ways, to judge how well it can still turn out: the
+    // remaining views in the root stack, in the last child's stack or in a new stack of the
+    // other axis, each with and without a spacer after them
+    fn completions(&self) -> Vec<IR> {
+        let mut completions = vec![self.children.iter().chain(&self.remaining).cloned().collect::<Vec<IR>>()];
+        if !self.remaining.is_empty() {
+            let mut nested = self.children.clone();
+            match nested.last_mut() {
+                Some(IR::VStack(inner) | IR::HStack(inner)) => inner.extend(self.remaining.iter().cloned()),
+                _ => nested.push(stack(!self.vertical, self.remaining.clone())),
+            }
+            completions.push(nested);
+            let mut grouped = self.children.clone();
+            grouped.push(stack(!self.vertical, self.remaining.clone()));
+            completions.push(grouped);
+        }
+        completions
+            .into_iter()
+            .flat_map(|children| {
+                let spaced = children.iter().cloned().chain([IR::Spacer]).collect();
+                [stack(self.vertical, children), stack(self.vertical, spaced)]
+            })
+            .collect()
+    }
+
+    // Every way of placing one more view: as a child of the root, after a spacer, or in a
+    // stack of the other axis with the child before it (`nest`)
+    fn expand(&self, ordered: bool, nest: bool) -> Vec<Partial> {
+        let mut next = Vec::new();
+        let choices = if ordered { self.remaining.len().min(1) } else { self.remaining.len() };
+        for index in 0..choices {
+            let view = &self.remaining[index];
+            if self.remaining[..index].contains(view) {
+                continue;
+            }
+            let mut remaining = self.remaining.clone();
+            remaining.remove(index);
+            let mut place = |children: Vec<IR>| next.push(Partial { vertical: self.vertical, children, remaining: remaining.clone() });
+            let mut children = self.children.clone();
+            let last = children.pop();
+            let kept = |last: &Option<IR>| children.iter().cloned().chain(last.clone()).collect::<Vec<IR>>();
+            place(kept(&last).into_iter().chain([view.clone()]).collect());
+            if last != Some(IR::Spacer) {
+                place(kept(&last).into_iter().chain([IR::Spacer, view.clone()]).collect());
+            }
+            if !nest {
+                continue;
+            }
+            let nested = match &last {
+                Some(IR::VStack(inner) | IR::HStack(inner)) => inner.clone(),
+                Some(IR::Spacer) | None => continue,
+                Some(leaf) => vec![leaf.clone()],
+            };
+            for gap in [vec![], vec![IR::Spacer]] {
+                let inner: Vec<IR> = nested.iter().cloned().chain(gap).chain([view.clone()]).collect();
+                place(children.iter().cloned().chain([stack(!self.vertical, inner)]).collect());
+            }
+        }
+        next
+    }
+}
+
+// How far a partial layout is from fitting the examples: points by which its content
+// overflows each screen, plus points between the views it has placed and the frames the
+// examples state for them. Lower is better; views not placed yet do not count.
+pub(crate) fn misfit(ir: &IR, examples: &[Example], metrics: &Metrics) -> f64 {
+    let ignores_safe_area = layout::ignores_safe_area(examples);
+    let mut total = 0.0;
+    for example in examples {
+        let metrics = &metrics.scaled(layout::dynamic_type(example).scale());
+        let Some(screen) = layout::screen_for(ir, example, metrics) else { continue };
+        let insets = if ignores_safe_area { Insets::default() } else { layout::safe_area(example) };
+        let simulated = layout::simulate_within(ir, screen, insets, metrics);
+        let (needed, bounds) = (simulated.required, simulated.bounds());
+        total += (needed.width - bounds.width).max(0.0) + (needed.height - bounds.height).max(0.0);
+    }
+    let deviation: f64 = fidelity::measure(ir, examples, metrics)
+        .iter()
+        .flat_map(|measurement| measurement.deltas())
+        .map(|(_, delta)| delta.abs())
+        .sum();
+    total + deviation
+}
+
+// The views of `ir` in order, without stacks and spacers
+fn views<'a>(ir: &'a IR, out: &mut Vec<&'a IR>) {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => children.iter().for_each(|child| views(child, out)),
+        IR::Spacer => {}
+        view => out.push(view),
+    }
+}
+
+struct Beam<'a> {
+    examples: &'a [Example],
+    metrics: &'a Metrics,
+    budget: &'a Budget,
+    weights: &'a CostWeights,
+    started: Instant,
+    /// The `budget.top_n` best complete layouts: fewest violations, then lowest weighted cost.
+    ranked: Vec<(usize, f64, IR)>,
+    satisfying: usize,
+    exhausted: Option<Exhausted>,
+    seen: LayoutSet,
+    stats: SearchStats,
+}
+
+impl Beam<'_> {
+    // Whether the budget has run out; running out after a satisfying layout was found does
+    // not count as exhausting it, unless the search was cancelled
+    fn out_of_budget(&mut self) -> bool {
+        let Some(reason) = self.budget.reached(self.started, &self.stats) else {
+            return false;
+        };
+        if self.satisfying == 0 || reason == Exhausted::Cancelled {
+            self.exhausted = Some(reason);
+        }
+        true
+    }
+
+    // Ranks a complete layout against the examples, as the enumerative search does
+    fn score(&mut self, ir: IR) -> usize {
+        self.stats.enumerated += 1;
+        if !self.seen.insert(&ir.canonicalize()) {
+            self.stats.pruned += 1;
+            return usize::MAX;
+        }
+        self.stats.memory_bytes = self.seen.bytes();
+        self.stats.scored += 1;
+        self.stats.simulated += self.examples.len();
+        let violations = verify(&ir, self.examples, self.metrics).len();
+        if violations == 0 {
+            self.satisfying += 1;
+        }
+        let cost = evaluate::cost(&ir, self.examples, self.metrics).total(self.weights);
+        let rank = self.ranked.partition_point(|(best, best_cost, _)| (*best, *best_cost) <= (violations, cost));
+        if rank < self.budget.top_n.max(1) {
+            self.ranked.insert(rank, (violations, cost, ir));
+            self.ranked.truncate(self.budget.top_n.max(1));
+        }
+        violations
+    }
+
+    // The heuristic of a partial layout: the least misfit of its quick completions, then how
+    // many views it places out of the example's order, then its cost to break ties
+    fn heuristic(&mut self, partial: &Partial, order: &[IR]) -> (f64, usize, f64) {
+        let completions = partial.completions();
+        self.stats.scored += 1;
+        self.stats.simulated += completions.len() * self.examples.len();
+        let fit = completions.iter().map(|ir| misfit(ir, self.examples, self.metrics)).fold(f64::INFINITY, f64::min);
+        let ir = partial.ir();
+        let mut placed = Vec::new();
+        views(&ir, &mut placed);
+        let out_of_order = placed.iter().zip(order).filter(|(a, b)| **a != *b).count();
+        (fit, out_of_order, evaluate::cost(&ir, self.examples, self.metrics).total(self.weights))
+    }
+}
+
+/// Searches for a layout satisfying every example with a beam of `budget.beam_width`.
+///
+/// The heuristic layout from `synthesize_layout` is scored first and returned as is when it
+/// already satisfies the examples, they state no frames and only one layout is wanted.
+/// Otherwise layouts are grown from an empty `VStack` and `HStack`, one requested view at a
+/// time (in the example's order, or any order unless it is an `HStack` example). Each view
+/// joins the root stack, after a spacer or not, or, within `budget.max_depth`, a stack of the
+/// other axis with the view before it. Of the layouts at each step, the `beam_width` whose
+/// content overflows the screens least and whose placed views land closest to their stated
+/// frames are kept. Complete layouts are ranked like the enumerative search's, and every
+/// layout scored counts towards `budget.max_candidates`.
+pub fn search(examples: &[Example], budget: &Budget, metrics: &Metrics, weights: &CostWeights) -> Option<Outcome> {
+    let (heuristic, mut trace) = synthesize_traced(examples.to_vec())?;
+    let mut beam = Beam {
+        examples,
+        metrics,
+        budget,
+        weights,
+        started: Instant::now(),
+        ranked: Vec::new(),
+        satisfying: 0,
+        exhausted: None,
+        seen: LayoutSet::new(),
+        stats: SearchStats::default(),
+    };
+
+    let geometry = examples.iter().any(|example| !expected_frames(example).is_empty());
+    let (leaves, ordered) = expected_leaves(&examples[0]);
+    let mut levels = 0;
+    if beam.score(heuristic.clone()) > 0 || geometry || budget.top_n > 1 {
+        let nest = budget.max_depth >= 2;
+        let width = budget.beam_width.max(1);
+        let mut frontier: Vec<Partial> =
+            [true, false].into_iter().map(|vertical| Partial { vertical, children: Vec::new(), remaining: leaves.clone() }).collect();
+        while !frontier.is_empty() && !beam.out_of_budget() {
+            levels += 1;
+            let mut scored = Vec::new();
+            let mut expanded = BTreeSet::new();
+            for partial in frontier.iter().flat_map(|partial| partial.expand(ordered, nest)) {
+                if !expanded.insert((partial.ir().canonicalize(), partial.remaining.clone())) {
+                    continue;
+                }
+                if partial.remaining.is_empty() {
+                    let mut spaced = partial.children.clone();
+                    if spaced.last() != Some(&IR::Spacer) {
+                        spaced.push(IR::Spacer);
+                        beam.score(stack(partial.vertical, spaced));
+                    }
+                    beam.score(partial.ir());
+                } else {
+                    let key = beam.heuristic(&partial, &leaves);
+                    scored.push((key, partial));
+                }
+                if beam.out_of_budget() {
+                    break;
+                }
+            }
+            // Ties keep the earlier layout ahead
+            scored.sort_by(|(a, _), (b, _)| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)).then(a.2.total_cmp(&b.2)));
+            frontier = scored.into_iter().take(width).map(|(_, partial)| partial).collect();
+        }
+    }
+
+    let mut ranked = beam.ranked.into_iter();
+    let (violations, _, ir) = ranked.next().expect("the heuristic layout was scored");
+    let alternatives = ranked.filter(|(violations, _, _)| *violations == 0).map(|(_, _, ir)| ir).collect();
+    if ir != heuristic {
+        let reason = match verify(&heuristic, examples, metrics).first() {
+            Some(violation) => violation.to_string(),
+            None => format!("costs more ({})", evaluate::cost(&heuristic, examples, metrics)),
+        };
+        trace = vec![format!("Direct translation {} rejected: {}", describe(&heuristic), reason)];
+    }
+    if levels > 0 {
+        trace.push(format!(
+            "Beam search kept the {} best of the partial layouts at each of {} steps, placing {} views",
+            budget.beam_width.max(1),
+            levels,
+            leaves.len()
+        ));
+    }
+    match beam.exhausted {
+        Some(reason) => trace.push(format!(
+            "Search stopped ({}) after {} candidates; showing the layout with the fewest problems ({})",
+            reason, beam.stats.scored, violations
+        )),
+        None if violations > 0 => trace.push(format!(
+            "No layout the beam reached satisfies every example; showing the one with the fewest problems ({})",
+            violations
+        )),
+        None => {}
+    }
+    Some(Outcome { ir, violations, candidates: beam.stats.scored, exhausted: beam.exhausted, trace, stats: beam.stats, alternatives })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+    use crate::synthesis::search;
+
+    #[test]
+    fn test_beam_finds_a_layout_for_stated_frames() {
+        // A toolbar row pinned to the top, with the logo at the bottom
+        let toolbar = IR::VStack(vec![
+            IR::HStack(vec![IR::Button("Back".to_string()), IR::Spacer, IR::Text("Settings".to_string())]),
+            IR::Spacer,
+            IR::Image("logo".to_string()),
+        ]);
+        let layout = layout::simulate(&toolbar, layout::Size::new(390.0, 844.0), &Metrics::default());
+        let at: Vec<String> = layout.leaves().map(|leaf| format!("@(x:{},y:{})", leaf.rect.x, leaf.rect.y)).collect();
+        let spec = format!("{{(width:390,height:844):{{button:\"Back\"{},title:\"Settings\"{},Image:\"logo\"{}}}}}", at[0], at[1], at[2]);
+        let examples = parse_examples(&spec).unwrap();
+
+        let budget = Budget { timeout: None, ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default(), &CostWeights::default()).unwrap();
+        assert_eq!(outcome.violations, 0, "{:?}", outcome.ir);
+        assert!(fidelity::measure(&outcome.ir, &examples, &Metrics::default()).iter().all(|m| m.passes(0.5)), "{:?}", outcome.ir);
+        assert!(outcome.trace.iter().any(|line| line.starts_with("Beam search kept the 32 best")), "{:?}", outcome.trace);
+    }
+
+    #[test]
+    fn test_beam_width_bounds_the_work() {
+        let examples = parse_examples(
+            "{(width:320,height:300):{title:\"One\",title:\"Two\",title:\"Three\",button:\"Four\",button:\"Five\",Image:\"six\"}}",
+        )
+        .unwrap();
+        let narrow = Budget { beam_width: 2, timeout: None, ..Budget::default() };
+        let wide = Budget { beam_width: 16, timeout: None, ..Budget::default() };
+        let weights = CostWeights::default();
+        let narrow = search(&examples, &narrow, &Metrics::default(), &weights).unwrap();
+        let wide = search(&examples, &wide, &Metrics::default(), &weights).unwrap();
+        assert!(narrow.candidates < wide.candidates, "{} vs {}", narrow.candidates, wide.candidates);
+        assert_eq!(wide.violations, 0, "{:?}", wide.ir);
+        // The enumerative search checks every arrangement the beam could reach, and more
+        let exhaustive = search::search(&examples, &Budget { timeout: None, ..Budget::default() }, &Metrics::default()).unwrap();
+        assert!(narrow.candidates < exhaustive.candidates);
+    }
+
+    #[test]
+    fn test_beam_respects_the_candidate_limit() {
+        let examples = parse_examples("{(width:320,height:200):{Image:\"logo\",title:\"Hi\",button:\"Go\"}}").unwrap();
+        let budget = Budget { max_candidates: 3, ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default(), &CostWeights::default()).unwrap();
+        assert!(outcome.candidates <= 4);
+        assert_eq!(outcome.exhausted, Some(Exhausted::Candidates));
+    }
+}
@@ -0,0 +1,164 @@
+// File: src/synthesis/bench.rs
+// Benchmark corpus runner (`bench`): synthesizes every `<name>.spec` of a directory and checks
+// the layout against the examples and, where there is one, the expected `<name>.swift`, so
+// changes to the algorithms can be measured by solve rate, time and candidates scored.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+use crate::ast::ir::{equivalent, IR};
+use crate::input::parser::{self, ParseOptions};
+use crate::input::swift;
+use crate::synthesis::search::Budget;
+use crate::synthesis::strategy::SynthesisStrategy;
+
+/// A spec of one screen and, optionally, the SwiftUI its layout is expected to be.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Benchmark {
+    pub name: String,
+    pub spec: PathBuf,
+    /// The `.swift` file next to the spec, if there is one.
+    pub expected: Option<PathBuf>,
+}
+
+/// Every `.spec` in `dir`, by name.
+pub fn load(dir: &Path) -> Result<Vec<Benchmark>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read benchmark directory '{}': {}", dir.display(), e))?;
+    let mut benchmarks: Vec<Benchmark> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "spec"))
+        .map(|spec| {
+            let expected = Some(spec.with_extension("swift")).filter(|expected| expected.is_file());
+            Benchmark { name: spec.file_stem().unwrap_or_default().to_string_lossy().into_owned(), spec, expected }
+        })
+        .collect();
+    if benchmarks.is_empty() {
+        return Err(format!("No .spec files in '{}'", dir.display()));
+    }
+    benchmarks.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(benchmarks)
+}
+
+/// How a benchmark went.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// The layout satisfies the examples and is the expected one.
+    Solved,
+    /// The layout satisfies the examples but is not the expected one.
+    Different,
+    /// No layout satisfying the examples was found within the budget.
+    Unsolved,
+    /// The spec or the expected SwiftUI could not be read.
+    Error(String),
+}
+
+impl Status {
+    fn label(&self) -> &'static str {
+        match self {
+            Status::Solved => "solved",
+            Status::Different => "different",
+            Status::Unsolved => "unsolved",
+            Status::Error(_) => "error",
+        }
+    }
+}
+
+/// The result of one benchmark.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Run {
+    pub name: String,
+    pub status: Status,
+    #[serde(rename = "ms", serialize_with = "as_millis")]
+    pub elapsed: Duration,
+    /// Candidates scored by the search.
+    pub candidates: usize,
+}
+
+fn as_millis<S: serde::Serializer>(elapsed: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(elapsed.as_secs_f64() * 1000.0)
+}
+
+fn read(path: &Path) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))
+}
+
+/// Synthesizes `benchmark` with `strategy`, timing only the synthesis. Built-in views in the
+/// expected SwiftUI may be written as the role components of `options`.
+pub fn run(benchmark: &Benchmark, strategy: &dyn SynthesisStrategy, budget: &Budget, options: &ParseOptions) -> Run {
+    let mut result = Run { name: benchmark.name.clone(), status: Status::Unsolved, elapsed: Duration::ZERO, candidates: 0 };
+    let spec = match read(&benchmark.spec).and_then(|spec| parser::parse_spec_with(&spec, options).map_err(|errors| errors[0].message.clone())) {
+        Ok(spec) if spec.screens.len() == 1 => spec,
+        Ok(spec) => return Run { status: Status::Error(format!("has {} screens; a benchmark has one", spec.screens.len())), ..result },
+        Err(message) => return Run { status: Status::Error(message), ..result },
+    };
+    let expected: Option<IR> = match benchmark.expected.as_deref().map(|path| read(path).and_then(|code| swift::parse_swiftui(&code))) {
+        Some(Ok(ir)) => Some(options.components.restore(&ir)),
+        Some(Err(message)) => return Run { status: Status::Error(message), ..result },
+        None => None,
+    };
+    let start = Instant::now();
+    let outcome = strategy.synthesize(&spec.screens[0].examples, budget);
+    result.elapsed = start.elapsed();
+    let Some(outcome) = outcome else { return result };
+    result.candidates = outcome.candidates;
+    result.status = match expected {
+        _ if outcome.violations > 0 => Status::Unsolved,
+        Some(expected) if !equivalent(&outcome.ir, &expected) => Status::Different,
+        _ => Status::Solved,
+    };
+    result
+}
+
+/// A line per benchmark with its result, time and candidates, then the solve rate and totals.
+pub fn report(runs: &[Run]) -> String {
+    let width = runs.iter().map(|run| run.name.len()).max().unwrap_or(0).max("benchmark".len());
+    let mut lines = vec![format!("{:width$}  {:9}  {:>10}  {:>10}", "benchmark", "result", "time", "candidates")];
+    for run in runs {
+        let time = format!("{:.2?}", run.elapsed);
+        let mut line = format!("{:width$}  {:9}  {:>10}  {:>10}", run.name, run.status.label(), time, run.candidates);
+        if let Status::Error(message) = &run.status {
+            line = format!("{}  {}", line.trim_end(), message);
+        }
+        lines.push(line);
+    }
+    let solved = runs.iter().filter(|run| run.status == Status::Solved).count();
+    let elapsed: Duration = runs.iter().map(|run| run.elapsed).sum();
+    let candidates: usize = runs.iter().map(|run| run.candidates).sum();
+    let rate = if runs.is_empty() { 0.0 } else { solved as f64 * 100.0 / runs.len() as f64 };
+    lines.push(format!("{} of {} solved ({:.1}%) in {:.2?}, {} candidates", solved, runs.len(), rate, elapsed, candidates));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Metrics;
+    use crate::synthesis::strategy;
+
+    #[test]
+    fn test_runs_are_checked_against_the_expected_layout() {
+        let dir = std::env::temp_dir().join(format!("swiftui-synth-bench-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("stack.spec"), "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}").unwrap();
+        fs::write(dir.join("stack.swift"), "VStack {\n    Text(\"Hi\")\n    Spacer()\n    Button(\"Go\") {}\n}").unwrap();
+        fs::write(dir.join("other.spec"), "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}").unwrap();
+        fs::write(dir.join("other.swift"), "HStack {\n    Text(\"Hi\")\n    Button(\"Go\") {}\n}").unwrap();
+        fs::write(dir.join("broken.spec"), "{(width:390").unwrap();
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        let benchmarks = load(&dir).unwrap();
+        assert_eq!(benchmarks.iter().map(|b| b.name.as_str()).collect::<Vec<_>>(), ["broken", "other", "stack"]);
+        let strategy = strategy::by_name("enumerative", Metrics::default(), Default::default()).unwrap();
+        let runs: Vec<Run> = benchmarks.iter().map(|b| run(b, strategy.as_ref(), &Budget::default(), &ParseOptions::default())).collect();
+        assert!(matches!(runs[0].status, Status::Error(_)));
+        assert_eq!((&runs[1].status, &runs[2].status), (&Status::Different, &Status::Solved));
+        assert!(runs[2].candidates > 0);
+        assert!(report(&runs).ends_with(&format!("1 of 3 solved (33.3%) in {:.2?}, {} candidates", runs.iter().map(|r| r.elapsed).sum::<Duration>(), runs[1].candidates + runs[2].candidates)));
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(load(&dir).is_err());
+    }
+}
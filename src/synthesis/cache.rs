@@ -0,0 +1,185 @@
+// File: src/synthesis/cache.rs
+// On-disk cache of search outcomes keyed by a fingerprint of the examples.
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::ast::Example;
+use crate::layout::Metrics;
+use crate::synthesis::evaluate::CostWeights;
+use crate::synthesis::search::{Budget, Outcome};
+
+/// Environment variable that overrides the cache directory.
+pub const CACHE_DIR_ENV: &str = "SWIFTUI_SYNTH_CACHE_DIR";
+
+/// Default cache directory: `$SWIFTUI_SYNTH_CACHE_DIR`, else `$XDG_CACHE_HOME/swiftui-synth`,
+/// else `~/.cache/swiftui-synth`.
+pub fn default_dir() -> Option<PathBuf> {
+    let env = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty()).map(PathBuf::from);
+    env(CACHE_DIR_ENV)
+        .or_else(|| env("XDG_CACHE_HOME").map(|dir| dir.join("swiftui-synth")))
+        .or_else(|| env("HOME").map(|home| home.join(".cache").join("swiftui-synth")))
+}
+
+// 64-bit FNV-1a: unlike `DefaultHasher`, its output is fixed across Rust releases.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(0x0100_0000_01b3))
+}
+
+/// Stable fingerprint of a search: the parsed examples, the strategy, the search depth, seed and `top_n`,
+/// the layout metrics (which include registered component sizes), the cost weights, and the
+/// crate version.
+///
+/// Whitespace and formatting of the spec do not matter, since the parsed examples are hashed.
+/// The timeout and candidate limit are left out because only complete searches are cached.
+pub fn fingerprint(examples: &[Example], budget: &Budget, strategy: &str, metrics: &Metrics, weights: &CostWeights) -> String {
+    let examples = serde_json::to_string(examples).expect("examples are serializable");
+    let key = format!(
+        "{}\n{}\n{}\n{:?}\n{}\n{}\n{:?}\n{:?}\n{}",
+        env!("CARGO_PKG_VERSION"),
+        strategy,
+        budget.max_depth,
+        budget.seed,
+        budget.top_n,
+        budget.beam_width,
+        metrics,
+        weights,
+        examples
+    );
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+/// Cached search outcomes stored as one JSON file per fingerprint.
+#[derive(Clone, Debug)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub fn new(dir: impl Into<PathBuf>) -> Cache {
+        Cache { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", key))
+    }
+
+    /// Returns the cached outcome for `key`. Unreadable or stale entries count as misses.
+    pub fn get(&self, key: &str) -> Option<Outcome> {
+        let contents = fs::read_to_string(self.entry(key)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// Stores `outcome` under `key`. Searches cut short by the budget are not cached,
+    /// since a later run with more budget may do better.
+    pub fn put(&self, key: &str, outcome: &Outcome) -> Result<(), String> {
+        if outcome.exhausted.is_some() {
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create cache directory '{}': {}", self.dir.display(), e))?;
+        let json = serde_json::to_string(outcome).expect("outcome is serializable");
+        // Write then rename, so a concurrent reader never sees a partial entry
+        let temp = self.dir.join(format!("{}.{}.tmp", key, std::process::id()));
+        fs::write(&temp, json).map_err(|e| format!("Failed to write cache entry '{}': {}", temp.display(), e))?;
+        fs::rename(&temp, self.entry(key)).map_err(|e| format!("Failed to write cache entry: {}", e))
+    }
+
+    /// Removes every cached entry and returns how many there were.
+    pub fn clear(&self) -> Result<usize, String> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(format!("Failed to read cache directory '{}': {}", self.dir.display(), e)),
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json" || ext == "tmp") {
+                fs::remove_file(&path).map_err(|e| format!("Failed to remove '{}': {}", path.display(), e))?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::IR;
+    use crate::layout::CustomSize;
+    use crate::input::parser::parse_examples;
+    use crate::synthesis::search::Exhausted;
+
+    fn temp_cache(name: &str) -> Cache {
+        let dir = std::env::temp_dir().join(format!("swiftui-synth-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Cache::new(dir)
+    }
+
+    fn outcome(exhausted: Option<Exhausted>) -> Outcome {
+        Outcome {
+            ir: IR::VStack(vec![IR::Text("Hi".to_string()), IR::Spacer]),
+            violations: 0,
+            candidates: 1,
+            exhausted,
+            trace: vec!["VStack chosen".to_string()],
+            stats: Default::default(),
+            alternatives: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_ignores_formatting() {
+        let a = parse_examples("{(width:390,height:844):{title:\"Hi\"}}").unwrap();
+        let b = parse_examples("{ (width: 390, height: 844) : { title: \"Hi\" } }").unwrap();
+        let c = parse_examples("{(width:390,height:844):{title:\"Hello\"}}").unwrap();
+        let budget = Budget::default();
+        let metrics = Metrics::default();
+        let weights = CostWeights::default();
+        assert_eq!(fingerprint(&a, &budget, "enumerative", &metrics, &weights), fingerprint(&b, &budget, "enumerative", &metrics, &weights));
+        assert_ne!(fingerprint(&a, &budget, "enumerative", &metrics, &weights), fingerprint(&c, &budget, "enumerative", &metrics, &weights));
+        assert_ne!(fingerprint(&a, &budget, "enumerative", &metrics, &weights), fingerprint(&a, &Budget { max_depth: 1, ..budget.clone() }, "enumerative", &metrics, &weights));
+        assert_ne!(fingerprint(&a, &budget, "enumerative", &metrics, &weights), fingerprint(&a, &Budget { seed: Some(1), ..budget.clone() }, "enumerative", &metrics, &weights));
+        assert_ne!(fingerprint(&a, &budget, "enumerative", &metrics, &weights), fingerprint(&a, &budget, "template", &metrics, &weights));
+        assert_ne!(fingerprint(&a, &budget, "enumerative", &metrics, &weights), fingerprint(&a, &Budget { top_n: 3, ..budget.clone() }, "enumerative", &metrics, &weights));
+        assert_ne!(fingerprint(&a, &budget, "beam", &metrics, &weights), fingerprint(&a, &Budget { beam_width: 8, ..budget.clone() }, "beam", &metrics, &weights));
+        let mut sized = metrics.clone();
+        sized.custom_sizes.insert("AvatarView".to_string(), CustomSize { width: Some(64.0), height: 64.0 });
+        assert_ne!(fingerprint(&a, &budget, "enumerative", &metrics, &weights), fingerprint(&a, &budget, "enumerative", &sized, &weights));
+        let flat = CostWeights { depth: 10.0, ..weights.clone() };
+        assert_ne!(fingerprint(&a, &budget, "enumerative", &metrics, &weights), fingerprint(&a, &budget, "enumerative", &metrics, &flat));
+        assert_eq!(fingerprint(&a, &budget, "enumerative", &metrics, &weights).len(), 16);
+    }
+
+    #[test]
+    fn test_round_trip_and_clear() {
+        let cache = temp_cache("roundtrip");
+        assert_eq!(cache.get("abc"), None);
+        assert_eq!(cache.clear().unwrap(), 0);
+
+        cache.put("abc", &outcome(None)).unwrap();
+        assert_eq!(cache.get("abc"), Some(outcome(None)));
+
+        // Best-effort results are not cached
+        cache.put("def", &outcome(Some(Exhausted::Timeout))).unwrap();
+        assert_eq!(cache.get("def"), None);
+
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.get("abc"), None);
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_entry_is_a_miss() {
+        let cache = temp_cache("corrupt");
+        fs::create_dir_all(cache.dir()).unwrap();
+        fs::write(cache.dir().join("abc.json"), "{not json").unwrap();
+        assert_eq!(cache.get("abc"), None);
+        fs::remove_dir_all(cache.dir()).unwrap();
+    }
+}
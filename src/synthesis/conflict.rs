@@ -0,0 +1,126 @@
+// File: src/synthesis/conflict.rs
+// Explains a spec no layout satisfies by the smallest set of its examples that already
+// contradict each other, so the user knows which examples (or their constraints) to fix.
+use std::fmt;
+
+use crate::ast::Example;
+use crate::layout::Metrics;
+use crate::synthesis::search::Outcome;
+use crate::synthesis::verify::{verify, Violation};
+
+/// A minimal set of examples no layout was found for: without any one of them, the rest
+/// are satisfied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    /// Zero-based indices of the examples, in order.
+    pub examples: Vec<usize>,
+    /// What the closest layout for just these examples breaks, by the spec's example indices.
+    pub problems: Vec<Violation>,
+}
+
+fn numbers(examples: &[usize]) -> String {
+    let numbers: Vec<String> = examples.iter().map(|index| (index + 1).to_string()).collect();
+    match numbers.as_slice() {
+        [init @ .., last] if !init.is_empty() => format!("{} and {}", init.join(", "), last),
+        _ => numbers.join(""),
+    }
+}
+
+impl fmt::Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.examples.as_slice() {
+            [_] => write!(f, "example {} cannot be satisfied even on its own", numbers(&self.examples))?,
+            [_, _] => write!(
+                f,
+                "examples {} contradict each other: each is satisfied by some layout, but none was found for both",
+                numbers(&self.examples)
+            )?,
+            _ => write!(
+                f,
+                "examples {} contradict each other: without any one of them the rest are satisfied, but no layout was found for all of them",
+                numbers(&self.examples)
+            )?,
+        }
+        if let Some(problem) = self.problems.first() {
+            write!(f, "; the closest layout breaks {}", problem)?;
+        }
+        Ok(())
+    }
+}
+
+/// Finds a minimal subset of `examples` that `solve` finds no satisfying layout for, by
+/// deletion: each example in turn is dropped if the rest still cannot be satisfied. Returns
+/// `None` when `solve` satisfies every example. The search behind `solve` is not exhaustive,
+/// so the conflict is as good as the search: each subset is searched again, so it costs up
+/// to one search per example.
+pub fn minimal_conflict(examples: &[Example], metrics: &Metrics, solve: impl Fn(&[Example]) -> Option<Outcome>) -> Option<Conflict> {
+    let subset = |indices: &[usize]| indices.iter().map(|&index| examples[index].clone()).collect::<Vec<_>>();
+    let unsatisfied = |indices: &[usize]| solve(&subset(indices)).is_none_or(|outcome| outcome.violations > 0);
+    let mut kept: Vec<usize> = (0..examples.len()).collect();
+    if kept.is_empty() || !unsatisfied(&kept) {
+        return None;
+    }
+    let mut i = 0;
+    while i < kept.len() {
+        let trial: Vec<usize> = kept.iter().copied().filter(|&index| index != kept[i]).collect();
+        if !trial.is_empty() && unsatisfied(&trial) {
+            kept = trial;
+        } else {
+            i += 1;
+        }
+    }
+    let examples_kept = subset(&kept);
+    let problems = match solve(&examples_kept) {
+        Some(outcome) => verify(&outcome.ir, &examples_kept, metrics)
+            .into_iter()
+            .map(|violation| Violation { example: kept[violation.example], ..violation })
+            .collect(),
+        None => Vec::new(),
+    };
+    Some(Conflict { examples: kept, problems })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+    use crate::synthesis::search::{search, Budget};
+
+    fn conflict(spec: &str) -> Option<Conflict> {
+        let examples = parse_examples(spec).unwrap();
+        let metrics = Metrics::default();
+        minimal_conflict(&examples, &metrics, |examples| search(examples, &Budget::default(), &metrics))
+    }
+
+    #[test]
+    fn test_contradicting_constraints_are_isolated() {
+        let conflict = conflict(
+            "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}
+             {(width:390,height:844):{title:\"Hi\",button:\"Go\",constraints:{\"button above title\"}}}
+             {(width:320,height:568):{title:\"Hi\",button:\"Go\"}}
+             {(width:390,height:844):{title:\"Hi\",button:\"Go\",constraints:{\"button below title\"}}}",
+        )
+        .unwrap();
+        assert_eq!(conflict.examples, [1, 3]);
+        let message = conflict.to_string();
+        assert!(message.starts_with("examples 2 and 4 contradict each other: each is satisfied by some layout"), "{}", message);
+        assert!(message.contains("; the closest layout breaks example "), "{}", message);
+    }
+
+    #[test]
+    fn test_an_impossible_example_conflicts_alone() {
+        let long = "word ".repeat(200);
+        let conflict = conflict(&format!(
+            "{{(width:390,height:844):{{title:\"{0}\"}}}} {{(width:100,height:100):{{title:\"{0}\"}}}}",
+            long.trim()
+        ))
+        .unwrap();
+        assert_eq!(conflict.examples, [1]);
+        assert!(conflict.to_string().starts_with("example 2 cannot be satisfied even on its own; the closest layout breaks example 2: content needs"));
+    }
+
+    #[test]
+    fn test_satisfiable_specs_have_no_conflict() {
+        assert_eq!(conflict("{(width:390,height:844):{title:\"Hi\"}} {(width:320,height:568):{title:\"Hi\"}}"), None);
+    }
+}
@@ -0,0 +1,134 @@
+// File: src/synthesis/constraint.rs
+// Constraint-based synthesis: instead of enumerating arrangements, the layout is built
+// directly so that the content fits the smallest screen while keeping the template's order.
+use crate::ast::{Example, IR};
+use crate::layout::{self, Metrics};
+use crate::synthesis::search::{Outcome, SearchStats};
+use crate::synthesis::swiftui::synthesize_traced;
+use crate::synthesis::verify::{describe, expected_leaves, verify};
+
+// Splits `leaves` into `rows` consecutive groups whose sizes differ by at most one.
+fn balanced_rows(leaves: &[IR], rows: usize) -> Vec<Vec<IR>> {
+    let (base, extra) = (leaves.len() / rows, leaves.len() % rows);
+    let mut start = 0;
+    (0..rows)
+        .map(|row| {
+            let len = base + usize::from(row < extra);
+            let group = leaves[start..start + len].to_vec();
+            start += len;
+            group
+        })
+        .collect()
+}
+
+// A column of rows; a trailing button row is pinned to the bottom like in the template.
+fn column(rows: Vec<Vec<IR>>) -> IR {
+    let mut children: Vec<IR> = rows
+        .into_iter()
+        .map(|mut row| if row.len() == 1 { row.remove(0) } else { IR::HStack(row) })
+        .collect();
+    let gap = match children.last() {
+        Some(IR::Button(_)) => children.len() - 1,
+        _ => children.len(),
+    };
+    children.insert(gap, IR::Spacer);
+    IR::VStack(children)
+}
+
+/// Solves for a layout that satisfies every example.
+///
+/// The constraints are: every requested element appears, in the template's order, and
+/// the content fits each example's screen. Elements are packed into rows, starting from
+/// the template's own arrangement and trading rows for columns (or the reverse, for
+/// `HStack` examples) until the layout fits. Falls back to the template when no packing does.
+pub fn solve(examples: &[Example], metrics: &Metrics) -> Option<Outcome> {
+    let (template, mut trace) = synthesize_traced(examples.to_vec())?;
+    let mut stats = SearchStats::default();
+    let score = |ir: &IR, stats: &mut SearchStats| {
+        stats.enumerated += 1;
+        stats.scored += 1;
+        stats.simulated += examples.len();
+        verify(ir, examples, metrics).len()
+    };
+
+    let template_violations = score(&template, &mut stats);
+    if template_violations == 0 {
+        trace.push(format!("Template satisfies every example ({}); no constraints to solve", examples.len()));
+        return Some(Outcome { ir: template, violations: 0, candidates: 1, exhausted: None, trace, stats, alternatives: Vec::new() });
+    }
+
+    let (leaves, ordered) = expected_leaves(&examples[0]);
+    // A column has one row per element; a row example starts as a single row
+    let row_counts: Vec<usize> =
+        if ordered { (2..=leaves.len()).collect() } else { (1..leaves.len()).rev().collect() };
+    for rows in row_counts {
+        let candidate = column(balanced_rows(&leaves, rows));
+        if score(&candidate, &mut stats) == 0 {
+            let smallest = examples
+                .iter()
+                .filter_map(layout::screen_size)
+                .min_by(|a, b| (a.width * a.height).total_cmp(&(b.width * b.height)));
+            let mut trace = vec![format!(
+                "Constraints: {} in order, fitting {}",
+                leaves.iter().map(describe).collect::<Vec<_>>().join(", "),
+                smallest.map_or("every screen".to_string(), |s| format!("a {}x{} screen", s.width, s.height)),
+            )];
+            trace.push(format!(
+                "Packed {} elements into {} rows, the smallest change to the template that fits every example",
+                leaves.len(),
+                rows
+            ));
+            let candidates = stats.scored;
+            return Some(Outcome { ir: candidate, violations: 0, candidates, exhausted: None, trace, stats, alternatives: Vec::new() });
+        }
+    }
+
+    trace.push(format!(
+        "No row packing satisfies every example; showing the template ({} problems)",
+        template_violations
+    ));
+    let candidates = stats.scored;
+    Some(Outcome { ir: template, violations: template_violations, candidates, exhausted: None, trace, stats, alternatives: Vec::new() })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    fn text(s: &str) -> IR {
+        IR::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_balanced_rows() {
+        let leaves = vec![text("A"), text("B"), text("C")];
+        assert_eq!(balanced_rows(&leaves, 2), vec![vec![text("A"), text("B")], vec![text("C")]]);
+        assert_eq!(balanced_rows(&leaves, 3).len(), 3);
+    }
+
+    #[test]
+    fn test_solve_packs_column_into_rows() {
+        // Too short for one element per row, tall enough for two rows
+        let examples = parse_examples("{(width:320,height:220):{Image:\"logo\",title:\"Hi\",button:\"Go\"}}").unwrap();
+        let outcome = solve(&examples, &Metrics::default()).unwrap();
+        assert_eq!(outcome.violations, 0);
+        assert_eq!(
+            outcome.ir,
+            IR::VStack(vec![
+                IR::HStack(vec![IR::Image("logo".to_string()), text("Hi")]),
+                IR::Spacer,
+                IR::Button("Go".to_string()),
+            ])
+        );
+        assert!(outcome.trace[1].starts_with("Packed 3 elements into 2 rows"));
+    }
+
+    #[test]
+    fn test_solve_keeps_fitting_template() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"Hi\"}}").unwrap();
+        let outcome = solve(&examples, &Metrics::default()).unwrap();
+        assert_eq!(outcome.ir, synthesize_traced(examples).unwrap().0);
+        assert_eq!(outcome.candidates, 1);
+    }
+}
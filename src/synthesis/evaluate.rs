@@ -1 +1,147 @@
-// Placeholder for future evaluation logic.
+// File: src/synthesis/evaluate.rs
+// Ranking cost of a candidate layout: a weighted sum of how big, deep and decorated it is
+// and how far its views land from the frames the examples state.
+use serde::Deserialize;
+use std::fmt;
+
+use crate::ast::{Example, IR};
+use crate::layout::{self, Insets, Metrics};
+use crate::synthesis::verify::expected_frames;
+
+/// Weights of the terms in [`cost`]. Raising `depth` biases the search toward flatter
+/// layouts; raising `geometry` toward layouts that reproduce the stated frames.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct CostWeights {
+    /// Per node in the layout, spacers and stacks included.
+    pub nodes: f64,
+    /// Per level of stack nesting (the root stack counts as one).
+    pub depth: f64,
+    /// Per modifier the renderer emits (`.font`, `.padding()`).
+    pub modifiers: f64,
+    /// Per point of distance between a view and the frame an example states for it.
+    pub geometry: f64,
+}
+
+impl Default for CostWeights {
+    fn default() -> Self {
+        CostWeights { nodes: 1.0, depth: 2.0, modifiers: 0.5, geometry: 0.1 }
+    }
+}
+
+/// The terms of a layout's cost, before weighting.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Cost {
+    pub nodes: usize,
+    pub depth: usize,
+    pub modifiers: usize,
+    /// Total deviation in points from the stated frames, over every example.
+    pub deviation: f64,
+}
+
+impl Cost {
+    pub fn total(&self, weights: &CostWeights) -> f64 {
+        weights.nodes * self.nodes as f64
+            + weights.depth * self.depth as f64
+            + weights.modifiers * self.modifiers as f64
+            + weights.geometry * self.deviation
+    }
+}
+
+impl fmt::Display for Cost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} nodes, depth {}, {} modifiers", self.nodes, self.depth, self.modifiers)?;
+        if self.deviation > 0.0 {
+            write!(f, ", {:.0}pt from the stated frames", self.deviation)?;
+        }
+        Ok(())
+    }
+}
+
+fn node_count(ir: &IR) -> usize {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => 1 + children.iter().map(node_count).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+fn stack_depth(ir: &IR) -> usize {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => 1 + children.iter().map(stack_depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+// Modifiers emitted by the default renderer: `.font` and `.padding()` on texts,
+// `.padding()` on buttons and stacks.
+fn modifier_count(ir: &IR) -> usize {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => 1 + children.iter().map(modifier_count).sum::<usize>(),
+        IR::Text(_) => 2,
+        IR::Button(_) => 1,
+        IR::Image(_) | IR::Spacer | IR::Custom { .. } => 0,
+    }
+}
+
+// Distance of each view with a stated frame from that frame, summed over the examples.
+// Repeated views are matched to their stated frames in order.
+fn deviation(ir: &IR, examples: &[Example], metrics: &Metrics) -> f64 {
+    let mut total = 0.0;
+    let ignores_safe_area = layout::ignores_safe_area(examples);
+    for example in examples {
+        let metrics = &metrics.scaled(layout::dynamic_type(example).scale());
+        let stated = expected_frames(example);
+        let Some(screen) = layout::screen_for(ir, example, metrics).filter(|_| !stated.is_empty()) else { continue };
+        let insets = if ignores_safe_area { Insets::default() } else { layout::safe_area(example) };
+        let simulated = layout::simulate_within(ir, screen, insets, metrics).with_direction(layout::layout_direction(example));
+        let mut leaves: Vec<&layout::Frame> = simulated.leaves().collect();
+        for (node, frame) in &stated {
+            // A view missing from the layout is already a violation
+            if let Some(pos) = leaves.iter().position(|leaf| &leaf.node == node) {
+                total += frame.deviation(&leaves.remove(pos).rect);
+            }
+        }
+    }
+    total
+}
+
+/// Measures `ir` against `examples`; weight it with [`Cost::total`].
+pub fn cost(ir: &IR, examples: &[Example], metrics: &Metrics) -> Cost {
+    Cost {
+        nodes: node_count(ir),
+        depth: stack_depth(ir),
+        modifiers: modifier_count(ir),
+        deviation: deviation(ir, examples, metrics),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    fn text(s: &str) -> IR {
+        IR::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_structural_terms() {
+        let ir = IR::VStack(vec![IR::HStack(vec![text("A"), IR::Image("logo".to_string())]), IR::Spacer]);
+        let cost = cost(&ir, &[], &Metrics::default());
+        assert_eq!(cost, Cost { nodes: 5, depth: 2, modifiers: 4, deviation: 0.0 });
+        assert_eq!(cost.total(&CostWeights::default()), 5.0 + 4.0 + 2.0);
+        let flat = CostWeights { depth: 10.0, ..CostWeights::default() };
+        assert_eq!(cost.total(&flat), 5.0 + 20.0 + 2.0);
+    }
+
+    #[test]
+    fn test_deviation_from_stated_frames() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"Hi\"@(y:16),button:\"Go\"@(y:0,h:54)}}").unwrap();
+        let ir = IR::VStack(vec![text("Hi"), IR::Spacer, IR::Button("Go".to_string())]);
+        // The title sits at the top inside the stack padding; the 54pt button is pinned 16pt above the bottom
+        assert_eq!(cost(&ir, &examples, &Metrics::default()).deviation, 844.0 - 16.0 - 54.0);
+
+        let unstated = parse_examples("{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}").unwrap();
+        assert_eq!(cost(&ir, &unstated, &Metrics::default()).deviation, 0.0);
+    }
+}
@@ -0,0 +1,143 @@
+// File: src/synthesis/fidelity.rs
+// How closely a layout reproduces the frames its examples state (`fidelity`): each view with
+// a stated frame is measured where the layout engine puts it at that example's size.
+use crate::ast::{Example, IR};
+use crate::layout::{self, Insets, Metrics, Rect, StatedFrame};
+use crate::synthesis::verify::{describe, expected_frames};
+
+/// Where a view stated to be at `stated` in an example landed in the simulated layout.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Measurement {
+    /// Index of the example, from 0.
+    pub example: usize,
+    pub node: IR,
+    pub stated: StatedFrame,
+    /// `None` if the layout has no such view.
+    pub actual: Option<Rect>,
+}
+
+impl Measurement {
+    /// Distance in points, actual minus stated, of each coordinate the example states.
+    pub fn deltas(&self) -> Vec<(&'static str, f64)> {
+        let Some(actual) = &self.actual else { return Vec::new() };
+        let stated = &self.stated;
+        [("x", stated.x, actual.x), ("y", stated.y, actual.y), ("w", stated.width, actual.width), ("h", stated.height, actual.height)]
+            .into_iter()
+            .filter_map(|(name, stated, actual)| stated.map(|stated| (name, actual - stated)))
+            .collect()
+    }
+
+    /// Largest distance of a coordinate from where it was stated; infinite for a missing view.
+    pub fn error(&self) -> f64 {
+        match self.actual {
+            Some(_) => self.deltas().iter().map(|(_, delta)| delta.abs()).fold(0.0, f64::max),
+            None => f64::INFINITY,
+        }
+    }
+
+    /// Whether every stated coordinate is within `threshold` points.
+    pub fn passes(&self, threshold: f64) -> bool {
+        self.error() <= threshold
+    }
+}
+
+/// Measures every view with a stated frame, at each example's size. Repeated views are
+/// matched to their stated frames in order, as the search's ranking does.
+pub fn measure(ir: &IR, examples: &[Example], metrics: &Metrics) -> Vec<Measurement> {
+    let mut measurements = Vec::new();
+    let ignores_safe_area = layout::ignores_safe_area(examples);
+    for (index, example) in examples.iter().enumerate() {
+        let metrics = &metrics.scaled(layout::dynamic_type(example).scale());
+        let stated = expected_frames(example);
+        let Some(screen) = layout::screen_for(ir, example, metrics).filter(|_| !stated.is_empty()) else { continue };
+        let insets = if ignores_safe_area { Insets::default() } else { layout::safe_area(example) };
+        let simulated = layout::simulate_within(ir, screen, insets, metrics).with_direction(layout::layout_direction(example));
+        let mut leaves: Vec<&layout::Frame> = simulated.leaves().collect();
+        for (node, frame) in stated {
+            let actual = leaves.iter().position(|leaf| leaf.node == node).map(|pos| leaves.remove(pos).rect);
+            measurements.push(Measurement { example: index, node, stated: frame, actual });
+        }
+    }
+    measurements
+}
+
+/// One line per measurement, grouped by example, and a summary of how many are within
+/// `threshold` points, e.g. `Text "Hi"  y +4.5  off by 4.5pt  FAIL`.
+pub fn report(measurements: &[Measurement], examples: &[Example], threshold: f64) -> String {
+    let width = measurements.iter().map(|m| describe(&m.node).chars().count()).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    let mut example = None;
+    for measurement in measurements {
+        if example != Some(measurement.example) {
+            example = Some(measurement.example);
+            let screen = examples.get(measurement.example).and_then(layout::describe_screen);
+            lines.push(match screen {
+                Some(screen) => format!("example {} ({}):", measurement.example + 1, screen),
+                None => format!("example {}:", measurement.example + 1),
+            });
+        }
+        let verdict = if measurement.passes(threshold) { "ok" } else { "FAIL" };
+        let detail = match measurement.actual {
+            Some(_) => {
+                let deltas: Vec<String> = measurement.deltas().iter().map(|(name, delta)| format!("{} {:+.1}", name, delta)).collect();
+                format!("{}  off by {:.1}pt", deltas.join("  "), measurement.error())
+            }
+            None => "missing from the layout".to_string(),
+        };
+        lines.push(format!("  {:width$}  {}  {}", describe(&measurement.node), detail, verdict, width = width));
+    }
+    let passed = measurements.iter().filter(|m| m.passes(threshold)).count();
+    lines.push(format!("{} of {} stated frames within {}pt", passed, measurements.len(), threshold));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    fn text(s: &str) -> IR {
+        IR::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_measures_stated_frames_per_example() {
+        let examples = parse_examples(
+            "{(width:390,height:844):{title:\"Hi\"@(y:16),button:\"Go\"@(y:790,h:54)}}
+             {(width:320,height:568):{title:\"Hi\",button:\"Go\"}}",
+        )
+        .unwrap();
+        let ir = IR::VStack(vec![text("Hi"), IR::Spacer, IR::Button("Go".to_string())]);
+        let measurements = measure(&ir, &examples, &Metrics::default());
+        // The second example states no frames
+        assert_eq!(measurements.len(), 2);
+        assert!(measurements.iter().all(|m| m.example == 0 && m.actual.is_some()));
+        let title = &measurements[0];
+        assert_eq!(title.deltas().len(), 1);
+        assert_eq!(title.deltas()[0].0, "y");
+        assert_eq!(title.error(), title.deltas()[0].1.abs());
+
+        let missing = measure(&IR::VStack(vec![text("Hi")]), &examples, &Metrics::default());
+        assert_eq!(missing[1].actual, None);
+        assert!(!missing[1].passes(1000.0));
+    }
+
+    #[test]
+    fn test_report() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"Hi\"@(x:0),button:\"Go\"@(x:-3)}}").unwrap();
+        let rect = Rect::new(0.0, 0.0, 40.0, 20.0);
+        let measurements = vec![
+            Measurement { example: 0, node: text("Hi"), stated: StatedFrame { x: Some(0.0), ..StatedFrame::default() }, actual: Some(rect) },
+            Measurement { example: 0, node: IR::Button("Go".to_string()), stated: StatedFrame { x: Some(-3.0), ..StatedFrame::default() }, actual: Some(rect) },
+            Measurement { example: 0, node: IR::Image("logo".to_string()), stated: StatedFrame::default(), actual: None },
+        ];
+        assert_eq!(
+            report(&measurements, &examples, 2.0),
+            "example 1 (390x844):
+  Text \"Hi\"     x +0.0  off by 0.0pt  ok
+  Button \"Go\"   x +3.0  off by 3.0pt  FAIL
+  Image \"logo\"  missing from the layout  FAIL
+1 of 3 stated frames within 2pt"
+        );
+    }
+}
@@ -0,0 +1,153 @@
+// File: src/synthesis/flow.rs
+// Navigation between the screens of a spec: `flows:{Login.button -> Home}` turns the
+// button into a NavigationLink pushing a route that shows the destination screen.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ast::{Screen, IR};
+use crate::output::render;
+use crate::synthesis::refine::Target;
+use crate::synthesis::verify::expected_leaves;
+
+/// Tapping `target` on screen `from` navigates to screen `to`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Flow {
+    pub from: String,
+    pub target: Target,
+    pub to: String,
+}
+
+impl fmt::Display for Flow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{} -> {}", self.from, self.target, self.to)
+    }
+}
+
+impl FromStr for Flow {
+    type Err = String;
+
+    /// Parses `Screen.element -> Screen`, where the element may carry its label: `Login.button "Sign up" -> SignUp`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let Some((source, to)) = text.rsplit_once("->") else {
+            return Err(format!("Expected a flow such as `Login.button -> Home`, found '{}'", text));
+        };
+        let Some((from, element)) = source.split_once('.') else {
+            return Err(format!("Expected `Screen.element` before '->' in '{}'", text));
+        };
+        let (from, to) = (from.trim(), to.trim());
+        if from.is_empty() || to.is_empty() {
+            return Err(format!("Expected a screen name on both sides of '->' in '{}'", text));
+        }
+        let target: Target = element.parse()?;
+        if target.key != "button" {
+            return Err(format!("Only buttons can navigate, but '{}' starts from a {}", text, target.key));
+        }
+        Ok(Flow { from: from.to_string(), target, to: to.to_string() })
+    }
+}
+
+/// Checks that each flow leads between screens of the spec and starts from a button its
+/// screen's first example has; the message names the first problem.
+pub fn validate(flows: &[Flow], screens: &[Screen]) -> Result<(), String> {
+    let find = |name: &str| screens.iter().find(|screen| screen.name.as_deref() == Some(name));
+    for flow in flows {
+        let Some(from) = find(&flow.from) else {
+            return Err(format!("Flow '{}' starts from unknown screen '{}'", flow, flow.from));
+        };
+        if find(&flow.to).is_none() {
+            return Err(format!("Flow '{}' leads to unknown screen '{}'", flow, flow.to));
+        }
+        let leaves = from.examples.first().map(|example| expected_leaves(example).0).unwrap_or_default();
+        if !leaves.iter().any(|leaf| flow.target.matches(leaf)) {
+            return Err(format!("Flow '{}' starts from a {} that screen '{}' does not have", flow, flow.target, flow.from));
+        }
+    }
+    Ok(())
+}
+
+/// Name of the `Route` case that shows `screen`: `Home` becomes `home`.
+pub fn route_case(screen: &str) -> String {
+    let mut chars = screen.chars();
+    chars.next().map_or(String::new(), |first| first.to_lowercase().chain(chars).collect())
+}
+
+/// Screens some flow leads to, in the order they are first named.
+pub fn destinations(flows: &[Flow]) -> Vec<&str> {
+    let mut names: Vec<&str> = Vec::new();
+    for flow in flows {
+        if !names.contains(&flow.to.as_str()) {
+            names.push(&flow.to);
+        }
+    }
+    names
+}
+
+/// `ir` for screen `screen` with the first button each flow starts from replaced by a
+/// `NavigationLink` pushing the destination's route.
+pub fn wire(ir: &IR, screen: Option<&str>, flows: &[Flow]) -> IR {
+    let mut wired = ir.clone();
+    for flow in flows.iter().filter(|flow| Some(flow.from.as_str()) == screen) {
+        link_first(&mut wired, flow);
+    }
+    wired
+}
+
+// Replaces the first button `flow` starts from; returns whether one was found.
+fn link_first(ir: &mut IR, flow: &Flow) -> bool {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => children.iter_mut().any(|child| link_first(child, flow)),
+        IR::Button(label) if flow.target.matches(&IR::Button(label.clone())) => {
+            let args = format!("{}, value: Route.{}", render::string_literal(label), route_case(&flow.to));
+            *ir = IR::Custom { name: "NavigationLink".to_string(), args };
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::{parse_spec_with, ParseOptions};
+
+    #[test]
+    fn test_parse_flows() {
+        let flow: Flow = "Login.button -> Home".parse().unwrap();
+        assert_eq!(flow, Flow { from: "Login".to_string(), target: "button".parse().unwrap(), to: "Home".to_string() });
+        let flow: Flow = "Login.button \"Sign up\" -> SignUp".parse().unwrap();
+        assert_eq!(flow.to_string(), "Login.button \"Sign up\" -> SignUp");
+        assert!("Login.button Home".parse::<Flow>().unwrap_err().starts_with("Expected a flow"));
+        assert!("button -> Home".parse::<Flow>().unwrap_err().starts_with("Expected `Screen.element`"));
+        assert!("Login.title -> Home".parse::<Flow>().unwrap_err().starts_with("Only buttons can navigate"));
+    }
+
+    #[test]
+    fn test_validate_and_wire() {
+        let spec = parse_spec_with(
+            "Login:{(width:390,height:844):{title:\"Hi\",button:\"Go\"}} Home:{(width:390,height:844):{title:\"Home\"}}",
+            &ParseOptions::default(),
+        )
+        .unwrap();
+        let flow = |text: &str| -> Vec<Flow> { vec![text.parse().unwrap()] };
+        assert_eq!(validate(&flow("Login.button -> Home"), &spec.screens), Ok(()));
+        assert!(validate(&flow("Login.button -> Settings"), &spec.screens).unwrap_err().contains("unknown screen 'Settings'"));
+        assert!(validate(&flow("Home.button -> Login"), &spec.screens).unwrap_err().contains("does not have"));
+
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::Spacer, IR::Button("Go".to_string())]);
+        let link = IR::Custom { name: "NavigationLink".to_string(), args: "\"Go\", value: Route.home".to_string() };
+        let wired = wire(&ir, Some("Login"), &flow("Login.button -> Home"));
+        assert_eq!(wired, IR::VStack(vec![IR::Text("Hi".to_string()), IR::Spacer, link]));
+        assert_eq!(wire(&ir, Some("Home"), &flow("Login.button -> Home")), ir);
+        assert_eq!(route_case("SignUp"), "signUp");
+    }
+
+    #[test]
+    fn test_destinations_keep_spec_order() {
+        let flows: Vec<Flow> = ["Login.button -> Welcome", "Welcome.button -> Account", "Login.button \"Help\" -> Account", "Account.button -> About"]
+            .iter()
+            .map(|text| text.parse().unwrap())
+            .collect();
+        assert_eq!(destinations(&flows), vec!["Welcome", "Account", "About"]);
+    }
+}
@@ -0,0 +1,401 @@
+// File: src/synthesis/genetic.rs
+// Evolutionary search (`--strategy genetic`): a population of complete layouts is bred by
+// mutating and crossing them, keeping the fitter, for problems too large or too loosely
+// specified for the exact search to finish within its budget.
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use crate::ast::{Example, IR};
+use crate::layout::Metrics;
+use crate::synthesis::beam::misfit;
+use crate::synthesis::evaluate::{self, CostWeights};
+use crate::synthesis::search::{Budget, Exhausted, Outcome, Rng, SearchStats};
+use crate::synthesis::swiftui::synthesize_traced;
+use crate::synthesis::verify::{describe, expected_frames, expected_leaves, verify};
+
+/// Layouts in each generation.
+pub const POPULATION: usize = 32;
+/// Generations without a fitter layout after which a search that has satisfied the examples
+/// stops; one that has not stops after four times as many.
+pub const PATIENCE: usize = 25;
+
+fn stack(vertical: bool, children: Vec<IR>) -> IR {
+    if vertical { IR::VStack(children) } else { IR::HStack(children) }
+}
+
+fn children_mut(ir: &mut IR) -> Option<&mut Vec<IR>> {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => Some(children),
+        _ => None,
+    }
+}
+
+// Paths of the stacks of `ir`, the root first
+fn stack_paths(ir: &IR, path: &mut Vec<usize>, out: &mut Vec<Vec<usize>>) {
+    if let IR::VStack(children) | IR::HStack(children) = ir {
+        out.push(path.clone());
+        for (index, child) in children.iter().enumerate() {
+            path.push(index);
+            stack_paths(child, path, out);
+            path.pop();
+        }
+    }
+}
+
+fn node_mut<'a>(ir: &'a mut IR, path: &[usize]) -> &'a mut IR {
+    match path.split_first() {
+        Some((index, rest)) => node_mut(&mut children_mut(ir).expect("paths lead through stacks")[*index], rest),
+        None => ir,
+    }
+}
+
+fn node<'a>(ir: &'a IR, path: &[usize]) -> &'a IR {
+    match (path.split_first(), ir) {
+        (Some((index, rest)), IR::VStack(children) | IR::HStack(children)) => node(&children[*index], rest),
+        _ => ir,
+    }
+}
+
+fn depth(ir: &IR) -> usize {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => 1 + children.iter().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+// The views of `ir`, sorted, which a crossover must keep
+fn views(ir: &IR) -> Vec<IR> {
+    fn collect(ir: &IR, out: &mut Vec<IR>) {
+        match ir {
+            IR::VStack(children) | IR::HStack(children) => children.iter().for_each(|child| collect(child, out)),
+            IR::Spacer => {}
+            view => out.push(view.clone()),
+        }
+    }
+    let mut out = Vec::new();
+    collect(ir, &mut out);
+    out.sort();
+    out
+}
+
+/// One random change to a layout, keeping its views: a stack changes axis, gains or loses a
+/// spacer, groups a run of its children into a stack of the other axis, or dissolves into
+/// its parent, or (when `reorder`) two neighboring children swap. The layout model has no
+/// alignments, so where a designer would change one, a spacer or a regrouping moves the view
+/// instead. Returns `None` when the change picked does not apply.
+fn mutate(ir: &IR, rng: &mut Rng, reorder: bool) -> Option<IR> {
+    let mut paths = Vec::new();
+    stack_paths(ir, &mut Vec::new(), &mut paths);
+    let path = paths[rng.below(paths.len())].clone();
+    let mut mutated = ir.clone();
+    let target = node_mut(&mut mutated, &path);
+    match rng.below(6) {
+        0 => {
+            let vertical = matches!(target, IR::VStack(_));
+            let children = std::mem::take(children_mut(target)?);
+            *target = stack(!vertical, children);
+        }
+        1 => {
+            let children = children_mut(target)?;
+            let gap = rng.below(children.len() + 1);
+            let beside = |index: Option<usize>| index.and_then(|index| children.get(index)) == Some(&IR::Spacer);
+            if beside(gap.checked_sub(1)) || beside(Some(gap)) {
+                return None;
+            }
+            children.insert(gap, IR::Spacer);
+        }
+        2 => {
+            let children = children_mut(target)?;
+            let spacers: Vec<usize> = (0..children.len()).filter(|index| children[*index] == IR::Spacer).collect();
+            if spacers.is_empty() {
+                return None;
+            }
+            children.remove(spacers[rng.below(spacers.len())]);
+        }
+        3 => {
+            let vertical = matches!(target, IR::VStack(_));
+            let children = children_mut(target)?;
+            if children.len() < 3 {
+                return None;
+            }
+            let len = 2 + rng.below(children.len() - 2);
+            let start = rng.below(children.len() - len + 1);
+            let group: Vec<IR> = children.drain(start..start + len).collect();
+            children.insert(start, stack(!vertical, group));
+        }
+        4 => {
+            let (index, parent) = path.split_last()?;
+            let inner = std::mem::take(children_mut(target)?);
+            let siblings = children_mut(node_mut(&mut mutated, parent))?;
+            siblings.splice(*index..=*index, inner);
+        }
+        _ => {
+            let children = children_mut(target)?;
+            if !reorder || children.len() < 2 {
+                return None;
+            }
+            let index = rng.below(children.len() - 1);
+            children.swap(index, index + 1);
+        }
+    }
+    // An emptied stack is dropped rather than kept as an empty view
+    (views(&mutated) == views(ir)).then_some(mutated)
+}
+
+/// Replaces a random subtree of `a` with a subtree of `b` holding the same views, so the
+/// child has every view once. Returns `None` when no subtree of `b` matches.
+fn crossover(a: &IR, b: &IR, rng: &mut Rng) -> Option<IR> {
+    let (mut ours, mut theirs) = (Vec::new(), Vec::new());
+    stack_paths(a, &mut Vec::new(), &mut ours);
+    stack_paths(b, &mut Vec::new(), &mut theirs);
+    let pairs: Vec<(&Vec<usize>, &Vec<usize>)> = ours
+        .iter()
+        .flat_map(|ours| theirs.iter().map(move |theirs| (ours, theirs)))
+        .filter(|(ours, theirs)| views(node(a, ours)) == views(node(b, theirs)) && node(a, ours) != node(b, theirs))
+        .collect();
+    if pairs.is_empty() {
+        return None;
+    }
+    let (ours, theirs) = pairs[rng.below(pairs.len())];
+    let mut child = a.clone();
+    *node_mut(&mut child, ours) = node(b, theirs).clone();
+    Some(child)
+}
+
+struct Evolution<'a> {
+    examples: &'a [Example],
+    metrics: &'a Metrics,
+    budget: &'a Budget,
+    weights: &'a CostWeights,
+    started: Instant,
+    /// Fitness of every layout scored, by canonical form.
+    scores: BTreeMap<IR, Fitness>,
+    /// The `budget.top_n` best layouts: fewest violations, then lowest weighted cost.
+    ranked: Vec<(usize, f64, IR)>,
+    exhausted: Option<Exhausted>,
+    stats: SearchStats,
+}
+
+// How fit a layout is, fittest first: its violations, then how far its content overflows the
+// screens and its views are from their stated frames (see `beam::misfit`), which tells apart
+// layouts with the same violations, then its weighted cost
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Fitness {
+    violations: usize,
+    misfit: f64,
+    cost: f64,
+}
+
+impl Fitness {
+    fn compare(&self, other: &Fitness) -> Ordering {
+        self.violations.cmp(&other.violations).then(self.misfit.total_cmp(&other.misfit)).then(self.cost.total_cmp(&other.cost))
+    }
+}
+
+impl Evolution<'_> {
+    fn fitness(&mut self, ir: &IR) -> Fitness {
+        self.stats.enumerated += 1;
+        let canonical = ir.canonicalize();
+        if let Some(score) = self.scores.get(&canonical) {
+            self.stats.pruned += 1;
+            return *score;
+        }
+        self.stats.scored += 1;
+        self.stats.simulated += self.examples.len();
+        let score = Fitness {
+            violations: verify(ir, self.examples, self.metrics).len(),
+            misfit: misfit(ir, self.examples, self.metrics),
+            cost: evaluate::cost(ir, self.examples, self.metrics).total(self.weights),
+        };
+        self.scores.insert(canonical, score);
+        let rank = self.ranked.partition_point(|(best, best_cost, _)| (*best, *best_cost) <= (score.violations, score.cost));
+        if rank < self.budget.top_n.max(1) {
+            self.ranked.insert(rank, (score.violations, score.cost, ir.clone()));
+            self.ranked.truncate(self.budget.top_n.max(1));
+        }
+        score
+    }
+
+    fn satisfied(&self) -> bool {
+        self.ranked.iter().filter(|(violations, _, _)| *violations == 0).count() >= self.budget.top_n.max(1)
+    }
+
+    // Whether the budget has run out; running out after a satisfying layout was found does
+    // not count as exhausting it, unless the search was cancelled
+    fn out_of_budget(&mut self) -> bool {
+        let Some(reason) = self.budget.reached(self.started, &self.stats) else {
+            return false;
+        };
+        if self.ranked.first().is_none_or(|(violations, _, _)| *violations > 0) || reason == Exhausted::Cancelled {
+            self.exhausted = Some(reason);
+        }
+        true
+    }
+}
+
+/// Searches for a layout satisfying every example by evolving a population of layouts.
+///
+/// The heuristic layout from `synthesize_layout` is scored first and returned as is when it
+/// already satisfies the examples, they state no frames and only one layout is wanted.
+/// Otherwise it seeds a population of [`POPULATION`] layouts: it (when it has every
+/// requested view), the flat stacks of the requested views and random mutations of those. Each generation keeps the two fittest
+/// (fewest violations, then lowest weighted cost) and breeds the rest from parents picked by
+/// tournament, crossing them over and then mutating the child; layouts deeper than
+/// `budget.max_depth` are not bred. Evolution stops when the budget runs out, or once
+/// `budget.top_n` satisfying layouts were found and [`PATIENCE`] generations passed without
+/// a fitter one (four times as many if they were not). The random choices come from `budget.seed` (0 when unset), so a search that
+/// does not time out gives the same layout every run.
+pub fn search(examples: &[Example], budget: &Budget, metrics: &Metrics, weights: &CostWeights) -> Option<Outcome> {
+    let (heuristic, mut trace) = synthesize_traced(examples.to_vec())?;
+    let mut evolution = Evolution {
+        examples,
+        metrics,
+        budget,
+        weights,
+        started: Instant::now(),
+        scores: BTreeMap::new(),
+        ranked: Vec::new(),
+        exhausted: None,
+        stats: SearchStats::default(),
+    };
+    let geometry = examples.iter().any(|example| !expected_frames(example).is_empty());
+    let mut generations = 0;
+    if evolution.fitness(&heuristic).violations > 0 || geometry || budget.top_n > 1 {
+        let mut rng = Rng(budget.seed.unwrap_or(0));
+        let (leaves, ordered) = expected_leaves(&examples[0]);
+        let allowed = |ir: &IR| depth(ir) <= budget.max_depth.max(1);
+        // The heuristic layout drops repeated views, so it only seeds layouts when it has them all
+        let requested = views(&IR::VStack(leaves.clone()));
+        let seeds = [heuristic.clone(), IR::VStack(leaves.clone()), IR::HStack(leaves)];
+        let mut population: Vec<IR> = seeds.into_iter().filter(|seed| views(seed) == requested).collect();
+        while population.len() < POPULATION {
+            let parent = &population[rng.below(population.len())];
+            let child = mutate(parent, &mut rng, !ordered).filter(allowed).unwrap_or_else(|| parent.clone());
+            population.push(child);
+        }
+
+        let (mut best, mut stale) = (evolution.ranked[0].clone(), 0);
+        while !evolution.out_of_budget() && stale < if evolution.satisfied() { PATIENCE } else { 4 * PATIENCE } {
+            generations += 1;
+            let mut scored: Vec<(Fitness, IR)> = population.iter().map(|ir| (evolution.fitness(ir), ir.clone())).collect();
+            scored.sort_by(|(a, _), (b, _)| a.compare(b));
+            let mut next: Vec<IR> = scored.iter().take(2).map(|(_, ir)| ir.clone()).collect();
+            let tournament = |rng: &mut Rng| {
+                let (a, b) = (&scored[rng.below(scored.len())], &scored[rng.below(scored.len())]);
+                if b.0.compare(&a.0).is_lt() { b.1.clone() } else { a.1.clone() }
+            };
+            while next.len() < POPULATION {
+                let (a, b) = (tournament(&mut rng), tournament(&mut rng));
+                let child = crossover(&a, &b, &mut rng).filter(allowed).unwrap_or(a);
+                let mut child = child;
+                for _ in 0..1 + rng.below(2) {
+                    child = mutate(&child, &mut rng, !ordered).filter(allowed).unwrap_or(child);
+                }
+                next.push(child);
+            }
+            population = next;
+            if (evolution.ranked[0].0, evolution.ranked[0].1) < (best.0, best.1) {
+                (best, stale) = (evolution.ranked[0].clone(), 0);
+            } else {
+                stale += 1;
+            }
+        }
+    }
+
+    let mut ranked = evolution.ranked.into_iter();
+    let (violations, _, ir) = ranked.next().expect("the heuristic layout was scored");
+    let alternatives = ranked.filter(|(violations, _, _)| *violations == 0).map(|(_, _, ir)| ir).collect();
+    if ir != heuristic {
+        let reason = match verify(&heuristic, examples, metrics).first() {
+            Some(violation) => violation.to_string(),
+            None => format!("costs more ({})", evaluate::cost(&heuristic, examples, metrics)),
+        };
+        trace = vec![format!("Direct translation {} rejected: {}", describe(&heuristic), reason)];
+    }
+    if generations > 0 {
+        trace.push(format!(
+            "Evolved {} generations of {} layouts (seed {}), scoring {} distinct layouts",
+            generations,
+            POPULATION,
+            budget.seed.unwrap_or(0),
+            evolution.stats.scored
+        ));
+    }
+    match evolution.exhausted {
+        Some(reason) => trace.push(format!(
+            "Search stopped ({}) after {} candidates; showing the layout with the fewest problems ({})",
+            reason, evolution.stats.scored, violations
+        )),
+        None if violations > 0 => trace.push(format!(
+            "No layout evolved satisfies every example; showing the one with the fewest problems ({})",
+            violations
+        )),
+        None => {}
+    }
+    Some(Outcome {
+        ir,
+        violations,
+        candidates: evolution.stats.scored,
+        exhausted: evolution.exhausted,
+        trace,
+        stats: evolution.stats,
+        alternatives,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    fn text(s: &str) -> IR {
+        IR::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_mutations_and_crossover_keep_the_views() {
+        let ir = IR::VStack(vec![text("A"), IR::HStack(vec![text("B"), IR::Spacer, text("C")]), text("D")]);
+        let mut rng = Rng(3);
+        let mut changed = 0;
+        for _ in 0..200 {
+            if let Some(mutated) = mutate(&ir, &mut rng, true) {
+                assert_eq!(views(&mutated), views(&ir), "{:?}", mutated);
+                changed += usize::from(mutated != ir);
+            }
+        }
+        assert!(changed > 100);
+
+        let other = IR::VStack(vec![text("A"), IR::VStack(vec![text("B"), text("C")]), text("D")]);
+        let child = crossover(&ir, &other, &mut Rng(1)).unwrap();
+        assert_eq!(views(&child), views(&ir));
+        assert_ne!(child, ir);
+        assert_eq!(crossover(&ir, &ir, &mut Rng(1)), None);
+    }
+
+    #[test]
+    fn test_evolution_satisfies_the_examples_and_is_seeded() {
+        let examples = parse_examples(
+            "{(width:320,height:300):{title:\"One\",title:\"Two\",title:\"Three\",button:\"Four\",button:\"Five\",Image:\"six\"}}",
+        )
+        .unwrap();
+        let weights = CostWeights::default();
+        let budget = |seed| Budget { seed: Some(seed), timeout: None, ..Budget::default() };
+        let outcome = search(&examples, &budget(1), &Metrics::default(), &weights).unwrap();
+        assert_eq!(outcome.violations, 0, "{:?}", outcome.ir);
+        assert!(depth(&outcome.ir) <= 3);
+        assert!(outcome.trace.iter().any(|line| line.starts_with("Evolved ")), "{:?}", outcome.trace);
+        assert_eq!(search(&examples, &budget(1), &Metrics::default(), &weights).unwrap(), outcome);
+        for seed in 2..4 {
+            assert_eq!(search(&examples, &budget(seed), &Metrics::default(), &weights).unwrap().violations, 0, "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_evolution_respects_the_candidate_limit() {
+        let examples = parse_examples("{(width:320,height:200):{Image:\"logo\",title:\"Hi\",button:\"Go\"}}").unwrap();
+        let budget = Budget { max_candidates: 5, ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default(), &CostWeights::default()).unwrap();
+        assert!(outcome.candidates <= 5 + POPULATION);
+    }
+}
@@ -0,0 +1,213 @@
+// File: src/synthesis/incremental.rs
+// Incremental synthesis for `serve`: when an edit to the spec only changes what some views
+// say, the previous layout is kept with the new labels put in, instead of searching again.
+use std::collections::BTreeMap;
+
+use crate::ast::{Example, Value, IR};
+use crate::layout::Metrics;
+use crate::synthesis::search::{Outcome, SearchStats};
+use crate::synthesis::verify::verify;
+
+/// A label that changed between two versions of a spec: the element key, then the old and
+/// the new label.
+pub type LabelChange = (String, String, String);
+
+// The keys whose string values are labels, and the HStack children, which are all texts
+fn is_label(key: &str) -> bool {
+    matches!(key, "title" | "button" | "Image")
+}
+
+// The labels of `old` and `new` elements, paired, or `None` if anything else differs
+fn pair_labels(old: &Value, new: &Value, labels: &mut Vec<LabelChange>) -> Option<()> {
+    let (Value::Dict(old), Value::Dict(new)) = (old, new) else { return (old == new).then_some(()) };
+    if old.len() != new.len() {
+        return None;
+    }
+    for ((old_key, old_value), (new_key, new_value)) in old.iter().zip(new) {
+        if old_key != new_key {
+            return None;
+        }
+        match (old_key.as_str(), old_value, new_value) {
+            ("HStack", Value::Dict(old), Value::Dict(new)) => {
+                if old.len() != new.len() {
+                    return None;
+                }
+                for ((_, old), (_, new)) in old.iter().zip(new) {
+                    match (old, new) {
+                        // A spacer is structure, not a label
+                        (Value::String(old), Value::String(new)) if old != "Spacer" && new != "Spacer" => {
+                            labels.push(("title".to_string(), old.clone(), new.clone()))
+                        }
+                        _ if old == new => {}
+                        _ => return None,
+                    }
+                }
+            }
+            (key, Value::String(old), Value::String(new)) if is_label(key) => labels.push((key.to_string(), old.clone(), new.clone())),
+            _ if old_value == new_value => {}
+            _ => return None,
+        }
+    }
+    Some(())
+}
+
+/// The labels that changed from `old` to `new` examples, or `None` if anything else did:
+/// the number of examples or elements, their keys and order, the dimensions, frames and
+/// constraints, or a label that changed in some places and not others. An empty list means
+/// nothing changed.
+pub fn label_changes(old: &[Example], new: &[Example]) -> Option<Vec<LabelChange>> {
+    if old.len() != new.len() {
+        return None;
+    }
+    let mut labels = Vec::new();
+    for ((old_dims, old_elements), (new_dims, new_elements)) in old.iter().zip(new) {
+        if old_dims != new_dims {
+            return None;
+        }
+        pair_labels(old_elements, new_elements, &mut labels)?;
+    }
+    // Every occurrence of a label must change the same way, or the layout cannot tell them apart
+    let mut renames: BTreeMap<(String, String), String> = BTreeMap::new();
+    for (key, old, new) in labels {
+        match renames.get(&(key.clone(), old.clone())) {
+            Some(renamed) if *renamed != new => return None,
+            Some(_) => {}
+            None => {
+                renames.insert((key, old), new);
+            }
+        }
+    }
+    Some(renames.into_iter().filter(|((_, old), new)| old != new).map(|((key, old), new)| (key, old, new)).collect())
+}
+
+/// `ir` with the labels in `changes` replaced, or `None` if one of them is not in it.
+pub fn relabel(ir: &IR, changes: &[LabelChange]) -> Option<IR> {
+    fn walk(ir: &IR, changes: &[LabelChange], used: &mut [bool]) -> IR {
+        let mut rename = |key: &str, label: &String| match changes.iter().position(|(k, old, _)| k == key && old == label) {
+            Some(i) => {
+                used[i] = true;
+                changes[i].2.clone()
+            }
+            None => label.clone(),
+        };
+        match ir {
+            IR::VStack(children) => IR::VStack(children.iter().map(|child| walk(child, changes, used)).collect()),
+            IR::HStack(children) => IR::HStack(children.iter().map(|child| walk(child, changes, used)).collect()),
+            IR::Text(label) => IR::Text(rename("title", label)),
+            IR::Button(label) => IR::Button(rename("button", label)),
+            IR::Image(label) => IR::Image(rename("Image", label)),
+            IR::Spacer | IR::Custom { .. } => ir.clone(),
+        }
+    }
+    let mut used = vec![false; changes.len()];
+    let relabeled = walk(ir, changes, &mut used);
+    used.iter().all(|&used| used).then_some(relabeled)
+}
+
+/// The last outcome synthesized for each screen, with the examples and settings it was for.
+#[derive(Clone, Debug, Default)]
+pub struct Previous {
+    screens: Vec<(Option<String>, String, Vec<Example>, Outcome)>,
+}
+
+impl Previous {
+    /// Remembers `outcome` for the screen `name`; `settings` identifies everything else the
+    /// search depended on, such as [`cache::fingerprint`](crate::synthesis::cache::fingerprint)
+    /// of no examples.
+    pub fn record(&mut self, name: Option<&str>, settings: &str, examples: &[Example], outcome: &Outcome) {
+        self.screens.retain(|(screen, ..)| screen.as_deref() != name);
+        self.screens.push((name.map(str::to_string), settings.to_string(), examples.to_vec(), outcome.clone()));
+    }
+
+    /// The previous outcome for the screen `name`, with the labels `examples` changed put in,
+    /// if it still satisfies every example; `None` when the screen must be searched again.
+    /// Alternatives that no longer satisfy the examples are dropped.
+    pub fn reuse(&self, name: Option<&str>, settings: &str, examples: &[Example], metrics: &Metrics) -> Option<Outcome> {
+        let (_, _, previous_examples, previous) =
+            self.screens.iter().find(|(screen, previous_settings, ..)| screen.as_deref() == name && previous_settings == settings)?;
+        if previous.violations > 0 {
+            return None;
+        }
+        let changes = label_changes(previous_examples, examples)?;
+        let ir = relabel(&previous.ir, &changes)?;
+        if !verify(&ir, examples, metrics).is_empty() {
+            return None;
+        }
+        let alternatives = previous
+            .alternatives
+            .iter()
+            .filter_map(|alternative| relabel(alternative, &changes))
+            .filter(|alternative| verify(alternative, examples, metrics).is_empty())
+            .collect();
+        let mut trace: Vec<String> = previous
+            .trace
+            .iter()
+            .filter(|line| !line.starts_with("Reused the previous layout"))
+            .map(|line| changes.iter().fold(line.clone(), |line, (_, old, new)| line.replace(&format!("\"{}\"", old), &format!("\"{}\"", new))))
+            .collect();
+        trace.push(match changes.len() {
+            0 => "Reused the previous layout: the examples have not changed".to_string(),
+            n => format!("Reused the previous layout with {} label(s) changed, since nothing else in the examples did", n),
+        });
+        Some(Outcome { ir, violations: 0, candidates: 1, exhausted: None, trace, stats: SearchStats::default(), alternatives })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+    use crate::synthesis::search::{search, Budget};
+
+    const SPEC: &str = "{(width:390,height:844):{title:\"Welcome\",button:\"Go\"}} {(width:320,height:568):{title:\"Welcome\",button:\"Go\"}}";
+
+    fn change(key: &str, old: &str, new: &str) -> LabelChange {
+        (key.to_string(), old.to_string(), new.to_string())
+    }
+
+    #[test]
+    fn test_label_changes_are_found() {
+        let old = parse_examples(SPEC).unwrap();
+        let new = parse_examples(&SPEC.replace("\"Go\"", "\"Continue\"")).unwrap();
+        assert_eq!(label_changes(&old, &new), Some(vec![change("button", "Go", "Continue")]));
+        assert_eq!(label_changes(&old, &old), Some(Vec::new()));
+        // A label changed in one example only, or anything but a label, is not
+        let one = parse_examples(&SPEC.replacen("\"Go\"", "\"Continue\"", 1)).unwrap();
+        assert_eq!(label_changes(&old, &one), None);
+        let resized = parse_examples(&SPEC.replace("width:320", "width:375")).unwrap();
+        assert_eq!(label_changes(&old, &resized), None);
+        let added = parse_examples(&SPEC.replace("button:\"Go\"", "button:\"Go\",Image:\"logo\"")).unwrap();
+        assert_eq!(label_changes(&old, &added), None);
+        let row = parse_examples("{(width:390,height:844):HStack:{\"A\",\"Spacer\"}}").unwrap();
+        let renamed = parse_examples("{(width:390,height:844):HStack:{\"B\",\"Spacer\"}}").unwrap();
+        assert_eq!(label_changes(&row, &renamed), Some(vec![change("title", "A", "B")]));
+    }
+
+    #[test]
+    fn test_relabel_needs_every_label() {
+        let ir = IR::VStack(vec![IR::Text("Welcome".to_string()), IR::Spacer, IR::Button("Go".to_string())]);
+        let relabeled = relabel(&ir, &[change("button", "Go", "Continue")]).unwrap();
+        assert_eq!(relabeled, IR::VStack(vec![IR::Text("Welcome".to_string()), IR::Spacer, IR::Button("Continue".to_string())]));
+        // A title is not a button's label
+        assert_eq!(relabel(&ir, &[change("title", "Go", "Continue")]), None);
+    }
+
+    #[test]
+    fn test_previous_layouts_are_reused() {
+        let metrics = Metrics::default();
+        let old = parse_examples(SPEC).unwrap();
+        let outcome = search(&old, &Budget::default(), &metrics).unwrap();
+        let mut previous = Previous::default();
+        previous.record(None, "settings", &old, &outcome);
+
+        let new = parse_examples(&SPEC.replace("\"Go\"", "\"Continue\"")).unwrap();
+        let reused = previous.reuse(None, "settings", &new, &metrics).unwrap();
+        assert_eq!(reused.ir, relabel(&outcome.ir, &[change("button", "Go", "Continue")]).unwrap());
+        assert!(reused.trace.last().unwrap().starts_with("Reused the previous layout with 1 label(s) changed"));
+        // Other settings, another screen or a label too long for the screen search again
+        assert_eq!(previous.reuse(None, "other", &new, &metrics), None);
+        assert_eq!(previous.reuse(Some("Login"), "settings", &new, &metrics), None);
+        let long = parse_examples(&SPEC.replace("\"Go\"", &format!("\"{}\"", "Go on ".repeat(100)))).unwrap();
+        assert_eq!(previous.reuse(None, "settings", &long, &metrics), None);
+    }
+}
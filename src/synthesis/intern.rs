@@ -0,0 +1,202 @@
+// File: src/synthesis/intern.rs
+// An arena of layout trees for the searches' record of the candidates they scored: each
+// distinct subtree is stored once and candidates are kept as ids into it (hash-consing), so a
+// deep search that tries millions of arrangements of the same views holds each arrangement
+// of a group of views once, and can tell how much memory its record takes.
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::{Hash, Hasher};
+use std::mem::size_of;
+
+use crate::ast::IR;
+
+/// A tree interned in an [`Interner`]; equal trees have equal ids.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(u32);
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Node {
+    Stack { vertical: bool, children: Box<[NodeId]> },
+    Leaf(IR),
+}
+
+/// The arena: nodes by id, and the ids of each node hash, to find a node already stored.
+#[derive(Clone, Debug, Default)]
+pub struct Interner {
+    nodes: Vec<Node>,
+    by_hash: BTreeMap<u64, Vec<NodeId>>,
+    bytes: usize,
+}
+
+fn hash(node: &Node) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    node.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Heap bytes a leaf's labels take
+fn leaf_bytes(ir: &IR) -> usize {
+    match ir {
+        IR::Text(label) | IR::Button(label) | IR::Image(label) => label.capacity(),
+        IR::Custom { name, args } => name.capacity() + args.capacity(),
+        _ => 0,
+    }
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    // The id of `node`, if it is stored
+    fn lookup(&self, node: &Node, hash: u64) -> Option<NodeId> {
+        self.by_hash.get(&hash)?.iter().copied().find(|id| self.nodes[id.0 as usize] == *node)
+    }
+
+    /// The id of `ir`, if it is stored, without storing anything.
+    pub fn find(&self, ir: &IR) -> Option<NodeId> {
+        let node = match ir {
+            IR::VStack(children) | IR::HStack(children) => Node::Stack {
+                vertical: matches!(ir, IR::VStack(_)),
+                children: children.iter().map(|child| self.find(child)).collect::<Option<_>>()?,
+            },
+            leaf => Node::Leaf(leaf.clone()),
+        };
+        self.lookup(&node, hash(&node))
+    }
+
+    /// The id of `ir`, storing those of its subtrees not stored yet.
+    pub fn intern(&mut self, ir: &IR) -> NodeId {
+        let node = match ir {
+            IR::VStack(children) | IR::HStack(children) => Node::Stack {
+                vertical: matches!(ir, IR::VStack(_)),
+                children: children.iter().map(|child| self.intern(child)).collect(),
+            },
+            leaf => Node::Leaf(leaf.clone()),
+        };
+        let hash = hash(&node);
+        if let Some(id) = self.lookup(&node, hash) {
+            return id;
+        }
+        let id = NodeId(u32::try_from(self.nodes.len()).expect("fewer than 2^32 distinct subtrees"));
+        self.bytes += size_of::<Node>() + size_of::<NodeId>() + size_of::<u64>() + size_of::<Vec<NodeId>>();
+        self.bytes += match &node {
+            Node::Stack { children, .. } => children.len() * size_of::<NodeId>(),
+            Node::Leaf(ir) => leaf_bytes(ir),
+        };
+        self.nodes.push(node);
+        self.by_hash.entry(hash).or_default().push(id);
+        id
+    }
+
+    /// The tree `id` stands for.
+    pub fn get(&self, id: NodeId) -> IR {
+        match &self.nodes[id.0 as usize] {
+            Node::Stack { vertical: true, children } => IR::VStack(children.iter().map(|&child| self.get(child)).collect()),
+            Node::Stack { vertical: false, children } => IR::HStack(children.iter().map(|&child| self.get(child)).collect()),
+            Node::Leaf(ir) => ir.clone(),
+        }
+    }
+
+    /// Number of distinct subtrees stored.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Approximate bytes the arena holds, for `--max-memory`.
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+}
+
+/// A set of layouts kept in an [`Interner`], such as the canonical forms of the candidates a
+/// search has scored.
+#[derive(Clone, Debug, Default)]
+pub struct LayoutSet {
+    interner: Interner,
+    ids: BTreeSet<NodeId>,
+}
+
+impl LayoutSet {
+    pub fn new() -> LayoutSet {
+        LayoutSet::default()
+    }
+
+    pub fn contains(&self, ir: &IR) -> bool {
+        self.interner.find(ir).is_some_and(|id| self.ids.contains(&id))
+    }
+
+    /// Adds `ir`; false if it was already in the set.
+    pub fn insert(&mut self, ir: &IR) -> bool {
+        let id = self.interner.intern(ir);
+        self.ids.insert(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Approximate bytes the set holds: its arena, and its ids with a word of tree overhead each.
+    pub fn bytes(&self) -> usize {
+        self.interner.bytes() + self.ids.len() * (size_of::<NodeId>() + size_of::<usize>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(label: &str) -> IR {
+        IR::Text(label.to_string())
+    }
+
+    #[test]
+    fn test_identical_subtrees_are_stored_once() {
+        let mut interner = Interner::new();
+        let row = IR::HStack(vec![text("A"), IR::Spacer, text("B")]);
+        let first = interner.intern(&IR::VStack(vec![row.clone(), text("C")]));
+        let stored = interner.len();
+        // The row and its leaves are shared; only the new root and leaf are added
+        let second = interner.intern(&IR::VStack(vec![text("D"), row.clone()]));
+        assert_eq!(interner.len(), stored + 2);
+        assert_ne!(first, second);
+        assert_eq!(interner.intern(&IR::VStack(vec![row.clone(), text("C")])), first);
+        assert_eq!(interner.get(second), IR::VStack(vec![text("D"), row]));
+        // The axis tells stacks of the same children apart
+        assert_ne!(interner.intern(&IR::HStack(vec![text("A")])), interner.intern(&IR::VStack(vec![text("A")])));
+    }
+
+    #[test]
+    fn test_sets_find_layouts_without_storing_them() {
+        let mut set = LayoutSet::new();
+        let layout = IR::VStack(vec![text("A"), IR::HStack(vec![text("B"), IR::Spacer])]);
+        assert!(set.insert(&layout));
+        assert!(!set.insert(&layout));
+        assert!(set.contains(&layout));
+        // A subtree of a layout in the set is stored, but not in the set itself
+        assert!(!set.contains(&IR::HStack(vec![text("B"), IR::Spacer])));
+        let bytes = set.bytes();
+        assert!(!set.contains(&IR::VStack(vec![text("C")])));
+        assert_eq!((set.len(), set.bytes()), (1, bytes));
+    }
+
+    #[test]
+    fn test_bytes_grow_with_new_subtrees_only() {
+        let mut interner = Interner::new();
+        assert_eq!((interner.bytes(), interner.is_empty()), (0, true));
+        let layout = IR::VStack(vec![text("Hello"), IR::Button("Go".to_string())]);
+        interner.intern(&layout);
+        let bytes = interner.bytes();
+        assert!(bytes > 0);
+        interner.intern(&layout);
+        assert_eq!(interner.bytes(), bytes);
+    }
+}
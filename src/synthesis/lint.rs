@@ -0,0 +1,213 @@
+// File: src/synthesis/lint.rs
+// Checks a synthesized layout for patterns that satisfy the examples but make poor SwiftUI:
+// deep nesting, text that can outgrow its fixed frame, small touch targets and stacks that
+// hold nothing. Each finding is a warning; none stops the code being generated.
+use serde::Deserialize;
+
+use crate::ast::{Example, IR};
+use crate::diagnostics::Diagnostic;
+use crate::layout::{self, text_style, Insets, Metrics};
+use crate::synthesis::verify::{describe, expected_frames};
+
+/// Thresholds of the lint pass, from the `[lint]` section of the config file.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct LintOptions {
+    /// Deepest stack nesting before W0003, the root stack counting as one.
+    pub max_depth: usize,
+    /// Smallest width and height in points of a button's tappable area before W0005.
+    pub min_touch_target: f64,
+    /// Codes of lints not to report, e.g. `["W0007"]`.
+    pub allow: Vec<String>,
+    /// Whether buttons are rendered with `.padding()`, which is part of their tappable area.
+    #[serde(skip)]
+    pub padding: bool,
+    /// Text style of texts that state no size, whose lines their stated heights are read in.
+    #[serde(skip)]
+    pub text_font: String,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        LintOptions { max_depth: 3, min_touch_target: 44.0, allow: Vec::new(), padding: true, text_font: "title".to_string() }
+    }
+}
+
+fn depth(ir: &IR) -> usize {
+    match ir {
+        IR::VStack(children) | IR::HStack(children) => 1 + children.iter().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+// Stacks that hold only spacers (or nothing), and stacks below the root around a single view
+fn stacks(ir: &IR, root: bool, out: &mut Vec<Diagnostic>) {
+    let (IR::VStack(children) | IR::HStack(children)) = ir else { return };
+    let kind = if matches!(ir, IR::VStack(_)) { "VStack" } else { "HStack" };
+    if children.iter().all(|child| *child == IR::Spacer) {
+        out.push(
+            Diagnostic::warning("W0006", format!("{} holds nothing but spacers", kind))
+                .with_suggestion("remove it, or replace it with a single Spacer()"),
+        );
+    } else if let [child] = children.as_slice() {
+        if !root {
+            out.push(
+                Diagnostic::warning("W0007", format!("{} wraps a single {}", kind, describe(child)))
+                    .with_suggestion(format!("use the {} directly", describe(child))),
+            );
+        }
+    }
+    for child in children {
+        stacks(child, false, out);
+    }
+}
+
+/// A button whose tappable area is under the minimum touch target in an example.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TouchTarget {
+    /// Index of the example, from 0.
+    pub example: usize,
+    pub node: IR,
+    pub width: f64,
+    pub height: f64,
+}
+
+/// Buttons of `ir` narrower or shorter than `minimum` points, each reported for the first
+/// example it is that small in. Without `padding` only a button's label is tappable.
+pub fn small_touch_targets(ir: &IR, examples: &[Example], metrics: &Metrics, minimum: f64, padding: bool) -> Vec<TouchTarget> {
+    let inset = if padding { 0.0 } else { 2.0 * metrics.padding };
+    let ignores_safe_area = layout::ignores_safe_area(examples);
+    let mut small: Vec<TouchTarget> = Vec::new();
+    for (index, example) in examples.iter().enumerate() {
+        let metrics = &metrics.scaled(layout::dynamic_type(example).scale());
+        let Some(screen) = layout::screen_for(ir, example, metrics) else { continue };
+        let insets = if ignores_safe_area { Insets::default() } else { layout::safe_area(example) };
+        for frame in layout::simulate_within(ir, screen, insets, metrics).leaves() {
+            let (width, height) = (frame.rect.width - inset, frame.rect.height - inset);
+            if !matches!(frame.node, IR::Button(_)) || (width >= minimum && height >= minimum) || small.iter().any(|t| t.node == frame.node) {
+                continue;
+            }
+            small.push(TouchTarget { example: index, node: frame.node.clone(), width, height });
+        }
+    }
+    small
+}
+
+/// Lints `ir`, laid out at each of `examples`' sizes. Each finding is reported once, for
+/// the first example it shows in.
+pub fn lint(ir: &IR, examples: &[Example], metrics: &Metrics, options: &LintOptions) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let depth = depth(ir);
+    if depth > options.max_depth {
+        diagnostics.push(
+            Diagnostic::warning("W0003", format!("Stacks are nested {} deep, more than {}", depth, options.max_depth))
+                .with_suggestion("group fewer elements per row or column, or raise max_depth in the [lint] config section"),
+        );
+    }
+
+    // Text stated at a fixed height wraps past it when it grows, e.g. translated, unless the
+    // heights tell whether it wraps or stays on one line, which its line limit then says
+    let wrapping = text_style::wrapping(examples, &text_style::classify(examples), &options.text_font);
+    let mut fixed = Vec::new();
+    for (index, example) in examples.iter().enumerate() {
+        for (node, frame) in expected_frames(example) {
+            let (IR::Text(text), Some(height)) = (&node, frame.height) else { continue };
+            if !fixed.contains(&node) && !wrapping.contains_key(text) {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        "W0004",
+                        format!("{} has a fixed height of {}pt in example {} but no line limit", describe(&node), height, index + 1),
+                    )
+                    .with_suggestion("add .lineLimit(1) to the text, or leave its height unstated so it can wrap"),
+                );
+                fixed.push(node);
+            }
+        }
+    }
+
+    // Buttons smaller than a fingertip
+    let minimum = options.min_touch_target;
+    for target in small_touch_targets(ir, examples, metrics, minimum, options.padding) {
+        diagnostics.push(
+            Diagnostic::warning(
+                "W0005",
+                format!(
+                    "{} is {:.0}x{:.0}pt in example {}, under the {}x{}pt minimum touch target",
+                    describe(&target.node),
+                    target.width,
+                    target.height,
+                    target.example + 1,
+                    minimum,
+                    minimum
+                ),
+            )
+            .with_suggestion(format!("give the button .frame(minWidth: {}, minHeight: {})", minimum, minimum)),
+        );
+    }
+
+    stacks(ir, true, &mut diagnostics);
+    diagnostics.retain(|diagnostic| !options.allow.iter().any(|code| code == diagnostic.code));
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    fn codes(ir: &IR, spec: &str, options: &LintOptions) -> Vec<&'static str> {
+        let examples = parse_examples(spec).unwrap();
+        lint(ir, &examples, &Metrics::default(), options).iter().map(|diagnostic| diagnostic.code).collect()
+    }
+
+    fn text(s: &str) -> IR {
+        IR::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_clean_layout() {
+        let ir = IR::VStack(vec![text("Hi"), IR::Spacer, IR::Button("Go".to_string())]);
+        assert_eq!(codes(&ir, "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}", &LintOptions::default()), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_nesting_and_empty_stacks() {
+        let ir = IR::VStack(vec![IR::HStack(vec![IR::VStack(vec![IR::HStack(vec![text("A"), text("B")])])]), IR::HStack(vec![IR::Spacer])]);
+        let spec = "{(width:390,height:844):{title:\"A\",title:\"B\"}}";
+        assert_eq!(codes(&ir, spec, &LintOptions::default()), vec!["W0003", "W0007", "W0007", "W0006"]);
+        let relaxed = LintOptions { max_depth: 4, allow: vec!["W0007".to_string()], ..LintOptions::default() };
+        assert_eq!(codes(&ir, spec, &relaxed), vec!["W0006"]);
+        // A single view in the root stack is how one-element layouts are rendered
+        assert_eq!(codes(&IR::VStack(vec![text("Hi")]), "{(width:390,height:844):{title:\"Hi\"}}", &LintOptions::default()), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_fixed_height_text() {
+        // Two lines stated two lines tall get no line limit
+        let examples = parse_examples(
+            "{(width:390,height:844):{title:\"Two\\nlines\"@(y:16,h:68)}} {(width:320,height:568):{title:\"Two\\nlines\"@(h:68)}}",
+        )
+        .unwrap();
+        let warnings = lint(&IR::VStack(vec![text("Two\nlines")]), &examples, &Metrics::default(), &LintOptions::default());
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].message, "Text \"Two\nlines\" has a fixed height of 68pt in example 1 but no line limit");
+        // A line tall, or taller than its lines, it is limited to one line or wraps
+        let ir = IR::VStack(vec![text("Hi"), text("fine")]);
+        assert_eq!(codes(&ir, "{(width:390,height:844):{title:\"Hi\"@(h:34),title:\"fine\"@(h:16,font:12)}}", &LintOptions::default()), Vec::<&str>::new());
+        assert_eq!(codes(&IR::VStack(vec![text("Hi")]), "{(width:390,height:844):{title:\"Hi\"@(w:100,h:102)}}", &LintOptions::default()), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn test_touch_targets() {
+        let ir = IR::VStack(vec![IR::Button("Go".to_string())]);
+        let spec = "{(width:390,height:844):{button:\"Go\"}}";
+        assert_eq!(codes(&ir, spec, &LintOptions::default()), Vec::<&str>::new());
+        // Without .padding() only the label is tappable
+        let examples = parse_examples(spec).unwrap();
+        let unpadded = LintOptions { padding: false, ..LintOptions::default() };
+        let warnings = lint(&ir, &examples, &Metrics::default(), &unpadded);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.starts_with("Button \"Go\" is "), "{}", warnings[0].message);
+        assert!(warnings[0].message.ends_with("pt in example 1, under the 44x44pt minimum touch target"), "{}", warnings[0].message);
+    }
+}
@@ -0,0 +1,237 @@
+// File: src/synthesis/llm.rs
+// Language-model hints (`--llm`, with the `llm` feature): the examples and the verified
+// `--top-n` layouts are sent to an OpenAI or Ollama chat endpoint, which may reorder them and
+// say why. The model only ever chooses among layouts the search verified; when it cannot be
+// reached or its reply makes no sense, synthesis carries on with the search's own order.
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde_json::{json, Value};
+
+use crate::config::LlmSection;
+use crate::utils::process::on_path;
+
+/// Names `--llm` accepts.
+pub const PROVIDERS: &[&str] = &["openai", "ollama"];
+
+/// Time allowed for a reply when `[llm] timeout_ms` is unset.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// A chat endpoint to ask for hints.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Provider {
+    /// The OpenAI chat completions API, authorized by `$OPENAI_API_KEY`.
+    OpenAi,
+    /// A local Ollama server.
+    Ollama,
+}
+
+impl FromStr for Provider {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Provider, String> {
+        match name {
+            "openai" => Ok(Provider::OpenAi),
+            "ollama" => Ok(Provider::Ollama),
+            _ => Err(format!("Unknown LLM provider '{}': expected one of {}", name, PROVIDERS.join(", "))),
+        }
+    }
+}
+
+impl Provider {
+    fn default_endpoint(self) -> String {
+        match self {
+            Provider::OpenAi => "https://api.openai.com/v1/chat/completions".to_string(),
+            Provider::Ollama => {
+                let host = std::env::var("OLLAMA_HOST").unwrap_or_else(|_| "http://localhost:11434".to_string());
+                format!("{}/api/chat", host.trim_end_matches('/'))
+            }
+        }
+    }
+
+    fn default_model(self) -> &'static str {
+        match self {
+            Provider::OpenAi => "gpt-4o-mini",
+            Provider::Ollama => "llama3.1",
+        }
+    }
+
+    /// The body of a chat request for `prompt`, asking for a reply in one piece.
+    pub fn request(self, model: &str, prompt: &str) -> Value {
+        let messages = json!([{ "role": "user", "content": prompt }]);
+        match self {
+            Provider::OpenAi => json!({ "model": model, "messages": messages, "temperature": 0 }),
+            Provider::Ollama => json!({ "model": model, "messages": messages, "stream": false, "options": { "temperature": 0 } }),
+        }
+    }
+
+    /// The text of the model's answer in a chat response.
+    pub fn answer(self, response: &str) -> Result<String, String> {
+        let response: Value = serde_json::from_str(response).map_err(|e| format!("the reply is not JSON: {}", e))?;
+        if let Some(error) = response.get("error") {
+            return Err(format!("the endpoint reported an error: {}", error.get("message").unwrap_or(error)));
+        }
+        let message = match self {
+            Provider::OpenAi => &response["choices"][0]["message"],
+            Provider::Ollama => &response["message"],
+        };
+        message["content"].as_str().map(str::to_string).ok_or_else(|| "the reply has no message".to_string())
+    }
+}
+
+/// What the model made of the layouts: the order to offer them in, as indices into the
+/// layouts sent, and its reason, if it gave one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    pub order: Vec<usize>,
+    pub reason: Option<String>,
+}
+
+/// The question asked about `candidates` (SwiftUI code, in the search's order) for the
+/// examples in `spec`.
+pub fn prompt(spec: &str, candidates: &[String]) -> String {
+    let mut prompt = format!(
+        "These examples give the views of a SwiftUI screen at one or more screen sizes, and sometimes \
+         where they should be:\n\n{}\n\nEach of the following {} layouts satisfies them. Order them from \
+         the one a SwiftUI developer would most likely want to the least, judging by how idiomatic and \
+         maintainable they are and what the screen is probably for.\n",
+        spec.trim(),
+        candidates.len()
+    );
+    for (i, code) in candidates.iter().enumerate() {
+        prompt.push_str(&format!("\nLayout {}:\n```swift\n{}\n```\n", i + 1, code.trim_end()));
+    }
+    prompt.push_str("\nAnswer with exactly two lines:\nORDER: the layout numbers, best first, separated by commas\nREASON: one sentence\n");
+    prompt
+}
+
+/// Reads the `ORDER:` and `REASON:` lines of an answer about `n` layouts. The order must
+/// name each layout once; layouts it leaves out follow in the search's order.
+pub fn parse_hint(answer: &str, n: usize) -> Result<Hint, String> {
+    let field = |name: &str| {
+        answer.lines().find_map(|line| {
+            let line = line.trim().trim_start_matches(['*', '#', ' ']);
+            line.get(..name.len()).filter(|head| head.eq_ignore_ascii_case(name)).map(|_| line[name.len()..].trim().to_string())
+        })
+    };
+    let order = field("ORDER:").ok_or("the answer has no ORDER line")?;
+    let mut hint = Hint { order: Vec::new(), reason: field("REASON:").filter(|reason| !reason.is_empty()) };
+    for number in order.split(|c: char| !c.is_ascii_digit()).filter(|number| !number.is_empty()) {
+        let index = number.parse::<usize>().ok().filter(|i| (1..=n).contains(i)).ok_or_else(|| format!("the answer names layout {}, of {}", number, n))?;
+        if hint.order.contains(&(index - 1)) {
+            return Err(format!("the answer names layout {} twice", index));
+        }
+        hint.order.push(index - 1);
+    }
+    if hint.order.is_empty() {
+        return Err("the answer's ORDER line names no layouts".to_string());
+    }
+    hint.order.extend((0..n).filter(|i| !hint.order.contains(i)).collect::<Vec<_>>());
+    Ok(hint)
+}
+
+/// A configured endpoint, reached with `curl` (at `$CURL` or on the `PATH`).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Client {
+    pub provider: Provider,
+    pub endpoint: String,
+    pub model: String,
+    pub timeout: Duration,
+}
+
+impl Client {
+    /// The client for `provider` with the `[llm]` settings.
+    pub fn new(provider: Provider, config: &LlmSection) -> Client {
+        Client {
+            provider,
+            endpoint: config.endpoint.clone().unwrap_or_else(|| provider.default_endpoint()),
+            model: config.model.clone().unwrap_or_else(|| provider.default_model().to_string()),
+            timeout: config.timeout_ms.map_or(DEFAULT_TIMEOUT, Duration::from_millis),
+        }
+    }
+
+    /// Asks the model to order `candidates` for the examples in `spec`. Any failure to reach
+    /// it or make sense of its answer is an error, for the caller to fall back on.
+    pub fn hint(&self, spec: &str, candidates: &[String]) -> Result<Hint, String> {
+        let body = self.provider.request(&self.model, &prompt(spec, candidates));
+        parse_hint(&self.provider.answer(&self.post(&body.to_string())?)?, candidates.len())
+    }
+
+    fn post(&self, body: &str) -> Result<String, String> {
+        let curl = std::env::var_os("CURL").map(PathBuf::from).or_else(|| on_path("curl")).ok_or("it needs curl on the PATH")?;
+        // The body and key go to curl as a config on stdin, keeping the key out of the
+        // process list
+        let quote = |text: &str| format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+        let mut config = format!("data-binary = {}\n", quote(body));
+        if self.provider == Provider::OpenAi {
+            let key = std::env::var("OPENAI_API_KEY").map_err(|_| "OPENAI_API_KEY is not set")?;
+            config.push_str(&format!("header = {}\n", quote(&format!("Authorization: Bearer {}", key))));
+        }
+        let mut child = Command::new(&curl)
+            .args(["--silent", "--show-error", "--fail-with-body", "--max-time"])
+            .arg(format!("{:.3}", self.timeout.as_secs_f64()))
+            .args(["--header", "Content-Type: application/json", "--config", "-"])
+            .arg(&self.endpoint)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("failed to run {}: {}", curl.display(), e))?;
+        child.stdin.take().expect("stdin is piped").write_all(config.as_bytes()).map_err(|e| format!("failed to send the request: {}", e))?;
+        let output = child.wait_with_output().map_err(|e| format!("failed to run {}: {}", curl.display(), e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            return Err(format!("{} could not be reached: {}", self.endpoint, stderr.trim()));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_numbers_the_layouts() {
+        let prompt = prompt("{(width:390,height:844):{title:\"Hi\"}}\n", &["VStack {\n}".to_string(), "HStack {\n}".to_string()]);
+        assert!(prompt.contains("{(width:390,height:844):{title:\"Hi\"}}\n\nEach of the following 2 layouts"));
+        assert!(prompt.contains("Layout 2:\n```swift\nHStack {\n}\n```\n"));
+        assert!(prompt.ends_with("REASON: one sentence\n"));
+    }
+
+    #[test]
+    fn test_answers_are_read_per_provider() {
+        let openai = r#"{"choices":[{"message":{"role":"assistant","content":"ORDER: 2, 1"}}]}"#;
+        assert_eq!(Provider::OpenAi.answer(openai).unwrap(), "ORDER: 2, 1");
+        let ollama = r#"{"model":"llama3.1","message":{"role":"assistant","content":"ORDER: 1"},"done":true}"#;
+        assert_eq!(Provider::Ollama.answer(ollama).unwrap(), "ORDER: 1");
+        let error = r#"{"error":{"message":"Incorrect API key provided"}}"#;
+        assert!(Provider::OpenAi.answer(error).unwrap_err().contains("Incorrect API key provided"));
+        assert!(Provider::Ollama.answer("<html>").unwrap_err().starts_with("the reply is not JSON"));
+        assert_eq!(Provider::Ollama.request("m", "p")["stream"], false);
+    }
+
+    #[test]
+    fn test_hints_must_name_the_layouts_sent() {
+        let hint = parse_hint("**ORDER:** 3, 1\nREASON: The spacer pins the button to the bottom.", 3).unwrap();
+        assert_eq!(hint.order, [2, 0, 1]);
+        assert_eq!(hint.reason.as_deref(), Some("The spacer pins the button to the bottom."));
+        assert_eq!(parse_hint("order: [2]", 2).unwrap(), Hint { order: vec![1, 0], reason: None });
+        assert!(parse_hint("ORDER: 4, 1", 3).unwrap_err().contains("names layout 4, of 3"));
+        assert!(parse_hint("ORDER: 1, 1", 3).unwrap_err().contains("twice"));
+        assert!(parse_hint("I like the first one", 3).unwrap_err().contains("no ORDER line"));
+    }
+
+    #[test]
+    fn test_providers_have_defaults() {
+        assert_eq!("ollama".parse::<Provider>(), Ok(Provider::Ollama));
+        assert!("claude".parse::<Provider>().unwrap_err().contains("expected one of openai, ollama"));
+        let config = LlmSection { model: Some("gpt-4o".to_string()), ..LlmSection::default() };
+        let client = Client::new(Provider::OpenAi, &config);
+        assert_eq!(client.endpoint, "https://api.openai.com/v1/chat/completions");
+        assert_eq!((client.model.as_str(), client.timeout), ("gpt-4o", DEFAULT_TIMEOUT));
+    }
+}
@@ -0,0 +1,95 @@
+// File: src/synthesis/memo.rs
+// Subtree memoization for the enumerative search: the arrangements of a group of views
+// nested in a candidate are worked out once per constraint signature, then reused by every
+// candidate and every ordering of the views the same group turns up in.
+use std::collections::BTreeMap;
+use std::mem::size_of;
+
+use crate::ast::IR;
+use crate::synthesis::intern::{Interner, NodeId};
+
+/// Most views a group may have for its arrangements to be memoized. Larger groups have too
+/// many to keep; the search builds their arrangements one by one as candidates need them.
+pub const MAX_GROUP: usize = 8;
+
+/// What a group's arrangements depend on: its views in order, the axis of its stack and the
+/// nesting depth left. Not where the group sits on the screen: every arrangement is scored
+/// as part of a whole candidate, against every example, so none is dropped for its region.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Signature {
+    views: Box<[NodeId]>,
+    vertical: bool,
+    depth: usize,
+}
+
+/// Solved groups, by signature, with their arrangements kept in an [`Interner`].
+#[derive(Clone, Debug, Default)]
+pub struct Memo {
+    interner: Interner,
+    solved: BTreeMap<Signature, Vec<NodeId>>,
+    bytes: usize,
+}
+
+impl Memo {
+    pub fn new() -> Memo {
+        Memo::default()
+    }
+
+    /// The signature of stacking `views` along an axis, nested up to `depth` levels.
+    pub fn signature(&mut self, views: &[IR], vertical: bool, depth: usize) -> Signature {
+        Signature { views: views.iter().map(|view| self.interner.intern(view)).collect(), vertical, depth }
+    }
+
+    /// The arrangements solved for `signature`, in the order they were found.
+    pub fn get(&self, signature: &Signature) -> Option<Vec<IR>> {
+        let solved = self.solved.get(signature)?;
+        Some(solved.iter().map(|&id| self.interner.get(id)).collect())
+    }
+
+    pub fn insert(&mut self, signature: Signature, arrangements: &[IR]) {
+        let ids: Vec<NodeId> = arrangements.iter().map(|ir| self.interner.intern(ir)).collect();
+        self.bytes += size_of::<Signature>() + size_of::<Vec<NodeId>>() + (signature.views.len() + ids.len()) * size_of::<NodeId>();
+        self.solved.insert(signature, ids);
+    }
+
+    /// Number of groups solved.
+    pub fn len(&self) -> usize {
+        self.solved.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solved.is_empty()
+    }
+
+    /// Approximate bytes the memo holds, counted with the candidates seen for `--max-memory`.
+    pub fn bytes(&self) -> usize {
+        self.interner.bytes() + self.bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(label: &str) -> IR {
+        IR::Text(label.to_string())
+    }
+
+    #[test]
+    fn test_groups_are_solved_once_per_signature() {
+        let mut memo = Memo::new();
+        let views = [text("A"), text("B")];
+        let signature = memo.signature(&views, false, 2);
+        assert_eq!(memo.get(&signature), None);
+        let arrangements = vec![IR::HStack(views.to_vec()), IR::HStack(vec![IR::VStack(views.to_vec())])];
+        memo.insert(signature, &arrangements);
+        assert_eq!((memo.len(), memo.is_empty()), (1, false));
+        assert_eq!(memo.get(&memo.clone().signature(&views, false, 2)), Some(arrangements));
+        // Another axis, depth or order of the views is another sub-problem
+        for (views, vertical, depth) in [(vec![text("A"), text("B")], true, 2), (vec![text("A"), text("B")], false, 1), (vec![text("B"), text("A")], false, 2)] {
+            let signature = memo.signature(&views, vertical, depth);
+            assert_eq!(memo.get(&signature), None);
+        }
+        assert!(memo.bytes() > 0);
+    }
+}
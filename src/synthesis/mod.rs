@@ -2,3 +2,25 @@ pub mod swiftui;
 pub mod evaluate;
 pub mod rewrite;
 pub mod net;
+pub mod verify;
+pub mod search;
+pub mod cache;
+pub mod strategy;
+pub mod constraint;
+pub mod refine;
+pub mod flow;
+pub mod fidelity;
+pub mod lint;
+pub mod a11y;
+pub mod bench;
+pub mod beam;
+pub mod genetic;
+pub mod assign;
+pub mod ranker;
+#[cfg(feature = "llm")]
+pub mod llm;
+pub mod conflict;
+pub mod incremental;
+pub mod intern;
+pub mod memo;
+pub mod pool;
@@ -0,0 +1,173 @@
+// File: src/synthesis/pool.rs
+// A rayon thread pool that runs syntheses off the caller's thread, so a server can keep
+// answering while several searches run. Results come back through a `Pending` handle, which
+// can be waited on or awaited from any async runtime.
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use rayon::{ThreadPool, ThreadPoolBuilder};
+
+/// Worker threads taking jobs in the order they are spawned. Dropping the pool lets the
+/// workers finish the jobs queued before they stop.
+pub struct Pool {
+    workers: ThreadPool,
+}
+
+impl Pool {
+    /// A pool of `threads` workers (at least one).
+    pub fn new(threads: usize) -> Pool {
+        let workers = ThreadPoolBuilder::new()
+            .num_threads(threads.max(1))
+            .thread_name(|index| format!("swiftui-synth-worker-{}", index))
+            .build()
+            .expect("worker threads start");
+        Pool { workers }
+    }
+
+    /// Runs `job` on the first free worker. A job that panics does not take its worker down;
+    /// the panic is raised again where its result is waited for.
+    pub fn spawn<T, F>(&self, job: F) -> Pending<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let pending = Pending { shared: Arc::new((Mutex::new(Slot { result: None, waker: None }), Condvar::new())) };
+        let shared = Arc::clone(&pending.shared);
+        // Caught here, as rayon aborts on a panic that leaves a spawned job
+        self.workers.spawn(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(job));
+            let (slot, ready) = &*shared;
+            let mut slot = slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            slot.result = Some(result);
+            if let Some(waker) = slot.waker.take() {
+                waker.wake();
+            }
+            ready.notify_all();
+        });
+        pending
+    }
+
+    /// Number of worker threads.
+    pub fn threads(&self) -> usize {
+        self.workers.current_num_threads()
+    }
+}
+
+/// The pool [`crate::synthesis::strategy::synthesize_async`] runs on: one worker per core,
+/// started on first use.
+pub fn shared() -> &'static Pool {
+    static POOL: OnceLock<Pool> = OnceLock::new();
+    POOL.get_or_init(|| Pool::new(thread::available_parallelism().map_or(1, |threads| threads.get())))
+}
+
+struct Slot<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// The result of a job on a [`Pool`], once it is done: [`Pending::wait`] blocks for it, and
+/// awaiting the handle suspends the task until it is ready instead.
+pub struct Pending<T> {
+    shared: Arc<(Mutex<Slot<T>>, Condvar)>,
+}
+
+impl<T> Pending<T> {
+    /// Blocks until the job is done and returns its result.
+    pub fn wait(self) -> T {
+        let (slot, ready) = &*self.shared;
+        let mut slot = slot.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        loop {
+            if let Some(result) = slot.result.take() {
+                return result.unwrap_or_else(|panic| panic::resume_unwind(panic));
+            }
+            slot = ready.wait(slot).unwrap_or_else(|poisoned| poisoned.into_inner());
+        }
+    }
+
+    /// Whether the job is done, so [`Pending::wait`] returns at once.
+    pub fn is_done(&self) -> bool {
+        self.shared.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).result.is_some()
+    }
+}
+
+impl<T> Future for Pending<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<T> {
+        let mut slot = self.shared.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        match slot.result.take() {
+            Some(result) => Poll::Ready(result.unwrap_or_else(|panic| panic::resume_unwind(panic))),
+            None => {
+                slot.waker = Some(context.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::mpsc;
+    use std::task::Wake;
+
+    #[test]
+    fn test_jobs_run_concurrently() {
+        let pool = Pool::new(2);
+        assert_eq!(pool.threads(), 2);
+        // Each job waits for the other to start, which only two workers can do
+        let started = Arc::new(AtomicUsize::new(0));
+        let jobs: Vec<Pending<usize>> = (0..2)
+            .map(|index| {
+                let started = Arc::clone(&started);
+                pool.spawn(move || {
+                    started.fetch_add(1, Ordering::SeqCst);
+                    while started.load(Ordering::SeqCst) < 2 {
+                        thread::yield_now();
+                    }
+                    index * 10
+                })
+            })
+            .collect();
+        assert_eq!(jobs.into_iter().map(Pending::wait).collect::<Vec<_>>(), [0, 10]);
+    }
+
+    #[test]
+    fn test_panics_reach_the_waiter_and_spare_the_worker() {
+        let pool = Pool::new(1);
+        let failed = pool.spawn(|| -> usize { panic!("no layout") });
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| failed.wait())).is_err());
+        assert_eq!(pool.spawn(|| 7).wait(), 7);
+    }
+
+    #[test]
+    fn test_awaiting_wakes_the_task() {
+        struct Flag(AtomicUsize);
+        impl Wake for Flag {
+            fn wake(self: Arc<Self>) {
+                self.0.fetch_add(1, Ordering::SeqCst);
+            }
+        }
+        let pool = Pool::new(1);
+        let (gate, opened) = mpsc::channel::<()>();
+        let mut pending = pool.spawn(move || {
+            opened.recv().unwrap();
+            "done"
+        });
+        let flag = Arc::new(Flag(AtomicUsize::new(0)));
+        let waker = Waker::from(Arc::clone(&flag));
+        let mut context = Context::from_waker(&waker);
+        assert_eq!(Pin::new(&mut pending).poll(&mut context), Poll::Pending);
+        gate.send(()).unwrap();
+        while flag.0.load(Ordering::SeqCst) == 0 {
+            thread::yield_now();
+        }
+        assert!(pending.is_done());
+        assert_eq!(Pin::new(&mut pending).poll(&mut context), Poll::Ready("done"));
+    }
+}
@@ -0,0 +1,231 @@
+// File: src/synthesis/ranker.rs
+// Learned ordering of the `--top-n` layouts: a logistic regression over features of each
+// layout, trained (`train-ranker`) on a log of the layouts people picked and passed over.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::ast::{Example, IR};
+use crate::layout::Metrics;
+use crate::synthesis::evaluate;
+
+/// Names of the features the model weighs, in order.
+pub const FEATURES: &[&str] = &["nodes", "depth", "modifiers", "deviation", "spacers", "hstacks", "vstacks"];
+
+// Learning rate, passes and L2 penalty of the gradient descent in `Ranker::train`
+const RATE: f64 = 0.5;
+const EPOCHS: usize = 500;
+const PENALTY: f64 = 0.01;
+
+fn count(ir: &IR, matches: &impl Fn(&IR) -> bool) -> usize {
+    let nested = match ir {
+        IR::VStack(children) | IR::HStack(children) => children.iter().map(|child| count(child, matches)).sum(),
+        _ => 0,
+    };
+    usize::from(matches(ir)) + nested
+}
+
+/// The features of `ir` for `examples`, by name. Deviation is per example, so specs with
+/// more examples are not judged further off.
+pub fn features(ir: &IR, examples: &[Example], metrics: &Metrics) -> BTreeMap<String, f64> {
+    let cost = evaluate::cost(ir, examples, metrics);
+    let values = [
+        cost.nodes as f64,
+        cost.depth as f64,
+        cost.modifiers as f64,
+        cost.deviation / examples.len().max(1) as f64,
+        count(ir, &|node| *node == IR::Spacer) as f64,
+        count(ir, &|node| matches!(node, IR::HStack(_))) as f64,
+        count(ir, &|node| matches!(node, IR::VStack(_))) as f64,
+    ];
+    FEATURES.iter().map(|name| name.to_string()).zip(values).collect()
+}
+
+/// One line of a feedback log: a layout offered to someone and whether they picked it.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Feedback {
+    pub accepted: bool,
+    /// The layout's [`features`]; ones missing from older logs count as 0.
+    pub features: BTreeMap<String, f64>,
+    /// The layout itself, for reference; training reads only the features.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<IR>,
+}
+
+/// Feedback lines for a pick among `layouts`: the one at `picked` accepted, the rest not.
+pub fn feedback(layouts: &[&IR], picked: usize, examples: &[Example], metrics: &Metrics) -> String {
+    layouts
+        .iter()
+        .enumerate()
+        .map(|(i, ir)| {
+            let line = Feedback { accepted: i == picked, features: features(ir, examples, metrics), layout: Some((*ir).clone()) };
+            serde_json::to_string(&line).expect("feedback serializes") + "\n"
+        })
+        .collect()
+}
+
+/// Parses a feedback log, one JSON object per line; blank lines are skipped.
+pub fn parse_log(log: &str) -> Result<Vec<Feedback>, String> {
+    log.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| serde_json::from_str(line).map_err(|e| format!("line {}: {}", i + 1, e)))
+        .collect()
+}
+
+/// A logistic regression model: the probability a layout is picked is the logistic function
+/// of `bias` plus the weighted sum of its standardized features.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Ranker {
+    pub features: Vec<String>,
+    pub mean: Vec<f64>,
+    pub scale: Vec<f64>,
+    pub weights: Vec<f64>,
+    pub bias: f64,
+}
+
+fn logistic(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+impl Ranker {
+    /// Fits the model to `log` by batch gradient descent on the L2-penalized log loss, from
+    /// zero weights, so the same log always gives the same model.
+    pub fn train(log: &[Feedback]) -> Result<Ranker, String> {
+        if !log.iter().any(|line| line.accepted) || log.iter().all(|line| line.accepted) {
+            return Err("The feedback log needs both accepted and rejected layouts to learn from".to_string());
+        }
+        let rows: Vec<Vec<f64>> =
+            log.iter().map(|line| FEATURES.iter().map(|name| line.features.get(*name).copied().unwrap_or(0.0)).collect()).collect();
+        let n = rows.len() as f64;
+        let mean: Vec<f64> = (0..FEATURES.len()).map(|j| rows.iter().map(|row| row[j]).sum::<f64>() / n).collect();
+        let scale: Vec<f64> = (0..FEATURES.len())
+            .map(|j| {
+                let spread = (rows.iter().map(|row| (row[j] - mean[j]).powi(2)).sum::<f64>() / n).sqrt();
+                if spread > 1e-9 { spread } else { 1.0 }
+            })
+            .collect();
+        let mut ranker = Ranker {
+            features: FEATURES.iter().map(|name| name.to_string()).collect(),
+            mean,
+            scale,
+            weights: vec![0.0; FEATURES.len()],
+            bias: 0.0,
+        };
+        for _ in 0..EPOCHS {
+            let mut gradient = vec![0.0; FEATURES.len()];
+            let mut bias_gradient = 0.0;
+            for (row, line) in rows.iter().zip(log) {
+                let error = ranker.probability(row) - f64::from(u8::from(line.accepted));
+                for (j, value) in ranker.standardize(row).into_iter().enumerate() {
+                    gradient[j] += error * value / n;
+                }
+                bias_gradient += error / n;
+            }
+            for (weight, gradient) in ranker.weights.iter_mut().zip(gradient) {
+                *weight -= RATE * (gradient + PENALTY * *weight);
+            }
+            ranker.bias -= RATE * bias_gradient;
+        }
+        Ok(ranker)
+    }
+
+    fn standardize(&self, row: &[f64]) -> Vec<f64> {
+        row.iter().zip(self.mean.iter().zip(&self.scale)).map(|(value, (mean, scale))| (value - mean) / scale).collect()
+    }
+
+    fn probability(&self, row: &[f64]) -> f64 {
+        logistic(self.bias + self.standardize(row).iter().zip(&self.weights).map(|(value, weight)| value * weight).sum::<f64>())
+    }
+
+    /// Reads a model written by `train-ranker`.
+    pub fn parse(json: &str) -> Result<Ranker, String> {
+        let ranker: Ranker = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        let n = ranker.features.len();
+        if ranker.mean.len() != n || ranker.scale.len() != n || ranker.weights.len() != n {
+            return Err(format!("expected {} means, scales and weights, one per feature", n));
+        }
+        if let Some(unknown) = ranker.features.iter().find(|name| !FEATURES.contains(&name.as_str())) {
+            return Err(format!("unknown feature '{}'; expected {}", unknown, FEATURES.join(", ")));
+        }
+        Ok(ranker)
+    }
+
+    /// The probability the model gives of `ir` being picked.
+    pub fn score(&self, ir: &IR, examples: &[Example], metrics: &Metrics) -> f64 {
+        let features = features(ir, examples, metrics);
+        let row: Vec<f64> = self.features.iter().map(|name| features[name]).collect();
+        self.probability(&row)
+    }
+
+    /// The fraction of `log` the model classifies correctly (picked when over 1/2).
+    pub fn accuracy(&self, log: &[Feedback]) -> f64 {
+        let correct = log
+            .iter()
+            .filter(|line| {
+                let row: Vec<f64> = self.features.iter().map(|name| line.features.get(name).copied().unwrap_or(0.0)).collect();
+                (self.probability(&row) > 0.5) == line.accepted
+            })
+            .count();
+        correct as f64 / log.len().max(1) as f64
+    }
+
+    /// Orders `layouts` from the most to the least likely to be picked; ties keep their order.
+    pub fn rank(&self, layouts: &mut [IR], examples: &[Example], metrics: &Metrics) {
+        let mut scored: Vec<(f64, IR)> = layouts.iter().map(|ir| (self.score(ir, examples, metrics), ir.clone())).collect();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        for (slot, (_, ir)) in layouts.iter_mut().zip(scored) {
+            *slot = ir;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    fn text(s: &str) -> IR {
+        IR::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_features_count_the_layout() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"A\",title:\"B\"}}").unwrap();
+        let ir = IR::VStack(vec![IR::HStack(vec![text("A"), IR::Spacer]), text("B")]);
+        let features = features(&ir, &examples, &Metrics::default());
+        assert_eq!(features["spacers"], 1.0);
+        assert_eq!(features["hstacks"], 1.0);
+        assert_eq!(features["vstacks"], 1.0);
+        assert_eq!(features["depth"], 2.0);
+        assert_eq!(features.len(), FEATURES.len());
+    }
+
+    #[test]
+    fn test_training_learns_a_preference() {
+        // Whoever picked these always took the layout without spacers
+        let examples = parse_examples("{(width:390,height:844):{title:\"A\",title:\"B\"}}").unwrap();
+        let plain = IR::VStack(vec![text("A"), text("B")]);
+        let spaced = IR::VStack(vec![text("A"), IR::Spacer, text("B")]);
+        let metrics = Metrics::default();
+        let log = parse_log(&feedback(&[&spaced, &plain], 1, &examples, &metrics).repeat(3)).unwrap();
+        assert_eq!(log.len(), 6);
+        let ranker = Ranker::train(&log).unwrap();
+        assert_eq!(ranker.accuracy(&log), 1.0);
+        assert!(ranker.score(&plain, &examples, &metrics) > ranker.score(&spaced, &examples, &metrics));
+
+        let mut layouts = vec![spaced.clone(), plain.clone()];
+        ranker.rank(&mut layouts, &examples, &metrics);
+        assert_eq!(layouts, [plain, spaced]);
+        assert_eq!(Ranker::parse(&serde_json::to_string(&ranker).unwrap()).unwrap(), ranker);
+    }
+
+    #[test]
+    fn test_invalid_logs_and_models_are_rejected() {
+        assert!(parse_log("{\"accepted\":true}").unwrap_err().starts_with("line 1:"));
+        let log = parse_log("{\"accepted\":true,\"features\":{\"nodes\":3}}\n\n").unwrap();
+        assert!(Ranker::train(&log).unwrap_err().contains("both accepted and rejected"));
+        let model = "{\"features\":[\"colour\"],\"mean\":[0],\"scale\":[1],\"weights\":[1],\"bias\":0}";
+        assert!(Ranker::parse(model).unwrap_err().contains("unknown feature 'colour'"));
+    }
+}
@@ -0,0 +1,507 @@
+// File: src/synthesis/refine.rs
+// Feedback on a synthesized layout ("button should be in the top bar"), hints and constraint
+// blocks given with the examples, kept as constraints on the examples so synthesis has to honor them.
+use std::fmt;
+use std::str::FromStr;
+
+use crate::ast::{Example, Value, IR};
+use crate::diagnostics;
+use crate::layout::{Layout, LayoutDirection};
+use crate::synthesis::verify::describe;
+
+/// Where a view should end up relative to the rest of the layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placement {
+    /// Nothing starts above it.
+    Top,
+    /// Nothing ends below it.
+    Bottom,
+    /// Nothing starts before it horizontally: to its left, or right to left to its right.
+    Leading,
+    /// Nothing ends after it horizontally.
+    Trailing,
+    /// Horizontally centered on the screen.
+    Center,
+}
+
+/// Placement words accepted in feedback, with the placement each means.
+const PLACEMENTS: &[(&str, Placement)] = &[
+    ("top", Placement::Top),
+    ("bottom", Placement::Bottom),
+    ("leading", Placement::Leading),
+    ("left", Placement::Leading),
+    ("trailing", Placement::Trailing),
+    ("right", Placement::Trailing),
+    ("center", Placement::Center),
+    ("centered", Placement::Center),
+    ("middle", Placement::Center),
+];
+
+// Words that carry no meaning in feedback such as "button should be in the top bar".
+const FILLER: &[&str] = &["should", "be", "is", "in", "at", "on", "to", "the", "of", "bar", "edge", "screen", "pinned", "aligned"];
+
+impl fmt::Display for Placement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let word = PLACEMENTS.iter().find(|(_, p)| p == self).map_or("", |(word, _)| word);
+        f.write_str(word)
+    }
+}
+
+/// The view a constraint is about: an element key such as `button`, or a custom view
+/// name, optionally narrowed to one label, e.g. `button "Go"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Target {
+    pub key: String,
+    pub label: Option<String>,
+}
+
+impl Target {
+    pub fn matches(&self, node: &IR) -> bool {
+        let label = match (self.key.as_str(), node) {
+            ("title", IR::Text(s)) | ("button", IR::Button(s)) | ("Image", IR::Image(s)) => s,
+            ("Custom", IR::Custom { args, .. }) => args,
+            (key, IR::Custom { name, args }) if key == name => args,
+            _ => return false,
+        };
+        self.label.as_ref().is_none_or(|wanted| wanted == label || label.contains(&format!("\"{}\"", wanted)))
+    }
+
+    /// Whether the example element `key:value` may be the view this names. A wildcard
+    /// (`title:*`) may be any label.
+    pub fn names_element(&self, key: &str, value: &Value) -> bool {
+        let node = match (key, value) {
+            (_, Value::Any) => return self.key == key,
+            ("title", Value::String(s)) => IR::Text(s.clone()),
+            ("button", Value::String(s)) => IR::Button(s.clone()),
+            ("Image", Value::String(s)) => IR::Image(s.clone()),
+            ("Custom", Value::String(s)) => match IR::custom(s) {
+                Ok(node) => node,
+                Err(_) => return false,
+            },
+            _ => return false,
+        };
+        self.matches(&node)
+    }
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.label {
+            Some(label) => write!(f, "{} \"{}\"", self.key, label),
+            None => f.write_str(&self.key),
+        }
+    }
+}
+
+/// How one view should sit relative to another.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relation {
+    /// Ends before the other view starts, vertically.
+    Above,
+    Below,
+    /// Comes earlier in reading order: above the other view, or beside it on its leading side.
+    Before,
+    After,
+    /// Ends before the other view starts, horizontally, at any height.
+    LeftOf,
+    RightOf,
+}
+
+/// Order words accepted in feedback, with the relation each means.
+const RELATIONS: &[(&str, Relation)] = &[
+    ("above", Relation::Above),
+    ("over", Relation::Above),
+    ("below", Relation::Below),
+    ("under", Relation::Below),
+    ("before", Relation::Before),
+    ("after", Relation::After),
+    ("leftof", Relation::LeftOf),
+    ("left", Relation::LeftOf),
+    ("rightof", Relation::RightOf),
+    ("right", Relation::RightOf),
+];
+
+impl fmt::Display for Relation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            // "left" alone is a placement; "of" is dropped as filler when parsing
+            Relation::LeftOf => f.write_str("left of"),
+            Relation::RightOf => f.write_str("right of"),
+            _ => {
+                let word = RELATIONS.iter().find(|(_, r)| r == self).map_or("", |(word, _)| word);
+                f.write_str(word)
+            }
+        }
+    }
+}
+
+/// Where the user wants a view, e.g. `button top` or `image above title`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Constraint {
+    /// A view placed relative to the whole layout.
+    Place { target: Target, placement: Placement },
+    /// A view placed relative to another.
+    Relate { target: Target, relation: Relation, other: Target },
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Constraint::Place { target, placement } => write!(f, "{} {}", target, placement),
+            Constraint::Relate { target, relation, other } => write!(f, "{} {} {}", target, relation, other),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+}
+
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text.trim_start();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').ok_or_else(|| format!("Unterminated label in '{}'", text.trim()))?;
+            tokens.push(Token::Quoted(quoted[..end].to_string()));
+            rest = &quoted[end + 1..];
+        } else {
+            let end = rest.find(|c: char| c.is_whitespace() || c == '"').unwrap_or(rest.len());
+            tokens.push(Token::Word(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+    Ok(tokens)
+}
+
+// Reads an element key, normalizing the built-in ones, and the label that may follow it.
+fn take_target(tokens: &mut std::iter::Peekable<impl Iterator<Item = Token>>) -> Option<Target> {
+    let Some(Token::Word(key)) = tokens.next() else { return None };
+    let key = match key.to_lowercase().as_str() {
+        "title" | "text" => "title".to_string(),
+        "button" => "button".to_string(),
+        "image" => "Image".to_string(),
+        "custom" => "Custom".to_string(),
+        _ => key,
+    };
+    let label = match tokens.peek() {
+        Some(Token::Quoted(label)) => {
+            let label = label.clone();
+            tokens.next();
+            Some(label)
+        }
+        _ => None,
+    };
+    Some(Target { key, label })
+}
+
+impl FromStr for Target {
+    type Err = String;
+
+    /// Parses an element reference such as `button` or `button "Go"`.
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut tokens = tokenize(text)?.into_iter().peekable();
+        match (take_target(&mut tokens), tokens.next()) {
+            (Some(target), None) => Ok(target),
+            _ => Err(format!("Expected an element such as `button` or `button \"Go\"`, found '{}'", text.trim())),
+        }
+    }
+}
+
+impl FromStr for Constraint {
+    type Err = String;
+
+    /// Parses feedback such as `button should be in the top bar`, `title "Hi" centered` or
+    /// `image above title`: an element key or custom view name with an optional quoted label,
+    /// then a placement word, or an order word and another element.
+    fn from_str(text: &str) -> Result<Constraint, String> {
+        let text = text.trim();
+        let mut tokens = tokenize(text)?.into_iter().peekable();
+        let Some(target) = take_target(&mut tokens) else {
+            return Err("Feedback is empty: name an element and where it should be, e.g. `button top`".to_string());
+        };
+
+        let words: Vec<Token> = tokens
+            .filter_map(|token| match token {
+                Token::Word(word) => {
+                    let word = word.trim_matches(|c: char| !c.is_alphanumeric()).to_string();
+                    let lower = word.to_lowercase();
+                    (!word.is_empty() && !FILLER.contains(&lower.as_str())).then_some(Token::Word(word))
+                }
+                quoted => Some(quoted),
+            })
+            .collect();
+        let first = match words.first() {
+            Some(Token::Word(word)) => word.to_lowercase(),
+            _ => String::new(),
+        };
+        // `left`/`right` place a view on their own, and relate it to the element that follows
+        let relation = RELATIONS.iter().find(|(name, _)| *name == first);
+        let placement_word = PLACEMENTS.iter().any(|(name, _)| *name == first);
+        if let Some((_, relation)) = relation.filter(|_| words.len() > 1 || !placement_word) {
+            let mut rest = words[1..].iter().cloned().peekable();
+            return match (take_target(&mut rest), rest.next()) {
+                (Some(other), None) => Ok(Constraint::Relate { target, relation: *relation, other }),
+                _ => Err(format!("Expected one element after '{}' in '{}', e.g. `{} {} title`", first, text, target.key, first)),
+            };
+        }
+        let placement = match words.as_slice() {
+            [_] => PLACEMENTS.iter().find(|(name, _)| *name == first).map(|(_, p)| *p),
+            _ => None,
+        };
+        let Some(placement) = placement else {
+            let mut message = format!(
+                "Could not understand where '{}' should be in '{}': expected top, bottom, leading, trailing or center, \
+                 or above, below, before, after, left of or right of another element",
+                target.key, text
+            );
+            let names = PLACEMENTS.iter().map(|(name, _)| *name).chain(RELATIONS.iter().map(|(name, _)| *name));
+            if let Some(help) = diagnostics::suggestion_text(&diagnostics::did_you_mean(&first, names)) {
+                message = format!("{} ({})", message, help);
+            }
+            return Err(message);
+        };
+        Ok(Constraint::Place { target, placement })
+    }
+}
+
+impl Constraint {
+    /// The views the constraint is about.
+    pub fn targets(&self) -> Vec<&Target> {
+        match self {
+            Constraint::Place { target, .. } => vec![target],
+            Constraint::Relate { target, other, .. } => vec![target, other],
+        }
+    }
+
+    /// Why `layout` breaks this constraint, or `None` if it holds. Views missing from the
+    /// layout are left for [`verify`](crate::synthesis::verify::verify) to report.
+    pub fn check(&self, layout: &Layout) -> Option<String> {
+        let leaves: Vec<_> = layout.leaves().collect();
+        let (target, placement) = match self {
+            Constraint::Place { target, placement } => (target, placement),
+            Constraint::Relate { target, relation, other } => {
+                let a = leaves.iter().find(|frame| target.matches(&frame.node))?;
+                let b = leaves.iter().find(|frame| frame.path != a.path && other.matches(&frame.node))?;
+                let (a, b, name) = (a.rect, b.rect, describe(&a.node));
+                // Half a point of slack absorbs rounding in the layout model
+                let above = a.max_y() <= b.y + 0.5;
+                let below = a.y >= b.max_y() - 0.5;
+                let same_row = a.y < b.max_y() && b.y < a.max_y();
+                let (left_of, right_of) = (a.max_x() <= b.x + 0.5, a.x >= b.max_x() - 0.5);
+                // Reading order follows the layout direction; left and right do not
+                let (leading_of, trailing_of) = match layout.direction {
+                    LayoutDirection::LeftToRight => (left_of, right_of),
+                    LayoutDirection::RightToLeft => (right_of, left_of),
+                };
+                let holds = match relation {
+                    Relation::Above => above,
+                    Relation::Below => below,
+                    Relation::Before => above || (same_row && leading_of),
+                    Relation::After => below || (same_row && trailing_of),
+                    Relation::LeftOf => left_of,
+                    Relation::RightOf => right_of,
+                };
+                let other = leaves.iter().find(|frame| other.matches(&frame.node)).map(|frame| describe(&frame.node));
+                return (!holds).then(|| format!("{} should be {} {}", name, relation, other.unwrap_or_default()));
+            }
+        };
+        let target = leaves.iter().find(|frame| target.matches(&frame.node))?;
+        let rect = target.rect;
+        let others = leaves.iter().filter(|frame| frame.path != target.path);
+        // The leading edge is the left one, or right to left the right one
+        let leading = *placement == Placement::Leading;
+        let left_edge = leading == (layout.direction == LayoutDirection::LeftToRight);
+        let offender = match placement {
+            Placement::Top => others.filter(|f| f.rect.y < rect.y - 0.5).min_by(|a, b| a.rect.y.total_cmp(&b.rect.y)),
+            Placement::Bottom => others.filter(|f| f.rect.max_y() > rect.max_y() + 0.5).max_by(|a, b| a.rect.max_y().total_cmp(&b.rect.max_y())),
+            Placement::Leading | Placement::Trailing if left_edge => others.filter(|f| f.rect.x < rect.x - 0.5).min_by(|a, b| a.rect.x.total_cmp(&b.rect.x)),
+            Placement::Leading | Placement::Trailing => others.filter(|f| f.rect.max_x() > rect.max_x() + 0.5).max_by(|a, b| a.rect.max_x().total_cmp(&b.rect.max_x())),
+            Placement::Center => {
+                let bounds = layout.bounds();
+                let offset = rect.x + rect.width / 2.0 - (bounds.x + bounds.width / 2.0);
+                return (offset.abs() > 0.5).then(|| {
+                    format!("{} should be centered but is {:.0}pt off center", describe(&target.node), offset.abs())
+                });
+            }
+        };
+        let relation = match placement {
+            Placement::Top => "above",
+            Placement::Bottom => "below",
+            Placement::Leading => "before",
+            _ => "after",
+        };
+        offender.map(|other| {
+            format!("{} should be at the {} but {} is {} it", describe(&target.node), placement, describe(&other.node), relation)
+        })
+    }
+}
+
+/// Parses a `--reject` file: one piece of feedback per line, `#` starting a comment.
+pub fn parse_constraints(contents: &str) -> Result<Vec<Constraint>, String> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.split('#').next().unwrap_or("").trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(index, line)| line.parse().map_err(|e| format!("line {}: {}", index + 1, e)))
+        .collect()
+}
+
+// Splits a hint into phrases at commas, semicolons and the word "and", outside quoted labels.
+fn hint_phrases(text: &str) -> Vec<&str> {
+    let mut phrases = Vec::new();
+    let (mut start, mut in_quotes) = (0, false);
+    for (i, ch) in text.char_indices() {
+        match ch {
+            '"' => in_quotes = !in_quotes,
+            ',' | ';' if !in_quotes => {
+                phrases.push(&text[start..i]);
+                start = i + 1;
+            }
+            ' ' if !in_quotes && text[i..].to_lowercase().starts_with(" and ") => {
+                phrases.push(&text[start..i]);
+                start = i + " and ".len();
+            }
+            _ => {}
+        }
+    }
+    phrases.push(&text[start..]);
+    phrases.into_iter().map(str::trim).filter(|phrase| !phrase.is_empty()).collect()
+}
+
+/// Parses a hint such as `title centered, button pinned to bottom and image above title`:
+/// phrases in the same words as feedback, separated by commas, semicolons or "and".
+/// A phrase that cannot be understood is returned alongside the error.
+pub fn parse_hint(text: &str) -> Result<Vec<Constraint>, (String, &str)> {
+    hint_phrases(text).into_iter().map(|phrase| phrase.parse().map_err(|e| (e, phrase))).collect()
+}
+
+/// Constraints on an example: feedback attached by [`constrain`], then its `hint` elements.
+pub fn constraints(example: &Example) -> Vec<Constraint> {
+    let mut found = Vec::new();
+    if let Value::Dict(dims) = &example.0 {
+        if let Some((_, Value::Dict(stated))) = dims.iter().find(|(k, _)| k == "constraints") {
+            found.extend(stated.iter().filter_map(|(_, v)| match v {
+                Value::String(s) => s.parse().ok(),
+                _ => None,
+            }));
+        }
+    }
+    if let Value::Dict(elements) = &example.1 {
+        for (_, hint) in elements.iter().filter(|(k, _)| k == "hint") {
+            if let Value::String(hint) = hint {
+                found.extend(parse_hint(hint).unwrap_or_default());
+            }
+        }
+    }
+    found
+}
+
+/// Adds `constraint` to every example, so synthesis and verification must honor it.
+/// Constraints already present are not added twice.
+pub fn constrain(examples: &mut [Example], constraint: &Constraint) {
+    for example in examples {
+        if constraints(example).contains(constraint) {
+            continue;
+        }
+        let Value::Dict(dims) = &mut example.0 else { continue };
+        if !dims.iter().any(|(k, _)| k == "constraints") {
+            dims.push(("constraints".to_string(), Value::Dict(Vec::new())));
+        }
+        if let Some((_, Value::Dict(stated))) = dims.iter_mut().find(|(k, _)| k == "constraints") {
+            stated.push((stated.len().to_string(), Value::String(constraint.to_string())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+    use crate::layout::{simulate, Metrics, Size};
+
+    #[test]
+    fn test_parse_feedback() {
+        let constraint: Constraint = "button should be in the top bar".parse().unwrap();
+        assert_eq!(constraint.to_string(), "button top");
+        let constraint: Constraint = "Title \"Hi, you\" centered".parse().unwrap();
+        let target = Target { key: "title".to_string(), label: Some("Hi, you".to_string()) };
+        assert_eq!(constraint, Constraint::Place { target, placement: Placement::Center });
+        assert_eq!(constraint.to_string().parse::<Constraint>().unwrap(), constraint);
+
+        assert!("button sideways".parse::<Constraint>().unwrap_err().contains("expected top, bottom"));
+        assert!("button bottm".parse::<Constraint>().unwrap_err().contains("did you mean `bottom`?"));
+        let err = parse_constraints("# feedback\nimage left\n\nbutton up\n").unwrap_err();
+        assert!(err.starts_with("line 4: "));
+    }
+
+    #[test]
+    fn test_check_placements() {
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::Spacer, IR::Button("Go".to_string())]);
+        let layout = simulate(&ir, Size::new(390.0, 844.0), &Metrics::default());
+        let check = |text: &str| text.parse::<Constraint>().unwrap().check(&layout);
+        assert_eq!(check("button top").unwrap(), "Button \"Go\" should be at the top but Text \"Hi\" is above it");
+        assert_eq!(check("button bottom"), None);
+        assert_eq!(check("title center"), None);
+        assert_eq!(check("image top"), None);
+    }
+
+    #[test]
+    fn test_parse_relations_and_hints() {
+        let constraint: Constraint = "image should be above the title".parse().unwrap();
+        assert_eq!(constraint.to_string(), "Image above title");
+        let constraint: Constraint = "button \"Go\" after title \"Hi\"".parse().unwrap();
+        assert_eq!(constraint.to_string().parse::<Constraint>().unwrap(), constraint);
+        assert!("image above".parse::<Constraint>().unwrap_err().starts_with("Expected one element after 'above'"));
+
+        let hint = parse_hint("title centered, button pinned to bottom and image above title").unwrap();
+        let phrases: Vec<String> = hint.iter().map(|c| c.to_string()).collect();
+        assert_eq!(phrases, vec!["title center", "button bottom", "Image above title"]);
+        assert_eq!(parse_hint("title \"Tom and Jerry\" top").unwrap().len(), 1);
+        let (err, phrase) = parse_hint("title centered; button wobbly").unwrap_err();
+        assert_eq!(phrase, "button wobbly");
+        assert!(err.starts_with("Could not understand where 'button' should be"));
+    }
+
+    #[test]
+    fn test_check_relations() {
+        let ir = IR::VStack(vec![IR::Text("Hi".to_string()), IR::HStack(vec![IR::Image("a".to_string()), IR::Button("Go".to_string())])]);
+        let layout = simulate(&ir, Size::new(390.0, 844.0), &Metrics::default());
+        let check = |text: &str| text.parse::<Constraint>().unwrap().check(&layout);
+        assert_eq!(check("title above button"), None);
+        assert_eq!(check("image before button"), None);
+        assert_eq!(check("button before image").unwrap(), "Button \"Go\" should be before Image \"a\"");
+        assert_eq!(check("image below title"), None);
+        assert!(check("image above button").is_some());
+        assert_eq!(check("image leftOf button"), None);
+        assert_eq!(check("button right of image"), None);
+        assert_eq!(check("title left of button").unwrap(), "Text \"Hi\" should be left of Button \"Go\"");
+        assert_eq!("image leftOf title".parse::<Constraint>().unwrap().to_string(), "Image left of title");
+        assert_eq!("button right".parse::<Constraint>().unwrap().to_string(), "button trailing");
+    }
+
+    #[test]
+    fn test_right_to_left_swaps_reading_order_only() {
+        let ir = IR::HStack(vec![IR::Image("a".to_string()), IR::Button("Go".to_string())]);
+        let layout = simulate(&ir, Size::new(390.0, 844.0), &Metrics::default()).with_direction(LayoutDirection::RightToLeft);
+        let check = |text: &str| text.parse::<Constraint>().unwrap().check(&layout);
+        assert_eq!(check("image before button"), None);
+        assert_eq!(check("image leading"), None);
+        assert_eq!(check("button trailing"), None);
+        assert_eq!(check("image rightOf button"), None);
+        assert_eq!(check("image leftOf button").unwrap(), "Image \"a\" should be left of Button \"Go\"");
+    }
+
+    #[test]
+    fn test_constrain_examples() {
+        let mut examples = parse_examples("{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}").unwrap();
+        let constraint: Constraint = "button top".parse().unwrap();
+        constrain(&mut examples, &constraint);
+        constrain(&mut examples, &constraint);
+        assert_eq!(constraints(&examples[0]), vec![constraint]);
+    }
+}
@@ -0,0 +1,853 @@
+// File: src/synthesis/search.rs
+// Enumerative search over stack arrangements, scored against every example and bounded by a budget.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::ControlFlow;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::ast::{Example, IR};
+use crate::layout::{self, Metrics};
+use crate::synthesis::evaluate::{self, CostWeights};
+use crate::synthesis::intern::LayoutSet;
+use crate::synthesis::memo::{self, Memo};
+use crate::synthesis::swiftui::synthesize_traced;
+use crate::synthesis::verify::{describe, expected_frames, expected_leaves, verify};
+
+/// Limits on how much work a search may do.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Budget {
+    /// Wall-clock limit; `None` searches until the candidates run out.
+    pub timeout: Option<Duration>,
+    /// Maximum stack nesting depth of a candidate (the root stack counts as one).
+    pub max_depth: usize,
+    /// Maximum number of candidates to score.
+    pub max_candidates: usize,
+    /// Shuffles the order in which arrangements are explored. The same seed always explores
+    /// the same candidates in the same order; `None` uses the natural order.
+    pub seed: Option<u64>,
+    /// Number of satisfying layouts to find and rank; see [`Outcome::alternatives`].
+    pub top_n: usize,
+    /// Partial layouts the beam search keeps at each step; see [`crate::synthesis::beam`].
+    pub beam_width: usize,
+    /// Bytes the search's record of the candidates it scored may take; `None` is unlimited.
+    /// Searches that keep no such record, whose memory does not grow, ignore it.
+    pub max_memory: Option<usize>,
+    /// Stops the search early, keeping the best layout scored so far.
+    pub cancel: CancelToken,
+}
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget {
+            timeout: Some(Duration::from_secs(5)),
+            max_depth: 3,
+            max_candidates: 10_000,
+            seed: None,
+            top_n: 1,
+            beam_width: 32,
+            max_memory: None,
+            cancel: CancelToken::default(),
+        }
+    }
+}
+
+impl Budget {
+    /// The limit a search started at `started` has reached after the work in `stats`, if any.
+    /// Searches check it before scoring another candidate.
+    pub fn reached(&self, started: Instant, stats: &SearchStats) -> Option<Exhausted> {
+        if self.cancel.is_cancelled() {
+            Some(Exhausted::Cancelled)
+        } else if stats.scored >= self.max_candidates {
+            Some(Exhausted::Candidates)
+        } else if self.max_memory.is_some_and(|limit| stats.memory_bytes >= limit) {
+            Some(Exhausted::Memory)
+        } else if self.timeout.is_some_and(|limit| started.elapsed() >= limit) {
+            Some(Exhausted::Timeout)
+        } else {
+            None
+        }
+    }
+}
+
+/// Cancels searches from another thread or a signal handler, such as Ctrl-C's; clones share
+/// the flag. A search that is cancelled still returns the best layout it scored.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// The flag behind the token, for a signal handler to set.
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.0)
+    }
+}
+
+// Budgets compare by their limits; tokens only by whether they are cancelled
+impl PartialEq for CancelToken {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_cancelled() == other.is_cancelled()
+    }
+}
+
+/// Which limit stopped a search before it finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Exhausted {
+    Timeout,
+    Candidates,
+    Memory,
+    /// The [`Budget::cancel`] token was cancelled, e.g. by Ctrl-C.
+    Cancelled,
+}
+
+impl fmt::Display for Exhausted {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Exhausted::Timeout => write!(f, "timeout reached"),
+            Exhausted::Candidates => write!(f, "candidate limit reached"),
+            Exhausted::Memory => write!(f, "memory limit reached"),
+            Exhausted::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// The best layout found, and how the search ended.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Outcome {
+    pub ir: IR,
+    /// Violations of the examples left in `ir`; zero means every example is satisfied.
+    pub violations: usize,
+    /// Number of candidates scored.
+    pub candidates: usize,
+    /// Set when the budget ran out before a satisfying layout was found.
+    pub exhausted: Option<Exhausted>,
+    /// Why each structural decision in `ir` was made, for `--explain`.
+    #[serde(default)]
+    pub trace: Vec<String>,
+    #[serde(default)]
+    pub stats: SearchStats,
+    /// Other layouts satisfying every example, cheapest first, up to `budget.top_n - 1`.
+    #[serde(default)]
+    pub alternatives: Vec<IR>,
+}
+
+/// Work done by one search, for `--stats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchStats {
+    /// Candidates generated, including pruned ones.
+    pub enumerated: usize,
+    /// Candidates skipped because an equivalent layout was already scored.
+    pub pruned: usize,
+    /// Candidates scored against the examples.
+    pub scored: usize,
+    /// Layout simulations run (one per scored candidate and example).
+    pub simulated: usize,
+    /// Approximate bytes of the record of candidates scored, for searches that keep one.
+    #[serde(default)]
+    pub memory_bytes: usize,
+    /// Groups of views nested in a candidate whose arrangements were worked out, and times
+    /// a group's were reused instead; see [`crate::synthesis::memo`].
+    #[serde(default)]
+    pub subtrees_solved: usize,
+    #[serde(default)]
+    pub subtrees_reused: usize,
+}
+
+/// Parses durations such as `5s`, `500ms`, `2m` or a bare number of seconds.
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let value: f64 = number.parse().map_err(|_| format!("Invalid duration '{}'", text))?;
+    let seconds = match unit.trim() {
+        "" | "s" => value,
+        "ms" => value / 1000.0,
+        "m" => value * 60.0,
+        other => return Err(format!("Unknown duration unit '{}': expected ms, s or m", other)),
+    };
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// Parses sizes such as `512MB`, `2GB`, `64KB` or a bare number of bytes. Units are binary:
+/// a kilobyte is 1024 bytes.
+pub fn parse_size(text: &str) -> Result<usize, String> {
+    let text = text.trim();
+    let split = text.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let value: f64 = number.parse().map_err(|_| format!("Invalid size '{}'", text))?;
+    let scale = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1u64,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        _ => return Err(format!("Unknown size unit '{}': expected B, KB, MB or GB", unit.trim())),
+    };
+    Ok((value * scale as f64) as usize)
+}
+
+struct Search<'a> {
+    examples: &'a [Example],
+    metrics: &'a Metrics,
+    budget: &'a Budget,
+    weights: &'a CostWeights,
+    started: Instant,
+    /// The `budget.top_n` best candidates so far: fewest violations, then lowest weighted cost.
+    ranked: Vec<(usize, f64, IR)>,
+    /// Number of satisfying candidates scored.
+    satisfying: usize,
+    exhausted: Option<Exhausted>,
+    /// Canonical forms of every candidate scored so far.
+    seen: LayoutSet,
+    /// The distinct arrangements of each nested group of views met so far.
+    memo: Memo,
+    stats: SearchStats,
+}
+
+impl Search<'_> {
+    // Scores one candidate. Breaks once the budget runs out; running out after a satisfying
+    // layout was found does not count as exhausting it, but being cancelled does, since a
+    // cheaper layout may have been next.
+    fn consider(&mut self, ir: IR) -> ControlFlow<()> {
+        self.stats.enumerated += 1;
+        if self.seen.contains(&ir.canonicalize()) {
+            self.stats.pruned += 1;
+            return ControlFlow::Continue(());
+        }
+        if self.out_of_budget() {
+            return ControlFlow::Break(());
+        }
+        self.score(ir);
+        ControlFlow::Continue(())
+    }
+
+    // Whether the budget has run out, noting why as `consider` explains
+    fn out_of_budget(&mut self) -> bool {
+        let Some(reason) = self.budget.reached(self.started, &self.stats) else { return false };
+        if self.satisfying == 0 || reason == Exhausted::Cancelled {
+            self.exhausted = Some(reason);
+        }
+        true
+    }
+
+    fn score(&mut self, ir: IR) -> usize {
+        self.stats.scored += 1;
+        self.stats.simulated += self.examples.len();
+        self.seen.insert(&ir.canonicalize());
+        self.stats.memory_bytes = self.seen.bytes() + self.memo.bytes();
+        let score = verify(&ir, self.examples, self.metrics).len();
+        if score == 0 {
+            self.satisfying += 1;
+        }
+        let cost = evaluate::cost(&ir, self.examples, self.metrics).total(self.weights);
+        // Ties keep the earlier candidate ahead
+        let rank = self.ranked.partition_point(|(best, best_cost, _)| (*best, *best_cost) <= (score, cost));
+        if rank < self.budget.top_n.max(1) {
+            self.ranked.insert(rank, (score, cost, ir));
+            self.ranked.truncate(self.budget.top_n.max(1));
+        }
+        score
+    }
+
+    // Every stack of `axis` over `items` in order, nesting runs of items up to `depth` levels.
+    fn stacks(&mut self, items: &[IR], vertical: bool, depth: usize, emit: &mut dyn FnMut(&mut Self, IR) -> ControlFlow<()>) -> ControlFlow<()> {
+        // More items than `cuts` has bits for are only stacked flat
+        let boundaries = u32::try_from(items.len().saturating_sub(1)).ok().and_then(|boundaries| 1u64.checked_shl(boundaries));
+        let Some(splits) = boundaries.filter(|_| items.len() > 1 && depth > 1) else {
+            return emit(self, stack(vertical, items.to_vec()));
+        };
+        // Each bit of `cuts` marks a boundary between groups of consecutive items
+        for cuts in 0..splits {
+            let mut groups = Vec::new();
+            let mut start = 0;
+            for i in 0..items.len() {
+                if i == items.len() - 1 || cuts & (1u64 << i) != 0 {
+                    groups.push(&items[start..=i]);
+                    start = i + 1;
+                }
+            }
+            if groups.len() == 1 && cuts != 0 {
+                continue;
+            }
+            if groups.len() == 1 {
+                // A single group is the flat stack
+                emit(self, stack(vertical, items.to_vec()))?;
+                continue;
+            }
+            self.product(&groups, vertical, depth, Vec::new(), emit)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    // Chooses an arrangement for each group in turn, emitting the enclosing stack once all are chosen.
+    fn product(&mut self, groups: &[&[IR]], vertical: bool, depth: usize, chosen: Vec<IR>, emit: &mut dyn FnMut(&mut Self, IR) -> ControlFlow<()>) -> ControlFlow<()> {
+        let Some((group, rest)) = groups.split_first() else {
+            return emit(self, stack(vertical, chosen));
+        };
+        if group.len() == 1 {
+            let mut chosen = chosen;
+            chosen.push(group[0].clone());
+            return self.product(rest, vertical, depth, chosen, emit);
+        }
+        if group.len() > memo::MAX_GROUP {
+            return self.stacks(group, !vertical, depth - 1, &mut |search, nested| {
+                let mut chosen = chosen.clone();
+                chosen.push(nested);
+                search.product(rest, vertical, depth, chosen, emit)
+            });
+        }
+        for nested in self.arrangements(group, !vertical, depth - 1)? {
+            let mut chosen = chosen.clone();
+            chosen.push(nested);
+            self.product(rest, vertical, depth, chosen, emit)?;
+        }
+        ControlFlow::Continue(())
+    }
+
+    // The distinct stacks of `axis` over `items`, as `stacks` emits them, equivalent ones
+    // dropped after the first: they could only make candidates equivalent to earlier ones.
+    // Solved once per group and then reused from the memo. Breaks once the budget runs out
+    // while they are collected, which count towards the memory limit, leaving them unsolved.
+    fn arrangements(&mut self, items: &[IR], vertical: bool, depth: usize) -> ControlFlow<(), Vec<IR>> {
+        let signature = self.memo.signature(items, vertical, depth);
+        if let Some(arrangements) = self.memo.get(&signature) {
+            self.stats.subtrees_reused += 1;
+            return ControlFlow::Continue(arrangements);
+        }
+        let mut arrangements = Vec::new();
+        let mut collected = 0;
+        self.stacks(items, vertical, depth, &mut |search, ir| {
+            collected += approximate_bytes(&ir);
+            search.stats.memory_bytes = search.seen.bytes() + search.memo.bytes() + collected;
+            if search.out_of_budget() {
+                return ControlFlow::Break(());
+            }
+            arrangements.push(ir);
+            ControlFlow::Continue(())
+        })?;
+        let mut distinct = LayoutSet::new();
+        arrangements.retain(|ir| distinct.insert(&ir.canonicalize()));
+        self.stats.subtrees_solved += 1;
+        self.memo.insert(signature, &arrangements);
+        self.stats.memory_bytes = self.seen.bytes() + self.memo.bytes();
+        ControlFlow::Continue(arrangements)
+    }
+}
+
+// Roughly the bytes `ir` takes, for arrangements held before they are memoized
+fn approximate_bytes(ir: &IR) -> usize {
+    std::mem::size_of::<IR>()
+        + match ir {
+            IR::VStack(children) | IR::HStack(children) => children.iter().map(approximate_bytes).sum(),
+            IR::Text(text) | IR::Button(text) | IR::Image(text) => text.len(),
+            IR::Custom { name, args } => name.len() + args.len(),
+            IR::Spacer => 0,
+        }
+}
+
+fn stack(vertical: bool, children: Vec<IR>) -> IR {
+    if vertical { IR::VStack(children) } else { IR::HStack(children) }
+}
+
+// Adds a root-level `Spacer` at each gap in turn, after trying none.
+fn with_spacers(search: &mut Search, ir: IR) -> ControlFlow<()> {
+    search.consider(ir.clone())?;
+    if let IR::VStack(children) | IR::HStack(children) = &ir {
+        for gap in 0..=children.len() {
+            let mut spaced = children.clone();
+            spaced.insert(gap, IR::Spacer);
+            search.consider(stack(matches!(ir, IR::VStack(_)), spaced))?;
+        }
+    }
+    ControlFlow::Continue(())
+}
+
+// SplitMix64: tiny, seedable and identical on every platform, which is all a shuffle needs.
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // A number below `bound`, which must not be 0.
+    pub(crate) fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    // Fisher-Yates shuffle.
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, (self.next() % (i as u64 + 1)) as usize);
+        }
+    }
+}
+
+// Calls `visit` with every ordering of `items` (Heap's algorithm), the given order first.
+fn permutations(items: &mut Vec<IR>, k: usize, visit: &mut dyn FnMut(&[IR]) -> ControlFlow<()>) -> ControlFlow<()> {
+    if k <= 1 {
+        return visit(items);
+    }
+    for i in 0..k - 1 {
+        permutations(items, k - 1, visit)?;
+        let j = if k.is_multiple_of(2) { i } else { 0 };
+        items.swap(j, k - 1);
+    }
+    permutations(items, k - 1, visit)
+}
+
+/// Searches for a layout satisfying every example, ranking candidates with the default
+/// cost weights; see [`search_with`].
+pub fn search(examples: &[Example], budget: &Budget, metrics: &Metrics) -> Option<Outcome> {
+    search_with(examples, budget, metrics, &CostWeights::default())
+}
+
+/// Searches for a layout satisfying every example.
+///
+/// The heuristic layout from [`synthesize_layout`] is scored first and returned as is
+/// when it already satisfies the examples, they state no frames and only one layout is
+/// wanted. Otherwise orderings of the requested views are arranged into stacks of both
+/// axes, nested up to `budget.max_depth` levels, until the orderings explored yield
+/// `budget.top_n` satisfying layouts or the budget runs out. Candidates are ranked by
+/// their violations, then by their [`evaluate::cost`] under `weights`, so of the
+/// arrangements explored the cheapest is returned, or the best seen so far when none
+/// satisfies the examples. Returns `None` when there are no examples.
+///
+/// With `budget.seed` set, orderings are explored starting from a seeded shuffle rather
+/// than the order of the example. The result depends only on the examples, the budget and
+/// the weights, except that a search stopped by its timeout depends on how far it got.
+pub fn search_with(examples: &[Example], budget: &Budget, metrics: &Metrics, weights: &CostWeights) -> Option<Outcome> {
+    let (heuristic, mut trace) = synthesize_traced(examples.to_vec())?;
+    let mut search = Search {
+        examples,
+        metrics,
+        budget,
+        weights,
+        started: Instant::now(),
+        ranked: Vec::new(),
+        satisfying: 0,
+        exhausted: None,
+        seen: LayoutSet::new(),
+        memo: Memo::new(),
+        stats: SearchStats::default(),
+    };
+
+    // The heuristic layout is always scored, so even a zero budget yields a result.
+    // Stated frames may be matched more closely by another arrangement, so they are searched for.
+    search.stats.enumerated += 1;
+    let geometry = examples.iter().any(|example| !expected_frames(example).is_empty());
+    if search.score(heuristic.clone()) > 0 || geometry || budget.top_n > 1 {
+        let (mut leaves, ordered) = expected_leaves(&examples[0]);
+        let count = if ordered { 1 } else { leaves.len() };
+        match (budget.seed, ordered) {
+            (Some(seed), false) => Rng(seed).shuffle(&mut leaves),
+            // The first ordering explored is the one the stated frames read in
+            (None, false) if geometry => order_by_stated_position(&mut leaves, examples),
+            _ => {}
+        }
+        let _ = permutations(&mut leaves, count, &mut |order| {
+            for vertical in [true, false] {
+                search.stacks(order, vertical, budget.max_depth.max(1), &mut with_spacers)?;
+            }
+            // Every arrangement of this ordering has been ranked against the satisfying ones
+            if search.satisfying >= budget.top_n.max(1) { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+        });
+    }
+
+    let mut ranked = search.ranked.into_iter();
+    let (violations, _, ir) = ranked.next().expect("the heuristic layout was scored");
+    let alternatives = ranked.filter(|(violations, _, _)| *violations == 0).map(|(_, _, ir)| ir).collect();
+    if ir != heuristic {
+        // The heuristic's placement reasons no longer apply; explain the search instead
+        let reason = match verify(&heuristic, examples, metrics).first() {
+            Some(violation) => violation.to_string(),
+            None => format!("costs more ({})", evaluate::cost(&heuristic, examples, metrics)),
+        };
+        trace = vec![format!("Direct translation {} rejected: {}", describe(&heuristic), reason)];
+        trace.extend(nested_stack_reasons(&ir, examples));
+    }
+    match search.exhausted {
+        Some(reason) => trace.push(format!(
+            "Search stopped ({}) after {} candidates; showing the layout with the fewest problems ({})",
+            reason, search.stats.scored, violations
+        )),
+        None if violations > 0 => trace.push(format!(
+            "No arrangement within depth {} satisfies every example; showing the layout with the fewest problems ({})",
+            budget.max_depth, violations
+        )),
+        None => trace.push(format!(
+            "Layout satisfies every example ({}) after scoring {}",
+            examples.len(),
+            plural(search.stats.scored, "candidate")
+        )),
+    }
+    if search.satisfying > 1 {
+        trace.push(format!(
+            "Cheapest of {} satisfying layouts: {}",
+            search.satisfying,
+            evaluate::cost(&ir, examples, metrics)
+        ));
+    }
+    trace.extend(spacer_reasons(&ir, examples, metrics));
+    Some(Outcome {
+        ir,
+        violations,
+        candidates: search.stats.scored,
+        exhausted: search.exhausted,
+        trace,
+        stats: search.stats,
+        alternatives,
+    })
+}
+
+// Puts the leaves that state a position in the order they read on the screen, top to bottom
+// and then leading to trailing, by the first frame stated for each; the others keep their place.
+fn order_by_stated_position(leaves: &mut [IR], examples: &[Example]) {
+    let frames: Vec<(IR, layout::StatedFrame)> = examples.iter().flat_map(expected_frames).collect();
+    let position = |leaf: &IR| {
+        frames.iter().find(|(node, frame)| node == leaf && (frame.y.is_some() || frame.x.is_some())).map(|(_, frame)| (frame.y.unwrap_or(0.0), frame.x.unwrap_or(0.0)))
+    };
+    let slots: Vec<usize> = (0..leaves.len()).filter(|&index| position(&leaves[index]).is_some()).collect();
+    let mut placed: Vec<IR> = slots.iter().map(|&index| leaves[index].clone()).collect();
+    placed.sort_by(|a, b| {
+        let ((ay, ax), (by, bx)) = (position(a).unwrap_or_default(), position(b).unwrap_or_default());
+        ay.total_cmp(&by).then(ax.total_cmp(&bx))
+    });
+    for (index, leaf) in slots.into_iter().zip(placed) {
+        leaves[index] = leaf;
+    }
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 { format!("1 {}", noun) } else { format!("{} {}s", count, noun) }
+}
+
+fn list(nodes: &[IR]) -> String {
+    let names: Vec<String> = nodes.iter().filter(|n| !matches!(n, IR::Spacer)).map(describe).collect();
+    match names.as_slice() {
+        [init @ .., last] if !init.is_empty() => format!("{} and {}", init.join(", "), last),
+        _ => names.join(""),
+    }
+}
+
+// Explains each stack nested inside the root by the screen it was needed for.
+fn nested_stack_reasons(ir: &IR, examples: &[Example]) -> Vec<String> {
+    let (IR::VStack(children) | IR::HStack(children)) = ir else { return Vec::new() };
+    let smallest = examples
+        .iter()
+        .filter_map(layout::screen_size)
+        .min_by(|a, b| (a.width * a.height).total_cmp(&(b.width * b.height)));
+    children
+        .iter()
+        .filter_map(|child| {
+            let (kind, nodes) = match child {
+                IR::HStack(nodes) => ("HStack groups", nodes),
+                IR::VStack(nodes) => ("VStack stacks", nodes),
+                _ => return None,
+            };
+            let direction = if matches!(child, IR::HStack(_)) { "into a row" } else { "into a column" };
+            Some(match smallest {
+                Some(screen) => format!(
+                    "{} {} {} so the content fits a {}x{} screen",
+                    kind, list(nodes), direction, screen.width, screen.height
+                ),
+                None => format!("{} {} {}", kind, list(nodes), direction),
+            })
+        })
+        .chain(children.iter().flat_map(|child| match child {
+            IR::VStack(_) | IR::HStack(_) => nested_stack_reasons(child, &[]),
+            _ => Vec::new(),
+        }))
+        .collect()
+}
+
+// Shows how each root-level spacer absorbs the space that varies between the examples.
+fn spacer_reasons(ir: &IR, examples: &[Example], metrics: &Metrics) -> Vec<String> {
+    let (IR::VStack(children) | IR::HStack(children)) = ir else { return Vec::new() };
+    let vertical = matches!(ir, IR::VStack(_));
+    let mut sizes: Vec<layout::Size> = examples.iter().filter_map(layout::screen_size).collect();
+    sizes.sort_by(|a, b| if vertical { a.height.total_cmp(&b.height) } else { a.width.total_cmp(&b.width) });
+    sizes.dedup_by(|a, b| if vertical { a.height == b.height } else { a.width == b.width });
+    if sizes.len() < 2 {
+        return Vec::new();
+    }
+    let (axis, unit) = if vertical { ("height", "tall") } else { ("width", "wide") };
+    children
+        .iter()
+        .enumerate()
+        .filter(|(_, child)| matches!(child, IR::Spacer))
+        .map(|(index, _)| {
+            let gaps: Vec<String> = sizes
+                .iter()
+                .map(|&screen| {
+                    let frame = layout::simulate(ir, screen, metrics);
+                    let rect = frame.frames.iter().find(|f| f.path == [index]).map(|f| f.rect);
+                    let (gap, extent) = if vertical {
+                        (rect.map_or(0.0, |r| r.height), screen.height)
+                    } else {
+                        (rect.map_or(0.0, |r| r.width), screen.width)
+                    };
+                    format!("{:.0}pt at {}pt {}", gap, extent, unit)
+                })
+                .collect();
+            format!("Spacer at index {} absorbs the extra {}: {}", index, axis, gaps.join(", "))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+    use crate::synthesis::swiftui::synthesize_layout;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("5s").unwrap(), Duration::from_secs(5));
+        assert_eq!(parse_duration("250ms").unwrap(), Duration::from_millis(250));
+        assert_eq!(parse_duration("2m").unwrap(), Duration::from_secs(120));
+        assert_eq!(parse_duration("1.5").unwrap(), Duration::from_millis(1500));
+        assert!(parse_duration("5h").unwrap_err().contains("Unknown duration unit 'h'"));
+        assert!(parse_duration("soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("512MB"), Ok(512 << 20));
+        assert_eq!(parse_size("2g"), Ok(2 << 30));
+        assert_eq!(parse_size("1.5KiB"), Ok(1536));
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert!(parse_size("5TB").unwrap_err().contains("Unknown size unit 'TB'"));
+        assert!(parse_size("lots").is_err());
+    }
+
+    #[test]
+    fn test_memory_limit_stops_the_search() {
+        let long = "word ".repeat(80);
+        let spec = format!("{{(width:320,height:200):{{title:\"{}\",button:\"A\",button:\"B\",Image:\"c\"}}}}", long.trim());
+        let examples = parse_examples(&spec).unwrap();
+        let unlimited = Budget { timeout: None, max_candidates: 2_000, ..Budget::default() };
+        let full = search(&examples, &unlimited, &Metrics::default()).unwrap();
+        assert!(full.stats.memory_bytes > 0);
+        // A limit below what the full search held stops it early, with the best so far
+        let limit = full.stats.memory_bytes / 4;
+        let bounded = search(&examples, &Budget { max_memory: Some(limit), ..unlimited.clone() }, &Metrics::default()).unwrap();
+        assert_eq!(bounded.exhausted, Some(Exhausted::Memory));
+        assert!(bounded.stats.scored < full.stats.scored);
+        assert!(bounded.stats.memory_bytes >= limit && bounded.stats.memory_bytes < full.stats.memory_bytes);
+        assert!(bounded.trace.last().unwrap().starts_with("Search stopped (memory limit reached)"));
+    }
+
+    #[test]
+    fn test_nested_groups_are_solved_once() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"A\"@(y:700),button:\"B\",Image:\"c\",title:\"D\",button:\"E\"}}").unwrap();
+        let budget = Budget { timeout: None, max_candidates: 100_000, ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default()).unwrap();
+        let stats = outcome.stats;
+        assert!(stats.subtrees_solved > 0 && stats.subtrees_reused > stats.subtrees_solved, "{:?}", stats);
+        // Groups arranged two equivalent ways are kept once, so few whole candidates repeat
+        assert!(stats.pruned * 10 < stats.scored, "{:?}", stats);
+        assert_eq!(search(&examples, &budget, &Metrics::default()).unwrap(), outcome);
+    }
+
+    #[test]
+    fn test_stated_positions_reorder_views() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"Hi\"@(y:700), button:\"Go\"@(y:60)}}").unwrap();
+        let outcome = search(&examples, &Budget::default(), &Metrics::default()).unwrap();
+        assert_eq!(outcome.ir, IR::VStack(vec![IR::Button("Go".to_string()), IR::Spacer, IR::Text("Hi".to_string())]));
+        assert_eq!(outcome.violations, 0);
+    }
+
+    #[test]
+    fn test_decorations_alone_do_not_place_views() {
+        let examples = parse_examples("{(width:390,height:844):{Image:\"bell\"@(cornerRadius:4), title:\"Inbox\", button:\"Go\"}}").unwrap();
+        let outcome = search(&examples, &Budget::default(), &Metrics::default()).unwrap();
+        let plain = parse_examples("{(width:390,height:844):{Image:\"bell\", title:\"Inbox\", button:\"Go\"}}").unwrap();
+        assert_eq!(outcome.ir, search(&plain, &Budget::default(), &Metrics::default()).unwrap().ir);
+        assert!(matches!(&outcome.ir, IR::VStack(children) if children.contains(&IR::Spacer)), "{:?}", outcome.ir);
+    }
+
+    #[test]
+    fn test_more_elements_than_cut_bits_are_stacked_flat() {
+        let titles: Vec<String> = (0..70).map(|i| format!("title:\"T{}\"", i)).collect();
+        let examples = parse_examples(&format!("{{(width:390,height:844):{{{}}}}}", titles.join(","))).unwrap();
+        let budget = Budget { timeout: Some(Duration::from_secs(1)), max_candidates: 50, ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default()).unwrap();
+        assert!(matches!(&outcome.ir, IR::VStack(children) | IR::HStack(children) if children.len() >= 70), "{:?}", outcome.ir);
+        assert!(outcome.candidates <= 50);
+    }
+
+    #[test]
+    fn test_large_groups_stay_within_the_budget() {
+        let titles: Vec<String> = (0..20).map(|i| format!("title:\"T{}\"", i)).collect();
+        let examples = parse_examples(&format!("{{(width:390,height:844):{{{}}}}}", titles.join(","))).unwrap();
+        let limit = Duration::from_millis(300);
+        let started = Instant::now();
+        let budget = Budget { timeout: Some(limit), max_candidates: usize::MAX, ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default()).unwrap();
+        assert!(started.elapsed() < limit * 5, "{:?}", started.elapsed());
+        assert_eq!(outcome.exhausted, Some(Exhausted::Timeout));
+        assert!(outcome.candidates > 100, "{}", outcome.candidates);
+
+        let budget = Budget { timeout: Some(Duration::from_secs(10)), max_candidates: usize::MAX, max_memory: Some(1 << 18), ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default()).unwrap();
+        assert_eq!(outcome.exhausted, Some(Exhausted::Memory));
+        assert!(outcome.stats.memory_bytes < 1 << 20, "{}", outcome.stats.memory_bytes);
+
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        let outcome = search(&examples, &Budget { timeout: None, cancel, ..Budget::default() }, &Metrics::default()).unwrap();
+        assert_eq!(outcome.exhausted, Some(Exhausted::Cancelled));
+    }
+
+    #[test]
+    fn test_thin_full_width_elements_become_dividers() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"Account\"@(y:60),title:*@(y:120,w:358,h:1),button:\"Sign out\"}}").unwrap();
+        let outcome = search(&examples, &Budget::default(), &Metrics::default()).unwrap();
+        assert_eq!(outcome.violations, 0);
+        let divider = IR::Custom { name: "Divider".to_string(), args: String::new() };
+        assert!(matches!(&outcome.ir, IR::VStack(children) if children.contains(&divider)), "{:?}", outcome.ir);
+        assert!(!format!("{:?}", outcome.ir).contains("Title"), "{:?}", outcome.ir);
+    }
+
+    #[test]
+    fn test_heuristic_layout_is_kept_when_it_satisfies_examples() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"Hello\",button:\"Go\"}}").unwrap();
+        let outcome = search(&examples, &Budget::default(), &Metrics::default()).unwrap();
+        assert_eq!(outcome.ir, synthesize_layout(examples).unwrap());
+        assert_eq!(outcome.violations, 0);
+        assert_eq!(outcome.candidates, 1);
+        assert_eq!(outcome.exhausted, None);
+    }
+
+    #[test]
+    fn test_search_rearranges_overflowing_column() {
+        // The heuristic column is too tall for a short screen, but a row fits
+        let examples = parse_examples("{(width:320,height:200):{Image:\"logo\",title:\"Hi\",button:\"Go\"}}").unwrap();
+        let seed = synthesize_layout(examples.clone()).unwrap();
+        assert!(!verify(&seed, &examples, &Metrics::default()).is_empty());
+
+        let outcome = search(&examples, &Budget::default(), &Metrics::default()).unwrap();
+        assert_eq!(outcome.violations, 0);
+        assert!(outcome.candidates > 1);
+        assert_ne!(outcome.ir, seed);
+        assert!(verify(&outcome.ir, &examples, &Metrics::default()).is_empty());
+
+        // A one-child nested stack is the same layout whichever axis it uses, so one is pruned
+        let stats = outcome.stats;
+        assert_eq!(stats.scored, outcome.candidates);
+        assert_eq!(stats.enumerated, stats.scored + stats.pruned);
+        assert!(stats.pruned > 0);
+        assert_eq!(stats.simulated, stats.scored * examples.len());
+    }
+
+    #[test]
+    fn test_exhausted_budget_returns_best_effort() {
+        let long = "word ".repeat(80);
+        let spec = format!("{{(width:320,height:200):{{title:\"{}\",button:\"Go\"}}}}", long.trim());
+        let examples = parse_examples(&spec).unwrap();
+        let budget = Budget { max_candidates: 5, ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default()).unwrap();
+        assert_eq!(outcome.exhausted, Some(Exhausted::Candidates));
+        assert_eq!(outcome.candidates, 5);
+        assert!(outcome.violations > 0);
+
+        let budget = Budget { timeout: Some(Duration::ZERO), ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default()).unwrap();
+        assert_eq!(outcome.exhausted, Some(Exhausted::Timeout));
+        assert_eq!(outcome.ir, synthesize_layout(examples.clone()).unwrap());
+
+        // A cancelled search stops as soon as it notices, with the heuristic layout scored
+        let budget = Budget { timeout: None, ..Budget::default() };
+        budget.cancel.cancel();
+        let outcome = search(&examples, &budget, &Metrics::default()).unwrap();
+        assert_eq!((outcome.exhausted, outcome.candidates), (Some(Exhausted::Cancelled), 1));
+        assert!(outcome.trace.last().unwrap().starts_with("Search stopped (cancelled) after 1 candidates"));
+        // Clones share the flag; budgets compare by their limits
+        assert!(budget.clone().cancel.is_cancelled());
+        assert_eq!(budget, Budget { timeout: None, cancel: budget.cancel.clone(), ..Budget::default() });
+    }
+
+    #[test]
+    fn test_seeded_search_is_reproducible() {
+        let examples = parse_examples(
+            "{(width:320,height:200):{Image:\"logo\",title:\"Hi\",Custom:\"Badge()\",button:\"Go\"}}",
+        )
+        .unwrap();
+        let seeded = |seed| Budget { seed: Some(seed), timeout: None, ..Budget::default() };
+        let first = search(&examples, &seeded(42), &Metrics::default()).unwrap();
+        let again = search(&examples, &seeded(42), &Metrics::default()).unwrap();
+        assert_eq!(first, again);
+        assert_eq!(first.violations, 0);
+
+        let mut a = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let mut b = a.clone();
+        Rng(42).shuffle(&mut a);
+        Rng(42).shuffle(&mut b);
+        assert_eq!(a, b);
+        Rng(7).shuffle(&mut b);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_cost_weights_rank_satisfying_layouts() {
+        // Both a flat row and a column with a nested row fit; the row is cheaper by default
+        let examples = parse_examples("{(width:320,height:200):{Image:\"logo\",title:\"Hi\",button:\"Go\"}}").unwrap();
+        let outcome = search(&examples, &Budget::default(), &Metrics::default()).unwrap();
+        assert!(matches!(outcome.ir, IR::HStack(_)));
+        assert!(outcome.trace.last().unwrap().starts_with("Cheapest of"));
+
+        // Views that state their frames are placed where the example puts them
+        let examples = parse_examples("{(width:390,height:844):{title:\"Hi\"@(y:16),button:\"Go\"@(y:90)}}").unwrap();
+        let outcome = search(&examples, &Budget::default(), &Metrics::default()).unwrap();
+        assert_eq!(outcome.ir, IR::VStack(vec![IR::Text("Hi".to_string()), IR::Button("Go".to_string()), IR::Spacer]));
+        let ignore_geometry = CostWeights { geometry: 0.0, ..CostWeights::default() };
+        let outcome = search_with(&examples, &Budget::default(), &Metrics::default(), &ignore_geometry).unwrap();
+        assert_eq!(outcome.ir, IR::VStack(vec![IR::Text("Hi".to_string()), IR::Button("Go".to_string())]));
+    }
+
+    #[test]
+    fn test_top_n_ranks_distinct_satisfying_layouts() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}").unwrap();
+        let budget = Budget { top_n: 3, ..Budget::default() };
+        let outcome = search(&examples, &budget, &Metrics::default()).unwrap();
+        assert_eq!(outcome.violations, 0);
+        assert_eq!(outcome.alternatives.len(), 2);
+        let weights = CostWeights::default();
+        let total = |ir: &IR| evaluate::cost(ir, &examples, &Metrics::default()).total(&weights);
+        assert!(total(&outcome.ir) <= total(&outcome.alternatives[0]));
+        assert!(total(&outcome.alternatives[0]) <= total(&outcome.alternatives[1]));
+        assert!(outcome.alternatives.iter().all(|ir| ir != &outcome.ir && verify(ir, &examples, &Metrics::default()).is_empty()));
+    }
+
+    #[test]
+    fn test_trace_explains_search_and_spacers() {
+        let examples = parse_examples("{(width:200,height:220):{Image:\"logo\",title:\"Hi\",button:\"Go\"}}").unwrap();
+        let outcome = search(&examples, &Budget::default(), &Metrics::default()).unwrap();
+        assert!(outcome.trace[0].starts_with("Direct translation VStack rejected: example 1: content needs"));
+        assert!(outcome.trace.iter().any(|line| line.starts_with("HStack groups Text \"Hi\" and Button \"Go\" into a row")));
+        assert!(outcome.trace.iter().any(|line| line.starts_with("Layout satisfies every example (1) after scoring")));
+
+        let examples = parse_examples(
+            "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}{(width:320,height:568):{title:\"Hi\",button:\"Go\"}}",
+        )
+        .unwrap();
+        let outcome = search(&examples, &Budget::default(), &Metrics::default()).unwrap();
+        assert_eq!(outcome.trace[1], "Spacer inserted before Button \"Go\" to pin it to the bottom of the screen");
+        assert_eq!(
+            outcome.trace.last().unwrap(),
+            "Spacer at index 1 absorbs the extra height: 400pt at 568pt tall, 676pt at 844pt tall"
+        );
+    }
+}
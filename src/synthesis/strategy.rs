@@ -0,0 +1,225 @@
+// File: src/synthesis/strategy.rs
+// Pluggable synthesis algorithms, selected with `--strategy`.
+use std::sync::Arc;
+
+use crate::ast::Example;
+use crate::layout::Metrics;
+use crate::synthesis::evaluate::CostWeights;
+use crate::synthesis::pool::{self, Pending};
+use crate::synthesis::search::{self, Budget, Outcome, SearchStats};
+use crate::synthesis::swiftui::synthesize_traced;
+use crate::synthesis::verify::{fill_wildcards, verify};
+use crate::synthesis::{assign, beam, constraint, genetic};
+
+/// Names accepted by [`by_name`], the default first.
+pub const STRATEGIES: &[&str] = &["enumerative", "template", "constraint", "beam", "genetic", "assignment"];
+
+/// An algorithm that turns examples into a layout.
+///
+/// Implementations score their result against the examples themselves and report
+/// it as an [`Outcome`], so the CLI's budget warnings, `--explain` and `--stats`
+/// work the same whichever strategy ran. Returns `None` when there are no examples.
+/// Wildcards are filled in first, see [`fill_wildcards`]. Strategies are shared between
+/// threads, as by [`synthesize_async`], so they hold no interior `Rc` or `RefCell`.
+pub trait SynthesisStrategy: Send + Sync {
+    fn name(&self) -> &'static str;
+    fn synthesize(&self, examples: &[Example], budget: &Budget) -> Option<Outcome>;
+}
+
+/// Direct translation of the first example, without any search.
+#[derive(Clone, Debug, Default)]
+pub struct Template {
+    pub metrics: Metrics,
+}
+
+impl SynthesisStrategy for Template {
+    fn name(&self) -> &'static str {
+        "template"
+    }
+
+    fn synthesize(&self, examples: &[Example], _budget: &Budget) -> Option<Outcome> {
+        let examples = &fill_wildcards(examples);
+        let (ir, trace) = synthesize_traced(examples.to_vec())?;
+        let violations = verify(&ir, examples, &self.metrics).len();
+        let stats = SearchStats { enumerated: 1, scored: 1, simulated: examples.len(), ..SearchStats::default() };
+        Some(Outcome { ir, violations, candidates: 1, exhausted: None, trace, stats, alternatives: Vec::new() })
+    }
+}
+
+/// Enumerates stack arrangements until one satisfies every example; see [`search::search_with`].
+#[derive(Clone, Debug, Default)]
+pub struct Enumerative {
+    pub metrics: Metrics,
+    /// Ranks the arrangements that satisfy the examples.
+    pub weights: CostWeights,
+}
+
+impl SynthesisStrategy for Enumerative {
+    fn name(&self) -> &'static str {
+        "enumerative"
+    }
+
+    fn synthesize(&self, examples: &[Example], budget: &Budget) -> Option<Outcome> {
+        search::search_with(&fill_wildcards(examples), budget, &self.metrics, &self.weights)
+    }
+}
+
+/// Builds a layout directly from the fit constraints; see [`constraint::solve`].
+#[derive(Clone, Debug, Default)]
+pub struct ConstraintBased {
+    pub metrics: Metrics,
+}
+
+impl SynthesisStrategy for ConstraintBased {
+    fn name(&self) -> &'static str {
+        "constraint"
+    }
+
+    fn synthesize(&self, examples: &[Example], _budget: &Budget) -> Option<Outcome> {
+        constraint::solve(&fill_wildcards(examples), &self.metrics)
+    }
+}
+
+/// Grows layouts a view at a time, keeping the `budget.beam_width` that fit best; see
+/// [`beam::search`].
+#[derive(Clone, Debug, Default)]
+pub struct BeamSearch {
+    pub metrics: Metrics,
+    pub weights: CostWeights,
+}
+
+impl SynthesisStrategy for BeamSearch {
+    fn name(&self) -> &'static str {
+        "beam"
+    }
+
+    fn synthesize(&self, examples: &[Example], budget: &Budget) -> Option<Outcome> {
+        beam::search(&fill_wildcards(examples), budget, &self.metrics, &self.weights)
+    }
+}
+
+/// Evolves a population of layouts from the seeded random generator; see [`genetic::search`].
+#[derive(Clone, Debug, Default)]
+pub struct Genetic {
+    pub metrics: Metrics,
+    pub weights: CostWeights,
+}
+
+impl SynthesisStrategy for Genetic {
+    fn name(&self) -> &'static str {
+        "genetic"
+    }
+
+    fn synthesize(&self, examples: &[Example], budget: &Budget) -> Option<Outcome> {
+        genetic::search(&fill_wildcards(examples), budget, &self.metrics, &self.weights)
+    }
+}
+
+/// Assigns the views to the cells of grids as an integer program; see [`assign::solve`].
+#[derive(Clone, Debug, Default)]
+pub struct Assignment {
+    pub metrics: Metrics,
+    pub weights: CostWeights,
+}
+
+impl SynthesisStrategy for Assignment {
+    fn name(&self) -> &'static str {
+        "assignment"
+    }
+
+    fn synthesize(&self, examples: &[Example], budget: &Budget) -> Option<Outcome> {
+        assign::solve(&fill_wildcards(examples), budget, &self.metrics, &self.weights)
+    }
+}
+
+/// Looks up a built-in strategy by name. Only the template and constraint strategies do
+/// not rank by `weights`.
+pub fn by_name(name: &str, metrics: Metrics, weights: CostWeights) -> Result<Box<dyn SynthesisStrategy>, String> {
+    match name {
+        "enumerative" => Ok(Box::new(Enumerative { metrics, weights })),
+        "template" => Ok(Box::new(Template { metrics })),
+        "constraint" => Ok(Box::new(ConstraintBased { metrics })),
+        "beam" => Ok(Box::new(BeamSearch { metrics, weights })),
+        "genetic" => Ok(Box::new(Genetic { metrics, weights })),
+        "assignment" => Ok(Box::new(Assignment { metrics, weights })),
+        other => Err(format!("Unknown strategy '{}': expected one of {}", other, STRATEGIES.join(", "))),
+    }
+}
+
+/// Runs `strategy` on a worker of the shared [`pool`], returning at once with a handle to
+/// wait for or await the outcome. Several syntheses run at a time, one per core; others
+/// queue. A clone of `budget` kept by the caller cancels the search with its `cancel` token.
+pub fn synthesize_async(strategy: Arc<dyn SynthesisStrategy>, examples: Vec<Example>, budget: Budget) -> Pending<Option<Outcome>> {
+    pool::shared().spawn(move || strategy.synthesize(&examples, &budget))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::IR;
+    use crate::input::parser::parse_examples;
+    use crate::synthesis::search::Exhausted;
+
+    #[test]
+    fn test_by_name() {
+        for name in STRATEGIES {
+            assert_eq!(by_name(name, Metrics::default(), CostWeights::default()).unwrap().name(), *name);
+        }
+        assert!(by_name("annealing", Metrics::default(), CostWeights::default()).err().unwrap().contains("Unknown strategy 'annealing'"));
+    }
+
+    #[test]
+    fn test_strategies_agree_when_template_fits() {
+        let examples = parse_examples("{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}").unwrap();
+        let results: Vec<IR> = STRATEGIES
+            .iter()
+            .map(|name| by_name(name, Metrics::default(), CostWeights::default()).unwrap().synthesize(&examples, &Budget::default()).unwrap().ir)
+            .collect();
+        assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
+    #[test]
+    fn test_searches_stop_when_cancelled() {
+        let long = "word ".repeat(80);
+        let spec = format!("{{(width:320,height:200):{{title:\"{}\",button:\"Go\",Image:\"logo\"}}}}", long.trim());
+        let examples = parse_examples(&spec).unwrap();
+        let budget = Budget { timeout: None, max_candidates: usize::MAX, ..Budget::default() };
+        budget.cancel.cancel();
+        for name in ["enumerative", "beam", "genetic", "assignment"] {
+            let outcome = by_name(name, Metrics::default(), CostWeights::default()).unwrap().synthesize(&examples, &budget).unwrap();
+            assert_eq!(outcome.exhausted, Some(Exhausted::Cancelled), "{}", name);
+            assert!(outcome.violations > 0, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_synthesize_async_matches_synthesize() {
+        let examples = parse_examples("{(width:320,height:200):{Image:\"logo\",title:\"Hi\",button:\"Go\"}}").unwrap();
+        let strategy: Arc<dyn SynthesisStrategy> = Arc::from(by_name("enumerative", Metrics::default(), CostWeights::default()).unwrap());
+        let pending: Vec<_> = (0..4).map(|_| synthesize_async(Arc::clone(&strategy), examples.clone(), Budget::default())).collect();
+        let expected = strategy.synthesize(&examples, &Budget::default());
+        for outcome in pending {
+            assert_eq!(outcome.wait().map(|outcome| outcome.ir), expected.as_ref().map(|outcome| outcome.ir.clone()));
+        }
+    }
+
+    #[test]
+    fn test_library_types_are_send_and_sync() {
+        fn shareable<T: Send + Sync>() {}
+        shareable::<Box<dyn SynthesisStrategy>>();
+        shareable::<Pending<Option<Outcome>>>();
+        shareable::<(Budget, Outcome, Example, Metrics, CostWeights)>();
+        shareable::<(crate::config::Config, crate::input::parser::ParseOptions, crate::input::parser::Spec)>();
+        shareable::<(crate::diagnostics::Diagnostic, crate::components::ComponentLibrary, crate::lsp::Server)>();
+        shareable::<(crate::output::backend::Registry, crate::synthesis::cache::Cache)>();
+    }
+
+    #[test]
+    fn test_template_reports_violations_without_searching() {
+        let examples = parse_examples("{(width:320,height:200):{Image:\"logo\",title:\"Hi\",button:\"Go\"}}").unwrap();
+        let outcome = Template::default().synthesize(&examples, &Budget::default()).unwrap();
+        assert_eq!(outcome.candidates, 1);
+        assert!(outcome.violations > 0);
+        assert!(Template::default().synthesize(&[], &Budget::default()).is_none());
+    }
+}
@@ -1,31 +1,83 @@
-use crate::ast::{IR, Value};
+use crate::ast::{Example, IR, Value};
+use crate::diagnostics::Diagnostic;
+use crate::synthesis::verify::{element_leaf, placeholder};
+
+/// Element keys [`synthesize_layout`] reads; `hint` shapes the search instead.
+const READ_KEYS: &[&str] = &["title", "button", "Image", "Custom", "hint", "HStack"];
+
+/// Warnings (W0008) for input [`synthesize_layout`] skips: HStack children that are not
+/// strings and elements it does not know. The parser produces neither, so these only come up
+/// for examples built by hand.
+pub fn ignored_input(examples: &[Example]) -> Vec<Diagnostic> {
+    let mut warnings = Vec::new();
+    for (i, (_, elements)) in examples.iter().enumerate() {
+        let Value::Dict(elements) = elements else { continue };
+        for (key, value) in elements {
+            match (key.as_str(), value) {
+                ("HStack", Value::Dict(children)) => {
+                    for (child, _) in children.iter().filter(|(_, v)| !matches!(v, Value::String(_))) {
+                        warnings.push(Diagnostic::warning(
+                            "W0008",
+                            format!("HStack child '{}' of example {} is not a string and is ignored", child, i + 1),
+                        ));
+                    }
+                }
+                ("Custom", Value::String(s)) if IR::custom(s).is_err() => warnings.push(Diagnostic::warning(
+                    "W0008",
+                    format!("Custom view '{}' of example {} cannot be read and is ignored", s, i + 1),
+                )),
+                (key, Value::String(_) | Value::Any) if READ_KEYS.contains(&key) => {}
+                // Data, shown by a control
+                (key, Value::Int(_) | Value::Float(_) | Value::Bool(_) | Value::Array(_)) if element_leaf(key, value).is_some() => {}
+                _ => warnings.push(Diagnostic::warning(
+                    "W0008",
+                    format!("Element '{}' of example {} is not a view synthesis knows and is ignored", key, i + 1),
+                )),
+            }
+        }
+    }
+    warnings
+}
 
 /// Synthesizes a SwiftUI layout from examples.
 /// Returns Some(IR) if a matching layout is found, or None otherwise.
 pub fn synthesize_layout(examples: Vec<(Value, Value)>) -> Option<IR> {
-    let (_dims, elements) = examples.get(0)?;
+    synthesize_traced(examples).map(|(ir, _)| ir)
+}
+
+/// Like [`synthesize_layout`], also returning the reason for each structural decision.
+pub fn synthesize_traced(examples: Vec<(Value, Value)>) -> Option<(IR, Vec<String>)> {
+    let (_dims, elements) = examples.first()?;
+    let mut trace = Vec::new();
+    if examples[1..].iter().any(|(_, other)| other != elements) {
+        trace.push(format!(
+            "Elements taken from example 1; the other {} examples were only used to check the result",
+            examples.len() - 1
+        ));
+    }
 
     // HStack support: look for a Dict with a "HStack" key
     if let Value::Dict(ref elems) = elements {
         if let Some((_, Value::Dict(children))) = elems.iter().find(|(k, _)| k == "HStack") {
             let mut ir_children = Vec::new();
-            for (_k, v) in children {
-                match v {
-                    Value::String(s) => {
-                        // Remove surrounding quotes if present
-                        let s = s.trim_matches('"');
-                        if s == "Spacer" {
-                            ir_children.push(IR::Spacer);
-                        } else {
-                            ir_children.push(IR::Text(s.to_string()));
-                        }
-                    }
-                    _ => {
-                        eprintln!("Unsupported HStack child type: {:?}", _k);
+            // Children that are not strings are skipped; `ignored_input` reports them
+            for (_, v) in children {
+                if let Value::String(s) = v {
+                    // Remove surrounding quotes if present
+                    let s = s.trim_matches('"');
+                    if s == "Spacer" {
+                        trace.push(format!("Spacer inserted at index {} where the example lists \"Spacer\"", ir_children.len()));
+                        ir_children.push(IR::Spacer);
+                    } else {
+                        ir_children.push(IR::Text(s.to_string()));
                     }
                 }
             }
-            return Some(IR::HStack(ir_children));
+            trace.insert(0, format!(
+                "HStack chosen because the example groups its {} elements into a row, kept in the order given",
+                ir_children.len()
+            ));
+            return Some((IR::HStack(ir_children), trace));
         }
     }
 
@@ -33,6 +85,7 @@ pub fn synthesize_layout(examples: Vec<(Value, Value)>) -> Option<IR> {
     let mut title = None;
     let mut button = None;
     let mut image = None; // Added Image support
+    let mut customs = Vec::new();
 
     if let Value::Dict(ref elems) = elements {
         for (k, v) in elems {
@@ -40,26 +93,46 @@ pub fn synthesize_layout(examples: Vec<(Value, Value)>) -> Option<IR> {
                 ("title", Value::String(s)) => title = Some(s.clone()),
                 ("button", Value::String(s)) => button = Some(s.clone()),
                 ("Image", Value::String(s)) => image = Some(s.clone()), // Added Image key
+                // Wildcards (`title:*`) get a placeholder label
+                ("title", Value::Any) => title = placeholder("title").map(str::to_string),
+                ("button", Value::Any) => button = placeholder("button").map(str::to_string),
+                ("Image", Value::Any) => image = placeholder("Image").map(str::to_string),
+                ("Custom", Value::String(s)) => customs.extend(IR::custom(s).ok()),
+                (k, Value::Int(_) | Value::Float(_) | Value::Bool(_) | Value::Array(_)) => customs.extend(element_leaf(k, v)),
                 _ => {}
             }
         }
     }
 
+    trace.push("VStack chosen because the example does not group its elements into a row".to_string());
     let mut children = Vec::new();
     if let Some(img) = image {
+        trace.push(format!("Image \"{}\" placed first, as a header above the other content", img));
         children.push(IR::Image(img));
     }
     if let Some(t) = title {
         children.push(IR::Text(t));
     }
+    if !customs.is_empty() {
+        trace.push(format!("{} custom view(s) placed after the title, in the order given", customs.len()));
+    }
+    children.extend(customs);
+    let button = button.filter(|b| {
+        if b.is_empty() {
+            trace.push("Button omitted because its label is empty".to_string());
+        }
+        !b.is_empty()
+    });
+    match &button {
+        Some(b) => trace.push(format!("Spacer inserted before Button \"{}\" to pin it to the bottom of the screen", b)),
+        None => trace.push("Spacer inserted last to push the content to the top of the screen".to_string()),
+    }
     children.push(IR::Spacer);
     if let Some(b) = button {
-        if !b.is_empty() {
-            children.push(IR::Button(b));
-        }
+        children.push(IR::Button(b));
     }
 
-    Some(IR::VStack(children))
+    Some((IR::VStack(children), trace))
 }
 
 #[cfg(test)]
@@ -87,8 +160,8 @@ mod tests {
 
         vec![(
             Value::Dict(vec![
-                ("width".to_string(), Value::Int(390)),
-                ("height".to_string(), Value::Int(844)),
+                ("width".to_string(), Value::Float(390.0)),
+                ("height".to_string(), Value::Float(844.0)),
             ]),
             Value::Dict(elements),
         )]
@@ -178,6 +251,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_synthesize_custom_after_title() {
+        let mut examples = create_example(Some("Stats"), Some("Refresh"), None, None);
+        if let Value::Dict(elements) = &mut examples[0].1 {
+            elements.push(("Custom".to_string(), Value::String("MyChartView(data: points)".to_string())));
+        }
+        let ir = synthesize_layout(examples).unwrap();
+
+        match ir {
+            IR::VStack(children) => {
+                assert_eq!(children.len(), 4);
+                assert!(matches!(&children[0], IR::Text(t) if t == "Stats"));
+                assert!(matches!(&children[1], IR::Custom { name, args } if name == "MyChartView" && args == "data: points"));
+                assert!(matches!(&children[2], IR::Spacer));
+                assert!(matches!(&children[3], IR::Button(b) if b == "Refresh"));
+            }
+            _ => panic!("Expected VStack"),
+        }
+    }
+
     #[test]
     fn test_synthesize_image() {
         let examples = create_example(None, None, Some("icon"), None);
@@ -192,4 +285,44 @@ mod tests {
             _ => panic!("Expected VStack"),
         }
     }
+
+    #[test]
+    fn test_synthesize_traced_records_decisions() {
+        let (ir, trace) = synthesize_traced(create_example(Some("Hi"), Some(""), Some("icon"), None)).unwrap();
+        assert_eq!(ir, synthesize_layout(create_example(Some("Hi"), Some(""), Some("icon"), None)).unwrap());
+        assert_eq!(
+            trace,
+            vec![
+                "VStack chosen because the example does not group its elements into a row",
+                "Image \"icon\" placed first, as a header above the other content",
+                "Button omitted because its label is empty",
+                "Spacer inserted last to push the content to the top of the screen",
+            ]
+        );
+
+        let (_, trace) = synthesize_traced(create_example(None, None, None, Some(vec!["A", "Spacer", "B"]))).unwrap();
+        assert_eq!(trace[0], "HStack chosen because the example groups its 3 elements into a row, kept in the order given");
+        assert_eq!(trace[1], "Spacer inserted at index 1 where the example lists \"Spacer\"");
+    }
+
+    #[test]
+    fn test_ignored_input_is_reported() {
+        let mut examples = create_example(Some("Hi"), None, None, Some(vec!["A"]));
+        if let (_, Value::Dict(elements)) = &mut examples[0] {
+            elements.push(("subtitle".to_string(), Value::String("Later".to_string())));
+            if let Some((_, Value::Dict(children))) = elements.iter_mut().find(|(k, _)| k == "HStack") {
+                children.push(("child1".to_string(), Value::Float(3.0)));
+            }
+        }
+        let messages: Vec<String> = ignored_input(&examples).into_iter().map(|warning| warning.message).collect();
+        assert_eq!(
+            messages,
+            [
+                "HStack child 'child1' of example 1 is not a string and is ignored",
+                "Element 'subtitle' of example 1 is not a view synthesis knows and is ignored",
+            ]
+        );
+        assert_eq!(synthesize_layout(examples).unwrap(), IR::HStack(vec![IR::Text("A".to_string())]));
+        assert!(ignored_input(&create_example(Some("Hi"), Some("Go"), Some("logo"), None)).is_empty());
+    }
 }
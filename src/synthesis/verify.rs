@@ -0,0 +1,388 @@
+// File: src/synthesis/verify.rs
+// Checks a layout against examples: every example's elements must be present, the
+// simulated layout must fit on the example's screen, and placement feedback must hold.
+use std::fmt;
+
+use crate::ast::{Example, Value, IR};
+use crate::ast::diff::kind_name;
+use crate::layout::{self, DynamicType, Insets, Metrics};
+use crate::output::render;
+use crate::synthesis::refine;
+
+/// A way in which a layout fails to satisfy one example.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Violation {
+    /// Zero-based index of the example.
+    pub example: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "example {}: {}", self.example + 1, self.message)
+    }
+}
+
+/// The leaf views an example asks for, and whether their order is significant.
+pub fn expected_leaves(example: &Example) -> (Vec<IR>, bool) {
+    let Value::Dict(elements) = &example.1 else { return (Vec::new(), false) };
+    if let Some((_, Value::Dict(children))) = elements.iter().find(|(k, _)| k == "HStack") {
+        let leaves = children
+            .iter()
+            .filter_map(|(_, v)| match v {
+                Value::String(s) if s != "Spacer" => Some(IR::Text(s.clone())),
+                _ => None,
+            })
+            .collect();
+        return (leaves, true);
+    }
+    let separators = separators(example);
+    let leaves = elements
+        .iter()
+        .enumerate()
+        .filter_map(|(index, (k, v))| if separators.contains(&index) { Some(divider()) } else { element_leaf(k, v) })
+        .collect();
+    (leaves, false)
+}
+
+fn divider() -> IR {
+    IR::Custom { name: layout::DIVIDER.to_string(), args: String::new() }
+}
+
+// Whether a `key:value` element has no content of its own: a text or image that is a
+// wildcard, empty, or drawn with rule characters only, e.g. `title:"———"`
+fn is_blank(key: &str, value: &Value) -> bool {
+    matches!(key, "title" | "Image")
+        && match value {
+            Value::Any => true,
+            Value::String(s) => s.chars().all(|c| c.is_whitespace() || "-_=~—–─━".contains(c)),
+            _ => false,
+        }
+}
+
+/// Indices of the elements of `example` that are separator lines, which layouts show as a
+/// `Divider()`: blank texts and images whose stated frame has the shape of one, a hairline
+/// across the screen (see [`layout::StatedFrame::is_separator`]).
+pub fn separators(example: &Example) -> Vec<usize> {
+    let Value::Dict(elements) = &example.1 else { return Vec::new() };
+    let Some(screen) = layout::screen_size(example) else { return Vec::new() };
+    layout::stated_frames(example)
+        .into_iter()
+        .filter(|(index, frame)| frame.is_separator(screen.width) && elements.get(*index).is_some_and(|(k, v)| is_blank(k, v)))
+        .map(|(index, _)| index)
+        .collect()
+}
+
+/// Label standing in for the content of a `key:*` element.
+pub fn placeholder(key: &str) -> Option<&'static str> {
+    match key {
+        "title" => Some("Title"),
+        "button" => Some("Button"),
+        "Image" => Some("placeholder"),
+        _ => None,
+    }
+}
+
+/// The view a `key:"value"` element asks for; an empty button is omitted, and a wildcard
+/// gets a placeholder label. Data under a key of its own is shown by the control made for it:
+/// `enabled:true` by a toggle, `rating:4.5` by a slider and `tags:["a","b"]` by a list.
+pub fn element_leaf(key: &str, value: &Value) -> Option<IR> {
+    let custom = |name: &str, args: String| Some(IR::Custom { name: name.to_string(), args });
+    match (key, value) {
+        (key, Value::Any) => element_leaf(key, &Value::String(placeholder(key)?.to_string())),
+        ("title", Value::String(s)) => Some(IR::Text(s.clone())),
+        ("button", Value::String(s)) if !s.is_empty() => Some(IR::Button(s.clone())),
+        ("Image", Value::String(s)) => Some(IR::Image(s.clone())),
+        ("Custom", Value::String(s)) => IR::custom(s).ok(),
+        (key, Value::Bool(on)) => custom("Toggle", format!("{}, isOn: .constant({})", render::string_literal(&data_label(key)), on)),
+        (_, Value::Int(_) | Value::Float(_)) => {
+            let (literal, x) = (render::value_literal(value)?, value.as_f64()?);
+            // The smallest of 1, 5 and the powers of ten the value fits in
+            let upper = match x.abs() {
+                m if m <= 1.0 => 1.0,
+                m if m <= 5.0 => 5.0,
+                m => 10f64.powi(m.log10().ceil() as i32),
+            };
+            let lower = if x < 0.0 { -upper } else { 0.0 };
+            custom("Slider", format!("value: .constant({}), in: {}...{}", literal, lower, upper))
+        }
+        (_, Value::Array(_)) => custom("List", format!("{}, id: \\.self, rowContent: {{ Text(\"\\($0)\") }}", render::value_literal(value)?)),
+        _ => None,
+    }
+}
+
+// Label of the control showing `key`, in words: `darkMode` is "Dark mode"
+fn data_label(key: &str) -> String {
+    let mut label = String::new();
+    for (i, c) in key.replace('_', " ").chars().enumerate() {
+        match c {
+            _ if i == 0 => label.push(c.to_ascii_uppercase()),
+            c if c.is_ascii_uppercase() => {
+                label.push(' ');
+                label.push(c.to_ascii_lowercase());
+            }
+            c => label.push(c),
+        }
+    }
+    label
+}
+
+// Kinds of view an example asks for without caring about their content (`title:*`).
+// Separators are dividers whatever their content.
+fn wildcard_leaves(example: &Example) -> Vec<IR> {
+    let Value::Dict(elements) = &example.1 else { return Vec::new() };
+    let separators = separators(example);
+    elements
+        .iter()
+        .enumerate()
+        .filter(|(index, (_, v))| *v == Value::Any && !separators.contains(index))
+        .filter_map(|(_, (k, v))| element_leaf(k, v))
+        .collect()
+}
+
+/// Copy of `examples` whose first example, which layouts are built from, has each wildcard
+/// filled in from the same element of a later example, e.g. the second `title` from the
+/// second `title` of another example. Wildcards no example fills in keep their placeholder.
+pub fn fill_wildcards(examples: &[Example]) -> Vec<Example> {
+    let mut filled = examples.to_vec();
+    let separators = examples.first().map(separators).unwrap_or_default();
+    let Some((first, rest)) = filled.split_first_mut() else { return filled };
+    let Value::Dict(elements) = &mut first.1 else { return filled };
+    for index in 0..elements.len() {
+        if elements[index].1 != Value::Any || separators.contains(&index) {
+            continue;
+        }
+        let key = elements[index].0.clone();
+        let nth = elements[..index].iter().filter(|(k, _)| *k == key).count();
+        let known = rest.iter().find_map(|example| match &example.1 {
+            Value::Dict(other) => match other.iter().filter(|(k, _)| *k == key).nth(nth) {
+                Some((_, value @ Value::String(_))) => Some(value.clone()),
+                _ => None,
+            },
+            _ => None,
+        });
+        if let Some(value) = known {
+            elements[index].1 = value;
+        }
+    }
+    filled
+}
+
+/// Leaf views of an example that state where they should appear, with that frame.
+pub fn expected_frames(example: &Example) -> Vec<(IR, layout::StatedFrame)> {
+    let Value::Dict(elements) = &example.1 else { return Vec::new() };
+    let separators = separators(example);
+    layout::stated_frames(example)
+        .into_iter()
+        .filter_map(|(index, frame)| {
+            let (key, value) = elements.get(index)?;
+            let leaf = if separators.contains(&index) { divider() } else { element_leaf(key, value)? };
+            Some((leaf, frame))
+        })
+        .collect()
+}
+
+/// Short human-readable name of a node, e.g. `Button "Go"`.
+pub(crate) fn describe(node: &IR) -> String {
+    match node {
+        IR::Text(s) | IR::Button(s) | IR::Image(s) => format!("{} \"{}\"", kind_name(node), s),
+        IR::Custom { name, args } => format!("{}({})", name, args),
+        other => kind_name(other).to_string(),
+    }
+}
+
+/// Returns every violation of `examples` by `ir`; an empty list means the layout satisfies them all.
+pub fn verify(ir: &IR, examples: &[Example], metrics: &Metrics) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let ignores_safe_area = layout::ignores_safe_area(examples);
+    for (index, example) in examples.iter().enumerate() {
+        let mut report = |message: String| violations.push(Violation { example: index, message });
+        // Text grows with the example's Dynamic Type size
+        let dynamic_type = layout::dynamic_type(example);
+        let metrics = &metrics.scaled(dynamic_type.scale());
+        let Some(screen) = layout::screen_for(ir, example, metrics) else {
+            report("missing width/height dimensions".to_string());
+            continue;
+        };
+        let insets = if ignores_safe_area { Insets::default() } else { layout::safe_area(example) };
+        let simulated = layout::simulate_within(ir, screen, insets, metrics).with_direction(layout::layout_direction(example));
+        let actual: Vec<IR> = simulated.leaves().map(|frame| frame.node.clone()).collect();
+        let (expected, ordered) = expected_leaves(example);
+        let wildcards = wildcard_leaves(example);
+
+        // Views with known content are matched first; wildcards then take any view of their kind
+        let mut unmatched = actual.clone();
+        let mut skipped = wildcards.clone();
+        for node in &expected {
+            if let Some(pos) = skipped.iter().position(|w| w == node) {
+                skipped.remove(pos);
+                continue;
+            }
+            match unmatched.iter().position(|n| n == node) {
+                Some(pos) => {
+                    unmatched.remove(pos);
+                }
+                None => report(format!("{} is missing from the layout", describe(node))),
+            }
+        }
+        for wildcard in &wildcards {
+            match unmatched.iter().position(|n| kind_name(n) == kind_name(wildcard)) {
+                Some(pos) => {
+                    unmatched.remove(pos);
+                }
+                None => report(format!("a {} is missing from the layout", kind_name(wildcard))),
+            }
+        }
+        for node in &unmatched {
+            report(format!("{} is not part of the example", describe(node)));
+        }
+        if ordered && unmatched.is_empty() && actual.len() == expected.len() && actual != expected {
+            report("elements appear in a different order than in the example".to_string());
+        }
+
+        if simulated.overflows() {
+            let (needed, bounds) = (simulated.required, simulated.bounds());
+            let area = if insets == Insets::default() { "screen" } else { "safe area" };
+            // At a larger text size the content may scroll, and text shrink a little, to fit;
+            // see `layout::text_fit`
+            let resized = dynamic_type != DynamicType::default();
+            if needed.height > bounds.height && !resized {
+                report(format!(
+                    "content needs {:.0}pt of height but the {} is {:.0}pt tall",
+                    needed.height, area, bounds.height
+                ));
+            }
+            if needed.width > bounds.width && !(resized && bounds.width / needed.width >= layout::MINIMUM_SCALE_FACTOR) {
+                report(format!(
+                    "content needs {:.0}pt of width but the {} is {:.0}pt wide",
+                    needed.width, area, bounds.width
+                ));
+            }
+        }
+
+        // Feedback from earlier rounds, see `refine`
+        for constraint in refine::constraints(example) {
+            if let Some(message) = constraint.check(&simulated) {
+                report(message);
+            }
+        }
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_examples;
+
+    fn layout(spec: &str) -> (IR, Vec<Example>) {
+        let examples = parse_examples(spec).unwrap();
+        let ir = crate::synthesis::swiftui::synthesize_layout(examples.clone()).unwrap();
+        (ir, examples)
+    }
+
+    #[test]
+    fn test_synthesized_layout_satisfies_its_examples() {
+        let (ir, examples) = layout(
+            "{(width:390,height:844):{Image:\"logo\",title:\"Hello\",button:\"Go\"}}
+             {(width:320,height:568):{Image:\"logo\",title:\"Hello\",button:\"Go\"}}",
+        );
+        assert_eq!(verify(&ir, &examples, &Metrics::default()), vec![]);
+    }
+
+    #[test]
+    fn test_data_is_shown_by_controls() {
+        let (ir, examples) = layout(r#"{(width:390,height:844):{darkMode:true, rating:4.5, volume:-30, tags:["a", "b"]}}"#);
+        let code = render::render_swiftui(&ir);
+        let views: Vec<&str> = code.lines().map(str::trim).filter(|line| line.starts_with(['T', 'S', 'L']) && line != &"Spacer()").collect();
+        assert_eq!(
+            views,
+            [
+                "Toggle(\"Dark mode\", isOn: .constant(true))",
+                "Slider(value: .constant(4.5), in: 0...5)",
+                "Slider(value: .constant(-30), in: -100...100)",
+                r#"List(["a", "b"], id: \.self, rowContent: { Text("\($0)") })"#,
+            ]
+        );
+        assert_eq!(verify(&ir, &examples, &Metrics::default()), vec![]);
+    }
+
+    #[test]
+    fn test_reports_missing_and_extra_elements() {
+        let (_, examples) = layout("{(width:390,height:844):{title:\"Hello\",button:\"Go\"}}");
+        let stale = IR::VStack(vec![IR::Text("Hello".to_string()), IR::Spacer, IR::Button("Continue".to_string())]);
+        let messages: Vec<String> = verify(&stale, &examples, &Metrics::default()).iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "example 1: Button \"Go\" is missing from the layout".to_string(),
+                "example 1: Button \"Continue\" is not part of the example".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_reports_hstack_order_and_overflow() {
+        let (_, examples) = layout("{(width:390,height:844):HStack:{\"A\",\"Spacer\",\"B\"}}");
+        let swapped = IR::HStack(vec![IR::Text("B".to_string()), IR::Spacer, IR::Text("A".to_string())]);
+        assert_eq!(
+            verify(&swapped, &examples, &Metrics::default())[0].message,
+            "elements appear in a different order than in the example"
+        );
+
+        let long = "word ".repeat(60);
+        let spec = format!("{{(width:320,height:200):{{title:\"{}\"}}}}", long.trim());
+        let (ir, examples) = layout(&spec);
+        let violations = verify(&ir, &examples, &Metrics::default());
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].message.contains("but the screen is 200pt tall"));
+    }
+
+    #[test]
+    fn test_large_text_may_scroll_but_must_keep_its_elements() {
+        let title = "Welcome to the application home screen";
+        let spec = |size: &str| format!("{{(width:390,height:400{}):{{title:\"{}\",button:\"Go\"}}}}", size, title);
+        let (ir, examples) = layout(&spec(",dynamicType:accessibility5"));
+        assert!(verify(&ir, &examples, &Metrics::default()).is_empty());
+        assert!(layout::text_fit(&ir, &examples, &Metrics::default()).scrolls);
+        let (_, unscaled) = layout(&spec(""));
+        assert!(!layout::text_fit(&ir, &unscaled, &Metrics::default()).scrolls);
+
+        let missing = IR::VStack(vec![IR::Text(title.to_string())]);
+        assert_eq!(verify(&missing, &examples, &Metrics::default())[0].message, "Button \"Go\" is missing from the layout");
+    }
+
+    #[test]
+    fn test_separator_lines_are_dividers() {
+        let examples = parse_examples(
+            "{(width:390,height:844):{title:\"Hi\",title:*@(w:390,h:1),Image:\"———\"@(w:\"90%\",h:0.5),title:\"Bye\"@(w:390,h:1),title:\"\"@(w:100,h:1)}}",
+        )
+        .unwrap();
+        // Blank and ruled elements across the screen; not ones with text, or too short
+        assert_eq!(separators(&examples[0]), [1, 2]);
+        let (leaves, _) = expected_leaves(&examples[0]);
+        assert_eq!(leaves[1..3], [divider(), divider()]);
+        assert_eq!(leaves[3], IR::Text("Bye".to_string()));
+        assert!(wildcard_leaves(&examples[0]).is_empty());
+        assert_eq!(expected_frames(&examples[0])[0].0, divider());
+    }
+
+    #[test]
+    fn test_wildcards_match_any_content() {
+        let (ir, examples) = layout("{(width:390,height:?):{title:*,button:\"Go\"}}");
+        assert_eq!(ir, IR::VStack(vec![IR::Text("Title".to_string()), IR::Spacer, IR::Button("Go".to_string())]));
+        assert!(verify(&ir, &examples, &Metrics::default()).is_empty());
+        let named = IR::VStack(vec![IR::Text("Welcome".to_string()), IR::Button("Go".to_string())]);
+        assert!(verify(&named, &examples, &Metrics::default()).is_empty());
+        let missing = IR::VStack(vec![IR::Button("Go".to_string())]);
+        assert_eq!(verify(&missing, &examples, &Metrics::default())[0].message, "a Text is missing from the layout");
+
+        let examples = parse_examples(
+            "{(width:390,height:844):{title:*,title:*,Image:*}} {(width:320,height:568):{title:\"A\",title:\"B\"}}",
+        )
+        .unwrap();
+        let (leaves, _) = expected_leaves(&fill_wildcards(&examples)[0]);
+        let image = IR::Image("placeholder".to_string());
+        assert_eq!(leaves, vec![IR::Text("A".to_string()), IR::Text("B".to_string()), image]);
+    }
+}
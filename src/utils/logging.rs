@@ -0,0 +1,158 @@
+// File: src/utils/logging.rs
+// What the CLI reports on stderr besides the code it writes: warnings, notes and, with `-v`,
+// debug events inside the `parse`, `synthesize` and `render` spans, as text for a terminal or
+// JSON lines (`--log-format json`) for CI and log collectors.
+use std::fmt;
+use std::str::FromStr;
+
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::fmt::format::{FmtSpan, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields, MakeWriter};
+use tracing_subscriber::registry::LookupSpan;
+
+/// How `--log-format` writes events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One line per event, as the CLI has always printed warnings: `Warning: ...`.
+    #[default]
+    Text,
+    /// One JSON object per event, with its level, fields and spans, and one per span closed
+    /// with the time spent in it.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            other => Err(format!("Unknown log format '{}': expected text or json", other)),
+        }
+    }
+}
+
+/// The most detailed level shown: warnings, errors and notes by default, debug events with
+/// `-v` and trace events with `-vv`. `-q` quiets stdout, not these: a note may be all that
+/// explains a failure.
+pub fn level(verbose: u8) -> Level {
+    match verbose {
+        0 => Level::INFO,
+        1 => Level::DEBUG,
+        _ => Level::TRACE,
+    }
+}
+
+// Text events: warnings, notes and errors as plain sentences; debug and trace events name
+// the spans they happened in, e.g. `debug synthesize{screen=LoginView} render: ...`
+struct Plain;
+
+impl<S, N> FormatEvent<S, N> for Plain
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        match *event.metadata().level() {
+            Level::ERROR => write!(writer, "Error: ")?,
+            Level::WARN => write!(writer, "Warning: ")?,
+            Level::INFO => write!(writer, "Note: ")?,
+            level => {
+                write!(writer, "{}", level.as_str().to_lowercase())?;
+                for span in ctx.event_scope().into_iter().flat_map(|scope| scope.from_root()) {
+                    write!(writer, " {}", span.name())?;
+                    if let Some(fields) = span.extensions().get::<FormattedFields<N>>().filter(|fields| !fields.is_empty()) {
+                        write!(writer, "{{{}}}", fields)?;
+                    }
+                }
+                write!(writer, ": ")?;
+            }
+        }
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// A subscriber writing events up to `level` in `format` to `writer`.
+pub fn subscriber<W>(level: Level, format: LogFormat, writer: W) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    let builder = tracing_subscriber::fmt().with_max_level(level).with_writer(writer);
+    match format {
+        LogFormat::Text => Box::new(builder.event_format(Plain).finish()),
+        LogFormat::Json => {
+            Box::new(builder.json().with_current_span(true).with_span_list(true).with_span_events(FmtSpan::CLOSE).finish())
+        }
+    }
+}
+
+/// Sends the process's events to stderr; later calls have no effect.
+pub fn init(level: Level, format: LogFormat) {
+    let _ = tracing::subscriber::set_global_default(subscriber(level, format, std::io::stderr));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl io::Write for Buffer {
+        fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(bytes)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn capture(level: Level, format: LogFormat) -> String {
+        let buffer = Buffer::default();
+        let writer = buffer.clone();
+        tracing::subscriber::with_default(subscriber(level, format, move || writer.clone()), || {
+            tracing::warn!("the cache is full");
+            let _span = tracing::info_span!("synthesize", screen = "LoginView").entered();
+            tracing::info!("examples 1 and 2 contradict each other");
+            tracing::debug!(candidates = 12, "searched");
+        });
+        let bytes = buffer.0.lock().unwrap().clone();
+        String::from_utf8(bytes).unwrap()
+    }
+
+    #[test]
+    fn test_verbosity_levels() {
+        assert_eq!(level(0), Level::INFO);
+        assert_eq!(level(1), Level::DEBUG);
+        assert_eq!(level(3), Level::TRACE);
+        assert_eq!("json".parse(), Ok(LogFormat::Json));
+        assert!("yaml".parse::<LogFormat>().unwrap_err().contains("expected text or json"));
+    }
+
+    #[test]
+    fn test_text_events_read_as_before() {
+        assert_eq!(capture(Level::INFO, LogFormat::Text), "Warning: the cache is full\nNote: examples 1 and 2 contradict each other\n");
+        assert_eq!(capture(Level::WARN, LogFormat::Text), "Warning: the cache is full\n");
+        let verbose = capture(Level::DEBUG, LogFormat::Text);
+        assert!(verbose.ends_with("debug synthesize{screen=\"LoginView\"}: searched candidates=12\n"), "{}", verbose);
+    }
+
+    #[test]
+    fn test_json_events_carry_their_spans() {
+        let lines: Vec<serde_json::Value> =
+            capture(Level::DEBUG, LogFormat::Json).lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines[0]["level"], "WARN");
+        assert_eq!(lines[0]["fields"]["message"], "the cache is full");
+        assert_eq!(lines[2]["fields"]["candidates"], 12);
+        assert_eq!(lines[2]["span"]["screen"], "LoginView");
+        assert_eq!(lines[2]["spans"][0]["name"], "synthesize");
+        // Closing the span reports the time spent in it
+        assert_eq!(lines[3]["fields"]["message"], "close");
+        assert!(lines[3]["fields"]["time.busy"].is_string());
+    }
+}
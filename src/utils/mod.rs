@@ -1 +1,3 @@
+pub mod logging;
+pub mod process;
 pub mod profiler;
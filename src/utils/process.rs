@@ -0,0 +1,10 @@
+// File: src/utils/process.rs
+// Finding the external programs that checks and optional features run, such as `swiftc`,
+// `tesseract` and `curl`.
+use std::path::PathBuf;
+
+/// The path of `program` in a directory of the `PATH`, if it is in one.
+pub fn on_path(program: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path).map(|dir| dir.join(program)).find(|candidate| candidate.is_file())
+}
@@ -1 +1,88 @@
-// Placeholder for future profiling utilities.
+// File: src/utils/profiler.rs
+// Per-phase wall-clock timings and peak memory for `--stats`.
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct Phase {
+    pub name: &'static str,
+    #[serde(rename = "ms", serialize_with = "as_millis")]
+    pub elapsed: Duration,
+}
+
+fn as_millis<S: serde::Serializer>(elapsed: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(elapsed.as_secs_f64() * 1000.0)
+}
+
+/// Records how long each named phase of a run takes.
+#[derive(Clone, Debug, Default)]
+pub struct Profiler {
+    phases: Vec<Phase>,
+}
+
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+
+    /// Runs `f`, recording its duration under `name`. Repeated names accumulate. Each run is
+    /// also a trace event, for `-vv`.
+    pub fn time<T>(&mut self, name: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        let elapsed = start.elapsed();
+        tracing::trace!(phase = name, ?elapsed, "timed a phase");
+        match self.phases.iter_mut().find(|phase| phase.name == name) {
+            Some(phase) => phase.elapsed += elapsed,
+            None => self.phases.push(Phase { name, elapsed }),
+        }
+        result
+    }
+
+    /// Phases in the order they first ran.
+    pub fn phases(&self) -> &[Phase] {
+        &self.phases
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|phase| phase.elapsed).sum()
+    }
+}
+
+/// Peak resident set size of this process in bytes, where the platform reports it.
+pub fn peak_memory_bytes() -> Option<u64> {
+    // Linux exposes the high-water mark as `VmHWM:   1234 kB`
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_accumulate_in_order() {
+        let mut profiler = Profiler::new();
+        assert_eq!(profiler.time("parse", || 1 + 1), 2);
+        profiler.time("search", || std::thread::sleep(Duration::from_millis(2)));
+        profiler.time("parse", || ());
+        let names: Vec<&str> = profiler.phases().iter().map(|phase| phase.name).collect();
+        assert_eq!(names, vec!["parse", "search"]);
+        assert!(profiler.phases()[1].elapsed >= Duration::from_millis(2));
+        assert!(profiler.total() >= Duration::from_millis(2));
+    }
+
+    #[test]
+    fn test_phase_serializes_as_milliseconds() {
+        let phase = Phase { name: "render", elapsed: Duration::from_micros(1500) };
+        assert_eq!(serde_json::to_string(&phase).unwrap(), r#"{"name":"render","ms":1.5}"#);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_peak_memory_on_linux() {
+        assert!(peak_memory_bytes().unwrap() > 0);
+    }
+}
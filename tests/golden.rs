@@ -0,0 +1,195 @@
+// File: tests/golden.rs
+// Golden snapshot tests: runs the CLI on every `tests/golden/<name>.spec` and compares what it
+// prints and writes with `tests/golden/<name>.out`. A spec whose first line is
+// `// args: --json` is run with those options after `synth <name>.spec`; `{out}` in them
+// stands for an empty directory, whose files are appended to the snapshot.
+//
+//     cargo test --test golden                    # check every snapshot
+//     cargo test --test golden -- json            # only cases whose name contains `json`
+//     cargo test --test golden -- --bless         # accept the current output as the snapshots
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitCode};
+
+const ARGS_HEADER: &str = "// args:";
+const BEGIN_MARKER: &str = "// swiftui-synth:begin";
+
+struct Case {
+    name: String,
+    spec: PathBuf,
+    snapshot: PathBuf,
+}
+
+// Every `.spec` in `dir`, with the snapshot it is checked against, by name
+fn cases(dir: &Path) -> Vec<Case> {
+    let mut cases: Vec<Case> = fs::read_dir(dir)
+        .expect("tests/golden is missing")
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "spec"))
+        .map(|spec| Case {
+            name: spec.file_stem().unwrap().to_string_lossy().into_owned(),
+            snapshot: spec.with_extension("out"),
+            spec,
+        })
+        .collect();
+    cases.sort_by(|a, b| a.name.cmp(&b.name));
+    cases
+}
+
+// Replaces what changes from run to run: timings, the directory given as `{out}`, and the
+// hashes of generated sections, which cover the command line naming that directory
+fn redact(text: &str, out_dir: &Path) -> String {
+    let text = text.replace(&out_dir.display().to_string(), "{out}");
+    let text: String = text
+        .split_inclusive('\n')
+        .map(|line| match line.strip_prefix(BEGIN_MARKER) {
+            Some(_) => format!("{} [hash]\n", BEGIN_MARKER),
+            None => line.to_string(),
+        })
+        .collect();
+    let mut redacted = String::with_capacity(text.len());
+    let mut rest = text.as_str();
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        redacted.push_str(&rest[..start]);
+        let number = rest[start..].find(|c: char| !(c.is_ascii_digit() || c == '.')).map_or(rest.len(), |len| start + len);
+        let after = &rest[number..];
+        let unit = ["ns", "µs", "ms", "s"].into_iter().find(|unit| {
+            after.starts_with(unit) && !after[unit.len()..].starts_with(|c: char| c.is_alphanumeric())
+        });
+        let json_timing = redacted.ends_with("\"elapsed_ms\": ");
+        let preceded = redacted.ends_with(|c: char| c.is_alphanumeric() || c == '_');
+        match unit {
+            Some(unit) if !preceded => {
+                redacted.push_str("[time]");
+                rest = &after[unit.len()..];
+            }
+            _ if json_timing => {
+                redacted.push_str("\"[time]\"");
+                rest = after;
+            }
+            _ => {
+                redacted.push_str(&rest[start..number]);
+                rest = after;
+            }
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+// Runs the CLI on the case and renders everything it produced as one snapshot
+fn run(case: &Case, out_dir: &Path) -> String {
+    let spec = fs::read_to_string(&case.spec).unwrap();
+    let header = spec.lines().next().and_then(|line| line.strip_prefix(ARGS_HEADER)).unwrap_or_default();
+    let out = out_dir.display().to_string();
+    let args: Vec<String> = header.split_whitespace().map(|arg| arg.replace("{out}", &out)).collect();
+    let output = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+        .current_dir(case.spec.parent().unwrap())
+        .arg("synth")
+        .arg(case.spec.file_name().unwrap())
+        .arg("--no-cache")
+        .args(&args)
+        .env("SWIFTUI_SYNTH_CACHE_DIR", out_dir.join(".cache"))
+        .output()
+        .expect("failed to start swiftui-synth");
+
+    let mut snapshot = String::from_utf8_lossy(&output.stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if !stderr.is_empty() {
+        snapshot.push_str(&format!("--- stderr ---\n{}", stderr));
+    }
+    if !output.status.success() {
+        snapshot.push_str("--- failed ---\n");
+    }
+    let mut files: Vec<PathBuf> = fs::read_dir(out_dir).unwrap().filter_map(|entry| entry.ok().map(|entry| entry.path())).filter(|path| path.is_file()).collect();
+    // The build manifest fingerprints files by their real paths, which differ every run
+    files.retain(|path| !path.ends_with(".swiftui-synth-build.json"));
+    files.sort();
+    for file in files {
+        snapshot.push_str(&format!("--- {} ---\n{}", file.file_name().unwrap().to_string_lossy(), fs::read_to_string(&file).unwrap()));
+    }
+    redact(&snapshot, out_dir)
+}
+
+// Line diff of two snapshots, `-` for expected lines missing and `+` for lines not expected
+fn diff(expected: &str, actual: &str) -> String {
+    let (a, b): (Vec<&str>, Vec<&str>) = (expected.lines().collect(), actual.lines().collect());
+    // Longest common subsequence of lines, filled from the end
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+        }
+    }
+    let (mut i, mut j, mut out) = (0, 0, String::new());
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            out.push_str(&format!("  {}\n", a[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if i < a.len() && (j == b.len() || lcs[i + 1][j] >= lcs[i][j + 1]) {
+            out.push_str(&format!("- {}\n", a[i]));
+            i += 1;
+        } else {
+            out.push_str(&format!("+ {}\n", b[j]));
+            j += 1;
+        }
+    }
+    out
+}
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let (mut bless, mut filters) = (false, Vec::new());
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bless" => bless = true,
+            // Options libtest takes a value for
+            "--skip" | "--test-threads" | "--format" | "--color" => {
+                args.next();
+            }
+            _ if arg.starts_with('-') => {}
+            _ => filters.push(arg),
+        }
+    }
+
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden");
+    let cases: Vec<Case> = cases(&dir)
+        .into_iter()
+        .filter(|case| filters.is_empty() || filters.iter().any(|filter| case.name.contains(filter.as_str())))
+        .collect();
+    println!("\nrunning {} golden cases", cases.len());
+    let mut failed = Vec::new();
+    for case in &cases {
+        let out_dir = std::env::temp_dir().join(format!("swiftui-synth-golden-{}-{}", std::process::id(), case.name));
+        let _ = fs::remove_dir_all(&out_dir);
+        fs::create_dir_all(&out_dir).unwrap();
+        let actual = run(case, &out_dir);
+        let _ = fs::remove_dir_all(&out_dir);
+
+        let expected = fs::read_to_string(&case.snapshot).ok();
+        let status = match expected {
+            Some(expected) if expected == actual => "ok",
+            _ if bless => {
+                fs::write(&case.snapshot, &actual).unwrap();
+                "blessed"
+            }
+            Some(expected) => {
+                failed.push(format!("{}:\n{}", case.name, diff(&expected, &actual)));
+                "FAILED"
+            }
+            None => {
+                failed.push(format!("{}: no snapshot at {}", case.name, case.snapshot.display()));
+                "FAILED"
+            }
+        };
+        println!("golden {} ... {}", case.name, status);
+    }
+    if failed.is_empty() {
+        println!("\ngolden result: ok. {} passed\n", cases.len());
+        return ExitCode::SUCCESS;
+    }
+    println!("\nfailures:\n\n{}", failed.join("\n"));
+    println!("accept intended changes with `cargo test --test golden -- --bless`\n");
+    println!("golden result: FAILED. {} passed; {} failed\n", cases.len() - failed.len(), failed.len());
+    ExitCode::FAILURE
+}
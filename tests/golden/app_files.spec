@@ -0,0 +1,4 @@
+// args: --out-dir {out} --app tabs
+Login:{(width:390,height:844):{title:"Welcome",button:"Sign in"}}
+Settings:{(width:390,height:844):{title:"Settings"}}
+flows:{Login.button -> Settings}
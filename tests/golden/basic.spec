@@ -0,0 +1,2 @@
+// args: -q
+{(width:390,height:844):{Image:"logo", title:"Welcome back",button:"Sign in"}}
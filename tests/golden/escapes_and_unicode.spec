@@ -0,0 +1,2 @@
+// args: -q
+{(width:390,height:844):{title:"Say \"hi\"\nto 日本 \u{1F44B}", button:"C:\\ ✅"}}
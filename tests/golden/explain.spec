@@ -0,0 +1,2 @@
+// args: -q --explain
+{(width:390,height:844):{title:"Hello"@(y:"10%"),button:"Continue"}}
@@ -0,0 +1,2 @@
+// args: -q
+{(width:390,height:844):HStack:{"Cancel", "Save"}}
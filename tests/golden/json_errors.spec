@@ -0,0 +1,2 @@
+// args: --json
+{(width:390,height:844):{title:"Hi", Imag:"logo"}}
@@ -0,0 +1,2 @@
+// args: -q
+{(width:390,height:844):{title:Welcome, RatingView:4.5, TagList:["new", "sale"]}}
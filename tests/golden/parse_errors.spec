@@ -0,0 +1,3 @@
+{(width:390,height:844):{title:"Hi", subtitle:"x"}}
+{(width:abc,height:844):{title:"Hi"}}
+{(width:390,height:844):{title:"Hi"@(z:1), button:"a\qb"}}
@@ -0,0 +1,2 @@
+// args: -q --post-format --config narrow.toml
+{(width:390,height:844):{title:"Welcome", Custom:"RatingView(value: 4, maximum: 5, tint: .orange)", button:"Continue"}}
@@ -0,0 +1,4 @@
+// Banner timings are redacted in the snapshot
+Login:{(width:390,height:844):{title:"Welcome",button:"Sign in"}}
+Home:{(device:"iPhone 15", orientation:landscape):{title:"Home"}}
+flows:{Login.button -> Home}
@@ -6,13 +6,17 @@
 use swiftui_synth::input::parser::parse_examples;
 use swiftui_synth::synthesis::swiftui::synthesize_layout;
 use swiftui_synth::output::render::render_swiftui;
+use swiftui_synth::input::swift::parse_swiftui;
+use swiftui_synth::ast::diff::diff;
+use std::io::Write;
+use std::process::{Command, Stdio};
 
 // --- Helper Functions ---
 
 // Helper to run the core logic (parse -> synthesize -> render) for a given input string.
 // Returns the rendered SwiftUI code or an error string.
 fn process_example(input: &str) -> Result<String, String> {
-    let examples = parse_examples(input)?; // Propagate parsing errors
+    let examples = parse_examples(input).map_err(|d| d.to_string())?; // Propagate parsing errors
     let ir = synthesize_layout(examples)
         .ok_or_else(|| "Failed to synthesize layout".to_string())?; // Handle synthesis failure
     Ok(render_swiftui(&ir)) // Render the IR
@@ -80,8 +84,8 @@ fn test_end_to_end_error_propagation() {
     // Test invalid dimension value
     assert!(process_example("{(width:abc,height:844):{title:\"Hello\"}}").is_err());
 
-    // Test unsupported element key
-    assert!(process_example("{(width:390,height:844):{TextField:\"placeholder\"}}").is_err());
+    // Test unsupported element key (one named like a view would be taken as a custom view)
+    assert!(process_example("{(width:390,height:844):{textField:\"placeholder\"}}").is_err());
 }
 
 #[test]
@@ -206,4 +210,1720 @@ fn test_end_to_end_image_title_button() {
     );
 
     assert_eq!(normalize_whitespace(&result), expected);
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_end_to_end_diff_regenerated_output() {
+    // Previously committed output vs. output regenerated from an edited spec
+    let committed = process_example("{(width:390,height:844):{title:\"Hello\",button:\"Go\"}}").unwrap();
+    let regenerated = process_example("{(width:390,height:844):{Image:\"logo\", title:\"Hello\",button:\"Next\"}}").unwrap();
+
+    let changes: Vec<String> = diff(&parse_swiftui(&committed).unwrap(), &parse_swiftui(&regenerated).unwrap())
+        .iter()
+        .map(|c| c.to_string())
+        .collect();
+
+    assert_eq!(
+        changes,
+        vec![
+            "Image inserted at index 0".to_string(),
+            "Button label changed at index 3: \"Go\" -> \"Next\"".to_string(),
+        ]
+    );
+}
+
+// Runs the CLI binary with `stdin` piped in and returns (stdout, stderr, success).
+fn run_cli(args: &[&str], stdin: &str) -> (String, String, bool) {
+    let cache_dir = std::env::temp_dir().join(format!("swiftui-synth-test-cache-{}", std::process::id()));
+    let mut child = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+        .args(args)
+        .env("SWIFTUI_SYNTH_CACHE_DIR", cache_dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to start swiftui-synth");
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+        output.status.success(),
+    )
+}
+
+#[test]
+fn test_cli_stdin_pipeline_mode() {
+    let spec = "{(width:390,height:844):{title:\"Hello\"}}";
+    let expected = process_example(spec).unwrap() + "\n";
+
+    // `synth -` with --quiet prints only the code
+    let (stdout, _, ok) = run_cli(&["synth", "-", "--quiet"], spec);
+    assert!(ok);
+    assert_eq!(stdout, expected);
+
+    // `--examples - -o -` is equivalent
+    let (stdout, _, ok) = run_cli(&["--examples", "-", "-o", "-"], spec);
+    assert!(ok);
+    assert_eq!(stdout, expected);
+
+    // Errors go to stderr, keeping stdout clean
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q"], "{(width:390,height:844):{titel:\"x\"}}");
+    assert!(!ok);
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("error[E0012]"));
+    assert!(stderr.contains("<stdin>:1:26"));
+}
+
+#[test]
+fn test_cli_reads_commented_multi_line_specs() {
+    let spec = "# Greeting\n{\n  (width: 390, height: 844):   // iPhone 14\n  { title: \"Hello # 1\" }\n}\n";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q"], spec);
+    assert!(ok, "{}", stderr);
+    assert_eq!(stdout, process_example("{(width:390,height:844):{title:\"Hello # 1\"}}").unwrap() + "\n");
+
+    // Diagnostics still point into the text as written
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q"], "// header\n{(width:390,height:844): # size\n  {titel:\"x\"}}");
+    assert!(!ok);
+    assert!(stderr.contains("<stdin>:3:4"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_escapes_survive_into_swift_literals() {
+    let spec = r#"{(width:390,height:844):{title:"Welcome\nback \u{1F44B}", button:"Say \"hi\""}}"#;
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q"], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(r#"Text("Welcome\nback 👋")"#), "{}", stdout);
+    assert!(stdout.contains(r#"Button("Say \"hi\"")"#), "{}", stdout);
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q"], r#"{(width:390,height:844):{title:"C:\Users"}}"#);
+    assert!(!ok);
+    assert!(stderr.contains("error[E0028]: Unknown escape sequence '\\U'"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_shows_data_with_controls() {
+    let spec = r#"{(width:390,height:844):{title:"Settings", rating:4.5, enabled:true, tags:["a","b"]}}"#;
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--strict"], spec);
+    assert!(ok, "{}", stderr);
+    assert!(!stderr.contains("W0008"), "{}", stderr);
+    assert!(stdout.contains("Slider(value: .constant(4.5), in: 0...5)"), "{}", stdout);
+    assert!(stdout.contains(r#"Toggle("Enabled", isOn: .constant(true))"#), "{}", stdout);
+    assert!(stdout.contains(r#"List(["a", "b"], id: \.self, rowContent: { Text("\($0)") })"#), "{}", stdout);
+}
+
+#[test]
+fn test_cli_reports_every_parse_error() {
+    let spec = "{(width:390,height:844):{titel:\"Hi\"}}\n{(width:320,height:abc):{title:\"Hi\"}}\n{(width:320,height:568):{title:\"Hi\"@(z:1)}}";
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q"], spec);
+    assert!(!ok);
+    let codes: Vec<&str> = stderr.lines().filter_map(|line| line.strip_prefix("error[")).map(|rest| &rest[..5]).collect();
+    assert_eq!(codes, vec!["E0012", "E0007", "E0018"], "{}", stderr);
+    assert!(stderr.contains("<stdin>:3:"), "{}", stderr);
+
+    let (stdout, _, ok) = run_cli(&["synth", "-", "--json"], spec);
+    assert!(!ok);
+    assert_eq!(stdout.matches("\"severity\": \"error\"").count(), 3, "{}", stdout);
+}
+
+#[test]
+fn test_cli_expands_bindings_across_screens() {
+    let spec = "let go = \"Continue\"\nlet header = { Image:\"logo\", title:\"Acme\" }\n\
+                Login:{(width:390,height:844):{@include header, button:go}}\nHome:{(width:390,height:844):{@include header}}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], spec);
+    assert!(ok, "{}", stderr);
+    assert_eq!(stdout.matches("Image(\"logo\")").count(), 2);
+    assert!(stdout.contains("Button(\"Continue\")"));
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q"], "{(width:390,height:844):{@include header}}");
+    assert!(!ok);
+    assert!(stderr.contains("error[E0027]: Unknown block 'header'"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_check_against_swift_file() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-check-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let swift = dir.join("ContentView.swift");
+    let spec = "{(width:390,height:844):{title:\"Hello\",button:\"Go\"}}\n{(width:320,height:568):{title:\"Hello\",button:\"Go\"}}";
+    std::fs::write(&swift, process_example(spec).unwrap()).unwrap();
+    let swift = swift.to_str().unwrap();
+
+    let (stdout, _, ok) = run_cli(&["check", "--examples", "-", "--against", swift], spec);
+    assert!(ok);
+    assert!(stdout.contains("satisfies all 2 examples"));
+
+    // The committed code has drifted from the examples
+    let changed = spec.replace("\"Go\"", "\"Continue\"");
+    let (stdout, stderr, ok) = run_cli(&["check", "--examples", "-", "--against", swift], &changed);
+    assert!(!ok);
+    assert!(stdout.contains("example 1: Button \"Continue\" is missing from the layout"));
+    assert!(stdout.contains("example 2: Button \"Go\" is not part of the example"));
+    assert!(stderr.contains("does not satisfy the examples (4 problems)"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_budget_exhaustion_prints_best_effort() {
+    let spec = format!("{{(width:320,height:200):{{title:\"{}\",button:\"Go\"}}}}", "word ".repeat(80).trim());
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--max-candidates", "2"], &spec);
+    assert!(ok);
+    assert!(stdout.starts_with("VStack {"));
+    assert!(stderr.contains("synthesis budget exhausted (candidate limit reached after 2 candidates)"));
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "--max-memory", "1KB", "--stats"], &spec);
+    assert!(ok);
+    assert!(stderr.contains("synthesis budget exhausted (memory limit reached after "), "{}", stderr);
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "--timeout", "soon"], &spec);
+    assert!(!ok);
+    assert!(stderr.contains("Invalid duration 'soon'"));
+}
+
+#[test]
+fn test_cli_cache_reuses_and_clears_results() {
+    // A dedicated cache dir keeps this test independent of the others
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-cache-cli-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+            .args(args)
+            .env("SWIFTUI_SYNTH_CACHE_DIR", &dir)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    };
+    let spec = "{(width:390,height:844):{title:\"Cached\"}}";
+
+    let first = run(&["--examples", spec]);
+    assert!(!first.contains("(cached)"));
+    let second = run(&["--examples", spec]);
+    assert!(second.contains("(cached)"));
+    assert_eq!(first.lines().skip(1).collect::<Vec<_>>(), second.lines().skip(1).collect::<Vec<_>>());
+    assert!(!run(&["--examples", spec, "--no-cache"]).contains("(cached)"));
+
+    assert!(run(&["cache", "clear"]).starts_with("Removed 1 cached results"));
+    assert!(!run(&["--examples", spec]).contains("(cached)"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_explain_keeps_stdout_clean_in_quiet_mode() {
+    let spec = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--explain"], spec);
+    assert!(ok);
+    assert_eq!(stdout, process_example(spec).unwrap() + "\n");
+    assert!(stderr.starts_with("Why this layout:\n  - VStack chosen"));
+
+    let (stdout, _, ok) = run_cli(&["synth", "-", "--json", "--explain"], spec);
+    assert!(ok);
+    assert!(stdout.contains("\"explanation\": ["));
+}
+
+#[test]
+fn test_cli_stats_report() {
+    let spec = "{(width:390,height:844):{title:\"Hi\"}}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--stats", "--no-cache"], spec);
+    assert!(ok);
+    assert_eq!(stdout, process_example(spec).unwrap() + "\n");
+    assert!(stderr.contains("candidates: 1 enumerated, 0 pruned, 1 scored"));
+    assert!(stderr.contains("cache hits: 0"));
+    assert!(stderr.contains("search:"));
+
+    let (stdout, _, ok) = run_cli(&["synth", "-", "--json", "--stats", "--no-cache"], spec);
+    assert!(ok);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["stats"]["scored"], 1);
+    assert_eq!(json["stats"]["phases"][0]["name"], "parse");
+}
+
+#[test]
+fn test_cli_strategy_selection() {
+    let spec = "{(width:320,height:220):{Image:\"logo\",title:\"Hi\",button:\"Go\"}}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--strategy", "template", "--no-cache"], spec);
+    assert!(ok);
+    assert_eq!(stdout, process_example(spec).unwrap() + "\n");
+    assert!(stderr.contains("the template strategy found no layout satisfying every example"));
+    // The best effort comes with what it breaks
+    assert!(stderr.contains("\n  - example 1: content needs 244pt of height but the screen is 220pt tall\n"), "{}", stderr);
+    let (stdout, _, ok) = run_cli(&["synth", "-", "--json", "--strategy", "template", "--no-cache"], spec);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(ok && json["status"] == "ok", "{}", stdout);
+    assert_eq!(json["unresolved"][0], "example 1: content needs 244pt of height but the screen is 220pt tall");
+
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--strategy", "constraint", "--no-cache"], spec);
+    assert!(ok);
+    assert!(stderr.is_empty());
+    assert!(stdout.contains("    HStack {\n        Image(\"logo\")"));
+
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--strategy", "beam", "--beam-width", "4", "--no-cache"], spec);
+    assert!(ok && stderr.is_empty(), "{}", stderr);
+    assert!(stdout.contains("HStack {"), "{}", stdout);
+    let (_, stderr, ok) = run_cli(&["synth", "-", "--strategy", "beam", "--beam-width", "0"], spec);
+    assert!(!ok && stderr.contains("--beam-width"), "{}", stderr);
+
+    let genetic = |seed: &str| run_cli(&["synth", "-", "-q", "--strategy", "genetic", "--seed", seed, "--no-cache"], spec);
+    let (stdout, stderr, ok) = genetic("7");
+    assert!(ok && stderr.is_empty(), "{}", stderr);
+    assert_eq!(genetic("7").0, stdout);
+
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--strategy", "assignment", "--no-cache"], spec);
+    assert!(ok && stderr.is_empty(), "{}", stderr);
+    assert!(stdout.contains("HStack {"), "{}", stdout);
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "--strategy", "annealing"], spec);
+    assert!(!ok);
+    assert!(stderr.contains("Unknown strategy 'annealing'"));
+}
+
+#[test]
+fn test_cli_component_library() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-components-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let library = dir.join("components.toml");
+    std::fs::write(
+        &library,
+        "[[component]]\nname = \"PrimaryButton\"\nparams = [\"title\"]\nrole = \"button\"\nheight = 50\n\n\
+         [[component]]\nname = \"AvatarView\"\nparams = [\"user\"]\nwidth = 64\nheight = 64\n",
+    )
+    .unwrap();
+    let library = library.to_str().unwrap();
+    let spec = "{(width:390,height:844):{AvatarView:\"me\",title:\"Hi\",button:\"Go\"}}";
+
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--components", library], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("    AvatarView(user: \"me\")\n"));
+    assert!(stdout.contains("    PrimaryButton(title: \"Go\")\n"));
+    assert!(!stdout.contains("Button(\"Go\")"));
+
+    // Code using the components satisfies the examples it was generated from
+    let swift = dir.join("ContentView.swift");
+    std::fs::write(&swift, &stdout).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+        .args(["check", "--examples", spec, "--against", swift.to_str().unwrap()])
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stdout));
+
+    // Without the library the component key is unknown
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q", "--strict"], spec);
+    assert!(!ok);
+    assert!(stderr.contains("error[E0012]"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_lenient_input_warns_and_strict_rejects_it() {
+    let spec = "{(width:390,height:844):{title:Welcome, RatingView:\"4\", button:\"Go\"}}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q"], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Text(\"Welcome\")"));
+    assert!(stdout.contains("RatingView(\"4\")"));
+    assert!(stderr.contains("warning[W0001]: Unknown element key 'RatingView' is taken as a custom view"));
+    assert!(stderr.contains("warning[W0002]: Value for key 'title' is not quoted"));
+
+    let (stdout, _, ok) = run_cli(&["synth", "-", "--json"], spec);
+    assert!(ok);
+    assert!(stdout.contains("\"code\": \"W0001\""));
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q", "--strict"], spec);
+    assert!(!ok);
+    assert!(stderr.contains("error[E0014]"));
+}
+
+#[test]
+fn test_cli_accepts_fractional_points() {
+    let spec = "{(width:402.5,height:874.5):{title:\"Hi\"@(y:80.5),button:\"Go\"}}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Text(\"Hi\")"));
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q"], "{(width:402.5,height:-1):{title:\"Hi\"}}");
+    assert!(!ok);
+    assert!(stderr.contains("error[E0007]: Invalid height value '-1'"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_passes_typed_values_to_custom_views() {
+    let spec = r#"{(width:390,height:844):{title:"Deals", TagList:["new", "sale"], PromoToggle:false}}"#;
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q"], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(r#"TagList(["new", "sale"])"#), "{}", stdout);
+    assert!(stdout.contains("PromoToggle(false)"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_top_n_lists_alternatives_without_a_terminal() {
+    let spec = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+    let (stdout, _, ok) = run_cli(&["synth", "-", "--json", "--top-n", "3", "--no-cache"], spec);
+    assert!(ok);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let alternatives = json["alternatives"].as_array().unwrap();
+    assert_eq!(alternatives.len(), 2);
+    assert!(alternatives.iter().all(|code| code != &json["code"]));
+
+    // Piped output never opens the picker; the cheapest layout is printed
+    let (stdout, _, ok) = run_cli(&["synth", "-", "-q", "--top-n", "3", "--no-cache"], spec);
+    assert!(ok);
+    assert_eq!(stdout.matches("Stack {").count(), 1);
+
+    let output = Command::new(env!("CARGO_BIN_EXE_swiftui-synth")).args(["--examples", spec, "--top-n", "0"]).output().unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("--top-n"));
+}
+
+#[test]
+fn test_cli_reject_file_constrains_next_round() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-reject-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let feedback = dir.join("feedback.txt");
+    std::fs::write(&feedback, "# from review\nbutton should be in the top bar\n").unwrap();
+    let spec = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "--reject", feedback.to_str().unwrap()], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.find("Button(\"Go\")").unwrap() < stdout.find("Text(\"Hi\")").unwrap());
+
+    std::fs::write(&feedback, "button sideways\n").unwrap();
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q", "--reject", feedback.to_str().unwrap()], spec);
+    assert!(!ok);
+    assert!(stderr.contains("line 1: Could not understand where 'button' should be"));
+
+    // Feedback is asked for in a terminal, so piped examples cannot be refined
+    let (_, stderr, ok) = run_cli(&["synth", "-", "--refine"], spec);
+    assert!(!ok);
+    assert!(stderr.contains("--refine asks for feedback in a terminal"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_reports_contradicting_examples() {
+    let spec = "{(width:390,height:844):{title:\"Hi\",button:\"Go\",constraints:{\"button above title\"}}}\n\
+                {(width:320,height:568):{title:\"Hi\",button:\"Go\"}}\n\
+                {(width:390,height:844):{title:\"Hi\",button:\"Go\",constraints:{\"button below title\"}}}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], spec);
+    assert!(ok && stdout.starts_with("VStack {"), "{}", stderr);
+    assert!(stderr.contains("Note: examples 1 and 3 contradict each other: each is satisfied by some layout, but none was found for both"), "{}", stderr);
+
+    let (stdout, _, ok) = run_cli(&["synth", "-", "--json", "--no-cache"], spec);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(ok && json["conflict"].as_str().unwrap().starts_with("examples 1 and 3 contradict"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_minimizes_failing_specs() {
+    let spec = "// Too much text for a small screen\n\
+                {(width:390,height:844):{Image:\"logo\",title:\"Welcome back\"@(x:16),button:\"Sign in\"}}\n\
+                {(width:200,height:120):{Image:\"logo\",title:\"Welcome back\",button:\"Sign in\"}}";
+    let (stdout, stderr, ok) = run_cli(&["minimize", "-", "--strategy", "template"], spec);
+    assert!(ok, "{}", stderr);
+    // The overflow by the same amount needs all three views
+    assert_eq!(stdout, "{(width:200,height:120):{Image:\"logo\", title:\"Welcome back\", button:\"Sign in\"}}\n");
+    assert!(stderr.starts_with("Reduced 2 examples with 6 elements to 1 with 3 after trying "), "{}", stderr);
+
+    let (_, stderr, ok) = run_cli(&["minimize", "-"], "{(width:390,height:844):{title:\"Hi\"}}");
+    assert!(!ok && stderr.contains("Synthesis satisfies every example of '<stdin>'; there is nothing to minimize"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_constraints_block_orders_views() {
+    let spec = "{(width:390,height:844):{title:\"Hi\",Image:\"logo\",button:\"Go\",constraints:{\"image leftOf title\",\"button below title\"}}}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], spec);
+    assert!(ok, "{}", stderr);
+    let hstack = stdout.find("HStack").expect("image and title side by side");
+    assert!(hstack < stdout.find("Image(\"logo\")").unwrap());
+    assert!(stdout.find("Image(\"logo\")").unwrap() < stdout.find("Text(\"Hi\")").unwrap());
+    assert!(stdout.find("Text(\"Hi\")").unwrap() < stdout.find("Button(\"Go\")").unwrap());
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q"], "{(width:390,height:844):{title:\"Hi\",constraints:{\"title beside\"}}}");
+    assert!(!ok);
+    assert!(stderr.contains("error[E0020]"));
+}
+
+#[test]
+fn test_cli_content_in_the_safe_area_ignores_it() {
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], "{(device:\"iPhone 15\"):{title:\"Hi\"@(y:0),button:\"Go\"}}");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.trim_end().ends_with("}\n.padding()\n.ignoresSafeArea()"), "{}", stdout);
+
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], "{(device:\"iPhone 15\"):{title:\"Hi\",button:\"Go\"}}");
+    assert!(ok, "{}", stderr);
+    assert!(!stdout.contains("ignoresSafeArea"));
+}
+
+#[test]
+fn test_cli_large_dynamic_type_scrolls() {
+    let elements = "{title:\"Welcome to the application home screen\",Image:\"logo\",button:\"Continue\"}";
+    let spec = format!("{{(width:390,height:844):{}}}{{(width:390,height:500,dynamicType:accessibility5):{}}}", elements, elements);
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], &spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("ScrollView {\n    VStack {"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_named_screens_write_one_file_each() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-screens-{}", std::process::id()));
+    let spec = "Login:{(width:390,height:844):{title:\"Sign in\",button:\"Continue\"}}\nHome:{(width:390,height:844):{title:\"Home\"}}";
+
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "--out-dir", dir.to_str().unwrap(), "--app", "stack"], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.is_empty());
+    let login = std::fs::read_to_string(dir.join("LoginView.swift")).unwrap();
+    let mut header = login.lines();
+    assert!(header.next().unwrap().starts_with("// swiftui-synth:begin "));
+    assert!(header.next().unwrap().starts_with("// Generated by swiftui-synth "));
+    let login = &login[login.find("import SwiftUI").unwrap()..];
+    assert!(login.starts_with("import SwiftUI\n\nstruct LoginView: View {\n    var body: some View {\n        VStack {"));
+    assert!(std::fs::read_to_string(dir.join("HomeView.swift")).unwrap().contains("Text(\"Home\")"));
+    assert!(std::fs::read_to_string(dir.join("App.swift")).unwrap().contains("NavigationStack {\n                LoginView()"));
+
+    let (stdout, _, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], spec);
+    assert!(ok);
+    assert!(stdout.contains("struct LoginView: View {") && stdout.contains("struct HomeView: View {"));
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-o", dir.join("Both.swift").to_str().unwrap()], spec);
+    assert!(!ok);
+    assert!(stderr.contains("write them to separate files with --out-dir"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_flows_link_buttons_to_screens() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-flows-{}", std::process::id()));
+    let spec = "Login:{(width:390,height:844):{title:\"Sign in\",button:\"Continue\"}}\nHome:{(width:390,height:844):{title:\"Home\"}}\nflows:{Login.button -> Home}";
+
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "--out-dir", dir.to_str().unwrap(), "--app", "stack"], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.is_empty());
+    let login = std::fs::read_to_string(dir.join("LoginView.swift")).unwrap();
+    assert!(login.contains("NavigationLink(\"Continue\", value: Route.home)"));
+    assert!(!login.contains("Button("));
+    let routes = std::fs::read_to_string(dir.join("Route.swift")).unwrap();
+    assert!(routes.contains("enum Route: Hashable {\n    case home\n}"));
+    assert!(routes.contains("case .home:\n                HomeView()"));
+    assert!(std::fs::read_to_string(dir.join("App.swift")).unwrap().contains("NavigationStack(path: $path)"));
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q"], &spec.replace("-> Home", "-> Settings"));
+    assert!(!ok);
+    assert!(stderr.contains("error[E0023]: Flow 'Login.button -> Settings' leads to unknown screen 'Settings'"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_validate_swift_reports_compiler_errors() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-validate-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    // Stands in for swiftc: rejects line 9 of the last file it is given, unless told to accept
+    let swiftc = dir.join("swiftc");
+    std::fs::write(&swiftc, "#!/bin/sh\n[ -n \"$ACCEPT\" ] && exit 0\nfor f; do last=$f; done\necho \"$last:9:13: error: expected expression\" >&2\nexit 1\n").unwrap();
+    std::fs::set_permissions(&swiftc, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let output_path = dir.join("ContentView.swift");
+    let run = |envs: &[(&str, &str)]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+            .args(["--examples", "{(width:390,height:844):{title:\"Hi\"}}", "--no-cache", "--validate", "swift", "-o"])
+            .arg(&output_path)
+            .env("PATH", "")
+            .env_remove("SDKROOT")
+            .env_remove("SWIFTC")
+            .envs(envs.iter().copied())
+            .output()
+            .unwrap();
+        (String::from_utf8_lossy(&output.stdout).into_owned(), String::from_utf8_lossy(&output.stderr).into_owned(), output.status.success())
+    };
+
+    let (_, stderr, ok) = run(&[("SWIFTC", swiftc.to_str().unwrap())]);
+    assert!(!ok);
+    assert!(stderr.contains("error[E0030]: swiftc rejected the generated code at ContentView.swift:9:13, in Text \"Hi\": expected expression"), "{}", stderr);
+    assert!(!output_path.exists());
+
+    let (stdout, _, ok) = run(&[("SWIFTC", swiftc.to_str().unwrap()), ("ACCEPT", "1")]);
+    assert!(ok);
+    assert!(stdout.contains(&format!("Validated with {}: the generated code parses", swiftc.display())));
+    assert!(output_path.exists());
+
+    // Without a compiler the code is still saved, with a warning
+    std::fs::remove_file(&output_path).unwrap();
+    let (_, stderr, ok) = run(&[]);
+    assert!(ok);
+    assert!(stderr.contains("--validate swift needs swiftc on the PATH"));
+    assert!(output_path.exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_post_format_runs_the_formatter_command() {
+    let spec = "{(width:390,height:844):{title:\"Hi\"}}";
+    let (stdout, _, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "--post-format", "sed s/VStack/LazyVStack/"], spec);
+    assert!(ok);
+    assert!(stdout.starts_with("LazyVStack {\n    Text(\"Hi\")"));
+
+    // A formatter that fails leaves the code to the built-in pretty-printer
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "--post-format", "no-such-formatter"], spec);
+    assert!(ok);
+    assert!(stderr.contains("Warning: Formatter 'no-such-formatter' failed"), "{}", stderr);
+    assert_eq!(stdout, run_cli(&["synth", "-", "-q", "--no-cache"], spec).0);
+}
+
+#[test]
+fn test_cli_regeneration_keeps_hand_edits() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-merge-cli-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let out_dir = dir.to_str().unwrap();
+    let run = |title: &str, extra: &[&str]| {
+        let spec = format!("Login:{{(width:390,height:844):{{title:\"{}\"}}}}", title);
+        let args: Vec<&str> = ["synth", "-", "-q", "--no-cache", "--out-dir", out_dir].iter().chain(extra).copied().collect();
+        run_cli(&args, &spec)
+    };
+    let path = dir.join("LoginView.swift");
+    assert!(run("Hi", &[]).2);
+    let generated = std::fs::read_to_string(&path).unwrap();
+    assert!(generated.starts_with("// swiftui-synth:begin "), "{}", generated);
+
+    // Code added around the markers survives regeneration
+    let extension = "\nextension LoginView {\n    static let id = 1\n}\n";
+    std::fs::write(&path, format!("// Owned by the login team\n{}{}", generated, extension)).unwrap();
+    assert!(run("Welcome", &[]).2);
+    let regenerated = std::fs::read_to_string(&path).unwrap();
+    assert!(regenerated.starts_with("// Owned by the login team\n// swiftui-synth:begin "));
+    assert!(regenerated.contains("Text(\"Welcome\")") && regenerated.ends_with(extension), "{}", regenerated);
+
+    // Edits inside the generated section are not clobbered without --force
+    let edited = regenerated.replace("Text(\"Welcome\")", "Text(\"Welcome\").bold()");
+    std::fs::write(&path, &edited).unwrap();
+    let (_, stderr, ok) = run("Hello", &[]);
+    assert!(!ok);
+    assert!(stderr.contains("Not overwriting 'LoginView.swift' (its generated section was edited by hand)"), "{}", stderr);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), edited);
+    assert!(run("Hello", &["--force"]).2);
+    let forced = std::fs::read_to_string(&path).unwrap();
+    assert!(forced.contains("Text(\"Hello\")") && !forced.contains(".bold()") && forced.ends_with(extension));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_output_is_identical_across_runs() {
+    // Each run is a new process, so anything hash-ordered would come out shuffled
+    let spec = "Login:{(width:390,height:844):{Image:\"logo\",title:\"Sign in\",button:\"Continue\",button:\"Help\"}}\nHome:{(width:390,height:844):{title:\"Home\",button:\"Profile\"}}\nProfile:{(width:390,height:844):{title:\"Me\"}}\nflows:{Login.button \"Continue\" -> Home, Login.button \"Help\" -> Profile, Home.button -> Profile}";
+    // The same directory every time, as it is named in the files' headers
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-stable-{}", std::process::id()));
+    let files = || {
+        let _ = std::fs::remove_dir_all(&dir);
+        let (_, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "--out-dir", dir.to_str().unwrap(), "--app", "tabs"], spec);
+        assert!(ok, "{}", stderr);
+        let mut files: Vec<(String, String)> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .map(|path| (path.file_name().unwrap().to_string_lossy().into_owned(), std::fs::read_to_string(&path).unwrap()))
+            .collect();
+        files.sort();
+        std::fs::remove_dir_all(&dir).unwrap();
+        files
+    };
+    let first = files();
+    assert_eq!(first.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), [".swiftui-synth-build.json", "App.swift", "HomeView.swift", "LoginView.swift", "ProfileView.swift", "Route.swift"]);
+    let routes = &first[5].1;
+    assert!(routes.find("case home").unwrap() < routes.find("case profile").unwrap(), "{}", routes);
+    for _ in 0..3 {
+        assert_eq!(files(), first);
+    }
+
+    // Alternatives are ranked the same way every time
+    let example = "{(width:390,height:844):{title:\"A\",button:\"B\",Image:\"c\"}}";
+    let alternatives = || {
+        let (stdout, _, ok) = run_cli(&["synth", "-", "--no-cache", "--json", "--top-n", "4"], example);
+        assert!(ok);
+        let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        (report["code"].clone(), report["alternatives"].clone())
+    };
+    let first = alternatives();
+    assert!(first.1.as_array().is_some_and(|alternatives| alternatives.len() == 3));
+    for _ in 0..3 {
+        assert_eq!(alternatives(), first);
+    }
+}
+
+#[test]
+fn test_cli_generated_files_record_their_provenance() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-provenance-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("Login.swift");
+    let spec = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+    let (_, stderr, ok) = run_cli(&["synth", "-", "--no-cache", "--strategy", "template", "-o", path.to_str().unwrap()], spec);
+    assert!(ok, "{}", stderr);
+    let code = std::fs::read_to_string(&path).unwrap();
+    let header: Vec<&str> = code.lines().skip(1).take(3).collect();
+    assert_eq!(header[0], format!("// Generated by swiftui-synth {} with the template strategy", env!("CARGO_PKG_VERSION")));
+    assert!(header[1].starts_with("// Examples: "));
+    assert_eq!(header[2], format!("// Command: swiftui-synth synth - --no-cache --strategy template -o {}", path.display()));
+
+    // `check` warns when the spec changed since, even if the code still satisfies it
+    let check = |examples: &str| run_cli(&["check", "--examples", examples, "--against", path.to_str().unwrap()], "");
+    let (_, stderr, ok) = check("{ (width: 390, height: 844): { title: \"Hi\", button: \"Go\" } }");
+    assert!(ok && stderr.is_empty(), "{}", stderr);
+    let (_, stderr, ok) = check("{(width:390,height:844):{title:\"Hi\",button:\"Go\"}} {(width:320,height:568):{title:\"Hi\",button:\"Go\"}}");
+    assert!(ok);
+    assert!(stderr.contains("was generated from different examples") && stderr.contains("regenerate it with `swiftui-synth synth - --no-cache"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_xcode_add_registers_files_with_the_project() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-xcode-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let xcodeproj = dir.join("Demo.xcodeproj");
+    std::fs::create_dir_all(&xcodeproj).unwrap();
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/Demo.xcodeproj/project.pbxproj");
+    std::fs::copy(fixture, xcodeproj.join("project.pbxproj")).unwrap();
+    let spec = "Login:{(width:390,height:844):{title:\"Sign in\"}}\nHome:{(width:390,height:844):{title:\"Home\"}}";
+    let add = || run_cli(&["xcode", "add", "-", "--no-cache", "--project", xcodeproj.to_str().unwrap(), "--group", "Demo/Generated"], spec);
+
+    let (stdout, stderr, ok) = add();
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Added LoginView.swift, HomeView.swift to group Demo/Generated of target Demo"), "{}", stdout);
+    assert!(std::fs::read_to_string(dir.join("Demo/Generated/LoginView.swift")).unwrap().contains("struct LoginView: View"));
+    let pbxproj = std::fs::read_to_string(xcodeproj.join("project.pbxproj")).unwrap();
+    assert!(pbxproj.contains("/* HomeView.swift in Sources */ = {isa = PBXBuildFile;"));
+    assert!(pbxproj.contains("path = Generated;"));
+
+    // Running again regenerates the files but leaves the project alone
+    let (stdout, _, ok) = add();
+    assert!(ok);
+    assert!(stdout.contains("LoginView.swift, HomeView.swift already in group Demo/Generated of target Demo"), "{}", stdout);
+    assert_eq!(std::fs::read_to_string(xcodeproj.join("project.pbxproj")).unwrap(), pbxproj);
+
+    let (_, stderr, ok) = run_cli(&["xcode", "add", "-", "--project", xcodeproj.to_str().unwrap(), "--app-target", "Widget"], spec);
+    assert!(!ok);
+    assert!(stderr.contains("No target 'Widget' in the project; it has Demo"), "{}", stderr);
+
+    // A package target compiles whatever is in its folder
+    let package = dir.join("Kit");
+    std::fs::create_dir_all(package.join("Sources/KitUI")).unwrap();
+    std::fs::write(package.join("Package.swift"), "// swift-tools-version:5.9\n").unwrap();
+    let (_, stderr, ok) = run_cli(&["xcode", "add", "-", "-q", "--no-cache", "--package", package.to_str().unwrap(), "--group", "Views"], spec);
+    assert!(ok, "{}", stderr);
+    assert!(package.join("Sources/KitUI/Views/HomeView.swift").is_file());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_emit_package_scaffolds_a_swift_package() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-package-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let package = dir.join("MyViews");
+    let spec = "Login:{(width:390,height:844):{title:\"Sign in\",button:\"Continue\"}}\nHome:{(width:390,height:844):{title:\"Home\"}}\nflows:{Login.button -> Home}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "--no-cache", "--emit-package", package.to_str().unwrap()], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Created Package.swift, Tests/MyViewsTests/MyViewsTests.swift in"), "{}", stdout);
+    let manifest = std::fs::read_to_string(package.join("Package.swift")).unwrap();
+    assert!(manifest.contains(".library(name: \"MyViews\", targets: [\"MyViews\"]),"));
+    let login = std::fs::read_to_string(package.join("Sources/MyViews/LoginView.swift")).unwrap();
+    assert!(login.contains("public struct LoginView: View {\n    public init() {}\n\n    public var body: some View {"), "{}", login);
+    assert!(std::fs::read_to_string(package.join("Sources/MyViews/Route.swift")).unwrap().contains("public enum Route: Hashable {"));
+    let tests = std::fs::read_to_string(package.join("Tests/MyViewsTests/MyViewsTests.swift")).unwrap();
+    assert!(tests.contains("func testLoginView()") && tests.contains("func testHomeView()"));
+
+    // The scaffolding is the team's once written; the views are regenerated
+    std::fs::write(package.join("Package.swift"), "// edited\n").unwrap();
+    let (stdout, _, ok) = run_cli(&["synth", "-", "--no-cache", "--emit-package", package.to_str().unwrap()], &spec.replace("Sign in", "Welcome"));
+    assert!(ok);
+    assert!(!stdout.contains("Created"));
+    assert_eq!(std::fs::read_to_string(package.join("Package.swift")).unwrap(), "// edited\n");
+    assert!(std::fs::read_to_string(package.join("Sources/MyViews/LoginView.swift")).unwrap().contains("Text(\"Welcome\")"));
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "--emit-package", dir.join("my-views").to_str().unwrap()], spec);
+    assert!(!ok);
+    assert!(stderr.contains("Package name 'my-views' is not a valid Swift module name"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_serve_regenerates_when_the_spec_changes() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-serve-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let spec = dir.join("login.spec");
+    std::fs::write(&spec, "{(width:390,height:844):{title:\"Hi\"}}").unwrap();
+    let views = dir.join("Views");
+    let mut child = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+        .args(["serve", spec.to_str().unwrap(), "--xcode", views.to_str().unwrap(), "--interval", "20ms", "--no-cache", "--explain"])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let file = views.join("ContentView.swift");
+    let wait_for = |text: &str| {
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(20);
+        while std::time::Instant::now() < deadline {
+            if std::fs::read_to_string(&file).is_ok_and(|code| code.contains(text)) {
+                return true;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        false
+    };
+
+    let found = wait_for("Text(\"Hi\")");
+    let code = std::fs::read_to_string(&file).unwrap_or_default();
+    std::fs::write(&spec, "{(width:390,height:844):{title:\"Welcome\",button:\"Go\"}}").unwrap();
+    let updated = wait_for("Text(\"Welcome\")");
+    // Only a label changes, so the layout is kept without searching again
+    std::fs::write(&spec, "{(width:390,height:844):{title:\"Welcome\",button:\"Continue\"}}").unwrap();
+    let relabeled = wait_for("Button(\"Continue\")");
+    child.kill().unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert!(found && updated && relabeled, "{}", stdout);
+    assert!(stderr.contains("Reused the previous layout with 1 label(s) changed"), "{}", stderr);
+    assert!(code.contains("struct ContentView: View {") && code.contains("#Preview {\n    ContentView()\n}\n// swiftui-synth:end"), "{}", code);
+    assert!(stdout.starts_with("Watching ") && stdout.contains("Updated ContentView.swift in "), "{}", stdout);
+}
+
+#[test]
+fn test_cli_lsp_answers_an_editor_over_stdio() {
+    let frame = |message: &str| format!("Content-Length: {}\r\n\r\n{}", message.len(), message);
+    let messages = [
+        r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#,
+        r#"{"jsonrpc":"2.0","method":"initialized","params":{}}"#,
+        r#"{"jsonrpc":"2.0","method":"textDocument/didOpen","params":{"textDocument":{"uri":"file:///login.spec","text":"{(width:390,height:844):{titel:\"Hi\"}}"}}}"#,
+        r#"{"jsonrpc":"2.0","id":2,"method":"swiftui-synth/synthesize","params":{"text":"Login:{(width:390,height:844):{title:\"Hi\"}}"}}"#,
+        r#"{"jsonrpc":"2.0","id":3,"method":"shutdown"}"#,
+        r#"{"jsonrpc":"2.0","method":"exit"}"#,
+    ];
+    let input: String = messages.iter().map(|message| frame(message)).collect();
+    let mut child = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+        .arg("--lsp")
+        .current_dir(std::env::temp_dir())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert!(output.status.success(), "{}", stdout);
+    let replies: Vec<&str> = stdout.split("Content-Length: ").skip(1).collect();
+    assert_eq!(replies.len(), 4, "{}", stdout);
+    assert!(replies[0].contains(r#""textDocumentSync":1"#), "{}", replies[0]);
+    assert!(replies[1].contains(r#""method":"textDocument/publishDiagnostics""#) && replies[1].contains(r#""code":"E0012""#), "{}", replies[1]);
+    // The synthesis runs on a worker, so the shutdown may be answered first
+    let synthesized = replies[2..].iter().find(|reply| reply.contains(r#""id":2"#)).expect("the synthesis is answered");
+    assert!(synthesized.contains(r#"struct LoginView: View {"#), "{}", synthesized);
+    assert!(replies[2..].iter().any(|reply| reply.contains(r#""id":3"#)), "{}", stdout);
+}
+
+#[test]
+fn test_cli_copy_without_a_clipboard_still_prints_the_code() {
+    let output = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+        .args(["--examples", "{(width:390,height:844):{title:\"Hi\"}}", "--copy", "-q", "--no-cache"])
+        .env_remove("DISPLAY")
+        .env_remove("WAYLAND_DISPLAY")
+        .output()
+        .unwrap();
+    let (stdout, stderr) = (String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    assert!(output.status.success(), "{}", stderr);
+    assert!(stdout.contains("Text(\"Hi\")"), "{}", stdout);
+    if cfg!(target_os = "linux") {
+        assert!(stderr.contains("Warning: Could not open the clipboard"), "{}", stderr);
+    }
+}
+
+#[test]
+fn test_cli_emit_tree_prints_the_layout_as_a_diagram() {
+    let examples = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "--emit-tree", "dot", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("digraph Layout {\n    node [shape=box];\n    n0 [label=\"VStack\"];\n"), "{}", stdout);
+    assert!(stdout.contains("    n1 [label=\"Text \\\"Hi\\\"\"];\n    n0 -> n1;\n") && stdout.ends_with("}\n"), "{}", stdout);
+    assert!(!stdout.contains("Synthesized"), "{}", stdout);
+
+    let (stdout, _, ok) = run_cli(&["--examples", examples, "--emit-tree", "mermaid", "--no-cache"], "");
+    assert!(ok && stdout.starts_with("graph TD\n    n0[\"VStack\"]\n    n0 --> n1[\"Text #quot;Hi#quot;\"]\n"), "{}", stdout);
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--emit-tree", "svg"], "");
+    assert!(!ok && stderr.contains("expected dot or mermaid"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_preview_draws_each_screen_as_png() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-png-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let single = dir.join("layout.png");
+    let examples = "{(width:390,height:844):{title:\"Hi\"}} {(width:320,height:568):{title:\"Hi\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "--preview", "png", single.to_str().unwrap(), "--preview-example", "2"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(&format!("Drew ContentView to {}", single.display())), "{}", stdout);
+    let png = std::fs::read(&single).unwrap();
+    assert!(png.starts_with(b"\x89PNG\r\n\x1a\n"));
+    // The second example's 320x568 screen, at @2x: the IHDR chunk holds the width and height
+    assert_eq!(&png[16..24], &[0, 0, 2, 128, 0, 0, 4, 112]);
+
+    let screens = "Login:{(width:390,height:844):{title:\"Hi\"}} Home:{(width:390,height:844):{title:\"Home\"}}";
+    let (_, stderr, ok) = run_cli(&["--examples", screens, "--preview", "png", dir.join("app.png").to_str().unwrap(), "-q"], "");
+    assert!(ok, "{}", stderr);
+    assert!(dir.join("app-LoginView.png").is_file() && dir.join("app-HomeView.png").is_file());
+
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--preview", "png", single.to_str().unwrap(), "--preview-example", "3"], "");
+    assert!(!ok && stderr.contains("--preview-example 3 is out of range: the screen has 2 examples"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_fidelity_reports_distance_from_stated_frames() {
+    let (stdout, stderr, ok) = run_cli(&["fidelity", "--examples", "{(width:390,height:844):{title:\"Hi\"@(y:16),button:\"Go\"@(y:774,h:54)}}"], "");
+    assert!(ok, "{}", stderr);
+    assert_eq!(
+        stdout,
+        "ContentView:\nexample 1 (390x844):\n  Text \"Hi\"    y +0.0  off by 0.0pt  ok\n  Button \"Go\"  y +0.0  h +0.0  off by 0.0pt  ok\n2 of 2 stated frames within 2pt\n"
+    );
+
+    let spec = "{(width:390,height:844):{title:\"Hi\"@(y:20),button:\"Go\"@(y:774)}}";
+    let (stdout, stderr, ok) = run_cli(&["fidelity", "-"], spec);
+    assert!(!ok, "{}", stdout);
+    assert!(stdout.contains("  Text \"Hi\"    y -4.0  off by 4.0pt  FAIL\n") && stdout.contains("1 of 2 stated frames within 2pt"), "{}", stdout);
+    assert!(stderr.contains("1 of the views with a stated frame are more than 2pt from it"), "{}", stderr);
+    let (stdout, _, ok) = run_cli(&["fidelity", "-", "--threshold", "5"], spec);
+    assert!(ok && stdout.contains("2 of 2 stated frames within 5pt"), "{}", stdout);
+
+    let (stdout, _, ok) = run_cli(&["fidelity", "--examples", "{(width:390,height:844):{title:\"Hi\"}}"], "");
+    assert!(ok && stdout == "ContentView: no stated frames to compare\n", "{}", stdout);
+}
+
+#[test]
+fn test_cli_generates_examples_from_swift() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-examples-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let view = dir.join("LoginView.swift");
+    std::fs::write(&view, "struct LoginView: View {\n    var body: some View {\n        VStack {\n            Text(\"Welcome\")\n            Spacer()\n            Button(\"Sign in\") {}\n        }\n    }\n}\n").unwrap();
+    let (spec, stderr, ok) = run_cli(&["examples", "--from", view.to_str().unwrap(), "--sizes", "390x844,1024x768"], "");
+    assert!(ok, "{}", stderr);
+    assert!(spec.starts_with("Login:{(width:390,height:844,safe_top:47,safe_bottom:34):{title:\"Welcome\"@("), "{}", spec);
+    assert!(spec.contains("{(width:1024,height:768):{"), "{}", spec);
+
+    // The layout synthesized from the examples puts every view where the view file does
+    let (stdout, stderr, ok) = run_cli(&["fidelity", "-", "--threshold", "0.5"], &spec);
+    assert!(ok, "{}\n{}", stdout, stderr);
+
+    let (_, stderr, ok) = run_cli(&["examples", "--from", view.to_str().unwrap(), "--sizes", "wide"], "");
+    assert!(!ok && stderr.contains("Invalid size 'wide'"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_bench_runs_the_starter_corpus() {
+    let corpus = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/specs");
+    let (stdout, stderr, ok) = run_cli(&["bench", corpus], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("benchmark ") && stdout.contains("\nlogin ") && stdout.contains("6 of 6 solved (100.0%)"), "{}", stdout);
+
+    let (stdout, _, ok) = run_cli(&["bench", corpus, "--json"], "");
+    let report: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert!(ok && report["solved"] == 6 && report["strategy"] == "enumerative", "{}", stdout);
+    assert_eq!(report["benchmarks"][0]["name"], "login");
+
+    let (_, stderr, ok) = run_cli(&["bench", "no-such-dir"], "");
+    assert!(!ok && stderr.contains("Failed to read benchmark directory 'no-such-dir'"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_calibrates_the_layout_model() {
+    let (harness, _, ok) = run_cli(&["calibrate", "--harness"], "");
+    assert!(ok && harness.contains("@main") && harness.contains(".calibrationProbe(0)"), "{}", harness);
+
+    let observations = r#"{"samples": [{"name": "title", "code": "VStack {\n    Text(\"Welcome\")\n}", "width": 390, "height": 844,
+        "frames": [{"x": 120, "y": 380, "width": 150, "height": 60}]}]}"#;
+    let (stdout, stderr, ok) = run_cli(&["calibrate", "-"], observations);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("[layout]\npadding = ") && stdout.contains("\ntitle_line_height = "), "{}", stdout);
+    assert!(stderr.contains("Fitted to 1 frames of 1 samples"), "{}", stderr);
+
+    let (_, stderr, ok) = run_cli(&["calibrate", "-"], "{\"samples\": 3}");
+    assert!(!ok && stderr.contains("Invalid observations"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_trains_a_ranker_on_picks() {
+    // Someone who always picked the layout with a spacer
+    let log = "{\"accepted\":true,\"features\":{\"spacers\":1,\"nodes\":4}}\n\
+               {\"accepted\":false,\"features\":{\"spacers\":0,\"nodes\":3}}\n"
+        .repeat(3);
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-ranker-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let model = dir.join("ranker.json");
+    let (_, stderr, ok) = run_cli(&["train-ranker", "-", "-o", model.to_str().unwrap()], &log);
+    assert!(ok, "{}", stderr);
+    assert!(stderr.contains("Trained on 6 layouts (3 picked); the model classifies 100% of them correctly"), "{}", stderr);
+
+    let spec = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+    let first = |extra: &[&str]| {
+        let args = [&["synth", "-", "--json", "--top-n", "3", "--no-cache"], extra].concat();
+        let (stdout, stderr, ok) = run_cli(&args, spec);
+        assert!(ok, "{}", stderr);
+        serde_json::from_str::<serde_json::Value>(&stdout).unwrap()["code"].as_str().unwrap().to_string()
+    };
+    assert!(!first(&[]).contains("Spacer()"));
+    assert!(first(&["--ranker", model.to_str().unwrap()]).contains("Spacer()"));
+
+    let (_, stderr, ok) = run_cli(&["train-ranker", "-"], "{\"accepted\":true,\"features\":{}}");
+    assert!(!ok && stderr.contains("both accepted and rejected"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_llm_needs_the_feature() {
+    let examples = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+    if cfg!(feature = "llm") {
+        let (_, stderr, ok) = run_cli(&["--examples", examples, "--llm", "ollama", "--no-cache"], "");
+        assert!(ok, "{}", stderr);
+        let (_, stderr, ok) = run_cli(&["--examples", examples, "--llm", "gemini"], "");
+        assert!(!ok && stderr.contains("invalid value 'gemini'"), "{}", stderr);
+        return;
+    }
+    // Without the feature there is no --llm flag, and a provider in the config is an error
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--llm", "ollama"], "");
+    assert!(!ok && stderr.contains("unexpected argument '--llm'"), "{}", stderr);
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-llm-feature-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("swiftui-synth.toml");
+    std::fs::write(&config, "[llm]\nprovider = \"ollama\"\n").unwrap();
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--no-cache", "--config", config.to_str().unwrap()], "");
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(!ok && stderr.contains("[llm] provider needs swiftui-synth built with the llm feature"), "{}", stderr);
+}
+
+#[cfg(all(unix, feature = "llm"))]
+#[test]
+fn test_cli_llm_orders_the_verified_layouts() {
+    use std::os::unix::fs::PermissionsExt;
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-llm-cli-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    // Stands in for curl: answers as Ollama would, preferring the third layout, or fails to connect
+    let curl = dir.join("curl");
+    let reply = r#"{"message":{"role":"assistant","content":"ORDER: 3, 1, 2\nREASON: The spacer keeps the button at the bottom."},"done":true}"#;
+    std::fs::write(&curl, format!("#!/bin/sh\ncat > /dev/null\n[ -n \"$FAIL\" ] && echo 'curl: (7) Failed to connect' >&2 && exit 7\nprintf '%s\\n' '{}'\n", reply)).unwrap();
+    std::fs::set_permissions(&curl, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let run = |envs: &[(&str, &str)]| {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+            .args(["synth", "-", "-q", "--top-n", "3", "--llm", "ollama", "--explain", "--no-cache"])
+            .env("CURL", &curl)
+            .envs(envs.iter().copied())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        child.stdin.take().unwrap().write_all(b"{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}").unwrap();
+        let output = child.wait_with_output().unwrap();
+        (String::from_utf8_lossy(&output.stdout).into_owned(), String::from_utf8_lossy(&output.stderr).into_owned(), output.status.success())
+    };
+
+    let (stdout, stderr, ok) = run(&[]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Spacer()"), "{}", stdout);
+    assert!(stderr.contains("The language model (llama3.1) ordered the 3 verified layouts: The spacer keeps the button at the bottom."), "{}", stderr);
+
+    let (stdout, stderr, ok) = run(&[("FAIL", "1")]);
+    assert!(ok, "{}", stderr);
+    assert!(!stdout.contains("Spacer()"), "{}", stdout);
+    assert!(stderr.contains("Warning: no hint from the language model"), "{}", stderr);
+    assert!(stderr.contains("Failed to connect"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_lints_the_synthesized_layout() {
+    let examples = "Login:{(width:390,height:844):{title:\"Two\\nlines\"@(h:68),button:\"Go\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("struct LoginView: View {"), "{}", stdout);
+    assert!(
+        stderr.contains("warning[W0004]: LoginView: Text \"Two\nlines\" has a fixed height of 68pt in example 1 but no line limit\n  = help: add .lineLimit(1)"),
+        "{}",
+        stderr
+    );
+    // A text whose height gives it a line limit is not warned about
+    let (stdout, stderr, ok) = run_cli(&["--examples", "{(width:390,height:844):{title:\"fine\"@(h:16,font:12)}}", "-q", "--no-cache"], "");
+    assert!(ok && stdout.contains(".lineLimit(1)") && !stderr.contains("W0004"), "{}{}", stdout, stderr);
+
+    // Reported with the other diagnostics in JSON
+    let (stdout, _, ok) = run_cli(&["--examples", examples, "--json", "--no-cache"], "");
+    assert!(ok && stdout.contains("\"code\": \"W0004\""), "{}", stdout);
+
+    // Allowed in the config file
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-lint-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("swiftui-synth.toml");
+    std::fs::write(&config, "[lint]\nallow = [\"W0004\"]\n").unwrap();
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--config", config.to_str().unwrap()], "");
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(ok && !stderr.contains("W0004"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_audit_a11y_reports_findings_with_severities() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-audit-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let tokens = dir.join("tokens.json");
+    std::fs::write(&tokens, r##"{"color": {"text": "#1C1C1E", "hint": "#8E8E93", "background": "#FFFFFF"}}"##).unwrap();
+
+    let examples = "{(width:390,height:844):{Image:\"logo\",button:\"Continue\"}}";
+    let (stdout, stderr, ok) = run_cli(&["audit", "a11y", "--examples", examples, "--tokens", tokens.to_str().unwrap()], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("ContentView:\nwarning  image-label  Image \"logo\" has no accessibility label"), "{}", stdout);
+    assert!(stdout.contains("color.hint #8E8E93 on color.background #FFFFFF has a contrast of 3.26:1"), "{}", stdout);
+    assert!(stdout.ends_with("0 errors, 2 warnings\n"), "{}", stdout);
+
+    // A one-symbol button in existing code is both unnamed and too small to tap
+    let swift = dir.join("View.swift");
+    std::fs::write(&swift, "VStack {\n    Button(\"+\") {}\n}\n").unwrap();
+    let (stdout, stderr, ok) = run_cli(&["audit", "a11y", "--examples", "{(width:390,height:844):{button:\"+\"}}", "--against", swift.to_str().unwrap()], "");
+    assert!(!ok, "{}", stdout);
+    assert!(stdout.contains("warning  button-label  Button \"+\" is labelled only with symbols"), "{}", stdout);
+    assert!(stdout.contains("error    touch-target  Button \"+\" is "), "{}", stdout);
+    assert!(stderr.contains("The audit found 1 accessibility error"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_assets_writes_an_asset_catalog_skeleton() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-catalog-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("swiftui-synth.toml"), "[tokens]\nfiles = [\"tokens.json\"]\n").unwrap();
+    std::fs::write(dir.join("tokens.json"), r##"{"color": {"accent": "#007AFF"}, "spacing": 8}"##).unwrap();
+    let catalog = dir.join("Assets.xcassets");
+    let config = dir.join("swiftui-synth.toml");
+
+    let examples = "{(width:390,height:844):{Image:\"logo\",title:\"Hi\"}}";
+    let args = ["--examples", examples, "--no-cache", "--config", config.to_str().unwrap(), "--assets", catalog.to_str().unwrap()];
+    let (stdout, stderr, ok) = run_cli(&args, "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(&format!("Added logo.imageset, colorAccent.colorset to {}", catalog.display())), "{}", stdout);
+    assert!(catalog.join("Contents.json").is_file());
+    assert!(catalog.join("logo.imageset/Contents.json").is_file());
+    assert!(std::fs::read_to_string(catalog.join("colorAccent.colorset/Contents.json")).unwrap().contains("\"red\": \"0x00\""));
+
+    // Sets that exist, perhaps with the real artwork, are kept
+    std::fs::write(catalog.join("logo.imageset/logo.png"), "artwork").unwrap();
+    let (stdout, _, ok) = run_cli(&args, "");
+    assert!(ok && !stdout.contains("Added"), "{}", stdout);
+    assert!(catalog.join("logo.imageset/logo.png").is_file());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_emit_tests_writes_snapshot_tests() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-snapshots-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let tests = dir.join("ScreenTests.swift");
+    let spec = "Login:{(width:390,height:844):{title:\"Hi\",button:\"Go\"}} {(width:320,height:568):{title:\"Hi\",button:\"Go\"}}\nHome:{(device:\"iPhone SE\"):{title:\"Home\"}}";
+    let (stdout, stderr, ok) = run_cli(
+        &["--examples-file", "-", "--no-cache", "--out-dir", dir.to_str().unwrap(), "--emit-tests", tests.to_str().unwrap(), "--test-module", "MyApp"],
+        spec,
+    );
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(&format!("Saved snapshot tests to {}", tests.display())), "{}", stdout);
+    let code = std::fs::read_to_string(&tests).unwrap();
+    assert!(code.contains("@testable import MyApp\n\nfinal class ScreenTests: XCTestCase {\n"), "{}", code);
+    assert!(code.contains("    func testLoginView_390x844() {\n        assertSnapshot(of: LoginView(), as: .image(layout: .fixed(width: 390, height: 844)))"), "{}", code);
+    assert!(code.contains("func testLoginView_320x568()") && code.contains("func testHomeView_375x667()"), "{}", code);
+
+    let (_, stderr, ok) = run_cli(&["--examples", "{(width:390,height:844):{title:\"Hi\"}}", "--emit-tests", "snapshot-tests.swift"], "");
+    assert!(!ok && stderr.contains("Test class name 'snapshot-tests' is not a Swift identifier"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_emit_ui_tests_uses_the_rendered_identifiers() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-ui-tests-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let tests = dir.join("AppUITests.swift");
+    let spec = "Login:{(width:390,height:844):{title:\"Welcome\",button:\"Sign in\"}}\nHome:{(width:390,height:844):{title:\"Home\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples-file", "-", "--no-cache", "--out-dir", dir.to_str().unwrap(), "--emit-ui-tests", tests.to_str().unwrap()], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(&format!("Saved UI tests to {}", tests.display())), "{}", stdout);
+    let login = std::fs::read_to_string(dir.join("LoginView.swift")).unwrap();
+    assert!(login.contains(".accessibilityIdentifier(\"text.welcome\")") && login.contains(".accessibilityIdentifier(\"button.sign-in\")"), "{}", login);
+    let code = std::fs::read_to_string(&tests).unwrap();
+    assert!(code.contains("final class AppUITests: XCTestCase {"), "{}", code);
+    assert!(code.contains("        XCTAssertTrue(app.staticTexts[\"text.welcome\"].waitForExistence(timeout: 5))\n"), "{}", code);
+    assert!(code.contains("        app.buttons[\"button.sign-in\"].tap()\n"), "{}", code);
+    assert!(code.contains("    func testHomeView() throws {\n        // Navigate from the first screen to HomeView here\n"), "{}", code);
+
+    // The identifiers alone, and code with them still checks against its examples
+    let examples = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+    let (stdout, _, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--accessibility-ids"], "");
+    assert!(ok && stdout.contains("Button(\"Go\") { }\n        .accessibilityIdentifier(\"button.go\")\n"), "{}", stdout);
+    let view = dir.join("View.swift");
+    std::fs::write(&view, &stdout).unwrap();
+    let (_, stderr, ok) = run_cli(&["check", "--examples", examples, "--against", view.to_str().unwrap()], "");
+    assert!(ok, "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_platform_targets_watchos() {
+    let examples = "{(device:\"Apple Watch Series 9 45mm\"):{title:\"Steps\",button:\"Start\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--platform", "watchos"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("ScrollView {\n    VStack {\n"), "{}", stdout);
+    assert!(stdout.contains("Text(\"Steps\")\n            .font(.headline)"), "{}", stdout);
+
+    // Without the platform, a watch is not a device the layout can be for
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--no-cache"], "");
+    assert!(!ok && stderr.contains("'Apple Watch Series 9 45mm' is a watchos device, but the layout is for ios"), "{}", stderr);
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--platform", "android"], "");
+    assert!(!ok && stderr.contains("Unknown platform 'android'"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_appkit_target_renders_view_controllers() {
+    let examples = "{(device:\"Mac window\"):{title:\"Welcome\",button:\"Sign in\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--target", "appkit"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("final class ContentViewController: NSViewController {\n    override func loadView() {\n"), "{}", stdout);
+    assert!(stdout.contains("let signInButton = NSButton(title: \"Sign in\", target: self, action: #selector(signInButtonClicked))"), "{}", stdout);
+    assert!(stdout.contains("@objc private func signInButtonClicked(_ sender: NSButton) {"), "{}", stdout);
+
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-appkit-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let spec = "Login:{(device:\"Mac window\"):{title:\"Welcome\",button:\"Sign in\"}}";
+    let (_, stderr, ok) = run_cli(&["--examples", spec, "--no-cache", "--target", "appkit", "--out-dir", dir.to_str().unwrap()], "");
+    assert!(ok, "{}", stderr);
+    let code = std::fs::read_to_string(dir.join("LoginViewController.swift")).unwrap();
+    assert!(code.contains("import AppKit\n\nfinal class LoginViewController: NSViewController {"), "{}", code);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--target", "appkit", "--emit-tests", "ScreenTests.swift"], "");
+    assert!(!ok && stderr.contains("--target appkit cannot be combined with"), "{}", stderr);
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--target", "appkit", "--platform", "ios"], "");
+    assert!(!ok && stderr.contains("--target appkit is for macos, but the platform is ios"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_xaml_target_writes_pages_and_code_behind() {
+    let examples = "{(width:390,height:844):{title:\"Welcome\",button:\"Sign in\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--target", "xaml", "--namespace", "Shop"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("<ContentPage xmlns=\"http://schemas.microsoft.com/dotnet/2021/maui\""), "{}", stdout);
+    assert!(stdout.contains("x:Class=\"Shop.MainPage\">"), "{}", stdout);
+    assert!(stdout.contains("<Button Text=\"Sign in\" Clicked=\"OnSignInClicked\""), "{}", stdout);
+
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-xaml-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let spec = format!("Login:{}", examples);
+    for _ in 0..2 {
+        // Regenerating replaces the marked sections of both files
+        let (_, stderr, ok) = run_cli(&["--examples", &spec, "--no-cache", "--target", "xaml", "--out-dir", dir.to_str().unwrap()], "");
+        assert!(ok, "{}", stderr);
+    }
+    let page = std::fs::read_to_string(dir.join("LoginPage.xaml")).unwrap();
+    assert!(page.starts_with("<!-- swiftui-synth:begin "), "{}", page);
+    assert!(page.contains("<!-- Generated by swiftui-synth ") && !page.contains("Command:"), "{}", page);
+    assert!(page.ends_with("</ContentPage>\n<!-- swiftui-synth:end -->\n"), "{}", page);
+    let code_behind = std::fs::read_to_string(dir.join("LoginPage.xaml.cs")).unwrap();
+    assert!(code_behind.contains("public partial class LoginPage : ContentPage") && code_behind.contains("private void OnSignInClicked"), "{}", code_behind);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--target", "xaml", "--validate", "swift"], "");
+    assert!(!ok && stderr.contains("--target xaml cannot be combined with"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_vue_target_writes_single_file_components() {
+    let examples = "{(width:390,height:844):{title:\"Welcome\",button:\"Sign in\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--target", "vue"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("<template>\n    <div class=\"screen vstack\">\n"), "{}", stdout);
+    assert!(stdout.contains("<button type=\"button\" @click=\"onSignInClick\">Sign in</button>"), "{}", stdout);
+    assert!(stdout.contains("<style scoped>\n.screen {"), "{}", stdout);
+
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-vue-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let spec = format!("Login:{}", examples);
+    let (_, stderr, ok) = run_cli(&["--examples", &spec, "--no-cache", "--target", "vue", "--out-dir", dir.to_str().unwrap()], "");
+    assert!(ok, "{}", stderr);
+    let component = std::fs::read_to_string(dir.join("LoginView.vue")).unwrap();
+    assert!(component.starts_with("<!-- swiftui-synth:begin "), "{}", component);
+    assert!(component.contains("<!-- Command: swiftui-synth ") && component.contains("\n<template>\n"), "{}", component);
+    assert!(component.ends_with("</style>\n<!-- swiftui-synth:end -->\n"), "{}", component);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--target", "vue", "--app", "stack", "--out-dir", "out"], "");
+    assert!(!ok && stderr.contains("--target vue cannot be combined with"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_texts_get_the_style_of_their_size() {
+    let examples = "{(width:390,height:844):{title:\"Welcome\"@(h:34),title:\"Terms apply\"@(font:12),button:\"Go\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Text(\"Welcome\")\n        .font(.title)\n"), "{}", stdout);
+    assert!(stdout.contains("Text(\"Terms apply\")\n        .font(.caption)\n"), "{}", stdout);
+
+    // Two lines of a title on the smaller screen wrap; one on both stays on one line
+    let examples = "{(width:390,height:844):{title:\"Welcome back\"@(h:34),title:\"Your order is on its way\"@(h:34)}} \
+                    {(width:320,height:568):{title:\"Welcome back\"@(h:34),title:\"Your order is on its way\"@(x:20,w:280,h:68)}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(".font(.title)\n        .lineLimit(1)\n        .truncationMode(.tail)\n"), "{}", stdout);
+    assert!(stdout.contains(".lineLimit(nil)\n        .multilineTextAlignment(.center)\n"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_images_fit_or_fill_their_stated_frames() {
+    let examples = "{(width:390,height:844):{Image:\"banner\"@(y:0,w:390,h:180,aspect:\"4:3\"),Image:\"avatar\"@(w:64,h:64),title:\"Hi\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(
+        stdout.contains("Image(\"banner\")\n        .resizable()\n        .scaledToFill()\n        .frame(maxWidth: .infinity, minHeight: 180, maxHeight: 180)\n        .clipped()\n"),
+        "{}",
+        stdout
+    );
+    assert!(stdout.contains("Image(\"avatar\")\n        .resizable()\n        .scaledToFit()\n        .frame(width: 64, height: 64)\n"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_stated_decorations_are_rendered() {
+    let examples = "{(width:390,height:844):{title:\"Plan\",button:\"Subscribe\"@(cornerRadius:12,border:\"1 #D1D1D6\",shadow:\"8 0 4\")}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(
+        stdout.contains(concat!(
+            "Button(\"Subscribe\") { }\n        .padding()\n",
+            "        .clipShape(RoundedRectangle(cornerRadius: 12))\n",
+            "        .overlay(RoundedRectangle(cornerRadius: 12).stroke(Color(red: 0.82, green: 0.82, blue: 0.839), lineWidth: 1))\n",
+            "        .shadow(radius: 8, x: 0, y: 4)\n"
+        )),
+        "{}",
+        stdout
+    );
+
+    let (_, stderr, ok) = run_cli(&["--examples", "{(width:390,height:844):{button:\"Go\"@(border:\"1 grey\")}}", "-q", "--no-cache"], "");
+    assert!(!ok && stderr.contains("Invalid border color 'grey'"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_overlays_anchor_a_badge() {
+    let examples = "{(width:390,height:844):{Image:\"bell\"@(overlay:\"topTrailing title:3\"),title:\"Inbox\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Image(\"bell\")\n        .overlay(alignment: .topTrailing) { Text(\"3\").font(.caption) }\n"), "{}", stdout);
+
+    let (_, stderr, ok) = run_cli(&["--examples", "{(width:390,height:844):{Image:\"bell\"@(overlay:\"corner title:3\")}}", "-q", "--no-cache"], "");
+    assert!(!ok && stderr.contains("Unknown overlay alignment 'corner'"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_animations_start_when_the_screen_appears() {
+    let examples = "Welcome:{(width:390,height:844):{title:\"Hello\"@(transition:\"move bottom\"),button:\"Start\"@(animation:\"spring\")}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("struct WelcomeView: View {\n    @State private var appeared = false\n"), "{}", stdout);
+    assert!(stdout.contains("if appeared {"), "{}", stdout);
+    assert!(stdout.contains(".transition(.move(edge: .bottom).animation(.default))"), "{}", stdout);
+    assert!(stdout.contains(".animation(.spring, value: appeared)"), "{}", stdout);
+    assert!(stdout.contains(".onAppear { appeared = true }"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_haptics_give_buttons_feedback() {
+    let examples = "Login:{(width:390,height:844):{title:\"Welcome\",button:\"Sign in\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--haptics"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("struct LoginView: View {\n    @State private var signInTapped = false\n"), "{}", stdout);
+    assert!(stdout.contains("Button(\"Sign in\") { signInTapped.toggle() }"), "{}", stdout);
+    assert!(stdout.contains(".sensoryFeedback(.impact, trigger: signInTapped)"), "{}", stdout);
+
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--haptics", "success"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(".sensoryFeedback(.success, trigger: signInTapped)"), "{}", stdout);
+
+    let (stdout, _, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok && !stdout.contains("sensoryFeedback") && !stdout.contains("@State"), "{}", stdout);
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "-q", "--haptics", "buzz"], "");
+    assert!(!ok && stderr.contains("buzz"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_gestures_call_handler_stubs() {
+    let examples = "Inbox:{(width:390,height:844):{Image:\"avatar\"@(onTap:\"openProfile\"),title:\"Messages\"@(onSwipe:\"left archive\")}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(".onTapGesture { openProfile() }"), "{}", stdout);
+    assert!(stdout.contains(".gesture(DragGesture(minimumDistance: 20).onEnded { value in if value.translation.width < -50 { archive() } })"), "{}", stdout);
+    assert!(stdout.contains("    private func openProfile() {\n        // Handle the tap\n    }"), "{}", stdout);
+    assert!(stdout.contains("    private func archive() {\n        // Handle the swipe\n    }"), "{}", stdout);
+
+    // AppKit code has no swipes, and says so
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "--no-cache", "--target", "appkit"], "");
+    assert!(ok && stdout.contains("NSClickGestureRecognizer"), "{}", stderr);
+    assert!(stderr.contains("Warning: AppKit code leaves out the left swipe calling archive() of Text \"Messages\""), "{}", stderr);
+
+    let (_, stderr, ok) = run_cli(&["--examples", "{(width:390,height:844):{title:\"Hi\"@(onTap:\"open profile\")}}", "-q", "--no-cache"], "");
+    assert!(!ok);
+    assert!(stderr.contains("Invalid onTap 'open profile'"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_backgrounds_fill_the_screen() {
+    let examples = "{(width:390,height:844):{title:\"Welcome\",button:\"Start\",background:{gradient:[\"#FF5733\",\"#C70039\"], direction:vertical}}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(
+        stdout.contains(concat!(
+            ".frame(maxWidth: .infinity, maxHeight: .infinity)\n",
+            ".background(LinearGradient(colors: [Color(red: 1, green: 0.341, blue: 0.2), Color(red: 0.78, green: 0, blue: 0.224)], startPoint: .top, endPoint: .bottom))"
+        )),
+        "{}",
+        stdout
+    );
+
+    let (stdout, stderr, ok) = run_cli(&["--examples", "{(width:390,height:844):{title:\"Now playing\",background:\"ultraThinMaterial\"}}", "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains(".background(.ultraThinMaterial)"), "{}", stdout);
+    let (_, stderr, ok) = run_cli(&["--examples", "{(width:390,height:844):{title:\"Hi\",background:\"glass\"}}", "-q", "--no-cache"], "");
+    assert!(!ok && stderr.contains("Unknown material 'glass'"), "{}", stderr);
+}
+
+#[test]
+fn test_cli_separator_lines_become_dividers() {
+    let examples = "{(width:390,height:844):{title:\"Account\"@(y:60),title:\"---\"@(y:120,w:\"100%\",h:1),button:\"Sign out\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("    Text(\"Account\")\n        .font(.title)\n        .padding()\n    Divider()\n"), "{}", stdout);
+    assert!(!stdout.contains("---"), "{}", stdout);
+}
+
+#[test]
+fn test_cli_snap_spacing_uses_the_spacing_tokens() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-spacing-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = dir.join("swiftui-synth.toml");
+    std::fs::write(&config, "[tokens]\nfiles = [\"tokens.json\"]\n\n[layout]\npadding = 13\nstack_spacing = 28\n").unwrap();
+    std::fs::write(dir.join("tokens.json"), r##"{"spacing": {"sm": "8px", "md": "12px", "lg": "24px"}, "color": {"accent": "#007AFF"}}"##).unwrap();
+    let examples = "{(width:390,height:844):{title:\"Welcome\",button:\"Sign in\"}}";
+    let args = ["--examples", examples, "-q", "--no-cache", "--target", "vue", "--config", config.to_str().unwrap()];
+
+    let (stdout, _, ok) = run_cli(&args, "");
+    assert!(ok && stdout.contains("padding: 13px;") && stdout.contains("gap: 28px;"), "{}", stdout);
+    let (stdout, stderr, ok) = run_cli(&[&args[..], &["--snap-spacing"]].concat(), "");
+    assert!(ok, "{}", stderr);
+    // The padding snaps to the tokens' 12; the spacing is too far from 24 and is kept
+    assert!(stdout.contains("padding: 12px;") && stdout.contains("gap: 28px;"), "{}", stdout);
+    assert!(stderr.contains("Warning: stack_spacing of 28pt is 4pt from the nearest step of the spacing scale, 24pt"), "{}", stderr);
+
+    // SwiftUI code spells the snapped values out
+    let (stdout, stderr, ok) = run_cli(&[&args[..4], &args[6..], &["--snap-spacing"]].concat(), "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("VStack(spacing: 28) {") && stdout.contains("    .padding(12)\n"), "{}", stdout);
+    assert!(!stdout.contains(".padding()"), "{}", stdout);
+
+    std::fs::write(&config, "[layout]\nstack_spacing = 28\n\n[spacing]\nsnap = true\ntolerance = 4\n").unwrap();
+    let (stdout, stderr, ok) = run_cli(&args, "");
+    assert!(ok && stdout.contains("gap: 24px;") && !stderr.contains("Warning"), "{}\n{}", stdout, stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_imports_storyboards() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-storyboard-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let storyboard = dir.join("Main.storyboard");
+    std::fs::write(
+        &storyboard,
+        r#"<document type="com.apple.InterfaceBuilder3.CocoaTouch.Storyboard.XIB" version="3.0">
+    <scenes>
+        <scene sceneID="s">
+            <objects>
+                <viewController id="c" customClass="LoginViewController">
+                    <view key="view" id="v">
+                        <rect key="frame" x="0.0" y="0.0" width="390" height="844"/>
+                        <subviews>
+                            <label id="l" text="Welcome"><rect key="frame" x="20" y="100" width="350" height="34"/></label>
+                            <switch id="t" userLabel="Remember me"><rect key="frame" x="20" y="300" width="51" height="31"/></switch>
+                            <button id="b"><rect key="frame" x="20" y="760" width="350" height="44"/><state key="normal" title="Sign in"/></button>
+                        </subviews>
+                    </view>
+                </viewController>
+            </objects>
+        </scene>
+    </scenes>
+</document>"#,
+    )
+    .unwrap();
+    let (stdout, stderr, ok) = run_cli(&["--examples-file", storyboard.to_str().unwrap(), "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("struct LoginView: View {"), "{}", stdout);
+    assert!(stdout.contains("Text(\"Welcome\")") && stdout.contains("Button(\"Sign in\")"), "{}", stdout);
+    assert!(stderr.contains("Warning: Login: skipped switch 'Remember me'"), "{}", stderr);
+
+    std::fs::write(&storyboard, "<plist/>").unwrap();
+    let (_, stderr, ok) = run_cli(&["--examples-file", storyboard.to_str().unwrap(), "--no-cache"], "");
+    assert!(!ok && stderr.contains("not an Interface Builder document"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_imports_sketch_artboards() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-sketch-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let page = dir.join("login.json");
+    std::fs::write(
+        &page,
+        r#"{"_class": "page", "layers": [{"_class": "artboard", "name": "Login - iPhone", "frame": {"x": 0, "y": 0, "width": 393, "height": 852}, "layers": [
+            {"_class": "text", "name": "Title", "frame": {"x": 20, "y": 100, "width": 353, "height": 34}, "attributedString": {"string": "Welcome"}},
+            {"_class": "symbolInstance", "name": "Navigation Bar", "frame": {"x": 0, "y": 0, "width": 393, "height": 44}},
+            {"_class": "symbolInstance", "name": "Button/Primary", "frame": {"x": 20, "y": 760, "width": 353, "height": 44},
+             "overrideValues": [{"overrideName": "A1_stringValue", "value": "Sign in"}]}
+        ]}]}"#,
+    )
+    .unwrap();
+    let (stdout, stderr, ok) = run_cli(&["--examples-file", page.to_str().unwrap(), "-q", "--no-cache"], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("struct LoginView: View {"), "{}", stdout);
+    assert!(stdout.contains("Text(\"Welcome\")") && stdout.contains("Button(\"Sign in\")"), "{}", stdout);
+    assert!(stderr.contains("Warning: Login - iPhone: skipped the symbol 'Navigation Bar'"), "{}", stderr);
+
+    let design = dir.join("login.xd");
+    std::fs::write(&design, "not a zip").unwrap();
+    let (_, stderr, ok) = run_cli(&["--examples-file", design.to_str().unwrap(), "--no-cache"], "");
+    assert!(!ok && stderr.contains("not an XD file"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_imports_annotated_screenshots() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-screenshot-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    // The header of a 1179x2556 PNG, an iPhone 15 screenshot, is all that is read
+    let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+    png.extend_from_slice(&1179u32.to_be_bytes());
+    png.extend_from_slice(&2556u32.to_be_bytes());
+    std::fs::write(dir.join("login.png"), &png).unwrap();
+    std::fs::write(
+        dir.join("boxes.csv"),
+        "label,x,y,width,height,text\nText,60,300,1059,100,Welcome\nbutton,60,2280,1059,132,Sign in\ntoggle,60,600,150,90,\n",
+    )
+    .unwrap();
+    let image = dir.join("login.png");
+    let boxes = dir.join("boxes.csv");
+    let args = ["--screenshot", image.to_str().unwrap(), "--annotations", boxes.to_str().unwrap(), "-q", "--no-cache"];
+    let (stdout, stderr, ok) = run_cli(&args, "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("struct LoginView: View {"), "{}", stdout);
+    assert!(stdout.contains("Text(\"Welcome\")") && stdout.contains("Spacer()\n            Button(\"Sign in\")"), "{}", stdout);
+    assert!(stderr.contains("Warning: Login: skipped the 'toggle' box"), "{}", stderr);
+
+    if !cfg!(feature = "ocr") {
+        let (_, stderr, ok) = run_cli(&["--screenshot", image.to_str().unwrap(), "--no-cache"], "");
+        assert!(!ok && stderr.contains("--screenshot needs --annotations, or swiftui-synth built with the ocr feature"), "{}", stderr);
+    }
+    std::fs::write(&image, "GIF89a").unwrap();
+    let (_, stderr, ok) = run_cli(&args, "");
+    assert!(!ok && stderr.contains("not a PNG or JPEG image"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(all(unix, feature = "ocr"))]
+#[test]
+fn test_cli_reads_screenshot_text_with_ocr() {
+    use std::os::unix::fs::PermissionsExt;
+    use tiny_skia::{Color, Paint, Pixmap, Rect, Transform};
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-ocr-cli-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    // An iPhone 15 screenshot at @3x with a title and a filled button
+    let mut pixmap = Pixmap::new(1179, 2556).unwrap();
+    pixmap.fill(Color::WHITE);
+    let mut paint = Paint::default();
+    paint.set_color_rgba8(0, 122, 255, 255);
+    pixmap.fill_rect(Rect::from_xywh(60.0, 2280.0, 1059.0, 132.0).unwrap(), &paint, Transform::identity(), None);
+    let image = dir.join("login.png");
+    pixmap.save_png(&image).unwrap();
+    // Stands in for tesseract, reading the words drawn above
+    let tesseract = dir.join("tesseract");
+    let tsv = "level\tpage_num\tblock_num\tpar_num\tline_num\tword_num\tleft\ttop\twidth\theight\tconf\ttext\n\
+               5\t1\t1\t1\t1\t1\t60\t300\t420\t100\t96\tWelcome\n\
+               5\t1\t2\t1\t1\t1\t470\t2316\t110\t60\t93\tSign\n\
+               5\t1\t2\t1\t1\t2\t600\t2316\t60\t60\t92\tin\n";
+    std::fs::write(&tesseract, format!("#!/bin/sh\nprintf '{}'\n", tsv.replace('\t', "\\t").replace('\n', "\\n"))).unwrap();
+    std::fs::set_permissions(&tesseract, std::fs::Permissions::from_mode(0o755)).unwrap();
+    let run = |envs: &[(&str, &str)]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+            .args(["--screenshot", image.to_str().unwrap(), "--no-cache", "-q"])
+            .env("PATH", "")
+            .env_remove("TESSERACT")
+            .envs(envs.iter().copied())
+            .output()
+            .unwrap();
+        (String::from_utf8_lossy(&output.stdout).into_owned(), String::from_utf8_lossy(&output.stderr).into_owned(), output.status.success())
+    };
+
+    let (stdout, stderr, ok) = run(&[("TESSERACT", tesseract.to_str().unwrap())]);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.starts_with("struct LoginView: View {"), "{}", stdout);
+    assert!(stdout.contains("Text(\"Welcome\")") && stdout.contains("Button(\"Sign in\")"), "{}", stdout);
+
+    let (_, stderr, ok) = run(&[]);
+    assert!(!ok && stderr.contains("needs tesseract on the PATH"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_template_dir_renders_from_user_templates() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-templates-cli-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("button.tera"), "PrimaryButton({{ literal }}) { }\n{{ indent }}.frame(maxWidth: .infinity)").unwrap();
+    let examples = "{(width:390,height:844):{title:\"Welcome\",button:\"Sign in\"}}";
+    let template_dir = dir.to_str().unwrap();
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--template-dir", template_dir], "");
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("    PrimaryButton(\"Sign in\") { }\n        .frame(maxWidth: .infinity)\n"), "{}", stdout);
+    assert!(stdout.contains("Text(\"Welcome\")\n        .font(.title)"), "{}", stdout);
+
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--no-cache", "--template-dir", template_dir, "--target", "vue"], "");
+    assert!(!ok && stderr.contains("--template-dir renders SwiftUI"), "{}", stderr);
+    std::fs::write(dir.join("label.tera"), "").unwrap();
+    let (_, stderr, ok) = run_cli(&["--examples", examples, "--no-cache", "--template-dir", template_dir], "");
+    assert!(!ok && stderr.contains("Unknown template"), "{}", stderr);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_backend_plugins_add_targets() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-plugins-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let plugin = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/plugins/echo.wasm");
+    let config = dir.join("swiftui-synth.toml");
+    std::fs::write(&config, format!("[backends]\nplugins = [{:?}]\n", plugin.display().to_string())).unwrap();
+    let examples = "{(width:390,height:844):{title:\"Hi\"}}";
+    let (stdout, stderr, ok) = run_cli(&["--examples", examples, "-q", "--no-cache", "--config", config.to_str().unwrap(), "--target", "echo"], "");
+    if cfg!(feature = "wasm-plugins") {
+        // The fixture echoes the request it is sent
+        assert!(ok, "{}", stderr);
+        assert!(stdout.starts_with("{\"ir\":") && stdout.contains("\"view_name\":\"ContentView\""), "{}", stdout);
+    } else {
+        assert!(!ok && stderr.contains("needs swiftui-synth built with the wasm-plugins feature"), "{}", stderr);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_warns_about_ignored_input_and_denies_warnings() {
+    let spec = "{(width:390,height:844):{title:\"Hi\"@(y:20,y:40), button:\"Go\", constraints:{\"Image above title\"}}}";
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], spec);
+    assert!(ok, "{}", stderr);
+    assert!(stdout.contains("Text(\"Hi\")"));
+    assert!(stderr.contains("warning[W0008]: Frame key 'y' is given twice; this later value is ignored"), "{}", stderr);
+    assert!(stderr.contains("warning[W0008]: The example has no Image, so 'Image above title' is ignored"), "{}", stderr);
+
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "--deny-warnings"], spec);
+    assert!(!ok);
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("error[W0008]: Frame key 'y' is given twice"), "{}", stderr);
+
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "--deny-warnings"], "{(width:390,height:844):{title:\"Hi\"}}");
+    assert!(ok, "{}", stderr);
+}
+
+#[test]
+fn test_cli_out_dir_rebuilds_only_changed_screens() {
+    let dir = std::env::temp_dir().join(format!("swiftui-synth-incremental-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let spec = dir.join("app.spec");
+    let out = dir.join("Views");
+    let write_spec = |brand: &str, home: &str| {
+        let text = format!(
+            "let brand = \"{}\"\nlet header = {{ title:brand }}\nLogin:{{(width:390,height:844):{{@include header, button:\"Sign in\"}}}}\nHome:{{(width:390,height:844):{{title:\"{}\"}}}}\n",
+            brand, home
+        );
+        std::fs::write(&spec, text).unwrap();
+    };
+    let build = |extra: &[&str]| {
+        let mut args = vec!["synth", spec.to_str().unwrap(), "--out-dir", out.to_str().unwrap(), "--no-cache"];
+        args.extend(extra);
+        let (stdout, stderr, ok) = run_cli(&args, "");
+        assert!(ok, "{}", stderr);
+        stdout
+    };
+
+    write_spec("Acme", "Home");
+    assert!(build(&[]).contains("Saved LoginView.swift, HomeView.swift to "));
+    let stdout = build(&[]);
+    assert!(stdout.contains("Up to date: LoginView.swift, HomeView.swift") && !stdout.contains("Synthesized"), "{}", stdout);
+    // A shared binding changes only the screen that includes it
+    write_spec("Acme Inc", "Home");
+    let stdout = build(&[]);
+    assert!(stdout.contains("Rebuilding LoginView: examples, let brand, let header changed"), "{}", stdout);
+    assert!(stdout.contains("Saved LoginView.swift to ") && stdout.contains("Up to date: HomeView.swift"), "{}", stdout);
+    assert!(std::fs::read_to_string(out.join("LoginView.swift")).unwrap().contains("Text(\"Acme Inc\")"));
+    // Removed files are written again, and --force rebuilds everything
+    std::fs::remove_file(out.join("HomeView.swift")).unwrap();
+    assert!(build(&[]).contains("Rebuilding HomeView: file HomeView.swift changed"));
+    assert!(build(&["--force"]).contains("Saved LoginView.swift, HomeView.swift to "));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_cli_logs_events_by_verbosity_and_format() {
+    let spec = "{(width:390,height:844):{title:\"Hi\",button:\"Go\"}}";
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache"], spec);
+    assert!(ok && stderr.is_empty(), "{}", stderr);
+    let (_, stderr, ok) = run_cli(&["synth", "-", "-q", "--no-cache", "-v"], spec);
+    assert!(ok);
+    assert!(stderr.contains("debug synthesize{screen=ContentView}: found a layout source=\"search\""), "{}", stderr);
+    assert!(!stderr.contains("trace"), "{}", stderr);
+    assert!(run_cli(&["synth", "-", "-q", "--no-cache", "-vv"], spec).1.contains("timed a phase phase=\"render\""));
+
+    // JSON lines, with warnings as events and the spans closed with their timings
+    let (stdout, stderr, ok) = run_cli(&["synth", "-", "--log-format", "json", "-q", "--no-cache", "--post-format", "nonexistent-formatter"], spec);
+    assert!(ok && stdout.contains("Text(\"Hi\")"), "{}", stderr);
+    let events: Vec<serde_json::Value> = stderr.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+    let warning = events.iter().find(|event| event["level"] == "WARN").unwrap();
+    assert!(warning["fields"]["message"].as_str().unwrap().ends_with("using the built-in pretty-printer"), "{}", warning);
+    let closed: Vec<&str> = events.iter().filter(|event| event["fields"]["message"] == "close").filter_map(|event| event["span"]["name"].as_str()).collect();
+    assert_eq!(closed, ["parse", "render", "synthesize"]);
+
+    let (_, stderr, ok) = run_cli(&["check", "--log-format", "yaml", "--against", "View.swift"], spec);
+    assert!(!ok && stderr.contains("expected text or json"), "{}", stderr);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_ctrl_c_stops_the_search_with_the_best_layout() {
+    let spec = format!(
+        "{{(width:320,height:200):{{title:\"{}\",button:\"A\",button:\"B\",button:\"C\",Image:\"a\",Image:\"b\",title:\"x\"}}}}",
+        "word ".repeat(80).trim()
+    );
+    let mut child = Command::new(env!("CARGO_BIN_EXE_swiftui-synth"))
+        .args(["synth", "-", "--no-cache", "--stats", "--timeout", "60s", "--max-candidates", "100000000"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(spec.as_bytes()).unwrap();
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let started = std::time::Instant::now();
+    assert!(Command::new("kill").args(["-INT", &child.id().to_string()]).status().unwrap().success());
+    let output = child.wait_with_output().unwrap();
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+    let (stdout, stderr) = (String::from_utf8_lossy(&output.stdout), String::from_utf8_lossy(&output.stderr));
+    assert_eq!(output.status.code(), Some(130), "{}", stderr);
+    assert!(stdout.contains("VStack {") && stdout.contains("candidates:"), "{}", stdout);
+    assert!(stderr.contains("Warning: synthesis cancelled after "), "{}", stderr);
+}
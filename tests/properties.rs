@@ -0,0 +1,132 @@
+// File: tests/properties.rs
+// Property tests: random and spec-shaped input must never make the parsers panic, and
+// rendering a random layout, reading it back and rendering it again must change nothing, with
+// any render options and every target whose code the reverse parser reads.
+// The cargo-fuzz targets in fuzz/ drive the same entry points with coverage guidance.
+use proptest::prelude::*;
+use swiftui_synth::ast::IR;
+use swiftui_synth::input::parser::{parse_examples_all, parse_spec_with, ParseOptions};
+use swiftui_synth::input::swift::parse_swiftui;
+use swiftui_synth::layout::TextFit;
+use swiftui_synth::output::backend::{Registry, RenderBackend};
+use swiftui_synth::output::render::{render_swiftui, RenderOptions};
+use swiftui_synth::output::template::{TemplateBackend, Templates};
+use swiftui_synth::platform::Platform;
+
+// Fragments specs are made of, and the multibyte and escape text that trips up slicing
+fn spec_token() -> impl Strategy<Value = String> {
+    prop_oneof![
+        prop::sample::select(vec![
+            "{", "}", "(", ")", "[", "]", ":", ",", "@", "\"", "\\", " ", "\n", "->", "screen ", "flow ", "let ", "@include ",
+            "width:390", "height:844", "title:", "button:", "Image:", "Custom:", "hint:", "constraints:", "x:", "y:", "w:", "h:",
+            "RatingView:", "orientation:landscape", "safe_top:47", "=", "é", "👋", "日本語", "\u{301}", "\\u{1F44B}", "\\n", "1.5",
+        ])
+        .prop_map(str::to_string),
+        "[a-zA-Z0-9_ ]{1,8}",
+        any::<String>(),
+    ]
+}
+
+fn spec_like() -> impl Strategy<Value = String> {
+    prop::collection::vec(spec_token(), 0..40).prop_map(|tokens| tokens.concat())
+}
+
+// Any text, including quotes, backslashes, control and multibyte characters
+fn label() -> impl Strategy<Value = String> {
+    prop_oneof!["[a-zA-Z0-9 ]{0,12}", any::<String>()]
+}
+
+fn layout() -> impl Strategy<Value = IR> {
+    let leaf = prop_oneof![
+        label().prop_map(IR::Text),
+        label().prop_map(IR::Button),
+        label().prop_map(IR::Image),
+        Just(IR::Spacer),
+        ("[A-Z][a-zA-Z0-9]{0,8}", "([a-z]{1,6}: [0-9]{1,3})?").prop_map(|(name, args)| IR::Custom { name, args }),
+    ];
+    leaf.prop_recursive(4, 32, 6, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..6).prop_map(IR::VStack),
+            prop::collection::vec(inner, 0..6).prop_map(IR::HStack),
+        ]
+    })
+}
+
+// Every way the options change the code: indentation, modifiers, wrappers and platform idioms
+fn render_options() -> impl Strategy<Value = RenderOptions> {
+    let fonts = prop::sample::select(vec!["title", "headline", "body", "caption"]);
+    let platforms = prop::sample::select(Platform::ALL.to_vec());
+    let text_fit = (any::<bool>(), prop::option::of(prop::sample::select(vec![0.5, 0.75, 0.9])))
+        .prop_map(|(scrolls, minimum_scale_factor)| TextFit { scrolls, minimum_scale_factor });
+    (1..=8usize, fonts, any::<bool>(), any::<bool>(), text_fit, any::<bool>(), any::<bool>(), platforms).prop_map(
+        |(indent_width, font, padding, ignores_safe_area, text_fit, public, accessibility_identifiers, platform)| RenderOptions {
+            indent_width,
+            text_font: font.to_string(),
+            padding,
+            ignores_safe_area,
+            text_fit,
+            public,
+            accessibility_identifiers,
+            platform,
+            ..RenderOptions::default()
+        },
+    )
+}
+
+// The backends whose code the reverse parser reads back: those of the registry that say so,
+// and the SwiftUI of the built-in templates
+fn round_tripping_backends() -> Vec<Box<dyn RenderBackend>> {
+    let registry = Registry::builtin();
+    let mut backends: Vec<Box<dyn RenderBackend>> = Vec::new();
+    for name in registry.names() {
+        if registry.get(name).is_some_and(|backend| backend.round_trips()) {
+            backends.push(match name {
+                "swiftui" => Box::new(swiftui_synth::output::backend::SwiftUi),
+                other => panic!("add the '{}' target to the round-trip properties", other),
+            });
+        }
+    }
+    backends.push(Box::new(TemplateBackend { templates: Templates::builtin() }));
+    backends
+}
+
+proptest! {
+    #[test]
+    fn parsing_never_panics(input in spec_like()) {
+        let options = ParseOptions::default();
+        let _ = parse_examples_all(&input, &options);
+        let _ = parse_spec_with(&input, &options);
+        let _ = parse_spec_with(&input, &ParseOptions { strict: true, ..ParseOptions::default() });
+    }
+
+    #[test]
+    fn diagnostics_point_at_whole_characters(input in spec_like()) {
+        if let Err(errors) = parse_spec_with(&input, &ParseOptions::default()) {
+            for span in errors.iter().filter_map(|diagnostic| diagnostic.span) {
+                prop_assert!(span.start <= span.end && input.is_char_boundary(span.start) && input.is_char_boundary(span.end));
+            }
+        }
+    }
+
+    #[test]
+    fn reverse_parsing_never_panics(input in prop_oneof![any::<String>(), spec_like()]) {
+        let _ = parse_swiftui(&input);
+    }
+
+    #[test]
+    fn rendering_round_trips(ir in layout()) {
+        let rendered = render_swiftui(&ir);
+        let parsed = parse_swiftui(&rendered).map_err(|e| TestCaseError::fail(format!("{}\n{}", e, rendered)))?;
+        prop_assert_eq!(render_swiftui(&parsed), rendered);
+    }
+
+    #[test]
+    fn every_round_tripping_backend_round_trips(ir in layout(), options in render_options()) {
+        for backend in round_tripping_backends() {
+            prop_assert!(backend.round_trips());
+            let rendered = backend.try_render(&ir, &options).map_err(TestCaseError::fail)?;
+            let parsed = parse_swiftui(&rendered).map_err(|e| TestCaseError::fail(format!("{}: {}\n{}", backend.name(), e, rendered)))?;
+            prop_assert_eq!(backend.render(&parsed, &options), rendered, "{}", backend.name());
+        }
+    }
+}